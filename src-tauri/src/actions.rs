@@ -1,22 +1,44 @@
+use crate::announcer;
 use crate::audio_feedback::{play_feedback_sound, play_feedback_sound_blocking, SoundType};
+use crate::clipboard;
+use crate::helpers::focused_app::WorkspaceContext;
 use crate::managers::audio::AudioRecordingManager;
-use crate::managers::history::HistoryManager;
-use crate::managers::transcription::TranscriptionManager;
-use crate::settings::{get_settings, AppSettings, PostProcessProvider};
+use crate::managers::disfluency::DisfluencyManager;
+use crate::managers::history::{HistoryManager, SessionMetrics};
+use crate::managers::transcription::{
+    mark_low_confidence, TranscriptionManager, TranscriptionOutput,
+};
+use crate::markdown_note;
+use crate::perf_metrics::{self, FinalTimings};
+use crate::pipeline::{self, Step};
+use crate::profiles;
+use crate::readback;
+use crate::refinement;
+use crate::session_hooks;
+use crate::session_journal::SessionJournal;
+use crate::session_naming::{self, SessionNaming};
+use crate::session_report::{self, SessionReport, SinkResult};
+use crate::settings::{
+    get_settings, AppSettings, OutputTarget, PostProcessProvider, TextStageId,
+    TranscriptionProvider,
+};
 use crate::shortcut;
+use crate::transcription_error;
 use crate::tray::{change_tray_icon, TrayIconState};
 use crate::utils::{self, show_recording_overlay, show_transcribing_overlay};
+use crate::webhook;
 use async_openai::types::{
     ChatCompletionRequestMessage, ChatCompletionRequestUserMessageArgs,
     CreateChatCompletionRequestArgs,
 };
 use ferrous_opencc::{config::BuiltinConfig, OpenCC};
-use log::{debug, error};
+use log::{debug, error, warn};
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Instant;
 use tauri::AppHandle;
+use tauri::Emitter;
 use tauri::Manager;
 
 // Shortcut Action Trait
@@ -153,6 +175,22 @@ async fn maybe_post_process_transcription(
         provider.id, model
     );
 
+    // Context from the app being dictated into, only when the user allowed sharing it. It
+    // goes where the prompt has ${context}, or after the prompt when it has none
+    let context = WorkspaceContext::capture(
+        settings.post_process_context_window,
+        settings.post_process_context_selection,
+    );
+    let mut prompt = prompt;
+    if prompt.contains("${context}") {
+        prompt = prompt.replace("${context}", &context.describe());
+    } else if !context.is_empty() {
+        prompt.push_str(
+            "\n\nContext from where the text will be inserted. Use it only to match the tone and terminology, don't include it in the reply:\n",
+        );
+        prompt.push_str(&context.describe());
+    }
+
     // Replace ${output} variable in the prompt with the actual text
     let processed_prompt = prompt.replace("${output}", transcription);
     debug!("Processed prompt length: {} chars", processed_prompt.len());
@@ -289,13 +327,20 @@ impl ShortcutAction for TranscribeAction {
         let start_time = Instant::now();
         debug!("TranscribeAction::start called for binding: {}", binding_id);
 
-        // Load model in the background
+        // Nothing is recorded while a password field has the focus
+        if utils::blocked_by_secure_field(app, "recording") {
+            return;
+        }
+
+        // Load model in the background. Recording starts right away, audio recorded before
+        // the model is ready is buffered and transcribed once it is
         let tm = app.state::<Arc<TranscriptionManager>>();
         tm.initiate_model_load();
 
         let binding_id = binding_id.to_string();
         change_tray_icon(app, TrayIconState::Recording);
         show_recording_overlay(app);
+        announcer::reset(app);
 
         let rm = app.state::<Arc<AudioRecordingManager>>();
 
@@ -344,6 +389,14 @@ impl ShortcutAction for TranscribeAction {
         }
 
         if recording_started {
+            // Keep the model loaded until this recording has been transcribed
+            tm.begin_session();
+            // Incognito dictations leave nothing on disk, not even their partial results
+            if !app.state::<Arc<HistoryManager>>().is_incognito() {
+                app.state::<SessionJournal>().begin();
+            }
+            session_hooks::session_started(app, &binding_id);
+
             // Dynamically register the cancel shortcut in a separate task to avoid deadlock
             shortcut::register_cancel_shortcut(app);
         }
@@ -363,8 +416,13 @@ impl ShortcutAction for TranscribeAction {
 
         let ah = app.clone();
         let rm = Arc::clone(&app.state::<Arc<AudioRecordingManager>>());
+        // The start was refused or failed, there is nothing to finish
+        if !rm.is_recording() {
+            debug!("No recording to stop for binding: {}", binding_id);
+            return_to_idle(app);
+            return;
+        }
         let tm = Arc::clone(&app.state::<Arc<TranscriptionManager>>());
-        let hm = Arc::clone(&app.state::<Arc<HistoryManager>>());
 
         change_tray_icon(app, TrayIconState::Transcribing);
         show_transcribing_overlay(app);
@@ -374,16 +432,24 @@ impl ShortcutAction for TranscribeAction {
 
         // Play audio feedback for recording stop
         play_feedback_sound(app, SoundType::Stop);
+        session_hooks::session_ended(app);
 
         let binding_id = binding_id.to_string(); // Clone binding_id for the async task
 
+        // Remember which session we are finalizing so an abort during transcription
+        // prevents the result from being saved or pasted
+        let session = tm.session_generation();
+        let journal_id = app.state::<SessionJournal>().current_id();
+
         tauri::async_runtime::spawn(async move {
-            let binding_id = binding_id.clone(); // Clone for the inner async task
             debug!(
                 "Starting async transcription task for binding: {}",
                 binding_id
             );
 
+            // Closes the session begun when recording started, also when there is nothing
+            // to transcribe
+            let session_guard = tm.session_guard();
             let stop_recording_time = Instant::now();
             if let Some(samples) = rm.stop_recording(&binding_id) {
                 debug!(
@@ -392,112 +458,56 @@ impl ShortcutAction for TranscribeAction {
                     samples.len()
                 );
 
+                let mut report = SessionReport {
+                    binding_id: binding_id.clone(),
+                    audio_duration_secs: samples.len() as f32 / 16000.0,
+                    chunk_count: rm.chunk_count(),
+                    provider: get_settings(&ah).transcription_provider.id().to_string(),
+                    model: tm.get_current_model(),
+                    ..Default::default()
+                };
+
                 let transcription_time = Instant::now();
                 let samples_clone = samples.clone(); // Clone for history saving
-                match tm.transcribe(samples) {
-                    Ok(transcription) => {
-                        debug!(
-                            "Transcription completed in {:?}: '{}'",
-                            transcription_time.elapsed(),
-                            transcription
-                        );
-                        if !transcription.is_empty() {
-                            // Set the final transcription in the overlay (replaces any partial transcriptions)
-                            crate::overlay::set_final_transcription(&ah, &transcription);
-
-                            let settings = get_settings(&ah);
-                            let mut final_text = transcription.clone();
-                            let mut post_processed_text: Option<String> = None;
-                            let mut post_process_prompt: Option<String> = None;
-
-                            // Check if post-processing is needed
-                            let needs_post_processing = settings.post_process_enabled
-                                || (settings.selected_language == "zh-Hans"
-                                    || settings.selected_language == "zh-Hant");
-
-                            // If post-processing is needed, show that state
-                            if needs_post_processing {
-                                crate::overlay::show_post_processing_overlay(&ah);
-                            }
-
-                            // First, check if Chinese variant conversion is needed
-                            if let Some(converted_text) =
-                                maybe_convert_chinese_variant(&settings, &transcription).await
-                            {
-                                final_text = converted_text.clone();
-                                post_processed_text = Some(converted_text);
-                            }
-                            // Then apply regular post-processing if enabled
-                            else if let Some(processed_text) =
-                                maybe_post_process_transcription(&settings, &transcription).await
-                            {
-                                final_text = processed_text.clone();
-                                post_processed_text = Some(processed_text);
-
-                                // Get the prompt that was used
-                                if let Some(prompt_id) = &settings.post_process_selected_prompt_id {
-                                    if let Some(prompt) = settings
-                                        .post_process_prompts
-                                        .iter()
-                                        .find(|p| &p.id == prompt_id)
-                                    {
-                                        post_process_prompt = Some(prompt.prompt.clone());
-                                    }
-                                }
-                            }
-
-                            // Save to history with post-processed text and prompt
-                            let hm_clone = Arc::clone(&hm);
-                            let transcription_for_history = transcription.clone();
-                            tauri::async_runtime::spawn(async move {
-                                if let Err(e) = hm_clone
-                                    .save_transcription(
-                                        samples_clone,
-                                        transcription_for_history,
-                                        post_processed_text,
-                                        post_process_prompt,
-                                    )
-                                    .await
-                                {
-                                    error!("Failed to save transcription to history: {}", e);
-                                }
-                            });
-
-                            // Paste the final text (either processed or original)
-                            let ah_clone = ah.clone();
-                            let paste_time = Instant::now();
-                            ah.run_on_main_thread(move || {
-                                match utils::paste(final_text, ah_clone.clone()) {
-                                    Ok(()) => debug!(
-                                        "Text pasted successfully in {:?}",
-                                        paste_time.elapsed()
-                                    ),
-                                    Err(e) => error!("Failed to paste transcription: {}", e),
-                                }
-                                // Hide the overlay after pasting is complete
-                                utils::hide_recording_overlay(&ah_clone);
-                                change_tray_icon(&ah_clone, TrayIconState::Idle);
-                            })
-                            .unwrap_or_else(|e| {
-                                error!("Failed to run paste on main thread: {:?}", e);
-                                utils::hide_recording_overlay(&ah);
-                                change_tray_icon(&ah, TrayIconState::Idle);
-                            });
-                        } else {
-                            utils::hide_recording_overlay(&ah);
-                            change_tray_icon(&ah, TrayIconState::Idle);
-                        }
+                let result = tm.transcribe_final(samples);
+                drop(session_guard);
+                let mut timings = FinalTimings {
+                    inference_ms: transcription_time.elapsed().as_millis() as u64,
+                    ..Default::default()
+                };
+                match result {
+                    Ok(_) if tm.session_generation() != session => {
+                        debug!("Session was cancelled during transcription, discarding result");
+                        return;
+                    }
+                    Ok(output) => {
+                        let dictation = FinishedDictation {
+                            output,
+                            samples: samples_clone,
+                            stop_time,
+                        };
+                        deliver_dictation(&ah, dictation, &mut report, &mut timings).await;
                     }
                     Err(err) => {
                         debug!("Global Shortcut Transcription error: {}", err);
-                        utils::hide_recording_overlay(&ah);
-                        change_tray_icon(&ah, TrayIconState::Idle);
+                        transcription_error::emit(&ah, "dictation", &err);
+                        report.error = Some(err.to_string());
+                        return_to_idle(&ah);
                     }
                 }
+
+                report.processing_ms = stop_time.elapsed().as_millis() as u64;
+                session_report::emit(&ah, &report);
+                timings.total_ms = report.processing_ms;
+                perf_metrics::finish_session(&ah, session, timings);
             } else {
                 debug!("No samples retrieved from recording stop");
-                utils::hide_recording_overlay(&ah);
-                change_tray_icon(&ah, TrayIconState::Idle);
+                return_to_idle(&ah);
+            }
+
+            // The session was handled, its partial transcriptions are no longer needed
+            if let Some(id) = &journal_id {
+                ah.state::<SessionJournal>().finish(id);
             }
         });
 
@@ -508,6 +518,426 @@ impl ShortcutAction for TranscribeAction {
     }
 }
 
+/// Hides the overlay and shows the idle tray icon once a dictation is over.
+fn return_to_idle(app: &AppHandle) {
+    utils::hide_recording_overlay(app);
+    change_tray_icon(app, TrayIconState::Idle);
+}
+
+/// A dictation the final pass transcribed, on its way to the user.
+struct FinishedDictation {
+    output: TranscriptionOutput,
+    /// The recording, for history
+    samples: Vec<f32>,
+    /// When the user stopped recording
+    stop_time: Instant,
+}
+
+/// Turns a transcription into the text the user gets, and delivers it: the spoken commands
+/// in it are carried out, the text pipeline runs, and the result is pasted or sent where the
+/// settings say, saved to history and passed on to the integrations. Fills in `report`.
+async fn deliver_dictation(
+    app: &AppHandle,
+    dictation: FinishedDictation,
+    report: &mut SessionReport,
+    timings: &mut FinalTimings,
+) {
+    let FinishedDictation {
+        output,
+        samples,
+        stop_time,
+    } = dictation;
+    let rm = app.state::<Arc<AudioRecordingManager>>();
+    let hm = Arc::clone(&app.state::<Arc<HistoryManager>>());
+    let disfluency = Arc::clone(&app.state::<Arc<DisfluencyManager>>());
+
+    // Where the live pass disagrees with this one, the more confident of the two is kept
+    let (output, refinement_changes) = match refinement::merge(&rm.live_segments(), &output) {
+        Some(merged) => (
+            TranscriptionOutput {
+                text: merged.text,
+                ..output
+            },
+            merged.changes,
+        ),
+        None => (output, Vec::new()),
+    };
+    // Scored before the text is corrected or post-processed, the UI highlights these for
+    // review
+    report.average_confidence = output.average_confidence();
+    report.low_confidence_words =
+        output.low_confidence_spans(get_settings(app).low_confidence_threshold);
+    let raw_transcription = output.text;
+
+    let (transcription, mut naming) =
+        apply_voice_commands(app, &disfluency, &raw_transcription).await;
+    debug!("Transcription completed: '{}'", transcription);
+    report.raw_text = transcription.clone();
+    if transcription.is_empty() {
+        // A dictation of only commands names the previous session
+        if !naming.is_empty() {
+            if let Err(e) = name_latest_session(&hm, naming).await {
+                error!("Failed to name the previous session: {}", e);
+            }
+        }
+        return_to_idle(app);
+        return;
+    }
+
+    // Set the final transcription in the overlay (replaces any partial transcriptions)
+    crate::overlay::set_final_transcription(app, &transcription);
+    announcer::final_text(app, &transcription);
+
+    let settings = get_settings(app);
+    naming.add_tags(session_naming::rule_tags(&settings));
+
+    let post_process_time = Instant::now();
+    let processed = run_text_pipeline(
+        app,
+        &settings,
+        &disfluency,
+        &raw_transcription,
+        &transcription,
+    )
+    .await;
+    timings.post_process_ms = post_process_time.elapsed().as_millis() as u64;
+    let final_text = processed.text;
+    let post_processed_text = (final_text != transcription).then(|| final_text.clone());
+
+    report.text = final_text.clone();
+    report.post_processed = post_processed_text.is_some();
+    report.post_process_prompt = processed.llm_prompt.clone();
+    let metrics = session_metrics(&settings, report, stop_time.elapsed().as_millis() as i64);
+
+    // Save to history with post-processed text and prompt, unless in incognito mode
+    let incognito = hm.is_incognito();
+    let history_task = (!incognito).then(|| {
+        let hm = Arc::clone(&hm);
+        let transcription = transcription.clone();
+        let post_process_prompt = processed.llm_prompt;
+        tauri::async_runtime::spawn(async move {
+            let history_id = hm
+                .save_transcription(
+                    samples,
+                    transcription,
+                    post_processed_text,
+                    post_process_prompt,
+                    output.segments,
+                    naming,
+                    metrics,
+                )
+                .await
+                .map_err(|e| {
+                    error!("Failed to save transcription to history: {}", e);
+                    e.to_string()
+                })?;
+            if let Err(e) = hm.save_refinement_changes(history_id, &refinement_changes) {
+                error!("Failed to save refinement changes: {}", e);
+            }
+            Ok::<i64, String>(history_id)
+        })
+    });
+
+    let sink = OutputSink::for_settings(&settings, incognito);
+    let output_time = Instant::now();
+    let output_result = send_output(
+        app,
+        &settings,
+        sink,
+        marked_output(&settings, final_text, &report.low_confidence_words),
+    )
+    .await;
+    timings.output_ms = output_time.elapsed().as_millis() as u64;
+    let history_result = match history_task {
+        Some(task) => Some(
+            task.await
+                .map_err(|e| e.to_string())
+                .and_then(|result| result),
+        ),
+        None => None,
+    };
+
+    // Undoing the paste can then mark its history entry, text appended to a note isn't
+    // undone
+    if let (Ok(()), Some(Ok(history_id))) = (&output_result, &history_result) {
+        match sink {
+            OutputSink::Paste => utils::link_last_output(app, *history_id),
+            OutputSink::Readback => readback::link_history(app, *history_id),
+            OutputSink::MarkdownNote => {}
+        }
+    }
+    report.sinks = vec![SinkResult::new(sink.name(), output_result)];
+    if let Some(history_result) = history_result {
+        report
+            .sinks
+            .push(SinkResult::new("history", history_result.map(|_| ())));
+    }
+
+    // Nothing leaves the app in incognito mode
+    if !incognito {
+        webhook::deliver(app, report);
+        #[cfg(target_os = "linux")]
+        crate::dbus_service::transcript_ready(app, &report.text);
+    }
+}
+
+/// Removes fillers and carries out the spoken commands at the start of a transcription,
+/// returning the text left to deliver, empty when it held only commands, and the title and
+/// tags it gave the session.
+async fn apply_voice_commands(
+    app: &AppHandle,
+    disfluency: &DisfluencyManager,
+    raw_transcription: &str,
+) -> (String, SessionNaming) {
+    let settings = get_settings(app);
+    // Fillers go before the voice commands below, so "um, scratch that" is still recognized
+    let transcription = if pipeline::is_enabled(&settings, TextStageId::Fillers) {
+        disfluency.clean(raw_transcription)
+    } else {
+        raw_transcription.to_string()
+    };
+    if !settings.session_voice_commands {
+        return (
+            confirm_by_voice(app, transcription).await,
+            SessionNaming::default(),
+        );
+    }
+
+    // Spoken "Title: ..." and "Tags: ..." sentences name the session instead of being pasted
+    let (transcription, naming) = session_naming::extract_voice_commands(&transcription);
+    // "Switch to the code profile" switches before the rest is delivered
+    let transcription = switch_profile_by_voice(app, transcription);
+    // "Scratch that" takes back the previous paste instead of being pasted
+    let transcription = if clipboard::is_undo_command(&transcription) {
+        if let Err(e) = utils::undo_last_dictation(app).await {
+            warn!("Failed to undo by voice: {}", e);
+        }
+        String::new()
+    } else {
+        transcription
+    };
+    (confirm_by_voice(app, transcription).await, naming)
+}
+
+/// "Send it" pastes a transcription held for confirmation instead of being pasted itself.
+async fn confirm_by_voice(app: &AppHandle, transcription: String) -> String {
+    if readback::pending_text(app).is_some() && readback::is_confirm_command(&transcription) {
+        if let Err(e) = readback::confirm(app).await {
+            warn!("Failed to confirm by voice: {}", e);
+        }
+        return String::new();
+    }
+    transcription
+}
+
+/// Text that went through the text pipeline.
+struct ProcessedText {
+    text: String,
+    /// The prompt the LLM step rewrote the text with, if it did
+    llm_prompt: Option<String>,
+}
+
+/// Runs `transcription` through the text pipeline's stages, in the order the settings give.
+async fn run_text_pipeline(
+    app: &AppHandle,
+    settings: &AppSettings,
+    disfluency: &DisfluencyManager,
+    raw_transcription: &str,
+    transcription: &str,
+) -> ProcessedText {
+    let mut text = transcription.to_string();
+    let mut llm_prompt = None;
+    for step in pipeline::steps(app, settings) {
+        let stage = match step {
+            Step::Text(stage) => stage,
+            Step::Llm => {
+                // Chinese variant conversion takes the place of the LLM for Chinese
+                if needs_llm_step(settings) {
+                    crate::overlay::show_post_processing_overlay(app);
+                }
+                if let Some(converted_text) = maybe_convert_chinese_variant(settings, &text).await {
+                    text = converted_text;
+                } else if let Some(processed_text) =
+                    maybe_post_process_transcription(settings, &text).await
+                {
+                    // What the cleanup dropped teaches the speaker's filler model
+                    disfluency.learn(raw_transcription, &processed_text);
+                    text = processed_text;
+                    llm_prompt = selected_prompt(settings);
+                }
+                continue;
+            }
+        };
+        let applied = stage.apply(&text, settings);
+        if applied != text {
+            debug!("The {:?} stage changed the text", stage.id());
+            text = applied;
+        }
+    }
+    ProcessedText { text, llm_prompt }
+}
+
+/// Whether the LLM step has anything to do: post-processing, or converting Chinese.
+fn needs_llm_step(settings: &AppSettings) -> bool {
+    settings.post_process_enabled
+        || settings.selected_language == "zh-Hans"
+        || settings.selected_language == "zh-Hant"
+}
+
+/// Text of the selected post-processing prompt.
+fn selected_prompt(settings: &AppSettings) -> Option<String> {
+    let prompt_id = settings.post_process_selected_prompt_id.as_ref()?;
+    settings
+        .post_process_prompts
+        .iter()
+        .find(|prompt| &prompt.id == prompt_id)
+        .map(|prompt| prompt.prompt.clone())
+}
+
+/// The history metrics of a session. Cloud sessions are counted under their provider.
+fn session_metrics(
+    settings: &AppSettings,
+    report: &SessionReport,
+    processing_ms: i64,
+) -> SessionMetrics {
+    SessionMetrics {
+        audio_secs: report.audio_duration_secs as f64,
+        model_id: if settings.transcription_provider == TranscriptionProvider::Local {
+            report.model.clone()
+        } else {
+            Some(report.provider.clone())
+        },
+        processing_ms,
+        average_confidence: report.average_confidence.map(f64::from),
+    }
+}
+
+/// Where the text of a dictation goes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputSink {
+    Paste,
+    /// Held until the user confirms it, after reading it back or not
+    Readback,
+    /// Appended to the Markdown note instead of being pasted
+    MarkdownNote,
+}
+
+impl OutputSink {
+    fn for_settings(settings: &AppSettings, incognito: bool) -> Self {
+        // Incognito text is pasted, never written to the note
+        if settings.output_target == OutputTarget::MarkdownNote && !incognito {
+            OutputSink::MarkdownNote
+        } else if settings.readback_before_paste || settings.confirm_before_paste {
+            OutputSink::Readback
+        } else {
+            OutputSink::Paste
+        }
+    }
+
+    /// Name of the sink in the session report
+    fn name(self) -> &'static str {
+        match self {
+            OutputSink::Paste => "paste",
+            OutputSink::Readback => "readback",
+            OutputSink::MarkdownNote => "markdown_note",
+        }
+    }
+}
+
+/// Flags words the decoder was unsure about in the text that is output, history keeps the
+/// clean transcription.
+fn marked_output(settings: &AppSettings, text: String, low_confidence_words: &[String]) -> String {
+    match settings.low_confidence_marker.as_deref() {
+        Some(marker) if !marker.is_empty() => {
+            mark_low_confidence(&text, low_confidence_words, marker)
+        }
+        _ => text,
+    }
+}
+
+/// Sends `text` to `sink`, leaving the overlay and tray icon idle afterwards.
+async fn send_output(
+    app: &AppHandle,
+    settings: &AppSettings,
+    sink: OutputSink,
+    text: String,
+) -> Result<(), String> {
+    match sink {
+        OutputSink::MarkdownNote => {
+            let result = markdown_note::append(settings, &text)
+                .map(|path| debug!("Appended to {}", path.display()));
+            if let Err(e) = &result {
+                error!("Failed to append to the Markdown note: {}", e);
+            }
+            return_to_idle(app);
+            result
+        }
+        OutputSink::Readback => {
+            readback::hold(app, text);
+            return_to_idle(app);
+            Ok(())
+        }
+        OutputSink::Paste => {
+            let ah = app.clone();
+            let paste_time = Instant::now();
+            let (paste_tx, paste_rx) = tokio::sync::oneshot::channel();
+            app.run_on_main_thread(move || {
+                let result = utils::paste(text, ah.clone());
+                match &result {
+                    Ok(()) => debug!("Text pasted successfully in {:?}", paste_time.elapsed()),
+                    Err(e) => error!("Failed to paste transcription: {}", e),
+                }
+                let _ = paste_tx.send(result);
+                // Hide the overlay after pasting is complete
+                return_to_idle(&ah);
+            })
+            .unwrap_or_else(|e| {
+                error!("Failed to run paste on main thread: {:?}", e);
+                return_to_idle(app);
+            });
+
+            paste_rx
+                .await
+                .unwrap_or_else(|_| Err("Paste did not run".to_string()))
+        }
+    }
+}
+
+/// Applies the title and tags of a dictation that held nothing else to the latest history
+/// entry. Its existing tags are kept.
+async fn name_latest_session(hm: &HistoryManager, naming: SessionNaming) -> anyhow::Result<()> {
+    let Some(entry) = hm.get_latest_entry().await? else {
+        return Ok(());
+    };
+    if let Some(title) = &naming.title {
+        hm.set_entry_title(entry.id, title).await?;
+    }
+    if !naming.tags.is_empty() {
+        let mut tags = entry.tags;
+        tags.extend(naming.tags);
+        hm.set_entry_tags(entry.id, &tags).await?;
+    }
+    Ok(())
+}
+
+/// Switches profile when `transcription` starts with a spoken switch command and returns
+/// the text after it. The text is returned whole when the switch fails.
+fn switch_profile_by_voice(app: &AppHandle, transcription: String) -> String {
+    let settings = get_settings(app);
+    let Some((profile, rest)) =
+        profiles::extract_switch_command(&transcription, &settings.profiles)
+    else {
+        return transcription;
+    };
+    match profiles::switch_to(app, &profile.id) {
+        Ok(_) => rest,
+        Err(e) => {
+            error!("Failed to switch profile by voice: {}", e);
+            transcription
+        }
+    }
+}
+
 // Cancel Action
 struct CancelAction;
 
@@ -521,6 +951,187 @@ impl ShortcutAction for CancelAction {
     }
 }
 
+// Cancel Session Action
+struct CancelSessionAction;
+
+impl ShortcutAction for CancelSessionAction {
+    fn start(&self, app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        // Also discards a transcription still running after the recording stopped
+        let rm = app.state::<Arc<AudioRecordingManager>>();
+        let tm = app.state::<Arc<TranscriptionManager>>();
+        if rm.is_recording() || tm.has_active_session() {
+            utils::cancel_current_session(app);
+        }
+    }
+
+    fn stop(&self, _app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        // Nothing to do on stop for cancel session
+    }
+}
+
+// Copy Next Part Action
+struct CopyNextPartAction;
+
+impl ShortcutAction for CopyNextPartAction {
+    fn start(&self, app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        if let Err(e) = utils::copy_next_chunk(app) {
+            error!("Failed to copy next part: {}", e);
+        }
+    }
+
+    fn stop(&self, _app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        // Nothing to do on stop for copy next part
+    }
+}
+
+// Name Session Action
+struct NameSessionAction;
+
+impl ShortcutAction for NameSessionAction {
+    fn start(&self, app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let hm = Arc::clone(&app.state::<Arc<HistoryManager>>());
+            match hm.get_latest_entry().await {
+                Ok(Some(entry)) => {
+                    crate::show_main_window(&app);
+                    // The UI opens its title and tag editor for the entry
+                    if let Err(e) = app.emit("name-session-requested", entry) {
+                        error!("Failed to emit name-session-requested event: {}", e);
+                    }
+                }
+                Ok(None) => debug!("No session to name yet"),
+                Err(e) => error!("Failed to load the latest session: {}", e),
+            }
+        });
+    }
+
+    fn stop(&self, _app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        // Nothing to do on stop for name session
+    }
+}
+
+// Cycle Profile Action
+struct CycleProfileAction;
+
+impl ShortcutAction for CycleProfileAction {
+    fn start(&self, app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        match profiles::cycle(app) {
+            Ok(Some(_)) => {}
+            Ok(None) => debug!("No profiles to cycle through"),
+            Err(e) => error!("Failed to switch profile: {}", e),
+        }
+    }
+
+    fn stop(&self, _app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        // Nothing to do on stop for cycle profile
+    }
+}
+
+// Repeat Last Paste Action
+struct RepeatLastPasteAction;
+
+impl ShortcutAction for RepeatLastPasteAction {
+    fn start(&self, app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = utils::repaste_history_entry(&app, None).await {
+                error!("Failed to paste the last transcription: {}", e);
+            }
+        });
+    }
+
+    fn stop(&self, _app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        // Nothing to do on stop for repeat last paste
+    }
+}
+
+// Undo Dictation Action
+struct UndoDictationAction;
+
+impl ShortcutAction for UndoDictationAction {
+    fn start(&self, app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = utils::undo_last_dictation(&app).await {
+                error!("Failed to undo the last dictation: {}", e);
+            }
+        });
+    }
+
+    fn stop(&self, _app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        // Nothing to do on stop for undo dictation
+    }
+}
+
+// Read Back Action
+struct ReadBackAction;
+
+impl ShortcutAction for ReadBackAction {
+    fn start(&self, app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = readback::read_back(&app).await {
+                error!("Failed to read the transcription back: {}", e);
+            }
+        });
+    }
+
+    fn stop(&self, _app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        // Nothing to do on stop for read back
+    }
+}
+
+// Confirm Readback Action
+struct ConfirmReadbackAction;
+
+impl ShortcutAction for ConfirmReadbackAction {
+    fn start(&self, app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = readback::confirm(&app).await {
+                error!("Failed to paste the held transcription: {}", e);
+            }
+        });
+    }
+
+    fn stop(&self, _app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        // Nothing to do on stop for confirm readback
+    }
+}
+
+// Discard Readback Action
+struct DiscardReadbackAction;
+
+impl ShortcutAction for DiscardReadbackAction {
+    fn start(&self, app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        if !readback::discard(app) {
+            debug!("No transcription held for readback");
+        }
+    }
+
+    fn stop(&self, _app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        // Nothing to do on stop for discard readback
+    }
+}
+
+// Open History Action
+struct OpenHistoryAction;
+
+impl ShortcutAction for OpenHistoryAction {
+    fn start(&self, app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        crate::show_main_window(app);
+        // The UI switches to its history section
+        if let Err(e) = app.emit("open-history-requested", ()) {
+            error!("Failed to emit open-history-requested event: {}", e);
+        }
+    }
+
+    fn stop(&self, _app: &AppHandle, _binding_id: &str, _shortcut_str: &str) {
+        // Nothing to do on stop for open history
+    }
+}
+
 // Test Action
 struct TestAction;
 
@@ -551,13 +1162,163 @@ pub static ACTION_MAP: Lazy<HashMap<String, Arc<dyn ShortcutAction>>> = Lazy::ne
         "transcribe".to_string(),
         Arc::new(TranscribeAction) as Arc<dyn ShortcutAction>,
     );
+    // Recording in a fixed mode, see `shortcut::handle_trigger`
+    map.insert(
+        "push_to_talk".to_string(),
+        Arc::new(TranscribeAction) as Arc<dyn ShortcutAction>,
+    );
+    map.insert(
+        "toggle_recording".to_string(),
+        Arc::new(TranscribeAction) as Arc<dyn ShortcutAction>,
+    );
     map.insert(
         "cancel".to_string(),
         Arc::new(CancelAction) as Arc<dyn ShortcutAction>,
     );
+    map.insert(
+        "cancel_session".to_string(),
+        Arc::new(CancelSessionAction) as Arc<dyn ShortcutAction>,
+    );
+    map.insert(
+        "copy_next_part".to_string(),
+        Arc::new(CopyNextPartAction) as Arc<dyn ShortcutAction>,
+    );
+    map.insert(
+        "name_session".to_string(),
+        Arc::new(NameSessionAction) as Arc<dyn ShortcutAction>,
+    );
+    map.insert(
+        "cycle_profile".to_string(),
+        Arc::new(CycleProfileAction) as Arc<dyn ShortcutAction>,
+    );
+    map.insert(
+        "repeat_last_paste".to_string(),
+        Arc::new(RepeatLastPasteAction) as Arc<dyn ShortcutAction>,
+    );
+    map.insert(
+        "undo_dictation".to_string(),
+        Arc::new(UndoDictationAction) as Arc<dyn ShortcutAction>,
+    );
+    map.insert(
+        "open_history".to_string(),
+        Arc::new(OpenHistoryAction) as Arc<dyn ShortcutAction>,
+    );
+    map.insert(
+        "read_back".to_string(),
+        Arc::new(ReadBackAction) as Arc<dyn ShortcutAction>,
+    );
+    map.insert(
+        "confirm_readback".to_string(),
+        Arc::new(ConfirmReadbackAction) as Arc<dyn ShortcutAction>,
+    );
+    map.insert(
+        "discard_readback".to_string(),
+        Arc::new(DiscardReadbackAction) as Arc<dyn ShortcutAction>,
+    );
     map.insert(
         "test".to_string(),
         Arc::new(TestAction) as Arc<dyn ShortcutAction>,
     );
     map
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::{get_default_settings, LLMPrompt};
+
+    #[test]
+    fn test_output_sink() {
+        let mut settings = get_default_settings();
+        assert_eq!(
+            OutputSink::for_settings(&settings, false),
+            OutputSink::Paste
+        );
+
+        settings.confirm_before_paste = true;
+        assert_eq!(
+            OutputSink::for_settings(&settings, false),
+            OutputSink::Readback
+        );
+
+        // The note wins over holding the text, except in incognito mode
+        settings.output_target = OutputTarget::MarkdownNote;
+        assert_eq!(
+            OutputSink::for_settings(&settings, false),
+            OutputSink::MarkdownNote
+        );
+        assert_eq!(
+            OutputSink::for_settings(&settings, true),
+            OutputSink::Readback
+        );
+        settings.confirm_before_paste = false;
+        assert_eq!(OutputSink::for_settings(&settings, true), OutputSink::Paste);
+        assert_eq!(OutputSink::MarkdownNote.name(), "markdown_note");
+    }
+
+    #[test]
+    fn test_session_metrics() {
+        let mut settings = get_default_settings();
+        let report = SessionReport {
+            audio_duration_secs: 2.5,
+            provider: "openai".to_string(),
+            model: Some("parakeet-tdt-0.6b-v3".to_string()),
+            average_confidence: Some(0.5),
+            ..Default::default()
+        };
+
+        let metrics = session_metrics(&settings, &report, 120);
+        assert_eq!(metrics.model_id.as_deref(), Some("parakeet-tdt-0.6b-v3"));
+        assert_eq!(metrics.audio_secs, 2.5);
+        assert_eq!(metrics.processing_ms, 120);
+        assert_eq!(metrics.average_confidence, Some(0.5));
+
+        settings.transcription_provider = TranscriptionProvider::OpenAi;
+        let metrics = session_metrics(&settings, &report, 120);
+        assert_eq!(metrics.model_id.as_deref(), Some("openai"));
+    }
+
+    #[test]
+    fn test_marked_output() {
+        let mut settings = get_default_settings();
+        let words = ["Jon".to_string()];
+        assert_eq!(
+            marked_output(&settings, "Call Jon.".to_string(), &words),
+            "Call Jon."
+        );
+
+        settings.low_confidence_marker = Some("[?]".to_string());
+        assert_eq!(
+            marked_output(&settings, "Call Jon.".to_string(), &words),
+            "Call Jon[?]."
+        );
+        assert_eq!(
+            marked_output(&settings, "Call Jon.".to_string(), &[]),
+            "Call Jon."
+        );
+    }
+
+    #[test]
+    fn test_llm_step_settings() {
+        let mut settings = get_default_settings();
+        settings.post_process_enabled = false;
+        settings.selected_language = "en".to_string();
+        assert!(!needs_llm_step(&settings));
+        settings.selected_language = "zh-Hant".to_string();
+        assert!(needs_llm_step(&settings));
+
+        settings.post_process_prompts = vec![LLMPrompt {
+            id: "tidy".to_string(),
+            name: "Tidy".to_string(),
+            prompt: "Tidy up: ${output}".to_string(),
+        }];
+        assert_eq!(selected_prompt(&settings), None);
+        settings.post_process_selected_prompt_id = Some("tidy".to_string());
+        assert_eq!(
+            selected_prompt(&settings).as_deref(),
+            Some("Tidy up: ${output}")
+        );
+        settings.post_process_selected_prompt_id = Some("gone".to_string());
+        assert_eq!(selected_prompt(&settings), None);
+    }
+}
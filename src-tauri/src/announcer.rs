@@ -0,0 +1,152 @@
+//! Reads recognized text out through the screen reader, for users who can't glance at the
+//! overlay. Only what is new since the last announcement is spoken, so partial results
+//! don't repeat the whole utterance every second.
+//!
+//! VoiceOver is driven through AppleScript on macOS and speech-dispatcher, which Orca
+//! speaks through, on Linux. Every platform also gets the `screen-reader-announcement`
+//! event so the main window can put the text in an ARIA live region, which is how
+//! Narrator and NVDA on Windows hear it.
+
+use crate::settings::{get_settings, ScreenReaderAnnouncements};
+use log::debug;
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Announcement {
+    pub text: String,
+    pub is_final: bool,
+}
+
+/// The part of `text` not yet covered by `announced`, on word boundaries. A revised
+/// transcription that no longer starts with what was announced is announced in full.
+fn unannounced<'a>(announced: &str, text: &'a str) -> &'a str {
+    let announced = announced.trim_end();
+    match text.strip_prefix(announced) {
+        Some(rest) if announced.is_empty() || rest.is_empty() || rest.starts_with(' ') => {
+            rest.trim()
+        }
+        _ => text.trim(),
+    }
+}
+
+#[derive(Default)]
+pub struct Announcer {
+    /// Text of the current recording spoken so far
+    announced: Mutex<String>,
+}
+
+impl Announcer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets what was announced, at the start of a recording.
+    pub fn reset(&self) {
+        self.announced.lock().unwrap().clear();
+    }
+
+    fn announce(&self, app: &AppHandle, text: &str, is_final: bool) {
+        let mode = get_settings(app).screen_reader_announcements;
+        let wanted = match mode {
+            ScreenReaderAnnouncements::Off => false,
+            ScreenReaderAnnouncements::Final => is_final,
+            ScreenReaderAnnouncements::Live => true,
+        };
+        if !wanted {
+            return;
+        }
+
+        let new_text = {
+            let mut announced = self.announced.lock().unwrap();
+            // In final-only mode nothing was announced during the recording
+            let new_text = unannounced(&announced, text).to_string();
+            *announced = if is_final {
+                String::new()
+            } else {
+                text.to_string()
+            };
+            new_text
+        };
+        if new_text.is_empty() {
+            return;
+        }
+
+        debug!("Announcing {} characters", new_text.len());
+        let _ = app.emit(
+            "screen-reader-announcement",
+            Announcement {
+                text: new_text.clone(),
+                is_final,
+            },
+        );
+        speak(new_text, is_final);
+    }
+}
+
+/// Announces the new part of a live transcription, when live announcements are on.
+pub fn partial(app: &AppHandle, text: &str) {
+    if let Some(announcer) = app.try_state::<Announcer>() {
+        announcer.announce(app, text, false);
+    }
+}
+
+/// Announces the final transcription, or what of it wasn't announced live.
+pub fn final_text(app: &AppHandle, text: &str) {
+    if let Some(announcer) = app.try_state::<Announcer>() {
+        announcer.announce(app, text, true);
+    }
+}
+
+pub fn reset(app: &AppHandle) {
+    if let Some(announcer) = app.try_state::<Announcer>() {
+        announcer.reset();
+    }
+}
+
+/// Hands `text` to VoiceOver, only if it is already running.
+#[cfg(target_os = "macos")]
+fn speak(text: String, _is_final: bool) {
+    let escaped = text.replace('\\', "\\\\").replace('"', "\\\"");
+    let script = format!(
+        "if application \"VoiceOver\" is running then tell application \"VoiceOver\" to output \"{}\"",
+        escaped
+    );
+    std::thread::spawn(move || {
+        let _ = std::process::Command::new("osascript")
+            .args(["-e", &script])
+            .status();
+    });
+}
+
+/// Speaks through speech-dispatcher. Partials may be cut off by the next message, the final
+/// text is queued after them.
+#[cfg(target_os = "linux")]
+fn speak(text: String, is_final: bool) {
+    let priority = if is_final { "message" } else { "progress" };
+    std::thread::spawn(move || {
+        let _ = std::process::Command::new("spd-say")
+            .args(["--priority", priority, "--", &text])
+            .status();
+    });
+}
+
+/// Screen readers pick the text up from the live region fed by the event.
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn speak(_text: String, _is_final: bool) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unannounced() {
+        assert_eq!(unannounced("", "Hello there"), "Hello there");
+        assert_eq!(unannounced("Hello", "Hello there friend"), "there friend");
+        assert_eq!(unannounced("Hello there", "Hello there"), "");
+        // "Hell" was a partial word, the revision is announced whole
+        assert_eq!(unannounced("Hell", "Hello there"), "Hello there");
+        assert_eq!(unannounced("Hello there", "Yellow there"), "Yellow there");
+    }
+}
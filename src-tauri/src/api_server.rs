@@ -0,0 +1,608 @@
+//! Optional localhost HTTP server speaking the OpenAI transcription API.
+//!
+//! `POST /v1/audio/transcriptions` accepts the same multipart form as OpenAI (`file`, `model`,
+//! `response_format`, ...) and answers in the same JSON shape, so tools that already talk to
+//! that API can point their base URL at `http://127.0.0.1:<port>/v1` and use the local model
+//! instead. Uploads must be WAV; the `model` field is accepted but the loaded model is used.
+
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::cli::{read_wav, to_srt};
+use crate::managers::file_jobs::FileJobQueue;
+use crate::managers::model::ModelManager;
+use crate::managers::transcription::TranscriptionManager;
+use crate::mcp;
+use crate::settings::get_settings;
+use anyhow::Result;
+use log::{debug, error, info, warn};
+use serde_json::json;
+use std::io::{BufRead, BufReader, Cursor, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// Uploads larger than this are rejected before being read into memory. About 25 minutes of
+/// 16 kHz mono WAV, or 6 minutes of 44.1 kHz stereo.
+const MAX_BODY_BYTES: usize = 50 * 1024 * 1024;
+
+/// Bodies are read in steps of this size, so memory only grows as data actually arrives.
+const BODY_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Connections handled at once, further ones are turned away with a 503.
+const MAX_CONNECTIONS: usize = 8;
+
+pub struct ApiServer {
+    running: Mutex<Option<RunningServer>>,
+}
+
+struct RunningServer {
+    port: u16,
+    shutdown: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+impl ApiServer {
+    pub fn new() -> Self {
+        Self {
+            running: Mutex::new(None),
+        }
+    }
+
+    /// Starts, stops or restarts the server so it matches the current settings. It runs
+    /// while either the transcription API or the MCP endpoint is enabled.
+    pub fn apply_settings(&self, app: &AppHandle) -> Result<()> {
+        let settings = get_settings(app);
+        let enabled = settings.api_server_enabled || settings.mcp_server_enabled;
+        let mut running = self.running.lock().unwrap();
+
+        if let Some(server) = running.as_ref() {
+            if enabled && server.port == settings.api_server_port {
+                return Ok(());
+            }
+        }
+
+        if let Some(server) = running.take() {
+            server.stop();
+        }
+
+        if enabled {
+            *running = Some(RunningServer::start(app.clone(), settings.api_server_port)?);
+        }
+
+        Ok(())
+    }
+}
+
+impl RunningServer {
+    fn start(app: AppHandle, port: u16) -> Result<Self> {
+        // Only ever listen on loopback, the endpoint has no authentication
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        listener.set_nonblocking(true)?;
+        info!(
+            "Transcription API listening on http://127.0.0.1:{}/v1",
+            port
+        );
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let shutdown_cloned = shutdown.clone();
+        let active = Arc::new(AtomicUsize::new(0));
+        let handle = thread::spawn(move || {
+            while !shutdown_cloned.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        let Some(slot) = ConnectionSlot::acquire(&active) else {
+                            let _ = stream.set_nonblocking(false);
+                            let response = Response::error(503, "Too many connections");
+                            let _ = write_response(&stream, &response);
+                            continue;
+                        };
+                        let app = app.clone();
+                        thread::spawn(move || {
+                            let _slot = slot;
+                            handle_connection(&app, stream);
+                        });
+                    }
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                        thread::sleep(Duration::from_millis(50));
+                    }
+                    Err(e) => {
+                        warn!("Failed to accept API connection: {}", e);
+                        thread::sleep(Duration::from_millis(50));
+                    }
+                }
+            }
+            debug!("Transcription API server on port {} stopped", port);
+        });
+
+        Ok(Self {
+            port,
+            shutdown,
+            handle,
+        })
+    }
+
+    fn stop(self) {
+        self.shutdown.store(true, Ordering::Relaxed);
+        if let Err(e) = self.handle.join() {
+            warn!("Failed to join API server thread: {:?}", e);
+        }
+    }
+}
+
+/// Counts towards [`MAX_CONNECTIONS`] until dropped.
+struct ConnectionSlot(Arc<AtomicUsize>);
+
+impl ConnectionSlot {
+    fn acquire(active: &Arc<AtomicUsize>) -> Option<Self> {
+        active
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+                (count < MAX_CONNECTIONS).then_some(count + 1)
+            })
+            .ok()
+            .map(|_| Self(active.clone()))
+    }
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn json(value: serde_json::Value) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/json",
+            body: value.to_string().into_bytes(),
+        }
+    }
+
+    fn text(text: String) -> Self {
+        Self {
+            status: 200,
+            content_type: "text/plain; charset=utf-8",
+            body: text.into_bytes(),
+        }
+    }
+
+    /// Error in the `{"error": {...}}` shape OpenAI clients know how to surface.
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        let error_type = if status >= 500 {
+            "server_error"
+        } else {
+            "invalid_request_error"
+        };
+        Self {
+            status,
+            ..Self::json(json!({
+                "error": {
+                    "message": message.into(),
+                    "type": error_type,
+                    "param": null,
+                    "code": null,
+                }
+            }))
+        }
+    }
+}
+
+fn handle_connection(app: &AppHandle, stream: TcpStream) {
+    let _ = stream.set_nonblocking(false);
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(30)));
+
+    let response = match read_request(&stream) {
+        Ok(request) => route(app, request),
+        Err(response) => response,
+    };
+
+    if let Err(e) = write_response(&stream, &response) {
+        debug!("Failed to write API response: {}", e);
+    }
+}
+
+fn read_request(stream: &TcpStream) -> Result<Request, Response> {
+    let bad_request = |e: std::io::Error| Response::error(400, e.to_string());
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).map_err(bad_request)?;
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method.to_string(), target),
+        _ => return Err(Response::error(400, "Malformed request line")),
+    };
+    let path = target.split('?').next().unwrap_or(target).to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).map_err(bad_request)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            headers.push((key.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    let mut request = Request {
+        method,
+        path,
+        headers,
+        body: Vec::new(),
+    };
+
+    // Web pages may reach loopback too, so browser requests from anywhere but a local page
+    // are refused on every route, before their body is read
+    if let Some(origin) = request.header("origin") {
+        if !is_local_origin(origin) {
+            return Err(Response::error(
+                403,
+                format!("Origin {} is not allowed", origin),
+            ));
+        }
+    }
+
+    if request
+        .header("transfer-encoding")
+        .is_some_and(|v| v.eq_ignore_ascii_case("chunked"))
+    {
+        return Err(Response::error(411, "Chunked uploads are not supported"));
+    }
+
+    let content_length: usize = request
+        .header("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    if content_length > MAX_BODY_BYTES {
+        return Err(Response::error(413, "Uploaded file is too large"));
+    }
+
+    // curl and others wait for this before sending large bodies
+    if request
+        .header("expect")
+        .is_some_and(|v| v.eq_ignore_ascii_case("100-continue"))
+    {
+        let mut writer = stream;
+        writer
+            .write_all(b"HTTP/1.1 100 Continue\r\n\r\n")
+            .map_err(bad_request)?;
+    }
+
+    request.body = read_body(&mut reader, content_length).map_err(bad_request)?;
+
+    Ok(request)
+}
+
+/// Reads exactly `length` bytes without reserving them all up front, a client that
+/// announces a large body but never sends it only costs one chunk.
+fn read_body(reader: &mut impl Read, length: usize) -> std::io::Result<Vec<u8>> {
+    let mut body = Vec::with_capacity(length.min(BODY_CHUNK_BYTES));
+    reader.take(length as u64).read_to_end(&mut body)?;
+    if body.len() < length {
+        return Err(std::io::Error::new(
+            ErrorKind::UnexpectedEof,
+            "Request body ended early",
+        ));
+    }
+    Ok(body)
+}
+
+fn write_response(mut stream: &TcpStream, response: &Response) -> std::io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason,
+        response.content_type,
+        response.body.len()
+    )?;
+    stream.write_all(&response.body)?;
+    stream.flush()
+}
+
+fn route(app: &AppHandle, request: Request) -> Response {
+    debug!("API request: {} {}", request.method, request.path);
+    let settings = get_settings(app);
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/v1/models") if settings.api_server_enabled => list_models(app),
+        ("POST", "/v1/audio/transcriptions") if settings.api_server_enabled => {
+            transcribe(app, &request)
+        }
+        ("POST", "/mcp") if settings.mcp_server_enabled => mcp_message(app, &request),
+        // No server-sent event stream is offered
+        ("GET", "/mcp") if settings.mcp_server_enabled => {
+            Response::error(405, "Only POST is supported")
+        }
+        _ => Response::error(404, format!("Unknown endpoint {}", request.path)),
+    }
+}
+
+fn mcp_message(app: &AppHandle, request: &Request) -> Response {
+    match mcp::handle(app, &request.body) {
+        Some(response) => Response::json(response),
+        None => Response {
+            status: 202,
+            content_type: "application/json",
+            body: Vec::new(),
+        },
+    }
+}
+
+fn is_local_origin(origin: &str) -> bool {
+    let host = origin
+        .strip_prefix("http://")
+        .or_else(|| origin.strip_prefix("https://"))
+        .unwrap_or(origin);
+    let host = host.rsplit_once(':').map_or(host, |(host, _)| host);
+    matches!(host, "localhost" | "127.0.0.1" | "[::1]")
+}
+
+fn list_models(app: &AppHandle) -> Response {
+    let model_manager = app.state::<Arc<ModelManager>>();
+    let models: Vec<_> = model_manager
+        .get_available_models()
+        .into_iter()
+        .filter(|model| model.is_downloaded)
+        .map(|model| {
+            json!({
+                "id": model.id,
+                "object": "model",
+                "created": 0,
+                "owned_by": "handy",
+            })
+        })
+        .collect();
+
+    Response::json(json!({ "object": "list", "data": models }))
+}
+
+fn transcribe(app: &AppHandle, request: &Request) -> Response {
+    let Some(boundary) = request.header("content-type").and_then(multipart_boundary) else {
+        return Response::error(400, "Expected a multipart/form-data request");
+    };
+    let fields = match parse_multipart(&request.body, &boundary) {
+        Ok(fields) => fields,
+        Err(e) => return Response::error(400, e),
+    };
+    let field = |name: &str| fields.iter().find(|f| f.name == name);
+
+    let Some(file) = field("file") else {
+        return Response::error(400, "Missing required field 'file'");
+    };
+    let response_format = field("response_format")
+        .map(|f| String::from_utf8_lossy(&f.data).trim().to_string())
+        .unwrap_or_else(|| "json".to_string());
+    if !matches!(
+        response_format.as_str(),
+        "json" | "text" | "verbose_json" | "srt"
+    ) {
+        return Response::error(
+            400,
+            format!("Unsupported response_format '{}'", response_format),
+        );
+    }
+
+    let samples = match read_wav(Cursor::new(&file.data)) {
+        Ok(samples) => samples,
+        Err(e) => {
+            return Response::error(400, format!("Could not decode audio, expected WAV: {}", e))
+        }
+    };
+    let duration = samples.len() as f32 / WHISPER_SAMPLE_RATE as f32;
+
+    let tm = app.state::<Arc<TranscriptionManager>>();
+    if !tm.is_model_loaded() {
+        tm.initiate_model_load();
+    }
+    // Queued with other uploads so concurrent requests share the engine fairly
+    let output = match app.state::<Arc<FileJobQueue>>().transcribe(samples) {
+        Ok(output) => output,
+        Err(e) => {
+            error!("API transcription failed: {}", e);
+            return Response::error(503, e.to_string());
+        }
+    };
+
+    match response_format.as_str() {
+        "text" => Response::text(format!("{}\n", output.text)),
+        "srt" => Response::text(to_srt(&output, duration)),
+        "verbose_json" => {
+            let segments: Vec<_> = output
+                .segments
+                .iter()
+                .enumerate()
+                .map(|(id, segment)| {
+                    json!({
+                        "id": id,
+                        "start": segment.start,
+                        "end": segment.end,
+                        "text": segment.text,
+                        "confidence": segment.confidence,
+                    })
+                })
+                .collect();
+            Response::json(json!({
+                "task": "transcribe",
+                "language": get_settings(app).selected_language,
+                "duration": duration,
+                "text": output.text,
+                "segments": segments,
+            }))
+        }
+        _ => Response::json(json!({ "text": output.text })),
+    }
+}
+
+#[derive(Debug)]
+struct MultipartField {
+    name: String,
+    data: Vec<u8>,
+}
+
+fn multipart_boundary(content_type: &str) -> Option<String> {
+    let mut params = content_type.split(';').map(str::trim);
+    if !params.next()?.eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+    params
+        .find_map(|param| param.strip_prefix("boundary="))
+        .map(|boundary| boundary.trim_matches('"').to_string())
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|i| i + from)
+}
+
+fn parse_multipart(body: &[u8], boundary: &str) -> Result<Vec<MultipartField>, String> {
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let part_end = format!("\r\n--{}", boundary).into_bytes();
+
+    let mut pos =
+        find(body, &delimiter, 0).ok_or("Multipart boundary not found")? + delimiter.len();
+    let mut fields = Vec::new();
+
+    // Each part is "\r\n<headers>\r\n\r\n<data>\r\n--boundary", the last boundary ends in "--"
+    while !body[pos..].starts_with(b"--") {
+        let headers_start = pos + 2;
+        let headers_end =
+            find(body, b"\r\n\r\n", headers_start).ok_or("Malformed multipart part")?;
+        let data_start = headers_end + 4;
+        let data_end = find(body, &part_end, data_start).ok_or("Unterminated multipart part")?;
+
+        let headers = String::from_utf8_lossy(&body[headers_start..headers_end]);
+        let name = headers
+            .lines()
+            .filter(|line| {
+                line.to_ascii_lowercase()
+                    .starts_with("content-disposition:")
+            })
+            .flat_map(|line| line.split(';').map(str::trim))
+            .find_map(|param| param.strip_prefix("name="))
+            .map(|name| name.trim_matches('"').to_string());
+
+        if let Some(name) = name {
+            fields.push(MultipartField {
+                name,
+                data: body[data_start..data_end].to_vec(),
+            });
+        }
+
+        pos = data_end + part_end.len();
+    }
+
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multipart_boundary() {
+        assert_eq!(
+            multipart_boundary("multipart/form-data; boundary=\"abc123\"").as_deref(),
+            Some("abc123")
+        );
+        assert_eq!(multipart_boundary("application/json"), None);
+    }
+
+    #[test]
+    fn test_is_local_origin() {
+        assert!(is_local_origin("http://localhost:6274"));
+        assert!(is_local_origin("http://127.0.0.1"));
+        assert!(!is_local_origin("https://example.com"));
+        assert!(!is_local_origin("http://localhost.example.com"));
+    }
+
+    #[test]
+    fn test_read_body() {
+        let data = vec![7u8; BODY_CHUNK_BYTES * 3];
+        let body = read_body(&mut Cursor::new(&data), data.len()).unwrap();
+        assert_eq!(body, data);
+
+        // Only the bytes asked for are consumed
+        let body = read_body(&mut Cursor::new(b"abcdef"), 3).unwrap();
+        assert_eq!(body, b"abc");
+
+        // A body shorter than its Content-Length is an error, not a zero-padded upload
+        let err = read_body(&mut Cursor::new(b"abc"), MAX_BODY_BYTES).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_connection_slots() {
+        let active = Arc::new(AtomicUsize::new(0));
+        let slots: Vec<_> = (0..MAX_CONNECTIONS)
+            .map(|_| ConnectionSlot::acquire(&active).unwrap())
+            .collect();
+        assert!(ConnectionSlot::acquire(&active).is_none());
+
+        drop(slots);
+        assert_eq!(active.load(Ordering::Acquire), 0);
+        assert!(ConnectionSlot::acquire(&active).is_some());
+    }
+
+    #[test]
+    fn test_parse_multipart() {
+        let body = b"--xyz\r\n\
+Content-Disposition: form-data; name=\"model\"\r\n\r\n\
+whisper-1\r\n\
+--xyz\r\n\
+Content-Disposition: form-data; name=\"file\"; filename=\"a.wav\"\r\n\
+Content-Type: audio/wav\r\n\r\n\
+RIFF\r\n--x\0data\r\n\
+--xyz--\r\n";
+
+        let fields = parse_multipart(body, "xyz").unwrap();
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0].name, "model");
+        assert_eq!(fields[0].data, b"whisper-1");
+        assert_eq!(fields[1].name, "file");
+        assert_eq!(fields[1].data, b"RIFF\r\n--x\0data");
+
+        assert!(parse_multipart(b"no boundary here", "xyz").is_err());
+    }
+}
@@ -2,11 +2,13 @@ use crate::settings::SoundTheme;
 use crate::settings::{self, AppSettings};
 use cpal::traits::{DeviceTrait, HostTrait};
 use log::{debug, error, warn};
-use rodio::OutputStreamBuilder;
+use rodio::buffer::SamplesBuffer;
+use rodio::{OutputStream, OutputStreamBuilder, Sink};
 use std::fs::File;
 use std::io::BufReader;
 use std::path::{Path, PathBuf};
 use std::thread;
+use std::time::Instant;
 use tauri::{AppHandle, Manager};
 
 pub enum SoundType {
@@ -67,6 +69,29 @@ pub fn play_test_sound(app: &AppHandle, sound_type: SoundType) {
     }
 }
 
+/// Plays `path` on the selected output device at full volume, returning once it ends.
+pub fn play_file_blocking(app: &AppHandle, path: &Path) -> Result<(), String> {
+    let settings = settings::get_settings(app);
+    play_audio_file(path, settings.selected_output_device, 1.0).map_err(|e| e.to_string())
+}
+
+/// Plays mono `samples` on the selected output device at full volume and waits for them to
+/// end. Returns the moment playback started, once the device was open.
+pub fn play_samples_blocking(
+    app: &AppHandle,
+    samples: Vec<f32>,
+    sample_rate: u32,
+) -> Result<Instant, String> {
+    let settings = settings::get_settings(app);
+    let stream_handle =
+        open_output_stream(settings.selected_output_device).map_err(|e| e.to_string())?;
+    let sink = Sink::connect_new(stream_handle.mixer());
+    let started = Instant::now();
+    sink.append(SamplesBuffer::new(1, sample_rate, samples));
+    sink.sleep_until_end();
+    Ok(started)
+}
+
 fn play_sound_async(app: &AppHandle, path: PathBuf) {
     let app_handle = app.clone();
     thread::spawn(move || {
@@ -94,6 +119,23 @@ fn play_audio_file(
     selected_device: Option<String>,
     volume: f32,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let stream_handle = open_output_stream(selected_device)?;
+    let mixer = stream_handle.mixer();
+
+    let file = File::open(path)?;
+    let buf_reader = BufReader::new(file);
+
+    let sink = rodio::play(mixer, buf_reader)?;
+    sink.set_volume(volume);
+    sink.sleep_until_end();
+
+    Ok(())
+}
+
+/// Opens `selected_device`, or the default output device when it is unset or gone.
+fn open_output_stream(
+    selected_device: Option<String>,
+) -> Result<OutputStream, Box<dyn std::error::Error>> {
     let stream_builder = if let Some(device_name) = selected_device {
         if device_name == "Default" {
             debug!("Using default device");
@@ -123,15 +165,5 @@ fn play_audio_file(
         OutputStreamBuilder::from_default_device()?
     };
 
-    let stream_handle = stream_builder.open_stream()?;
-    let mixer = stream_handle.mixer();
-
-    let file = File::open(path)?;
-    let buf_reader = BufReader::new(file);
-
-    let sink = rodio::play(mixer, buf_reader)?;
-    sink.set_volume(volume);
-    sink.sleep_until_end();
-
-    Ok(())
+    Ok(stream_builder.open_stream()?)
 }
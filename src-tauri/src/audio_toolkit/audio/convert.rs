@@ -0,0 +1,191 @@
+//! Offline conversions for audio files: decoding, downmixing, resampling, trimming and
+//! peak normalization, and writing the result back out as 16-bit WAV.
+
+use anyhow::{anyhow, Result};
+use hound::{WavSpec, WavWriter};
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+
+use super::FrameResampler;
+
+/// Decoded audio with channels interleaved, samples from -1.0 to 1.0.
+#[derive(Clone, Debug)]
+pub struct AudioBuffer {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+impl AudioBuffer {
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels.max(1) as usize
+    }
+
+    pub fn duration_secs(&self) -> f32 {
+        self.frames() as f32 / self.sample_rate as f32
+    }
+
+    /// Averages the channels into one.
+    pub fn to_mono(&self) -> AudioBuffer {
+        let channels = self.channels.max(1) as usize;
+        AudioBuffer {
+            samples: self
+                .samples
+                .chunks(channels)
+                .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+                .collect(),
+            sample_rate: self.sample_rate,
+            channels: 1,
+        }
+    }
+
+    /// Resamples every channel to `sample_rate`.
+    pub fn resample(&self, sample_rate: u32) -> AudioBuffer {
+        if sample_rate == self.sample_rate {
+            return self.clone();
+        }
+
+        let channels = self.channels.max(1) as usize;
+        let frames = self.frames();
+        // The resampler pads its last chunk, cut the output back to the exact length
+        let expected = (frames as u64 * sample_rate as u64 / self.sample_rate as u64) as usize;
+
+        let resampled: Vec<Vec<f32>> = (0..channels)
+            .map(|channel| {
+                let input: Vec<f32> = self
+                    .samples
+                    .iter()
+                    .skip(channel)
+                    .step_by(channels)
+                    .copied()
+                    .collect();
+                let mut output = Vec::with_capacity(expected);
+                let mut resampler = FrameResampler::new(
+                    self.sample_rate as usize,
+                    sample_rate as usize,
+                    Duration::from_millis(30),
+                );
+                resampler.push(&input, |frame: &[f32]| output.extend_from_slice(frame));
+                resampler.finish(|frame: &[f32]| output.extend_from_slice(frame));
+                output.resize(expected, 0.0);
+                output
+            })
+            .collect();
+
+        let mut samples = Vec::with_capacity(expected * channels);
+        for frame in 0..expected {
+            samples.extend(resampled.iter().map(|channel| channel[frame]));
+        }
+        AudioBuffer {
+            samples,
+            sample_rate,
+            channels: self.channels,
+        }
+    }
+
+    /// Keeps the audio from `start_secs` up to `end_secs`, or to the end when `None`.
+    pub fn trim(&self, start_secs: f32, end_secs: Option<f32>) -> Result<AudioBuffer> {
+        let duration = self.duration_secs();
+        let end_secs = end_secs.unwrap_or(duration).min(duration);
+        if start_secs < 0.0 || start_secs >= end_secs {
+            return Err(anyhow!(
+                "Invalid trim range {:.2}s to {:.2}s for {:.2}s of audio",
+                start_secs,
+                end_secs,
+                duration
+            ));
+        }
+
+        let channels = self.channels.max(1) as usize;
+        let to_index = |secs: f32| (secs * self.sample_rate as f32) as usize * channels;
+        Ok(AudioBuffer {
+            samples: self.samples[to_index(start_secs)..to_index(end_secs)].to_vec(),
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+        })
+    }
+
+    /// Scales the audio so its loudest sample reaches `peak_dbfs`, e.g. -1.0. Silence is
+    /// left alone.
+    pub fn normalize(&self, peak_dbfs: f32) -> AudioBuffer {
+        let peak = self.samples.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+        let target = 10f32.powf(peak_dbfs.min(0.0) / 20.0);
+        let gain = if peak > f32::EPSILON {
+            target / peak
+        } else {
+            1.0
+        };
+        AudioBuffer {
+            samples: self.samples.iter().map(|s| s * gain).collect(),
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+        }
+    }
+}
+
+/// Decodes a WAV stream of any sample rate, channel count and sample format.
+pub fn decode_wav<R: Read>(reader: R) -> Result<AudioBuffer> {
+    let mut wav = hound::WavReader::new(reader)?;
+    let spec = wav.spec();
+
+    let samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => wav.samples::<f32>().collect::<Result<_, _>>()?,
+        hound::SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample - 1)) as f32;
+            wav.samples::<i32>()
+                .map(|s| s.map(|v| v as f32 / scale))
+                .collect::<Result<_, _>>()?
+        }
+    };
+
+    Ok(AudioBuffer {
+        samples,
+        sample_rate: spec.sample_rate,
+        channels: spec.channels.max(1),
+    })
+}
+
+/// Writes `audio` as a 16-bit PCM WAV file.
+pub fn write_wav<P: AsRef<Path>>(path: P, audio: &AudioBuffer) -> Result<()> {
+    let spec = WavSpec {
+        channels: audio.channels,
+        sample_rate: audio.sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut writer = WavWriter::create(path.as_ref(), spec)?;
+    for sample in &audio.samples {
+        writer.write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)?;
+    }
+    writer.finalize()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mono_trim_and_normalize() {
+        // one second of stereo at 10 Hz, left at 0.2 and right at 0.4
+        let stereo = AudioBuffer {
+            samples: [0.2, 0.4].repeat(10),
+            sample_rate: 10,
+            channels: 2,
+        };
+
+        let mono = stereo.to_mono();
+        assert_eq!(mono.frames(), 10);
+        assert!((mono.samples[0] - 0.3).abs() < 1e-6);
+
+        let trimmed = stereo.trim(0.2, Some(0.5)).unwrap();
+        assert_eq!(trimmed.frames(), 3);
+        assert_eq!(trimmed.channels, 2);
+        assert!(stereo.trim(0.5, Some(0.2)).is_err());
+
+        let normalized = mono.normalize(0.0);
+        assert!((normalized.samples[0] - 1.0).abs() < 1e-6);
+    }
+}
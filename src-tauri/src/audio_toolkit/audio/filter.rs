@@ -0,0 +1,172 @@
+use std::f32::consts::PI;
+
+/// Lowest and highest high-pass cutoff accepted by `FilterStage`, in Hz.
+pub const MIN_HIGH_PASS_HZ: f32 = 20.0;
+pub const MAX_HIGH_PASS_HZ: f32 = 500.0;
+
+// Pre-emphasis coefficient as usually given for 16 kHz audio. The input runs at the device
+// rate, so it is converted to keep the same corner frequency there.
+const PRE_EMPHASIS_COEFF: f32 = 0.97;
+const PRE_EMPHASIS_RATE: f32 = 16000.0;
+
+// Butterworth response, flat down to the cutoff
+const HIGH_PASS_Q: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FilterSettings {
+    /// Tilts the spectrum towards the consonant range, y[n] = x[n] - a * x[n-1]
+    pub pre_emphasis: bool,
+    /// Cutoff of a second-order high-pass that removes rumble, desk bumps and plosives,
+    /// `None` leaves low frequencies alone
+    pub high_pass_hz: Option<f32>,
+}
+
+/// Second-order high-pass section, coefficients from the Audio EQ Cookbook.
+struct HighPass {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    // transposed direct form II state
+    z1: f32,
+    z2: f32,
+}
+
+impl HighPass {
+    fn new(cutoff_hz: f32, sample_rate: u32) -> Self {
+        // stay clear of Nyquist for very low device rates
+        let cutoff_hz = cutoff_hz.min(sample_rate as f32 * 0.45);
+        let w0 = 2.0 * PI * cutoff_hz / sample_rate as f32;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * HIGH_PASS_Q);
+        let a0 = 1.0 + alpha;
+
+        Self {
+            b0: (1.0 + cos) / 2.0 / a0,
+            b1: -(1.0 + cos) / a0,
+            b2: (1.0 + cos) / 2.0 / a0,
+            a1: -2.0 * cos / a0,
+            a2: (1.0 - alpha) / a0,
+            z1: 0.0,
+            z2: 0.0,
+        }
+    }
+
+    fn process(&mut self, x: f32) -> f32 {
+        let y = self.b0 * x + self.z1;
+        self.z1 = self.b1 * x - self.a1 * y + self.z2;
+        self.z2 = self.b2 * x - self.a2 * y;
+        y
+    }
+}
+
+/// Applies the optional high-pass filter and pre-emphasis to raw input, before gain so the
+/// AGC doesn't react to rumble that is about to be removed.
+pub struct FilterStage {
+    sample_rate: u32,
+    settings: FilterSettings,
+    high_pass: Option<HighPass>,
+    pre_emphasis_coeff: f32,
+    previous: f32,
+}
+
+impl FilterStage {
+    pub fn new(sample_rate: u32, settings: FilterSettings) -> Self {
+        let mut stage = Self {
+            sample_rate,
+            settings,
+            high_pass: None,
+            pre_emphasis_coeff: PRE_EMPHASIS_COEFF.powf(PRE_EMPHASIS_RATE / sample_rate as f32),
+            previous: 0.0,
+        };
+        stage.set_settings(settings);
+        stage
+    }
+
+    pub fn settings(&self) -> FilterSettings {
+        self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: FilterSettings) {
+        self.settings = FilterSettings {
+            pre_emphasis: settings.pre_emphasis,
+            high_pass_hz: settings
+                .high_pass_hz
+                .map(|hz| hz.clamp(MIN_HIGH_PASS_HZ, MAX_HIGH_PASS_HZ)),
+        };
+        self.high_pass = self
+            .settings
+            .high_pass_hz
+            .map(|hz| HighPass::new(hz, self.sample_rate));
+        self.previous = 0.0;
+    }
+
+    /// Whether `process` would leave samples unchanged.
+    pub fn is_bypassed(&self) -> bool {
+        !self.settings.pre_emphasis && self.high_pass.is_none()
+    }
+
+    pub fn process(&mut self, samples: &mut [f32]) {
+        if self.is_bypassed() {
+            return;
+        }
+
+        for sample in samples.iter_mut() {
+            let mut value = *sample;
+            if let Some(high_pass) = self.high_pass.as_mut() {
+                value = high_pass.process(value);
+            }
+            if self.settings.pre_emphasis {
+                let input = value;
+                value -= self.pre_emphasis_coeff * self.previous;
+                self.previous = input;
+            }
+            *sample = value.clamp(-1.0, 1.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, sample_rate: u32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| 0.5 * (2.0 * PI * freq * i as f32 / sample_rate as f32).sin())
+            .collect()
+    }
+
+    fn tail_peak(samples: &[f32]) -> f32 {
+        samples[samples.len() / 2..]
+            .iter()
+            .fold(0.0f32, |max, s| max.max(s.abs()))
+    }
+
+    #[test]
+    fn test_high_pass_removes_rumble_and_keeps_speech() {
+        let settings = FilterSettings {
+            pre_emphasis: false,
+            high_pass_hz: Some(100.0),
+        };
+
+        let mut rumble = sine(20.0, 48000, 48000);
+        FilterStage::new(48000, settings).process(&mut rumble);
+        assert!(tail_peak(&rumble) < 0.05, "20 Hz should be attenuated");
+
+        let mut speech = sine(1000.0, 48000, 48000);
+        FilterStage::new(48000, settings).process(&mut speech);
+        assert!(tail_peak(&speech) > 0.48, "1 kHz should pass");
+    }
+
+    #[test]
+    fn test_bypassed_by_default() {
+        let mut stage = FilterStage::new(16000, FilterSettings::default());
+        assert!(stage.is_bypassed());
+
+        let mut samples = sine(50.0, 16000, 1600);
+        let original = samples.clone();
+        stage.process(&mut samples);
+        assert_eq!(samples, original);
+    }
+}
@@ -0,0 +1,203 @@
+/// Lowest and highest manual gain accepted by `GainStage`.
+pub const MIN_INPUT_GAIN: f32 = 0.1;
+pub const MAX_INPUT_GAIN: f32 = 10.0;
+
+// Envelope level the AGC steers towards, roughly -10 dBFS
+const AGC_TARGET: f32 = 0.3;
+const AGC_MIN_GAIN: f32 = 0.25;
+const AGC_MAX_GAIN: f32 = 10.0;
+// Below this envelope the input is treated as silence and the AGC holds its gain, so room
+// noise isn't pumped up between words
+const AGC_GATE: f32 = 0.002;
+const ENVELOPE_ATTACK_SECS: f32 = 0.005;
+const ENVELOPE_RELEASE_SECS: f32 = 0.3;
+const GAIN_DECREASE_SECS: f32 = 0.05;
+const GAIN_INCREASE_SECS: f32 = 1.5;
+
+// Speech RMS the calibration aims for, about -20 dBFS
+const CALIBRATION_TARGET_RMS: f32 = 0.1;
+// Leave room so the loudest peak in the calibration take doesn't clip
+const CALIBRATION_MAX_PEAK: f32 = 0.9;
+const CALIBRATION_FRAME_SECS: f32 = 0.02;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GainSettings {
+    /// Fixed multiplier applied to every sample
+    pub gain: f32,
+    /// Automatic gain control on top of the fixed gain
+    pub agc: bool,
+}
+
+impl Default for GainSettings {
+    fn default() -> Self {
+        Self {
+            gain: 1.0,
+            agc: false,
+        }
+    }
+}
+
+fn time_constant(secs: f32, sample_rate: u32) -> f32 {
+    (-1.0 / (secs * sample_rate as f32)).exp()
+}
+
+/// Applies the manual gain and optional AGC to raw input, limiting the result to -1.0..=1.0.
+pub struct GainStage {
+    settings: GainSettings,
+    envelope: f32,
+    agc_gain: f32,
+    envelope_attack: f32,
+    envelope_release: f32,
+    gain_decrease: f32,
+    gain_increase: f32,
+}
+
+impl GainStage {
+    pub fn new(sample_rate: u32, settings: GainSettings) -> Self {
+        let mut stage = Self {
+            settings,
+            envelope: 0.0,
+            agc_gain: 1.0,
+            envelope_attack: time_constant(ENVELOPE_ATTACK_SECS, sample_rate),
+            envelope_release: time_constant(ENVELOPE_RELEASE_SECS, sample_rate),
+            gain_decrease: time_constant(GAIN_DECREASE_SECS, sample_rate),
+            gain_increase: time_constant(GAIN_INCREASE_SECS, sample_rate),
+        };
+        stage.set_settings(settings);
+        stage
+    }
+
+    pub fn settings(&self) -> GainSettings {
+        self.settings
+    }
+
+    pub fn set_settings(&mut self, settings: GainSettings) {
+        self.settings = GainSettings {
+            gain: settings.gain.clamp(MIN_INPUT_GAIN, MAX_INPUT_GAIN),
+            agc: settings.agc,
+        };
+        if !self.settings.agc {
+            self.envelope = 0.0;
+            self.agc_gain = 1.0;
+        }
+    }
+
+    /// Whether `process` would leave samples unchanged.
+    pub fn is_bypassed(&self) -> bool {
+        !self.settings.agc && self.settings.gain == 1.0
+    }
+
+    pub fn process(&mut self, samples: &mut [f32]) {
+        if self.is_bypassed() {
+            return;
+        }
+
+        for sample in samples.iter_mut() {
+            let mut value = *sample * self.settings.gain;
+
+            if self.settings.agc {
+                let level = value.abs();
+                let coeff = if level > self.envelope {
+                    self.envelope_attack
+                } else {
+                    self.envelope_release
+                };
+                self.envelope = coeff * self.envelope + (1.0 - coeff) * level;
+
+                if self.envelope > AGC_GATE {
+                    let desired = (AGC_TARGET / self.envelope).clamp(AGC_MIN_GAIN, AGC_MAX_GAIN);
+                    let coeff = if desired < self.agc_gain {
+                        self.gain_decrease
+                    } else {
+                        self.gain_increase
+                    };
+                    self.agc_gain = coeff * self.agc_gain + (1.0 - coeff) * desired;
+                }
+                value *= self.agc_gain;
+            }
+
+            *sample = value.clamp(-1.0, 1.0);
+        }
+    }
+}
+
+/// Levels measured from a calibration take and the manual gain suggested for it.
+#[derive(Debug, Clone)]
+pub struct GainRecommendation {
+    pub gain: f32,
+    /// RMS of the louder (speech) part of the take
+    pub speech_rms: f32,
+    /// RMS of the quieter (background) part of the take
+    pub noise_floor: f32,
+    pub peak: f32,
+}
+
+/// Suggests a manual gain that brings speech in `samples` to a comfortable level without
+/// clipping. Returns `None` when the take is silent.
+pub fn recommend_gain(samples: &[f32], sample_rate: u32) -> Option<GainRecommendation> {
+    let frame_len = ((sample_rate as f32 * CALIBRATION_FRAME_SECS) as usize).max(1);
+    let mut frame_rms: Vec<f32> = samples
+        .chunks(frame_len)
+        .map(|frame| (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt())
+        .collect();
+    if frame_rms.is_empty() {
+        return None;
+    }
+    frame_rms.sort_by(|a, b| a.total_cmp(b));
+
+    let percentile = |p: f32| frame_rms[((frame_rms.len() - 1) as f32 * p) as usize];
+    let speech_rms = percentile(0.9);
+    let noise_floor = percentile(0.1);
+    let peak = samples.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+
+    if speech_rms < 1e-4 {
+        return None;
+    }
+
+    let mut gain = CALIBRATION_TARGET_RMS / speech_rms;
+    if peak > 0.0 {
+        gain = gain.min(CALIBRATION_MAX_PEAK / peak);
+    }
+
+    Some(GainRecommendation {
+        gain: gain.clamp(MIN_INPUT_GAIN, MAX_INPUT_GAIN),
+        speech_rms,
+        noise_floor,
+        peak,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_agc_raises_quiet_input() {
+        let mut stage = GainStage::new(
+            16000,
+            GainSettings {
+                gain: 1.0,
+                agc: true,
+            },
+        );
+        let mut samples: Vec<f32> = (0..48000).map(|i| 0.02 * (i as f32 * 0.05).sin()).collect();
+        stage.process(&mut samples);
+
+        let tail_peak = samples[40000..]
+            .iter()
+            .fold(0.0f32, |max, s| max.max(s.abs()));
+        assert!(tail_peak > 0.1, "quiet input should be boosted");
+        assert!(samples.iter().all(|s| s.abs() <= 1.0));
+    }
+
+    #[test]
+    fn test_recommend_gain_for_quiet_take() {
+        let samples: Vec<f32> = (0..48000)
+            .map(|i| 0.025 * (i as f32 * 0.05).sin())
+            .collect();
+        let recommendation = recommend_gain(&samples, 16000).expect("take is not silent");
+        assert!(recommendation.gain > 3.0 && recommendation.gain < 6.0);
+
+        assert!(recommend_gain(&[0.0; 16000], 16000).is_none());
+    }
+}
@@ -0,0 +1,94 @@
+use crate::audio_toolkit::audio::AudioVisualiser;
+
+const WINDOW_SIZE: usize = 512;
+const BANDS: usize = 8;
+
+/// One loudness reading covering the samples fed since the previous one.
+#[derive(Debug, Clone)]
+pub struct AudioLevels {
+    /// Root mean square amplitude, 0.0 to 1.0
+    pub rms: f32,
+    /// Largest absolute sample, 0.0 to 1.0
+    pub peak: f32,
+    /// Normalized energy per frequency band from low to high, 0.0 to 1.0
+    pub bands: Vec<f32>,
+}
+
+/// Turns raw input samples into periodic `AudioLevels` readings for live meters.
+pub struct LevelMeter {
+    spectrum: AudioVisualiser,
+    bands: Vec<f32>,
+    sum_squares: f64,
+    peak: f32,
+    count: usize,
+    samples_per_reading: usize,
+}
+
+impl LevelMeter {
+    pub fn new(sample_rate: u32, readings_per_second: u32) -> Self {
+        Self {
+            spectrum: AudioVisualiser::new(sample_rate, WINDOW_SIZE, BANDS, 80.0, 8000.0),
+            bands: vec![0.0; BANDS],
+            sum_squares: 0.0,
+            peak: 0.0,
+            count: 0,
+            samples_per_reading: (sample_rate / readings_per_second.max(1)).max(1) as usize,
+        }
+    }
+
+    /// Accumulates `samples` and returns a reading once enough audio for one period arrived.
+    pub fn feed(&mut self, samples: &[f32]) -> Option<AudioLevels> {
+        for &sample in samples {
+            self.sum_squares += (sample as f64) * (sample as f64);
+            self.peak = self.peak.max(sample.abs());
+        }
+        self.count += samples.len();
+
+        if let Some(bands) = self.spectrum.feed(samples) {
+            self.bands = bands;
+        }
+
+        if self.count < self.samples_per_reading {
+            return None;
+        }
+
+        let levels = AudioLevels {
+            rms: ((self.sum_squares / self.count as f64).sqrt() as f32).min(1.0),
+            peak: self.peak.min(1.0),
+            bands: self.bands.clone(),
+        };
+        self.sum_squares = 0.0;
+        self.peak = 0.0;
+        self.count = 0;
+
+        Some(levels)
+    }
+
+    pub fn reset(&mut self) {
+        self.spectrum.reset();
+        self.bands.fill(0.0);
+        self.sum_squares = 0.0;
+        self.peak = 0.0;
+        self.count = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reading_after_one_period() {
+        let mut meter = LevelMeter::new(16000, 10);
+        assert!(meter.feed(&[0.5; 1000]).is_none());
+
+        let levels = meter
+            .feed(&[-0.5; 600])
+            .expect("one period of audio was fed");
+        assert!((levels.rms - 0.5).abs() < 1e-6);
+        assert!((levels.peak - 0.5).abs() < 1e-6);
+        assert_eq!(levels.bands.len(), BANDS);
+
+        assert!(meter.feed(&[0.0; 100]).is_none());
+    }
+}
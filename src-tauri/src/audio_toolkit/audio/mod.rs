@@ -1,12 +1,22 @@
 // Re-export all audio components
+mod convert;
 mod device;
+mod filter;
+mod gain;
+mod level_meter;
 mod recorder;
 mod resampler;
 mod utils;
 mod visualizer;
 
+pub use convert::{decode_wav, write_wav, AudioBuffer};
 pub use device::{list_input_devices, list_output_devices, CpalDeviceInfo};
-pub use recorder::AudioRecorder;
+pub use filter::{FilterSettings, FilterStage, MAX_HIGH_PASS_HZ, MIN_HIGH_PASS_HZ};
+pub use gain::{
+    recommend_gain, GainRecommendation, GainSettings, GainStage, MAX_INPUT_GAIN, MIN_INPUT_GAIN,
+};
+pub use level_meter::{AudioLevels, LevelMeter};
+pub use recorder::{AudioChunk, AudioRecorder, Endpointing};
 pub use resampler::FrameResampler;
-pub use utils::save_wav_file;
+pub use utils::{encode_wav, save_wav_file};
 pub use visualizer::AudioVisualiser;
@@ -1,6 +1,10 @@
 use std::{
     io::Error,
-    sync::{mpsc, Arc, Mutex},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        Arc, Mutex,
+    },
     time::Duration,
 };
 
@@ -10,7 +14,10 @@ use cpal::{
 };
 
 use crate::audio_toolkit::{
-    audio::{AudioVisualiser, FrameResampler},
+    audio::{
+        AudioLevels, AudioVisualiser, FilterSettings, FilterStage, FrameResampler, GainSettings,
+        GainStage, LevelMeter,
+    },
     constants,
     vad::{self, VadFrame},
     VoiceActivityDetector,
@@ -19,16 +26,73 @@ use crate::audio_toolkit::{
 enum Cmd {
     Start,
     Stop(mpsc::Sender<Vec<f32>>),
+    Pause,
+    Resume,
+    SwitchDevice(Device, mpsc::Sender<Result<(), String>>),
+    SetLevelsRate(u32),
+    SetGain(GainSettings),
+    SetFilter(FilterSettings),
+    SetMaxUtterance(Option<Duration>),
+    SetEndpointing(Endpointing),
+    Capture(Duration, mpsc::Sender<(Vec<f32>, u32)>),
     Shutdown,
 }
 
+/// Emit a partial chunk every ~1 second of speech (16,000 samples at 16kHz)
+const CHUNK_SIZE: usize = 16_000;
+/// Length of the frames the VAD classifies
+const FRAME: Duration = Duration::from_millis(30);
+/// Audio an utterance cut off at the maximum duration shares with the next one, 0.5 s, so a
+/// word split by the cut is heard whole by one of them
+const OVERLAP_SAMPLES: usize = 8_000;
+
+/// How much silence after the VAD hangover ends an utterance.
+#[derive(Clone, Debug)]
+pub struct Endpointing {
+    pub pause: Duration,
+    /// A shorter pause that ends an utterance once the caller found what was said so far
+    /// sounds finished, and stored one more than the utterance's index in the counter.
+    pub finished_pause: Option<(Duration, Arc<AtomicUsize>)>,
+}
+
+impl Default for Endpointing {
+    fn default() -> Self {
+        Self {
+            pause: Duration::from_millis(300),
+            finished_pause: None,
+        }
+    }
+}
+
+/// Audio of one utterance handed to the chunk callback for live transcription. Partial chunks
+/// hold the utterance so far and are followed by a final one once it ends, either at a pause
+/// or when it hits the maximum utterance duration.
+#[derive(Clone, Debug)]
+pub struct AudioChunk {
+    pub samples: Vec<f32>,
+    /// Position of the utterance in the recording, starting at 0
+    pub utterance: usize,
+    /// Offset of the utterance's first sample in the recording
+    pub start: usize,
+    /// Samples at the start that also end the previous utterance, which was cut off at the
+    /// maximum duration. Their text is in both transcriptions.
+    pub overlap: usize,
+    pub is_final: bool,
+}
+
 pub struct AudioRecorder {
     device: Option<Device>,
     cmd_tx: Option<mpsc::Sender<Cmd>>,
     worker_handle: Option<std::thread::JoinHandle<()>>,
     vad: Option<Arc<Mutex<Box<dyn vad::VoiceActivityDetector>>>>,
     level_cb: Option<Arc<dyn Fn(Vec<f32>) + Send + Sync + 'static>>,
-    chunk_cb: Option<Arc<dyn Fn(Vec<f32>) + Send + Sync + 'static>>,
+    chunk_cb: Option<Arc<dyn Fn(AudioChunk) + Send + Sync + 'static>>,
+    levels_cb: Option<Arc<dyn Fn(AudioLevels) + Send + Sync + 'static>>,
+    levels_rate: u32,
+    gain: GainSettings,
+    filter: FilterSettings,
+    max_utterance: Option<Duration>,
+    endpointing: Endpointing,
 }
 
 impl AudioRecorder {
@@ -40,6 +104,12 @@ impl AudioRecorder {
             vad: None,
             level_cb: None,
             chunk_cb: None,
+            levels_cb: None,
+            levels_rate: 0,
+            gain: GainSettings::default(),
+            filter: FilterSettings::default(),
+            max_utterance: None,
+            endpointing: Endpointing::default(),
         })
     }
 
@@ -58,78 +128,134 @@ impl AudioRecorder {
 
     pub fn with_chunk_callback<F>(mut self, cb: F) -> Self
     where
-        F: Fn(Vec<f32>) + Send + Sync + 'static,
+        F: Fn(AudioChunk) + Send + Sync + 'static,
     {
         self.chunk_cb = Some(Arc::new(cb));
         self
     }
 
+    /// Reports RMS, peak and spectrum bands `readings_per_second` times per second while
+    /// recording, 0 turns the readings off.
+    pub fn with_audio_levels_callback<F>(mut self, readings_per_second: u32, cb: F) -> Self
+    where
+        F: Fn(AudioLevels) + Send + Sync + 'static,
+    {
+        self.levels_cb = Some(Arc::new(cb));
+        self.levels_rate = readings_per_second;
+        self
+    }
+
+    /// Changes how often the audio levels callback fires, 0 turns it off.
+    pub fn set_audio_levels_rate(&mut self, readings_per_second: u32) {
+        self.levels_rate = readings_per_second;
+        if let Some(tx) = &self.cmd_tx {
+            let _ = tx.send(Cmd::SetLevelsRate(readings_per_second));
+        }
+    }
+
+    pub fn with_gain(mut self, gain: GainSettings) -> Self {
+        self.gain = gain;
+        self
+    }
+
+    /// Changes the input gain and AGC, taking effect immediately if the recorder is open.
+    pub fn set_gain(&mut self, gain: GainSettings) {
+        self.gain = gain;
+        if let Some(tx) = &self.cmd_tx {
+            let _ = tx.send(Cmd::SetGain(gain));
+        }
+    }
+
+    pub fn with_filter(mut self, filter: FilterSettings) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Changes the high-pass filter and pre-emphasis, taking effect immediately if the
+    /// recorder is open.
+    pub fn set_filter(&mut self, filter: FilterSettings) {
+        self.filter = filter;
+        if let Some(tx) = &self.cmd_tx {
+            let _ = tx.send(Cmd::SetFilter(filter));
+        }
+    }
+
+    /// Closes an utterance after `max` of speech even when the speaker never pauses, so
+    /// continuous speech, humming or music still produce chunks. `None` waits for a pause.
+    pub fn with_max_utterance(mut self, max: Option<Duration>) -> Self {
+        self.max_utterance = max;
+        self
+    }
+
+    /// Changes the maximum utterance duration, taking effect immediately if the recorder is
+    /// open.
+    pub fn set_max_utterance(&mut self, max: Option<Duration>) {
+        self.max_utterance = max;
+        if let Some(tx) = &self.cmd_tx {
+            let _ = tx.send(Cmd::SetMaxUtterance(max));
+        }
+    }
+
+    /// Sets the silence that ends an utterance.
+    pub fn with_endpointing(mut self, endpointing: Endpointing) -> Self {
+        self.endpointing = endpointing;
+        self
+    }
+
+    /// Changes the silence that ends an utterance, taking effect immediately if the recorder
+    /// is open.
+    pub fn set_endpointing(&mut self, endpointing: Endpointing) {
+        self.endpointing = endpointing.clone();
+        if let Some(tx) = &self.cmd_tx {
+            let _ = tx.send(Cmd::SetEndpointing(endpointing));
+        }
+    }
+
+    /// Collects `duration` of input as delivered by the device, before filtering, gain and
+    /// VAD, without affecting a recording in progress. The receiver gets the samples and their sample rate.
+    pub fn capture(
+        &self,
+        duration: Duration,
+    ) -> Result<mpsc::Receiver<(Vec<f32>, u32)>, Box<dyn std::error::Error>> {
+        let tx = self.cmd_tx.as_ref().ok_or("recorder is not open")?;
+        let (reply_tx, reply_rx) = mpsc::channel();
+        tx.send(Cmd::Capture(duration, reply_tx))?;
+        Ok(reply_rx)
+    }
+
     pub fn open(&mut self, device: Option<Device>) -> Result<(), Box<dyn std::error::Error>> {
         if self.worker_handle.is_some() {
             return Ok(()); // already open
         }
 
-        let (sample_tx, sample_rx) = mpsc::channel::<Vec<f32>>();
         let (cmd_tx, cmd_rx) = mpsc::channel::<Cmd>();
 
-        let host = crate::audio_toolkit::get_cpal_host();
-        let device = match device {
-            Some(dev) => dev,
-            None => host
-                .default_input_device()
-                .ok_or_else(|| Error::new(std::io::ErrorKind::NotFound, "No input device found"))?,
-        };
+        let device = Self::resolve_device(device)?;
 
         let thread_device = device.clone();
         let vad = self.vad.clone();
         // Move the optional level callback into the worker thread
         let level_cb = self.level_cb.clone();
         let chunk_cb = self.chunk_cb.clone();
+        let levels_cb = self.levels_cb.clone();
+        let levels_rate = self.levels_rate;
+        // the worker applies the gain and filter along with the first batch of commands
+        cmd_tx.send(Cmd::SetGain(self.gain))?;
+        cmd_tx.send(Cmd::SetFilter(self.filter))?;
+        cmd_tx.send(Cmd::SetMaxUtterance(self.max_utterance))?;
+        cmd_tx.send(Cmd::SetEndpointing(self.endpointing.clone()))?;
 
         let worker = std::thread::spawn(move || {
-            let config = AudioRecorder::get_preferred_config(&thread_device)
-                .expect("failed to fetch preferred config");
-
-            let sample_rate = config.sample_rate().0;
-            let channels = config.channels() as usize;
-
-            log::info!(
-                "Using device: {:?}\nSample rate: {}\nChannels: {}\nFormat: {:?}",
-                thread_device.name(),
-                sample_rate,
-                channels,
-                config.sample_format()
+            // the consumer owns the input stream so it can replace it on a device switch
+            run_consumer(
+                thread_device,
+                vad,
+                cmd_rx,
+                level_cb,
+                chunk_cb,
+                levels_cb,
+                levels_rate,
             );
-
-            let stream = match config.sample_format() {
-                cpal::SampleFormat::U8 => {
-                    AudioRecorder::build_stream::<u8>(&thread_device, &config, sample_tx, channels)
-                        .unwrap()
-                }
-                cpal::SampleFormat::I8 => {
-                    AudioRecorder::build_stream::<i8>(&thread_device, &config, sample_tx, channels)
-                        .unwrap()
-                }
-                cpal::SampleFormat::I16 => {
-                    AudioRecorder::build_stream::<i16>(&thread_device, &config, sample_tx, channels)
-                        .unwrap()
-                }
-                cpal::SampleFormat::I32 => {
-                    AudioRecorder::build_stream::<i32>(&thread_device, &config, sample_tx, channels)
-                        .unwrap()
-                }
-                cpal::SampleFormat::F32 => {
-                    AudioRecorder::build_stream::<f32>(&thread_device, &config, sample_tx, channels)
-                        .unwrap()
-                }
-                _ => panic!("unsupported sample format"),
-            };
-
-            stream.play().expect("failed to start stream");
-
-            // keep the stream alive while we process samples
-            run_consumer(sample_rate, vad, sample_rx, cmd_rx, level_cb, chunk_cb);
-            // stream is dropped here, after run_consumer returns
         });
 
         self.device = Some(device);
@@ -154,6 +280,44 @@ impl AudioRecorder {
         Ok(resp_rx.recv()?) // wait for the samples
     }
 
+    /// Stops taking in audio without ending the recording. The utterance in progress is
+    /// closed, and `resume` continues the recording where it left off.
+    pub fn pause(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(tx) = &self.cmd_tx {
+            tx.send(Cmd::Pause)?;
+        }
+        Ok(())
+    }
+
+    pub fn resume(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(tx) = &self.cmd_tx {
+            tx.send(Cmd::Resume)?;
+        }
+        Ok(())
+    }
+
+    /// Moves an open recorder to another input device without ending the current recording.
+    /// Audio captured so far is kept and the resampler is reconfigured for the new device.
+    pub fn switch_device(
+        &mut self,
+        device: Option<Device>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let tx = self.cmd_tx.as_ref().ok_or("recorder is not open")?;
+        let device = Self::resolve_device(device)?;
+
+        let (reply_tx, reply_rx) = mpsc::channel();
+        tx.send(Cmd::SwitchDevice(device.clone(), reply_tx))?;
+        reply_rx.recv()??;
+
+        self.device = Some(device);
+        Ok(())
+    }
+
+    /// Name of the device the recorder is capturing from, if it is open.
+    pub fn device_name(&self) -> Option<String> {
+        self.device.as_ref().and_then(|device| device.name().ok())
+    }
+
     pub fn close(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         if let Some(tx) = self.cmd_tx.take() {
             let _ = tx.send(Cmd::Shutdown);
@@ -165,6 +329,58 @@ impl AudioRecorder {
         Ok(())
     }
 
+    fn resolve_device(device: Option<Device>) -> Result<Device, Error> {
+        match device {
+            Some(dev) => Ok(dev),
+            None => crate::audio_toolkit::get_cpal_host()
+                .default_input_device()
+                .ok_or_else(|| Error::new(std::io::ErrorKind::NotFound, "No input device found")),
+        }
+    }
+
+    /// Starts capturing from `device`, returning the running stream, the receiver for its
+    /// mono samples and the device sample rate.
+    fn open_stream(
+        device: &Device,
+    ) -> Result<(cpal::Stream, mpsc::Receiver<Vec<f32>>, u32), Box<dyn std::error::Error>> {
+        let (sample_tx, sample_rx) = mpsc::channel::<Vec<f32>>();
+        let config = AudioRecorder::get_preferred_config(device)?;
+
+        let sample_rate = config.sample_rate().0;
+        let channels = config.channels() as usize;
+
+        log::info!(
+            "Using device: {:?}\nSample rate: {}\nChannels: {}\nFormat: {:?}",
+            device.name(),
+            sample_rate,
+            channels,
+            config.sample_format()
+        );
+
+        let stream = match config.sample_format() {
+            cpal::SampleFormat::U8 => {
+                AudioRecorder::build_stream::<u8>(device, &config, sample_tx, channels)?
+            }
+            cpal::SampleFormat::I8 => {
+                AudioRecorder::build_stream::<i8>(device, &config, sample_tx, channels)?
+            }
+            cpal::SampleFormat::I16 => {
+                AudioRecorder::build_stream::<i16>(device, &config, sample_tx, channels)?
+            }
+            cpal::SampleFormat::I32 => {
+                AudioRecorder::build_stream::<i32>(device, &config, sample_tx, channels)?
+            }
+            cpal::SampleFormat::F32 => {
+                AudioRecorder::build_stream::<f32>(device, &config, sample_tx, channels)?
+            }
+            format => return Err(format!("unsupported sample format {:?}", format).into()),
+        };
+
+        stream.play()?;
+
+        Ok((stream, sample_rx, sample_rate))
+    }
+
     fn build_stream<T>(
         device: &cpal::Device,
         config: &cpal::SupportedStreamConfig,
@@ -250,16 +466,149 @@ impl AudioRecorder {
     }
 }
 
+/// Splits a recording into utterances for the live transcription. An utterance ends once the
+/// VAD hears a pause, or after `max_samples` of speech when it never does.
+#[derive(Default)]
+struct Utterances {
+    endpointing: Endpointing,
+    /// Offset into the recording where the current utterance starts
+    start: usize,
+    /// Samples the current utterance shares with the previous one
+    overlap: usize,
+    index: usize,
+    samples_since_chunk: usize,
+    silent_frames: usize,
+    max_samples: Option<usize>,
+}
+
+impl Utterances {
+    fn reset(&mut self) {
+        if let Some((_, finished)) = &self.endpointing.finished_pause {
+            finished.store(0, Ordering::Relaxed);
+        }
+        *self = Self {
+            endpointing: self.endpointing.clone(),
+            max_samples: self.max_samples,
+            ..Self::default()
+        };
+    }
+
+    /// Silent frames that end the current utterance.
+    fn pause_frames(&self) -> usize {
+        let pause = match &self.endpointing.finished_pause {
+            Some((pause, finished)) if finished.load(Ordering::Relaxed) == self.index + 1 => *pause,
+            _ => self.endpointing.pause,
+        };
+        (pause.as_millis() / FRAME.as_millis()).max(1) as usize
+    }
+
+    /// Ends the current utterance where the recording stands, returning its final chunk
+    /// unless nothing new was said in it.
+    fn close(&mut self, recording: &[f32]) -> Option<AudioChunk> {
+        let chunk = (recording.len() - self.start > self.overlap).then(|| AudioChunk {
+            samples: recording[self.start..].to_vec(),
+            utterance: self.index,
+            start: self.start,
+            overlap: self.overlap,
+            is_final: true,
+        });
+        if chunk.is_some() {
+            self.index += 1;
+        }
+        self.start = recording.len();
+        self.overlap = 0;
+        self.samples_since_chunk = 0;
+        self.silent_frames = 0;
+        chunk
+    }
+
+    /// Takes the recording so far after a frame added `added` samples to it, and returns the
+    /// chunk to transcribe if one is due.
+    fn feed(&mut self, recording: &[f32], added: usize) -> Option<AudioChunk> {
+        if added == 0 {
+            self.silent_frames += 1;
+        } else {
+            self.silent_frames = 0;
+            self.samples_since_chunk += added;
+        }
+
+        let len = recording.len() - self.start;
+        let pause_frames = self.pause_frames();
+        if self.silent_frames >= pause_frames && self.overlap > 0 && len == self.overlap {
+            // Nothing was said after the cut, the overlap was transcribed already
+            self.start = recording.len();
+            self.overlap = 0;
+            return None;
+        }
+        let paused = self.silent_frames >= pause_frames && len > 0;
+        let too_long = self.max_samples.is_some_and(|max| len >= max);
+
+        if paused || too_long {
+            if too_long {
+                log::debug!(
+                    "Utterance {} reached the maximum duration, closing it",
+                    self.index
+                );
+            }
+            let chunk = AudioChunk {
+                samples: recording[self.start..].to_vec(),
+                utterance: self.index,
+                start: self.start,
+                overlap: self.overlap,
+                is_final: true,
+            };
+            // A pause is a clean cut, the speaker is mid-word when the duration runs out
+            self.overlap = if paused {
+                0
+            } else {
+                OVERLAP_SAMPLES.min(len.saturating_sub(1))
+            };
+            self.start = recording.len() - self.overlap;
+            self.index += 1;
+            self.samples_since_chunk = 0;
+            Some(chunk)
+        } else if self.samples_since_chunk >= CHUNK_SIZE
+            // Once the speaker pauses, the words since the last chunk are transcribed
+            // so the caller can tell whether the utterance sounds finished
+            || (self.endpointing.finished_pause.is_some()
+                && self.silent_frames == 1
+                && self.samples_since_chunk > 0)
+        {
+            self.samples_since_chunk = 0;
+            Some(AudioChunk {
+                samples: recording[self.start..].to_vec(),
+                utterance: self.index,
+                start: self.start,
+                overlap: self.overlap,
+                is_final: false,
+            })
+        } else {
+            None
+        }
+    }
+}
+
 fn run_consumer(
-    in_sample_rate: u32,
+    mut device: Device,
     vad: Option<Arc<Mutex<Box<dyn vad::VoiceActivityDetector>>>>,
-    sample_rx: mpsc::Receiver<Vec<f32>>,
     cmd_rx: mpsc::Receiver<Cmd>,
     level_cb: Option<Arc<dyn Fn(Vec<f32>) + Send + Sync + 'static>>,
-    chunk_cb: Option<Arc<dyn Fn(Vec<f32>) + Send + Sync + 'static>>,
+    chunk_cb: Option<Arc<dyn Fn(AudioChunk) + Send + Sync + 'static>>,
+    levels_cb: Option<Arc<dyn Fn(AudioLevels) + Send + Sync + 'static>>,
+    mut levels_rate: u32,
 ) {
+    // keep the stream alive while we process samples; it is dropped when we return
+    let (stream, mut sample_rx, mut sample_rate) = match AudioRecorder::open_stream(&device) {
+        Ok(opened) => opened,
+        Err(e) => {
+            log::error!("Failed to open input stream: {}", e);
+            return;
+        }
+    };
+    let mut _stream = Some(stream);
+
     let mut frame_resampler = FrameResampler::new(
-        in_sample_rate as usize,
+        sample_rate as usize,
         constants::WHISPER_SAMPLE_RATE as usize,
         Duration::from_millis(30),
     );
@@ -268,28 +617,37 @@ fn run_consumer(
     let mut recording = false;
 
     // For periodic chunk emission during recording
-    // Emit chunks every ~1 second (16,000 samples at 16kHz)
-    const CHUNK_SIZE: usize = 16_000;
-    let mut samples_since_last_chunk = 0;
+    let mut utterances = Utterances::default();
 
     // ---------- spectrum visualisation setup ---------------------------- //
     const BUCKETS: usize = 16;
     const WINDOW_SIZE: usize = 512;
     let mut visualizer = AudioVisualiser::new(
-        in_sample_rate,
+        sample_rate,
         WINDOW_SIZE,
         BUCKETS,
         400.0,  // vocal_min_hz
         4000.0, // vocal_max_hz
     );
 
+    // ---------- audio level telemetry ----------------------------------- //
+    let new_level_meter = |sample_rate: u32, rate: u32| {
+        (levels_cb.is_some() && rate > 0).then(|| LevelMeter::new(sample_rate, rate))
+    };
+    let mut level_meter = new_level_meter(sample_rate, levels_rate);
+
+    // ---------- input filter, gain and calibration capture -------------- //
+    let mut filter_stage = FilterStage::new(sample_rate, FilterSettings::default());
+    let mut gain_stage = GainStage::new(sample_rate, GainSettings::default());
+    let mut capture: Option<(usize, Vec<f32>, mpsc::Sender<(Vec<f32>, u32)>)> = None;
+
     fn handle_frame(
         samples: &[f32],
         recording: bool,
         vad: &Option<Arc<Mutex<Box<dyn vad::VoiceActivityDetector>>>>,
         out_buf: &mut Vec<f32>,
-        samples_since_last_chunk: &mut usize,
-        chunk_cb: &Option<Arc<dyn Fn(Vec<f32>) + Send + Sync + 'static>>,
+        utterances: &mut Utterances,
+        chunk_cb: &Option<Arc<dyn Fn(AudioChunk) + Send + Sync + 'static>>,
     ) -> usize {
         if !recording {
             return 0;
@@ -311,17 +669,10 @@ fn run_consumer(
             added_samples = samples.len();
         }
 
-        // Check if we should emit a chunk
-        if added_samples > 0 {
-            *samples_since_last_chunk += added_samples;
-
-            // If we've accumulated enough samples, emit a chunk for real-time transcription
-            if *samples_since_last_chunk >= CHUNK_SIZE {
-                if let Some(cb) = chunk_cb {
-                    // Clone the accumulated samples and emit them
-                    cb(out_buf.clone());
-                }
-                *samples_since_last_chunk = 0;
+        // Check if we should emit a chunk for real-time transcription
+        if let Some(cb) = chunk_cb {
+            if let Some(chunk) = utterances.feed(out_buf, added_samples) {
+                cb(chunk);
             }
         }
 
@@ -329,38 +680,62 @@ fn run_consumer(
     }
 
     loop {
-        let raw = match sample_rx.recv() {
-            Ok(s) => s,
-            Err(_) => break, // stream closed
-        };
+        // time out so commands are still handled when a device stops delivering audio
+        match sample_rx.recv_timeout(Duration::from_millis(100)) {
+            Ok(mut raw) => {
+                if let Some((wanted, buf, _)) = capture.as_mut() {
+                    buf.extend_from_slice(&raw);
+                    if buf.len() >= *wanted {
+                        let (_, buf, reply_tx) = capture.take().unwrap();
+                        let _ = reply_tx.send((buf, sample_rate));
+                    }
+                }
+
+                filter_stage.process(&mut raw);
+                gain_stage.process(&mut raw);
 
-        // ---------- spectrum processing ---------------------------------- //
-        if let Some(buckets) = visualizer.feed(&raw) {
-            if let Some(cb) = &level_cb {
-                cb(buckets);
+                // ---------- spectrum processing ---------------------------------- //
+                if let Some(buckets) = visualizer.feed(&raw) {
+                    if let Some(cb) = &level_cb {
+                        cb(buckets);
+                    }
+                }
+
+                if recording {
+                    if let (Some(meter), Some(cb)) = (level_meter.as_mut(), &levels_cb) {
+                        if let Some(levels) = meter.feed(&raw) {
+                            cb(levels);
+                        }
+                    }
+                }
+
+                // ---------- existing pipeline ------------------------------------ //
+                frame_resampler.push(&raw, &mut |frame: &[f32]| {
+                    handle_frame(
+                        frame,
+                        recording,
+                        &vad,
+                        &mut processed_samples,
+                        &mut utterances,
+                        &chunk_cb,
+                    );
+                });
             }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break, // stream closed
         }
 
-        // ---------- existing pipeline ------------------------------------ //
-        frame_resampler.push(&raw, &mut |frame: &[f32]| {
-            handle_frame(
-                frame,
-                recording,
-                &vad,
-                &mut processed_samples,
-                &mut samples_since_last_chunk,
-                &chunk_cb,
-            );
-        });
-
         // non-blocking check for a command
         while let Ok(cmd) = cmd_rx.try_recv() {
             match cmd {
                 Cmd::Start => {
                     processed_samples.clear();
                     recording = true;
-                    samples_since_last_chunk = 0; // Reset chunk counter
+                    utterances.reset(); // Reset chunk counter
                     visualizer.reset(); // Reset visualization buffer
+                    if let Some(meter) = level_meter.as_mut() {
+                        meter.reset();
+                    }
                     if let Some(v) = &vad {
                         v.lock().unwrap().reset();
                     }
@@ -376,13 +751,109 @@ fn run_consumer(
                             true,
                             &vad,
                             &mut processed_samples,
-                            &mut 0, // Don't track chunks in final processing
-                            &None,  // Don't emit chunks when finishing
+                            &mut utterances,
+                            &None, // Don't emit chunks when finishing
                         );
                     });
 
                     let _ = reply_tx.send(std::mem::take(&mut processed_samples));
                 }
+                Cmd::Pause if recording => {
+                    // Frames still in the resampler were heard before the pause
+                    frame_resampler.finish(&mut |frame: &[f32]| {
+                        handle_frame(
+                            frame,
+                            true,
+                            &vad,
+                            &mut processed_samples,
+                            &mut utterances,
+                            &chunk_cb,
+                        );
+                    });
+                    recording = false;
+                    if let (Some(cb), Some(chunk)) =
+                        (&chunk_cb, utterances.close(&processed_samples))
+                    {
+                        cb(chunk);
+                    }
+                }
+                Cmd::Resume if !recording => {
+                    recording = true;
+                    if let Some(v) = &vad {
+                        v.lock().unwrap().reset();
+                    }
+                }
+                Cmd::Pause | Cmd::Resume => {}
+                Cmd::SwitchDevice(new_device, reply_tx) => {
+                    // Samples still buffered in the resampler belong to the old rate
+                    frame_resampler.finish(&mut |frame: &[f32]| {
+                        handle_frame(
+                            frame,
+                            recording,
+                            &vad,
+                            &mut processed_samples,
+                            &mut utterances,
+                            &chunk_cb,
+                        );
+                    });
+
+                    // Release the old device first, some backends only allow one stream
+                    _stream = None;
+                    let (opened, reply) = match AudioRecorder::open_stream(&new_device) {
+                        Ok(opened) => {
+                            device = new_device;
+                            (Ok(opened), Ok(()))
+                        }
+                        Err(e) => {
+                            log::warn!(
+                                "Failed to switch input device, keeping the previous one: {}",
+                                e
+                            );
+                            (AudioRecorder::open_stream(&device), Err(e.to_string()))
+                        }
+                    };
+                    let _ = reply_tx.send(reply);
+
+                    match opened {
+                        Ok((new_stream, new_rx, rate)) => {
+                            _stream = Some(new_stream);
+                            sample_rx = new_rx;
+                            frame_resampler = FrameResampler::new(
+                                rate as usize,
+                                constants::WHISPER_SAMPLE_RATE as usize,
+                                Duration::from_millis(30),
+                            );
+                            visualizer =
+                                AudioVisualiser::new(rate, WINDOW_SIZE, BUCKETS, 400.0, 4000.0);
+                            sample_rate = rate;
+                            level_meter = new_level_meter(sample_rate, levels_rate);
+                            filter_stage = FilterStage::new(sample_rate, filter_stage.settings());
+                            gain_stage = GainStage::new(sample_rate, gain_stage.settings());
+                            // A capture can't mix sample rates, dropping it fails the request
+                            capture = None;
+                        }
+                        Err(e) => {
+                            log::error!("Failed to reopen input stream: {}", e);
+                            return;
+                        }
+                    }
+                }
+                Cmd::SetLevelsRate(rate) => {
+                    levels_rate = rate;
+                    level_meter = new_level_meter(sample_rate, levels_rate);
+                }
+                Cmd::SetGain(gain) => gain_stage.set_settings(gain),
+                Cmd::SetFilter(filter) => filter_stage.set_settings(filter),
+                Cmd::SetEndpointing(endpointing) => utterances.endpointing = endpointing,
+                Cmd::SetMaxUtterance(max) => {
+                    utterances.max_samples = max.map(|max| {
+                        (max.as_secs_f32() * constants::WHISPER_SAMPLE_RATE as f32) as usize
+                    });
+                }
+                Cmd::Capture(duration, reply_tx) => {
+                    let wanted = (duration.as_secs_f32() * sample_rate as f32) as usize;
+                    capture = Some((wanted, Vec::with_capacity(wanted), reply_tx));
+                }
                 Cmd::Shutdown => return,
             }
         }
@@ -1,6 +1,7 @@
 use anyhow::Result;
 use hound::{WavSpec, WavWriter};
 use log::debug;
+use std::io::Cursor;
 use std::path::Path;
 
 /// Save audio samples as a WAV file
@@ -24,3 +25,25 @@ pub async fn save_wav_file<P: AsRef<Path>>(file_path: P, samples: &[f32]) -> Res
     debug!("Saved WAV file: {:?}", file_path.as_ref());
     Ok(())
 }
+
+/// Encode audio samples as an in-memory 16-bit WAV file, e.g. for uploading to an API
+pub fn encode_wav(samples: &[f32]) -> Result<Vec<u8>> {
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 16000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut cursor = Cursor::new(Vec::new());
+    {
+        let mut writer = WavWriter::new(&mut cursor, spec)?;
+        for sample in samples {
+            let sample_i16 = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            writer.write_sample(sample_i16)?;
+        }
+        writer.finalize()?;
+    }
+
+    Ok(cursor.into_inner())
+}
@@ -0,0 +1,329 @@
+use super::text::{extract_punctuation, match_score, preserve_case_pattern};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Log10 probability of a word the model has never seen, when it has no `<unk>` entry
+const UNKNOWN_LOG_PROB: f32 = -7.0;
+
+/// How many orders of magnitude of language model probability a fully different spelling
+/// costs. Close spellings cost a fraction of it, so only a clearly likelier word wins.
+const SPELLING_PENALTY: f64 = 10.0;
+
+/// Log10 probability at which a word is likely enough where it stands to be left alone
+/// without looking for a replacement
+const LIKELY_LOG_PROB: f32 = -2.0;
+
+/// Largest length difference `match_score` considers at all
+const MAX_LENGTH_DIFFERENCE: usize = 5;
+
+/// What `match_score` scales the spelling difference of a phonetic match by
+const PHONETIC_FACTOR: f64 = 0.3;
+
+const SENTENCE_START: &str = "<s>";
+
+/// A backoff n-gram language model read from an ARPA file, the text format written by
+/// KenLM's `lmplz`, SRILM and most other toolkits. KenLM's binary format isn't read.
+#[derive(Debug, Default)]
+pub struct NgramModel {
+    order: usize,
+    /// Log10 probability and backoff weight of each n-gram, its lowercase words joined by
+    /// spaces
+    ngrams: HashMap<String, (f32, f32)>,
+    /// The unigrams that are real words, the candidates for corrections
+    vocabulary: Vec<String>,
+    /// Indexes into `vocabulary` by word length in bytes, and by first letter and length, so
+    /// candidates can be looked up without going through every word
+    by_length: HashMap<usize, Vec<usize>>,
+    by_initial_and_length: HashMap<(char, usize), Vec<usize>>,
+}
+
+impl NgramModel {
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::parse_arpa(&content)
+    }
+
+    pub fn parse_arpa(content: &str) -> Result<Self, String> {
+        let mut model = Self::default();
+        let mut section: Option<usize> = None;
+
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line == "\\data\\" || line.starts_with("ngram ") {
+                continue;
+            }
+            if line == "\\end\\" {
+                break;
+            }
+            if let Some(order) = line
+                .strip_prefix('\\')
+                .and_then(|rest| rest.strip_suffix("-grams:"))
+            {
+                let order = order
+                    .parse::<usize>()
+                    .map_err(|_| format!("Line {}: unknown section '{}'", index + 1, line))?;
+                model.order = model.order.max(order);
+                section = Some(order);
+                continue;
+            }
+            let Some(order) = section else {
+                return Err(format!("Line {}: n-gram outside of a section", index + 1));
+            };
+
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let bad_line = || format!("Line {}: expected a {}-gram", index + 1, order);
+            if fields.len() != order + 1 && fields.len() != order + 2 {
+                return Err(bad_line());
+            }
+            let log_prob = fields[0].parse::<f32>().map_err(|_| bad_line())?;
+            let backoff = match fields.get(order + 1) {
+                Some(field) => field.parse::<f32>().map_err(|_| bad_line())?,
+                None => 0.0,
+            };
+            let key = fields[1..=order].join(" ").to_lowercase();
+            if order == 1 && is_word(&key) && !model.ngrams.contains_key(&key) {
+                model.vocabulary.push(key.clone());
+            }
+            model.ngrams.entry(key).or_insert((log_prob, backoff));
+        }
+
+        if model.vocabulary.is_empty() {
+            return Err("The language model has no words".to_string());
+        }
+        for (index, word) in model.vocabulary.iter().enumerate() {
+            model.by_length.entry(word.len()).or_default().push(index);
+            if let Some(initial) = word.chars().next() {
+                model
+                    .by_initial_and_length
+                    .entry((initial, word.len()))
+                    .or_default()
+                    .push(index);
+            }
+        }
+        Ok(model)
+    }
+
+    pub fn ngram_count(&self) -> usize {
+        self.ngrams.len()
+    }
+
+    /// The vocabulary words that may score under `threshold` against `word`. A spelling
+    /// difference scores at least the length difference over the longer length, and only a
+    /// phonetic match, which Soundex finds between words with the same first letter, scores
+    /// less. Words further off in length are never looked at.
+    fn candidates<'a>(&'a self, word: &str, threshold: f64) -> impl Iterator<Item = &'a str> {
+        let length = word.len();
+        let initial = word.chars().next();
+        (length.saturating_sub(MAX_LENGTH_DIFFERENCE)..=length + MAX_LENGTH_DIFFERENCE)
+            .filter_map(move |other| {
+                let difference = length.abs_diff(other) as f64;
+                let longest = length.max(other) as f64;
+                if difference < threshold * longest {
+                    self.by_length.get(&other)
+                } else if PHONETIC_FACTOR * difference < threshold * longest {
+                    initial.and_then(|initial| self.by_initial_and_length.get(&(initial, other)))
+                } else {
+                    None
+                }
+            })
+            .flatten()
+            .map(|&index| self.vocabulary[index].as_str())
+    }
+
+    /// Log10 probability of `word` following `history`, backing off to shorter histories
+    /// for n-grams the model doesn't have.
+    pub fn log_prob(&self, history: &[&str], word: &str) -> f32 {
+        let history = &history[history.len().saturating_sub(self.order.saturating_sub(1))..];
+        let mut backoff = 0.0;
+        for start in 0..=history.len() {
+            let context = history[start..].join(" ");
+            let key = if context.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", context, word)
+            };
+            if let Some((log_prob, _)) = self.ngrams.get(&key) {
+                return backoff + log_prob;
+            }
+            if let Some((_, weight)) = self.ngrams.get(&context) {
+                backoff += weight;
+            }
+        }
+        backoff
+            + self
+                .ngrams
+                .get("<unk>")
+                .map_or(UNKNOWN_LOG_PROB, |(log_prob, _)| *log_prob)
+    }
+}
+
+fn is_word(unigram: &str) -> bool {
+    !unigram.starts_with('<') && unigram.chars().any(char::is_alphabetic)
+}
+
+/// Replaces words the model finds unlikely with similarly spelled words it finds likelier,
+/// judging each word by how well it follows the words before it and leads into the next.
+/// Words the model already finds likely where they stand are kept without a search.
+///
+/// `weight` scales the model's say against how different the spellings are, and
+/// `threshold` is the most different a replacement may be, as for custom words.
+pub fn rescore(text: &str, model: &NgramModel, weight: f64, threshold: f64) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let cleaned: Vec<String> = words
+        .iter()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphabetic())
+                .to_lowercase()
+        })
+        .collect();
+
+    let mut history: Vec<String> = vec![SENTENCE_START.to_string()];
+    let mut corrected_words = Vec::with_capacity(words.len());
+
+    for (i, word) in words.iter().enumerate() {
+        let original = &cleaned[i];
+        if original.is_empty() || original.len() > 50 {
+            corrected_words.push(word.to_string());
+            history = vec![SENTENCE_START.to_string()];
+            continue;
+        }
+
+        let context: Vec<&str> = history.iter().map(String::as_str).collect();
+        let next = cleaned.get(i + 1).filter(|next| !next.is_empty());
+        let score = |candidate: &str| {
+            let mut log_prob = model.log_prob(&context, candidate) as f64;
+            if let Some(next) = next {
+                let mut extended = context.clone();
+                extended.push(candidate);
+                log_prob += model.log_prob(&extended, next) as f64;
+            }
+            weight * log_prob
+        };
+
+        let mut best: &str = original;
+        if model.log_prob(&context, original) < LIKELY_LOG_PROB {
+            best = best_candidate(original, model, threshold, score);
+        }
+
+        if best == original {
+            corrected_words.push(word.to_string());
+        } else {
+            let (prefix, suffix) = extract_punctuation(word);
+            let corrected = preserve_case_pattern(word, best);
+            corrected_words.push(format!("{}{}{}", prefix, corrected, suffix));
+        }
+
+        if word.ends_with(['.', '!', '?']) {
+            history = vec![SENTENCE_START.to_string()];
+        } else {
+            history.push(best.to_string());
+        }
+    }
+
+    corrected_words.join(" ")
+}
+
+/// The candidate, or `original` itself, whose `score` minus the cost of its spelling
+/// difference is highest.
+fn best_candidate<'a>(
+    original: &'a str,
+    model: &'a NgramModel,
+    threshold: f64,
+    score: impl Fn(&str) -> f64,
+) -> &'a str {
+    let mut best = original;
+    let mut best_score = score(original);
+    for candidate in model.candidates(original, threshold) {
+        if candidate == original {
+            continue;
+        }
+        let distance = match_score(original, candidate);
+        if distance >= threshold {
+            continue;
+        }
+        let candidate_score = score(candidate) - SPELLING_PENALTY * distance;
+        if candidate_score > best_score {
+            best = candidate;
+            best_score = candidate_score;
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARPA: &str = "\
+\\data\\
+ngram 1=7
+ngram 2=3
+
+\\1-grams:
+-1.0 <s> -0.5
+-2.0 the -0.3
+-3.0 patient -0.3
+-3.5 has -0.2
+-4.5 tachycardia -0.1
+-3.0 attack -0.1
+-5.0 <unk>
+
+\\2-grams:
+-0.3 <s> the
+-0.2 the patient
+-0.4 has tachycardia
+
+\\end\\
+";
+
+    #[test]
+    fn test_parse_arpa() {
+        let model = NgramModel::parse_arpa(ARPA).unwrap();
+        assert_eq!(model.order, 2);
+        assert_eq!(model.ngram_count(), 10);
+        assert_eq!(
+            model.vocabulary,
+            ["the", "patient", "has", "tachycardia", "attack"]
+        );
+        assert!(NgramModel::parse_arpa("\\1-grams:\n-1.0").is_err());
+        assert!(NgramModel::parse_arpa("\\1-grams:\n-1.0 <s>\n").is_err());
+    }
+
+    #[test]
+    fn test_log_prob_backs_off() {
+        let model = NgramModel::parse_arpa(ARPA).unwrap();
+        assert_eq!(model.log_prob(&["the"], "patient"), -0.2);
+        // No "patient has" bigram: backoff of "patient" plus the unigram
+        assert_eq!(model.log_prob(&["the", "patient"], "has"), -0.3 + -3.5);
+        assert_eq!(model.log_prob(&["has"], "zebra"), -0.2 + -5.0);
+    }
+
+    #[test]
+    fn test_candidates_skip_words_too_different() {
+        let model = NgramModel::parse_arpa(ARPA).unwrap();
+        let candidates: Vec<&str> = model.candidates("tachycardea", 0.3).collect();
+        assert_eq!(candidates, ["tachycardia"]);
+        let candidates: Vec<&str> = model.candidates("hat", 0.5).collect();
+        assert_eq!(candidates, ["the", "has"]);
+    }
+
+    #[test]
+    fn test_rescore_prefers_likely_words() {
+        let model = NgramModel::parse_arpa(ARPA).unwrap();
+        assert_eq!(
+            rescore("The patient has tachycardea.", &model, 1.0, 0.5),
+            "The patient has tachycardia."
+        );
+        // Too different to be the same word
+        assert_eq!(
+            rescore("The patient has a cold.", &model, 1.0, 0.5),
+            "The patient has a cold."
+        );
+        assert_eq!(
+            rescore("The patient has tachycardea.", &model, 1.0, 0.01),
+            "The patient has tachycardea."
+        );
+    }
+}
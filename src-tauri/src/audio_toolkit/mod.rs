@@ -1,12 +1,19 @@
 pub mod audio;
 pub mod constants;
+pub mod language_model;
+pub mod rich_text;
 pub mod text;
 pub mod utils;
 pub mod vad;
 
 pub use audio::{
-    list_input_devices, list_output_devices, save_wav_file, AudioRecorder, CpalDeviceInfo,
+    encode_wav, list_input_devices, list_output_devices, save_wav_file, AudioChunk, AudioLevels,
+    AudioRecorder, CpalDeviceInfo,
+};
+pub use language_model::NgramModel;
+pub use rich_text::RichText;
+pub use text::{
+    apply_custom_words, apply_sounds_like, capitalize_proper_nouns, strip_overlap, WordHint,
 };
-pub use text::apply_custom_words;
 pub use utils::get_cpal_host;
 pub use vad::{SileroVad, VoiceActivityDetector};
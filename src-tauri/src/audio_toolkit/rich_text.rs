@@ -0,0 +1,316 @@
+//! Turns spoken formatting commands into a small document model that can be rendered as
+//! plain text, Markdown, or HTML.
+//!
+//! Supported commands:
+//! - "bold ... end bold" wraps the enclosed words in emphasis
+//! - "bullet" / "bullet point" / "next bullet" starts a list item, "end list" closes the list
+//! - spoken URLs such as "example dot com" (and literal ones like "example.com") become links
+
+const KNOWN_TLDS: &[&str] = &[
+    "com", "org", "net", "io", "dev", "app", "ai", "edu", "gov", "co", "uk", "de", "fr", "info",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Inline {
+    Text(String),
+    Bold(String),
+    Link(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Block {
+    Paragraph(Vec<Inline>),
+    List(Vec<Vec<Inline>>),
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RichText {
+    blocks: Vec<Block>,
+}
+
+/// Lowercased word with surrounding punctuation removed, used to match commands.
+fn keyword(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+}
+
+fn is_tld(word: &str) -> bool {
+    KNOWN_TLDS.contains(&keyword(word).as_str())
+}
+
+/// Returns the URL if `word` already looks like a link (e.g. "https://x.y" or "example.com").
+fn literal_url(word: &str) -> Option<String> {
+    let trimmed = word.trim_end_matches(|c: char| matches!(c, '.' | ',' | '!' | '?' | ';' | ':'));
+    if trimmed.starts_with("http://") || trimmed.starts_with("https://") {
+        return Some(trimmed.to_string());
+    }
+    let (host, tld) = trimmed.rsplit_once('.')?;
+    if !host.is_empty()
+        && host
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '.' | '-'))
+        && KNOWN_TLDS.contains(&tld.to_lowercase().as_str())
+    {
+        Some(trimmed.to_lowercase())
+    } else {
+        None
+    }
+}
+
+struct Builder {
+    blocks: Vec<Block>,
+    current: Vec<Inline>,
+    in_list: bool,
+    list_items: Vec<Vec<Inline>>,
+    bold: bool,
+}
+
+impl Builder {
+    fn push_word(&mut self, word: &str) {
+        let styled_bold = self.bold;
+        match self.current.last_mut() {
+            Some(Inline::Text(text)) if !styled_bold => {
+                text.push(' ');
+                text.push_str(word);
+            }
+            Some(Inline::Bold(text)) if styled_bold => {
+                text.push(' ');
+                text.push_str(word);
+            }
+            _ if styled_bold => self.current.push(Inline::Bold(word.to_string())),
+            _ => self.current.push(Inline::Text(word.to_string())),
+        }
+    }
+
+    fn flush_current(&mut self) {
+        let inlines = std::mem::take(&mut self.current);
+        if inlines.is_empty() {
+            return;
+        }
+        if self.in_list {
+            self.list_items.push(inlines);
+        } else {
+            self.blocks.push(Block::Paragraph(inlines));
+        }
+    }
+
+    fn close_list(&mut self) {
+        self.flush_current();
+        if self.in_list {
+            let items = std::mem::take(&mut self.list_items);
+            if !items.is_empty() {
+                self.blocks.push(Block::List(items));
+            }
+            self.in_list = false;
+        }
+    }
+}
+
+impl RichText {
+    /// Parses a transcription, interpreting the spoken formatting commands described above.
+    pub fn parse(text: &str) -> Self {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let mut b = Builder {
+            blocks: Vec::new(),
+            current: Vec::new(),
+            in_list: false,
+            list_items: Vec::new(),
+            bold: false,
+        };
+
+        let mut i = 0;
+        while i < words.len() {
+            let kw = keyword(words[i]);
+            let next = words.get(i + 1).map(|w| keyword(w)).unwrap_or_default();
+
+            match (kw.as_str(), next.as_str()) {
+                ("end", "bold") => {
+                    b.bold = false;
+                    i += 2;
+                    continue;
+                }
+                ("bold", _) => {
+                    b.bold = true;
+                    i += 1;
+                    continue;
+                }
+                ("end", "list") | ("end", "bullets") => {
+                    b.bold = false;
+                    b.close_list();
+                    i += 2;
+                    continue;
+                }
+                ("next", "bullet") | ("bullet", "point") => {
+                    b.bold = false;
+                    b.flush_current();
+                    b.in_list = true;
+                    i += 2;
+                    continue;
+                }
+                ("bullet", _) => {
+                    b.bold = false;
+                    b.flush_current();
+                    b.in_list = true;
+                    i += 1;
+                    continue;
+                }
+                _ => {}
+            }
+
+            // Spoken URL: "<name> dot <name> ... dot <tld>"
+            let mut j = i;
+            let mut parts = vec![kw.clone()];
+            while j + 2 < words.len() && keyword(words[j + 1]) == "dot" {
+                parts.push(keyword(words[j + 2]));
+                j += 2;
+            }
+            if j > i && is_tld(words[j]) && parts.iter().all(|p| !p.is_empty()) {
+                b.current.push(Inline::Link(parts.join(".")));
+                i = j + 1;
+                continue;
+            }
+
+            if let Some(url) = literal_url(words[i]) {
+                b.current.push(Inline::Link(url));
+            } else {
+                b.push_word(words[i]);
+            }
+            i += 1;
+        }
+        b.close_list();
+
+        RichText { blocks: b.blocks }
+    }
+
+    pub fn to_plain_text(&self) -> String {
+        self.render(
+            |inline| match inline {
+                Inline::Text(t) | Inline::Bold(t) | Inline::Link(t) => t.clone(),
+            },
+            "• ",
+        )
+    }
+
+    pub fn to_markdown(&self) -> String {
+        self.render(
+            |inline| match inline {
+                Inline::Text(t) => t.clone(),
+                Inline::Bold(t) => format!("**{}**", t),
+                Inline::Link(url) => format!("[{}]({})", url, href(url)),
+            },
+            "- ",
+        )
+    }
+
+    pub fn to_html(&self) -> String {
+        let inline_html = |inlines: &[Inline]| -> String {
+            inlines
+                .iter()
+                .map(|inline| match inline {
+                    Inline::Text(t) => escape_html(t),
+                    Inline::Bold(t) => format!("<strong>{}</strong>", escape_html(t)),
+                    Inline::Link(url) => format!(
+                        "<a href=\"{}\">{}</a>",
+                        escape_html(&href(url)),
+                        escape_html(url)
+                    ),
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        self.blocks
+            .iter()
+            .map(|block| match block {
+                Block::Paragraph(inlines) => format!("<p>{}</p>", inline_html(inlines)),
+                Block::List(items) => format!(
+                    "<ul>{}</ul>",
+                    items
+                        .iter()
+                        .map(|item| format!("<li>{}</li>", inline_html(item)))
+                        .collect::<String>()
+                ),
+            })
+            .collect()
+    }
+
+    fn render(&self, inline: impl Fn(&Inline) -> String, bullet: &str) -> String {
+        let join = |inlines: &[Inline]| inlines.iter().map(&inline).collect::<Vec<_>>().join(" ");
+
+        self.blocks
+            .iter()
+            .map(|block| match block {
+                Block::Paragraph(inlines) => join(inlines),
+                Block::List(items) => items
+                    .iter()
+                    .map(|item| format!("{}{}", bullet, join(item)))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+fn href(url: &str) -> String {
+    if url.starts_with("http://") || url.starts_with("https://") {
+        url.to_string()
+    } else {
+        format!("https://{}", url)
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_text_is_unchanged() {
+        let doc = RichText::parse("hello there world");
+        assert_eq!(doc.to_plain_text(), "hello there world");
+        assert_eq!(doc.to_html(), "<p>hello there world</p>");
+    }
+
+    #[test]
+    fn test_bold_command() {
+        let doc = RichText::parse("this is bold very important end bold okay");
+        assert_eq!(doc.to_markdown(), "this is **very important** okay");
+        assert_eq!(
+            doc.to_html(),
+            "<p>this is <strong>very important</strong> okay</p>"
+        );
+    }
+
+    #[test]
+    fn test_bullet_list() {
+        let doc = RichText::parse("Groceries: bullet point milk, bullet eggs. End list. Thanks");
+        assert_eq!(
+            doc.to_markdown(),
+            "Groceries:\n\n- milk,\n- eggs.\n\nThanks"
+        );
+        assert_eq!(
+            doc.to_html(),
+            "<p>Groceries:</p><ul><li>milk,</li><li>eggs.</li></ul><p>Thanks</p>"
+        );
+    }
+
+    #[test]
+    fn test_spoken_and_literal_links() {
+        let doc = RichText::parse("visit example dot com or github.com for <details>");
+        assert_eq!(
+            doc.to_markdown(),
+            "visit [example.com](https://example.com) or [github.com](https://github.com) for <details>"
+        );
+        assert!(doc
+            .to_html()
+            .contains("<a href=\"https://example.com\">example.com</a>"));
+        assert!(doc.to_html().contains("&lt;details&gt;"));
+    }
+}
@@ -1,6 +1,35 @@
 use natural::phonetics::soundex;
 use strsim::levenshtein;
 
+/// Custom words this short get a proportionally stricter default threshold, since a single
+/// changed letter is a large share of a short word.
+const SHORT_WORD_LEN: usize = 5;
+
+/// Most words the transcriptions of two overlapping stretches of audio are searched for a
+/// shared run, well beyond what is said in the overlap
+const MAX_OVERLAP_WORDS: usize = 8;
+
+/// A custom word and the hints for recognizing it in a transcription.
+#[derive(Debug, Clone, Copy)]
+pub struct WordHint<'a> {
+    pub word: &'a str,
+    /// Alternate spellings or "sounds like" strings the engine writes instead of the word.
+    /// Entries with several words are replaced as whole phrases.
+    pub sounds_like: &'a [String],
+    /// Overrides the global threshold for this word
+    pub threshold: Option<f64>,
+}
+
+impl<'a> WordHint<'a> {
+    pub fn new(word: &'a str) -> Self {
+        Self {
+            word,
+            sounds_like: &[],
+            threshold: None,
+        }
+    }
+}
+
 /// Applies custom word corrections to transcribed text using fuzzy matching
 ///
 /// This function corrects words in the input text by finding the best matches
@@ -8,20 +37,50 @@ use strsim::levenshtein;
 /// - Levenshtein distance for string similarity
 /// - Soundex phonetic matching for pronunciation similarity
 ///
+/// Each custom word is also compared against its `sounds_like` spellings, and multi-word
+/// spellings ("get hub" for "GitHub") are replaced as phrases first.
+///
 /// # Arguments
 /// * `text` - The input text to correct
 /// * `custom_words` - List of custom words to match against
 /// * `threshold` - Maximum similarity score to accept (0.0 = exact match, 1.0 = any match)
+///   for words without their own threshold. It is scaled down for short words.
 ///
 /// # Returns
 /// The corrected text with custom words applied
-pub fn apply_custom_words(text: &str, custom_words: &[String], threshold: f64) -> String {
+pub fn apply_custom_words(text: &str, custom_words: &[WordHint], threshold: f64) -> String {
     if custom_words.is_empty() {
         return text.to_string();
     }
 
-    // Pre-compute lowercase versions to avoid repeated allocations
-    let custom_words_lower: Vec<String> = custom_words.iter().map(|w| w.to_lowercase()).collect();
+    let phrases: Vec<(&str, &str)> = custom_words
+        .iter()
+        .flat_map(|hint| {
+            hint.sounds_like
+                .iter()
+                .map(|spelling| spelling.trim())
+                .filter(|spelling| spelling.contains(char::is_whitespace))
+                .map(move |spelling| (spelling, hint.word))
+        })
+        .collect();
+    let text = replace_phrases(text, &phrases);
+
+    // Pre-compute lowercase spellings and thresholds to avoid repeated allocations
+    let candidates: Vec<(Vec<String>, f64)> = custom_words
+        .iter()
+        .map(|hint| {
+            let spellings = std::iter::once(hint.word)
+                .chain(hint.sounds_like.iter().map(|s| s.trim()))
+                .filter(|s| !s.is_empty() && !s.contains(char::is_whitespace))
+                .map(|s| s.to_lowercase())
+                .collect();
+            let threshold = hint.threshold.unwrap_or_else(|| {
+                let len = hint.word.chars().count().min(SHORT_WORD_LEN);
+                threshold * len as f64 / SHORT_WORD_LEN as f64
+            });
+            (spellings, threshold)
+        })
+        .collect();
 
     let words: Vec<&str> = text.split_whitespace().collect();
     let mut corrected_words = Vec::new();
@@ -42,39 +101,18 @@ pub fn apply_custom_words(text: &str, custom_words: &[String], threshold: f64) -
             continue;
         }
 
-        let mut best_match: Option<&String> = None;
+        let mut best_match: Option<&str> = None;
         let mut best_score = f64::MAX;
 
-        for (i, custom_word_lower) in custom_words_lower.iter().enumerate() {
-            // Skip if lengths are too different (optimization)
-            let len_diff = (cleaned_word.len() as i32 - custom_word_lower.len() as i32).abs();
-            if len_diff > 5 {
-                continue;
-            }
+        for (i, (spellings, word_threshold)) in candidates.iter().enumerate() {
+            for spelling in spellings {
+                let combined_score = match_score(&cleaned_word, spelling);
 
-            // Calculate Levenshtein distance (normalized by length)
-            let levenshtein_dist = levenshtein(&cleaned_word, custom_word_lower);
-            let max_len = cleaned_word.len().max(custom_word_lower.len()) as f64;
-            let levenshtein_score = if max_len > 0.0 {
-                levenshtein_dist as f64 / max_len
-            } else {
-                1.0
-            };
-
-            // Calculate phonetic similarity using Soundex
-            let phonetic_match = soundex(&cleaned_word, custom_word_lower);
-
-            // Combine scores: favor phonetic matches, but also consider string similarity
-            let combined_score = if phonetic_match {
-                levenshtein_score * 0.3 // Give significant boost to phonetic matches
-            } else {
-                levenshtein_score
-            };
-
-            // Accept if the score is good enough (configurable threshold)
-            if combined_score < threshold && combined_score < best_score {
-                best_match = Some(&custom_words[i]);
-                best_score = combined_score;
+                // Accept if the score is good enough (configurable threshold)
+                if combined_score < *word_threshold && combined_score < best_score {
+                    best_match = Some(custom_words[i].word);
+                    best_score = combined_score;
+                }
             }
         }
 
@@ -93,9 +131,148 @@ pub fn apply_custom_words(text: &str, custom_words: &[String], threshold: f64) -
     corrected_words.join(" ")
 }
 
-/// Preserves the case pattern of the original word when applying a replacement
-fn preserve_case_pattern(original: &str, replacement: &str) -> String {
-    if original.chars().all(|c| c.is_uppercase()) {
+/// Replaces the `sounds_like` spellings of the custom words with the words, matching them
+/// exactly on word boundaries. For text from an engine biased toward the words, which needs
+/// no fuzzy matching but may still write a spelling the user listed.
+pub fn apply_sounds_like(text: &str, custom_words: &[WordHint]) -> String {
+    let spellings: Vec<(&str, &str)> = custom_words
+        .iter()
+        .flat_map(|hint| {
+            hint.sounds_like
+                .iter()
+                .map(|spelling| spelling.trim())
+                .filter(|spelling| !spelling.is_empty())
+                .map(move |spelling| (spelling, hint.word))
+        })
+        .collect();
+    replace_phrases(text, &spellings)
+}
+
+/// Similarity of two lowercase words, 0.0 for an exact match.
+pub(super) fn match_score(word: &str, candidate: &str) -> f64 {
+    // Skip if lengths are too different (optimization)
+    let len_diff = (word.len() as i32 - candidate.len() as i32).abs();
+    if len_diff > 5 {
+        return f64::MAX;
+    }
+
+    // Calculate Levenshtein distance (normalized by length)
+    let levenshtein_dist = levenshtein(word, candidate);
+    let max_len = word.len().max(candidate.len()) as f64;
+    let levenshtein_score = if max_len > 0.0 {
+        levenshtein_dist as f64 / max_len
+    } else {
+        1.0
+    };
+
+    // Calculate phonetic similarity using Soundex
+    let phonetic_match = soundex(word, candidate);
+
+    // Combine scores: favor phonetic matches, but also consider string similarity
+    if phonetic_match {
+        levenshtein_score * 0.3 // Give significant boost to phonetic matches
+    } else {
+        levenshtein_score
+    }
+}
+
+/// Capitalizes known names (e.g. "New York", "McDonald") wherever they appear in the text,
+/// matching case-insensitively on word boundaries and writing them with their canonical casing.
+/// Multi-word names are supported; longer names win over names they contain.
+pub fn capitalize_proper_nouns(text: &str, names: &[String]) -> String {
+    let names: Vec<(&str, &str)> = names
+        .iter()
+        .map(|name| name.trim())
+        .map(|name| (name, name))
+        .collect();
+    replace_phrases(text, &names)
+}
+
+/// Removes the words at the start of `next` that repeat the end of `previous`, for
+/// transcriptions of overlapping audio. The longest run of words ending `previous` that also
+/// starts `next` is taken as the overlap, comparing words without case and punctuation.
+pub fn strip_overlap(previous: &str, next: &str) -> String {
+    let normalize = |word: &str| -> String {
+        word.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    let previous: Vec<String> = previous.split_whitespace().map(normalize).collect();
+    let next_words: Vec<&str> = next.split_whitespace().collect();
+    let normalized: Vec<String> = next_words.iter().map(|word| normalize(word)).collect();
+
+    let longest = previous.len().min(next_words.len()).min(MAX_OVERLAP_WORDS);
+    let overlap = (1..=longest)
+        .rev()
+        .find(|&len| previous[previous.len() - len..] == normalized[..len])
+        .unwrap_or(0);
+    next_words[overlap..].join(" ")
+}
+
+/// Replaces each `(phrase, replacement)` wherever the phrase appears in the text, matching
+/// case-insensitively on word boundaries. Longer phrases win over phrases they contain.
+fn replace_phrases(text: &str, phrases: &[(&str, &str)]) -> String {
+    let mut phrases: Vec<(&str, &str)> = phrases
+        .iter()
+        .copied()
+        .filter(|(phrase, _)| !phrase.is_empty())
+        .collect();
+    if phrases.is_empty() {
+        return text.to_string();
+    }
+    phrases.sort_by_key(|(phrase, _)| std::cmp::Reverse(phrase.chars().count()));
+
+    let mut result = String::with_capacity(text.len());
+    let mut pos = 0;
+    let mut prev: Option<char> = None;
+
+    while let Some(c) = text[pos..].chars().next() {
+        let rest = &text[pos..];
+        if !prev.is_some_and(|p| p.is_alphanumeric()) {
+            let found = phrases.iter().find_map(|(phrase, replacement)| {
+                let len = match_ignore_case(rest, phrase)?;
+                let at_boundary = !rest[len..]
+                    .chars()
+                    .next()
+                    .is_some_and(|n| n.is_alphanumeric());
+                at_boundary.then_some((*replacement, len))
+            });
+            if let Some((replacement, len)) = found {
+                result.push_str(replacement);
+                pos += len;
+                prev = replacement.chars().last();
+                continue;
+            }
+        }
+
+        result.push(c);
+        pos += c.len_utf8();
+        prev = Some(c);
+    }
+
+    result
+}
+
+/// Returns the byte length of the prefix of `haystack` that equals `needle` ignoring case.
+fn match_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    let mut chars = haystack.char_indices();
+    for n in needle.chars() {
+        let (_, h) = chars.next()?;
+        if !h.to_lowercase().eq(n.to_lowercase()) {
+            return None;
+        }
+    }
+    Some(chars.next().map_or(haystack.len(), |(i, _)| i))
+}
+
+/// Preserves the case pattern of the original word when applying a replacement.
+/// Replacements that contain capitals (iPhone, GitHub, NASA) have a canonical casing
+/// and are used as written; lowercase entries follow the original word.
+pub(super) fn preserve_case_pattern(original: &str, replacement: &str) -> String {
+    if replacement.chars().any(|c| c.is_uppercase()) {
+        replacement.to_string()
+    } else if original.chars().all(|c| c.is_uppercase()) {
         replacement.to_uppercase()
     } else if original.chars().next().map_or(false, |c| c.is_uppercase()) {
         let mut chars: Vec<char> = replacement.chars().collect();
@@ -109,7 +286,7 @@ fn preserve_case_pattern(original: &str, replacement: &str) -> String {
 }
 
 /// Extracts punctuation prefix and suffix from a word
-fn extract_punctuation(word: &str) -> (&str, &str) {
+pub(super) fn extract_punctuation(word: &str) -> (&str, &str) {
     let prefix_end = word.chars().take_while(|c| !c.is_alphabetic()).count();
     let suffix_start = word
         .char_indices()
@@ -136,10 +313,14 @@ fn extract_punctuation(word: &str) -> (&str, &str) {
 mod tests {
     use super::*;
 
+    fn hints<'a>(words: &[&'a str]) -> Vec<WordHint<'a>> {
+        words.iter().map(|word| WordHint::new(word)).collect()
+    }
+
     #[test]
     fn test_apply_custom_words_exact_match() {
         let text = "hello world";
-        let custom_words = vec!["Hello".to_string(), "World".to_string()];
+        let custom_words = hints(&["Hello", "World"]);
         let result = apply_custom_words(text, &custom_words, 0.5);
         assert_eq!(result, "Hello World");
     }
@@ -147,11 +328,64 @@ mod tests {
     #[test]
     fn test_apply_custom_words_fuzzy_match() {
         let text = "helo wrold";
-        let custom_words = vec!["hello".to_string(), "world".to_string()];
+        let custom_words = hints(&["hello", "world"]);
         let result = apply_custom_words(text, &custom_words, 0.5);
         assert_eq!(result, "hello world");
     }
 
+    #[test]
+    fn test_apply_custom_words_hints() {
+        let sounds_like = vec!["get hub".to_string(), "kubernetis".to_string()];
+        let custom_words = vec![
+            WordHint {
+                word: "GitHub",
+                sounds_like: &sounds_like[..1],
+                threshold: None,
+            },
+            WordHint {
+                word: "Kubernetes",
+                sounds_like: &sounds_like[1..],
+                threshold: Some(0.01),
+            },
+        ];
+        assert_eq!(
+            apply_custom_words("push to get hub and kubernetis.", &custom_words, 0.5),
+            "push to GitHub and Kubernetes."
+        );
+        // a tiny threshold only accepts the listed spellings
+        assert_eq!(
+            apply_custom_words("kubernetas", &custom_words, 0.5),
+            "kubernetas"
+        );
+
+        // short words get a stricter default threshold
+        let custom_words = hints(&["Ned"]);
+        assert_eq!(apply_custom_words("ted", &custom_words, 0.5), "ted");
+    }
+
+    #[test]
+    fn test_apply_sounds_like() {
+        let sounds_like = vec!["get hub".to_string(), "kubernetis".to_string()];
+        let custom_words = vec![
+            WordHint {
+                word: "GitHub",
+                sounds_like: &sounds_like[..1],
+                threshold: None,
+            },
+            WordHint {
+                word: "Kubernetes",
+                sounds_like: &sounds_like[1..],
+                threshold: None,
+            },
+        ];
+        assert_eq!(
+            apply_sounds_like("Push to Get hub and kubernetis.", &custom_words),
+            "Push to GitHub and Kubernetes."
+        );
+        // near misses are left to the biased engine
+        assert_eq!(apply_sounds_like("kubernetas", &custom_words), "kubernetas");
+    }
+
     #[test]
     fn test_preserve_case_pattern() {
         assert_eq!(preserve_case_pattern("HELLO", "world"), "WORLD");
@@ -159,6 +393,24 @@ mod tests {
         assert_eq!(preserve_case_pattern("hello", "WORLD"), "WORLD");
     }
 
+    #[test]
+    fn test_apply_custom_words_keeps_canonical_casing() {
+        let text = "Iphone and GITHUB";
+        let custom_words = hints(&["iPhone", "GitHub"]);
+        let result = apply_custom_words(text, &custom_words, 0.5);
+        assert_eq!(result, "iPhone and GitHub");
+    }
+
+    #[test]
+    fn test_capitalize_proper_nouns() {
+        let names = vec!["New York".to_string(), "Ada".to_string()];
+        assert_eq!(
+            capitalize_proper_nouns("ada moved to new york, then nevada.", &names),
+            "Ada moved to New York, then nevada."
+        );
+        assert_eq!(capitalize_proper_nouns("canada", &names), "canada");
+    }
+
     #[test]
     fn test_extract_punctuation() {
         assert_eq!(extract_punctuation("hello"), ("", ""));
@@ -173,4 +425,20 @@ mod tests {
         let result = apply_custom_words(text, &custom_words, 0.5);
         assert_eq!(result, "hello world");
     }
+
+    #[test]
+    fn test_strip_overlap() {
+        assert_eq!(
+            strip_overlap("we should meet on Tuesday", "on tuesday, at noon"),
+            "at noon"
+        );
+        // The longest shared run wins over a shorter one
+        assert_eq!(strip_overlap("that is that", "is that so"), "so");
+        assert_eq!(
+            strip_overlap("hello there", "general Kenobi"),
+            "general Kenobi"
+        );
+        assert_eq!(strip_overlap("", "hello"), "hello");
+        assert_eq!(strip_overlap("all of it", "of it"), "");
+    }
 }
@@ -0,0 +1,345 @@
+//! Automation entry points shared by the `handy://` URL scheme, the MCP tools and the
+//! commands of the same names, so Shortcuts, AppleScript (`open location`) and scripts can
+//! drive Handy:
+//!
+//! - `handy://start-dictation`, `handy://stop-dictation`, `handy://toggle-dictation`
+//! - `handy://transcribe-file?path=/Users/me/memo.wav`
+//! - `handy://last-transcript`
+//!
+//! A URL can't return anything, so following x-callback-url, the text of the last two is
+//! sent to `x-success` as its `result` parameter, and a transcribed file is also copied to
+//! the clipboard. Failures are sent to `x-error` as `errorMessage`. Callbacks only go to the
+//! apps in [`CALLBACK_SCHEMES`], never to a website.
+//!
+//! Any web page can open a URL, so the user confirms each one that would start the
+//! microphone, read a file or a past dictation, or call another app back.
+
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::commands::audio::read_audio_file;
+use crate::managers::audio::AudioRecordingManager;
+use crate::managers::file_jobs::FileJobQueue;
+use crate::managers::history::{HistoryEntry, HistoryManager};
+use crate::shortcut;
+use log::{info, warn};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, Url};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tauri_plugin_opener::OpenerExt;
+
+pub const URL_SCHEME: &str = "handy";
+
+/// Schemes of the automation apps `x-success` and `x-error` may call back
+const CALLBACK_SCHEMES: &[&str] = &["shortcuts", "raycast", "alfred", "drafts"];
+
+#[derive(Debug, PartialEq)]
+pub enum UrlAction {
+    StartDictation,
+    StopDictation,
+    ToggleDictation,
+    TranscribeFile(String),
+    LastTranscript,
+}
+
+/// A `handy://` URL's action and its x-callback-url callbacks.
+#[derive(Debug, PartialEq)]
+pub struct UrlRequest {
+    pub action: UrlAction,
+    pub success: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Reads the action of a `handy://` URL.
+pub fn parse_url(url: &str) -> Result<UrlRequest, String> {
+    let url = Url::parse(url).map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+    if url.scheme() != URL_SCHEME {
+        return Err(format!("Not a {}:// URL", URL_SCHEME));
+    }
+    let query = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    };
+    // `handy://start-dictation` has its action as the host, `handy:start-dictation` as the path
+    let name = url
+        .host_str()
+        .unwrap_or_else(|| url.path())
+        .trim_matches('/')
+        .to_lowercase();
+    let action = match name.as_str() {
+        "start-dictation" => UrlAction::StartDictation,
+        "stop-dictation" => UrlAction::StopDictation,
+        "toggle-dictation" => UrlAction::ToggleDictation,
+        "transcribe-file" => {
+            UrlAction::TranscribeFile(query("path").ok_or("transcribe-file needs a path")?)
+        }
+        "last-transcript" => UrlAction::LastTranscript,
+        other => return Err(format!("Unknown action {}", other)),
+    };
+    Ok(UrlRequest {
+        action,
+        success: callback(query("x-success"))?,
+        error: callback(query("x-error"))?,
+    })
+}
+
+/// Checks that a callback URL opens one of the automation apps, so results never reach a
+/// website.
+fn callback(url: Option<String>) -> Result<Option<String>, String> {
+    let Some(url) = url else {
+        return Ok(None);
+    };
+    let scheme = Url::parse(&url)
+        .map_err(|e| format!("Invalid callback URL {}: {}", url, e))?
+        .scheme()
+        .to_string();
+    if !CALLBACK_SCHEMES.contains(&scheme.as_str()) {
+        return Err(format!("Callbacks to {}:// URLs aren't allowed", scheme));
+    }
+    Ok(Some(url))
+}
+
+/// What the user is asked to allow before running the request, for actions that start the
+/// microphone or read a file or a past dictation, and for any request with a callback.
+/// `recording` tells whether a toggle would start or stop the dictation.
+fn confirmation(request: &UrlRequest, recording: bool) -> Option<String> {
+    let (what, sensitive) = match &request.action {
+        UrlAction::StartDictation => ("start recording from the microphone".to_string(), true),
+        UrlAction::ToggleDictation if !recording => {
+            ("start recording from the microphone".to_string(), true)
+        }
+        UrlAction::StopDictation | UrlAction::ToggleDictation => {
+            ("stop the dictation in progress".to_string(), false)
+        }
+        UrlAction::TranscribeFile(path) => (format!("read and transcribe the file {}", path), true),
+        UrlAction::LastTranscript => ("read your last dictation".to_string(), true),
+    };
+    let callback = request.success.as_deref().or(request.error.as_deref());
+    if !sensitive && callback.is_none() {
+        return None;
+    }
+    let mut message = format!("A link asks Handy to {}.", what);
+    if let Some(scheme) = callback.and_then(|url| Url::parse(url).ok()) {
+        message.push_str(&format!(
+            " The result is sent to the app opening {}:// links.",
+            scheme.scheme()
+        ));
+    }
+    Some(message)
+}
+
+async fn confirm(app: &AppHandle, message: String) -> bool {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        app.dialog()
+            .message(message)
+            .title("Allow this link?")
+            .kind(MessageDialogKind::Warning)
+            .buttons(MessageDialogButtons::OkCancelCustom(
+                "Allow".to_string(),
+                "Deny".to_string(),
+            ))
+            .blocking_show()
+    })
+    .await
+    .unwrap_or(false)
+}
+
+/// Runs the action of a `handy://` URL the app was opened with.
+pub fn handle_url(app: &AppHandle, url: &str) {
+    info!("Opened with {}", url);
+    let request = match parse_url(url) {
+        Ok(request) => request,
+        Err(e) => {
+            warn!("Ignoring URL: {}", e);
+            return;
+        }
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let recording = app.state::<Arc<AudioRecordingManager>>().is_recording();
+        if let Some(message) = confirmation(&request, recording) {
+            if !confirm(&app, message).await {
+                info!("The user denied the URL action");
+                if let Some(Ok(callback)) = request
+                    .error
+                    .map(|url| with_param(&url, "errorMessage", "Denied by the user"))
+                {
+                    if let Err(e) = app.opener().open_url(callback.as_str(), None::<String>) {
+                        warn!("Failed to open the callback URL: {}", e);
+                    }
+                }
+                return;
+            }
+        }
+        let copy_result = matches!(request.action, UrlAction::TranscribeFile(_));
+        let result = match request.action {
+            UrlAction::StartDictation => start_dictation(&app, "url").map(|_| None),
+            UrlAction::StopDictation => stop_dictation(&app, "url").map(|_| None),
+            UrlAction::ToggleDictation => {
+                shortcut::toggle_binding(&app, "transcribe", "url");
+                Ok(None)
+            }
+            UrlAction::TranscribeFile(path) => {
+                let app = app.clone();
+                tauri::async_runtime::spawn_blocking(move || transcribe_file(&app, &path))
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|result| result)
+                    .map(Some)
+            }
+            UrlAction::LastTranscript => last_transcript(&app).await,
+        };
+
+        let callback = match &result {
+            Ok(text) => {
+                // The last transcript only goes to the callback, so a link can't put past
+                // dictations on the clipboard
+                if let Some(text) = text.as_ref().filter(|_| copy_result) {
+                    if let Err(e) = app.clipboard().write_text(text) {
+                        warn!("Failed to copy the result: {}", e);
+                    }
+                }
+                request
+                    .success
+                    .map(|url| with_param(&url, "result", text.as_deref().unwrap_or_default()))
+            }
+            Err(e) => {
+                warn!("URL action failed: {}", e);
+                request.error.map(|url| with_param(&url, "errorMessage", e))
+            }
+        };
+        if let Some(Ok(callback)) = callback {
+            if let Err(e) = app.opener().open_url(callback.as_str(), None::<String>) {
+                warn!("Failed to open the callback URL: {}", e);
+            }
+        }
+    });
+}
+
+fn with_param(url: &str, name: &str, value: &str) -> Result<Url, String> {
+    let mut url = Url::parse(url).map_err(|e| e.to_string())?;
+    url.query_pairs_mut().append_pair(name, value);
+    Ok(url)
+}
+
+/// Starts a dictation as the transcribe shortcut does, `source` naming what asked for it.
+pub fn start_dictation(app: &AppHandle, source: &str) -> Result<(), String> {
+    if app.state::<Arc<AudioRecordingManager>>().is_recording() {
+        return Err("A dictation is already in progress".to_string());
+    }
+    shortcut::toggle_binding(app, "transcribe", source);
+    Ok(())
+}
+
+/// Stops the dictation in progress, which is then transcribed and delivered.
+pub fn stop_dictation(app: &AppHandle, source: &str) -> Result<(), String> {
+    if !app.state::<Arc<AudioRecordingManager>>().is_recording() {
+        return Err("No dictation is in progress".to_string());
+    }
+    shortcut::toggle_binding(app, "transcribe", source);
+    Ok(())
+}
+
+/// Transcribes a WAV file through the file job queue. Blocks until it is done.
+pub fn transcribe_file(app: &AppHandle, path: &str) -> Result<String, String> {
+    let audio = read_audio_file(path)?
+        .to_mono()
+        .resample(WHISPER_SAMPLE_RATE);
+    let output = app
+        .state::<Arc<FileJobQueue>>()
+        .transcribe_file(audio.samples, path)
+        .map_err(|e| e.to_string())?;
+    Ok(output.text)
+}
+
+/// The latest `limit` history entries, newest first.
+pub async fn last_transcripts(app: &AppHandle, limit: usize) -> Result<Vec<HistoryEntry>, String> {
+    let hm = Arc::clone(&app.state::<Arc<HistoryManager>>());
+    let entries = hm.get_history_entries().await.map_err(|e| e.to_string())?;
+    Ok(entries.into_iter().take(limit).collect())
+}
+
+/// Text of the latest dictation, post-processed when it was.
+pub async fn last_transcript(app: &AppHandle) -> Result<Option<String>, String> {
+    Ok(last_transcripts(app, 1)
+        .await?
+        .into_iter()
+        .next()
+        .map(|entry| {
+            entry
+                .post_processed_text
+                .unwrap_or(entry.transcription_text)
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_url() {
+        assert_eq!(
+            parse_url("handy://start-dictation").unwrap().action,
+            UrlAction::StartDictation
+        );
+        assert_eq!(
+            parse_url("handy:toggle-dictation").unwrap().action,
+            UrlAction::ToggleDictation
+        );
+        let request = parse_url(
+            "handy://transcribe-file?path=%2FUsers%2Fme%2Fmemo%201.wav&x-success=shortcuts%3A%2F%2Fx-callback-url",
+        )
+        .unwrap();
+        assert_eq!(
+            request.action,
+            UrlAction::TranscribeFile("/Users/me/memo 1.wav".to_string())
+        );
+        assert_eq!(
+            request.success.as_deref(),
+            Some("shortcuts://x-callback-url")
+        );
+        assert!(parse_url("handy://transcribe-file").is_err());
+        assert!(
+            parse_url("handy://last-transcript?x-success=https%3A%2F%2Fexample.com%2Fcollect")
+                .is_err()
+        );
+        assert!(parse_url("handy://last-transcript?x-error=file%3A%2F%2F%2Ftmp").is_err());
+        assert!(parse_url("handy://format-disk").is_err());
+        assert!(parse_url("https://start-dictation").is_err());
+    }
+
+    #[test]
+    fn test_confirmation() {
+        let asks = |url: &str, recording: bool| confirmation(&parse_url(url).unwrap(), recording);
+        assert!(asks("handy://start-dictation", false).is_some());
+        assert!(asks("handy://toggle-dictation", false).is_some());
+        assert!(asks("handy://transcribe-file?path=%2Ftmp%2Fa.wav", false).is_some());
+        // Reading past dictations is confirmed even without a callback to send them to
+        assert!(asks("handy://last-transcript", false).is_some());
+        let message = asks(
+            "handy://last-transcript?x-success=shortcuts%3A%2F%2Fx-callback-url",
+            false,
+        )
+        .unwrap();
+        assert!(message.contains("last dictation") && message.contains("shortcuts://"));
+        // Any callback is confirmed, whatever the action
+        assert!(asks("handy://stop-dictation?x-error=raycast%3A%2F%2Fdone", true).is_some());
+        assert!(asks("handy://stop-dictation", true).is_none());
+        assert!(asks("handy://toggle-dictation", true).is_none());
+    }
+
+    #[test]
+    fn test_with_param() {
+        assert_eq!(
+            with_param(
+                "shortcuts://x-callback-url/ok?a=1",
+                "result",
+                "Hi there & bye"
+            )
+            .unwrap()
+            .as_str(),
+            "shortcuts://x-callback-url/ok?a=1&result=Hi+there+%26+bye"
+        );
+    }
+}
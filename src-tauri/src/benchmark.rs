@@ -0,0 +1,141 @@
+//! Compares the downloaded models on one clip: how fast each transcribes it on this machine,
+//! how much memory it takes and, given a reference transcript, how many words it gets wrong.
+
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::helpers::process_memory::PeakMemory;
+use crate::managers::model::ModelInfo;
+use crate::managers::transcription::TranscriptionManager;
+use log::{info, warn};
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Emitter};
+
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct ModelBenchmark {
+    pub model_id: String,
+    pub model_name: String,
+    pub load_ms: u64,
+    pub transcribe_ms: u64,
+    /// Transcription time over clip duration, below 1 is faster than real time
+    pub real_time_factor: f64,
+    /// How far the app's resident memory rose above what it used before while the model
+    /// loaded and ran, where the platform reports it
+    pub peak_ram_mb: Option<f64>,
+    pub text: String,
+    /// Share of reference words missed, swapped or added, when a reference was given
+    pub word_error_rate: Option<f64>,
+    /// Set when the model failed to load or transcribe, the measurements are then empty
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct BenchmarkProgress {
+    pub model_id: String,
+    pub completed: usize,
+    pub total: usize,
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|c| c.is_alphanumeric() || *c == '\'')
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Word-level edit distance between `reference` and `hypothesis` over the reference length,
+/// ignoring case and punctuation. `None` for an empty reference.
+pub fn word_error_rate(reference: &str, hypothesis: &str) -> Option<f64> {
+    let reference = words(reference);
+    if reference.is_empty() {
+        return None;
+    }
+    let hypothesis = words(hypothesis);
+    let edits = strsim::generic_levenshtein(&reference, &hypothesis);
+    Some(edits as f64 / reference.len() as f64)
+}
+
+/// Runs `samples`, 16 kHz mono, through each of `models` in turn, emitting
+/// `benchmark-progress` as each one finishes. A model that fails is reported with its error
+/// and doesn't stop the others.
+pub fn run(
+    app: &AppHandle,
+    tm: &TranscriptionManager,
+    models: &[ModelInfo],
+    samples: &[f32],
+    reference: Option<&str>,
+) -> Vec<ModelBenchmark> {
+    let clip_secs = samples.len() as f64 / WHISPER_SAMPLE_RATE as f64;
+    let mut results = Vec::with_capacity(models.len());
+
+    for (index, model) in models.iter().enumerate() {
+        info!("Benchmarking {} on {:.1}s of audio", model.id, clip_secs);
+        let memory = PeakMemory::start();
+        let outcome = tm.benchmark_model(&model.id, samples.to_vec());
+        let peak_ram_mb = memory
+            .finish()
+            .map(|bytes| bytes as f64 / (1024.0 * 1024.0));
+
+        let mut result = ModelBenchmark {
+            model_id: model.id.clone(),
+            model_name: model.name.clone(),
+            load_ms: 0,
+            transcribe_ms: 0,
+            real_time_factor: 0.0,
+            peak_ram_mb: None,
+            text: String::new(),
+            word_error_rate: None,
+            error: None,
+        };
+        match outcome {
+            Ok((output, load_time, transcribe_time)) => {
+                result.load_ms = load_time.as_millis() as u64;
+                result.transcribe_ms = transcribe_time.as_millis() as u64;
+                if clip_secs > 0.0 {
+                    result.real_time_factor = transcribe_time.as_secs_f64() / clip_secs;
+                }
+                result.peak_ram_mb = peak_ram_mb;
+                result.word_error_rate =
+                    reference.and_then(|reference| word_error_rate(reference, &output.text));
+                result.text = output.text;
+            }
+            Err(e) => {
+                warn!("Benchmark of {} failed: {}", model.id, e);
+                result.error = Some(e.to_string());
+            }
+        }
+        results.push(result);
+
+        let _ = app.emit(
+            "benchmark-progress",
+            BenchmarkProgress {
+                model_id: model.id.clone(),
+                completed: index + 1,
+                total: models.len(),
+            },
+        );
+    }
+
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_error_rate() {
+        let reference = "The quick brown fox jumps.";
+        assert_eq!(
+            word_error_rate(reference, "the quick brown fox jumps"),
+            Some(0.0)
+        );
+        // one substitution and one deletion out of five words
+        assert_eq!(word_error_rate(reference, "The quick frown fox"), Some(0.4));
+        assert_eq!(word_error_rate("", "anything"), None);
+    }
+}
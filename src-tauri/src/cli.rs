@@ -0,0 +1,248 @@
+//! Headless command line entry point.
+//!
+//! `handy transcribe <file|-> [--model id] [--format txt|json|srt]` reuses the regular
+//! `ModelManager` and `TranscriptionManager` (and therefore the user's downloaded models and
+//! settings) without showing any window, so recordings can be transcribed from scripts. The
+//! app it runs in has only the settings store: no windows, webview, tray or the state the
+//! desktop app manages.
+
+use crate::audio_toolkit::audio::decode_wav;
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::managers::model::ModelManager;
+use crate::managers::transcription::{
+    TranscriptSegment, TranscriptionManager, TranscriptionOutput,
+};
+use crate::settings::get_settings;
+use crate::transcription_error::TranscriptionError;
+use anyhow::Result;
+use std::io::{Cursor, Read, Write};
+use std::sync::Arc;
+use tauri::{AppHandle, RunEvent};
+
+const USAGE: &str = "Usage: handy transcribe <file.wav|-> [--model <id>] [--format txt|json|srt]";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Txt,
+    Json,
+    Srt,
+}
+
+#[derive(Debug)]
+struct TranscribeArgs {
+    input: String,
+    model: Option<String>,
+    format: OutputFormat,
+}
+
+/// Returns true when the process was started as `handy transcribe ...`.
+pub fn is_cli_invocation(args: &[String]) -> bool {
+    args.get(1).map(|a| a == "transcribe").unwrap_or(false)
+}
+
+/// Runs the `transcribe` subcommand and exits the process with an appropriate status code.
+pub fn run(args: Vec<String>) {
+    let parsed = match parse_args(&args[2..]) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    // The configured main window would start a webview
+    let mut context = crate::tauri_context();
+    context.config_mut().app.windows.clear();
+
+    let app = tauri::Builder::default()
+        .plugin(tauri_plugin_store::Builder::default().build())
+        .build(context)
+        .expect("error while building headless tauri application");
+
+    let mut parsed = Some(parsed);
+    app.run(move |app_handle, event| {
+        if let RunEvent::Ready = event {
+            #[cfg(target_os = "macos")]
+            {
+                let _ = app_handle.set_activation_policy(tauri::ActivationPolicy::Accessory);
+            }
+
+            if let Some(parsed) = parsed.take() {
+                let app_handle = app_handle.clone();
+                std::thread::spawn(move || {
+                    let code = match transcribe(&app_handle, &parsed) {
+                        Ok(()) => 0,
+                        Err(e) => {
+                            eprintln!("handy: {}", e);
+                            1
+                        }
+                    };
+                    app_handle.exit(code);
+                });
+            }
+        }
+    });
+}
+
+fn parse_args(args: &[String]) -> Result<TranscribeArgs, String> {
+    let mut input = None;
+    let mut model = None;
+    let mut format = OutputFormat::Txt;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--model" | "-m" => {
+                model = Some(
+                    iter.next()
+                        .ok_or_else(|| "--model requires a value".to_string())?
+                        .clone(),
+                );
+            }
+            "--format" | "-f" => {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| "--format requires a value".to_string())?;
+                format = match value.as_str() {
+                    "txt" => OutputFormat::Txt,
+                    "json" => OutputFormat::Json,
+                    "srt" => OutputFormat::Srt,
+                    other => return Err(format!("Unknown format '{}'", other)),
+                };
+            }
+            other if input.is_none() && (other == "-" || !other.starts_with('-')) => {
+                input = Some(other.to_string());
+            }
+            other => return Err(format!("Unexpected argument '{}'", other)),
+        }
+    }
+
+    Ok(TranscribeArgs {
+        input: input.ok_or_else(|| "Missing input file".to_string())?,
+        model,
+        format,
+    })
+}
+
+fn transcribe(app_handle: &AppHandle, args: &TranscribeArgs) -> Result<()> {
+    let model_manager = Arc::new(ModelManager::new(app_handle)?);
+    let transcription_manager = TranscriptionManager::new(app_handle, model_manager)?;
+
+    let model_id = match &args.model {
+        Some(id) => id.clone(),
+        None => get_settings(app_handle).selected_model,
+    };
+    if model_id.is_empty() {
+        anyhow::bail!("No model selected. Pass --model or pick one in the Handy settings.");
+    }
+    transcription_manager.load_model(&model_id)?;
+
+    let samples = if args.input == "-" {
+        let mut bytes = Vec::new();
+        std::io::stdin().read_to_end(&mut bytes)?;
+        read_wav(Cursor::new(bytes))?
+    } else {
+        read_wav(std::fs::File::open(&args.input)?)?
+    };
+    let duration = samples.len() as f32 / WHISPER_SAMPLE_RATE as f32;
+
+    let output = transcription_manager.transcribe_detailed(samples)?;
+
+    let rendered = match args.format {
+        OutputFormat::Txt => format!("{}\n", output.text),
+        OutputFormat::Json => format!(
+            "{}\n",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "model": model_id,
+                "duration": duration,
+                "text": output.text,
+                "segments": output.segments,
+            }))?
+        ),
+        OutputFormat::Srt => to_srt(&output, duration),
+    };
+
+    let mut stdout = std::io::stdout();
+    stdout.write_all(rendered.as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Decodes a WAV stream into 16 kHz mono f32 samples.
+pub(crate) fn read_wav<R: Read>(reader: R) -> Result<Vec<f32>, TranscriptionError> {
+    Ok(decode_wav(reader)
+        .map_err(TranscriptionError::audio_format)?
+        .to_mono()
+        .resample(WHISPER_SAMPLE_RATE)
+        .samples)
+}
+
+pub(crate) fn to_srt(output: &TranscriptionOutput, duration: f32) -> String {
+    let fallback;
+    let segments: &[TranscriptSegment] = if output.segments.is_empty() {
+        fallback = [TranscriptSegment {
+            start: 0.0,
+            end: duration,
+            text: output.text.clone(),
+            confidence: None,
+            words: Vec::new(),
+        }];
+        &fallback
+    } else {
+        &output.segments
+    };
+
+    let mut srt = String::new();
+    for (i, segment) in segments.iter().filter(|s| !s.text.is_empty()).enumerate() {
+        srt.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            format_srt_timestamp(segment.start),
+            format_srt_timestamp(segment.end),
+            segment.text
+        ));
+    }
+    srt
+}
+
+fn format_srt_timestamp(seconds: f32) -> String {
+    let total_ms = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02},{:03}",
+        total_ms / 3_600_000,
+        (total_ms / 60_000) % 60,
+        (total_ms / 1000) % 60,
+        total_ms % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let parsed = parse_args(&args(&["in.wav", "--model", "small", "--format", "srt"])).unwrap();
+        assert_eq!(parsed.input, "in.wav");
+        assert_eq!(parsed.model.as_deref(), Some("small"));
+        assert_eq!(parsed.format, OutputFormat::Srt);
+
+        let parsed = parse_args(&args(&["-"])).unwrap();
+        assert_eq!(parsed.input, "-");
+        assert_eq!(parsed.format, OutputFormat::Txt);
+
+        assert!(parse_args(&args(&[])).is_err());
+        assert!(parse_args(&args(&["in.wav", "--format", "doc"])).is_err());
+    }
+
+    #[test]
+    fn test_format_srt_timestamp() {
+        assert_eq!(format_srt_timestamp(0.0), "00:00:00,000");
+        assert_eq!(format_srt_timestamp(61.5), "00:01:01,500");
+        assert_eq!(format_srt_timestamp(3723.042), "01:02:03,042");
+    }
+}
@@ -1,22 +1,31 @@
-use crate::settings::{get_settings, ClipboardHandling, PasteMethod};
+use crate::audio_toolkit::RichText;
+use crate::helpers::caret_insert;
+use crate::helpers::focused_app::{self, FieldKind};
+use crate::settings::{
+    get_settings, ClipboardHandling, HumanizedTypingSettings, PasteMethod, TextFormatting,
+};
+use crate::shortcut;
 use enigo::Enigo;
 use enigo::Key;
 use enigo::Keyboard;
 use enigo::Settings;
-use log::info;
-use tauri::AppHandle;
+use log::{error, info, warn};
+use serde::Serialize;
+use specta::Type;
+use std::sync::{Mutex, PoisonError};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_clipboard_manager::ClipboardExt;
 
 #[cfg(target_os = "linux")]
-use crate::utils::is_wayland;
-#[cfg(target_os = "linux")]
-use std::process::Command;
+use crate::helpers::linux_input::{self, Keys};
 
 /// Sends a Ctrl+V or Cmd+V paste command using platform-specific virtual key codes.
 /// This ensures the paste works regardless of keyboard layout (e.g., Russian, AZERTY, DVORAK).
 fn send_paste_ctrl_v() -> Result<(), String> {
     #[cfg(target_os = "linux")]
-    if try_wayland_send_paste(&PasteMethod::CtrlV)? {
+    if linux_input::try_press(Keys::CtrlV, 1)? {
         return Ok(());
     }
 
@@ -48,11 +57,56 @@ fn send_paste_ctrl_v() -> Result<(), String> {
     Ok(())
 }
 
+/// Sends the app's undo shortcut, Ctrl+Z or Cmd+Z, by virtual key code like the paste.
+fn send_undo() -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    if linux_input::try_press(Keys::CtrlZ, 1)? {
+        return Ok(());
+    }
+
+    #[cfg(target_os = "macos")]
+    let (modifier_key, z_key_code) = (Key::Meta, Key::Other(6));
+    #[cfg(target_os = "windows")]
+    let (modifier_key, z_key_code) = (Key::Control, Key::Other(0x5A)); // VK_Z
+    #[cfg(target_os = "linux")]
+    let (modifier_key, z_key_code) = (Key::Control, Key::Unicode('z'));
+
+    let mut enigo = Enigo::new(&Settings::default())
+        .map_err(|e| format!("Failed to initialize Enigo: {}", e))?;
+    enigo
+        .key(modifier_key, enigo::Direction::Press)
+        .map_err(|e| format!("Failed to press modifier key: {}", e))?;
+    enigo
+        .key(z_key_code, enigo::Direction::Click)
+        .map_err(|e| format!("Failed to click Z key: {}", e))?;
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    enigo
+        .key(modifier_key, enigo::Direction::Release)
+        .map_err(|e| format!("Failed to release modifier key: {}", e))?;
+    Ok(())
+}
+
+fn send_backspaces(count: usize) -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    if linux_input::try_press(Keys::Backspace, count)? {
+        return Ok(());
+    }
+
+    let mut enigo = Enigo::new(&Settings::default())
+        .map_err(|e| format!("Failed to initialize Enigo: {}", e))?;
+    for _ in 0..count {
+        enigo
+            .key(Key::Backspace, enigo::Direction::Click)
+            .map_err(|e| format!("Failed to press Backspace: {}", e))?;
+    }
+    Ok(())
+}
+
 /// Sends a Shift+Insert paste command (Windows and Linux only).
 /// This is more universal for terminal applications and legacy software.
 fn send_paste_shift_insert() -> Result<(), String> {
     #[cfg(target_os = "linux")]
-    if try_wayland_send_paste(&PasteMethod::ShiftInsert)? {
+    if linux_input::try_press(Keys::ShiftInsert, 1)? {
         return Ok(());
     }
 
@@ -84,6 +138,11 @@ fn send_paste_shift_insert() -> Result<(), String> {
 /// Pastes text directly using the enigo text method.
 /// This tries to use system input methods if possible, otherwise simulates keystrokes one by one.
 fn paste_via_direct_input(text: &str) -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    if linux_input::try_type(text)? {
+        return Ok(());
+    }
+
     let mut enigo = Enigo::new(&Settings::default())
         .map_err(|e| format!("Failed to initialize Enigo: {}", e))?;
 
@@ -94,16 +153,107 @@ fn paste_via_direct_input(text: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Types text one character at a time with randomized, human-like delays and occasional
+/// pauses. Some remote desktops, exam platforms and web apps drop or flag text that arrives
+/// as a single instantaneous burst.
+fn paste_via_humanized_typing(text: &str, timing: &HumanizedTypingSettings) -> Result<(), String> {
+    let mut enigo = Enigo::new(&Settings::default())
+        .map_err(|e| format!("Failed to initialize Enigo: {}", e))?;
+    let mut jitter = Jitter::new();
+    let mut burst_remaining = jitter.burst_length(timing.burst_length);
+    let mut buf = [0u8; 4];
+
+    for ch in text.chars() {
+        enigo
+            .text(ch.encode_utf8(&mut buf))
+            .map_err(|e| format!("Failed to type character: {}", e))?;
+
+        let delay = timing.mean_delay_ms as f64 + jitter.normal() * timing.delay_jitter_ms as f64;
+        std::thread::sleep(Duration::from_millis(delay.max(0.0) as u64));
+
+        if timing.burst_length > 0 {
+            burst_remaining = burst_remaining.saturating_sub(1);
+            if burst_remaining == 0 {
+                let pause = timing.burst_pause_ms as f64 * (0.5 + jitter.uniform());
+                std::thread::sleep(Duration::from_millis(pause as u64));
+                burst_remaining = jitter.burst_length(timing.burst_length);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The humanized typing in progress. Later output waits for it, so texts don't interleave.
+static TYPING: Mutex<Option<JoinHandle<()>>> = Mutex::new(None);
+
+/// Types `text` on a background thread, after the typing already in progress.
+fn start_humanized_typing(text: String, timing: HumanizedTypingSettings) {
+    let mut typing = TYPING.lock().unwrap_or_else(PoisonError::into_inner);
+    let previous = typing.take();
+    *typing = Some(std::thread::spawn(move || {
+        if let Some(previous) = previous {
+            let _ = previous.join();
+        }
+        if let Err(e) = paste_via_humanized_typing(&text, &timing) {
+            error!("Humanized typing failed: {}", e);
+        }
+    }));
+}
+
+/// Blocks until the humanized typing in progress, if any, has finished.
+fn wait_for_typing() {
+    let typing = TYPING.lock().unwrap_or_else(PoisonError::into_inner).take();
+    if let Some(typing) = typing {
+        let _ = typing.join();
+    }
+}
+
+/// Small xorshift generator for typing delays, which don't need a real RNG.
+struct Jitter(u64);
+
+impl Jitter {
+    fn new() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0x9E37_79B9_7F4A_7C15);
+        Jitter(seed | 1)
+    }
+
+    /// Uniformly distributed in `[0, 1)`.
+    fn uniform(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Approximately standard normal (sum of twelve uniforms).
+    fn normal(&mut self) -> f64 {
+        (0..12).map(|_| self.uniform()).sum::<f64>() - 6.0
+    }
+
+    /// Varies the burst length by up to 50% either way so pauses don't fall on a fixed rhythm.
+    fn burst_length(&mut self, mean: u32) -> u32 {
+        ((mean as f64) * (0.5 + self.uniform())).round().max(1.0) as u32
+    }
+}
+
 /// Pastes text using the clipboard method with Ctrl+V/Cmd+V.
 /// Saves the current clipboard, writes the text, sends paste command, then restores the clipboard.
-fn paste_via_clipboard_ctrl_v(text: &str, app_handle: &AppHandle) -> Result<(), String> {
+/// When `html` is provided it is offered alongside `text`, so rich text targets pick up formatting.
+fn paste_via_clipboard_ctrl_v(
+    text: &str,
+    html: Option<&str>,
+    app_handle: &AppHandle,
+) -> Result<(), String> {
     let clipboard = app_handle.clipboard();
 
     // get the current clipboard content
     let clipboard_content = clipboard.read_text().unwrap_or_default();
 
-    clipboard
-        .write_text(text)
+    write_clipboard(app_handle, text, html)
         .map_err(|e| format!("Failed to write to clipboard: {}", e))?;
 
     // small delay to ensure the clipboard content has been written to
@@ -123,14 +273,17 @@ fn paste_via_clipboard_ctrl_v(text: &str, app_handle: &AppHandle) -> Result<(),
 
 /// Pastes text using the clipboard method with Shift+Insert (Windows/Linux only).
 /// Saves the current clipboard, writes the text, sends paste command, then restores the clipboard.
-fn paste_via_clipboard_shift_insert(text: &str, app_handle: &AppHandle) -> Result<(), String> {
+fn paste_via_clipboard_shift_insert(
+    text: &str,
+    html: Option<&str>,
+    app_handle: &AppHandle,
+) -> Result<(), String> {
     let clipboard = app_handle.clipboard();
 
     // get the current clipboard content
     let clipboard_content = clipboard.read_text().unwrap_or_default();
 
-    clipboard
-        .write_text(text)
+    write_clipboard(app_handle, text, html)
         .map_err(|e| format!("Failed to write to clipboard: {}", e))?;
 
     // small delay to ensure the clipboard content has been written to
@@ -148,91 +301,236 @@ fn paste_via_clipboard_shift_insert(text: &str, app_handle: &AppHandle) -> Resul
     Ok(())
 }
 
-/// Attempts to paste using Wayland-specific tools (`wtype` or `dotool`).
-/// Returns `Ok(true)` if a Wayland tool handled the paste, `Ok(false)` if not applicable,
-/// or `Err` on failure from the underlying tool.
-#[cfg(target_os = "linux")]
-fn try_wayland_send_paste(paste_method: &PasteMethod) -> Result<bool, String> {
-    if is_wayland() {
-        if is_wtype_available() {
-            send_paste_via_wtype(paste_method)?;
-            return Ok(true);
-        } else if is_dotool_available() {
-            send_paste_via_dotool(paste_method)?;
-            return Ok(true);
-        }
+/// Writes `text` to the clipboard, or `html` with `text` as the plain fallback when provided.
+fn write_clipboard(app_handle: &AppHandle, text: &str, html: Option<&str>) -> Result<(), String> {
+    let clipboard = app_handle.clipboard();
+    match html {
+        Some(html) => clipboard.write_html(html, Some(text)),
+        None => clipboard.write_text(text),
     }
-
-    Ok(false)
+    .map_err(|e| e.to_string())
 }
 
-/// Check if wtype is available (Wayland text input tool)
-#[cfg(target_os = "linux")]
-fn is_wtype_available() -> bool {
-    Command::new("which")
-        .arg("wtype")
-        .output()
-        .map(|output| output.status.success())
-        .unwrap_or(false)
+/// A long transcription being copied to the clipboard one part at a time, because some
+/// target apps silently truncate large pastes.
+#[derive(Default)]
+pub struct ChunkedCopy {
+    parts: Vec<String>,
+    next: usize,
 }
 
-/// Check if dotool is available (another Wayland text input tool)
-#[cfg(target_os = "linux")]
-fn is_dotool_available() -> bool {
-    Command::new("which")
-        .arg("dotool")
-        .output()
-        .map(|output| output.status.success())
-        .unwrap_or(false)
+pub type ManagedChunkedCopy = Mutex<ChunkedCopy>;
+
+/// Most pastes kept for undoing one after the other
+const MAX_UNDO_OUTPUTS: usize = 20;
+
+/// Said on its own, takes back the last paste instead of being pasted
+const UNDO_COMMANDS: &[&str] = &["scratch that", "undo that", "undo last segment"];
+
+/// What a paste put into the focused app, so it can be taken back.
+#[derive(Debug, PartialEq)]
+pub struct PastedOutput {
+    /// Characters typed or pasted, each removed by one backspace
+    chars: usize,
+    /// Rich text doesn't map to characters, so its paste is undone with the app's undo
+    rich_text: bool,
+    /// History entry the text came from, once it is saved
+    history_id: Option<i64>,
+    /// Application the text went into, when the platform can tell
+    app: Option<String>,
 }
 
-/// Paste using wtype and return a friendly error on failure.
-#[cfg(target_os = "linux")]
-fn send_paste_via_wtype(paste_method: &PasteMethod) -> Result<(), String> {
-    let args: Vec<&str> = match paste_method {
-        PasteMethod::CtrlV => vec!["-M", "ctrl", "-k", "v"],
-        PasteMethod::ShiftInsert => vec!["-M", "shift", "-k", "Insert"],
-        _ => return Err("Unsupported paste method".into()),
-    };
+/// The pastes made one after the other into the same app, the latest last. Undoing them in
+/// reverse order keeps the document as it was before each one.
+pub type ManagedOutputStack = Mutex<Vec<PastedOutput>>;
 
-    let output = Command::new("wtype")
-        .args(&args)
-        .output()
-        .map_err(|e| format!("Failed to execute wtype: {}", e))?;
+/// Adds `output` to `stack`. Pastes into another app are dropped, their text can't be
+/// reached from the new one.
+fn push_output(stack: &mut Vec<PastedOutput>, output: PastedOutput) {
+    if stack.last().is_some_and(|last| last.app != output.app) {
+        stack.clear();
+    }
+    stack.push(output);
+    if stack.len() > MAX_UNDO_OUTPUTS {
+        stack.remove(0);
+    }
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("wtype failed: {}", stderr));
+/// Links the last output to the history entry it came from.
+pub fn link_last_output(app_handle: &AppHandle, history_id: i64) {
+    if let Some(output) = app_handle
+        .state::<ManagedOutputStack>()
+        .lock()
+        .unwrap()
+        .last_mut()
+    {
+        output.history_id = Some(history_id);
     }
+}
+
+/// Removes the text of the last paste still on the stack from the focused app, with
+/// backspaces or, for rich text, the app's undo. Repeated calls take back earlier pastes.
+/// Only works while the cursor is still right after that text, and not at all once another
+/// app is focused. Returns the history entry the text came from, if known.
+pub fn undo_last_output(app_handle: &AppHandle) -> Result<Option<i64>, String> {
+    let stack = app_handle.state::<ManagedOutputStack>();
+    let mut stack = stack.lock().unwrap();
+    let output = stack.last().ok_or("Nothing to undo")?;
+    if output.app.is_some() && focused_app::focused_app() != output.app {
+        return Err("The text went into another application, switch back to undo it".to_string());
+    }
+    let output = stack.pop().ok_or("Nothing to undo")?;
+    info!(
+        "Undoing an output of {} characters, {} more can be undone",
+        output.chars,
+        stack.len()
+    );
+    if output.rich_text {
+        send_undo()?;
+    } else {
+        send_backspaces(output.chars)?;
+    }
+    Ok(output.history_id)
+}
+
+/// Whether `text` is only a spoken undo command, e.g. "Scratch that."
+pub fn is_undo_command(text: &str) -> bool {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    !words.is_empty() && UNDO_COMMANDS.contains(&words.join(" ").as_str())
+}
 
+/// Payload of the `clipboard-chunk-copied` event.
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct ClipboardChunkProgress {
+    /// 1-based number of the part now on the clipboard
+    pub part: usize,
+    pub total: usize,
+}
+
+/// Puts the first part of `text` on the clipboard and queues the rest for
+/// `copy_next_chunk`, replacing any chunked copy still in progress.
+fn start_chunked_copy(app_handle: &AppHandle, text: &str, max_chars: usize) -> Result<(), String> {
+    let parts = split_into_chunks(text, max_chars);
+    info!(
+        "Copying transcription to the clipboard in {} parts",
+        parts.len()
+    );
+    *app_handle.state::<ManagedChunkedCopy>().lock().unwrap() = ChunkedCopy { parts, next: 0 };
+
+    shortcut::register_copy_next_part_shortcut(app_handle);
+    copy_next_chunk(app_handle)?;
     Ok(())
 }
 
-/// Paste using dotool and return a friendly error on failure.
-#[cfg(target_os = "linux")]
-fn send_paste_via_dotool(paste_method: &PasteMethod) -> Result<(), String> {
-    let command;
-    match paste_method {
-        PasteMethod::CtrlV => command = "echo key ctrl+v | dotool",
-        PasteMethod::ShiftInsert => command = "echo key shift+insert | dotool",
-        _ => return Err("Unsupported paste method".into()),
+/// Copies the next part of a chunked copy to the clipboard. Returns `None` when there is
+/// nothing left to copy.
+pub fn copy_next_chunk(app_handle: &AppHandle) -> Result<Option<ClipboardChunkProgress>, String> {
+    let state = app_handle.state::<ManagedChunkedCopy>();
+    let mut chunked = state.lock().unwrap();
+    let Some(part) = chunked.parts.get(chunked.next) else {
+        return Ok(None);
+    };
+
+    app_handle
+        .clipboard()
+        .write_text(part)
+        .map_err(|e| format!("Failed to copy to clipboard: {}", e))?;
+    chunked.next += 1;
+
+    let progress = ClipboardChunkProgress {
+        part: chunked.next,
+        total: chunked.parts.len(),
+    };
+    if chunked.next == chunked.parts.len() {
+        *chunked = ChunkedCopy::default();
+        shortcut::unregister_copy_next_part_shortcut(app_handle);
     }
-    let output = Command::new("sh")
-        .arg("-c")
-        .arg(command)
-        .output()
-        .map_err(|e| format!("Failed to execute dotool: {}", e))?;
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("dotool failed: {}", stderr));
+    drop(chunked);
+
+    if let Err(e) = app_handle.emit("clipboard-chunk-copied", &progress) {
+        warn!("Failed to emit clipboard-chunk-copied event: {}", e);
     }
+    Ok(Some(progress))
+}
 
-    Ok(())
+/// Splits `text` into parts of at most `max_chars` characters, preferring to end a part at a
+/// paragraph, line, sentence or word break. The parts join back into `text` unchanged.
+fn split_into_chunks(text: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut parts = Vec::new();
+    let mut rest = text;
+
+    while let Some((limit, _)) = rest.char_indices().nth(max_chars) {
+        let window = &rest[..limit];
+        // Only break early if the part keeps at least half of its allowed size
+        let min_cut = window
+            .char_indices()
+            .nth(max_chars / 2)
+            .map_or(0, |(i, _)| i);
+        let cut = ["\n\n", "\n", ". ", "? ", "! ", " "]
+            .iter()
+            .find_map(|sep| {
+                window
+                    .rfind(sep)
+                    .map(|i| i + sep.len())
+                    .filter(|&i| i > min_cut)
+            })
+            .unwrap_or(limit);
+
+        parts.push(rest[..cut].to_string());
+        rest = &rest[cut..];
+    }
+
+    if !rest.is_empty() {
+        parts.push(rest.to_string());
+    }
+    parts
 }
 
 pub fn paste(text: String, app_handle: AppHandle) -> Result<(), String> {
     let settings = get_settings(&app_handle);
     let paste_method = settings.paste_method;
+    if paste_method != PasteMethod::None
+        && crate::utils::blocked_by_secure_field(&app_handle, "paste")
+    {
+        return Err("Refused to paste into a password field".to_string());
+    }
+    let field = if settings.fit_output_to_field {
+        focused_app::focused_field_kind()
+    } else {
+        None
+    };
+    let typed = matches!(
+        paste_method,
+        PasteMethod::Direct | PasteMethod::HumanizedTyping
+    );
+
+    // Interpret spoken formatting commands. Rich text is only offered through the clipboard;
+    // direct input and accessibility insertion can't carry HTML, so they fall back to the
+    // Markdown rendering. Code and single-line fields get the text as it was spoken.
+    let formatting = match field {
+        Some(FieldKind::Code | FieldKind::SingleLine) => TextFormatting::Plain,
+        _ => settings.text_formatting,
+    };
+    let (text, html) = match formatting {
+        TextFormatting::Plain => (text, None),
+        TextFormatting::Markdown => (RichText::parse(&text).to_markdown(), None),
+        TextFormatting::RichText => {
+            let doc = RichText::parse(&text);
+            let html = match paste_method {
+                PasteMethod::Direct | PasteMethod::HumanizedTyping | PasteMethod::Accessibility => {
+                    None
+                }
+                _ => Some(doc.to_html()),
+            };
+            (doc.to_markdown(), html)
+        }
+    };
+    let text = fit_to_field(text, field, typed);
 
     // Append trailing space if setting is enabled
     let text = if settings.append_trailing_space {
@@ -243,24 +541,162 @@ pub fn paste(text: String, app_handle: AppHandle) -> Result<(), String> {
 
     info!("Using paste method: {:?}", paste_method);
 
+    // A paste that fails halfway can't be undone reliably, and neither can the pastes before
+    // it, so the stack is only put back once this one went through
+    let mut outputs =
+        std::mem::take(&mut *app_handle.state::<ManagedOutputStack>().lock().unwrap());
+    let output = (paste_method != PasteMethod::None).then(|| PastedOutput {
+        chars: text.chars().count(),
+        rich_text: html.is_some(),
+        history_id: None,
+        app: focused_app::focused_app(),
+    });
+
+    if !matches!(
+        paste_method,
+        PasteMethod::None | PasteMethod::HumanizedTyping
+    ) {
+        wait_for_typing();
+    }
+
     // Perform the paste operation
     match paste_method {
         PasteMethod::None => {
             // Intentionally do not perform any paste action; history/clipboard update
             info!("PasteMethod::None selected - skipping paste action");
         }
-        PasteMethod::CtrlV => paste_via_clipboard_ctrl_v(&text, &app_handle)?,
+        PasteMethod::CtrlV => paste_via_clipboard_ctrl_v(&text, html.as_deref(), &app_handle)?,
         PasteMethod::Direct => paste_via_direct_input(&text)?,
-        PasteMethod::ShiftInsert => paste_via_clipboard_shift_insert(&text, &app_handle)?,
+        PasteMethod::Accessibility => {
+            if !caret_insert::insert_at_caret(&text) {
+                info!("Accessibility insertion unavailable, pasting instead");
+                paste_via_clipboard_ctrl_v(&text, None, &app_handle)?
+            }
+        }
+        PasteMethod::ShiftInsert => {
+            paste_via_clipboard_shift_insert(&text, html.as_deref(), &app_handle)?
+        }
+        PasteMethod::HumanizedTyping => {
+            // Typing can take many seconds, so don't hold up the main thread while it runs
+            start_humanized_typing(text.clone(), settings.humanized_typing.clone())
+        }
     }
 
+    if let Some(output) = output {
+        push_output(&mut outputs, output);
+    }
+    *app_handle.state::<ManagedOutputStack>().lock().unwrap() = outputs;
+
     // After pasting, optionally copy to clipboard based on settings
     if settings.clipboard_handling == ClipboardHandling::CopyToClipboard {
-        let clipboard = app_handle.clipboard();
-        clipboard
-            .write_text(&text)
-            .map_err(|e| format!("Failed to copy to clipboard: {}", e))?;
+        match settings.clipboard_chunk_chars {
+            Some(max_chars) if text.chars().count() > max_chars => {
+                start_chunked_copy(&app_handle, &text, max_chars)?
+            }
+            _ => write_clipboard(&app_handle, &text, html.as_deref())
+                .map_err(|e| format!("Failed to copy to clipboard: {}", e))?,
+        }
     }
 
     Ok(())
 }
+
+/// Adapts `text` to the focused field. A single-line field would submit or drop everything
+/// after a newline, so lines are joined. Code editors indent each typed line themselves, so
+/// the indentation of typed lines is left to them and trailing spaces are dropped.
+fn fit_to_field(text: String, field: Option<FieldKind>, typed: bool) -> String {
+    match field {
+        Some(FieldKind::SingleLine) => text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(" "),
+        Some(FieldKind::Code) => text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                if typed && i > 0 {
+                    line.trim()
+                } else {
+                    line.trim_end()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Some(FieldKind::MultiLine) | None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_into_chunks_breaks_at_sentences() {
+        let text = "First sentence here. Second one follows. And a third to finish.";
+        let parts = split_into_chunks(text, 30);
+
+        assert_eq!(parts.concat(), text);
+        assert!(parts.iter().all(|p| p.chars().count() <= 30));
+        assert_eq!(parts[0], "First sentence here. ");
+
+        // no break available, so the text is cut at the limit
+        assert_eq!(split_into_chunks("abcdefgh", 3), vec!["abc", "def", "gh"]);
+        assert_eq!(split_into_chunks("short", 30), vec!["short"]);
+    }
+
+    #[test]
+    fn test_fit_to_field() {
+        let text = "First line\n\n  second line  \n    indented".to_string();
+        assert_eq!(
+            fit_to_field(text.clone(), Some(FieldKind::SingleLine), false),
+            "First line second line indented"
+        );
+        assert_eq!(
+            fit_to_field(text.clone(), Some(FieldKind::Code), false),
+            "First line\n\n  second line\n    indented"
+        );
+        assert_eq!(
+            fit_to_field(text.clone(), Some(FieldKind::Code), true),
+            "First line\n\nsecond line\nindented"
+        );
+        assert_eq!(fit_to_field(text.clone(), None, true), text);
+    }
+
+    fn output(chars: usize, app: &str) -> PastedOutput {
+        PastedOutput {
+            chars,
+            rich_text: false,
+            history_id: None,
+            app: Some(app.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_push_output_keeps_one_app() {
+        let mut stack = Vec::new();
+        push_output(&mut stack, output(1, "Notes"));
+        push_output(&mut stack, output(2, "Notes"));
+        assert_eq!(stack.len(), 2);
+
+        // Text pasted elsewhere can't be undone from the new app
+        push_output(&mut stack, output(3, "Mail"));
+        assert_eq!(stack, vec![output(3, "Mail")]);
+
+        for chars in 0..MAX_UNDO_OUTPUTS {
+            push_output(&mut stack, output(chars, "Mail"));
+        }
+        assert_eq!(stack.len(), MAX_UNDO_OUTPUTS);
+        assert_eq!(stack[0].chars, 0);
+    }
+
+    #[test]
+    fn test_is_undo_command() {
+        assert!(is_undo_command("Scratch that."));
+        assert!(is_undo_command("  undo that! "));
+        assert!(is_undo_command("Undo last segment"));
+        assert!(!is_undo_command("Scratch that idea, let's go with plan B."));
+        assert!(!is_undo_command(""));
+    }
+}
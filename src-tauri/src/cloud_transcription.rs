@@ -0,0 +1,366 @@
+//! Network transcription engines (OpenAI, Deepgram, Azure Speech) for users whose hardware
+//! can't run the larger local models. They are only used when selected in the settings, and
+//! requests are retried on transient failures before the caller falls back to the local model.
+
+use crate::audio_toolkit::encode_wav;
+use crate::managers::transcription::{
+    TranscriptSegment, TranscriptionOutput, VocabularyBias, WordConfidence,
+};
+use crate::settings::{AppSettings, TranscriptionProvider};
+use anyhow::{anyhow, Result};
+use log::{debug, warn};
+use reqwest::multipart::{Form, Part};
+use reqwest::{RequestBuilder, StatusCode};
+use serde::Serialize;
+use serde_json::Value;
+use specta::Type;
+use std::time::Duration;
+
+const MAX_ATTEMPTS: u32 = 3;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// Deepgram rejects requests with more keywords than this
+const MAX_DEEPGRAM_KEYWORDS: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct CloudProviderInfo {
+    pub id: String,
+    pub name: String,
+    pub privacy_notice: String,
+    pub has_api_key: bool,
+    /// Whether transcripts come with confidence scores, so low-confidence words can be
+    /// flagged. The local models don't report any.
+    pub reports_confidence: bool,
+}
+
+pub fn provider_info(provider: TranscriptionProvider) -> CloudProviderInfo {
+    let name = match provider {
+        TranscriptionProvider::Local => "Local model",
+        TranscriptionProvider::OpenAi => "OpenAI",
+        TranscriptionProvider::Deepgram => "Deepgram",
+        TranscriptionProvider::Azure => "Azure Speech",
+    };
+    let privacy_notice = match provider {
+        TranscriptionProvider::Local => "Audio never leaves this computer.".to_string(),
+        _ => format!(
+            "Recordings are uploaded to {} for transcription and are subject to their privacy policy.",
+            name
+        ),
+    };
+
+    CloudProviderInfo {
+        id: provider.id().to_string(),
+        name: name.to_string(),
+        privacy_notice,
+        has_api_key: provider == TranscriptionProvider::Local
+            || crate::secrets::get_api_key(provider.id()).is_some(),
+        reports_confidence: reports_confidence(provider),
+    }
+}
+
+/// Whether `provider` scores what it transcribes: OpenAI per segment and Deepgram per word.
+/// Azure's short-audio endpoint and transcribe-rs, which runs the local models, report no
+/// confidence, so with them nothing is flagged and sessions have no average confidence.
+pub fn reports_confidence(provider: TranscriptionProvider) -> bool {
+    matches!(
+        provider,
+        TranscriptionProvider::OpenAi | TranscriptionProvider::Deepgram
+    )
+}
+
+/// Whether `provider` can be biased toward the custom words: OpenAI through its prompt and
+/// Deepgram through keyword boosting. Azure's short-audio endpoint has no such option.
+pub fn supports_vocabulary_bias(provider: TranscriptionProvider) -> bool {
+    matches!(
+        provider,
+        TranscriptionProvider::OpenAi | TranscriptionProvider::Deepgram
+    )
+}
+
+pub async fn transcribe(
+    provider: TranscriptionProvider,
+    api_key: &str,
+    audio: &[f32],
+    settings: &AppSettings,
+) -> Result<TranscriptionOutput> {
+    let wav = encode_wav(audio)?;
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()?;
+    let language = settings.selected_language.as_str();
+    let bias = VocabularyBias::from_settings(settings);
+
+    debug!("Sending {} bytes of audio to {}", wav.len(), provider.id());
+
+    match provider {
+        TranscriptionProvider::Local => Err(anyhow!("Local transcription is not a cloud provider")),
+        TranscriptionProvider::OpenAi => {
+            transcribe_openai(&client, api_key, wav, language, bias.as_ref()).await
+        }
+        TranscriptionProvider::Deepgram => {
+            transcribe_deepgram(&client, api_key, wav, language, bias.as_ref()).await
+        }
+        TranscriptionProvider::Azure => {
+            transcribe_azure(
+                &client,
+                api_key,
+                wav,
+                language,
+                &settings.azure_speech_region,
+            )
+            .await
+        }
+    }
+}
+
+/// Sends the request built by `request`, retrying timeouts, connection errors, rate limits
+/// and server errors with exponential backoff. Client errors such as a bad API key fail fast.
+async fn send_with_retry(request: impl Fn() -> RequestBuilder) -> Result<Value> {
+    let mut attempt = 1;
+    loop {
+        let (error, retryable) = match request().send().await {
+            Ok(response) if response.status().is_success() => return Ok(response.json().await?),
+            Ok(response) => {
+                let status = response.status();
+                let body = response.text().await.unwrap_or_default();
+                (
+                    anyhow!("HTTP {}: {}", status, body),
+                    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
+                )
+            }
+            Err(e) => {
+                let retryable = e.is_timeout() || e.is_connect();
+                (e.into(), retryable)
+            }
+        };
+
+        if !retryable || attempt >= MAX_ATTEMPTS {
+            return Err(error);
+        }
+
+        let delay = Duration::from_millis(500 * 2u64.pow(attempt - 1));
+        warn!(
+            "Cloud transcription attempt {} failed ({}), retrying in {}ms",
+            attempt,
+            error,
+            delay.as_millis()
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// ISO 639-1 code for providers that don't understand script variants like `zh-Hans`.
+fn base_language(language: &str) -> Option<&str> {
+    match language {
+        "auto" => None,
+        other => Some(other.split('-').next().unwrap_or(other)),
+    }
+}
+
+async fn transcribe_openai(
+    client: &reqwest::Client,
+    api_key: &str,
+    wav: Vec<u8>,
+    language: &str,
+    bias: Option<&VocabularyBias>,
+) -> Result<TranscriptionOutput> {
+    let language = base_language(language);
+    let prompt = bias.map(VocabularyBias::prompt);
+    let response = send_with_retry(|| {
+        let mut form = Form::new()
+            .part("file", Part::bytes(wav.clone()).file_name("audio.wav"))
+            .text("model", "whisper-1")
+            .text("response_format", "verbose_json");
+        if let Some(language) = language {
+            form = form.text("language", language.to_string());
+        }
+        if let Some(prompt) = &prompt {
+            form = form.text("prompt", prompt.clone());
+        }
+        client
+            .post("https://api.openai.com/v1/audio/transcriptions")
+            .bearer_auth(api_key)
+            .multipart(form)
+    })
+    .await?;
+
+    let segments = response["segments"]
+        .as_array()
+        .map(|segments| {
+            segments
+                .iter()
+                .map(|segment| TranscriptSegment {
+                    start: segment["start"].as_f64().unwrap_or(0.0) as f32,
+                    end: segment["end"].as_f64().unwrap_or(0.0) as f32,
+                    text: segment["text"].as_str().unwrap_or("").trim().to_string(),
+                    // the mean token log probability, as a probability
+                    confidence: segment["avg_logprob"]
+                        .as_f64()
+                        .map(|logprob| logprob.exp().clamp(0.0, 1.0) as f32),
+                    words: Vec::new(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(TranscriptionOutput {
+        text: response["text"].as_str().unwrap_or("").to_string(),
+        segments,
+    })
+}
+
+async fn transcribe_deepgram(
+    client: &reqwest::Client,
+    api_key: &str,
+    wav: Vec<u8>,
+    language: &str,
+    bias: Option<&VocabularyBias>,
+) -> Result<TranscriptionOutput> {
+    let mut url =
+        "https://api.deepgram.com/v1/listen?model=nova-2&smart_format=true&utterances=true"
+            .to_string();
+    match base_language(language) {
+        Some(language) => url.push_str(&format!("&language={}", language)),
+        None => url.push_str("&detect_language=true"),
+    }
+    // Keywords are single words with an intensifier, phrases are boosted word by word
+    let keywords: Vec<(&str, String)> = bias
+        .map(|bias| {
+            bias.words
+                .iter()
+                .flat_map(|word| word.split_whitespace())
+                .take(MAX_DEEPGRAM_KEYWORDS)
+                .map(|word| ("keywords", format!("{}:{}", word, bias.boost)))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let response = send_with_retry(|| {
+        client
+            .post(&url)
+            .query(&keywords)
+            .header("Authorization", format!("Token {}", api_key))
+            .header("Content-Type", "audio/wav")
+            .body(wav.clone())
+    })
+    .await?;
+
+    let text = response["results"]["channels"][0]["alternatives"][0]["transcript"]
+        .as_str()
+        .unwrap_or("")
+        .to_string();
+    let segments = response["results"]["utterances"]
+        .as_array()
+        .map(|utterances| {
+            utterances
+                .iter()
+                .map(|utterance| TranscriptSegment {
+                    start: utterance["start"].as_f64().unwrap_or(0.0) as f32,
+                    end: utterance["end"].as_f64().unwrap_or(0.0) as f32,
+                    text: utterance["transcript"].as_str().unwrap_or("").to_string(),
+                    confidence: utterance["confidence"].as_f64().map(|c| c as f32),
+                    words: deepgram_words(&utterance["words"]),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(TranscriptionOutput { text, segments })
+}
+
+fn deepgram_words(words: &Value) -> Vec<WordConfidence> {
+    words
+        .as_array()
+        .map(|words| {
+            words
+                .iter()
+                .filter_map(|word| {
+                    // smart_format adds punctuation and casing to `punctuated_word` only
+                    let text = word["punctuated_word"]
+                        .as_str()
+                        .or_else(|| word["word"].as_str())?;
+                    Some(WordConfidence {
+                        text: text.to_string(),
+                        confidence: word["confidence"].as_f64()? as f32,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Azure's short-audio endpoint needs a full locale rather than a language code.
+fn azure_locale(language: &str) -> String {
+    match language {
+        "auto" | "en" => "en-US",
+        "zh-Hans" | "zh" => "zh-CN",
+        "zh-Hant" => "zh-TW",
+        "de" => "de-DE",
+        "es" => "es-ES",
+        "fr" => "fr-FR",
+        "it" => "it-IT",
+        "ja" => "ja-JP",
+        "ko" => "ko-KR",
+        "nl" => "nl-NL",
+        "pl" => "pl-PL",
+        "pt" => "pt-BR",
+        "ru" => "ru-RU",
+        "uk" => "uk-UA",
+        other if other.contains('-') => other,
+        other => return format!("{}-{}", other, other.to_uppercase()),
+    }
+    .to_string()
+}
+
+async fn transcribe_azure(
+    client: &reqwest::Client,
+    api_key: &str,
+    wav: Vec<u8>,
+    language: &str,
+    region: &str,
+) -> Result<TranscriptionOutput> {
+    let url = format!(
+        "https://{}.stt.speech.microsoft.com/speech/recognition/conversation/cognitiveservices/v1?language={}&format=simple",
+        region.trim(),
+        azure_locale(language)
+    );
+
+    let response = send_with_retry(|| {
+        client
+            .post(&url)
+            .header("Ocp-Apim-Subscription-Key", api_key)
+            .header(
+                "Content-Type",
+                "audio/wav; codecs=audio/pcm; samplerate=16000",
+            )
+            .body(wav.clone())
+    })
+    .await?;
+
+    match response["RecognitionStatus"].as_str() {
+        Some("Success") => {}
+        Some("NoMatch") | Some("InitialSilenceTimeout") => {
+            return Ok(TranscriptionOutput {
+                text: String::new(),
+                segments: Vec::new(),
+            })
+        }
+        status => return Err(anyhow!("Azure recognition failed: {:?}", status)),
+    }
+
+    // Offsets are reported in 100ns ticks
+    let text = response["DisplayText"].as_str().unwrap_or("").to_string();
+    let start = response["Offset"].as_f64().unwrap_or(0.0) / 1e7;
+    let end = start + response["Duration"].as_f64().unwrap_or(0.0) / 1e7;
+
+    Ok(TranscriptionOutput {
+        segments: vec![TranscriptSegment {
+            start: start as f32,
+            end: end as f32,
+            text: text.clone(),
+            confidence: None,
+            words: Vec::new(),
+        }],
+        text,
+    })
+}
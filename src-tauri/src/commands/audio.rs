@@ -1,11 +1,22 @@
 use crate::audio_feedback;
-use crate::audio_toolkit::audio::{list_input_devices, list_output_devices};
+use crate::audio_toolkit::audio::{
+    decode_wav, list_input_devices, list_output_devices, write_wav, AudioBuffer, MAX_HIGH_PASS_HZ,
+    MAX_INPUT_GAIN, MIN_HIGH_PASS_HZ, MIN_INPUT_GAIN,
+};
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
 use crate::managers::audio::{AudioRecordingManager, MicrophoneMode};
-use crate::settings::{get_settings, write_settings};
+use crate::managers::captions::{CaptionManager, CaptionStatus};
+use crate::managers::file_jobs::{FileJobQueue, InterruptedFileJob};
+use crate::managers::transcription::TranscriptionOutput;
+use crate::power_policy;
+use crate::self_test::{self, SelfTestReport};
+use crate::settings::{get_settings, write_settings, CaptionSource, EndpointSensitivity};
+use crate::setup_wizard::{self, Microphone, TestTakeReport};
 use log::warn;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 use std::sync::Arc;
+use std::time::Duration;
 use tauri::{AppHandle, Manager};
 
 #[derive(Serialize, Type)]
@@ -87,6 +98,146 @@ pub fn get_available_microphones() -> Result<Vec<AudioDevice>, String> {
     Ok(result)
 }
 
+/// How many `audio-level` events are sent per second while recording, 0 disables them.
+#[tauri::command]
+#[specta::specta]
+pub fn set_audio_level_rate(app: AppHandle, readings_per_second: u32) -> Result<(), String> {
+    let readings_per_second = readings_per_second.min(60);
+    let mut settings = get_settings(&app);
+    settings.audio_level_rate_hz = readings_per_second;
+    let effective_rate = power_policy::audio_level_rate_hz(&settings);
+    write_settings(&app, settings);
+
+    let rm = app.state::<Arc<AudioRecordingManager>>();
+    rm.update_audio_level_rate(effective_rate);
+    Ok(())
+}
+
+/// Multiplier applied to the microphone signal, clamped to the supported range.
+#[tauri::command]
+#[specta::specta]
+pub fn set_input_gain(app: AppHandle, gain: f32) -> Result<(), String> {
+    if !gain.is_finite() {
+        return Err("Gain must be a number".to_string());
+    }
+    let mut settings = get_settings(&app);
+    settings.input_gain = gain.clamp(MIN_INPUT_GAIN, MAX_INPUT_GAIN);
+    write_settings(&app, settings);
+
+    app.state::<Arc<AudioRecordingManager>>().update_gain();
+    Ok(())
+}
+
+/// Seconds of speech after which the live transcription closes an utterance even if the
+/// speaker never pauses, `None` waits for a pause.
+#[tauri::command]
+#[specta::specta]
+pub fn set_max_utterance_duration(app: AppHandle, seconds: Option<u32>) -> Result<(), String> {
+    if seconds == Some(0) {
+        return Err("The maximum utterance duration must be at least 1 second".to_string());
+    }
+    let mut settings = get_settings(&app);
+    settings.max_utterance_secs = seconds;
+    write_settings(&app, settings);
+
+    app.state::<Arc<AudioRecordingManager>>()
+        .update_max_utterance();
+    Ok(())
+}
+
+/// How soon a pause ends an utterance, and whether only once its live text reads as a
+/// finished sentence. Unfinished ones then end after `unfinished_pause_ms` of silence.
+#[tauri::command]
+#[specta::specta]
+pub fn set_endpointing(
+    app: AppHandle,
+    sensitivity: EndpointSensitivity,
+    semantic: bool,
+    unfinished_pause_ms: u32,
+) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.endpoint_sensitivity = sensitivity;
+    settings.semantic_endpointing = semantic;
+    settings.unfinished_pause_ms = unfinished_pause_ms;
+    write_settings(&app, settings);
+
+    app.state::<Arc<AudioRecordingManager>>()
+        .update_endpointing();
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_agc_enabled(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.agc_enabled = enabled;
+    write_settings(&app, settings);
+
+    app.state::<Arc<AudioRecordingManager>>().update_gain();
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_pre_emphasis(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.pre_emphasis = enabled;
+    write_settings(&app, settings);
+
+    app.state::<Arc<AudioRecordingManager>>().update_filter();
+    Ok(())
+}
+
+/// Cutoff of the high-pass filter that removes rumble and plosives, `None` turns it off.
+#[tauri::command]
+#[specta::specta]
+pub fn set_high_pass_cutoff(app: AppHandle, cutoff_hz: Option<u32>) -> Result<(), String> {
+    if let Some(hz) = cutoff_hz {
+        if !(MIN_HIGH_PASS_HZ..=MAX_HIGH_PASS_HZ).contains(&(hz as f32)) {
+            return Err(format!(
+                "The high-pass cutoff must be between {} and {} Hz",
+                MIN_HIGH_PASS_HZ, MAX_HIGH_PASS_HZ
+            ));
+        }
+    }
+    let mut settings = get_settings(&app);
+    settings.high_pass_cutoff_hz = cutoff_hz;
+    write_settings(&app, settings);
+
+    app.state::<Arc<AudioRecordingManager>>().update_filter();
+    Ok(())
+}
+
+#[derive(Serialize, Type)]
+pub struct GainCalibration {
+    pub recommended_gain: f32,
+    pub speech_rms: f32,
+    pub noise_floor: f32,
+    pub peak: f32,
+}
+
+const CALIBRATION_DURATION: Duration = Duration::from_secs(3);
+
+/// Records three seconds from the microphone and recommends an input gain. The user should
+/// speak normally while it runs; the recommendation is not applied automatically.
+#[tauri::command]
+#[specta::specta]
+pub async fn calibrate_input_gain(app: AppHandle) -> Result<GainCalibration, String> {
+    let rm = app.state::<Arc<AudioRecordingManager>>().inner().clone();
+    let recommendation =
+        tauri::async_runtime::spawn_blocking(move || rm.calibrate_gain(CALIBRATION_DURATION))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| e.to_string())?;
+
+    Ok(GainCalibration {
+        recommended_gain: recommendation.gain,
+        speech_rms: recommendation.speech_rms,
+        noise_floor: recommendation.noise_floor,
+        peak: recommendation.peak,
+    })
+}
+
 #[tauri::command]
 #[specta::specta]
 pub fn set_selected_microphone(app: AppHandle, device_name: String) -> Result<(), String> {
@@ -193,3 +344,226 @@ pub fn get_clamshell_microphone(app: AppHandle) -> Result<String, String> {
         .clamshell_microphone
         .unwrap_or_else(|| "default".to_string()))
 }
+
+const MIN_CONVERSION_RATE: u32 = 8_000;
+const MAX_CONVERSION_RATE: u32 = 192_000;
+
+#[derive(Serialize, Type)]
+pub struct AudioFileInfo {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub duration_secs: f32,
+    /// Whether the file is already 16 kHz mono, the format the models expect
+    pub transcription_ready: bool,
+}
+
+impl From<&AudioBuffer> for AudioFileInfo {
+    fn from(audio: &AudioBuffer) -> Self {
+        Self {
+            sample_rate: audio.sample_rate,
+            channels: audio.channels,
+            duration_secs: audio.duration_secs(),
+            transcription_ready: audio.sample_rate == WHISPER_SAMPLE_RATE && audio.channels == 1,
+        }
+    }
+}
+
+/// Steps applied by `convert_audio_file`, in the order listed. The defaults turn any WAV
+/// file into 16 kHz mono for transcription.
+#[derive(Deserialize, Type)]
+pub struct AudioConversion {
+    /// Output sample rate, 16 kHz when not set
+    pub sample_rate: Option<u32>,
+    /// Mixes all channels into one, on unless turned off
+    pub mono: Option<bool>,
+    pub trim_start_secs: Option<f32>,
+    pub trim_end_secs: Option<f32>,
+    /// Scales the audio so its loudest sample reaches this level, e.g. -1.0
+    pub normalize_peak_dbfs: Option<f32>,
+}
+
+pub fn read_audio_file(path: &str) -> Result<AudioBuffer, String> {
+    let file = std::fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    decode_wav(std::io::BufReader::new(file))
+        .map_err(|e| format!("Only WAV files can be converted ({}): {}", path, e))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_audio_file_info(path: String) -> Result<AudioFileInfo, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        read_audio_file(&path).map(|audio| AudioFileInfo::from(&audio))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Converts the WAV file at `input` and writes the result to `output` as 16-bit WAV.
+#[tauri::command]
+#[specta::specta]
+pub async fn convert_audio_file(
+    input: String,
+    output: String,
+    conversion: AudioConversion,
+) -> Result<AudioFileInfo, String> {
+    let sample_rate = conversion.sample_rate.unwrap_or(WHISPER_SAMPLE_RATE);
+    if !(MIN_CONVERSION_RATE..=MAX_CONVERSION_RATE).contains(&sample_rate) {
+        return Err(format!(
+            "Sample rate must be between {} and {} Hz",
+            MIN_CONVERSION_RATE, MAX_CONVERSION_RATE
+        ));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut audio = read_audio_file(&input)?;
+        if conversion.mono.unwrap_or(true) {
+            audio = audio.to_mono();
+        }
+        if conversion.trim_start_secs.is_some() || conversion.trim_end_secs.is_some() {
+            audio = audio
+                .trim(
+                    conversion.trim_start_secs.unwrap_or(0.0),
+                    conversion.trim_end_secs,
+                )
+                .map_err(|e| e.to_string())?;
+        }
+        audio = audio.resample(sample_rate);
+        if let Some(peak_dbfs) = conversion.normalize_peak_dbfs {
+            audio = audio.normalize(peak_dbfs);
+        }
+
+        write_wav(&output, &audio).map_err(|e| format!("Failed to write {}: {}", output, e))?;
+        Ok(AudioFileInfo::from(&audio))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Transcribes the WAV file at `path` through the file job queue. Progress is checkpointed,
+/// so running it again after an interruption continues where it stopped.
+#[tauri::command]
+#[specta::specta]
+pub async fn transcribe_audio_file(
+    app: AppHandle,
+    path: String,
+) -> Result<TranscriptionOutput, String> {
+    let queue = app.state::<Arc<FileJobQueue>>().inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let audio = read_audio_file(&path)?
+            .to_mono()
+            .resample(WHISPER_SAMPLE_RATE);
+        queue
+            .transcribe_file(audio.samples, &path)
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_interrupted_file_jobs(app: AppHandle) -> Result<Vec<InterruptedFileJob>, String> {
+    app.state::<Arc<FileJobQueue>>()
+        .interrupted_jobs()
+        .map_err(|e| e.to_string())
+}
+
+/// Continues an interrupted file job from its checkpoint. Jobs uploaded through the API can't
+/// be resumed from here; submitting the same audio again resumes them.
+#[tauri::command]
+#[specta::specta]
+pub async fn resume_file_job(app: AppHandle, id: String) -> Result<TranscriptionOutput, String> {
+    let source = app
+        .state::<Arc<FileJobQueue>>()
+        .interrupted_source(&id)
+        .map_err(|e| e.to_string())?
+        .ok_or("This job was uploaded through the API, submit the same audio again to resume it")?;
+    transcribe_audio_file(app, source).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn discard_file_job(app: AppHandle, id: String) -> Result<(), String> {
+    app.state::<Arc<FileJobQueue>>()
+        .discard_checkpoint(&id)
+        .map_err(|e| e.to_string())
+}
+
+/// Plays a known phrase through the speakers, records it with the selected microphone and
+/// scores its transcription, checking the devices, the pipeline and the model in one go.
+#[tauri::command]
+#[specta::specta]
+pub async fn run_audio_self_test(app: AppHandle) -> Result<SelfTestReport, String> {
+    tauri::async_runtime::spawn_blocking(move || self_test::run(&app))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// The input devices for the setup wizard, the one that will be used marked.
+#[tauri::command]
+#[specta::specta]
+pub fn detect_microphones(app: AppHandle) -> Result<Vec<Microphone>, String> {
+    setup_wizard::detect_microphones(&get_settings(&app))
+}
+
+/// Records five seconds from the microphone and transcribes them, reporting the levels, the
+/// latency and what to fix. The user should say a sentence while it runs.
+#[tauri::command]
+#[specta::specta]
+pub async fn run_setup_test(app: AppHandle) -> Result<TestTakeReport, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        setup_wizard::run_test_take(&app, &get_settings(&app))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_caption_status(app: AppHandle) -> CaptionStatus {
+    app.state::<Arc<CaptionManager>>().status()
+}
+
+/// Opens the caption window and starts captioning the configured source.
+#[tauri::command]
+#[specta::specta]
+pub async fn start_captions(app: AppHandle) -> Result<CaptionStatus, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let captions = app.state::<Arc<CaptionManager>>();
+        captions.start()?;
+        Ok(captions.status())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn stop_captions(app: AppHandle) {
+    app.state::<Arc<CaptionManager>>().stop();
+}
+
+/// Sets what the captions transcribe, `device` naming the input to record from or `None`
+/// to pick one for the source. Running captions switch over right away.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_caption_source(
+    app: AppHandle,
+    source: CaptionSource,
+    device: Option<String>,
+) -> Result<(), String> {
+    let mut settings = get_settings(&app);
+    settings.caption_source = source;
+    settings.caption_device = device;
+    write_settings(&app, settings);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let captions = app.state::<Arc<CaptionManager>>();
+        if captions.is_running() {
+            captions.start()?;
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
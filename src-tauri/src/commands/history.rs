@@ -1,6 +1,16 @@
-use crate::managers::history::{HistoryEntry, HistoryManager};
+use crate::cli::read_wav;
+use crate::managers::history::{
+    DictationStats, HistoryEntry, HistoryManager, HistorySnippet, HistoryTag, StatsPeriod,
+    TranscriptionVersion,
+};
+use crate::managers::transcription::TranscriptionManager;
+use crate::refinement::RefinementChange;
+use crate::session_journal::{RecoveredSession, SessionJournal};
+use crate::settings::SessionTagRule;
+use crate::utils;
 use std::sync::Arc;
-use tauri::{AppHandle, State};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
 
 #[tauri::command]
 #[specta::specta]
@@ -27,6 +37,87 @@ pub async fn toggle_history_entry_saved(
         .map_err(|e| e.to_string())
 }
 
+/// Entries matching a text `query` that carry all of `tags`, newest first.
+#[tauri::command]
+#[specta::specta]
+pub async fn search_history(
+    history_manager: State<'_, Arc<HistoryManager>>,
+    query: Option<String>,
+    tags: Vec<String>,
+) -> Result<Vec<HistoryEntry>, String> {
+    history_manager
+        .search_entries(query.as_deref(), &tags)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Renames an entry, an empty title restores the default one.
+#[tauri::command]
+#[specta::specta]
+pub async fn rename_history_entry(
+    history_manager: State<'_, Arc<HistoryManager>>,
+    id: i64,
+    title: String,
+) -> Result<(), String> {
+    history_manager
+        .set_entry_title(id, &title)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Replaces an entry's tags and returns them as stored, lowercased and dash-joined.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_history_entry_tags(
+    history_manager: State<'_, Arc<HistoryManager>>,
+    id: i64,
+    tags: Vec<String>,
+) -> Result<Vec<String>, String> {
+    history_manager
+        .set_entry_tags(id, &tags)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_history_tags(
+    history_manager: State<'_, Arc<HistoryManager>>,
+) -> Result<Vec<HistoryTag>, String> {
+    history_manager
+        .get_all_tags()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Writes the history to `path` as JSON grouped by tag, returns the number of entries.
+#[tauri::command]
+#[specta::specta]
+pub async fn export_history_by_tag(
+    history_manager: State<'_, Arc<HistoryManager>>,
+    path: String,
+) -> Result<usize, String> {
+    history_manager
+        .export_by_tag(std::path::Path::new(&path))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Words per minute, audio time, latency and confidence of the last `days` days, per day or
+/// week and per model.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_dictation_stats(
+    history_manager: State<'_, Arc<HistoryManager>>,
+    period: StatsPeriod,
+    days: u32,
+) -> Result<DictationStats, String> {
+    history_manager
+        .get_dictation_stats(period, days)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn get_audio_file_path(
@@ -40,6 +131,176 @@ pub async fn get_audio_file_path(
         .map(|s| s.to_string())
 }
 
+/// The recording `file_name` as WAV, decrypted when the history is encrypted. For playback,
+/// where the stored file may not be readable as it is.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_audio_file(
+    history_manager: State<'_, Arc<HistoryManager>>,
+    file_name: String,
+) -> Result<Vec<u8>, String> {
+    history_manager
+        .read_audio(&file_name)
+        .map_err(|e| e.to_string())
+}
+
+/// Per-utterance audio clips of a history entry, in transcript order.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_history_snippets(
+    _app: AppHandle,
+    history_manager: State<'_, Arc<HistoryManager>>,
+    id: i64,
+) -> Result<Vec<HistorySnippet>, String> {
+    history_manager
+        .get_snippets(id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn update_snippet_storage_limit(
+    app: AppHandle,
+    history_manager: State<'_, Arc<HistoryManager>>,
+    limit_mb: u32,
+) -> Result<(), String> {
+    let mut settings = crate::settings::get_settings(&app);
+    settings.snippet_storage_limit_mb = limit_mb;
+    crate::settings::write_settings(&app, settings);
+
+    history_manager
+        .run_maintenance()
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Transcribes an entry's saved audio again with `model_id` and stores the result as a new
+/// version of the entry. The model doesn't need to be the selected one.
+#[tauri::command]
+#[specta::specta]
+pub async fn retranscribe(
+    history_manager: State<'_, Arc<HistoryManager>>,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+    history_id: i64,
+    model_id: String,
+) -> Result<TranscriptionVersion, String> {
+    let entry = history_manager
+        .get_entry_by_id(history_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("History entry {} not found", history_id))?;
+    if !entry.audio_available {
+        return Err("The audio for this entry is no longer available".to_string());
+    }
+
+    let audio = history_manager
+        .read_audio(&entry.file_name)
+        .map_err(|e| e.to_string())?;
+    let tm = transcription_manager.inner().clone();
+    let model = model_id.clone();
+    let output = tauri::async_runtime::spawn_blocking(move || -> anyhow::Result<_> {
+        let samples = read_wav(std::io::Cursor::new(audio))?;
+        Ok(tm.transcribe_with_model(&model, samples)?)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+
+    history_manager
+        .add_transcription_version(history_id, &model_id, output.text)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_transcription_versions(
+    history_manager: State<'_, Arc<HistoryManager>>,
+    history_id: i64,
+) -> Result<Vec<TranscriptionVersion>, String> {
+    history_manager
+        .get_transcription_versions(history_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Pastes history entry `id`, or the latest one, again using the current output settings.
+/// The main window is hidden first so the text lands in the app that was focused before it.
+#[tauri::command]
+#[specta::specta]
+pub async fn paste_history_entry(app: AppHandle, id: Option<i64>) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window("main") {
+        if window.is_focused().unwrap_or(false) {
+            window.hide().map_err(|e| e.to_string())?;
+            // Give the window manager time to hand the focus back
+            tokio::time::sleep(Duration::from_millis(250)).await;
+        }
+    }
+    utils::repaste_history_entry(&app, id).await
+}
+
+/// Removes the last pasted dictation from the app it went into and tags its history entry.
+/// The cursor has to still be right after the pasted text.
+#[tauri::command]
+#[specta::specta]
+pub async fn undo_last_dictation(app: AppHandle) -> Result<(), String> {
+    utils::undo_last_dictation(&app).await
+}
+
+/// Where the accurate pass of entry `history_id` disagreed with the live transcription.
+#[tauri::command]
+#[specta::specta]
+pub fn get_refinement_changes(
+    history_manager: State<'_, Arc<HistoryManager>>,
+    history_id: i64,
+) -> Result<Vec<RefinementChange>, String> {
+    history_manager
+        .get_refinement_changes(history_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Dictations interrupted by a crash or power loss, with the text transcribed before the
+/// interruption, most recent first.
+#[tauri::command]
+#[specta::specta]
+pub fn get_recoverable_sessions(
+    journal: State<'_, SessionJournal>,
+) -> Result<Vec<RecoveredSession>, String> {
+    journal.recoverable_sessions().map_err(|e| e.to_string())
+}
+
+/// Saves an interrupted dictation to history and removes its journal.
+#[tauri::command]
+#[specta::specta]
+pub fn restore_session(
+    history_manager: State<'_, Arc<HistoryManager>>,
+    journal: State<'_, SessionJournal>,
+    id: String,
+) -> Result<(), String> {
+    let session = journal
+        .recoverable_sessions()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|s| s.id == id)
+        .ok_or_else(|| format!("Session {} not found", id))?;
+
+    history_manager
+        .save_recovered_transcription(session.started_at, session.text)
+        .map_err(|e| e.to_string())?;
+    journal.discard(&id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn discard_recoverable_session(
+    journal: State<'_, SessionJournal>,
+    id: String,
+) -> Result<(), String> {
+    journal.discard(&id).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn delete_history_entry(
@@ -99,3 +360,111 @@ pub async fn update_recording_retention_period(
 
     Ok(())
 }
+
+#[tauri::command]
+#[specta::specta]
+pub async fn update_audio_retention_days(
+    app: AppHandle,
+    history_manager: State<'_, Arc<HistoryManager>>,
+    days: Option<u32>,
+) -> Result<(), String> {
+    let mut settings = crate::settings::get_settings(&app);
+    settings.audio_retention_days = days;
+    crate::settings::write_settings(&app, settings);
+
+    history_manager
+        .run_maintenance()
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn update_save_recording_audio(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = crate::settings::get_settings(&app);
+    settings.save_recording_audio = enabled;
+    crate::settings::write_settings(&app, settings);
+
+    Ok(())
+}
+
+/// Caps the disk space used by session audio, `None` removes the cap.
+#[tauri::command]
+#[specta::specta]
+pub async fn update_recording_storage_limit(
+    app: AppHandle,
+    history_manager: State<'_, Arc<HistoryManager>>,
+    limit_mb: Option<u32>,
+) -> Result<(), String> {
+    let mut settings = crate::settings::get_settings(&app);
+    settings.recording_storage_limit_mb = limit_mb;
+    crate::settings::write_settings(&app, settings);
+
+    history_manager
+        .run_maintenance()
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn update_archive_expired_history(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = crate::settings::get_settings(&app);
+    settings.archive_expired_history = enabled;
+    crate::settings::write_settings(&app, settings);
+
+    Ok(())
+}
+
+/// Turns encryption of the history at rest on or off and converts the existing history.
+/// Returns how many texts and recordings were converted.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_history_encryption(
+    history_manager: State<'_, Arc<HistoryManager>>,
+    enabled: bool,
+) -> Result<usize, String> {
+    let hm = history_manager.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || hm.set_encryption(enabled))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_incognito(history_manager: State<'_, Arc<HistoryManager>>) -> bool {
+    history_manager.is_incognito()
+}
+
+/// Keeps dictations out of history, and off the disk, until turned off or the app restarts.
+#[tauri::command]
+#[specta::specta]
+pub fn set_incognito(history_manager: State<'_, Arc<HistoryManager>>, enabled: bool) {
+    history_manager.set_incognito(enabled);
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn update_session_voice_commands(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = crate::settings::get_settings(&app);
+    settings.session_voice_commands = enabled;
+    crate::settings::write_settings(&app, settings);
+
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn update_session_tag_rules(
+    app: AppHandle,
+    rules: Vec<SessionTagRule>,
+) -> Result<(), String> {
+    let mut settings = crate::settings::get_settings(&app);
+    settings.session_tag_rules = rules;
+    crate::settings::write_settings(&app, settings);
+
+    Ok(())
+}
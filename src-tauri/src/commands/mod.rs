@@ -1,11 +1,30 @@
 pub mod audio;
 pub mod history;
 pub mod models;
+pub mod plugins;
 pub mod transcription;
 
-use crate::settings::{get_settings, write_settings, AppSettings, LogLevel};
-use crate::utils::cancel_current_operation;
-use tauri::{AppHandle, Manager};
+use crate::automation;
+use crate::diagnostics;
+use crate::log_buffer::{self, LogEntry, LogQuery};
+use crate::managers::model::{ModelInfo, ModelManager};
+use crate::managers::usage::{UsageCounters, UsageSnapshot};
+use crate::profiles;
+use crate::secrets;
+use crate::session_hooks::{self, HookOutput, MAX_HOOK_TIMEOUT_SECS};
+use crate::session_report::SessionReport;
+use crate::settings::{
+    get_settings, write_settings, AppSettings, DictationProfile, LogLevel,
+    ScreenReaderAnnouncements, SessionHook,
+};
+use crate::settings_bundle::{self, SensitiveChange, SettingsBundle};
+use crate::setup_manifest::{self, ManifestApplied, SetupManifest};
+use crate::shortcut;
+use crate::tray::TrayManager;
+use crate::utils::{cancel_current_operation, cancel_current_session, set_session_paused};
+use crate::webhook::{self, WebhookPayload};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, State};
 use tauri_plugin_opener::OpenerExt;
 
 #[tauri::command]
@@ -14,6 +33,398 @@ pub fn cancel_operation(app: AppHandle) {
     cancel_current_operation(&app);
 }
 
+#[tauri::command]
+#[specta::specta]
+pub fn cancel_session(app: AppHandle) {
+    cancel_current_session(&app);
+}
+
+/// The former name of `cancel_session`, kept for existing callers.
+#[tauri::command]
+#[specta::specta]
+pub fn abort_session(app: AppHandle) {
+    cancel_current_session(&app);
+}
+
+/// Pauses the recording without finishing it, keeping its transcript so far.
+#[tauri::command]
+#[specta::specta]
+pub fn pause_session(app: AppHandle) -> Result<(), String> {
+    set_session_paused(&app, true)
+}
+
+/// Continues a paused recording into the same transcript.
+#[tauri::command]
+#[specta::specta]
+pub fn resume_session(app: AppHandle) -> Result<(), String> {
+    set_session_paused(&app, false)
+}
+
+/// Starts dictation, or stops it when recording, like the transcribe shortcut in toggle
+/// mode. Returns whether it is recording afterwards.
+#[tauri::command]
+#[specta::specta]
+pub fn toggle_recording(app: AppHandle) -> bool {
+    shortcut::toggle_binding(&app, "transcribe", "command")
+}
+
+/// Starts a dictation for automation, failing when one is already in progress.
+#[tauri::command]
+#[specta::specta]
+pub fn start_dictation(app: AppHandle) -> Result<(), String> {
+    automation::start_dictation(&app, "command")
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn stop_dictation(app: AppHandle) -> Result<(), String> {
+    automation::stop_dictation(&app, "command")
+}
+
+/// Transcribes the WAV file at `path` and returns its text.
+#[tauri::command]
+#[specta::specta]
+pub async fn transcribe_file_at_path(app: AppHandle, path: String) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || automation::transcribe_file(&app, &path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Text of the latest dictation, `None` when the history is empty.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_last_transcript(app: AppHandle) -> Result<Option<String>, String> {
+    automation::last_transcript(&app).await
+}
+
+/// Stops the shortcuts from starting dictation for `minutes`, or until resumed when `None`.
+#[tauri::command]
+#[specta::specta]
+pub fn pause_shortcuts(tray: State<'_, Arc<TrayManager>>, minutes: Option<u32>) {
+    tray.pause(minutes.map(|minutes| std::time::Duration::from_secs(minutes as u64 * 60)));
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn resume_shortcuts(tray: State<'_, Arc<TrayManager>>) {
+    tray.set_paused(false);
+}
+
+/// Copies the next part of a long transcription that is being copied in parts. Returns `None`
+/// when no parts are left.
+#[tauri::command]
+#[specta::specta]
+pub fn copy_next_clipboard_part(
+    app: AppHandle,
+) -> Result<Option<crate::clipboard::ClipboardChunkProgress>, String> {
+    crate::clipboard::copy_next_chunk(&app)
+}
+
+/// Opt-in read-back of recognized text through the screen reader.
+#[tauri::command]
+#[specta::specta]
+pub fn set_screen_reader_announcements(app: AppHandle, mode: ScreenReaderAnnouncements) {
+    let mut settings = get_settings(&app);
+    settings.screen_reader_announcements = mode;
+    write_settings(&app, settings);
+}
+
+/// Replaces the session hooks. Every hook needs a command and a timeout of at most
+/// `MAX_HOOK_TIMEOUT_SECS`.
+#[tauri::command]
+#[specta::specta]
+pub fn set_session_hooks(app: AppHandle, hooks: Vec<SessionHook>) -> Result<(), String> {
+    for hook in &hooks {
+        if hook.command.trim().is_empty() {
+            return Err(format!("Hook '{}' has no command", hook.name));
+        }
+        if hook.timeout_secs == 0 || hook.timeout_secs > MAX_HOOK_TIMEOUT_SECS {
+            return Err(format!(
+                "The timeout of hook '{}' must be between 1 and {} seconds",
+                hook.name, MAX_HOOK_TIMEOUT_SECS
+            ));
+        }
+    }
+    let mut settings = get_settings(&app);
+    settings.session_hooks = hooks;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+/// Sets the webhook each finished dictation is sent to. `secret` replaces the signing
+/// secret in the OS keychain when given, an empty one removes it.
+#[tauri::command]
+#[specta::specta]
+pub fn set_webhook(
+    app: AppHandle,
+    enabled: bool,
+    url: String,
+    secret: Option<String>,
+) -> Result<(), String> {
+    let url = url.trim().to_string();
+    if enabled && !(url.starts_with("https://") || url.starts_with("http://")) {
+        return Err("The webhook URL must start with http:// or https://".to_string());
+    }
+    if let Some(secret) = secret {
+        secrets::set_webhook_secret(secret.trim())
+            .map_err(|e| format!("Failed to store the webhook secret: {}", e))?;
+    }
+    let mut settings = get_settings(&app);
+    settings.webhook_enabled = enabled;
+    settings.webhook_url = url;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+/// Sends a sample dictation to the webhook, retrying like a real delivery.
+#[tauri::command]
+#[specta::specta]
+pub async fn test_webhook(app: AppHandle) -> Result<(), String> {
+    let settings = get_settings(&app);
+    if settings.webhook_url.is_empty() {
+        return Err("No webhook URL is set".to_string());
+    }
+    let report = SessionReport {
+        binding_id: "test".to_string(),
+        text: "This is a test dictation.".to_string(),
+        raw_text: "this is a test dictation".to_string(),
+        audio_duration_secs: 2.0,
+        ..Default::default()
+    };
+    let payload = WebhookPayload::from_report(&report, &settings);
+    let secret = secrets::get_webhook_secret();
+    webhook::send(&settings.webhook_url, secret.as_deref(), &payload)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Replaces the dictation profiles. Each needs a name and an id of its own; when the active
+/// profile is removed the base settings are back until the next switch.
+#[tauri::command]
+#[specta::specta]
+pub fn set_profiles(app: AppHandle, profiles: Vec<DictationProfile>) -> Result<(), String> {
+    for (index, profile) in profiles.iter().enumerate() {
+        if profile.id.trim().is_empty() || profile.name.trim().is_empty() {
+            return Err("Every profile needs an id and a name".to_string());
+        }
+        if profiles[..index].iter().any(|p| p.id == profile.id) {
+            return Err(format!("Profile id '{}' is used twice", profile.id));
+        }
+    }
+    let settings = get_settings(&app);
+    let active_removed = settings
+        .active_profile_id
+        .as_ref()
+        .is_some_and(|active| !profiles.iter().any(|p| &p.id == active));
+    if active_removed {
+        profiles::clear(&app);
+    }
+    let mut settings = get_settings(&app);
+    settings.profiles = profiles;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+/// Switches to profile `id`, loading its model when another one is loaded.
+#[tauri::command]
+#[specta::specta]
+pub fn switch_profile(app: AppHandle, id: String) -> Result<DictationProfile, String> {
+    profiles::switch_to(&app, &id)
+}
+
+/// Leaves the active profile, restoring the settings from before the first profile switch.
+#[tauri::command]
+#[specta::specta]
+pub fn clear_profile(app: AppHandle) {
+    profiles::clear(&app);
+}
+
+/// Writes the settings to `path` as a JSON bundle for another machine. API keys, devices,
+/// shortcuts and local paths stay behind; custom words and regex rules are included on
+/// request.
+#[tauri::command]
+#[specta::specta]
+pub fn export_settings(
+    app: AppHandle,
+    path: String,
+    include_custom_words: bool,
+    include_regex_rules: bool,
+) -> Result<(), String> {
+    let bundle = settings_bundle::export(
+        &get_settings(&app),
+        include_custom_words,
+        include_regex_rules,
+    )?;
+    let json = serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+fn read_settings_bundle(path: &str) -> Result<SettingsBundle, String> {
+    let json =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    serde_json::from_str(&json).map_err(|e| format!("Not a settings bundle: {}", e))
+}
+
+/// The hooks, webhook, plugins, providers, servers, context sharing and other sensitive
+/// settings the bundle at `path` would change, for the user to confirm before importing it.
+#[tauri::command]
+#[specta::specta]
+pub fn preview_settings_import(
+    app: AppHandle,
+    path: String,
+) -> Result<Vec<SensitiveChange>, String> {
+    settings_bundle::sensitive_changes(&get_settings(&app), &read_settings_bundle(&path)?)
+}
+
+/// Applies a bundle written by `export_settings`, upgrading it from the version that wrote
+/// it. Of the settings `preview_settings_import` lists, only the `confirmed` ones are taken
+/// over. Returns the resulting settings.
+#[tauri::command]
+#[specta::specta]
+pub fn import_settings(
+    app: AppHandle,
+    path: String,
+    confirmed: Vec<String>,
+) -> Result<AppSettings, String> {
+    let bundle = read_settings_bundle(&path)?;
+    let settings = settings_bundle::import(&get_settings(&app), bundle, &confirmed)?;
+    write_settings(&app, settings.clone());
+    Ok(settings)
+}
+
+/// Writes a manifest of this setup to `path`: the models in use with where they come from,
+/// and the settings including profiles, custom words and regex rules. Machine-specific
+/// settings stay behind as with `export_settings`.
+#[tauri::command]
+#[specta::specta]
+pub fn generate_setup_manifest(
+    app: AppHandle,
+    model_manager: State<'_, Arc<ModelManager>>,
+    path: String,
+) -> Result<(), String> {
+    let manifest = setup_manifest::generate(
+        &get_settings(&app),
+        &model_manager.get_available_models(),
+        &model_manager.models_dir(),
+    )?;
+    let json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+fn read_setup_manifest(path: &str) -> Result<SetupManifest, String> {
+    let json =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    serde_json::from_str(&json).map_err(|e| format!("Not a setup manifest: {}", e))
+}
+
+/// The sensitive settings the manifest at `path` would change, for the user to confirm
+/// before applying it, as with `preview_settings_import`.
+#[tauri::command]
+#[specta::specta]
+pub fn preview_manifest(app: AppHandle, path: String) -> Result<Vec<SensitiveChange>, String> {
+    settings_bundle::sensitive_changes(&get_settings(&app), &read_setup_manifest(&path)?.settings)
+}
+
+/// Sets this machine up from a manifest written by `generate_setup_manifest`: downloads the
+/// models it lists that are missing, then applies its settings, the sensitive ones only
+/// when `confirmed`, and loads the selected model if another one is loaded. Fails without
+/// changing the settings when a model here, or one it downloads, isn't the version the
+/// manifest pins.
+#[tauri::command]
+#[specta::specta]
+pub async fn apply_manifest(
+    app: AppHandle,
+    model_manager: State<'_, Arc<ModelManager>>,
+    path: String,
+    confirmed: Vec<String>,
+) -> Result<ManifestApplied, String> {
+    let manifest = read_setup_manifest(&path)?;
+    let models_dir = model_manager.models_dir();
+    let plan = setup_manifest::plan(
+        &manifest,
+        &model_manager.get_available_models(),
+        &models_dir,
+    )?;
+
+    for model_id in &plan.to_download {
+        model_manager
+            .download_model(model_id)
+            .await
+            .map_err(|e| format!("Failed to download {}: {}", model_id, e))?;
+    }
+    let downloaded: Vec<ModelInfo> = model_manager
+        .get_available_models()
+        .into_iter()
+        .filter(|model| plan.to_download.contains(&model.id))
+        .collect();
+    setup_manifest::verify_downloads(&manifest, &downloaded, &models_dir)?;
+
+    let settings = settings_bundle::import(&get_settings(&app), manifest.settings, &confirmed)?;
+    write_settings(&app, settings.clone());
+    profiles::reload_model(&app, &settings.selected_model);
+
+    Ok(ManifestApplied {
+        downloaded: plan.to_download,
+        settings,
+    })
+}
+
+/// Runs `hook` once, outside a session, and returns its output so it can be checked before
+/// it is saved.
+#[tauri::command]
+#[specta::specta]
+pub async fn test_session_hook(hook: SessionHook) -> Result<HookOutput, String> {
+    let timeout_secs = hook.timeout_secs.clamp(1, MAX_HOOK_TIMEOUT_SECS);
+    tauri::async_runtime::spawn_blocking(move || {
+        session_hooks::run_hook(
+            &SessionHook {
+                timeout_secs,
+                ..hook
+            },
+            "test",
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// The usage counters as they would be shared in diagnostics: content-free and bucketed.
+#[tauri::command]
+#[specta::specta]
+pub fn get_usage_counters(usage: State<'_, Arc<UsageCounters>>) -> UsageSnapshot {
+    usage.snapshot()
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn reset_usage_counters(usage: State<'_, Arc<UsageCounters>>) -> Result<(), String> {
+    usage.reset().map_err(|e| e.to_string())
+}
+
+/// Writes a zip for bug reports to `path`: recent logs, redacted settings, the models with
+/// their hashes and the audio devices, with a loopback latency test when asked.
+#[tauri::command]
+#[specta::specta]
+pub async fn generate_diagnostics(
+    app: AppHandle,
+    path: String,
+    loopback_test: bool,
+) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        diagnostics::generate(&app, std::path::Path::new(&path), loopback_test)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_usage_counters_enabled(app: AppHandle, enabled: bool) {
+    let mut settings = get_settings(&app);
+    settings.usage_counters = enabled;
+    write_settings(&app, settings);
+}
+
 #[tauri::command]
 #[specta::specta]
 pub fn get_app_dir_path(app: AppHandle) -> Result<String, String> {
@@ -66,6 +477,21 @@ pub fn set_log_level(app: AppHandle, level: LogLevel) -> Result<(), String> {
     Ok(())
 }
 
+/// Recent log lines for the in-app log viewer, oldest first. Poll with `after` set to the
+/// last id received to follow the log.
+#[specta::specta]
+#[tauri::command]
+pub fn get_logs(query: LogQuery) -> Vec<LogEntry> {
+    log_buffer::query(&query)
+}
+
+/// Empties the log viewer. The log file is left alone.
+#[specta::specta]
+#[tauri::command]
+pub fn clear_logs() {
+    log_buffer::clear();
+}
+
 #[specta::specta]
 #[tauri::command]
 pub fn open_recordings_folder(app: AppHandle) -> Result<(), String> {
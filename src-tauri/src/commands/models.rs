@@ -1,8 +1,57 @@
-use crate::managers::model::{ModelInfo, ModelManager};
+use crate::benchmark::{self, ModelBenchmark};
+use crate::cli::read_wav;
+use crate::helpers::{process_memory, system_memory};
+use crate::managers::hf_hub::{self, HubModel};
+use crate::managers::history::HistoryManager;
+use crate::managers::model::{
+    default_models_dir, download_client, load_ca_certificates, DownloadProgress, ModelInfo,
+    ModelManager,
+};
+use crate::managers::model_integrity::IntegrityReport;
+use crate::managers::model_storage::{EvictionProposal, ModelDiskUsage};
 use crate::managers::transcription::TranscriptionManager;
-use crate::settings::{get_settings, write_settings};
+use crate::secrets;
+use crate::settings::{
+    get_settings, write_settings, HubModelConfig, InferenceCores, InferencePriority, LowMemoryGuard,
+};
+use crate::setup_wizard::{self, ModelRecommendation};
+use log::{info, warn};
+use serde::Serialize;
+use specta::Type;
+use std::path::Path;
 use std::sync::Arc;
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, Emitter, Listener, State};
+
+/// Share of the combined setup progress taken by the download, the rest is loading
+const DOWNLOAD_SHARE: f64 = 0.85;
+
+#[derive(Clone, Debug, Serialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelSetupStage {
+    Downloading,
+    Loading,
+    Ready,
+}
+
+/// Emitted as `model-setup-progress` while a model is downloaded and loaded in one go
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct ModelSetupProgress {
+    pub model_id: String,
+    pub stage: ModelSetupStage,
+    /// Of download and load together, from 0 to 100
+    pub percentage: f64,
+}
+
+fn emit_setup_progress(app: &AppHandle, model_id: &str, stage: ModelSetupStage, percentage: f64) {
+    let _ = app.emit(
+        "model-setup-progress",
+        ModelSetupProgress {
+            model_id: model_id.to_string(),
+            stage,
+            percentage,
+        },
+    );
+}
 
 #[tauri::command]
 #[specta::specta]
@@ -12,6 +61,107 @@ pub async fn get_available_models(
     Ok(model_manager.get_available_models())
 }
 
+/// The text-to-speech voices transcriptions can be read back with.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_voice_models(
+    model_manager: State<'_, Arc<ModelManager>>,
+) -> Result<Vec<ModelInfo>, String> {
+    Ok(model_manager.get_voice_models())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_readback_voice(
+    app_handle: AppHandle,
+    model_manager: State<'_, Arc<ModelManager>>,
+    voice_id: String,
+) -> Result<(), String> {
+    if !model_manager
+        .get_model_info(&voice_id)
+        .is_some_and(|model| model.is_voice())
+    {
+        return Err(format!("Unknown voice: {}", voice_id));
+    }
+    let mut settings = get_settings(&app_handle);
+    settings.readback_voice = voice_id;
+    write_settings(&app_handle, settings);
+    Ok(())
+}
+
+/// Sets the `piper` program voices are spoken with, `None` looks it up on the PATH.
+#[tauri::command]
+#[specta::specta]
+pub fn set_piper_path(app_handle: AppHandle, path: Option<String>) -> Result<(), String> {
+    let path = path.filter(|p| !p.is_empty());
+    if let Some(path) = &path {
+        if !Path::new(path).is_file() {
+            return Err(format!("{} is not a file", path));
+        }
+    }
+
+    let mut settings = get_settings(&app_handle);
+    settings.piper_path = path;
+    write_settings(&app_handle, settings);
+    Ok(())
+}
+
+/// Sets the small model kept loaded for live partials while the selected one loads on
+/// demand, `None` keeps no standby model.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_standby_model(
+    app_handle: AppHandle,
+    model_manager: State<'_, Arc<ModelManager>>,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+    model_id: Option<String>,
+) -> Result<(), String> {
+    if let Some(model_id) = &model_id {
+        let model_info = model_manager
+            .get_model_info(model_id)
+            .filter(|model| !model.is_voice())
+            .ok_or_else(|| format!("Model not found: {}", model_id))?;
+        if !model_info.is_downloaded {
+            return Err(format!("Model not downloaded: {}", model_id));
+        }
+    }
+
+    let mut settings = get_settings(&app_handle);
+    settings.standby_model = model_id;
+    write_settings(&app_handle, settings);
+
+    let tm = Arc::clone(&transcription_manager);
+    tauri::async_runtime::spawn_blocking(move || tm.load_standby())
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Sets the model finished recordings are transcribed again with, `None` leaves the final
+/// pass to the selected model.
+#[tauri::command]
+#[specta::specta]
+pub fn set_rescore_model(
+    app_handle: AppHandle,
+    model_manager: State<'_, Arc<ModelManager>>,
+    model_id: Option<String>,
+) -> Result<(), String> {
+    if let Some(model_id) = &model_id {
+        let model_info = model_manager
+            .get_model_info(model_id)
+            .filter(|model| !model.is_voice())
+            .ok_or_else(|| format!("Model not found: {}", model_id))?;
+        if !model_info.is_downloaded {
+            return Err(format!("Model not downloaded: {}", model_id));
+        }
+    }
+
+    let mut settings = get_settings(&app_handle);
+    settings.rescore_model = model_id;
+    write_settings(&app_handle, settings);
+    Ok(())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn get_model_info(
@@ -21,6 +171,69 @@ pub async fn get_model_info(
     Ok(model_manager.get_model_info(&model_id))
 }
 
+/// Transcribes a WAV clip with each downloaded model, or with `model_ids` only, and reports
+/// speed, memory and, given the clip's `reference_text`, word error rate. Without an
+/// `audio_path` the most recent dictation whose audio was kept is used.
+#[tauri::command]
+#[specta::specta]
+pub async fn benchmark_models(
+    app: AppHandle,
+    model_manager: State<'_, Arc<ModelManager>>,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+    history_manager: State<'_, Arc<HistoryManager>>,
+    audio_path: Option<String>,
+    reference_text: Option<String>,
+    model_ids: Option<Vec<String>>,
+) -> Result<Vec<ModelBenchmark>, String> {
+    let audio = match audio_path {
+        Some(path) => {
+            std::fs::read(&path).map_err(|e| format!("Failed to open {}: {}", path, e))?
+        }
+        None => {
+            let entry = history_manager
+                .get_history_entries()
+                .await
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .find(|entry| entry.audio_available)
+                .ok_or_else(|| {
+                    "No audio clip given and no dictation with saved audio to use".to_string()
+                })?;
+            history_manager
+                .read_audio(&entry.file_name)
+                .map_err(|e| e.to_string())?
+        }
+    };
+
+    let models: Vec<ModelInfo> = model_manager
+        .get_available_models()
+        .into_iter()
+        .filter(|model| model.is_downloaded)
+        .filter(|model| {
+            model_ids
+                .as_ref()
+                .map_or(true, |ids| ids.contains(&model.id))
+        })
+        .collect();
+    if models.is_empty() {
+        return Err("No downloaded models to benchmark".to_string());
+    }
+
+    let tm = transcription_manager.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let samples = read_wav(std::io::Cursor::new(audio)).map_err(|e| e.to_string())?;
+        Ok(benchmark::run(
+            &app,
+            &tm,
+            &models,
+            &samples,
+            reference_text.as_deref(),
+        ))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn download_model(
@@ -51,6 +264,62 @@ pub async fn set_active_model(
     model_manager: State<'_, Arc<ModelManager>>,
     transcription_manager: State<'_, Arc<TranscriptionManager>>,
     model_id: String,
+) -> Result<(), String> {
+    let model_manager = Arc::clone(&model_manager);
+    let transcription_manager = Arc::clone(&transcription_manager);
+    // Loading takes seconds, off the async runtime so `cancel_model_load` gets through
+    tauri::async_runtime::spawn_blocking(move || {
+        activate_model(
+            &app_handle,
+            &model_manager,
+            &transcription_manager,
+            model_id,
+            false,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Makes `model_id` the active model although the low memory guard blocked it, once the
+/// user confirmed they want it anyway.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_active_model_ignoring_memory(
+    app_handle: AppHandle,
+    model_manager: State<'_, Arc<ModelManager>>,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+    model_id: String,
+) -> Result<(), String> {
+    let model_manager = Arc::clone(&model_manager);
+    let transcription_manager = Arc::clone(&transcription_manager);
+    tauri::async_runtime::spawn_blocking(move || {
+        activate_model(
+            &app_handle,
+            &model_manager,
+            &transcription_manager,
+            model_id,
+            true,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Stops the model load in progress between its phases, leaving no model loaded. Returns
+/// whether a load was in progress.
+#[tauri::command]
+#[specta::specta]
+pub fn cancel_model_load(transcription_manager: State<'_, Arc<TranscriptionManager>>) -> bool {
+    transcription_manager.cancel_model_load()
+}
+
+fn activate_model(
+    app_handle: &AppHandle,
+    model_manager: &ModelManager,
+    transcription_manager: &TranscriptionManager,
+    model_id: String,
+    ignore_memory: bool,
 ) -> Result<(), String> {
     // Check if model exists and is available
     let model_info = model_manager
@@ -62,15 +331,83 @@ pub async fn set_active_model(
     }
 
     // Load the model in the transcription manager
-    transcription_manager
-        .load_model(&model_id)
-        .map_err(|e| e.to_string())?;
+    if ignore_memory {
+        transcription_manager.load_model_ignoring_memory(&model_id)
+    } else {
+        transcription_manager.load_model(&model_id)
+    }
+    .map_err(|e| e.to_string())?;
 
     // Update settings
+    let mut settings = get_settings(app_handle);
+    settings.selected_model = model_id;
+    write_settings(app_handle, settings);
+
+    // The standby model is dropped when it just became the selected one
+    if let Err(e) = transcription_manager.load_standby() {
+        warn!("Failed to update the standby model: {}", e);
+    }
+
+    Ok(())
+}
+
+/// Downloads `model_id` if needed and makes it the active model as soon as it is on disk,
+/// so picking a model that isn't downloaded yet takes a single step. Both steps are
+/// reported as one `model-setup-progress`. Models are loaded from their complete files, so
+/// loading starts when the last one has arrived.
+#[tauri::command]
+#[specta::specta]
+pub async fn download_and_activate_model(
+    app_handle: AppHandle,
+    model_manager: State<'_, Arc<ModelManager>>,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+    model_id: String,
+) -> Result<(), String> {
+    let model_info = model_manager
+        .get_model_info(&model_id)
+        .ok_or_else(|| format!("Model not found: {}", model_id))?;
+
+    if !model_info.is_downloaded {
+        emit_setup_progress(&app_handle, &model_id, ModelSetupStage::Downloading, 0.0);
+        let app = app_handle.clone();
+        let id = model_id.clone();
+        let listener = app_handle.listen("model-download-progress", move |event| {
+            let Ok(progress) = serde_json::from_str::<DownloadProgress>(event.payload()) else {
+                return;
+            };
+            if progress.model_id == id {
+                emit_setup_progress(
+                    &app,
+                    &id,
+                    ModelSetupStage::Downloading,
+                    progress.percentage * DOWNLOAD_SHARE,
+                );
+            }
+        });
+        let downloaded = model_manager.download_model(&model_id).await;
+        app_handle.unlisten(listener);
+        downloaded.map_err(|e| e.to_string())?;
+    }
+
+    emit_setup_progress(
+        &app_handle,
+        &model_id,
+        ModelSetupStage::Loading,
+        DOWNLOAD_SHARE * 100.0,
+    );
+    let tm = transcription_manager.inner().clone();
+    let id = model_id.clone();
+    tauri::async_runtime::spawn_blocking(move || tm.load_model(&id))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
     let mut settings = get_settings(&app_handle);
     settings.selected_model = model_id.clone();
     write_settings(&app_handle, settings);
+    info!("Downloaded and activated model {}", model_id);
 
+    emit_setup_progress(&app_handle, &model_id, ModelSetupStage::Ready, 100.0);
     Ok(())
 }
 
@@ -102,8 +439,13 @@ pub async fn is_model_loading(
 #[tauri::command]
 #[specta::specta]
 pub async fn has_any_models_available(
+    app_handle: AppHandle,
     model_manager: State<'_, Arc<ModelManager>>,
 ) -> Result<bool, String> {
+    // The simulated engine transcribes without any model
+    if get_settings(&app_handle).mock_engine.enabled {
+        return Ok(true);
+    }
     let models = model_manager.get_available_models();
     Ok(models.iter().any(|m| m.is_downloaded))
 }
@@ -133,5 +475,309 @@ pub async fn cancel_download(
 #[specta::specta]
 pub async fn get_recommended_first_model() -> Result<String, String> {
     // Recommend Parakeet V3 model for first-time users - fastest and most accurate
-    Ok("parakeet-tdt-0.6b-v3".to_string())
+    Ok(setup_wizard::DEFAULT_MODEL.to_string())
+}
+
+/// The model tier for this machine's memory and cores. Pass the real-time factor of the
+/// setup test, when it ran, to step down a tier if inference was slow.
+#[tauri::command]
+#[specta::specta]
+pub fn recommend_model_tier(realtime_factor: Option<f32>) -> ModelRecommendation {
+    setup_wizard::recommend_model(realtime_factor)
+}
+
+/// Stores models in a machine-wide cache shared with other Handy-based apps and OS users.
+/// Takes effect the next time Handy starts.
+#[tauri::command]
+#[specta::specta]
+pub fn set_shared_model_cache(app_handle: AppHandle, enabled: bool) {
+    let mut settings = get_settings(&app_handle);
+    settings.shared_model_cache = enabled;
+    write_settings(&app_handle, settings);
+}
+
+/// Checks a downloaded model's files, naming the missing and damaged ones.
+#[tauri::command]
+#[specta::specta]
+pub async fn verify_model(
+    model_manager: State<'_, Arc<ModelManager>>,
+    model_id: String,
+) -> Result<IntegrityReport, String> {
+    let model_manager = Arc::clone(&model_manager);
+    tauri::async_runtime::spawn_blocking(move || model_manager.verify_model(&model_id))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Downloads a model's missing and damaged files again. Returns the files repaired.
+#[tauri::command]
+#[specta::specta]
+pub async fn repair_model(
+    model_manager: State<'_, Arc<ModelManager>>,
+    model_id: String,
+) -> Result<Vec<String>, String> {
+    model_manager
+        .repair_model(&model_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Memory of the machine and of Handy, in MB, to show next to the models' requirements.
+#[derive(Serialize, Type)]
+pub struct MemoryStatus {
+    pub total_mb: Option<u64>,
+    pub available_mb: Option<u64>,
+    /// Memory Handy itself holds
+    pub app_resident_mb: Option<u64>,
+    /// Estimate for the model loaded, if any
+    pub loaded_model_mb: Option<u64>,
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_memory_status(
+    model_manager: State<'_, Arc<ModelManager>>,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+) -> MemoryStatus {
+    const MB: u64 = 1024 * 1024;
+    let memory = system_memory::system_memory();
+    MemoryStatus {
+        total_mb: memory.map(|memory| memory.total_bytes / MB),
+        available_mb: memory.map(|memory| memory.available_bytes / MB),
+        app_resident_mb: process_memory::resident_bytes().map(|bytes| bytes / MB),
+        loaded_model_mb: transcription_manager
+            .get_current_model()
+            .and_then(|id| model_manager.get_model_info(&id))
+            .map(|model| model.ram_mb),
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_low_memory_guard(app_handle: AppHandle, guard: LowMemoryGuard) {
+    let mut settings = get_settings(&app_handle);
+    settings.low_memory_guard = guard;
+    write_settings(&app_handle, settings);
+}
+
+/// Takes effect from the next model load or transcription.
+#[tauri::command]
+#[specta::specta]
+pub fn set_inference_tuning(
+    app_handle: AppHandle,
+    threads: Option<u32>,
+    priority: InferencePriority,
+    cores: InferenceCores,
+) -> Result<(), String> {
+    if threads == Some(0) {
+        return Err("Inference needs at least one thread".to_string());
+    }
+    let mut settings = get_settings(&app_handle);
+    settings.inference_threads = threads;
+    settings.inference_priority = priority;
+    settings.inference_cores = cores;
+    write_settings(&app_handle, settings);
+    Ok(())
+}
+
+/// Searches the Hugging Face Hub for Parakeet ONNX exports matching `query`.
+#[tauri::command]
+#[specta::specta]
+pub async fn search_hub_models(
+    app_handle: AppHandle,
+    query: String,
+) -> Result<Vec<HubModel>, String> {
+    let client = download_client(&get_settings(&app_handle)).map_err(|e| e.to_string())?;
+    hf_hub::search(&client, query.trim())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Adds a Hub repo to the model catalog, so it can be downloaded and selected like the
+/// built-in models. Returns its catalog entry.
+#[tauri::command]
+#[specta::specta]
+pub async fn add_hub_model(
+    app_handle: AppHandle,
+    model_manager: State<'_, Arc<ModelManager>>,
+    repo_id: String,
+) -> Result<ModelInfo, String> {
+    let client = download_client(&get_settings(&app_handle)).map_err(|e| e.to_string())?;
+    let model = hf_hub::model(&client, repo_id.trim())
+        .await
+        .map_err(|e| e.to_string())?;
+    let config = HubModelConfig {
+        repo_id: model.repo_id,
+        quantization: model.quantization,
+        files: model.files,
+        size_mb: model.size_mb.unwrap_or_default(),
+        sha256: model.sha256,
+    };
+    let info = hf_hub::model_info(&config);
+
+    let mut settings = get_settings(&app_handle);
+    settings
+        .hub_models
+        .retain(|hub| hub.repo_id != config.repo_id);
+    settings.hub_models.push(config);
+    write_settings(&app_handle, settings);
+
+    model_manager.merge_catalog(vec![info.clone()]);
+    model_manager
+        .get_model_info(&info.id)
+        .ok_or_else(|| format!("Failed to add {}", info.id))
+}
+
+/// Stores the Hugging Face access token used for gated repos, or removes it when empty.
+#[tauri::command]
+#[specta::specta]
+pub fn set_hub_token(token: String) -> Result<(), String> {
+    secrets::set_api_key(hf_hub::TOKEN_ID, token.trim()).map_err(|e| e.to_string())
+}
+
+/// Fetches the model manifest for models published since this version.
+#[tauri::command]
+#[specta::specta]
+pub async fn refresh_model_catalog(
+    model_manager: State<'_, Arc<ModelManager>>,
+) -> Result<usize, String> {
+    model_manager
+        .refresh_catalog()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Folder the models are currently stored in.
+#[tauri::command]
+#[specta::specta]
+pub fn get_models_dir(model_manager: State<'_, Arc<ModelManager>>) -> String {
+    model_manager.models_dir().to_string_lossy().into_owned()
+}
+
+/// Moves the downloaded models to `path`, or back to the app data folder when `None`, and
+/// stores models there from then on. Returns how many models were moved.
+#[tauri::command]
+#[specta::specta]
+pub async fn move_models(
+    app_handle: AppHandle,
+    model_manager: State<'_, Arc<ModelManager>>,
+    path: Option<String>,
+) -> Result<usize, String> {
+    let target = match path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty()) {
+        Some(path) => Path::new(&path).to_path_buf(),
+        None => default_models_dir(&app_handle).map_err(|e| e.to_string())?,
+    };
+    let model_manager = Arc::clone(&model_manager);
+    tauri::async_runtime::spawn_blocking(move || model_manager.move_models(&target))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+/// Disk usage of each downloaded or partially downloaded model, largest first.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_model_disk_usage(
+    model_manager: State<'_, Arc<ModelManager>>,
+) -> Result<Vec<ModelDiskUsage>, String> {
+    let model_manager = Arc::clone(&model_manager);
+    tauri::async_runtime::spawn_blocking(move || model_manager.disk_usage())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Limits the disk space the models may take, `None` for no limit. Returns the models
+/// the new limit proposes to delete, which are only deleted through `evict_models`.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_model_storage_limit(
+    app_handle: AppHandle,
+    model_manager: State<'_, Arc<ModelManager>>,
+    limit_gb: Option<f32>,
+) -> Result<Option<EvictionProposal>, String> {
+    if limit_gb.is_some_and(|limit| !limit.is_finite() || limit <= 0.0) {
+        return Err("The limit must be a positive number of GB".to_string());
+    }
+    let mut settings = get_settings(&app_handle);
+    settings.model_storage_limit_gb = limit_gb;
+    write_settings(&app_handle, settings);
+
+    let model_manager = Arc::clone(&model_manager);
+    tauri::async_runtime::spawn_blocking(move || model_manager.eviction_proposal(&[]))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Deletes the models of a confirmed eviction proposal. The selected model and the one
+/// loaded are refused, in case they changed since it was made.
+#[tauri::command]
+#[specta::specta]
+pub async fn evict_models(
+    app_handle: AppHandle,
+    model_manager: State<'_, Arc<ModelManager>>,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+    model_ids: Vec<String>,
+) -> Result<(), String> {
+    let selected = get_settings(&app_handle).selected_model;
+    let loaded = transcription_manager.get_current_model();
+    if let Some(id) = model_ids
+        .iter()
+        .find(|id| **id == selected || Some(*id) == loaded.as_ref())
+    {
+        return Err(format!("{} is in use and can't be deleted", id));
+    }
+    for id in &model_ids {
+        model_manager.delete_model(id).map_err(|e| e.to_string())?;
+        info!("Deleted {} to stay under the model storage limit", id);
+    }
+    Ok(())
+}
+
+/// Sends model downloads through `proxy` (e.g. `http://proxy.corp:8080`), `None` goes back to
+/// the system proxy configuration.
+#[tauri::command]
+#[specta::specta]
+pub fn set_download_proxy(app_handle: AppHandle, proxy: Option<String>) -> Result<(), String> {
+    let proxy = proxy
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty());
+    if let Some(proxy) = &proxy {
+        reqwest::Proxy::all(proxy.as_str()).map_err(|e| format!("Invalid proxy URL: {}", e))?;
+    }
+
+    let mut settings = get_settings(&app_handle);
+    settings.download_proxy = proxy;
+    write_settings(&app_handle, settings);
+    Ok(())
+}
+
+/// Trusts the root certificates in `path` for model downloads, in addition to the system ones.
+#[tauri::command]
+#[specta::specta]
+pub fn set_download_ca_cert(app_handle: AppHandle, path: Option<String>) -> Result<(), String> {
+    let path = path.filter(|p| !p.is_empty());
+    if let Some(path) = &path {
+        load_ca_certificates(Path::new(path)).map_err(|e| e.to_string())?;
+    }
+
+    let mut settings = get_settings(&app_handle);
+    settings.download_ca_cert_path = path;
+    write_settings(&app_handle, settings);
+    Ok(())
+}
+
+/// Converts a downloaded fp32 Parakeet model to int8 on this machine and returns the id
+/// of the resulting model. Quantization takes a few minutes, so it runs off the async runtime.
+#[tauri::command]
+#[specta::specta]
+pub async fn quantize_model(
+    model_manager: State<'_, Arc<ModelManager>>,
+    model_id: String,
+) -> Result<String, String> {
+    let model_manager = model_manager.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || model_manager.quantize_model(&model_id))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
 }
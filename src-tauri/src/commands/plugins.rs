@@ -0,0 +1,55 @@
+use crate::managers::plugins::{PluginInfo, PluginManager};
+use crate::settings::{get_settings, write_settings, PluginConfig};
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+use tauri_plugin_opener::OpenerExt;
+
+/// Longest a plugin may be given to transform the text
+const MAX_PLUGIN_TIMEOUT_MS: u64 = 10_000;
+
+/// The plugins in the plugins folder, in the order they run.
+#[tauri::command]
+#[specta::specta]
+pub fn get_plugins(plugins: State<'_, Arc<PluginManager>>) -> Vec<PluginInfo> {
+    plugins.list()
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_plugin_config(
+    app: AppHandle,
+    id: String,
+    enabled: bool,
+    timeout_ms: u64,
+) -> Result<(), String> {
+    if timeout_ms == 0 || timeout_ms > MAX_PLUGIN_TIMEOUT_MS {
+        return Err(format!(
+            "The timeout must be between 1 and {} ms",
+            MAX_PLUGIN_TIMEOUT_MS
+        ));
+    }
+    let mut settings = get_settings(&app);
+    let config = PluginConfig {
+        id: id.clone(),
+        enabled,
+        timeout_ms,
+    };
+    match settings.plugins.iter_mut().find(|config| config.id == id) {
+        Some(existing) => *existing = config,
+        None => settings.plugins.push(config),
+    }
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn open_plugins_folder(
+    app: AppHandle,
+    plugins: State<'_, Arc<PluginManager>>,
+) -> Result<(), String> {
+    let path = plugins.dir().to_string_lossy().as_ref().to_string();
+    app.opener()
+        .open_path(path, None::<String>)
+        .map_err(|e| format!("Failed to open plugins folder: {}", e))
+}
@@ -1,8 +1,22 @@
+use crate::cloud_transcription::{self, provider_info, CloudProviderInfo};
+use crate::managers::chunk_workers::MAX_CHUNK_WORKERS;
+use crate::managers::disfluency::{DisfluencyManager, DisfluencyModelSummary};
+use crate::managers::model::{EngineType, ModelManager};
 use crate::managers::transcription::TranscriptionManager;
-use crate::settings::{get_settings, write_settings, ModelUnloadTimeout};
+use crate::mock_engine;
+use crate::power_policy::{self, PowerPolicyEvent};
+use crate::readback;
+use crate::settings::{
+    get_settings, write_settings, BatteryPolicy, MockEngineSettings, ModelUnloadTimeout,
+    TranscriptionProvider,
+};
 use serde::Serialize;
 use specta::Type;
-use tauri::{AppHandle, State};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, State};
+
+/// Anything shorter would cut off ordinary transcriptions on slower machines
+const MIN_TRANSCRIPTION_TIMEOUT_SECS: u32 = 10;
 
 #[derive(Serialize, Type)]
 pub struct ModelLoadStatus {
@@ -18,6 +32,79 @@ pub fn set_model_unload_timeout(app: AppHandle, timeout: ModelUnloadTimeout) {
     write_settings(&app, settings);
 }
 
+/// Takes effect right away when running on battery.
+#[tauri::command]
+#[specta::specta]
+pub fn set_battery_policy(app: AppHandle, policy: BatteryPolicy) {
+    let mut settings = get_settings(&app);
+    settings.battery_policy = policy;
+    write_settings(&app, settings);
+    power_policy::apply(&app);
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_power_state(app: AppHandle) -> PowerPolicyEvent {
+    power_policy::state(&get_settings(&app))
+}
+
+/// Turns the `perf-metrics` events on or off.
+#[tauri::command]
+#[specta::specta]
+pub fn set_perf_metrics_enabled(app: AppHandle, enabled: bool) {
+    let mut settings = get_settings(&app);
+    settings.perf_metrics_enabled = enabled;
+    write_settings(&app, settings);
+}
+
+/// Switches between the simulated engine and the models. The loaded model is unloaded so the
+/// next transcription loads the other kind.
+#[tauri::command]
+#[specta::specta]
+pub fn set_mock_engine(
+    app: AppHandle,
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+    mock_engine: MockEngineSettings,
+) -> Result<(), String> {
+    if let Some(path) = mock_engine
+        .script_path
+        .as_deref()
+        .filter(|_| mock_engine.enabled)
+    {
+        mock_engine::validate_script(path)?;
+    }
+    let mut settings = get_settings(&app);
+    settings.mock_engine = mock_engine;
+    write_settings(&app, settings);
+    transcription_manager
+        .unload_model()
+        .map_err(|e| format!("Failed to unload the model: {}", e))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_idle_check_interval(app: AppHandle, seconds: u64) {
+    let mut settings = get_settings(&app);
+    settings.idle_check_interval_secs = seconds.max(1);
+    write_settings(&app, settings);
+}
+
+/// How long before an idle unload the `model-unload-pending` event is sent.
+#[tauri::command]
+#[specta::specta]
+pub fn set_unload_warning_seconds(app: AppHandle, seconds: u64) {
+    let mut settings = get_settings(&app);
+    settings.unload_warning_secs = seconds;
+    write_settings(&app, settings);
+}
+
+/// Cancels a pending idle unload by restarting the inactivity timer.
+#[tauri::command]
+#[specta::specta]
+pub fn keep_model_loaded(transcription_manager: State<'_, Arc<TranscriptionManager>>) {
+    transcription_manager.keep_model_loaded();
+}
+
 #[tauri::command]
 #[specta::specta]
 pub fn get_model_load_status(
@@ -38,3 +125,278 @@ pub fn unload_model_manually(
         .unload_model()
         .map_err(|e| format!("Failed to unload model: {}", e))
 }
+
+/// Unloads the model even while a recording or transcription is in progress.
+#[tauri::command]
+#[specta::specta]
+pub fn force_unload_model(
+    transcription_manager: State<'_, Arc<TranscriptionManager>>,
+) -> Result<(), String> {
+    transcription_manager
+        .force_unload_model()
+        .map_err(|e| format!("Failed to unload model: {}", e))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_transcription_providers() -> Vec<CloudProviderInfo> {
+    [
+        TranscriptionProvider::Local,
+        TranscriptionProvider::OpenAi,
+        TranscriptionProvider::Deepgram,
+        TranscriptionProvider::Azure,
+    ]
+    .into_iter()
+    .map(provider_info)
+    .collect()
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_low_confidence_threshold(app: AppHandle, threshold: f32) {
+    let mut settings = get_settings(&app);
+    settings.low_confidence_threshold = threshold.clamp(0.0, 1.0);
+    write_settings(&app, settings);
+}
+
+/// Marks low-confidence words in pasted text with `marker`, `None` turns marking off. Only
+/// providers whose `reports_confidence` is set produce any.
+#[tauri::command]
+#[specta::specta]
+pub fn set_low_confidence_marker(app: AppHandle, marker: Option<String>) {
+    let mut settings = get_settings(&app);
+    settings.low_confidence_marker = marker.filter(|m| !m.is_empty());
+    write_settings(&app, settings);
+}
+
+/// Lets engines that support it favour the custom words while decoding. Engines that don't
+/// keep correcting the text afterwards. `None` goes back to biasing wherever it is supported.
+#[tauri::command]
+#[specta::specta]
+pub fn set_vocabulary_biasing(app: AppHandle, enabled: Option<bool>) {
+    let mut settings = get_settings(&app);
+    settings.vocabulary_biasing = enabled;
+    write_settings(&app, settings);
+}
+
+/// Whether the selected model or provider can be biased toward the custom words: Whisper
+/// models and the OpenAI and Deepgram providers can, for the others the setting has no effect
+/// and custom words are only corrected afterwards.
+#[tauri::command]
+#[specta::specta]
+pub fn supports_vocabulary_bias(app: AppHandle) -> bool {
+    let settings = get_settings(&app);
+    match settings.transcription_provider {
+        TranscriptionProvider::Local => app
+            .state::<Arc<ModelManager>>()
+            .get_model_info(&settings.selected_model)
+            .is_some_and(|model| matches!(model.engine_type, EngineType::Whisper)),
+        provider => cloud_transcription::supports_vocabulary_bias(provider),
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_vocabulary_boost(app: AppHandle, boost: f32) -> Result<(), String> {
+    if !boost.is_finite() || boost <= 0.0 {
+        return Err("Boost must be a positive number".to_string());
+    }
+    let mut settings = get_settings(&app);
+    settings.vocabulary_boost = boost;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+/// Rescores transcriptions with the ARPA language model at `path`, `None` stops rescoring.
+/// The file is read right away so a broken one is reported here. Returns its n-gram count.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_language_model(app: AppHandle, path: Option<String>) -> Result<usize, String> {
+    let tm = app.state::<Arc<TranscriptionManager>>().inner().clone();
+    let model_path = path.clone();
+    let ngram_count =
+        tauri::async_runtime::spawn_blocking(move || tm.set_language_model(model_path.as_deref()))
+            .await
+            .map_err(|e| e.to_string())??;
+
+    let mut settings = get_settings(&app);
+    settings.language_model_path = path;
+    write_settings(&app, settings);
+    Ok(ngram_count)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_language_model_weight(app: AppHandle, weight: f64) -> Result<(), String> {
+    if !weight.is_finite() || weight <= 0.0 {
+        return Err("Weight must be a positive number".to_string());
+    }
+    let mut settings = get_settings(&app);
+    settings.language_model_weight = weight;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+/// Seconds a local transcription may run before the engine is reloaded, `None` waits
+/// indefinitely. Long recordings get proportionally more time.
+#[tauri::command]
+#[specta::specta]
+pub fn set_transcription_timeout(app: AppHandle, seconds: Option<u32>) -> Result<(), String> {
+    if seconds.is_some_and(|secs| secs < MIN_TRANSCRIPTION_TIMEOUT_SECS) {
+        return Err(format!(
+            "The transcription timeout must be at least {} seconds",
+            MIN_TRANSCRIPTION_TIMEOUT_SECS
+        ));
+    }
+    let mut settings = get_settings(&app);
+    settings.transcription_timeout_secs = seconds;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+/// Number of live chunks sent to a cloud provider at once. Local engines run one chunk at a
+/// time regardless.
+#[tauri::command]
+#[specta::specta]
+pub fn set_transcription_workers(app: AppHandle, workers: u32) -> Result<(), String> {
+    if workers == 0 || workers as usize > MAX_CHUNK_WORKERS {
+        return Err(format!(
+            "The number of workers must be between 1 and {}",
+            MAX_CHUNK_WORKERS
+        ));
+    }
+    let mut settings = get_settings(&app);
+    settings.transcription_workers = workers;
+    write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_filler_removal(app: AppHandle, enabled: bool) {
+    let mut settings = get_settings(&app);
+    settings.filler_removal = enabled;
+    write_settings(&app, settings);
+    crate::tray::refresh_tray_menu(&app);
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_disfluency_removal(app: AppHandle, enabled: bool) {
+    let mut settings = get_settings(&app);
+    settings.disfluency_removal = enabled;
+    write_settings(&app, settings);
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_disfluency_learning(app: AppHandle, enabled: bool) {
+    let mut settings = get_settings(&app);
+    settings.disfluency_learning = enabled;
+    write_settings(&app, settings);
+}
+
+/// Switches to `speaker`'s disfluency model, which starts out untrained for a new speaker.
+#[tauri::command]
+#[specta::specta]
+pub fn set_disfluency_speaker(app: AppHandle, speaker: String) -> Result<(), String> {
+    let speaker = speaker.trim();
+    if speaker.is_empty() {
+        return Err("Speaker name cannot be empty".to_string());
+    }
+    let mut settings = get_settings(&app);
+    settings.disfluency_speaker = speaker.to_string();
+    write_settings(&app, settings);
+    Ok(())
+}
+
+/// The fillers `speaker`'s model currently removes, the current speaker when `None`.
+#[tauri::command]
+#[specta::specta]
+pub fn get_disfluency_model(
+    app: AppHandle,
+    disfluency_manager: State<'_, Arc<DisfluencyManager>>,
+    speaker: Option<String>,
+) -> DisfluencyModelSummary {
+    let speaker = speaker.unwrap_or_else(|| get_settings(&app).disfluency_speaker);
+    disfluency_manager.summary(&speaker)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn reset_disfluency_model(
+    disfluency_manager: State<'_, Arc<DisfluencyManager>>,
+    speaker: String,
+) -> Result<(), String> {
+    disfluency_manager
+        .reset(&speaker)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_transcription_provider(app: AppHandle, provider: TranscriptionProvider) {
+    let mut settings = get_settings(&app);
+    settings.transcription_provider = provider;
+    write_settings(&app, settings);
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_cloud_fallback_to_local(app: AppHandle, enabled: bool) {
+    let mut settings = get_settings(&app);
+    settings.cloud_fallback_to_local = enabled;
+    write_settings(&app, settings);
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_azure_speech_region(app: AppHandle, region: String) {
+    let mut settings = get_settings(&app);
+    settings.azure_speech_region = region.trim().to_string();
+    write_settings(&app, settings);
+}
+
+/// Stores the API key for a cloud provider in the OS keychain. An empty key removes it.
+#[tauri::command]
+#[specta::specta]
+pub fn set_cloud_api_key(provider: TranscriptionProvider, api_key: String) -> Result<(), String> {
+    if provider == TranscriptionProvider::Local {
+        return Err("The local model does not use an API key".to_string());
+    }
+    crate::secrets::set_api_key(provider.id(), api_key.trim())
+        .map_err(|e| format!("Failed to store API key: {}", e))
+}
+
+/// The transcription held for readback, waiting to be confirmed or discarded.
+#[tauri::command]
+#[specta::specta]
+pub fn get_pending_readback(app: AppHandle) -> Option<String> {
+    readback::pending_text(&app)
+}
+
+/// Reads the held transcription aloud again, or the latest one when none is held.
+#[tauri::command]
+#[specta::specta]
+pub async fn read_back(app: AppHandle) -> Result<(), String> {
+    readback::read_back(&app).await
+}
+
+/// Replaces the held transcription with the text edited in the preview.
+#[tauri::command]
+#[specta::specta]
+pub fn edit_pending_readback(app: AppHandle, text: String) -> Result<(), String> {
+    readback::edit(&app, text)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn confirm_readback(app: AppHandle) -> Result<(), String> {
+    readback::confirm(&app).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn discard_readback(app: AppHandle) -> bool {
+    readback::discard(&app)
+}
@@ -0,0 +1,129 @@
+//! The `org.handy.Transcription` D-Bus service on the session bus, so compositor key
+//! bindings and scripts can control dictation without a global shortcut:
+//!
+//! ```sh
+//! busctl --user call org.handy.Transcription /org/handy/Transcription \
+//!     org.handy.Transcription StartRecording
+//! ```
+//!
+//! `GetStatus` answers `idle`, `recording` or `paused`, and the `TranscriptReady` signal
+//! carries the text of each finished dictation.
+
+use crate::managers::audio::AudioRecordingManager;
+use crate::shortcut;
+use log::{debug, info, warn};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+use zbus::object_server::SignalEmitter;
+use zbus::{connection, fdo, interface, Connection};
+
+pub const SERVICE_NAME: &str = "org.handy.Transcription";
+pub const OBJECT_PATH: &str = "/org/handy/Transcription";
+
+/// The connection the service is served on, kept to emit signals
+#[derive(Default)]
+pub struct DbusService {
+    connection: Mutex<Option<Connection>>,
+}
+
+struct Transcription {
+    app: AppHandle,
+}
+
+impl Transcription {
+    fn is_recording(&self) -> bool {
+        self.app
+            .state::<Arc<AudioRecordingManager>>()
+            .is_recording()
+    }
+}
+
+#[interface(name = "org.handy.Transcription")]
+impl Transcription {
+    /// Starts a dictation, as the transcribe shortcut does.
+    async fn start_recording(&self) -> fdo::Result<()> {
+        if self.is_recording() {
+            return Err(fdo::Error::Failed(
+                "A dictation is already in progress".to_string(),
+            ));
+        }
+        shortcut::toggle_binding(&self.app, "transcribe", "dbus");
+        Ok(())
+    }
+
+    /// Stops the dictation in progress, which is then transcribed and delivered.
+    async fn stop_recording(&self) -> fdo::Result<()> {
+        if !self.is_recording() {
+            return Err(fdo::Error::Failed(
+                "No dictation is in progress".to_string(),
+            ));
+        }
+        shortcut::toggle_binding(&self.app, "transcribe", "dbus");
+        Ok(())
+    }
+
+    async fn get_status(&self) -> String {
+        let rm = self.app.state::<Arc<AudioRecordingManager>>();
+        let status = if rm.is_paused() {
+            "paused"
+        } else if rm.is_recording() {
+            "recording"
+        } else {
+            "idle"
+        };
+        status.to_string()
+    }
+
+    #[zbus(signal)]
+    async fn transcript_ready(emitter: &SignalEmitter<'_>, text: &str) -> zbus::Result<()>;
+}
+
+/// Claims the service name on the session bus in the background. Without a session bus,
+/// e.g. in a bare X session, the service is simply not offered.
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let service = Transcription { app: app.clone() };
+        let connection = connection::Builder::session()
+            .and_then(|builder| builder.name(SERVICE_NAME))
+            .and_then(|builder| builder.serve_at(OBJECT_PATH, service));
+        let connection = match connection {
+            Ok(builder) => builder.build().await,
+            Err(e) => Err(e),
+        };
+        match connection {
+            Ok(connection) => {
+                info!("D-Bus service {} registered", SERVICE_NAME);
+                *app.state::<DbusService>().connection.lock().unwrap() = Some(connection);
+            }
+            Err(e) => warn!("Failed to register the D-Bus service: {}", e),
+        }
+    });
+}
+
+/// Emits `TranscriptReady` with the text of a finished dictation.
+pub fn transcript_ready(app: &AppHandle, text: &str) {
+    let Some(connection) = app
+        .state::<DbusService>()
+        .connection
+        .lock()
+        .unwrap()
+        .clone()
+    else {
+        return;
+    };
+    let text = text.to_string();
+    tauri::async_runtime::spawn(async move {
+        let emitter = match SignalEmitter::new(&connection, OBJECT_PATH) {
+            Ok(emitter) => emitter,
+            Err(e) => {
+                warn!("Failed to emit TranscriptReady: {}", e);
+                return;
+            }
+        };
+        match Transcription::transcript_ready(&emitter, &text).await {
+            Ok(()) => debug!("Emitted TranscriptReady"),
+            Err(e) => warn!("Failed to emit TranscriptReady: {}", e),
+        }
+    });
+}
@@ -0,0 +1,498 @@
+//! The diagnostics bundle attached to bug reports: a zip of the recent logs, the settings,
+//! the models with their file hashes, the audio devices and an optional loopback latency
+//! test. Nothing said ends up in it: log lines that carry transcribed text have the text
+//! cut, settings holding the user's own words, prompts or secrets are reduced to a count,
+//! API keys and the webhook and proxy URLs are cut wherever they still turn up, and the
+//! home directory is replaced by `~` throughout.
+
+use crate::audio_feedback;
+use crate::audio_toolkit::{list_input_devices, list_output_devices, CpalDeviceInfo};
+use crate::helpers::system_memory;
+use crate::managers::audio::AudioRecordingManager;
+use crate::managers::model::ModelManager;
+use crate::managers::model_integrity;
+use crate::managers::transcription::TranscriptionManager;
+use crate::managers::usage::UsageCounters;
+use crate::power_policy;
+use crate::settings::{get_settings, AppSettings};
+use log::info;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+/// Settings holding the user's own words, prompts, commands or secrets
+const REDACTED_SETTINGS: &[&str] = &[
+    "custom_words",
+    "proper_nouns",
+    "snippets",
+    "regex_rules",
+    "session_tag_rules",
+    "session_hooks",
+    "profiles",
+    "trigger_apps",
+    "post_process_prompts",
+    "post_process_api_keys",
+    "webhook_url",
+    "download_proxy",
+    "low_confidence_marker",
+];
+
+/// Log messages followed by what was said, which is cut from the first `: ` after them
+const CONTENT_MESSAGES: &[&str] = &[
+    "Transcription result",
+    "Transcription completed in",
+    "Rescored with",
+    "Removed disfluencies",
+    "Expanded snippets",
+    "Self-test heard",
+];
+
+/// The loopback test plays `CLICKS` clicks `CLICK_INTERVAL` apart, starting
+/// `LOOPBACK_LEAD_IN` into a recording of `LOOPBACK_DURATION`.
+const CLICKS: usize = 4;
+const CLICK_INTERVAL: Duration = Duration::from_secs(1);
+const CLICK_LENGTH: Duration = Duration::from_millis(5);
+const CLICK_SAMPLE_RATE: u32 = 48000;
+const LOOPBACK_DURATION: Duration = Duration::from_secs(5);
+const LOOPBACK_LEAD_IN: Duration = Duration::from_millis(300);
+/// How long after a click was played it may still be heard
+const MAX_LATENCY: Duration = Duration::from_millis(500);
+/// Quietest recorded click that counts as heard
+const MIN_CLICK_LEVEL: f32 = 0.02;
+
+#[derive(Serialize)]
+struct Summary {
+    app_version: String,
+    os: String,
+    arch: String,
+    total_memory_mb: Option<u64>,
+    available_memory_mb: Option<u64>,
+    on_battery: bool,
+    loaded_model: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ModelFile {
+    name: String,
+    size_bytes: Option<u64>,
+    sha256: Option<String>,
+    /// The hash the catalog lists, if any
+    expected_sha256: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ModelDiagnostics {
+    id: String,
+    engine_type: String,
+    files: Vec<ModelFile>,
+}
+
+#[derive(Serialize)]
+struct AudioDevices {
+    inputs: Vec<String>,
+    default_input: Option<String>,
+    outputs: Vec<String>,
+    default_output: Option<String>,
+    selected_microphone: Option<String>,
+    selected_output_device: Option<String>,
+    loopback: Option<LoopbackResult>,
+}
+
+/// Round trip from the speakers to the microphone, accurate to about the input device's
+/// buffer size.
+#[derive(Clone, Debug, Serialize)]
+struct LoopbackResult {
+    clicks_played: usize,
+    latencies_ms: Vec<u64>,
+    median_ms: Option<u64>,
+    /// Loudest sample recorded, from 0 to 1
+    peak_level: f32,
+    error: Option<String>,
+}
+
+/// Writes the bundle to `path`, running the loopback test first when asked. Takes a while:
+/// the model files are hashed and the test records for five seconds.
+pub fn generate(app: &AppHandle, path: &Path, loopback_test: bool) -> Result<(), String> {
+    let home = app.path().home_dir().ok();
+    let home = home.as_ref().map(|home| home.to_string_lossy().to_string());
+    let settings = get_settings(app);
+    let secrets = secrets(&settings);
+    let redact = |text: String| scrub(&redact_home(&text, home.as_deref()), &secrets);
+    let loopback = loopback_test.then(|| loopback_latency(app));
+
+    let mut files = vec![
+        (
+            "summary.json".to_string(),
+            to_json(&summary(app, &settings)),
+        ),
+        (
+            "settings.json".to_string(),
+            redact(to_json(&redact_settings(&settings)?)),
+        ),
+        ("models.json".to_string(), redact(to_json(&models(app)))),
+        (
+            "audio.json".to_string(),
+            to_json(&audio_devices(&settings, loopback)),
+        ),
+    ];
+    if let Some(usage) = app.try_state::<Arc<UsageCounters>>() {
+        files.push(("usage.json".to_string(), to_json(&usage.snapshot())));
+    }
+    let log_dir = app.path().app_log_dir().map_err(|e| e.to_string())?;
+    for entry in fs::read_dir(&log_dir).into_iter().flatten().flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !name.ends_with(".log") {
+            continue;
+        }
+        let log = fs::read_to_string(entry.path()).map_err(|e| e.to_string())?;
+        files.push((format!("logs/{}", name), redact(redact_log(&log))));
+    }
+
+    let file =
+        File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut zip = ZipWriter::new(file);
+    for (name, contents) in files {
+        zip.start_file(name, SimpleFileOptions::default())
+            .map_err(|e| e.to_string())?;
+        zip.write_all(contents.as_bytes())
+            .map_err(|e| e.to_string())?;
+    }
+    zip.finish().map_err(|e| e.to_string())?;
+    info!("Wrote diagnostics to {}", path.display());
+    Ok(())
+}
+
+fn to_json(value: &impl Serialize) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|e| format!("\"{}\"", e))
+}
+
+fn summary(app: &AppHandle, settings: &AppSettings) -> Summary {
+    const MB: u64 = 1024 * 1024;
+    let memory = system_memory::system_memory();
+    Summary {
+        app_version: app.package_info().version.to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        total_memory_mb: memory.map(|memory| memory.total_bytes / MB),
+        available_memory_mb: memory.map(|memory| memory.available_bytes / MB),
+        on_battery: power_policy::state(settings).on_battery,
+        loaded_model: app.state::<Arc<TranscriptionManager>>().get_current_model(),
+    }
+}
+
+/// The downloaded models with the hash of each file.
+fn models(app: &AppHandle) -> Vec<ModelDiagnostics> {
+    let model_manager = app.state::<Arc<ModelManager>>();
+    let models_dir = model_manager.models_dir();
+    model_manager
+        .get_available_models()
+        .into_iter()
+        .filter(|model| model.is_downloaded)
+        .map(|model| ModelDiagnostics {
+            files: model_integrity::expected_files(&model)
+                .into_iter()
+                .map(|name| {
+                    let path = model_integrity::file_path(&models_dir, &model, &name);
+                    ModelFile {
+                        size_bytes: path.metadata().ok().map(|metadata| metadata.len()),
+                        sha256: model_integrity::sha256_file(&path).ok(),
+                        expected_sha256: model.sha256.get(&name).cloned(),
+                        name,
+                    }
+                })
+                .collect(),
+            id: model.id,
+            engine_type: format!("{:?}", model.engine_type),
+        })
+        .collect()
+}
+
+fn audio_devices(settings: &AppSettings, loopback: Option<LoopbackResult>) -> AudioDevices {
+    let inputs = list_input_devices().unwrap_or_default();
+    let outputs = list_output_devices().unwrap_or_default();
+    let default_name = |devices: &[CpalDeviceInfo]| {
+        devices
+            .iter()
+            .find(|device| device.is_default)
+            .map(|device| device.name.clone())
+    };
+    AudioDevices {
+        default_input: default_name(&inputs),
+        default_output: default_name(&outputs),
+        inputs: inputs.into_iter().map(|device| device.name).collect(),
+        outputs: outputs.into_iter().map(|device| device.name).collect(),
+        selected_microphone: settings.selected_microphone.clone(),
+        selected_output_device: settings.selected_output_device.clone(),
+        loopback,
+    }
+}
+
+/// Plays clicks through the speakers while the microphone records, and times how long
+/// each took to be heard.
+fn loopback_latency(app: &AppHandle) -> LoopbackResult {
+    let failed = |error: String| LoopbackResult {
+        clicks_played: 0,
+        latencies_ms: Vec::new(),
+        median_ms: None,
+        peak_level: 0.0,
+        error: Some(error),
+    };
+    let rm = Arc::clone(&app.state::<Arc<AudioRecordingManager>>());
+    if rm.is_recording() {
+        return failed("A recording was in progress".to_string());
+    }
+
+    let capture_started = std::time::Instant::now();
+    let capture = thread::spawn(move || rm.capture_input(LOOPBACK_DURATION));
+    thread::sleep(LOOPBACK_LEAD_IN);
+    let played = audio_feedback::play_samples_blocking(
+        app,
+        click_train(CLICK_SAMPLE_RATE),
+        CLICK_SAMPLE_RATE,
+    );
+    let captured = capture
+        .join()
+        .map_err(|_| "The recording thread panicked".to_string())
+        .and_then(|result| result.map_err(|e| e.to_string()));
+
+    let (play_started, (samples, sample_rate)) = match (played, captured) {
+        (Ok(play_started), Ok(captured)) => (play_started, captured),
+        (Err(e), _) => return failed(format!("Failed to play the clicks: {}", e)),
+        (_, Err(e)) => return failed(format!("Failed to record: {}", e)),
+    };
+    let peak_level = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    let play_offset = play_started.duration_since(capture_started).as_secs_f64();
+    let latencies_ms: Vec<u64> = latencies(&onsets(&samples, sample_rate), play_offset)
+        .into_iter()
+        .map(|latency| (latency * 1000.0).round() as u64)
+        .collect();
+    let median_ms = median(&mut latencies_ms.clone());
+    LoopbackResult {
+        clicks_played: CLICKS,
+        error: latencies_ms
+            .is_empty()
+            .then(|| "The microphone didn't pick up the clicks".to_string()),
+        latencies_ms,
+        median_ms,
+        peak_level,
+    }
+}
+
+/// `CLICKS` short 2 kHz bursts, `CLICK_INTERVAL` apart.
+fn click_train(sample_rate: u32) -> Vec<f32> {
+    let rate = sample_rate as f64;
+    let interval = (CLICK_INTERVAL.as_secs_f64() * rate) as usize;
+    let click = (CLICK_LENGTH.as_secs_f64() * rate) as usize;
+    let mut samples = vec![0.0; interval * (CLICKS - 1) + click];
+    for index in 0..CLICKS {
+        for n in 0..click {
+            let t = n as f64 / rate;
+            samples[index * interval + n] =
+                (0.8 * (2.0 * std::f64::consts::PI * 2000.0 * t).sin()) as f32;
+        }
+    }
+    samples
+}
+
+/// Times in seconds where the recording gets loud after a quiet stretch, at most one per
+/// half click interval.
+fn onsets(samples: &[f32], sample_rate: u32) -> Vec<f64> {
+    let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    let threshold = (peak / 2.0).max(MIN_CLICK_LEVEL);
+    let gap = (CLICK_INTERVAL.as_secs_f64() / 2.0 * sample_rate as f64) as usize;
+    let mut onsets = Vec::new();
+    let mut last: Option<usize> = None;
+    for (index, sample) in samples.iter().enumerate() {
+        if sample.abs() >= threshold && last.is_none_or(|last| index - last >= gap) {
+            onsets.push(index as f64 / sample_rate as f64);
+            last = Some(index);
+        }
+    }
+    onsets
+}
+
+/// How long after each click, played from `play_offset` into the recording, it was heard.
+/// Clicks never heard are left out.
+fn latencies(onsets: &[f64], play_offset: f64) -> Vec<f64> {
+    (0..CLICKS)
+        .filter_map(|index| {
+            let played = play_offset + index as f64 * CLICK_INTERVAL.as_secs_f64();
+            onsets
+                .iter()
+                .map(|onset| onset - played)
+                .find(|latency| (0.0..MAX_LATENCY.as_secs_f64()).contains(latency))
+        })
+        .collect()
+}
+
+fn median(values: &mut [u64]) -> Option<u64> {
+    values.sort_unstable();
+    values.get(values.len() / 2).copied()
+}
+
+/// The settings as JSON, with the ones in `REDACTED_SETTINGS` replaced by how many entries
+/// they have.
+fn redact_settings(settings: &AppSettings) -> Result<Value, String> {
+    let mut value = serde_json::to_value(settings).map_err(|e| e.to_string())?;
+    let object = value
+        .as_object_mut()
+        .ok_or("Settings must be a JSON object")?;
+    for key in REDACTED_SETTINGS {
+        if let Some(setting) = object.get_mut(*key) {
+            *setting = redacted(setting);
+        }
+    }
+    Ok(value)
+}
+
+fn redacted(value: &Value) -> Value {
+    match value {
+        Value::Null => Value::Null,
+        Value::String(text) if text.is_empty() => Value::String(String::new()),
+        Value::Array(items) => Value::String(format!("[redacted, {} entries]", items.len())),
+        Value::Object(entries) => Value::String(format!("[redacted, {} entries]", entries.len())),
+        _ => Value::String("[redacted]".to_string()),
+    }
+}
+
+/// Cuts what was said from the log lines that carry it. A transcription spanning lines
+/// continues on lines that don't start with a timestamp, those are cut too.
+fn redact_log(log: &str) -> String {
+    let mut redacted = String::with_capacity(log.len());
+    let mut in_content = false;
+    for line in log.lines() {
+        if !line.starts_with('[') {
+            if in_content {
+                continue;
+            }
+        } else {
+            in_content = false;
+        }
+        let cut = CONTENT_MESSAGES.iter().find_map(|message| {
+            let start = line.find(message)?;
+            let colon = line[start..].find(": ")?;
+            Some(start + colon + 2)
+        });
+        match cut {
+            Some(cut) => {
+                redacted.push_str(&line[..cut]);
+                redacted.push_str("[redacted]");
+                in_content = true;
+            }
+            None => redacted.push_str(line),
+        }
+        redacted.push('\n');
+    }
+    redacted
+}
+
+/// Values of the settings that must never leave the machine, wherever they turn up
+fn secrets(settings: &AppSettings) -> Vec<String> {
+    settings
+        .post_process_api_keys
+        .values()
+        .chain([&settings.webhook_url])
+        .chain(settings.download_proxy.as_ref())
+        .filter(|secret| !secret.trim().is_empty())
+        .cloned()
+        .collect()
+}
+
+fn scrub(text: &str, secrets: &[String]) -> String {
+    secrets.iter().fold(text.to_string(), |text, secret| {
+        text.replace(secret, "[redacted]")
+    })
+}
+
+fn redact_home(text: &str, home: Option<&str>) -> String {
+    match home {
+        Some(home) if home.len() > 1 => text.replace(home, "~"),
+        _ => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_log() {
+        let log = "[2025-01-01][12:00:00][handy::actions][DEBUG] Transcription completed in 1.2s: 'call mom\nabout the cake'\n\
+            [2025-01-01][12:00:00][handy::managers::transcription][INFO] Transcription result: call mom\n\
+            [2025-01-01][12:00:01][handy::actions][DEBUG] Text pasted successfully in 20ms\n";
+        assert_eq!(
+            redact_log(log),
+            "[2025-01-01][12:00:00][handy::actions][DEBUG] Transcription completed in 1.2s: [redacted]\n\
+            [2025-01-01][12:00:00][handy::managers::transcription][INFO] Transcription result: [redacted]\n\
+            [2025-01-01][12:00:01][handy::actions][DEBUG] Text pasted successfully in 20ms\n"
+        );
+    }
+
+    #[test]
+    fn test_api_key_never_reaches_the_bundle() {
+        let mut settings = crate::settings::get_default_settings();
+        settings
+            .post_process_api_keys
+            .insert("openai".to_string(), "sk-test-4f9a1c".to_string());
+        let secrets = secrets(&settings);
+
+        let settings_json = to_json(&redact_settings(&settings).unwrap());
+        assert!(!scrub(&settings_json, &secrets).contains("sk-test-4f9a1c"));
+        // Even a log line dumping the settings loses the key
+        let log = format!(
+            "[2025-01-01][12:00:00][handy::settings][DEBUG] Settings: {:?}\n",
+            settings
+        );
+        assert!(log.contains("sk-test-4f9a1c"));
+        assert!(!scrub(&redact_log(&log), &secrets).contains("sk-test-4f9a1c"));
+    }
+
+    #[test]
+    fn test_redacted_setting_keeps_only_the_count() {
+        assert_eq!(
+            redacted(&serde_json::json!([{ "word": "Kubernetes" }, { "word": "Handy" }])),
+            Value::String("[redacted, 2 entries]".to_string())
+        );
+        assert_eq!(
+            redacted(&serde_json::json!("https://hooks.example.com/secret")),
+            Value::String("[redacted]".to_string())
+        );
+        assert_eq!(redacted(&Value::Null), Value::Null);
+        assert_eq!(
+            redact_home("/home/alex/models", Some("/home/alex")),
+            "~/models"
+        );
+    }
+
+    #[test]
+    fn test_loopback_latencies() {
+        let rate = 16000;
+        let mut recording = vec![0.0f32; rate as usize * 5];
+        // Clicks played 0.3s in, heard 120ms later; the last one is lost
+        for index in 0..CLICKS - 1 {
+            let heard = ((0.42 + index as f64) * rate as f64) as usize;
+            recording[heard..heard + 80].fill(0.3);
+        }
+        let latencies = latencies(&onsets(&recording, rate), 0.3);
+        assert_eq!(latencies.len(), CLICKS - 1);
+        assert!(latencies
+            .iter()
+            .all(|latency| (latency - 0.12).abs() < 0.001));
+        assert_eq!(median(&mut vec![140, 120, 500]), Some(140));
+
+        let clicks = click_train(CLICK_SAMPLE_RATE);
+        assert_eq!(
+            onsets(&clicks, CLICK_SAMPLE_RATE).len(),
+            CLICKS,
+            "every click is found in the clean signal"
+        );
+    }
+}
@@ -0,0 +1,226 @@
+//! Rule-based filler removal: the hesitation sounds of the transcription language, and for
+//! English the discourse fillers "like", "you know" and "I mean" and words repeated by
+//! accident ("the the"). Unlike a regex, a small heuristic keeps "like" where it works as a
+//! verb or preposition, and removal never merges two sentences.
+
+/// Hesitations per language. Only sounds that aren't words of the language are listed,
+/// "um" for example means "around" in German.
+const HESITATIONS: &[(&str, &[&str])] = &[
+    ("en", &["um", "umm", "uh", "uhm", "er", "erm", "hmm", "mm"]),
+    ("de", &["äh", "ähm", "öh", "öhm", "hm", "hmm", "ehm"]),
+    ("fr", &["euh", "heu", "hum", "hmm"]),
+    ("es", &["eh", "ehm", "em", "mmm"]),
+    ("it", &["eh", "ehm", "mmm"]),
+    ("pt", &["hum", "ahn", "hmm"]),
+    ("nl", &["eh", "ehm", "uh", "uhm"]),
+];
+
+/// Used when the language is detected automatically or has no list of its own
+const COMMON_HESITATIONS: &[&str] = &["uh", "uhm", "umm", "erm", "hmm", "mm", "ähm", "euh", "ehm"];
+
+/// "like" right after these, without a comma in between, is a verb or part of the phrase
+/// ("I like", "looks like")
+const LIKE_KEEP_AFTER: &[&str] = &[
+    "i",
+    "you",
+    "we",
+    "they",
+    "i'd",
+    "you'd",
+    "we'd",
+    "they'd",
+    "would",
+    "do",
+    "don't",
+    "does",
+    "doesn't",
+    "did",
+    "didn't",
+    "not",
+    "really",
+    "just",
+    "also",
+    "look",
+    "looks",
+    "looked",
+    "feel",
+    "feels",
+    "felt",
+    "seem",
+    "seems",
+    "sound",
+    "sounds",
+    "something",
+    "nothing",
+    "more",
+    "much",
+];
+
+/// Two-word fillers, removed only when set off by commas or sentence ends
+const PHRASES: &[(&str, &str)] = &[("you", "know"), ("i", "mean")];
+
+/// Words that are doubled on purpose ("I had had enough", "very very good")
+const REPEATS_KEPT: &[&str] = &[
+    "had", "that", "very", "really", "so", "no", "yes", "yeah", "ha", "bye", "is",
+];
+
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric() || *c == '\'')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn ends_clause(word: &str) -> bool {
+    word.ends_with([',', '.', '?', '!', ';', ':'])
+}
+
+fn ends_sentence(word: &str) -> bool {
+    word.ends_with(['.', '?', '!'])
+}
+
+/// Removes fillers from `text`. `language` is a language setting like "en" or "zh-Hans",
+/// anything without a hesitation list of its own, including "auto", uses a cautious
+/// common one.
+pub fn remove_fillers(text: &str, language: &str) -> String {
+    let language = language.split('-').next().unwrap_or(language);
+    let hesitations = HESITATIONS
+        .iter()
+        .find(|(code, _)| *code == language)
+        .map(|(_, words)| *words)
+        .unwrap_or(COMMON_HESITATIONS);
+    let english = language == "en";
+
+    remove_spans(text, |words, i| {
+        let word = normalize(words[i]);
+        if word.is_empty() {
+            return (0, false);
+        }
+        // "Well, um, I think" keeps its comma
+        if hesitations.contains(&word.as_str()) {
+            return (1, false);
+        }
+        if !english {
+            return (0, false);
+        }
+
+        let previous = i.checked_sub(1).map(|p| words[p]);
+        let set_off_before = previous.map_or(true, ends_clause);
+
+        if let Some(next) = words.get(i + 1) {
+            let is_phrase = PHRASES
+                .iter()
+                .any(|(first, second)| word == *first && normalize(next) == *second);
+            // "it's, you know, fine" loses both commas
+            if is_phrase && set_off_before && ends_clause(next) {
+                return (2, true);
+            }
+
+            // "the the" loses its first word, a deliberate "no, no" has a comma
+            if word == normalize(next)
+                && !ends_clause(words[i])
+                && !REPEATS_KEPT.contains(&word.as_str())
+            {
+                return (1, false);
+            }
+        }
+
+        if word == "like" {
+            let parenthetical = words[i].ends_with(',')
+                || (previous.is_some_and(|p| p.ends_with(',')) && ends_sentence(words[i]));
+            let verb = previous.is_some_and(|p| {
+                !ends_clause(p) && LIKE_KEEP_AFTER.contains(&normalize(p).as_str())
+            });
+            if parenthetical && !verb {
+                return (1, true);
+            }
+        }
+
+        (0, false)
+    })
+}
+
+/// Removes the words of `text` that `span_at` marks, keeping sentence punctuation and
+/// capitalization intact. `span_at` is called with the words and a position and returns how
+/// many words starting there to drop, 0 to keep the word, and whether a comma before them
+/// only set them off and goes too.
+pub(crate) fn remove_spans(
+    text: &str,
+    span_at: impl Fn(&[&str], usize) -> (usize, bool),
+) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut kept: Vec<String> = Vec::with_capacity(words.len());
+    let mut capitalize_next = false;
+
+    let mut i = 0;
+    while i < words.len() {
+        let (span, drop_comma) = span_at(&words, i);
+        let span = span.min(words.len() - i);
+        if span == 0 {
+            let mut word = words[i].to_string();
+            if capitalize_next {
+                word = capitalize(&word);
+                capitalize_next = false;
+            }
+            kept.push(word);
+            i += 1;
+            continue;
+        }
+
+        // A filler that started a sentence passes its capital on, one that ended a sentence
+        // passes on its full stop; commas around it go with it
+        let first = words[i];
+        let last = words[i + span - 1];
+        if first.starts_with(|c: char| c.is_uppercase()) {
+            capitalize_next = true;
+        }
+        if let Some(end) = last.chars().last().filter(|c| matches!(c, '.' | '?' | '!')) {
+            if let Some(previous) = kept.last_mut() {
+                previous.truncate(previous.trim_end_matches([',', ';', ':']).len());
+                previous.push(end);
+            }
+            capitalize_next = true;
+        } else if drop_comma && last.ends_with(',') {
+            if let Some(previous) = kept.last_mut() {
+                previous.truncate(previous.trim_end_matches(',').len());
+            }
+        }
+        i += span;
+    }
+
+    kept.join(" ")
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remove_fillers() {
+        assert_eq!(
+            remove_fillers("Um, so it was, like, huge, you know.", "en"),
+            "So it was huge."
+        );
+        assert_eq!(
+            remove_fillers("I like, a lot. Looks like rain. Do you know him?", "en"),
+            "I like, a lot. Looks like rain. Do you know him?"
+        );
+        assert_eq!(
+            remove_fillers("The the report. I had had enough.", "en"),
+            "The report. I had had enough."
+        );
+        assert_eq!(
+            remove_fillers("Wir treffen uns ähm um fünf.", "de"),
+            "Wir treffen uns um fünf."
+        );
+        assert_eq!(remove_fillers("Euh, c'est bon.", "auto"), "C'est bon.");
+    }
+}
@@ -0,0 +1,91 @@
+//! Inserts text at the caret through the platform's accessibility interfaces instead of a
+//! simulated paste. The clipboard is left alone, and apps that intercept Cmd+V or Ctrl+V
+//! still receive the text. Where no interface reaches the focused field, the caller falls
+//! back to pasting.
+
+#[cfg(target_os = "macos")]
+use log::debug;
+
+/// Sets the focused element's selected text, which replaces the selection or inserts at the
+/// caret. Some apps accept the attribute but ignore it, so with nothing selected the length
+/// of the field is checked to have changed.
+#[cfg(target_os = "macos")]
+const INSERT_SCRIPT: &[&str] = &[
+    "on run argv",
+    "tell application \"System Events\"",
+    "set field to focused UI element of (first application process whose frontmost is true)",
+    "set selectedText to value of attribute \"AXSelectedText\" of field",
+    "set lengthBefore to value of attribute \"AXNumberOfCharacters\" of field",
+    "set value of attribute \"AXSelectedText\" of field to item 1 of argv",
+    "if selectedText is not \"\" then return \"inserted\"",
+    "if (value of attribute \"AXNumberOfCharacters\" of field) is lengthBefore then return \"ignored\"",
+    "return \"inserted\"",
+    "end tell",
+    "end run",
+];
+
+/// Inserts `text` into the focused field at the caret. Returns `false` when the field can't
+/// be reached this way, in which case nothing was inserted.
+#[cfg(target_os = "macos")]
+pub fn insert_at_caret(text: &str) -> bool {
+    if text.is_empty() {
+        return true;
+    }
+    let mut command = std::process::Command::new("osascript");
+    for line in INSERT_SCRIPT {
+        command.args(["-e", line]);
+    }
+    // Passed as an argument, the text needs no AppleScript escaping
+    match command.arg(text).output() {
+        Ok(output) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).trim() == "inserted"
+        }
+        Ok(output) => {
+            debug!(
+                "Accessibility insertion failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            false
+        }
+        Err(e) => {
+            debug!("Failed to run osascript: {}", e);
+            false
+        }
+    }
+}
+
+/// Classic edit controls replace their selection on `EM_REPLACESEL`, which Windows carries
+/// across processes. UI Automation's patterns can only replace a field's whole value, so
+/// other fields are left to the paste.
+#[cfg(target_os = "windows")]
+pub fn insert_at_caret(text: &str) -> bool {
+    use windows::Win32::Foundation::{LPARAM, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::SendMessageW;
+
+    const EM_REPLACESEL: u32 = 0x00C2;
+    const ES_READONLY: i32 = 0x0800;
+
+    let Some((control, style)) = super::focused_app::focused_edit_control() else {
+        return false;
+    };
+    if style & ES_READONLY != 0 {
+        return false;
+    }
+    let wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+    unsafe {
+        // A true wparam lets the app undo the insertion like typing
+        SendMessageW(
+            control,
+            EM_REPLACESEL,
+            Some(WPARAM(1)),
+            Some(LPARAM(wide.as_ptr() as isize)),
+        );
+    }
+    true
+}
+
+/// Text fields on Linux are only exposed through AT-SPI, which isn't wired up yet.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn insert_at_caret(_text: &str) -> bool {
+    false
+}
@@ -0,0 +1,318 @@
+//! What the user is dictating into: the focused window, the text selected in it and the kind
+//! of field that has the focus. Used to give LLM post-processing the document's tone and
+//! terminology, only when the user opted in, to fit the output to the field and to keep
+//! dictation out of password fields.
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+use std::process::Command;
+
+/// Selected text longer than this is cut, the context is meant to be lightweight
+const MAX_SELECTION_CHARS: usize = 1000;
+
+/// Words in the name of applications whose text fields hold code
+const CODE_EDITORS: &[&str] = &[
+    "code",
+    "visual studio",
+    "vscodium",
+    "cursor",
+    "xcode",
+    "intellij",
+    "idea",
+    "pycharm",
+    "webstorm",
+    "goland",
+    "clion",
+    "rider",
+    "rustrover",
+    "android studio",
+    "sublime text",
+    "zed",
+    "neovide",
+    "emacs",
+];
+
+/// The kind of text field that has the keyboard focus.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FieldKind {
+    /// A newline would submit the field or be dropped
+    SingleLine,
+    MultiLine,
+    /// An editor that indents new lines itself
+    Code,
+}
+
+/// Detects the focused field from its accessibility role, or from the application when it is
+/// a code editor. `None` when the platform can't tell.
+pub fn focused_field_kind() -> Option<FieldKind> {
+    if focused_app().as_deref().is_some_and(is_code_editor) {
+        return Some(FieldKind::Code);
+    }
+    focused_field_role()
+}
+
+/// Name of the focused application, `None` when the platform can't tell.
+pub fn focused_app() -> Option<String> {
+    // Windows titles end with the application, e.g. "main.rs - Visual Studio Code"
+    app_name()
+        .or_else(|| window_title().and_then(|title| title.rsplit(" - ").next().map(str::to_string)))
+}
+
+/// Whether the focused field hides what is typed into it, like a password field. `false` when
+/// the platform can't tell.
+pub fn is_secure_field() -> bool {
+    focused_field_is_secure().unwrap_or(false)
+}
+
+fn is_code_editor(app: &str) -> bool {
+    let words: Vec<String> = app
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    CODE_EDITORS.iter().any(|editor| {
+        let editor: Vec<&str> = editor.split(' ').collect();
+        words
+            .windows(editor.len())
+            .any(|window| window.iter().zip(&editor).all(|(a, b)| a == b))
+    })
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct WorkspaceContext {
+    pub app_name: Option<String>,
+    pub window_title: Option<String>,
+    pub selected_text: Option<String>,
+}
+
+impl WorkspaceContext {
+    /// Reads the parts of the context the user allowed. Parts the platform can't provide,
+    /// or that fail to read, are left out.
+    pub fn capture(window: bool, selection: bool) -> Self {
+        let mut context = Self::default();
+        if window {
+            context.app_name = app_name();
+            context.window_title = window_title();
+        }
+        if selection {
+            context.selected_text = selected_text().map(|text| {
+                if text.chars().count() > MAX_SELECTION_CHARS {
+                    text.chars().take(MAX_SELECTION_CHARS).collect()
+                } else {
+                    text
+                }
+            });
+        }
+        context
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.app_name.is_none() && self.window_title.is_none() && self.selected_text.is_none()
+    }
+
+    /// The context as prompt text, empty when nothing was captured.
+    pub fn describe(&self) -> String {
+        let mut lines = Vec::new();
+        match (&self.app_name, &self.window_title) {
+            (Some(app), Some(title)) => lines.push(format!("Application: {} ({})", app, title)),
+            (Some(app), None) => lines.push(format!("Application: {}", app)),
+            (None, Some(title)) => lines.push(format!("Window: {}", title)),
+            (None, None) => {}
+        }
+        if let Some(selection) = &self.selected_text {
+            lines.push(format!("Selected text:\n\"\"\"\n{}\n\"\"\"", selection));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Trimmed stdout of a successful command, `None` when it failed or printed nothing.
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(target_os = "macos")]
+fn app_name() -> Option<String> {
+    command_output(
+        "osascript",
+        &[
+            "-e",
+            "tell application \"System Events\" to get name of first application process whose frontmost is true",
+        ],
+    )
+}
+
+#[cfg(target_os = "macos")]
+fn window_title() -> Option<String> {
+    command_output(
+        "osascript",
+        &[
+            "-e",
+            "tell application \"System Events\" to get name of front window of (first application process whose frontmost is true)",
+        ],
+    )
+}
+
+/// Reads the focused element's selection through the accessibility API, which needs the
+/// accessibility permission Handy already asks for to paste.
+#[cfg(target_os = "macos")]
+fn selected_text() -> Option<String> {
+    command_output(
+        "osascript",
+        &[
+            "-e",
+            "tell application \"System Events\" to get value of attribute \"AXSelectedText\" of focused UI element of (first application process whose frontmost is true)",
+        ],
+    )
+}
+
+#[cfg(target_os = "macos")]
+fn focused_field_role() -> Option<FieldKind> {
+    let role = command_output(
+        "osascript",
+        &[
+            "-e",
+            "tell application \"System Events\" to get value of attribute \"AXRole\" of focused UI element of (first application process whose frontmost is true)",
+        ],
+    )?;
+    match role.as_str() {
+        "AXTextField" | "AXSearchField" | "AXComboBox" => Some(FieldKind::SingleLine),
+        "AXTextArea" => Some(FieldKind::MultiLine),
+        _ => None,
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn focused_field_is_secure() -> Option<bool> {
+    let subrole = command_output(
+        "osascript",
+        &[
+            "-e",
+            "tell application \"System Events\" to get value of attribute \"AXSubrole\" of focused UI element of (first application process whose frontmost is true)",
+        ],
+    )?;
+    Some(subrole == "AXSecureTextField")
+}
+
+#[cfg(target_os = "linux")]
+fn app_name() -> Option<String> {
+    command_output("xdotool", &["getactivewindow", "getwindowclassname"])
+}
+
+#[cfg(target_os = "linux")]
+fn window_title() -> Option<String> {
+    command_output("xdotool", &["getactivewindow", "getwindowname"])
+}
+
+/// The primary selection holds whatever text is currently selected, on Wayland through
+/// wl-paste and on X11 through xclip.
+#[cfg(target_os = "linux")]
+fn selected_text() -> Option<String> {
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        command_output("wl-paste", &["--primary", "--no-newline"])
+    } else {
+        command_output("xclip", &["-o", "-selection", "primary"])
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn app_name() -> Option<String> {
+    None
+}
+
+/// The focused classic edit control and its style, `None` when the focus is elsewhere. Fields
+/// drawn by the app itself, as in browsers, need UI Automation and stay unknown.
+#[cfg(target_os = "windows")]
+pub(super) fn focused_edit_control() -> Option<(windows::Win32::Foundation::HWND, i32)> {
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetClassNameW, GetGUIThreadInfo, GetWindowLongW, GUITHREADINFO, GWL_STYLE,
+    };
+
+    let mut info = GUITHREADINFO {
+        cbSize: std::mem::size_of::<GUITHREADINFO>() as u32,
+        ..Default::default()
+    };
+    let mut class = [0u16; 64];
+    let (len, style) = unsafe {
+        GetGUIThreadInfo(0, &mut info).ok()?;
+        if info.hwndFocus.is_invalid() {
+            return None;
+        }
+        (
+            GetClassNameW(info.hwndFocus, &mut class),
+            GetWindowLongW(info.hwndFocus, GWL_STYLE),
+        )
+    };
+    let class = String::from_utf16_lossy(&class[..len.max(0) as usize]).to_lowercase();
+    (class == "edit" || class.starts_with("richedit")).then_some((info.hwndFocus, style))
+}
+
+/// Classic edit controls tell their kind through their style.
+#[cfg(target_os = "windows")]
+fn focused_field_role() -> Option<FieldKind> {
+    const ES_MULTILINE: i32 = 0x0004;
+
+    if focused_edit_control()?.1 & ES_MULTILINE != 0 {
+        Some(FieldKind::MultiLine)
+    } else {
+        Some(FieldKind::SingleLine)
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn focused_field_is_secure() -> Option<bool> {
+    const ES_PASSWORD: i32 = 0x0020;
+
+    Some(focused_edit_control()?.1 & ES_PASSWORD != 0)
+}
+
+#[cfg(target_os = "windows")]
+fn window_title() -> Option<String> {
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowTextW};
+
+    let mut buffer = [0u16; 512];
+    let len = unsafe {
+        let window = GetForegroundWindow();
+        GetWindowTextW(window, &mut buffer)
+    };
+    let title = String::from_utf16_lossy(&buffer[..len.max(0) as usize]);
+    (!title.trim().is_empty()).then_some(title)
+}
+
+/// Reading another application's selection needs UI Automation, which isn't wired up yet.
+#[cfg(target_os = "windows")]
+fn selected_text() -> Option<String> {
+    None
+}
+
+/// Field roles on Linux need AT-SPI, which isn't wired up yet.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn focused_field_role() -> Option<FieldKind> {
+    None
+}
+
+/// Password fields on Linux are only exposed through AT-SPI, which isn't wired up yet.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn focused_field_is_secure() -> Option<bool> {
+    None
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn app_name() -> Option<String> {
+    None
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn window_title() -> Option<String> {
+    None
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+fn selected_text() -> Option<String> {
+    None
+}
@@ -0,0 +1,192 @@
+//! Keyboard output on Linux. X11 takes synthetic input through the XTest extension, which
+//! enigo drives. Wayland compositors ignore that input outside of XWayland windows, so there
+//! keys and text go through wtype, which speaks the virtual-keyboard protocol, or dotool,
+//! which writes to uinput and also works where that protocol is missing, as under GNOME.
+//! The backend is detected once per run.
+
+use log::info;
+use once_cell::sync::Lazy;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backend {
+    XTest,
+    Wtype,
+    Dotool,
+}
+
+/// Key presses sent on their own or with a modifier.
+#[derive(Clone, Copy, Debug)]
+pub enum Keys {
+    CtrlV,
+    ShiftInsert,
+    CtrlZ,
+    Backspace,
+}
+
+impl Keys {
+    fn wtype_args(self) -> &'static [&'static str] {
+        match self {
+            Keys::CtrlV => &["-M", "ctrl", "-k", "v"],
+            Keys::ShiftInsert => &["-M", "shift", "-k", "Insert"],
+            Keys::CtrlZ => &["-M", "ctrl", "-k", "z"],
+            Keys::Backspace => &["-k", "BackSpace"],
+        }
+    }
+
+    fn dotool_name(self) -> &'static str {
+        match self {
+            Keys::CtrlV => "ctrl+v",
+            Keys::ShiftInsert => "shift+insert",
+            Keys::CtrlZ => "ctrl+z",
+            Keys::Backspace => "backspace",
+        }
+    }
+}
+
+static BACKEND: Lazy<Backend> = Lazy::new(|| {
+    let backend = choose_backend(
+        crate::utils::is_wayland(),
+        &std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default(),
+        is_available("wtype"),
+        is_available("dotool"),
+    );
+    info!("Using {:?} for keyboard output", backend);
+    backend
+});
+
+pub fn backend() -> Backend {
+    *BACKEND
+}
+
+/// wtype is preferred on Wayland, except under GNOME, whose compositor lacks the
+/// virtual-keyboard protocol. Without either tool only XWayland windows can be reached,
+/// through XTest.
+fn choose_backend(wayland: bool, desktop: &str, has_wtype: bool, has_dotool: bool) -> Backend {
+    if !wayland {
+        return Backend::XTest;
+    }
+    let gnome = desktop.to_lowercase().contains("gnome");
+    match (has_wtype, has_dotool) {
+        (true, false) => Backend::Wtype,
+        (true, true) if !gnome => Backend::Wtype,
+        (_, true) => Backend::Dotool,
+        (false, false) => Backend::XTest,
+    }
+}
+
+fn is_available(tool: &str) -> bool {
+    Command::new("which")
+        .arg(tool)
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Presses `keys` `times` times through the Wayland backend. Returns `Ok(false)` on X11,
+/// where the caller sends them through XTest.
+pub fn try_press(keys: Keys, times: usize) -> Result<bool, String> {
+    match backend() {
+        Backend::XTest => Ok(false),
+        Backend::Wtype => {
+            let args: Vec<&str> = (0..times)
+                .flat_map(|_| keys.wtype_args())
+                .copied()
+                .collect();
+            run("wtype", &args, None)?;
+            Ok(true)
+        }
+        Backend::Dotool => {
+            let script = vec![format!("key {}", keys.dotool_name()); times].join("\n");
+            run("dotool", &[], Some(&script))?;
+            Ok(true)
+        }
+    }
+}
+
+/// Types `text` through the Wayland backend. Returns `Ok(false)` on X11, where the caller
+/// types it through XTest.
+pub fn try_type(text: &str) -> Result<bool, String> {
+    match backend() {
+        Backend::XTest => Ok(false),
+        // Read from stdin, so text starting with a dash isn't taken for an option
+        Backend::Wtype => {
+            run("wtype", &["-"], Some(text))?;
+            Ok(true)
+        }
+        Backend::Dotool => {
+            run("dotool", &[], Some(&dotool_type_script(text)))?;
+            Ok(true)
+        }
+    }
+}
+
+/// dotool types up to the end of a line, so line breaks become Enter presses.
+fn dotool_type_script(text: &str) -> String {
+    text.split('\n')
+        .map(|line| format!("type {}", line))
+        .collect::<Vec<_>>()
+        .join("\nkey enter\n")
+}
+
+fn run(program: &str, args: &[&str], stdin: Option<&str>) -> Result<(), String> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to execute {}: {}", program, e))?;
+    if let Some(input) = stdin {
+        if let Some(mut pipe) = child.stdin.take() {
+            pipe.write_all(input.as_bytes())
+                .map_err(|e| format!("Failed to write to {}: {}", program, e))?;
+        }
+    }
+    drop(child.stdin.take());
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to execute {}: {}", program, e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{} failed: {}", program, stderr.trim()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choose_backend() {
+        assert_eq!(choose_backend(false, "KDE", true, true), Backend::XTest);
+        assert_eq!(choose_backend(true, "sway", true, true), Backend::Wtype);
+        assert_eq!(
+            choose_backend(true, "ubuntu:GNOME", true, true),
+            Backend::Dotool
+        );
+        assert_eq!(
+            choose_backend(true, "ubuntu:GNOME", true, false),
+            Backend::Wtype
+        );
+        assert_eq!(
+            choose_backend(true, "Hyprland", false, true),
+            Backend::Dotool
+        );
+        assert_eq!(
+            choose_backend(true, "Hyprland", false, false),
+            Backend::XTest
+        );
+    }
+
+    #[test]
+    fn test_dotool_type_script_presses_enter_between_lines() {
+        assert_eq!(dotool_type_script("one line"), "type one line");
+        assert_eq!(
+            dotool_type_script("first\nsecond"),
+            "type first\nkey enter\ntype second"
+        );
+    }
+}
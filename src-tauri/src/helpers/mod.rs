@@ -1 +1,7 @@
+pub mod caret_insert;
 pub mod clamshell;
+pub mod focused_app;
+#[cfg(target_os = "linux")]
+pub mod linux_input;
+pub mod process_memory;
+pub mod system_memory;
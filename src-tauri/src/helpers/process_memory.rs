@@ -0,0 +1,103 @@
+//! Resident memory of the Handy process, for measuring what a model costs to run.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// How often `PeakMemory` samples the resident size
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Memory of the process currently held in RAM, in bytes. `None` where the platform doesn't
+/// report it.
+#[cfg(target_os = "linux")]
+pub fn resident_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(target_os = "macos")]
+pub fn resident_bytes() -> Option<u64> {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "rss=", "-p", &std::process::id().to_string()])
+        .output()
+        .ok()?;
+    let kb: u64 = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(target_os = "windows")]
+pub fn resident_bytes() -> Option<u64> {
+    use windows::Win32::System::ProcessStatus::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+    use windows::Win32::System::Threading::GetCurrentProcess;
+
+    let mut counters = PROCESS_MEMORY_COUNTERS::default();
+    unsafe {
+        GetProcessMemoryInfo(
+            GetCurrentProcess(),
+            &mut counters,
+            std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32,
+        )
+        .ok()?;
+    }
+    Some(counters.WorkingSetSize as u64)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub fn resident_bytes() -> Option<u64> {
+    None
+}
+
+/// Samples the resident size in the background until `finish`, keeping the highest value
+/// and the one at the start to measure it against.
+pub struct PeakMemory {
+    running: Arc<AtomicBool>,
+    baseline: Option<u64>,
+    peak: Arc<AtomicU64>,
+    sampler: JoinHandle<()>,
+}
+
+impl PeakMemory {
+    pub fn start() -> Self {
+        let running = Arc::new(AtomicBool::new(true));
+        let baseline = resident_bytes();
+        let peak = Arc::new(AtomicU64::new(baseline.unwrap_or(0)));
+        let sampler = {
+            let running = running.clone();
+            let peak = peak.clone();
+            thread::spawn(move || {
+                while running.load(Ordering::Relaxed) {
+                    if let Some(bytes) = resident_bytes() {
+                        peak.fetch_max(bytes, Ordering::Relaxed);
+                    }
+                    thread::sleep(SAMPLE_INTERVAL);
+                }
+            })
+        };
+
+        Self {
+            running,
+            baseline,
+            peak,
+            sampler,
+        }
+    }
+
+    /// Stops sampling and returns how far the peak rose above the resident size at `start`,
+    /// in bytes, so memory the process already used isn't counted. `None` if it couldn't be
+    /// measured.
+    pub fn finish(self) -> Option<u64> {
+        self.running.store(false, Ordering::Relaxed);
+        let _ = self.sampler.join();
+        if let Some(bytes) = resident_bytes() {
+            self.peak.fetch_max(bytes, Ordering::Relaxed);
+        }
+        let baseline = self.baseline?;
+        Some(self.peak.load(Ordering::Relaxed).saturating_sub(baseline))
+    }
+}
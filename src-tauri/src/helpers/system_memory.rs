@@ -0,0 +1,123 @@
+//! Physical memory of the machine, to check a model fits before loading it.
+
+/// Total and currently available physical memory, in bytes. Available memory counts what
+/// the OS can hand out without swapping, caches it would drop included.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SystemMemory {
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+}
+
+/// `None` where the platform doesn't report it.
+#[cfg(target_os = "linux")]
+pub fn system_memory() -> Option<SystemMemory> {
+    parse_meminfo(&std::fs::read_to_string("/proc/meminfo").ok()?)
+}
+
+#[cfg(target_os = "macos")]
+pub fn system_memory() -> Option<SystemMemory> {
+    let run = |program: &str, args: &[&str]| -> Option<String> {
+        let output = std::process::Command::new(program)
+            .args(args)
+            .output()
+            .ok()?;
+        Some(String::from_utf8_lossy(&output.stdout).into_owned())
+    };
+    let total_bytes = run("sysctl", &["-n", "hw.memsize"])?.trim().parse().ok()?;
+    let available_bytes = parse_vm_stat(&run("vm_stat", &[])?)?;
+    Some(SystemMemory {
+        total_bytes,
+        available_bytes,
+    })
+}
+
+#[cfg(target_os = "windows")]
+pub fn system_memory() -> Option<SystemMemory> {
+    use windows::Win32::System::SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+
+    let mut status = MEMORYSTATUSEX {
+        dwLength: std::mem::size_of::<MEMORYSTATUSEX>() as u32,
+        ..Default::default()
+    };
+    unsafe { GlobalMemoryStatusEx(&mut status).ok()? };
+    Some(SystemMemory {
+        total_bytes: status.ullTotalPhys,
+        available_bytes: status.ullAvailPhys,
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub fn system_memory() -> Option<SystemMemory> {
+    None
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_meminfo(meminfo: &str) -> Option<SystemMemory> {
+    let field = |name: &str| -> Option<u64> {
+        let line = meminfo.lines().find(|line| line.starts_with(name))?;
+        let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+        Some(kb * 1024)
+    };
+    Some(SystemMemory {
+        total_bytes: field("MemTotal:")?,
+        available_bytes: field("MemAvailable:")?,
+    })
+}
+
+/// Free, inactive, speculative and purgeable pages, which macOS reclaims before swapping.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_vm_stat(vm_stat: &str) -> Option<u64> {
+    let page_size: u64 = vm_stat
+        .lines()
+        .next()?
+        .split("page size of ")
+        .nth(1)?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()?;
+    let pages = |name: &str| -> u64 {
+        vm_stat
+            .lines()
+            .find(|line| line.starts_with(name))
+            .and_then(|line| line.rsplit(':').next())
+            .and_then(|count| count.trim().trim_end_matches('.').parse().ok())
+            .unwrap_or(0)
+    };
+    let free = pages("Pages free")
+        + pages("Pages inactive")
+        + pages("Pages speculative")
+        + pages("Pages purgeable");
+    Some(free * page_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_meminfo() {
+        let meminfo = "MemTotal:        8039260 kB\nMemFree:          312840 kB\nMemAvailable:    2621440 kB\nBuffers:          102400 kB\n";
+        assert_eq!(
+            parse_meminfo(meminfo),
+            Some(SystemMemory {
+                total_bytes: 8039260 * 1024,
+                available_bytes: 2621440 * 1024,
+            })
+        );
+        assert_eq!(parse_meminfo("MemTotal: 100 kB\n"), None);
+    }
+
+    #[test]
+    fn test_parse_vm_stat() {
+        let vm_stat = "Mach Virtual Memory Statistics: (page size of 16384 bytes)\n\
+            Pages free:                               10000.\n\
+            Pages active:                            200000.\n\
+            Pages inactive:                           50000.\n\
+            Pages speculative:                         2000.\n\
+            Pages wired down:                         90000.\n\
+            Pages purgeable:                           1000.\n";
+        assert_eq!(parse_vm_stat(vm_stat), Some(63000 * 16384));
+        assert_eq!(parse_vm_stat("nonsense"), None);
+    }
+}
@@ -0,0 +1,138 @@
+//! Encryption of the history at rest. Texts in the history database and the recordings on
+//! disk are sealed with ChaCha20-Poly1305 under a key kept in the OS credential store.
+//! Sealed values carry a marker, so plain ones written before encryption was turned on, or
+//! after it was turned off, are read as they are.
+
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+
+use crate::secrets;
+
+/// Starts every sealed text stored in the database
+const TEXT_MARKER: &str = "enc1:";
+/// Starts every sealed file
+const FILE_MARKER: &[u8] = b"HANDYENC1";
+const NONCE_LEN: usize = 12;
+
+pub struct HistoryCipher {
+    cipher: ChaCha20Poly1305,
+}
+
+impl HistoryCipher {
+    pub fn new(key: &[u8]) -> Result<Self> {
+        if key.len() != 32 {
+            return Err(anyhow!("History key must be 32 bytes, got {}", key.len()));
+        }
+        Ok(Self {
+            cipher: ChaCha20Poly1305::new(Key::from_slice(key)),
+        })
+    }
+
+    /// The cipher for the key in the credential store, `None` when there is no key yet.
+    pub fn load() -> Result<Option<Self>> {
+        secrets::get_history_key()?
+            .map(|key| Self::new(&STANDARD.decode(key)?))
+            .transpose()
+    }
+
+    /// The cipher for the key in the credential store, creating the key when there is none.
+    pub fn load_or_create() -> Result<Self> {
+        if let Some(cipher) = Self::load()? {
+            return Ok(cipher);
+        }
+        let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        secrets::set_history_key(&STANDARD.encode(key))?;
+        Self::new(&key)
+    }
+
+    fn seal(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, data)
+            .map_err(|_| anyhow!("Failed to encrypt"))?;
+        Ok([nonce.as_slice(), ciphertext.as_slice()].concat())
+    }
+
+    fn open(&self, sealed: &[u8]) -> Result<Vec<u8>> {
+        if sealed.len() < NONCE_LEN {
+            return Err(anyhow!("Encrypted value is truncated"));
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow!("Failed to decrypt, the history key doesn't match"))
+    }
+
+    pub fn seal_text(&self, text: &str) -> Result<String> {
+        Ok(format!(
+            "{}{}",
+            TEXT_MARKER,
+            STANDARD.encode(self.seal(text.as_bytes())?)
+        ))
+    }
+
+    /// The plain text of `stored`, which is returned as it is when it isn't sealed.
+    pub fn open_text(&self, stored: &str) -> Result<String> {
+        let Some(encoded) = stored.strip_prefix(TEXT_MARKER) else {
+            return Ok(stored.to_string());
+        };
+        Ok(String::from_utf8(self.open(&STANDARD.decode(encoded)?)?)?)
+    }
+
+    pub fn seal_bytes(&self, data: &[u8]) -> Result<Vec<u8>> {
+        Ok([FILE_MARKER, self.seal(data)?.as_slice()].concat())
+    }
+
+    /// The plain content of `stored`, which is returned as it is when it isn't sealed.
+    pub fn open_bytes(&self, stored: &[u8]) -> Result<Vec<u8>> {
+        match stored.strip_prefix(FILE_MARKER) {
+            Some(sealed) => self.open(sealed),
+            None => Ok(stored.to_vec()),
+        }
+    }
+}
+
+pub fn is_sealed_text(stored: &str) -> bool {
+    stored.starts_with(TEXT_MARKER)
+}
+
+pub fn is_sealed_bytes(stored: &[u8]) -> bool {
+    stored.starts_with(FILE_MARKER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_round_trip() {
+        let cipher = HistoryCipher::new(&[7; 32]).unwrap();
+        let sealed = cipher.seal_text("meet me at noon").unwrap();
+
+        assert!(is_sealed_text(&sealed));
+        assert!(!sealed.contains("noon"));
+        assert_eq!(cipher.open_text(&sealed).unwrap(), "meet me at noon");
+        // Values written before encryption was turned on pass through
+        assert_eq!(cipher.open_text("plain").unwrap(), "plain");
+        // Each sealing uses a fresh nonce
+        assert_ne!(sealed, cipher.seal_text("meet me at noon").unwrap());
+    }
+
+    #[test]
+    fn test_bytes_need_the_same_key() {
+        let cipher = HistoryCipher::new(&[7; 32]).unwrap();
+        let sealed = cipher.seal_bytes(b"RIFF....WAVE").unwrap();
+
+        assert!(is_sealed_bytes(&sealed));
+        assert_eq!(cipher.open_bytes(&sealed).unwrap(), b"RIFF....WAVE");
+        assert_eq!(cipher.open_bytes(b"RIFF").unwrap(), b"RIFF");
+
+        let other = HistoryCipher::new(&[8; 32]).unwrap();
+        assert!(other.open_bytes(&sealed).is_err());
+        assert!(HistoryCipher::new(&[7; 16]).is_err());
+    }
+}
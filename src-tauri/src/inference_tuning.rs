@@ -0,0 +1,267 @@
+//! Priority, core placement and thread cap for model loading and inference, so a long
+//! transcription doesn't make the rest of the machine stutter. transcribe-rs doesn't expose
+//! the ONNX Runtime or whisper.cpp thread settings, so the work runs on a fresh thread
+//! tuned with the OS's own controls, and the runtimes' worker threads, created from it,
+//! inherit them where the OS passes them on: priority and affinity on Linux, the QoS class
+//! on macOS. On Windows only the calling thread is tuned.
+
+use crate::power_policy;
+use crate::settings::{AppSettings, InferenceCores, InferencePriority};
+use log::debug;
+use std::thread;
+
+/// Runs `work` with the inference settings applied, on a thread of its own so the
+/// caller's thread keeps its priority. Without any set, `work` runs right here.
+pub fn run<T: Send>(settings: &AppSettings, work: impl FnOnce() -> T + Send) -> T {
+    if power_policy::inference_priority(settings) == InferencePriority::Normal
+        && settings.inference_cores == InferenceCores::Any
+        && settings.inference_threads.is_none()
+    {
+        return work();
+    }
+    thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                apply_to_current_thread(settings);
+                work()
+            })
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn apply_to_current_thread(settings: &AppSettings) {
+    let nice = match power_policy::inference_priority(settings) {
+        InferencePriority::Normal => 0,
+        InferencePriority::BelowNormal => 5,
+        InferencePriority::Idle => 19,
+    };
+    // On Linux the nice value is per thread, and threads started from this one inherit it
+    if nice != 0 {
+        let tid = unsafe { libc::syscall(libc::SYS_gettid) } as libc::id_t;
+        if unsafe { libc::setpriority(libc::PRIO_PROCESS, tid, nice) } != 0 {
+            debug!(
+                "Failed to lower the inference priority: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+
+    let all = read_cpu_list("/sys/devices/system/cpu/online").unwrap_or_default();
+    let (performance, efficiency) = core_types(&all);
+    let cpus = choose_cpus(
+        &all,
+        &performance,
+        &efficiency,
+        settings.inference_cores,
+        settings.inference_threads,
+    );
+    if cpus.len() < all.len() && !cpus.is_empty() {
+        let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+        for cpu in &cpus {
+            unsafe { libc::CPU_SET(*cpu, &mut set) };
+        }
+        let size = std::mem::size_of::<libc::cpu_set_t>();
+        if unsafe { libc::sched_setaffinity(0, size, &set) } != 0 {
+            debug!(
+                "Failed to pin inference to CPUs {:?}: {}",
+                cpus,
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+}
+
+/// Performance and efficiency cores of a hybrid CPU, both empty on others: Intel lists
+/// them as separate PMUs, ARM big.LITTLE gives the little cores a lower capacity.
+#[cfg(target_os = "linux")]
+fn core_types(all: &[usize]) -> (Vec<usize>, Vec<usize>) {
+    if let (Some(performance), Some(efficiency)) = (
+        read_cpu_list("/sys/devices/cpu_core/cpus"),
+        read_cpu_list("/sys/devices/cpu_atom/cpus"),
+    ) {
+        return (performance, efficiency);
+    }
+    let capacities: Vec<(usize, u32)> = all
+        .iter()
+        .filter_map(|cpu| {
+            let path = format!("/sys/devices/system/cpu/cpu{}/cpu_capacity", cpu);
+            let capacity = std::fs::read_to_string(path).ok()?.trim().parse().ok()?;
+            Some((*cpu, capacity))
+        })
+        .collect();
+    let Some(max) = capacities.iter().map(|(_, capacity)| *capacity).max() else {
+        return (Vec::new(), Vec::new());
+    };
+    let (performance, efficiency): (Vec<_>, Vec<_>) = capacities
+        .into_iter()
+        .partition(|(_, capacity)| *capacity == max);
+    if efficiency.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    (
+        performance.into_iter().map(|(cpu, _)| cpu).collect(),
+        efficiency.into_iter().map(|(cpu, _)| cpu).collect(),
+    )
+}
+
+#[cfg(target_os = "linux")]
+fn read_cpu_list(path: &str) -> Option<Vec<usize>> {
+    parse_cpu_list(&std::fs::read_to_string(path).ok()?)
+}
+
+/// On Apple silicon the QoS class decides the cores: background work stays on the
+/// efficiency cores, user-initiated work is preferred on the performance cores. There is
+/// no affinity or thread cap.
+#[cfg(target_os = "macos")]
+fn apply_to_current_thread(settings: &AppSettings) {
+    use libc::qos_class_t::*;
+
+    let priority = power_policy::inference_priority(settings);
+    let qos = match (settings.inference_cores, priority) {
+        (InferenceCores::Efficiency, _) | (_, InferencePriority::Idle) => QOS_CLASS_BACKGROUND,
+        (_, InferencePriority::BelowNormal) => QOS_CLASS_UTILITY,
+        (InferenceCores::Performance, InferencePriority::Normal) => QOS_CLASS_USER_INITIATED,
+        (InferenceCores::Any, InferencePriority::Normal) => return,
+    };
+    if unsafe { libc::pthread_set_qos_class_self_np(qos, 0) } != 0 {
+        debug!("Failed to set the inference QoS class");
+    }
+}
+
+/// Windows threads don't pass their priority on, so only the calling thread is tuned. The
+/// efficiency cores are asked for with EcoQoS.
+#[cfg(target_os = "windows")]
+fn apply_to_current_thread(settings: &AppSettings) {
+    use windows::Win32::System::Threading::{
+        GetCurrentThread, SetThreadAffinityMask, SetThreadInformation, SetThreadPriority,
+        ThreadPowerThrottling, THREAD_POWER_THROTTLING_CURRENT_VERSION,
+        THREAD_POWER_THROTTLING_EXECUTION_SPEED, THREAD_POWER_THROTTLING_STATE,
+        THREAD_PRIORITY_BELOW_NORMAL, THREAD_PRIORITY_IDLE,
+    };
+
+    unsafe {
+        let thread = GetCurrentThread();
+        let priority = match power_policy::inference_priority(settings) {
+            InferencePriority::Normal => None,
+            InferencePriority::BelowNormal => Some(THREAD_PRIORITY_BELOW_NORMAL),
+            InferencePriority::Idle => Some(THREAD_PRIORITY_IDLE),
+        };
+        if let Some(priority) = priority {
+            if let Err(e) = SetThreadPriority(thread, priority) {
+                debug!("Failed to lower the inference priority: {}", e);
+            }
+        }
+        if settings.inference_cores == InferenceCores::Efficiency {
+            let state = THREAD_POWER_THROTTLING_STATE {
+                Version: THREAD_POWER_THROTTLING_CURRENT_VERSION,
+                ControlMask: THREAD_POWER_THROTTLING_EXECUTION_SPEED,
+                StateMask: THREAD_POWER_THROTTLING_EXECUTION_SPEED,
+            };
+            if let Err(e) = SetThreadInformation(
+                thread,
+                ThreadPowerThrottling,
+                &state as *const _ as *const std::ffi::c_void,
+                std::mem::size_of::<THREAD_POWER_THROTTLING_STATE>() as u32,
+            ) {
+                debug!("Failed to request efficiency cores: {}", e);
+            }
+        }
+        if let Some(threads) = settings.inference_threads {
+            let threads = threads.clamp(1, usize::BITS - 1);
+            SetThreadAffinityMask(thread, (1usize << threads) - 1);
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn apply_to_current_thread(_settings: &AppSettings) {}
+
+/// Parses a kernel CPU list such as `0-3,8,10-11`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_cpu_list(list: &str) -> Option<Vec<usize>> {
+    let mut cpus = Vec::new();
+    for part in list.trim().split(',').filter(|part| !part.is_empty()) {
+        match part.split_once('-') {
+            Some((first, last)) => {
+                cpus.extend(first.trim().parse::<usize>().ok()?..=last.trim().parse().ok()?)
+            }
+            None => cpus.push(part.trim().parse().ok()?),
+        }
+    }
+    Some(cpus)
+}
+
+/// The CPUs inference may run on: the wanted core type when the CPU has both kinds, then at
+/// most `threads` of them.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn choose_cpus(
+    all: &[usize],
+    performance: &[usize],
+    efficiency: &[usize],
+    cores: InferenceCores,
+    threads: Option<u32>,
+) -> Vec<usize> {
+    let hybrid = !performance.is_empty() && !efficiency.is_empty();
+    let mut cpus = match cores {
+        InferenceCores::Performance if hybrid => performance.to_vec(),
+        InferenceCores::Efficiency if hybrid => efficiency.to_vec(),
+        _ => all.to_vec(),
+    };
+    if let Some(threads) = threads {
+        cpus.truncate(threads.max(1) as usize);
+    }
+    cpus
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpu_list() {
+        assert_eq!(
+            parse_cpu_list("0-3,8,10-11\n"),
+            Some(vec![0, 1, 2, 3, 8, 10, 11])
+        );
+        assert_eq!(parse_cpu_list("5"), Some(vec![5]));
+        assert_eq!(parse_cpu_list("a-b"), None);
+    }
+
+    #[test]
+    fn test_choose_cpus() {
+        let all: Vec<usize> = (0..12).collect();
+        let performance: Vec<usize> = (0..4).collect();
+        let efficiency: Vec<usize> = (4..12).collect();
+        assert_eq!(
+            choose_cpus(
+                &all,
+                &performance,
+                &efficiency,
+                InferenceCores::Performance,
+                None
+            ),
+            performance
+        );
+        assert_eq!(
+            choose_cpus(
+                &all,
+                &performance,
+                &efficiency,
+                InferenceCores::Efficiency,
+                Some(2)
+            ),
+            [4, 5]
+        );
+        // Not a hybrid CPU: the core type makes no difference
+        assert_eq!(
+            choose_cpus(&all, &[], &[], InferenceCores::Efficiency, Some(3)),
+            [0, 1, 2]
+        );
+        assert_eq!(
+            choose_cpus(&all, &[], &[], InferenceCores::Any, None).len(),
+            12
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! Bindings triggered by mouse buttons and gamepad buttons, which the global shortcut plugin
+//! can't register. Triggers are written as `mouse:middle`, `mouse:<button number>` (side
+//! buttons, the number as reported by the OS) or `gamepad:<button>`, e.g. `gamepad:south`.
+//! Listener threads are started the first time a trigger of their kind is registered.
+
+use crate::shortcut::{self, TriggerRole};
+use gilrs::{Button as GamepadButton, EventType as GamepadEvent, Gilrs};
+use log::{debug, error, info};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_plugin_global_shortcut::ShortcutState;
+
+const GAMEPAD_BUTTONS: &[(&str, GamepadButton)] = &[
+    ("south", GamepadButton::South),
+    ("east", GamepadButton::East),
+    ("north", GamepadButton::North),
+    ("west", GamepadButton::West),
+    ("left_trigger", GamepadButton::LeftTrigger),
+    ("left_trigger2", GamepadButton::LeftTrigger2),
+    ("right_trigger", GamepadButton::RightTrigger),
+    ("right_trigger2", GamepadButton::RightTrigger2),
+    ("select", GamepadButton::Select),
+    ("start", GamepadButton::Start),
+    ("mode", GamepadButton::Mode),
+    ("left_thumb", GamepadButton::LeftThumb),
+    ("right_thumb", GamepadButton::RightThumb),
+    ("dpad_up", GamepadButton::DPadUp),
+    ("dpad_down", GamepadButton::DPadDown),
+    ("dpad_left", GamepadButton::DPadLeft),
+    ("dpad_right", GamepadButton::DPadRight),
+];
+
+/// Whether `raw` names a mouse or gamepad trigger rather than a keyboard shortcut.
+pub fn is_device_trigger(raw: &str) -> bool {
+    let raw = raw.trim().to_lowercase();
+    raw.starts_with("mouse:") || raw.starts_with("gamepad:")
+}
+
+/// Checks a device trigger and returns it in canonical (lowercase) form.
+pub fn validate_trigger(raw: &str) -> Result<String, String> {
+    let raw = raw.trim().to_lowercase();
+    if let Some(button) = raw.strip_prefix("mouse:") {
+        match button {
+            "left" | "right" | "1" | "2" => {
+                Err("The left and right mouse buttons can't be used as triggers".to_string())
+            }
+            "middle" => Ok(raw),
+            number if number.parse::<u8>().is_ok() => Ok(raw),
+            _ => Err(format!("Unknown mouse button '{}'", button)),
+        }
+    } else if let Some(button) = raw.strip_prefix("gamepad:") {
+        if GAMEPAD_BUTTONS.iter().any(|(name, _)| *name == button) {
+            Ok(raw)
+        } else {
+            Err(format!("Unknown gamepad button '{}'", button))
+        }
+    } else {
+        Err(format!("'{}' is not a mouse or gamepad trigger", raw))
+    }
+}
+
+fn mouse_trigger(button: rdev::Button) -> Option<String> {
+    match button {
+        rdev::Button::Middle => Some("mouse:middle".to_string()),
+        rdev::Button::Unknown(number) => Some(format!("mouse:{}", number)),
+        _ => None,
+    }
+}
+
+fn gamepad_trigger(button: GamepadButton) -> Option<String> {
+    GAMEPAD_BUTTONS
+        .iter()
+        .find(|(_, b)| *b == button)
+        .map(|(name, _)| format!("gamepad:{}", name))
+}
+
+/// Registered device triggers, mapped to the binding and role they fire.
+pub struct TriggerListener {
+    registered: Arc<Mutex<HashMap<String, (String, TriggerRole)>>>,
+    mouse_started: AtomicBool,
+    gamepad_started: AtomicBool,
+}
+
+impl TriggerListener {
+    pub fn new() -> Self {
+        Self {
+            registered: Arc::new(Mutex::new(HashMap::new())),
+            mouse_started: AtomicBool::new(false),
+            gamepad_started: AtomicBool::new(false),
+        }
+    }
+
+    pub fn register(
+        &self,
+        app: &AppHandle,
+        trigger: &str,
+        binding_id: &str,
+        role: TriggerRole,
+    ) -> Result<(), String> {
+        let trigger = validate_trigger(trigger)?;
+        {
+            let mut registered = self.registered.lock().unwrap();
+            if registered.contains_key(&trigger) {
+                return Err(format!("Trigger '{}' is already in use", trigger));
+            }
+            registered.insert(trigger.clone(), (binding_id.to_string(), role));
+        }
+
+        if trigger.starts_with("mouse:") && !self.mouse_started.swap(true, Ordering::SeqCst) {
+            self.start_mouse_listener(app);
+        }
+        if trigger.starts_with("gamepad:") && !self.gamepad_started.swap(true, Ordering::SeqCst) {
+            self.start_gamepad_listener(app);
+        }
+
+        debug!(
+            "Registered trigger '{}' for binding '{}'",
+            trigger, binding_id
+        );
+        Ok(())
+    }
+
+    pub fn unregister(&self, trigger: &str) {
+        let trigger = trigger.trim().to_lowercase();
+        self.registered.lock().unwrap().remove(&trigger);
+    }
+
+    fn start_mouse_listener(&self, app: &AppHandle) {
+        let registered = self.registered.clone();
+        let app = app.clone();
+        std::thread::spawn(move || {
+            info!("Listening for mouse button triggers");
+            let result = rdev::listen(move |event| {
+                let (button, state) = match event.event_type {
+                    rdev::EventType::ButtonPress(button) => (button, ShortcutState::Pressed),
+                    rdev::EventType::ButtonRelease(button) => (button, ShortcutState::Released),
+                    _ => return,
+                };
+                if let Some(trigger) = mouse_trigger(button) {
+                    dispatch(&app, &registered, &trigger, state);
+                }
+            });
+            if let Err(e) = result {
+                error!("Mouse trigger listener stopped: {:?}", e);
+            }
+        });
+    }
+
+    fn start_gamepad_listener(&self, app: &AppHandle) {
+        let registered = self.registered.clone();
+        let app = app.clone();
+        std::thread::spawn(move || {
+            let mut gilrs = match Gilrs::new() {
+                Ok(gilrs) => gilrs,
+                Err(e) => {
+                    error!("Gamepad support is unavailable: {}", e);
+                    return;
+                }
+            };
+            info!("Listening for gamepad button triggers");
+            loop {
+                let Some(event) = gilrs.next_event_blocking(Some(Duration::from_secs(1))) else {
+                    continue;
+                };
+                let (button, state) = match event.event {
+                    GamepadEvent::ButtonPressed(button, _) => (button, ShortcutState::Pressed),
+                    GamepadEvent::ButtonReleased(button, _) => (button, ShortcutState::Released),
+                    _ => continue,
+                };
+                if let Some(trigger) = gamepad_trigger(button) {
+                    dispatch(&app, &registered, &trigger, state);
+                }
+            }
+        });
+    }
+}
+
+fn dispatch(
+    app: &AppHandle,
+    registered: &Mutex<HashMap<String, (String, TriggerRole)>>,
+    trigger: &str,
+    state: ShortcutState,
+) {
+    let target = registered.lock().unwrap().get(trigger).cloned();
+    if let Some((binding_id, role)) = target {
+        shortcut::handle_trigger(app, &binding_id, role, trigger, state);
+    }
+}
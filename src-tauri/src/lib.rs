@@ -1,25 +1,73 @@
 mod actions;
+mod announcer;
+mod api_server;
 mod audio_feedback;
 pub mod audio_toolkit;
+mod automation;
+mod benchmark;
+pub mod cli;
 mod clipboard;
+mod cloud_transcription;
 mod commands;
+#[cfg(target_os = "linux")]
+mod dbus_service;
+mod diagnostics;
+mod fillers;
 mod helpers;
+mod history_crypto;
+mod inference_tuning;
+mod input_triggers;
 mod llm_client;
+mod log_buffer;
 mod managers;
+mod markdown_note;
+mod mcp;
+mod mock_engine;
+mod model_prefetch;
 mod overlay;
+mod perf_metrics;
+mod pipeline;
+mod power_policy;
+mod profiles;
+mod readback;
+mod refinement;
+mod secrets;
+mod self_test;
+mod session_hooks;
+mod session_journal;
+mod session_naming;
+mod session_report;
 mod settings;
+mod settings_bundle;
+mod setup_manifest;
+mod setup_wizard;
 mod shortcut;
 mod signal_handle;
+mod snippets;
+mod text_rules;
+mod transcription_error;
 mod tray;
+mod tts;
 mod utils;
+mod webhook;
 use specta_typescript::{BigIntExportBehavior, Typescript};
 use tauri_specta::{collect_commands, Builder};
 
+use announcer::Announcer;
+use api_server::ApiServer;
 use env_filter::Builder as EnvFilterBuilder;
 use managers::audio::AudioRecordingManager;
+use managers::captions::CaptionManager;
+use managers::disfluency::DisfluencyManager;
+use managers::file_jobs::FileJobQueue;
 use managers::history::HistoryManager;
 use managers::model::ModelManager;
+use managers::plugins::PluginManager;
 use managers::transcription::TranscriptionManager;
+use managers::usage::UsageCounters;
+use perf_metrics::PerfMetrics;
+use session_hooks::SessionHooks;
+use session_journal::SessionJournal;
 #[cfg(unix)]
 use signal_hook::consts::SIGUSR2;
 #[cfg(unix)]
@@ -28,11 +76,13 @@ use std::collections::HashMap;
 use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::{Arc, Mutex};
 use tauri::image::Image;
+use tray::TrayManager;
 
 use tauri::tray::TrayIconBuilder;
 use tauri::Emitter;
 use tauri::{AppHandle, Manager};
 use tauri_plugin_autostart::{MacosLauncher, ManagerExt};
+use tauri_plugin_deep_link::DeepLinkExt;
 use tauri_plugin_log::{Builder as LogBuilder, RotationStrategy, Target, TargetKind};
 
 use crate::settings::get_settings;
@@ -53,6 +103,10 @@ fn level_filter_from_u8(value: u8) -> log::LevelFilter {
     }
 }
 
+fn file_log_filter(metadata: &log::Metadata) -> bool {
+    metadata.level() <= level_filter_from_u8(FILE_LOG_LEVEL.load(Ordering::Relaxed))
+}
+
 fn build_console_filter() -> env_filter::Filter {
     let mut builder = EnvFilterBuilder::new();
 
@@ -124,6 +178,47 @@ fn initialize_core_logic(app_handle: &AppHandle) {
     app_handle.manage(model_manager.clone());
     app_handle.manage(transcription_manager.clone());
     app_handle.manage(history_manager.clone());
+    app_handle.manage(Arc::new(
+        FileJobQueue::new(app_handle, transcription_manager.clone())
+            .expect("Failed to initialize file job queue"),
+    ));
+    app_handle.manage(Arc::new(
+        DisfluencyManager::new(app_handle).expect("Failed to initialize disfluency manager"),
+    ));
+    app_handle
+        .manage(SessionJournal::new(app_handle).expect("Failed to initialize session journal"));
+    app_handle.manage(Arc::new(
+        PluginManager::new(app_handle).expect("Failed to initialize plugin manager"),
+    ));
+    app_handle.manage(Arc::new(CaptionManager::new(app_handle)));
+    app_handle.manage(Announcer::new());
+    app_handle.manage(SessionHooks::new());
+    app_handle.manage(PerfMetrics::new());
+    app_handle.manage(Arc::new(
+        UsageCounters::new(app_handle).expect("Failed to initialize usage counters"),
+    ));
+
+    HistoryManager::start_maintenance(&history_manager);
+
+    // Offline, the catalog stays as of the last manifest fetched
+    let catalog_manager = model_manager.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = catalog_manager.refresh_catalog().await {
+            log::warn!("Failed to update the model catalog: {}", e);
+        }
+    });
+    model_prefetch::start(app_handle);
+    power_policy::start(app_handle);
+
+    // Start the local transcription API if the user enabled it
+    let api_server = ApiServer::new();
+    if let Err(e) = api_server.apply_settings(app_handle) {
+        log::error!("Failed to start transcription API server: {}", e);
+    }
+    app_handle.manage(api_server);
+
+    // Mouse and gamepad triggers are registered alongside the keyboard shortcuts
+    app_handle.manage(input_triggers::TriggerListener::new());
 
     // Initialize the shortcuts
     shortcut::init_shortcuts(app_handle);
@@ -134,6 +229,13 @@ fn initialize_core_logic(app_handle: &AppHandle) {
     #[cfg(unix)]
     signal_handle::setup_signal_handler(app_handle.clone(), signals);
 
+    // Compositor key bindings and scripts can also control dictation over D-Bus
+    #[cfg(target_os = "linux")]
+    {
+        app_handle.manage(dbus_service::DbusService::default());
+        dbus_service::start(app_handle);
+    }
+
     // Apply macOS Accessory policy if starting hidden
     #[cfg(target_os = "macos")]
     {
@@ -160,34 +262,20 @@ fn initialize_core_logic(app_handle: &AppHandle) {
         )
         .show_menu_on_left_click(true)
         .icon_as_template(true)
-        .on_menu_event(|app, event| match event.id.as_ref() {
-            "settings" => {
-                show_main_window(app);
+        .on_menu_event(|app, event| {
+            if let Some(tray_manager) = app.try_state::<Arc<TrayManager>>() {
+                tray_manager.handle_menu_event(event.id.as_ref());
             }
-            "check_updates" => {
-                let settings = settings::get_settings(app);
-                if settings.update_checks_enabled {
-                    show_main_window(app);
-                    let _ = app.emit("check-for-updates", ());
-                }
-            }
-            "cancel" => {
-                use crate::utils::cancel_current_operation;
-
-                // Use centralized cancellation that handles all operations
-                cancel_current_operation(app);
-            }
-            "quit" => {
-                app.exit(0);
-            }
-            _ => {}
         })
         .build(app_handle)
         .unwrap();
     app_handle.manage(tray);
 
-    // Initialize tray menu with idle state
-    utils::update_tray_menu(app_handle, &utils::TrayIconState::Idle);
+    // The tray manager builds the menu and keeps it in step with the backend
+    let tray_manager = Arc::new(TrayManager::new(app_handle));
+    TrayManager::listen(&tray_manager);
+    app_handle.manage(tray_manager.clone());
+    tray_manager.refresh();
 
     // Get the autostart manager and configure based on user setting
     let autostart_manager = app_handle.autolaunch();
@@ -217,6 +305,11 @@ fn trigger_update_check(app: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// Shared by the GUI and the headless CLI so the assets are only embedded once
+fn tauri_context() -> tauri::Context<tauri::Wry> {
+    tauri::generate_context!()
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Parse console logging directives from RUST_LOG, falling back to info-level logging
@@ -226,6 +319,7 @@ pub fn run() {
     let specta_builder = Builder::<tauri::Wry>::new().commands(collect_commands![
         shortcut::change_binding,
         shortcut::reset_binding,
+        shortcut::change_stop_binding,
         shortcut::change_ptt_setting,
         shortcut::change_audio_feedback_setting,
         shortcut::change_audio_feedback_volume_setting,
@@ -238,8 +332,18 @@ pub fn run() {
         shortcut::change_debug_mode_setting,
         shortcut::change_word_correction_threshold_setting,
         shortcut::change_paste_method_setting,
+        shortcut::set_output_target,
+        shortcut::set_trigger_app_scope,
         shortcut::change_clipboard_handling_setting,
+        shortcut::change_clipboard_chunk_size_setting,
+        shortcut::change_humanized_typing_settings,
+        shortcut::change_text_formatting_setting,
+        shortcut::change_api_server_enabled_setting,
+        shortcut::change_api_server_port_setting,
+        shortcut::change_mcp_server_enabled_setting,
         shortcut::change_post_process_enabled_setting,
+        shortcut::change_post_process_context_window_setting,
+        shortcut::change_post_process_context_selection_setting,
         shortcut::change_post_process_base_url_setting,
         shortcut::change_post_process_api_key_setting,
         shortcut::change_post_process_model_setting,
@@ -248,35 +352,106 @@ pub fn run() {
         shortcut::add_post_process_prompt,
         shortcut::update_post_process_prompt,
         shortcut::delete_post_process_prompt,
+        shortcut::add_snippet,
+        shortcut::update_snippet,
+        shortcut::delete_snippet,
+        shortcut::set_regex_rules,
+        shortcut::set_text_pipeline,
+        shortcut::preview_regex_rules,
         shortcut::set_post_process_selected_prompt,
         shortcut::update_custom_words,
+        shortcut::update_proper_nouns,
         shortcut::suspend_binding,
         shortcut::resume_binding,
         shortcut::change_mute_while_recording_setting,
         shortcut::change_append_trailing_space_setting,
+        shortcut::change_fit_output_to_field_setting,
+        shortcut::change_block_secure_fields_setting,
+        shortcut::change_readback_before_paste_setting,
+        shortcut::change_confirm_before_paste_setting,
         shortcut::change_update_checks_setting,
         trigger_update_check,
         commands::cancel_operation,
+        commands::copy_next_clipboard_part,
+        commands::get_usage_counters,
+        commands::reset_usage_counters,
+        commands::generate_diagnostics,
+        commands::set_usage_counters_enabled,
+        commands::set_screen_reader_announcements,
+        commands::set_session_hooks,
+        commands::set_webhook,
+        commands::test_webhook,
+        commands::set_profiles,
+        commands::switch_profile,
+        commands::clear_profile,
+        commands::export_settings,
+        commands::preview_settings_import,
+        commands::import_settings,
+        commands::generate_setup_manifest,
+        commands::preview_manifest,
+        commands::apply_manifest,
+        commands::test_session_hook,
+        commands::cancel_session,
+        commands::abort_session,
+        commands::pause_session,
+        commands::resume_session,
+        commands::toggle_recording,
+        commands::start_dictation,
+        commands::stop_dictation,
+        commands::transcribe_file_at_path,
+        commands::get_last_transcript,
+        commands::pause_shortcuts,
+        commands::resume_shortcuts,
         commands::get_app_dir_path,
         commands::get_app_settings,
         commands::get_default_settings,
         commands::get_log_dir_path,
         commands::set_log_level,
+        commands::get_logs,
+        commands::clear_logs,
         commands::open_recordings_folder,
         commands::open_log_dir,
         commands::open_app_data_dir,
         commands::models::get_available_models,
+        commands::models::get_voice_models,
+        commands::models::set_readback_voice,
+        commands::models::set_piper_path,
+        commands::models::set_standby_model,
+        commands::models::set_rescore_model,
         commands::models::get_model_info,
+        commands::models::benchmark_models,
         commands::models::download_model,
         commands::models::delete_model,
         commands::models::cancel_download,
+        commands::models::set_shared_model_cache,
+        commands::models::refresh_model_catalog,
+        commands::models::set_active_model_ignoring_memory,
+        commands::models::cancel_model_load,
+        commands::models::get_memory_status,
+        commands::models::set_low_memory_guard,
+        commands::models::set_inference_tuning,
+        commands::models::verify_model,
+        commands::models::repair_model,
+        commands::models::search_hub_models,
+        commands::models::add_hub_model,
+        commands::models::set_hub_token,
+        commands::models::get_models_dir,
+        commands::models::move_models,
+        commands::models::get_model_disk_usage,
+        commands::models::set_model_storage_limit,
+        commands::models::evict_models,
+        commands::models::set_download_proxy,
+        commands::models::set_download_ca_cert,
+        commands::models::quantize_model,
         commands::models::set_active_model,
+        commands::models::download_and_activate_model,
         commands::models::get_current_model,
         commands::models::get_transcription_model_status,
         commands::models::is_model_loading,
         commands::models::has_any_models_available,
         commands::models::has_any_models_or_downloads,
         commands::models::get_recommended_first_model,
+        commands::models::recommend_model_tier,
         commands::audio::update_microphone_mode,
         commands::audio::get_microphone_mode,
         commands::audio::get_available_microphones,
@@ -288,16 +463,99 @@ pub fn run() {
         commands::audio::play_test_sound,
         commands::audio::check_custom_sounds,
         commands::audio::set_clamshell_microphone,
+        commands::audio::set_audio_level_rate,
+        commands::audio::set_input_gain,
+        commands::audio::set_max_utterance_duration,
+        commands::audio::set_endpointing,
+        commands::audio::get_audio_file_info,
+        commands::audio::convert_audio_file,
+        commands::audio::transcribe_audio_file,
+        commands::audio::get_interrupted_file_jobs,
+        commands::audio::resume_file_job,
+        commands::audio::discard_file_job,
+        commands::audio::set_agc_enabled,
+        commands::audio::set_pre_emphasis,
+        commands::audio::set_high_pass_cutoff,
+        commands::audio::calibrate_input_gain,
+        commands::audio::run_audio_self_test,
+        commands::audio::detect_microphones,
+        commands::audio::run_setup_test,
         commands::audio::get_clamshell_microphone,
+        commands::audio::get_caption_status,
+        commands::audio::start_captions,
+        commands::audio::stop_captions,
+        commands::audio::set_caption_source,
+        commands::plugins::get_plugins,
+        commands::plugins::set_plugin_config,
+        commands::plugins::open_plugins_folder,
         commands::transcription::set_model_unload_timeout,
+        commands::transcription::set_battery_policy,
+        commands::transcription::get_power_state,
+        commands::transcription::set_perf_metrics_enabled,
+        commands::transcription::set_mock_engine,
+        commands::transcription::set_idle_check_interval,
+        commands::transcription::set_unload_warning_seconds,
+        commands::transcription::keep_model_loaded,
         commands::transcription::get_model_load_status,
         commands::transcription::unload_model_manually,
+        commands::transcription::force_unload_model,
+        commands::transcription::get_transcription_providers,
+        commands::transcription::set_transcription_provider,
+        commands::transcription::set_low_confidence_threshold,
+        commands::transcription::set_low_confidence_marker,
+        commands::transcription::set_vocabulary_biasing,
+        commands::transcription::supports_vocabulary_bias,
+        commands::transcription::set_vocabulary_boost,
+        commands::transcription::set_language_model,
+        commands::transcription::set_language_model_weight,
+        commands::transcription::set_transcription_timeout,
+        commands::transcription::set_transcription_workers,
+        commands::transcription::set_filler_removal,
+        commands::transcription::set_disfluency_removal,
+        commands::transcription::set_disfluency_learning,
+        commands::transcription::set_disfluency_speaker,
+        commands::transcription::get_disfluency_model,
+        commands::transcription::reset_disfluency_model,
+        commands::transcription::set_cloud_fallback_to_local,
+        commands::transcription::set_azure_speech_region,
+        commands::transcription::set_cloud_api_key,
+        commands::transcription::get_pending_readback,
+        commands::transcription::read_back,
+        commands::transcription::edit_pending_readback,
+        commands::transcription::confirm_readback,
+        commands::transcription::discard_readback,
         commands::history::get_history_entries,
         commands::history::toggle_history_entry_saved,
         commands::history::get_audio_file_path,
+        commands::history::get_audio_file,
         commands::history::delete_history_entry,
+        commands::history::get_history_snippets,
+        commands::history::update_snippet_storage_limit,
+        commands::history::update_save_recording_audio,
+        commands::history::update_recording_storage_limit,
+        commands::history::retranscribe,
+        commands::history::get_transcription_versions,
+        commands::history::get_refinement_changes,
+        commands::history::paste_history_entry,
+        commands::history::undo_last_dictation,
+        commands::history::get_recoverable_sessions,
+        commands::history::restore_session,
+        commands::history::discard_recoverable_session,
         commands::history::update_history_limit,
         commands::history::update_recording_retention_period,
+        commands::history::update_audio_retention_days,
+        commands::history::update_archive_expired_history,
+        commands::history::set_history_encryption,
+        commands::history::get_incognito,
+        commands::history::set_incognito,
+        commands::history::search_history,
+        commands::history::rename_history_entry,
+        commands::history::set_history_entry_tags,
+        commands::history::get_history_tags,
+        commands::history::export_history_by_tag,
+        commands::history::get_dictation_stats,
+        commands::history::update_session_voice_commands,
+        commands::history::update_session_tag_rules,
         helpers::clamshell::is_laptop,
     ]);
 
@@ -325,10 +583,9 @@ pub fn run() {
                 Target::new(TargetKind::LogDir {
                     file_name: Some("handy".into()),
                 })
-                .filter(|metadata| {
-                    let file_level = FILE_LOG_LEVEL.load(Ordering::Relaxed);
-                    metadata.level() <= level_filter_from_u8(file_level)
-                }),
+                .filter(file_log_filter),
+                // The in-app log viewer shows what the log file gets
+                log_buffer::target(file_log_filter),
             ])
             .build(),
     );
@@ -342,6 +599,8 @@ pub fn run() {
         .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
             show_main_window(app);
         }))
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
@@ -364,6 +623,9 @@ pub fn run() {
             Some(vec![]),
         ))
         .manage(Mutex::new(ShortcutToggleStates::default()))
+        .manage(Mutex::new(clipboard::ChunkedCopy::default()))
+        .manage(clipboard::ManagedOutputStack::default())
+        .manage(readback::ManagedReadback::default())
         .setup(move |app| {
             let settings = get_settings(&app.handle());
             let tauri_log_level: tauri_plugin_log::LogLevel = settings.log_level.into();
@@ -374,6 +636,19 @@ pub fn run() {
 
             initialize_core_logic(&app_handle);
 
+            // handy:// URLs, both the one the app was launched with and later ones
+            let url_handle = app_handle.clone();
+            app.deep_link().on_open_url(move |event| {
+                for url in event.urls() {
+                    automation::handle_url(&url_handle, url.as_str());
+                }
+            });
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                for url in urls {
+                    automation::handle_url(&app_handle, url.as_str());
+                }
+            }
+
             // Show main window only if not starting hidden
             if !settings.start_hidden {
                 if let Some(main_window) = app_handle.get_webview_window("main") {
@@ -406,6 +681,6 @@ pub fn run() {
             _ => {}
         })
         .invoke_handler(specta_builder.invoke_handler())
-        .run(tauri::generate_context!())
+        .run(tauri_context())
         .expect("error while running tauri application");
 }
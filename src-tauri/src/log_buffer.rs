@@ -0,0 +1,181 @@
+//! The most recent log lines kept in memory for the in-app log viewer, so users don't have
+//! to find the log file. It is fed by a target of the log plugin with the same level as the
+//! log file, and read by polling: each entry gets an increasing id, and the viewer asks for
+//! the entries after the last one it has.
+
+use crate::settings::LogLevel;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tauri_plugin_log::{Target, TargetKind};
+
+/// Entries kept before the oldest are dropped
+const CAPACITY: usize = 2000;
+
+static BUFFER: Lazy<Mutex<LogBuffer>> = Lazy::new(|| Mutex::new(LogBuffer::default()));
+
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct LogEntry {
+    pub id: u64,
+    pub level: LogLevel,
+    /// Module path of the code that logged, such as `handy::managers::audio`
+    pub module: String,
+    /// The line as written to the log file, timestamp included
+    pub line: String,
+}
+
+#[derive(Default)]
+struct LogBuffer {
+    entries: VecDeque<LogEntry>,
+    next_id: u64,
+}
+
+/// Which entries to return; every field left unset matches all of them.
+#[derive(Clone, Debug, Default, Deserialize, Type)]
+pub struct LogQuery {
+    /// Only entries logged after the one with this id
+    pub after: Option<u64>,
+    /// Only entries at least this severe
+    pub level: Option<LogLevel>,
+    /// Only entries from this module or the ones below it
+    pub module: Option<String>,
+    /// Only the last this many of the matching entries
+    pub limit: Option<u32>,
+}
+
+/// The log plugin target feeding the buffer. `filter` decides what is kept, like for the
+/// log file.
+pub fn target(filter: impl Fn(&log::Metadata) -> bool + Send + Sync + 'static) -> Target {
+    let dispatch = fern::Dispatch::new().chain(fern::Output::call(push));
+    Target::new(TargetKind::Dispatch(dispatch)).filter(filter)
+}
+
+fn push(record: &log::Record) {
+    // The logger must not panic, even after another thread did while holding the lock
+    let Ok(mut buffer) = BUFFER.lock() else {
+        return;
+    };
+    let id = buffer.next_id;
+    buffer.next_id += 1;
+    if buffer.entries.len() == CAPACITY {
+        buffer.entries.pop_front();
+    }
+    buffer.entries.push_back(LogEntry {
+        id,
+        level: record.level().into(),
+        module: record.target().to_string(),
+        line: record.args().to_string(),
+    });
+}
+
+/// The matching entries, oldest first.
+pub fn query(query: &LogQuery) -> Vec<LogEntry> {
+    match BUFFER.lock() {
+        Ok(buffer) => matching(&buffer.entries, query),
+        Err(_) => Vec::new(),
+    }
+}
+
+pub fn clear() {
+    if let Ok(mut buffer) = BUFFER.lock() {
+        buffer.entries.clear();
+    }
+}
+
+fn matching(entries: &VecDeque<LogEntry>, query: &LogQuery) -> Vec<LogEntry> {
+    let mut matching: Vec<LogEntry> = entries
+        .iter()
+        .filter(|entry| query.after.is_none_or(|after| entry.id > after))
+        .filter(|entry| {
+            query
+                .level
+                .is_none_or(|level| severity(entry.level) >= severity(level))
+        })
+        .filter(|entry| {
+            query.module.as_deref().is_none_or(|module| {
+                entry
+                    .module
+                    .strip_prefix(module)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+        })
+        .cloned()
+        .collect();
+    if let Some(limit) = query.limit {
+        let skip = matching.len().saturating_sub(limit as usize);
+        matching.drain(..skip);
+    }
+    matching
+}
+
+fn severity(level: LogLevel) -> u8 {
+    match level {
+        LogLevel::Trace => 0,
+        LogLevel::Debug => 1,
+        LogLevel::Info => 2,
+        LogLevel::Warn => 3,
+        LogLevel::Error => 4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries() -> VecDeque<LogEntry> {
+        [
+            (LogLevel::Debug, "handy::managers::audio"),
+            (LogLevel::Info, "handy::managers::audio_feedback"),
+            (LogLevel::Warn, "handy::managers::audio"),
+            (LogLevel::Error, "handy::actions"),
+            (LogLevel::Info, "handy::managers::model"),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(id, (level, module))| LogEntry {
+            id: id as u64,
+            level,
+            module: module.to_string(),
+            line: String::new(),
+        })
+        .collect()
+    }
+
+    fn ids(query: LogQuery) -> Vec<u64> {
+        matching(&entries(), &query)
+            .into_iter()
+            .map(|entry| entry.id)
+            .collect()
+    }
+
+    #[test]
+    fn test_matching() {
+        assert_eq!(ids(LogQuery::default()), [0, 1, 2, 3, 4]);
+        assert_eq!(
+            ids(LogQuery {
+                level: Some(LogLevel::Warn),
+                ..Default::default()
+            }),
+            [2, 3]
+        );
+        // A module matches its submodules, not modules sharing its name as a prefix
+        assert_eq!(
+            ids(LogQuery {
+                module: Some("handy::managers::audio".to_string()),
+                ..Default::default()
+            }),
+            [0, 2]
+        );
+        assert_eq!(
+            ids(LogQuery {
+                module: Some("handy::managers".to_string()),
+                after: Some(1),
+                limit: Some(1),
+                ..Default::default()
+            }),
+            [4]
+        );
+    }
+}
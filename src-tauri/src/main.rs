@@ -2,5 +2,11 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if handy_app_lib::cli::is_cli_invocation(&args) {
+        handy_app_lib::cli::run(args);
+        return;
+    }
+
     handy_app_lib::run()
 }
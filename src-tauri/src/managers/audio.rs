@@ -1,11 +1,26 @@
-use crate::audio_toolkit::{list_input_devices, vad::SmoothedVad, AudioRecorder, SileroVad};
+use crate::announcer;
+use crate::audio_toolkit::audio::{
+    recommend_gain, Endpointing, FilterSettings, GainRecommendation, GainSettings,
+};
+use crate::audio_toolkit::{
+    list_input_devices, strip_overlap, vad::SmoothedVad, AudioChunk, AudioRecorder, SileroVad,
+};
 use crate::helpers::clamshell;
+use crate::managers::chunk_workers::{ChunkWorkers, Coalesce};
+use crate::managers::transcription::TranscriptionManager;
+use crate::perf_metrics::{self, ChunkMetrics};
+use crate::power_policy;
+use crate::refinement::LiveSegment;
+use crate::session_journal::SessionJournal;
 use crate::settings::{get_settings, AppSettings};
 use crate::utils;
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
-use tauri::Manager;
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
 
 fn set_mute(mute: bool) {
     // Expected behavior:
@@ -98,6 +113,9 @@ fn set_mute(mute: bool) {
 
 const WHISPER_SAMPLE_RATE: usize = 16000;
 
+/// How often the open input device is checked for unplugging or a changed OS default.
+const DEVICE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
 /* ──────────────────────────────────────────────────────────────── */
 
 #[derive(Clone, Debug)]
@@ -112,58 +130,499 @@ pub enum MicrophoneMode {
     OnDemand,
 }
 
+/// Payload of the `audio-level` event sent while recording, all values 0.0 to 1.0.
+#[derive(Clone, Debug, Serialize)]
+pub struct AudioLevelEvent {
+    pub rms: f32,
+    pub peak: f32,
+    pub bands: Vec<f32>,
+}
+
+/// Sent when the microphone in use disappeared and capture moved to the default device.
+#[derive(Clone, Debug, Serialize)]
+pub struct MicrophoneDisconnectedEvent {
+    pub device: String,
+    pub fallback: Option<String>,
+}
+
+fn gain_settings(settings: &AppSettings) -> GainSettings {
+    GainSettings {
+        gain: settings.input_gain,
+        agc: settings.agc_enabled,
+    }
+}
+
+fn filter_settings(settings: &AppSettings) -> FilterSettings {
+    FilterSettings {
+        pre_emphasis: settings.pre_emphasis,
+        high_pass_hz: settings.high_pass_cutoff_hz.map(|hz| hz as f32),
+    }
+}
+
+fn max_utterance(settings: &AppSettings) -> Option<Duration> {
+    settings
+        .max_utterance_secs
+        .map(|secs| Duration::from_secs(secs as u64))
+}
+
+/// Pauses ending an utterance. With semantic endpointing the sensitivity's pause only ends
+/// one whose live text reads as finished, `finished_utterance` holding one more than its index.
+fn endpointing(settings: &AppSettings, finished_utterance: &Arc<AtomicUsize>) -> Endpointing {
+    let pause = Duration::from_millis(settings.endpoint_sensitivity.pause_ms());
+    if !settings.semantic_endpointing {
+        return Endpointing {
+            pause,
+            finished_pause: None,
+        };
+    }
+    Endpointing {
+        pause: pause.max(Duration::from_millis(settings.unfinished_pause_ms as u64)),
+        finished_pause: Some((pause, finished_utterance.clone())),
+    }
+}
+
+/// Whether live text ends a sentence, a trailing ellipsis meaning the speaker trailed off.
+fn sounds_finished(text: &str) -> bool {
+    let text = text.trim_end();
+    !text.ends_with("...")
+        && !text.ends_with('…')
+        && text.ends_with(['.', '?', '!', '。', '？', '！'])
+}
+
+/// Latest transcription of one utterance, times in seconds from the start of the recording.
+struct LiveUtterance {
+    text: String,
+    is_final: bool,
+    start: f32,
+    end: f32,
+    confidence: Option<f32>,
+}
+
+/// Live transcription of the current recording, kept per utterance so each chunk only
+/// transcribes the utterance it belongs to.
+#[derive(Default)]
+struct LiveTranscript {
+    utterances: BTreeMap<usize, LiveUtterance>,
+}
+
+impl LiveTranscript {
+    /// Stores the text of `index`, unless its final text already arrived, which happens
+    /// when a partial was queued after the final chunk of its utterance.
+    fn update(&mut self, index: usize, utterance: LiveUtterance) -> bool {
+        if self
+            .utterances
+            .get(&index)
+            .is_some_and(|existing| existing.is_final)
+        {
+            return false;
+        }
+        self.utterances.insert(index, utterance);
+        true
+    }
+
+    fn text(&self) -> String {
+        self.text_before(usize::MAX)
+    }
+
+    /// Text of the utterances before `index`.
+    fn text_before(&self, index: usize) -> String {
+        self.utterances
+            .range(..index)
+            .map(|(_, utterance)| utterance.text.trim())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// The finished utterances, for merging with the accurate pass.
+    fn final_segments(&self) -> Vec<LiveSegment> {
+        self.utterances
+            .values()
+            .filter(|utterance| utterance.is_final)
+            .map(|utterance| LiveSegment {
+                start: utterance.start,
+                end: utterance.end,
+                text: utterance.text.clone(),
+                confidence: utterance.confidence,
+            })
+            .collect()
+    }
+}
+
+/// Most audio transcribed at once when waiting chunks are merged, Whisper's 30 s window
+const MAX_COALESCED_SAMPLES: usize = 30 * WHISPER_SAMPLE_RATE;
+
+/// Audio waiting for a worker beyond which the live transcription is reported as lagging
+const LAG_THRESHOLD_MS: u64 = 2000;
+
+/// Payload of `transcription-lagging`.
+#[derive(Clone, Debug, Serialize)]
+pub struct TranscriptionLaggingEvent {
+    /// Audio recorded but not yet handed to the engine
+    pub backlog_ms: u64,
+}
+
+/// A chunk with the session it was recorded in.
+struct QueuedChunk {
+    chunk: AudioChunk,
+    /// Last utterance in `chunk`, later than `chunk.utterance` when whole utterances were
+    /// merged while the workers were behind
+    last_utterance: usize,
+    session: u64,
+    journal_id: Option<String>,
+    /// When the recorder handed the chunk over
+    captured: Instant,
+}
+
+impl Coalesce for QueuedChunk {
+    fn coalesce(&mut self, next: Self) -> Result<(), Self> {
+        if next.session != self.session {
+            return Err(next);
+        }
+        // A newer chunk of the same utterance holds all of its audio
+        if !self.chunk.is_final && next.chunk.utterance == self.chunk.utterance {
+            *self = next;
+            return Ok(());
+        }
+        // Finished utterances are transcribed together, partials stay separate so their
+        // final chunk can replace them
+        let fits = self.chunk.samples.len() + next.chunk.samples.len() <= MAX_COALESCED_SAMPLES;
+        if self.chunk.is_final
+            && next.chunk.is_final
+            && next.chunk.utterance == self.last_utterance + 1
+            && fits
+        {
+            // The audio the two share is transcribed once
+            self.chunk
+                .samples
+                .extend_from_slice(&next.chunk.samples[next.chunk.overlap..]);
+            self.last_utterance = next.chunk.utterance;
+            return Ok(());
+        }
+        Err(next)
+    }
+
+    fn duration_ms(&self) -> u64 {
+        self.chunk.samples.len() as u64 * 1000 / WHISPER_SAMPLE_RATE as u64
+    }
+}
+
+/// Chunks recorded while the model was still loading. They are transcribed in order once it
+/// is ready, so the live transcript of a cold start doesn't miss its first sentence.
+#[derive(Default)]
+struct ColdStartQueue {
+    chunks: VecDeque<QueuedChunk>,
+    draining: bool,
+}
+
+impl ColdStartQueue {
+    /// Queues `chunk`, replacing a queued partial of the same utterance since the newer chunk
+    /// holds all of its audio. Returns whether a drain has to be started.
+    fn push(&mut self, chunk: QueuedChunk) -> bool {
+        let covered = self.chunks.iter_mut().find(|queued| {
+            !queued.chunk.is_final
+                && queued.session == chunk.session
+                && queued.chunk.utterance == chunk.chunk.utterance
+        });
+        match covered {
+            Some(queued) => *queued = chunk,
+            None => self.chunks.push_back(chunk),
+        }
+        !std::mem::replace(&mut self.draining, true)
+    }
+}
+
+/// Transcribed text of a chunk, on its way into the live transcript.
+struct ChunkText {
+    text: String,
+    confidence: Option<f32>,
+    utterance: usize,
+    last_utterance: usize,
+    is_final: bool,
+    /// Whether the chunk starts with audio of the previous utterance
+    overlaps: bool,
+    /// Time span of the chunk in the recording, in seconds
+    start: f32,
+    end: f32,
+    session: u64,
+    journal_id: Option<String>,
+    audio_ms: u64,
+    queue_ms: u64,
+    inference_ms: u64,
+}
+
+type LiveWorkers = ChunkWorkers<QueuedChunk, Option<ChunkText>>;
+
+/// Cloud providers take parallel requests, local engines serialize on the engine anyway and
+/// get a single worker.
+fn chunk_worker_count(app_handle: &tauri::AppHandle) -> usize {
+    let parallel = app_handle
+        .try_state::<Arc<TranscriptionManager>>()
+        .is_some_and(|tm| tm.runs_in_parallel());
+    if parallel {
+        get_settings(app_handle).transcription_workers as usize
+    } else {
+        1
+    }
+}
+
+/// Emits `transcription-lagging` while audio queues up faster than it is transcribed, and
+/// once more after the backlog cleared so the UI can drop its warning.
+fn report_backlog(app_handle: &tauri::AppHandle, backlog_ms: u64, lagging: &AtomicBool) {
+    let is_lagging = backlog_ms > LAG_THRESHOLD_MS;
+    let was_lagging = lagging.swap(is_lagging, Ordering::Relaxed);
+    if is_lagging && !was_lagging {
+        warn!(
+            "Live transcription is falling behind, {}ms of audio queued",
+            backlog_ms
+        );
+    }
+    if is_lagging || was_lagging {
+        let _ = app_handle.emit(
+            "transcription-lagging",
+            TranscriptionLaggingEvent { backlog_ms },
+        );
+    }
+}
+
+/// Waits for the model load to finish, then hands the queued chunks to the workers in order.
+/// When the load failed they are dropped, the final transcription reports the error.
+fn drain_cold_start(
+    app_handle: &tauri::AppHandle,
+    queue: &Mutex<ColdStartQueue>,
+    workers: &LiveWorkers,
+) {
+    let tm = Arc::clone(&app_handle.state::<Arc<TranscriptionManager>>());
+    let loaded = tm.wait_for_model();
+    debug!("Model ready after cold start, draining buffered chunks");
+
+    loop {
+        let next = {
+            let mut queue = queue.lock().unwrap();
+            match queue.chunks.pop_front() {
+                Some(next) => next,
+                None => {
+                    queue.draining = false;
+                    break;
+                }
+            }
+        };
+        if loaded {
+            workers.submit(next);
+        }
+    }
+}
+
+/// Transcribes a chunk on a worker, `None` when it is stale or no model is loaded.
+fn transcribe_chunk(app_handle: &tauri::AppHandle, queued: QueuedChunk) -> Option<ChunkText> {
+    let tm = app_handle.state::<Arc<TranscriptionManager>>();
+    let QueuedChunk {
+        chunk,
+        last_utterance,
+        session,
+        journal_id,
+        captured,
+    } = queued;
+    if tm.session_generation() != session || !(tm.is_model_loaded() || tm.has_standby()) {
+        return None;
+    }
+
+    let start = chunk.start as f32 / WHISPER_SAMPLE_RATE as f32;
+    let end = (chunk.start + chunk.samples.len()) as f32 / WHISPER_SAMPLE_RATE as f32;
+    let audio_ms = chunk.samples.len() as u64 * 1000 / WHISPER_SAMPLE_RATE as u64;
+    let queue_ms = captured.elapsed().as_millis() as u64;
+    let inference_start = Instant::now();
+    match tm.transcribe_live(chunk.samples) {
+        Ok(output) => Some(ChunkText {
+            confidence: output.average_confidence(),
+            text: output.text,
+            utterance: chunk.utterance,
+            last_utterance,
+            is_final: chunk.is_final,
+            overlaps: chunk.overlap > 0,
+            start,
+            end,
+            session,
+            journal_id,
+            audio_ms,
+            queue_ms,
+            inference_ms: inference_start.elapsed().as_millis() as u64,
+        }),
+        Err(e) => {
+            debug!("Chunk transcription failed: {}", e);
+            None
+        }
+    }
+}
+
+/// Adds a transcribed chunk to the live transcript, the overlay and the session journal.
+/// Called in the order the chunks were recorded.
+fn apply_chunk(
+    app_handle: &tauri::AppHandle,
+    live_transcript: &Mutex<LiveTranscript>,
+    finished_utterance: &AtomicUsize,
+    chunk: ChunkText,
+) {
+    let tm = app_handle.state::<Arc<TranscriptionManager>>();
+    if tm.session_generation() != chunk.session {
+        debug!("Discarding chunk transcription from aborted session");
+        return;
+    }
+    let post_process_start = Instant::now();
+    let (text, live_text) = {
+        let mut live = live_transcript.lock().unwrap();
+        // Words heard in the overlap are already in the previous utterance's text
+        let text = if chunk.overlaps {
+            strip_overlap(&live.text_before(chunk.utterance), &chunk.text)
+        } else {
+            chunk.text
+        };
+        let utterance = LiveUtterance {
+            text: text.clone(),
+            is_final: chunk.is_final,
+            start: chunk.start,
+            end: chunk.end,
+            confidence: chunk.confidence,
+        };
+        if !live.update(chunk.utterance, utterance) {
+            return;
+        }
+        // Tells the recorder whether a pause may end the utterance early
+        if !chunk.is_final {
+            let finished = if sounds_finished(&text) {
+                chunk.utterance + 1
+            } else {
+                0
+            };
+            finished_utterance.store(finished, Ordering::Relaxed);
+        }
+        // Merged utterances are all in the text and time span of the first one
+        for index in chunk.utterance + 1..=chunk.last_utterance {
+            let merged = LiveUtterance {
+                text: String::new(),
+                is_final: true,
+                start: chunk.end,
+                end: chunk.end,
+                confidence: None,
+            };
+            live.update(index, merged);
+        }
+        (text, live.text())
+    };
+    let output_start = Instant::now();
+    if !text.is_empty() {
+        // Journal the partial so it survives a crash before the final transcription
+        // is saved
+        if let Some(id) = &chunk.journal_id {
+            app_handle
+                .state::<SessionJournal>()
+                .append(id, chunk.utterance, &text);
+        }
+    }
+    if !live_text.is_empty() {
+        // Emit the partial transcription to the overlay
+        crate::overlay::emit_transcription_update(app_handle, &live_text);
+        announcer::partial(app_handle, &live_text);
+    }
+    perf_metrics::record_chunk(
+        app_handle,
+        ChunkMetrics {
+            session: chunk.session,
+            utterance: chunk.utterance,
+            is_final: chunk.is_final,
+            audio_ms: chunk.audio_ms,
+            queue_ms: chunk.queue_ms,
+            inference_ms: chunk.inference_ms,
+            post_process_ms: (output_start - post_process_start).as_millis() as u64,
+            output_ms: output_start.elapsed().as_millis() as u64,
+        },
+    );
+}
+
 /* ──────────────────────────────────────────────────────────────── */
 
 fn create_audio_recorder(
     vad_path: &str,
     app_handle: &tauri::AppHandle,
+    chunk_count: Arc<AtomicUsize>,
+    workers: Arc<LiveWorkers>,
+    cold_start: Arc<Mutex<ColdStartQueue>>,
+    finished_utterance: &Arc<AtomicUsize>,
 ) -> Result<AudioRecorder, anyhow::Error> {
     let silero = SileroVad::new(vad_path, 0.3)
         .map_err(|e| anyhow::anyhow!("Failed to create SileroVad: {}", e))?;
     let smoothed_vad = SmoothedVad::new(Box::new(silero), 15, 15, 2);
+    let level_rate = power_policy::audio_level_rate_hz(&get_settings(app_handle));
 
     // Recorder with VAD plus a spectrum-level callback that forwards updates to
     // the frontend.
     let recorder = AudioRecorder::new()
         .map_err(|e| anyhow::anyhow!("Failed to create AudioRecorder: {}", e))?
         .with_vad(Box::new(smoothed_vad))
+        .with_gain(gain_settings(&get_settings(app_handle)))
+        .with_filter(filter_settings(&get_settings(app_handle)))
+        .with_max_utterance(max_utterance(&get_settings(app_handle)))
+        .with_endpointing(endpointing(&get_settings(app_handle), finished_utterance))
         .with_level_callback({
             let app_handle = app_handle.clone();
             move |levels| {
                 utils::emit_levels(&app_handle, &levels);
             }
         })
+        .with_audio_levels_callback(level_rate, {
+            let app_handle = app_handle.clone();
+            move |levels| {
+                let _ = app_handle.emit(
+                    "audio-level",
+                    AudioLevelEvent {
+                        rms: levels.rms,
+                        peak: levels.peak,
+                        bands: levels.bands,
+                    },
+                );
+            }
+        })
         .with_chunk_callback({
             let app_handle = app_handle.clone();
+            let lagging = AtomicBool::new(false);
             move |audio_chunk| {
-                // Spawn a task to transcribe this chunk in real-time
-                let ah = app_handle.clone();
-                let chunk = audio_chunk.clone();
-                tauri::async_runtime::spawn(async move {
-                    use crate::managers::transcription::TranscriptionManager;
-                    use std::sync::Arc;
-
-                    // Get the transcription manager
-                    let tm = ah.state::<Arc<TranscriptionManager>>();
-
-                    // Only transcribe if model is loaded
-                    if !tm.is_model_loaded() {
-                        return;
-                    }
+                chunk_count.fetch_add(1, Ordering::Relaxed);
 
-                    // Transcribe the chunk
-                    match tm.transcribe(chunk) {
-                        Ok(text) => {
-                            if !text.is_empty() {
-                                // Emit the partial transcription to the overlay
-                                crate::overlay::emit_transcription_update(&ah, &text);
-                            }
-                        }
-                        Err(e) => {
-                            log::debug!("Chunk transcription failed: {}", e);
+                let Some(tm) = app_handle.try_state::<Arc<TranscriptionManager>>() else {
+                    return;
+                };
+                // Capture the session this chunk belongs to so an abort that happens
+                // while the chunk is queued or being transcribed discards its result
+                let chunk = QueuedChunk {
+                    last_utterance: audio_chunk.utterance,
+                    chunk: audio_chunk,
+                    session: tm.session_generation(),
+                    journal_id: app_handle
+                        .try_state::<SessionJournal>()
+                        .and_then(|journal| journal.current_id()),
+                    captured: Instant::now(),
+                };
+
+                // While the model is still loading, hold on to the audio instead of
+                // dropping it, later chunks queue behind it to keep the order. A standby
+                // model answers in the meantime.
+                {
+                    let mut queue = cold_start.lock().unwrap();
+                    if queue.draining || (tm.is_model_loading() && !tm.has_standby()) {
+                        if queue.push(chunk) {
+                            let ah = app_handle.clone();
+                            let queue = cold_start.clone();
+                            let workers = workers.clone();
+                            std::thread::spawn(move || drain_cold_start(&ah, &queue, &workers));
                         }
+                        return;
                     }
-                });
+                }
+
+                // Hand the chunk to the workers so the recorder never waits on the engine
+                workers.set_workers(chunk_worker_count(&app_handle));
+                workers.submit(chunk);
+                report_backlog(&app_handle, workers.backlog_ms(), &lagging);
             }
         });
 
@@ -182,6 +641,14 @@ pub struct AudioRecordingManager {
     is_open: Arc<Mutex<bool>>,
     is_recording: Arc<Mutex<bool>>,
     did_mute: Arc<Mutex<bool>>,
+    chunk_count: Arc<AtomicUsize>,
+    live_transcript: Arc<Mutex<LiveTranscript>>,
+    chunk_workers: Arc<LiveWorkers>,
+    cold_start: Arc<Mutex<ColdStartQueue>>,
+    /// One more than the index of the utterance whose live text reads as finished
+    finished_utterance: Arc<AtomicUsize>,
+    /// The recording takes in no audio until resumed
+    paused: Arc<AtomicBool>,
 }
 
 impl AudioRecordingManager {
@@ -195,6 +662,26 @@ impl AudioRecordingManager {
             MicrophoneMode::OnDemand
         };
 
+        let live_transcript = Arc::new(Mutex::new(LiveTranscript::default()));
+        let finished_utterance = Arc::new(AtomicUsize::new(0));
+        let chunk_workers = Arc::new(ChunkWorkers::new(
+            chunk_worker_count(app),
+            {
+                let app = app.clone();
+                move |chunk| transcribe_chunk(&app, chunk)
+            },
+            {
+                let app = app.clone();
+                let live_transcript = live_transcript.clone();
+                let finished_utterance = finished_utterance.clone();
+                move |chunk: Option<ChunkText>| {
+                    if let Some(chunk) = chunk {
+                        apply_chunk(&app, &live_transcript, &finished_utterance, chunk);
+                    }
+                }
+            },
+        ));
+
         let manager = Self {
             state: Arc::new(Mutex::new(RecordingState::Idle)),
             mode: Arc::new(Mutex::new(mode.clone())),
@@ -204,6 +691,12 @@ impl AudioRecordingManager {
             is_open: Arc::new(Mutex::new(false)),
             is_recording: Arc::new(Mutex::new(false)),
             did_mute: Arc::new(Mutex::new(false)),
+            chunk_count: Arc::new(AtomicUsize::new(0)),
+            live_transcript,
+            chunk_workers,
+            cold_start: Arc::new(Mutex::new(ColdStartQueue::default())),
+            finished_utterance,
+            paused: Arc::new(AtomicBool::new(false)),
         };
 
         // Always-on?  Open immediately.
@@ -211,12 +704,18 @@ impl AudioRecordingManager {
             manager.start_microphone_stream()?;
         }
 
+        {
+            let watcher = manager.clone();
+            std::thread::spawn(move || watcher.watch_devices());
+        }
+
         Ok(manager)
     }
 
     /* ---------- helper methods --------------------------------------------- */
 
-    fn get_effective_microphone_device(&self, settings: &AppSettings) -> Option<cpal::Device> {
+    /// Name of the microphone the settings ask for, `None` to follow the OS default.
+    fn get_effective_microphone_name(&self, settings: &AppSettings) -> Option<String> {
         // Check if we're in clamshell mode and have a clamshell microphone configured
         let use_clamshell_mic = if let Ok(is_clamshell) = clamshell::is_clamshell() {
             is_clamshell && settings.clamshell_microphone.is_some()
@@ -224,17 +723,21 @@ impl AudioRecordingManager {
             false
         };
 
-        let device_name = if use_clamshell_mic {
-            settings.clamshell_microphone.as_ref().unwrap()
+        if use_clamshell_mic {
+            settings.clamshell_microphone.clone()
         } else {
-            settings.selected_microphone.as_ref()?
-        };
+            settings.selected_microphone.clone()
+        }
+    }
+
+    fn get_effective_microphone_device(&self, settings: &AppSettings) -> Option<cpal::Device> {
+        let device_name = self.get_effective_microphone_name(settings)?;
 
         // Find the device by name
         match list_input_devices() {
             Ok(devices) => devices
                 .into_iter()
-                .find(|d| d.name == *device_name)
+                .find(|d| d.name == device_name)
                 .map(|d| d.device),
             Err(e) => {
                 debug!("Failed to list devices, using default: {}", e);
@@ -243,6 +746,93 @@ impl AudioRecordingManager {
         }
     }
 
+    /* ---------- hot-plug handling ------------------------------------------ */
+
+    /// Keeps the open stream on the right device: fails over to the default device when the
+    /// one in use is unplugged, follows OS default changes when no microphone is selected,
+    /// and returns to the selected microphone once it is plugged back in.
+    fn watch_devices(&self) {
+        let mut known_devices: Vec<String> = Vec::new();
+
+        loop {
+            std::thread::sleep(DEVICE_POLL_INTERVAL);
+            if !*self.is_open.lock().unwrap() {
+                continue;
+            }
+
+            let devices = match list_input_devices() {
+                Ok(devices) => devices,
+                Err(e) => {
+                    debug!("Failed to list input devices: {}", e);
+                    continue;
+                }
+            };
+            let names: Vec<String> = devices.iter().map(|d| d.name.clone()).collect();
+            if names != known_devices {
+                let _ = self.app_handle.emit("microphone-devices-changed", &names);
+                known_devices = names.clone();
+            }
+
+            let Some(current) = self
+                .recorder
+                .lock()
+                .unwrap()
+                .as_ref()
+                .and_then(|rec| rec.device_name())
+            else {
+                continue;
+            };
+            let default_name = devices
+                .iter()
+                .find(|d| d.is_default)
+                .map(|d| d.name.clone());
+            let wanted = self.get_effective_microphone_name(&get_settings(&self.app_handle));
+
+            if !names.contains(&current) {
+                warn!(
+                    "Microphone '{}' disconnected, switching to the default device",
+                    current
+                );
+                if self.switch_device(None) {
+                    let _ = self.app_handle.emit(
+                        "microphone-disconnected",
+                        MicrophoneDisconnectedEvent {
+                            device: current,
+                            fallback: default_name,
+                        },
+                    );
+                }
+            } else if let Some(wanted) = wanted
+                .as_ref()
+                .filter(|w| **w != current && names.contains(w))
+            {
+                info!("Microphone '{}' is available again, switching back", wanted);
+                let device = devices
+                    .into_iter()
+                    .find(|d| d.name == *wanted)
+                    .map(|d| d.device);
+                self.switch_device(device);
+            } else if wanted.is_none() && default_name.as_ref().is_some_and(|d| *d != current) {
+                info!("Default microphone changed, following it");
+                self.switch_device(None);
+            }
+        }
+    }
+
+    /// Moves the open stream to `device` (the default when `None`), keeping any recording.
+    fn switch_device(&self, device: Option<cpal::Device>) -> bool {
+        match self.recorder.lock().unwrap().as_mut() {
+            Some(rec) => match rec.switch_device(device) {
+                Ok(()) => true,
+                Err(e) => {
+                    error!("Failed to switch microphone: {}", e);
+                    false
+                }
+            },
+            None => false,
+        }
+    }
+
     /* ---------- microphone life-cycle -------------------------------------- */
 
     /// Applies mute if mute_while_recording is enabled and stream is open
@@ -294,6 +884,10 @@ impl AudioRecordingManager {
             *recorder_opt = Some(create_audio_recorder(
                 vad_path.to_str().unwrap(),
                 &self.app_handle,
+                self.chunk_count.clone(),
+                self.chunk_workers.clone(),
+                self.cold_start.clone(),
+                &self.finished_utterance,
             )?);
         }
 
@@ -379,7 +973,10 @@ impl AudioRecordingManager {
 
             if let Some(rec) = self.recorder.lock().unwrap().as_ref() {
                 if rec.start().is_ok() {
+                    self.chunk_count.store(0, Ordering::Relaxed);
+                    *self.live_transcript.lock().unwrap() = LiveTranscript::default();
                     *self.is_recording.lock().unwrap() = true;
+                    self.paused.store(false, Ordering::Relaxed);
                     *state = RecordingState::Recording {
                         binding_id: binding_id.to_string(),
                     };
@@ -395,14 +992,115 @@ impl AudioRecordingManager {
     }
 
     pub fn update_selected_device(&self) -> Result<(), anyhow::Error> {
-        // If currently open, restart the microphone stream to use the new device
-        if *self.is_open.lock().unwrap() {
-            self.stop_microphone_stream();
-            self.start_microphone_stream()?;
+        if !*self.is_open.lock().unwrap() {
+            return Ok(());
+        }
+
+        // Mid-recording, move the running recorder over so the session continues on the
+        // new device instead of losing what was captured so far
+        if *self.is_recording.lock().unwrap() {
+            let settings = get_settings(&self.app_handle);
+            let device = self.get_effective_microphone_device(&settings);
+            if let Some(rec) = self.recorder.lock().unwrap().as_mut() {
+                rec.switch_device(device)
+                    .map_err(|e| anyhow::anyhow!("Failed to switch microphone: {}", e))?;
+                info!("Switched microphone during recording");
+                return Ok(());
+            }
         }
+
+        // Otherwise restart the microphone stream to use the new device
+        self.stop_microphone_stream();
+        self.start_microphone_stream()?;
         Ok(())
     }
 
+    /// Applies a changed `audio-level` event rate to the running recorder.
+    pub fn update_audio_level_rate(&self, readings_per_second: u32) {
+        if let Some(rec) = self.recorder.lock().unwrap().as_mut() {
+            rec.set_audio_levels_rate(readings_per_second);
+        }
+    }
+
+    /// Applies changed input gain and AGC settings to the running recorder.
+    pub fn update_gain(&self) {
+        let gain = gain_settings(&get_settings(&self.app_handle));
+        if let Some(rec) = self.recorder.lock().unwrap().as_mut() {
+            rec.set_gain(gain);
+        }
+    }
+
+    /// Applies changed high-pass filter and pre-emphasis settings to the running recorder.
+    pub fn update_filter(&self) {
+        let filter = filter_settings(&get_settings(&self.app_handle));
+        if let Some(rec) = self.recorder.lock().unwrap().as_mut() {
+            rec.set_filter(filter);
+        }
+    }
+
+    /// Applies a changed maximum utterance duration to the running recorder.
+    pub fn update_max_utterance(&self) {
+        let max = max_utterance(&get_settings(&self.app_handle));
+        if let Some(rec) = self.recorder.lock().unwrap().as_mut() {
+            rec.set_max_utterance(max);
+        }
+    }
+
+    /// Applies changed endpointing settings to the running recorder.
+    pub fn update_endpointing(&self) {
+        let endpointing = endpointing(&get_settings(&self.app_handle), &self.finished_utterance);
+        if let Some(rec) = self.recorder.lock().unwrap().as_mut() {
+            rec.set_endpointing(endpointing);
+        }
+    }
+
+    /// Listens to the microphone for `duration` and suggests an input gain for it. Works
+    /// whether or not the stream is open, but not while recording.
+    pub fn calibrate_gain(&self, duration: Duration) -> Result<GainRecommendation, anyhow::Error> {
+        if self.is_recording() {
+            return Err(anyhow::anyhow!("Can't calibrate while recording"));
+        }
+
+        let (samples, sample_rate) = self.capture_input(duration)?;
+        let recommendation = recommend_gain(&samples, sample_rate)
+            .ok_or_else(|| anyhow::anyhow!("No sound was picked up by the microphone"))?;
+        info!(
+            "Gain calibration: speech RMS {:.4}, noise floor {:.4}, peak {:.3}, recommended gain {:.2}",
+            recommendation.speech_rms,
+            recommendation.noise_floor,
+            recommendation.peak,
+            recommendation.gain
+        );
+        Ok(recommendation)
+    }
+
+    /// Records `duration` of raw microphone input, opening the stream for it if needed.
+    /// Returns the samples and their sample rate.
+    pub fn capture_input(&self, duration: Duration) -> Result<(Vec<f32>, u32), anyhow::Error> {
+        let was_open = *self.is_open.lock().unwrap();
+        self.start_microphone_stream()?;
+
+        // Wait without holding the recorder lock so a recording can still start meanwhile
+        let capture = match self.recorder.lock().unwrap().as_ref() {
+            Some(rec) => rec
+                .capture(duration)
+                .map_err(|e| anyhow::anyhow!("Failed to start capture: {}", e)),
+            None => Err(anyhow::anyhow!("Recorder not available")),
+        };
+        let result = capture.and_then(|rx| {
+            rx.recv_timeout(duration + Duration::from_secs(2))
+                .map_err(|_| anyhow::anyhow!("The microphone stopped delivering audio"))
+        });
+
+        if !was_open
+            && !self.is_recording()
+            && matches!(*self.mode.lock().unwrap(), MicrophoneMode::OnDemand)
+        {
+            self.stop_microphone_stream();
+        }
+        result
+    }
+
     pub fn stop_recording(&self, binding_id: &str) -> Option<Vec<f32>> {
         let mut state = self.state.lock().unwrap();
 
@@ -412,6 +1110,7 @@ impl AudioRecordingManager {
             } if active == binding_id => {
                 *state = RecordingState::Idle;
                 drop(state);
+                self.paused.store(false, Ordering::Relaxed);
 
                 let samples = if let Some(rec) = self.recorder.lock().unwrap().as_ref() {
                     match rec.stop() {
@@ -447,6 +1146,67 @@ impl AudioRecordingManager {
             _ => None,
         }
     }
+
+    /// Pauses the recording, keeping what was recorded and transcribed so far for when it
+    /// resumes. Mute is lifted meanwhile. Returns whether a running recording was paused.
+    pub fn pause_recording(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        if !matches!(*state, RecordingState::Recording { .. })
+            || self.paused.swap(true, Ordering::Relaxed)
+        {
+            return false;
+        }
+        if let Some(rec) = self.recorder.lock().unwrap().as_ref() {
+            if let Err(e) = rec.pause() {
+                error!("pause() failed: {e}");
+            }
+        }
+        drop(state);
+        self.remove_mute();
+        debug!("Recording paused");
+        true
+    }
+
+    /// Continues a paused recording. Returns whether one was paused.
+    pub fn resume_recording(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        if !matches!(*state, RecordingState::Recording { .. })
+            || !self.paused.swap(false, Ordering::Relaxed)
+        {
+            return false;
+        }
+        if let Some(rec) = self.recorder.lock().unwrap().as_ref() {
+            if let Err(e) = rec.resume() {
+                error!("resume() failed: {e}");
+            }
+        }
+        drop(state);
+        self.apply_mute();
+        debug!("Recording resumed");
+        true
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Drops the live transcription of the current recording and the chunks still waiting
+    /// for the model, so nothing of a cancelled session is shown or merged.
+    pub fn discard_live_transcript(&self) {
+        *self.live_transcript.lock().unwrap() = LiveTranscript::default();
+        self.cold_start.lock().unwrap().chunks.clear();
+    }
+
+    /// The finished utterances of the live transcription of the current or last recording.
+    pub fn live_segments(&self) -> Vec<LiveSegment> {
+        self.live_transcript.lock().unwrap().final_segments()
+    }
+
+    /// Partial transcription chunks emitted during the current or last recording.
+    pub fn chunk_count(&self) -> usize {
+        self.chunk_count.load(Ordering::Relaxed)
+    }
+
     pub fn is_recording(&self) -> bool {
         matches!(
             *self.state.lock().unwrap(),
@@ -461,6 +1221,7 @@ impl AudioRecordingManager {
         if let RecordingState::Recording { .. } = *state {
             *state = RecordingState::Idle;
             drop(state);
+            self.paused.store(false, Ordering::Relaxed);
 
             if let Some(rec) = self.recorder.lock().unwrap().as_ref() {
                 let _ = rec.stop(); // Discard the result
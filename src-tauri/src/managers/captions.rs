@@ -0,0 +1,276 @@
+//! Live captions in a window of their own, transcribed from the microphone or from what the
+//! computer plays. The caption stream records through its own recorder, so it runs whether
+//! or not a dictation is in progress, and its text never reaches the transcript, the
+//! history or the focused app. Partial and final captions are sent to the caption window
+//! alone.
+
+use crate::audio_toolkit::audio::{list_input_devices, AudioChunk, AudioRecorder};
+use crate::audio_toolkit::vad::SmoothedVad;
+use crate::audio_toolkit::SileroVad;
+use crate::managers::transcription::TranscriptionManager;
+use crate::overlay::{self, CAPTION_WINDOW};
+use crate::settings::{get_settings, CaptionSource};
+use log::{debug, info};
+use serde::Serialize;
+use specta::Type;
+use std::sync::{mpsc, Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Parts of the names inputs carrying the system's output go by: PulseAudio and PipeWire
+/// monitor sources, Windows' Stereo Mix and loopback drivers such as BlackHole on macOS
+const LOOPBACK_NAMES: &[&str] = &[
+    "monitor of",
+    ".monitor",
+    "stereo mix",
+    "what u hear",
+    "loopback",
+    "blackhole",
+    "soundflower",
+];
+
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct CaptionStatus {
+    pub running: bool,
+    pub source: CaptionSource,
+    /// Input the captions record from, `None` while stopped or on the default input
+    pub device: Option<String>,
+}
+
+struct CaptionStream {
+    recorder: AudioRecorder,
+    source: CaptionSource,
+}
+
+pub struct CaptionManager {
+    app_handle: AppHandle,
+    stream: Mutex<Option<CaptionStream>>,
+}
+
+impl CaptionManager {
+    pub fn new(app_handle: &AppHandle) -> Self {
+        Self {
+            app_handle: app_handle.clone(),
+            stream: Mutex::new(None),
+        }
+    }
+
+    pub fn status(&self) -> CaptionStatus {
+        let stream = self.stream.lock().unwrap();
+        match stream.as_ref() {
+            Some(stream) => CaptionStatus {
+                running: true,
+                source: stream.source,
+                device: stream.recorder.device_name(),
+            },
+            None => CaptionStatus {
+                running: false,
+                source: get_settings(&self.app_handle).caption_source,
+                device: None,
+            },
+        }
+    }
+
+    /// Opens the caption window and starts transcribing the source the settings select.
+    /// A running stream is restarted, so a changed source or device takes effect.
+    pub fn start(&self) -> Result<(), String> {
+        self.stop_stream();
+
+        let settings = get_settings(&self.app_handle);
+        let devices = list_input_devices().map_err(|e| e.to_string())?;
+        let names: Vec<String> = devices.iter().map(|d| d.name.clone()).collect();
+        let wanted = pick_device(
+            settings.caption_source,
+            settings.caption_device.as_deref(),
+            settings.selected_microphone.as_deref(),
+            &names,
+        )?;
+        let device = wanted.and_then(|name| {
+            devices
+                .into_iter()
+                .find(|d| d.name == name)
+                .map(|d| d.device)
+        });
+
+        let (chunk_tx, chunk_rx) = mpsc::channel::<AudioChunk>();
+        let mut recorder = self.create_recorder(chunk_tx)?;
+        recorder.open(device).map_err(|e| e.to_string())?;
+        recorder.start().map_err(|e| e.to_string())?;
+        info!(
+            "Captions started from {:?} ({})",
+            recorder.device_name(),
+            match settings.caption_source {
+                CaptionSource::Microphone => "microphone",
+                CaptionSource::SystemAudio => "system audio",
+            }
+        );
+
+        let tm = self.app_handle.state::<Arc<TranscriptionManager>>();
+        tm.initiate_model_load();
+        let app_handle = self.app_handle.clone();
+        std::thread::spawn(move || run_captions(&app_handle, chunk_rx));
+
+        *self.stream.lock().unwrap() = Some(CaptionStream {
+            recorder,
+            source: settings.caption_source,
+        });
+        overlay::show_caption_window(&self.app_handle)?;
+        let _ = self.app_handle.emit("captions-changed", self.status());
+        Ok(())
+    }
+
+    /// Stops the caption stream and hides the caption window.
+    pub fn stop(&self) {
+        self.stop_stream();
+        overlay::hide_caption_window(&self.app_handle);
+        let _ = self.app_handle.emit("captions-changed", self.status());
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.stream.lock().unwrap().is_some()
+    }
+
+    fn stop_stream(&self) {
+        if let Some(mut stream) = self.stream.lock().unwrap().take() {
+            let _ = stream.recorder.stop();
+            // Closing drops the chunk sender, which ends the transcription thread once it
+            // has worked through what is queued
+            let _ = stream.recorder.close();
+            info!("Captions stopped");
+        }
+    }
+
+    fn create_recorder(&self, chunk_tx: mpsc::Sender<AudioChunk>) -> Result<AudioRecorder, String> {
+        let vad_path = self
+            .app_handle
+            .path()
+            .resolve(
+                "resources/models/silero_vad_v4.onnx",
+                tauri::path::BaseDirectory::Resource,
+            )
+            .map_err(|e| format!("Failed to resolve VAD path: {}", e))?;
+        let silero = SileroVad::new(vad_path.to_str().unwrap(), 0.3)
+            .map_err(|e| format!("Failed to create SileroVad: {}", e))?;
+        let smoothed_vad = SmoothedVad::new(Box::new(silero), 15, 15, 2);
+
+        let recorder = AudioRecorder::new()
+            .map_err(|e| format!("Failed to create AudioRecorder: {}", e))?
+            .with_vad(Box::new(smoothed_vad))
+            .with_chunk_callback(move |chunk| {
+                let _ = chunk_tx.send(chunk);
+            });
+        Ok(recorder)
+    }
+}
+
+/// Transcribes chunks as they arrive and sends the text to the caption window, until the
+/// recorder is closed. Captions always run on the local model, and don't open a dictation
+/// session, so they stay out of the way of cancelling and of the overlay.
+fn run_captions(app_handle: &AppHandle, chunks: mpsc::Receiver<AudioChunk>) {
+    let tm = app_handle.state::<Arc<TranscriptionManager>>();
+    tm.wait_for_model();
+
+    while let Ok(mut chunk) = chunks.recv() {
+        // Behind on transcription, skip to the newest partial. Finals are never skipped,
+        // an utterance's final always comes before the next utterance starts.
+        while !chunk.is_final {
+            match chunks.try_recv() {
+                Ok(next) => chunk = next,
+                Err(_) => break,
+            }
+        }
+        if !tm.is_model_loaded() {
+            continue;
+        }
+        let is_final = chunk.is_final;
+        match tm.transcribe_on_device(chunk.samples) {
+            Ok(output) if output.text.is_empty() => {}
+            Ok(output) => {
+                let event = if is_final {
+                    "caption-final"
+                } else {
+                    "caption-partial"
+                };
+                overlay::emit_to_window(app_handle, CAPTION_WINDOW, event, output.text);
+            }
+            Err(e) => debug!("Caption transcription failed: {}", e),
+        }
+    }
+}
+
+fn is_loopback(name: &str) -> bool {
+    let name = name.to_lowercase();
+    LOOPBACK_NAMES.iter().any(|part| name.contains(part))
+}
+
+/// Picks the input to caption from `names`, `None` standing for the default input. A
+/// device set for the captions wins. The microphone source follows the dictation
+/// microphone, and system audio takes the first loopback input, as none of the platforms
+/// offer a way to record their output that works everywhere.
+fn pick_device(
+    source: CaptionSource,
+    requested: Option<&str>,
+    selected_microphone: Option<&str>,
+    names: &[String],
+) -> Result<Option<String>, String> {
+    if let Some(requested) = requested {
+        return match names.iter().find(|name| *name == requested) {
+            Some(name) => Ok(Some(name.clone())),
+            None => Err(format!("The caption input {} is not connected", requested)),
+        };
+    }
+    match source {
+        CaptionSource::Microphone => Ok(selected_microphone
+            .and_then(|wanted| names.iter().find(|name| *name == wanted))
+            .cloned()),
+        CaptionSource::SystemAudio => names
+            .iter()
+            .find(|name| is_loopback(name))
+            .cloned()
+            .map(Some)
+            .ok_or_else(|| {
+                "No input carries the system audio. Enable a monitor source or Stereo Mix, \
+                 or install a loopback driver such as BlackHole"
+                    .to_string()
+            }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_pick_device_for_system_audio() {
+        let devices = names(&["USB Mic", "Monitor of Built-in Audio Analog Stereo"]);
+        assert_eq!(
+            pick_device(CaptionSource::SystemAudio, None, None, &devices).unwrap(),
+            Some("Monitor of Built-in Audio Analog Stereo".to_string())
+        );
+        assert!(pick_device(CaptionSource::SystemAudio, None, None, &names(&["USB Mic"])).is_err());
+        assert!(is_loopback("Stereo Mix (Realtek(R) Audio)"));
+        assert!(is_loopback("BlackHole 2ch"));
+    }
+
+    #[test]
+    fn test_pick_device_for_microphone() {
+        let devices = names(&["USB Mic", "Webcam"]);
+        assert_eq!(
+            pick_device(CaptionSource::Microphone, None, Some("Webcam"), &devices).unwrap(),
+            Some("Webcam".to_string())
+        );
+        // An unplugged dictation microphone falls back to the default input
+        assert_eq!(
+            pick_device(CaptionSource::Microphone, None, Some("Headset"), &devices).unwrap(),
+            None
+        );
+        assert_eq!(
+            pick_device(CaptionSource::SystemAudio, Some("USB Mic"), None, &devices).unwrap(),
+            Some("USB Mic".to_string())
+        );
+        assert!(pick_device(CaptionSource::Microphone, Some("Headset"), None, &devices).is_err());
+    }
+}
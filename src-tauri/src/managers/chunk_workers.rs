@@ -0,0 +1,274 @@
+//! Worker threads that transcribe live chunks off the recorder's thread. Chunks are queued
+//! as they arrive and taken by however many workers the engine can keep busy; results are
+//! handed on in the order the chunks were queued, whichever worker finishes first. When the
+//! workers fall behind, a new chunk is folded into the one waiting before it instead of
+//! growing the queue.
+
+use log::{debug, error};
+use std::collections::{BTreeMap, VecDeque};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+/// Most workers `set_workers` starts, whatever is asked for
+pub const MAX_CHUNK_WORKERS: usize = 8;
+
+/// Jobs that can be combined with the job queued before them.
+pub trait Coalesce: Sized {
+    /// Folds `next` into `self`, or hands it back when the two can't be combined.
+    fn coalesce(&mut self, next: Self) -> Result<(), Self>;
+
+    /// Audio the job holds, in milliseconds
+    fn duration_ms(&self) -> u64;
+}
+
+struct Queue<T> {
+    jobs: VecDeque<(u64, T)>,
+    next_seq: u64,
+    /// Workers running and how many should be
+    running: usize,
+    target: usize,
+    closed: bool,
+}
+
+/// Results waiting for the ones queued before them. `None` stands for a job whose worker
+/// panicked, so the results after it aren't held back forever.
+struct Finished<R> {
+    next_seq: u64,
+    ready: BTreeMap<u64, Option<R>>,
+}
+
+struct Shared<T, R> {
+    queue: Mutex<Queue<T>>,
+    available: Condvar,
+    finished: Mutex<Finished<R>>,
+    work: Box<dyn Fn(T) -> R + Send + Sync>,
+    deliver: Box<dyn Fn(R) + Send + Sync>,
+}
+
+pub struct ChunkWorkers<T, R> {
+    shared: Arc<Shared<T, R>>,
+}
+
+impl<T: Coalesce + Send + 'static, R: Send + 'static> ChunkWorkers<T, R> {
+    /// Starts `workers` threads running `work` on submitted jobs. `deliver` gets each result
+    /// in submission order and runs on the worker that completed the sequence.
+    pub fn new<W, D>(workers: usize, work: W, deliver: D) -> Self
+    where
+        W: Fn(T) -> R + Send + Sync + 'static,
+        D: Fn(R) + Send + Sync + 'static,
+    {
+        let pool = Self {
+            shared: Arc::new(Shared {
+                queue: Mutex::new(Queue {
+                    jobs: VecDeque::new(),
+                    next_seq: 0,
+                    running: 0,
+                    target: 0,
+                    closed: false,
+                }),
+                available: Condvar::new(),
+                finished: Mutex::new(Finished {
+                    next_seq: 0,
+                    ready: BTreeMap::new(),
+                }),
+                work: Box::new(work),
+                deliver: Box::new(deliver),
+            }),
+        };
+        pool.set_workers(workers);
+        pool
+    }
+
+    /// Queues `job` behind the ones already submitted. A job still waiting for a worker means
+    /// they are behind, `job` is then merged into it when possible and keeps its place.
+    pub fn submit(&self, job: T) {
+        let mut queue = self.shared.queue.lock().unwrap();
+        let job = match queue.jobs.back_mut() {
+            Some((_, waiting)) => match waiting.coalesce(job) {
+                Ok(()) => return,
+                Err(job) => job,
+            },
+            None => job,
+        };
+        let seq = queue.next_seq;
+        queue.next_seq += 1;
+        queue.jobs.push_back((seq, job));
+        self.shared.available.notify_one();
+    }
+
+    /// Grows or shrinks the pool to `workers` threads, at least one. Surplus workers stop
+    /// once they finish their current job.
+    pub fn set_workers(&self, workers: usize) {
+        let workers = workers.clamp(1, MAX_CHUNK_WORKERS);
+        let mut queue = self.shared.queue.lock().unwrap();
+        if queue.target == workers {
+            return;
+        }
+        debug!("Running {} chunk transcription worker(s)", workers);
+        queue.target = workers;
+        while queue.running < workers {
+            let shared = Arc::clone(&self.shared);
+            let spawned = thread::Builder::new()
+                .name("chunk-worker".to_string())
+                .spawn(move || shared.run_worker());
+            match spawned {
+                Ok(_) => queue.running += 1,
+                Err(e) => {
+                    error!("Failed to start chunk transcription worker: {}", e);
+                    break;
+                }
+            }
+        }
+        self.shared.available.notify_all();
+    }
+
+    /// Audio queued and not yet picked up by a worker, in milliseconds.
+    pub fn backlog_ms(&self) -> u64 {
+        let queue = self.shared.queue.lock().unwrap();
+        queue.jobs.iter().map(|(_, job)| job.duration_ms()).sum()
+    }
+}
+
+impl<T, R> Drop for ChunkWorkers<T, R> {
+    fn drop(&mut self) {
+        self.shared.queue.lock().unwrap().closed = true;
+        self.shared.available.notify_all();
+    }
+}
+
+impl<T, R> Shared<T, R> {
+    fn run_worker(&self) {
+        loop {
+            let (seq, job) = {
+                let mut queue = self.queue.lock().unwrap();
+                loop {
+                    if queue.closed || queue.running > queue.target {
+                        queue.running -= 1;
+                        return;
+                    }
+                    if let Some(next) = queue.jobs.pop_front() {
+                        break next;
+                    }
+                    queue = self.available.wait(queue).unwrap();
+                }
+            };
+
+            let result = panic::catch_unwind(AssertUnwindSafe(|| (self.work)(job)));
+            if result.is_err() {
+                error!("Chunk transcription worker panicked");
+            }
+            self.finish(seq, result.ok());
+        }
+    }
+
+    /// Records the result of job `seq` and delivers every result that is now in order. The
+    /// lock is held while delivering so two workers can't hand results on out of order.
+    fn finish(&self, seq: u64, result: Option<R>) {
+        let mut finished = self.finished.lock().unwrap();
+        finished.ready.insert(seq, result);
+        loop {
+            let next_seq = finished.next_seq;
+            let Some(result) = finished.ready.remove(&next_seq) else {
+                break;
+            };
+            finished.next_seq += 1;
+            if let Some(result) = result {
+                (self.deliver)(result);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    /// Numbers merged while their sum stays within 10
+    #[derive(Debug, PartialEq)]
+    struct Span(Vec<u64>);
+
+    impl Coalesce for Span {
+        fn coalesce(&mut self, next: Self) -> Result<(), Self> {
+            if self.0.iter().chain(&next.0).sum::<u64>() > 10 {
+                return Err(next);
+            }
+            self.0.extend(next.0);
+            Ok(())
+        }
+
+        fn duration_ms(&self) -> u64 {
+            self.0.iter().sum()
+        }
+    }
+
+    #[test]
+    fn test_results_delivered_in_submission_order() {
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+        let pool = ChunkWorkers::new(
+            4,
+            |span: Span| {
+                let n = span.0[0] - 1;
+                // later jobs finish first
+                thread::sleep(Duration::from_millis(40 - n * 5));
+                if n == 3 {
+                    panic!("worker failure");
+                }
+                n
+            },
+            move |n| tx.lock().unwrap().send(n).unwrap(),
+        );
+        for n in 0..8 {
+            // let a worker take each job so none are merged
+            while pool.backlog_ms() > 0 {
+                thread::sleep(Duration::from_millis(1));
+            }
+            pool.submit(Span(vec![n + 1]));
+        }
+
+        let delivered: Vec<u64> = (0..7)
+            .map(|_| rx.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect();
+        assert_eq!(delivered, vec![0, 1, 2, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn test_waiting_jobs_coalesce_when_behind() {
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let release_rx = Mutex::new(release_rx);
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+        let pool = ChunkWorkers::new(
+            1,
+            move |span: Span| {
+                release_rx.lock().unwrap().recv().unwrap();
+                span
+            },
+            move |span| tx.lock().unwrap().send(span).unwrap(),
+        );
+
+        // the first job occupies the only worker, the rest wait and are merged up to the cap
+        pool.submit(Span(vec![1]));
+        while pool.backlog_ms() > 0 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        for n in 2..=5 {
+            pool.submit(Span(vec![n]));
+        }
+        assert_eq!(pool.backlog_ms(), 14);
+
+        for _ in 0..3 {
+            release_tx.send(()).unwrap();
+        }
+        let delivered: Vec<Span> = (0..3)
+            .map(|_| rx.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect();
+        assert_eq!(
+            delivered,
+            vec![Span(vec![1]), Span(vec![2, 3, 4]), Span(vec![5])]
+        );
+    }
+}
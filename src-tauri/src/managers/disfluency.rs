@@ -0,0 +1,326 @@
+//! Disfluency removal that adapts to the speaker.
+//!
+//! Instead of a fixed filler list, each speaker has a model that counts how often a word (or
+//! two-word phrase like "you know") shows up in their raw transcriptions and how often the
+//! post-processing cleanup deleted it. Patterns the cleanup reliably deletes are removed from
+//! later transcriptions before they're delivered. A handful of universal hesitations start
+//! out as fillers and are unlearned if this speaker's cleanups keep them.
+
+use crate::fillers::{remove_fillers, remove_spans};
+use crate::settings::get_settings;
+use anyhow::Result;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+const MODELS_FILE: &str = "disfluency_models.json";
+
+/// Hesitations every speaker starts with, each counted as this many seen-and-removed
+/// occurrences so a few kept instances don't immediately outweigh them.
+const SEED_FILLERS: &[&str] = &["um", "umm", "uh", "uhm", "er", "erm", "ah", "hmm", "mm"];
+const SEED_WEIGHT: u32 = 4;
+
+/// Share of occurrences the cleanup must have deleted for a pattern to be removed.
+const REMOVAL_PROBABILITY: f32 = 0.6;
+/// Deletions needed before a learned pattern is trusted.
+const MIN_REMOVALS: u32 = 3;
+
+/// Alignments larger than this (raw words times cleaned words) are skipped.
+const MAX_ALIGNMENT_CELLS: usize = 4_000_000;
+
+#[derive(Default, Serialize, Deserialize)]
+struct SpeakerModel {
+    /// Occurrences in raw transcriptions of patterns the cleanup has deleted at least once
+    seen: HashMap<String, u32>,
+    /// Occurrences the cleanup deleted
+    removed: HashMap<String, u32>,
+    /// Transcriptions learned from
+    samples: u32,
+}
+
+impl SpeakerModel {
+    fn probability(&self, pattern: &str) -> Option<f32> {
+        let seed = if SEED_FILLERS.contains(&pattern) {
+            SEED_WEIGHT
+        } else {
+            0
+        };
+        let removed = self.removed.get(pattern).copied().unwrap_or(0) + seed;
+        if removed < MIN_REMOVALS {
+            return None;
+        }
+        let seen = self.seen.get(pattern).copied().unwrap_or(0) + seed;
+        Some(removed as f32 / (seen.max(removed) + 1) as f32)
+    }
+
+    fn is_filler(&self, pattern: &str) -> bool {
+        self.probability(pattern)
+            .is_some_and(|p| p >= REMOVAL_PROBABILITY)
+    }
+
+    /// Counts the words of `raw` that `cleaned` dropped without replacing them.
+    fn learn(&mut self, raw: &str, cleaned: &str) {
+        let raw: Vec<String> = raw.split_whitespace().map(normalize).collect();
+        let cleaned: Vec<String> = cleaned.split_whitespace().map(normalize).collect();
+        let Some(deleted) = deleted_words(&raw, &cleaned) else {
+            return;
+        };
+
+        let mut count = |pattern: String, was_deleted: bool| {
+            if was_deleted {
+                *self.removed.entry(pattern.clone()).or_insert(0) += 1;
+            }
+            if was_deleted
+                || self.removed.contains_key(&pattern)
+                || SEED_FILLERS.contains(&pattern.as_str())
+            {
+                *self.seen.entry(pattern).or_insert(0) += 1;
+            }
+        };
+
+        for i in 0..raw.len() {
+            if raw[i].is_empty() {
+                continue;
+            }
+            count(raw[i].clone(), deleted[i]);
+            if let Some(next) = raw.get(i + 1).filter(|w| !w.is_empty()) {
+                count(format!("{} {}", raw[i], next), deleted[i] && deleted[i + 1]);
+            }
+        }
+        self.samples += 1;
+    }
+
+    /// Removes the speaker's fillers from `text`, keeping sentence punctuation and
+    /// capitalization intact.
+    fn clean(&self, text: &str) -> String {
+        remove_spans(text, |words, i| {
+            let word = normalize(words[i]);
+            if word.is_empty() {
+                return (0, false);
+            }
+            let pair = words
+                .get(i + 1)
+                .map(|next| format!("{} {}", word, normalize(next)));
+
+            if pair.as_deref().is_some_and(|p| self.is_filler(p)) {
+                (2, false)
+            } else if self.is_filler(&word) {
+                (1, false)
+            } else {
+                (0, false)
+            }
+        })
+    }
+}
+
+/// A pattern the model currently removes.
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct FillerPattern {
+    pub pattern: String,
+    /// Share of occurrences the cleanup deleted
+    pub probability: f32,
+    pub removed: u32,
+    pub seen: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct DisfluencyModelSummary {
+    pub speaker: String,
+    /// Transcriptions the model learned from
+    pub samples: u32,
+    pub fillers: Vec<FillerPattern>,
+}
+
+pub struct DisfluencyManager {
+    path: PathBuf,
+    models: Mutex<HashMap<String, SpeakerModel>>,
+    app_handle: AppHandle,
+}
+
+impl DisfluencyManager {
+    pub fn new(app_handle: &AppHandle) -> Result<Self> {
+        let path = app_handle.path().app_data_dir()?.join(MODELS_FILE);
+        let models = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                warn!("Ignoring unreadable disfluency models: {}", e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+
+        Ok(Self {
+            path,
+            models: Mutex::new(models),
+            app_handle: app_handle.clone(),
+        })
+    }
+
+    /// Removes fillers from `text`: the rule-based ones of the transcription language, then
+    /// the current speaker's learned ones, each when enabled.
+    pub fn clean(&self, text: &str) -> String {
+        let settings = get_settings(&self.app_handle);
+        let mut cleaned = text.to_string();
+        if settings.filler_removal {
+            cleaned = remove_fillers(&cleaned, &settings.selected_language);
+        }
+        if settings.disfluency_removal {
+            let models = self.models.lock().unwrap();
+            cleaned = match models.get(&settings.disfluency_speaker) {
+                Some(model) => model.clean(&cleaned),
+                None => SpeakerModel::default().clean(&cleaned),
+            };
+        }
+        if cleaned != text {
+            debug!("Removed disfluencies: '{}' -> '{}'", text, cleaned);
+        }
+        cleaned
+    }
+
+    /// Updates the current speaker's model from a raw transcription and its post-processed
+    /// version. Only runs when the user opted in to learning.
+    pub fn learn(&self, raw: &str, cleaned: &str) {
+        let settings = get_settings(&self.app_handle);
+        if !settings.disfluency_learning {
+            return;
+        }
+
+        let mut models = self.models.lock().unwrap();
+        models
+            .entry(settings.disfluency_speaker)
+            .or_default()
+            .learn(raw, cleaned);
+        if let Err(e) = self.save(&models) {
+            warn!("Failed to save disfluency models: {}", e);
+        }
+    }
+
+    pub fn summary(&self, speaker: &str) -> DisfluencyModelSummary {
+        let models = self.models.lock().unwrap();
+        let empty = SpeakerModel::default();
+        let model = models.get(speaker).unwrap_or(&empty);
+
+        let mut fillers: Vec<FillerPattern> = model
+            .removed
+            .keys()
+            .map(String::as_str)
+            .chain(SEED_FILLERS.iter().copied())
+            .filter(|pattern| model.is_filler(pattern))
+            .map(|pattern| FillerPattern {
+                pattern: pattern.to_string(),
+                probability: model.probability(pattern).unwrap_or(0.0),
+                removed: model.removed.get(pattern).copied().unwrap_or(0),
+                seen: model.seen.get(pattern).copied().unwrap_or(0),
+            })
+            .collect();
+        fillers.sort_by(|a, b| b.probability.total_cmp(&a.probability));
+        fillers.dedup_by(|a, b| a.pattern == b.pattern);
+
+        DisfluencyModelSummary {
+            speaker: speaker.to_string(),
+            samples: model.samples,
+            fillers,
+        }
+    }
+
+    /// Forgets everything learned for `speaker`.
+    pub fn reset(&self, speaker: &str) -> Result<()> {
+        let mut models = self.models.lock().unwrap();
+        models.remove(speaker);
+        self.save(&models)?;
+        info!("Reset disfluency model for speaker '{}'", speaker);
+        Ok(())
+    }
+
+    fn save(&self, models: &HashMap<String, SpeakerModel>) -> Result<()> {
+        fs::write(&self.path, serde_json::to_string(models)?)?;
+        Ok(())
+    }
+}
+
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric() || *c == '\'')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Marks the words of `raw` that were dropped in `cleaned` with nothing put in their place.
+/// Words that were rewritten, e.g. "twenty five" into "25", don't count as dropped.
+fn deleted_words(raw: &[String], cleaned: &[String]) -> Option<Vec<bool>> {
+    let (n, m) = (raw.len(), cleaned.len());
+    if (n + 1) * (m + 1) > MAX_ALIGNMENT_CELLS {
+        return None;
+    }
+
+    // Longest common subsequence, filled from the end so it can be walked forwards
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if raw[i] == cleaned[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut deleted = vec![false; n];
+    let (mut i, mut j) = (0, 0);
+    // Start of the current run of unmatched words on each side
+    let (mut gap_i, mut gap_j) = (0, 0);
+    loop {
+        let at_end = i == n || j == m;
+        if at_end || raw[i] == cleaned[j] {
+            let gap_end = if at_end { n } else { i };
+            let cleaned_gap_end = if at_end { m } else { j };
+            // Everything until the end is unmatched once either side runs out
+            if cleaned_gap_end == gap_j {
+                deleted[gap_i..gap_end].fill(true);
+            }
+            if at_end {
+                break;
+            }
+            i += 1;
+            j += 1;
+            gap_i = i;
+            gap_j = j;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+
+    Some(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_learns_speaker_fillers() {
+        let mut model = SpeakerModel::default();
+        for _ in 0..4 {
+            model.learn(
+                "So basically we should, you know, ship it",
+                "So we should ship it.",
+            );
+        }
+        // a rewrite isn't a deletion
+        model.learn("I need twenty five", "I need 25.");
+
+        assert!(model.is_filler("basically"));
+        assert!(model.is_filler("you know"));
+        assert!(!model.is_filler("twenty"));
+        assert_eq!(
+            model.clean("Um, basically it works, you know."),
+            "It works."
+        );
+    }
+}
@@ -0,0 +1,596 @@
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::audio_toolkit::strip_overlap;
+use crate::managers::history::HistoryManager;
+use crate::managers::transcription::{
+    TranscriptSegment, TranscriptionManager, TranscriptionOutput,
+};
+use crate::transcription_error::TranscriptionError;
+use anyhow::Result;
+use chrono::Utc;
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+/// Files are transcribed in pieces of about this length so several files can share a batch.
+const SEGMENT_SECS: usize = 30;
+/// How far back from a segment boundary to look for a pause to cut at.
+const CUT_SEARCH_SECS: usize = 3;
+/// A tail shorter than this is kept with the previous segment instead of standing alone.
+const MIN_TAIL_SECS: usize = 5;
+const CUT_FRAME_SAMPLES: usize = WHISPER_SAMPLE_RATE as usize / 50;
+/// Each segment but the first starts this much before the cut, at a pause within it, so a
+/// word at the cut is heard whole by one of the two. The repeated words are dropped when
+/// the segments are merged.
+const OVERLAP_SECS: usize = 2;
+
+/// Largest batch handed to the transcription manager at once.
+const MAX_BATCH_SIZE: usize = 8;
+/// Segments one file may contribute to a single batch, so a long file can't crowd out the
+/// files queued behind it.
+const MAX_SEGMENTS_PER_FILE: usize = 2;
+
+/// Least time between two checkpoints of the same job.
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(15);
+const CHECKPOINT_EXTENSION: &str = "json";
+
+/// Progress of a file job, saved while it runs so a job interrupted by a crash or an error
+/// continues where it stopped when the same audio is submitted again. Sealed like the
+/// history when it is encrypted.
+#[derive(Serialize, Deserialize)]
+struct Checkpoint {
+    /// Identifies the audio, and names the checkpoint file
+    fingerprint: String,
+    /// Path of the transcribed file, `None` for audio uploaded through the API
+    source: Option<String>,
+    duration_secs: f32,
+    total_segments: usize,
+    /// Unix timestamp of the last save
+    updated_at: i64,
+    /// Transcribed segments by index, with their start time in seconds
+    completed: BTreeMap<usize, (f32, TranscriptionOutput)>,
+}
+
+/// A checkpoint left behind by a file job that didn't finish.
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct InterruptedFileJob {
+    pub id: String,
+    pub source: Option<String>,
+    pub duration_secs: f32,
+    pub completed_segments: usize,
+    pub total_segments: usize,
+    pub updated_at: i64,
+}
+
+struct FileJob {
+    id: u64,
+    /// Remaining pieces with their index and start time in seconds
+    segments: VecDeque<(usize, f32, Vec<f32>)>,
+    progress: Checkpoint,
+    last_saved: Instant,
+    reply: mpsc::Sender<Result<TranscriptionOutput, TranscriptionError>>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    jobs: VecDeque<FileJob>,
+    next_id: u64,
+}
+
+/// Transcribes whole audio files submitted from several callers at once. Files are split
+/// into segments that are scheduled round-robin, so a short file submitted behind a long one
+/// finishes early, and segments from different files are transcribed together in batches.
+pub struct FileJobQueue {
+    app_handle: AppHandle,
+    state: Arc<(Mutex<QueueState>, Condvar)>,
+    checkpoint_dir: PathBuf,
+}
+
+impl FileJobQueue {
+    pub fn new(
+        app_handle: &AppHandle,
+        transcription_manager: Arc<TranscriptionManager>,
+    ) -> Result<Self> {
+        let checkpoint_dir = app_handle.path().app_data_dir()?.join("file_jobs");
+        fs::create_dir_all(&checkpoint_dir)?;
+        let state = Arc::new((Mutex::new(QueueState::default()), Condvar::new()));
+
+        let worker_state = state.clone();
+        let worker_app = app_handle.clone();
+        let worker_dir = checkpoint_dir.clone();
+        thread::spawn(move || {
+            run_worker(&worker_app, worker_state, transcription_manager, worker_dir)
+        });
+
+        Ok(Self {
+            app_handle: app_handle.clone(),
+            state,
+            checkpoint_dir,
+        })
+    }
+
+    /// Queues 16 kHz mono `samples` and blocks until their transcription is done.
+    /// Segment timestamps are relative to the start of the whole file.
+    pub fn transcribe(&self, samples: Vec<f32>) -> Result<TranscriptionOutput, TranscriptionError> {
+        self.transcribe_from(samples, None)
+    }
+
+    /// Like `transcribe`, for audio read from the file at `source`. The path is kept in the
+    /// checkpoint so an interrupted job can be resumed with `resume`.
+    pub fn transcribe_file(
+        &self,
+        samples: Vec<f32>,
+        source: &str,
+    ) -> Result<TranscriptionOutput, TranscriptionError> {
+        self.transcribe_from(samples, Some(source.to_string()))
+    }
+
+    fn transcribe_from(
+        &self,
+        samples: Vec<f32>,
+        source: Option<String>,
+    ) -> Result<TranscriptionOutput, TranscriptionError> {
+        if samples.is_empty() {
+            return Ok(TranscriptionOutput {
+                text: String::new(),
+                segments: Vec::new(),
+            });
+        }
+
+        let (reply_tx, reply_rx) = mpsc::channel();
+        {
+            let (lock, condvar) = &*self.state;
+            let mut state = lock.lock().unwrap();
+            let id = state.next_id;
+            state.next_id += 1;
+            let ranges = overlapping(&samples, split_at_pauses(&samples));
+            let progress = self.load_progress(&samples, ranges.len(), source);
+            let segments: VecDeque<_> = ranges
+                .into_iter()
+                .enumerate()
+                .filter(|(index, _)| !progress.completed.contains_key(index))
+                .map(|(index, (start, end))| {
+                    (
+                        index,
+                        start as f32 / WHISPER_SAMPLE_RATE as f32,
+                        samples[start..end].to_vec(),
+                    )
+                })
+                .collect();
+            debug!("Queued file job {} with {} segments", id, segments.len());
+            state.jobs.push_back(FileJob {
+                id,
+                segments,
+                progress,
+                last_saved: Instant::now(),
+                reply: reply_tx,
+            });
+            condvar.notify_one();
+        }
+
+        reply_rx
+            .recv()
+            .map_err(|_| TranscriptionError::engine("File transcription worker stopped"))?
+    }
+
+    /// The checkpoint of earlier work on `samples`, or a fresh one. A checkpoint written for
+    /// a different split of the audio can't be matched up and is started over.
+    fn load_progress(
+        &self,
+        samples: &[f32],
+        total_segments: usize,
+        source: Option<String>,
+    ) -> Checkpoint {
+        let fingerprint = fingerprint(samples);
+        let path = checkpoint_path(&self.checkpoint_dir, &fingerprint);
+        match read_checkpoint(&self.app_handle, &path) {
+            Ok(checkpoint) if checkpoint.total_segments == total_segments => {
+                info!(
+                    "Resuming file job from checkpoint, {} of {} segments done",
+                    checkpoint.completed.len(),
+                    total_segments
+                );
+                Checkpoint {
+                    source: source.or(checkpoint.source),
+                    ..checkpoint
+                }
+            }
+            _ => Checkpoint {
+                fingerprint,
+                source,
+                duration_secs: samples.len() as f32 / WHISPER_SAMPLE_RATE as f32,
+                total_segments,
+                updated_at: Utc::now().timestamp(),
+                completed: BTreeMap::new(),
+            },
+        }
+    }
+
+    /// Jobs that stopped before finishing, most recently saved first. Jobs still running
+    /// aren't listed.
+    pub fn interrupted_jobs(&self) -> Result<Vec<InterruptedFileJob>> {
+        let running: Vec<String> = {
+            let state = self.state.0.lock().unwrap();
+            state
+                .jobs
+                .iter()
+                .map(|job| job.progress.fingerprint.clone())
+                .collect()
+        };
+
+        let mut jobs = Vec::new();
+        for entry in fs::read_dir(&self.checkpoint_dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(CHECKPOINT_EXTENSION) {
+                continue;
+            }
+            let checkpoint = match read_checkpoint(&self.app_handle, &path) {
+                Ok(checkpoint) => checkpoint,
+                Err(e) => {
+                    warn!("Skipping unreadable file job checkpoint {:?}: {}", path, e);
+                    continue;
+                }
+            };
+            if running.contains(&checkpoint.fingerprint) {
+                continue;
+            }
+            jobs.push(InterruptedFileJob {
+                id: checkpoint.fingerprint,
+                source: checkpoint.source,
+                duration_secs: checkpoint.duration_secs,
+                completed_segments: checkpoint.completed.len(),
+                total_segments: checkpoint.total_segments,
+                updated_at: checkpoint.updated_at,
+            });
+        }
+        jobs.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        Ok(jobs)
+    }
+
+    /// Path of the file an interrupted job was transcribing, `None` for uploaded audio.
+    pub fn interrupted_source(&self, id: &str) -> Result<Option<String>> {
+        let path = checkpoint_path(&self.checkpoint_dir, id);
+        Ok(read_checkpoint(&self.app_handle, &path)?.source)
+    }
+
+    /// Deletes the checkpoint of an interrupted job, its next run starts over.
+    pub fn discard_checkpoint(&self, id: &str) -> Result<()> {
+        fs::remove_file(checkpoint_path(&self.checkpoint_dir, id))?;
+        Ok(())
+    }
+}
+
+fn checkpoint_path(dir: &Path, fingerprint: &str) -> PathBuf {
+    // Ids come from the frontend, keep them inside the checkpoint directory
+    let name: String = fingerprint
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect();
+    dir.join(format!("{}.{}", name, CHECKPOINT_EXTENSION))
+}
+
+fn read_checkpoint(app: &AppHandle, path: &Path) -> Result<Checkpoint> {
+    let history = app.state::<Arc<HistoryManager>>();
+    Ok(serde_json::from_slice(
+        &history.open_bytes(fs::read(path)?)?,
+    )?)
+}
+
+/// Writes through a temporary file so a crash mid-write leaves the previous checkpoint.
+fn write_checkpoint(app: &AppHandle, dir: &Path, checkpoint: &Checkpoint) -> Result<()> {
+    let path = checkpoint_path(dir, &checkpoint.fingerprint);
+    let tmp = path.with_extension("tmp");
+    let history = app.state::<Arc<HistoryManager>>();
+    fs::write(&tmp, history.seal_bytes(serde_json::to_vec(checkpoint)?)?)?;
+    fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+fn save_checkpoint(app: &AppHandle, dir: &Path, job: &mut FileJob) {
+    job.progress.updated_at = Utc::now().timestamp();
+    job.last_saved = Instant::now();
+    match write_checkpoint(app, dir, &job.progress) {
+        Ok(()) => debug!(
+            "Checkpointed file job {} at {} of {} segments",
+            job.id,
+            job.progress.completed.len(),
+            job.progress.total_segments
+        ),
+        Err(e) => warn!("Failed to checkpoint file job {}: {}", job.id, e),
+    }
+}
+
+/// FNV-1a over the samples, stable across runs and builds unlike the std hasher.
+fn fingerprint(samples: &[f32]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in (samples.len() as u64)
+        .to_le_bytes()
+        .into_iter()
+        .chain(samples.iter().flat_map(|s| s.to_bits().to_le_bytes()))
+    {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+fn run_worker(
+    app: &AppHandle,
+    state: Arc<(Mutex<QueueState>, Condvar)>,
+    tm: Arc<TranscriptionManager>,
+    checkpoint_dir: PathBuf,
+) {
+    let (lock, condvar) = &*state;
+    loop {
+        let batch = {
+            let mut state = lock.lock().unwrap();
+            while state.jobs.is_empty() {
+                state = condvar.wait(state).unwrap();
+            }
+            take_batch(&mut state.jobs, MAX_BATCH_SIZE)
+        };
+
+        let (owners, clips): (Vec<_>, Vec<_>) = batch
+            .into_iter()
+            .map(|(id, index, offset, clip)| ((id, index, offset), clip))
+            .unzip();
+        debug!("Transcribing a batch of {} file segments", clips.len());
+        let results = tm.transcribe_batch(clips);
+
+        let mut state = lock.lock().unwrap();
+        for ((id, segment, offset), result) in owners.into_iter().zip(results) {
+            let Some(index) = state.jobs.iter().position(|job| job.id == id) else {
+                continue; // an earlier segment already failed the job
+            };
+            match result {
+                Ok(output) => {
+                    let job = &mut state.jobs[index];
+                    job.progress.completed.insert(segment, (offset, output));
+                    if job.last_saved.elapsed() >= CHECKPOINT_INTERVAL {
+                        save_checkpoint(app, &checkpoint_dir, job);
+                    }
+                }
+                Err(e) => {
+                    error!("File job {} failed: {}", id, e);
+                    // Keep what was done so resubmitting the audio continues from here
+                    let mut job = state.jobs.remove(index).unwrap();
+                    if !job.progress.completed.is_empty() {
+                        save_checkpoint(app, &checkpoint_dir, &mut job);
+                    }
+                    let _ = job.reply.send(Err(e));
+                }
+            }
+        }
+
+        // Every segment handed out has come back, so a job with none left is complete
+        let (finished, pending): (VecDeque<_>, VecDeque<_>) = std::mem::take(&mut state.jobs)
+            .into_iter()
+            .partition(|job| job.segments.is_empty());
+        state.jobs = pending;
+        drop(state);
+
+        for job in finished {
+            debug!("File job {} finished", job.id);
+            let path = checkpoint_path(&checkpoint_dir, &job.progress.fingerprint);
+            if path.exists() {
+                if let Err(e) = fs::remove_file(&path) {
+                    warn!("Failed to remove checkpoint of file job {}: {}", job.id, e);
+                }
+            }
+            let _ = job.reply.send(Ok(merge_outputs(job.progress.completed)));
+        }
+    }
+}
+
+/// Takes up to `batch_size` segments, at most `MAX_SEGMENTS_PER_FILE` from each file, and
+/// rotates the queue so the next batch starts with the next file.
+fn take_batch(jobs: &mut VecDeque<FileJob>, batch_size: usize) -> Vec<(u64, usize, f32, Vec<f32>)> {
+    let mut batch = Vec::new();
+    for job in jobs.iter_mut() {
+        for _ in 0..MAX_SEGMENTS_PER_FILE {
+            if batch.len() == batch_size {
+                break;
+            }
+            match job.segments.pop_front() {
+                Some((index, offset, clip)) => batch.push((job.id, index, offset, clip)),
+                None => break,
+            }
+        }
+        if batch.len() == batch_size {
+            break;
+        }
+    }
+    jobs.rotate_left(1.min(jobs.len()));
+    batch
+}
+
+/// Joins the outputs of a file's segments, dropping the words each one repeats from the end
+/// of the one before.
+fn merge_outputs(outputs: BTreeMap<usize, (f32, TranscriptionOutput)>) -> TranscriptionOutput {
+    let mut text = String::new();
+    let mut segments = Vec::new();
+    for (offset, output) in outputs.into_values() {
+        let kept = strip_overlap(&text, &output.text);
+        let repeated = output.text.split_whitespace().count() - kept.split_whitespace().count();
+        if !kept.is_empty() {
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            text.push_str(&kept);
+        }
+        segments.extend(
+            drop_words(output.segments, repeated)
+                .into_iter()
+                .map(|segment| TranscriptSegment {
+                    start: segment.start + offset,
+                    end: segment.end + offset,
+                    ..segment
+                }),
+        );
+    }
+    TranscriptionOutput { text, segments }
+}
+
+/// Removes the first `count` words from the segments, dropping the segments left empty.
+fn drop_words(segments: Vec<TranscriptSegment>, mut count: usize) -> Vec<TranscriptSegment> {
+    let mut kept = Vec::with_capacity(segments.len());
+    for mut segment in segments {
+        if count > 0 {
+            let words: Vec<&str> = segment.text.split_whitespace().collect();
+            let dropped = count.min(words.len());
+            count -= dropped;
+            if dropped == words.len() {
+                continue;
+            }
+            segment.text = words[dropped..].join(" ");
+            segment.words.drain(..dropped.min(segment.words.len()));
+        }
+        kept.push(segment);
+    }
+    kept
+}
+
+/// Moves the start of every range but the first back by up to `OVERLAP_SECS`, to the
+/// quietest moment in that stretch so the overlap doesn't begin mid-word.
+fn overlapping(samples: &[f32], ranges: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    let overlap = OVERLAP_SECS * WHISPER_SAMPLE_RATE as usize;
+    ranges
+        .into_iter()
+        .enumerate()
+        .map(|(index, (start, end))| {
+            if index == 0 || start < overlap {
+                return (start, end);
+            }
+            let search_start = start - overlap;
+            let search_end = start - CUT_FRAME_SAMPLES;
+            (quietest_point(samples, search_start, search_end), end)
+        })
+        .collect()
+}
+
+/// The middle of the quietest `CUT_FRAME_SAMPLES` frame between `from` and `to`, or `to`
+/// when the stretch holds no frame.
+fn quietest_point(samples: &[f32], from: usize, to: usize) -> usize {
+    samples[from..to]
+        .chunks(CUT_FRAME_SAMPLES)
+        .enumerate()
+        .map(|(i, frame)| (i, frame.iter().map(|s| s * s).sum::<f32>()))
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| from + i * CUT_FRAME_SAMPLES + CUT_FRAME_SAMPLES / 2)
+        .unwrap_or(to)
+}
+
+/// Splits 16 kHz audio into `(start, end)` sample ranges of about `SEGMENT_SECS`, cutting
+/// at the quietest moment shortly before each boundary so words aren't split in half.
+fn split_at_pauses(samples: &[f32]) -> Vec<(usize, usize)> {
+    let rate = WHISPER_SAMPLE_RATE as usize;
+    let segment_len = SEGMENT_SECS * rate;
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while samples.len() - start > segment_len + MIN_TAIL_SECS * rate {
+        let search_start = start + segment_len - CUT_SEARCH_SECS * rate;
+        let cut = quietest_point(samples, search_start, start + segment_len);
+        ranges.push((start, cut));
+        start = cut;
+    }
+    ranges.push((start, samples.len()));
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_at_pauses_cuts_in_silence() {
+        let rate = WHISPER_SAMPLE_RATE as usize;
+        let mut samples = vec![0.5f32; 70 * rate];
+        // a pause a little before the first 30 second boundary
+        samples[28 * rate..28 * rate + rate / 10].fill(0.0);
+
+        let ranges = split_at_pauses(&samples);
+        assert_eq!(ranges.len(), 3);
+        assert!(ranges[0].1 > 28 * rate && ranges[0].1 < 28 * rate + rate / 10);
+        assert_eq!(ranges[1].0, ranges[0].1);
+        assert_eq!(ranges[2].1, samples.len());
+
+        assert_eq!(split_at_pauses(&samples[..10 * rate]), vec![(0, 10 * rate)]);
+    }
+
+    #[test]
+    fn test_overlapping_starts_at_a_pause_before_the_cut() {
+        let rate = WHISPER_SAMPLE_RATE as usize;
+        let mut samples = vec![0.5f32; 70 * rate];
+        samples[28 * rate..28 * rate + rate / 10].fill(0.0);
+        // and another within the overlap before it, but outside the cut search
+        let pause = 26 * rate + rate / 2;
+        samples[pause..pause + rate / 10].fill(0.0);
+
+        let ranges = split_at_pauses(&samples);
+        let overlapped = overlapping(&samples, ranges.clone());
+        assert_eq!(overlapped[0], ranges[0]);
+        assert!(overlapped[1].0 > pause && overlapped[1].0 < pause + rate / 10);
+        assert_eq!(overlapped[1].1, ranges[1].1);
+    }
+
+    #[test]
+    fn test_merge_outputs_drops_repeated_words() {
+        let segment = |start: f32, end: f32, text: &str| TranscriptSegment {
+            start,
+            end,
+            text: text.to_string(),
+            confidence: None,
+            words: Vec::new(),
+        };
+        let mut outputs = BTreeMap::new();
+        outputs.insert(
+            0,
+            (
+                0.0,
+                TranscriptionOutput {
+                    text: "we should meet on Tuesday".to_string(),
+                    segments: vec![segment(0.0, 28.0, "we should meet on Tuesday")],
+                },
+            ),
+        );
+        outputs.insert(
+            1,
+            (
+                27.0,
+                TranscriptionOutput {
+                    text: "on tuesday, at noon".to_string(),
+                    segments: vec![
+                        segment(0.0, 1.0, "on tuesday,"),
+                        segment(1.0, 2.5, "at noon"),
+                    ],
+                },
+            ),
+        );
+
+        let merged = merge_outputs(outputs);
+        assert_eq!(merged.text, "we should meet on Tuesday at noon");
+        assert_eq!(merged.segments.len(), 2);
+        assert_eq!(merged.segments[1].text, "at noon");
+        assert_eq!(merged.segments[1].start, 28.0);
+    }
+
+    #[test]
+    fn test_fingerprint_identifies_audio() {
+        let samples = vec![0.25f32; 1600];
+        assert_eq!(fingerprint(&samples), fingerprint(&[0.25f32; 1600]));
+        assert_ne!(fingerprint(&samples), fingerprint(&samples[..1599]));
+
+        let mut changed = samples.clone();
+        changed[800] = -0.25;
+        assert_ne!(fingerprint(&samples), fingerprint(&changed));
+        assert_eq!(fingerprint(&samples).len(), 16);
+    }
+}
@@ -0,0 +1,297 @@
+//! Parakeet models found on the Hugging Face Hub. Repos tagged `onnx` are searched, and
+//! those with the files of an istupakov-style Parakeet ONNX export can be added to the
+//! catalog and downloaded file by file like the built-in Parakeet models. Gated repos need
+//! a Hub access token, which is stored with the API keys and only ever sent to the Hub.
+
+use crate::managers::model::{EngineType, ModelInfo, Quantization};
+use crate::secrets;
+use crate::settings::HubModelConfig;
+use anyhow::Result;
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use specta::Type;
+use std::collections::HashMap;
+
+pub const HUB_URL: &str = "https://huggingface.co";
+
+/// The token's id among the stored API keys
+pub const TOKEN_ID: &str = "huggingface";
+
+/// Repos a search returns at most
+const SEARCH_LIMIT: &str = "30";
+
+/// Files of the int8 and fp32 exports; the fp32 encoder may keep its weights in a
+/// separate `encoder-model.onnx.data`.
+const INT8_FILES: &[&str] = &[
+    "encoder-model.int8.onnx",
+    "decoder_joint-model.int8.onnx",
+    "nemo128.onnx",
+    "vocab.txt",
+];
+const FP32_FILES: &[&str] = &[
+    "encoder-model.onnx",
+    "decoder_joint-model.onnx",
+    "nemo128.onnx",
+    "vocab.txt",
+];
+const OPTIONAL_FILES: &[&str] = &["config.json"];
+
+/// A compatible repo, as the browser lists it
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct HubModel {
+    pub repo_id: String,
+    pub downloads: u64,
+    pub likes: u64,
+    pub license: Option<String>,
+    pub languages: Vec<String>,
+    /// Needs an access token with the repo's terms accepted
+    pub gated: bool,
+    /// Size of the files that would be downloaded, when the Hub reported file sizes
+    pub size_mb: Option<u64>,
+    pub quantization: Quantization,
+    pub files: Vec<String>,
+    /// SHA-256 of the files in Git LFS
+    pub sha256: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct ApiModel {
+    id: String,
+    #[serde(default)]
+    downloads: u64,
+    #[serde(default)]
+    likes: u64,
+    #[serde(default)]
+    tags: Vec<String>,
+    /// `false`, or how access is granted (`"auto"`, `"manual"`)
+    #[serde(default)]
+    gated: Value,
+    #[serde(default)]
+    siblings: Vec<ApiFile>,
+}
+
+#[derive(Deserialize)]
+struct ApiFile {
+    rfilename: String,
+    size: Option<u64>,
+    /// Set for files in Git LFS, which the model weights are
+    lfs: Option<ApiLfs>,
+}
+
+#[derive(Deserialize)]
+struct ApiLfs {
+    sha256: String,
+}
+
+/// The files to download for a Parakeet export among a repo's `files`, preferring int8,
+/// or `None` when the repo isn't one.
+pub fn parakeet_files(files: &[&str]) -> Option<(Quantization, Vec<String>)> {
+    let (quantization, required) = if INT8_FILES.iter().all(|file| files.contains(file)) {
+        (Quantization::Int8, INT8_FILES)
+    } else if FP32_FILES.iter().all(|file| files.contains(file)) {
+        (Quantization::Fp32, FP32_FILES)
+    } else {
+        return None;
+    };
+    let mut download: Vec<String> = required.iter().map(|file| file.to_string()).collect();
+    if quantization == Quantization::Fp32 && files.contains(&"encoder-model.onnx.data") {
+        download.push("encoder-model.onnx.data".to_string());
+    }
+    download.extend(
+        OPTIONAL_FILES
+            .iter()
+            .filter(|file| files.contains(file))
+            .map(|file| file.to_string()),
+    );
+    Some((quantization, download))
+}
+
+/// The browser's view of a repo, `None` when it isn't a compatible export.
+fn hub_model(api: ApiModel) -> Option<HubModel> {
+    let names: Vec<&str> = api
+        .siblings
+        .iter()
+        .map(|file| file.rfilename.as_str())
+        .collect();
+    let (quantization, files) = parakeet_files(&names)?;
+    let sizes: Option<Vec<u64>> = files
+        .iter()
+        .map(|name| {
+            api.siblings
+                .iter()
+                .find(|file| &file.rfilename == name)
+                .and_then(|file| file.size)
+        })
+        .collect();
+    let sha256 = api
+        .siblings
+        .iter()
+        .filter(|file| files.contains(&file.rfilename))
+        .filter_map(|file| Some((file.rfilename.clone(), file.lfs.as_ref()?.sha256.clone())))
+        .collect();
+    Some(HubModel {
+        license: api
+            .tags
+            .iter()
+            .find_map(|tag| tag.strip_prefix("license:"))
+            .map(str::to_string),
+        // Languages are tagged by their bare ISO 639-1 code
+        languages: api
+            .tags
+            .iter()
+            .filter(|tag| tag.len() == 2 && tag.chars().all(|c| c.is_ascii_lowercase()))
+            .cloned()
+            .collect(),
+        gated: !matches!(api.gated, Value::Bool(false) | Value::Null),
+        size_mb: sizes.map(|sizes| sizes.iter().sum::<u64>() / (1024 * 1024)),
+        repo_id: api.id,
+        downloads: api.downloads,
+        likes: api.likes,
+        quantization,
+        files,
+        sha256,
+    })
+}
+
+fn get(client: &reqwest::Client, url: &str) -> reqwest::RequestBuilder {
+    let request = client.get(url);
+    match secrets::get_api_key(TOKEN_ID) {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    }
+}
+
+/// Searches the Hub's ONNX repos for `query`, returning the compatible ones with their
+/// download size, most downloaded first.
+pub async fn search(client: &reqwest::Client, query: &str) -> Result<Vec<HubModel>> {
+    let url = format!("{}/api/models", HUB_URL);
+    let results: Vec<ApiModel> = get(client, &url)
+        .query(&[
+            ("search", query),
+            ("filter", "onnx"),
+            ("sort", "downloads"),
+            ("direction", "-1"),
+            ("full", "true"),
+            ("limit", SEARCH_LIMIT),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    // The search lists file names only, the sizes take a request per repo
+    let compatible: Vec<String> = results
+        .into_iter()
+        .filter_map(hub_model)
+        .map(|model| model.repo_id)
+        .collect();
+    let details = join_all(compatible.iter().map(|repo| model(client, repo))).await;
+    Ok(details.into_iter().filter_map(Result::ok).collect())
+}
+
+/// A repo's metadata and file sizes, failing when it isn't a compatible export.
+pub async fn model(client: &reqwest::Client, repo_id: &str) -> Result<HubModel> {
+    let url = format!("{}/api/models/{}?blobs=true", HUB_URL, repo_id);
+    let api: ApiModel = get(client, &url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    hub_model(api).ok_or_else(|| {
+        anyhow::anyhow!(
+            "{} doesn't have the files of a Parakeet ONNX export",
+            repo_id
+        )
+    })
+}
+
+/// The catalog entry of an added repo. Its files go in a directory named after the repo.
+pub fn model_info(config: &HubModelConfig) -> ModelInfo {
+    ModelInfo {
+        id: format!("hf:{}", config.repo_id),
+        name: config.repo_id.clone(),
+        description: "From the Hugging Face Hub.".to_string(),
+        filename: format!("hf--{}", config.repo_id.replace('/', "--")),
+        url: Some(format!("{}/{}/resolve/main", HUB_URL, config.repo_id)),
+        size_mb: config.size_mb,
+        is_downloaded: false,
+        is_downloading: false,
+        partial_size: 0,
+        is_directory: true,
+        engine_type: EngineType::Parakeet,
+        // Not benchmarked
+        accuracy_score: 0.0,
+        speed_score: 0.0,
+        quantization: config.quantization,
+        ram_mb: config.size_mb * 2,
+        sha256: config.sha256.clone(),
+        download_files: config.files.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parakeet_files_prefers_int8() {
+        let (quantization, files) = parakeet_files(&[
+            "encoder-model.onnx",
+            "encoder-model.onnx.data",
+            "encoder-model.int8.onnx",
+            "decoder_joint-model.onnx",
+            "decoder_joint-model.int8.onnx",
+            "nemo128.onnx",
+            "vocab.txt",
+            "config.json",
+            "README.md",
+        ])
+        .unwrap();
+        assert_eq!(quantization, Quantization::Int8);
+        assert_eq!(files.len(), 5);
+        assert!(files.contains(&"config.json".to_string()));
+
+        let (quantization, files) = parakeet_files(&[
+            "encoder-model.onnx",
+            "encoder-model.onnx.data",
+            "decoder_joint-model.onnx",
+            "nemo128.onnx",
+            "vocab.txt",
+        ])
+        .unwrap();
+        assert_eq!(quantization, Quantization::Fp32);
+        assert!(files.contains(&"encoder-model.onnx.data".to_string()));
+
+        assert!(parakeet_files(&["model.onnx", "tokenizer.json"]).is_none());
+    }
+
+    #[test]
+    fn test_hub_model_reads_metadata() {
+        let api: ApiModel = serde_json::from_value(serde_json::json!({
+            "id": "someone/parakeet-onnx",
+            "downloads": 1200,
+            "tags": ["onnx", "en", "de", "license:cc-by-4.0", "asr"],
+            "gated": "auto",
+            "siblings": [
+                {
+                    "rfilename": "encoder-model.int8.onnx",
+                    "size": 600 * 1024 * 1024,
+                    "lfs": { "sha256": "ab12" }
+                },
+                { "rfilename": "decoder_joint-model.int8.onnx", "size": 18 * 1024 * 1024 },
+                { "rfilename": "nemo128.onnx", "size": 1024 * 1024 },
+                { "rfilename": "vocab.txt", "size": 1024 * 1024 }
+            ]
+        }))
+        .unwrap();
+        let model = hub_model(api).unwrap();
+        assert_eq!(model.license.as_deref(), Some("cc-by-4.0"));
+        assert_eq!(model.languages, ["en", "de"]);
+        assert!(model.gated);
+        assert_eq!(model.size_mb, Some(620));
+        assert_eq!(model.sha256.len(), 1);
+        assert_eq!(model.sha256["encoder-model.int8.onnx"], "ab12");
+    }
+}
@@ -1,15 +1,52 @@
-use anyhow::Result;
-use chrono::{DateTime, Local, Utc};
-use log::{debug, error};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Local, NaiveDate, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use log::{debug, error, info};
 use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use specta::Type;
-use std::fs;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_sql::{Migration, MigrationKind};
 
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
 use crate::audio_toolkit::save_wav_file;
+use crate::history_crypto::{self, HistoryCipher};
+use crate::managers::transcription::TranscriptSegment;
+use crate::refinement::RefinementChange;
+use crate::session_naming::{normalize_tags, SessionNaming};
+
+/// Longest range the dictation statistics cover.
+const MAX_STATS_DAYS: u32 = 366;
+/// How often the background maintenance task re-applies the retention settings.
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Snippets live in this subdirectory of the recordings directory.
+const SNIPPETS_DIR: &str = "snippets";
+/// Audio kept on each side of a segment so its first and last words aren't clipped.
+const SNIPPET_PADDING_SECS: f32 = 0.25;
+/// Longer segments get no snippet, the full recording already covers them.
+const MAX_SNIPPET_SECS: f32 = 30.0;
+/// Columns holding dictated text, sealed when the history is encrypted. Tags stay readable
+/// so entries can be filtered by them.
+const ENCRYPTED_COLUMNS: &[(&str, &str)] = &[
+    ("transcription_history", "title"),
+    ("transcription_history", "transcription_text"),
+    ("transcription_history", "post_processed_text"),
+    ("transcription_history", "post_process_prompt"),
+    ("history_snippets", "text"),
+    ("transcription_versions", "transcription_text"),
+    ("refinement_changes", "live_text"),
+    ("refinement_changes", "refined_text"),
+];
 
 #[derive(Clone, Debug, Serialize, Deserialize, Type)]
 pub struct HistoryEntry {
@@ -21,12 +58,198 @@ pub struct HistoryEntry {
     pub transcription_text: String,
     pub post_processed_text: Option<String>,
     pub post_process_prompt: Option<String>,
+    /// Whether the session audio is still on disk
+    #[serde(default)]
+    pub audio_available: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// The audio behind one segment of a history entry's transcription.
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+pub struct HistorySnippet {
+    pub id: i64,
+    pub history_id: i64,
+    pub segment_index: i64,
+    pub start_secs: f64,
+    pub end_secs: f64,
+    pub text: String,
+    /// Relative to the recordings directory, resolve it with `get_audio_file_path`
+    pub file_name: String,
+}
+
+/// One transcription of a history entry's audio. The first version is the original
+/// transcription, which has no recorded `model_id`.
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+pub struct TranscriptionVersion {
+    pub id: i64,
+    pub history_id: i64,
+    pub model_id: Option<String>,
+    pub transcription_text: String,
+    pub created_at: i64,
+}
+
+/// A tag in use and how many entries carry it.
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+pub struct HistoryTag {
+    pub tag: String,
+    pub entry_count: i64,
+}
+
+/// Measurements of one dictation, kept for the statistics. They hold no text and outlive
+/// the history entry they were recorded with.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Type)]
+pub struct SessionMetrics {
+    pub audio_secs: f64,
+    /// Local model or cloud provider that transcribed the session
+    pub model_id: Option<String>,
+    /// From the end of the recording until the text was ready
+    pub processing_ms: i64,
+    pub average_confidence: Option<f64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsPeriod {
+    Day,
+    /// Weeks starting on Monday
+    Week,
+}
+
+/// Dictation statistics of a day, a week, a model or everything.
+#[derive(Clone, Debug, Default, Serialize, Type)]
+pub struct StatsSummary {
+    /// First day of the period as YYYY-MM-DD, or the model id
+    pub label: String,
+    pub sessions: i64,
+    pub audio_secs: f64,
+    pub words: i64,
+    pub characters: i64,
+    /// Words produced per minute of audio
+    pub words_per_minute: f64,
+    pub average_latency_ms: f64,
+    /// Mean decoder confidence of the sessions that reported one, a proxy for accuracy. Only
+    /// cloud providers report confidence, so `None` when every session was transcribed locally
+    pub average_confidence: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct DictationStats {
+    /// Every period of the range, oldest first, including ones without sessions
+    pub periods: Vec<StatsSummary>,
+    /// Most used first
+    pub models: Vec<StatsSummary>,
+    pub total: StatsSummary,
+}
+
+/// A row of the `session_metrics` table.
+struct MetricsRow {
+    timestamp: i64,
+    audio_secs: f64,
+    char_count: i64,
+    word_count: i64,
+    model_id: Option<String>,
+    processing_ms: i64,
+    average_confidence: Option<f64>,
+}
+
+fn summarize<'a>(label: String, rows: impl IntoIterator<Item = &'a MetricsRow>) -> StatsSummary {
+    let mut summary = StatsSummary {
+        label,
+        ..Default::default()
+    };
+    let mut latency_ms = 0i64;
+    let mut confidences = Vec::new();
+    for row in rows {
+        summary.sessions += 1;
+        summary.audio_secs += row.audio_secs;
+        summary.words += row.word_count;
+        summary.characters += row.char_count;
+        latency_ms += row.processing_ms;
+        confidences.extend(row.average_confidence);
+    }
+
+    if summary.audio_secs > 0.0 {
+        summary.words_per_minute = summary.words as f64 / (summary.audio_secs / 60.0);
+    }
+    if summary.sessions > 0 {
+        summary.average_latency_ms = latency_ms as f64 / summary.sessions as f64;
+    }
+    if !confidences.is_empty() {
+        summary.average_confidence =
+            Some(confidences.iter().sum::<f64>() / confidences.len() as f64);
+    }
+    summary
+}
+
+/// Local date the period containing `timestamp` starts on.
+fn period_start(timestamp: i64, period: StatsPeriod) -> NaiveDate {
+    let date = DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .with_timezone(&Local)
+        .date_naive();
+    match period {
+        StatsPeriod::Day => date,
+        StatsPeriod::Week => {
+            date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64)
+        }
+    }
+}
+
+fn aggregate_stats(rows: &[MetricsRow], period: StatsPeriod, from: i64, to: i64) -> DictationStats {
+    let mut by_period: BTreeMap<NaiveDate, Vec<&MetricsRow>> = BTreeMap::new();
+    let mut start = period_start(from, period);
+    let end = period_start(to, period);
+    let step = match period {
+        StatsPeriod::Day => 1,
+        StatsPeriod::Week => 7,
+    };
+    while start <= end {
+        by_period.insert(start, Vec::new());
+        start += chrono::Duration::days(step);
+    }
+
+    let mut by_model: HashMap<String, Vec<&MetricsRow>> = HashMap::new();
+    for row in rows {
+        by_period
+            .entry(period_start(row.timestamp, period))
+            .or_default()
+            .push(row);
+        by_model
+            .entry(
+                row.model_id
+                    .clone()
+                    .unwrap_or_else(|| "unknown".to_string()),
+            )
+            .or_default()
+            .push(row);
+    }
+
+    let mut models: Vec<StatsSummary> = by_model
+        .into_iter()
+        .map(|(model, rows)| summarize(model, rows))
+        .collect();
+    models.sort_by(|a, b| b.sessions.cmp(&a.sessions).then(a.label.cmp(&b.label)));
+
+    DictationStats {
+        periods: by_period
+            .into_iter()
+            .map(|(start, rows)| summarize(start.format("%Y-%m-%d").to_string(), rows))
+            .collect(),
+        models,
+        total: summarize("total".to_string(), rows),
+    }
 }
 
 pub struct HistoryManager {
     app_handle: AppHandle,
     recordings_dir: PathBuf,
+    archive_dir: PathBuf,
     db_path: PathBuf,
+    /// Loaded the first time encrypted history is read or written
+    cipher: Mutex<Option<Arc<HistoryCipher>>>,
+    /// Nothing is saved while set, it lasts until turned off or the app quits
+    incognito: AtomicBool,
 }
 
 impl HistoryManager {
@@ -34,7 +257,13 @@ impl HistoryManager {
         // Create recordings directory in app data dir
         let app_data_dir = app_handle.path().app_data_dir()?;
         let recordings_dir = app_data_dir.join("recordings");
+        let archive_dir = app_data_dir.join("archive");
         let db_path = app_data_dir.join("history.db");
+        // Earlier versions decrypted recordings into the cache for playback
+        let decrypted_dir = app_handle.path().app_cache_dir()?.join("decrypted-audio");
+        if decrypted_dir.exists() {
+            let _ = fs::remove_dir_all(&decrypted_dir);
+        }
 
         // Ensure recordings directory exists
         if !recordings_dir.exists() {
@@ -45,7 +274,10 @@ impl HistoryManager {
         let manager = Self {
             app_handle: app_handle.clone(),
             recordings_dir,
+            archive_dir,
             db_path,
+            cipher: Mutex::new(None),
+            incognito: AtomicBool::new(false),
         };
 
         // Initialize database
@@ -54,6 +286,23 @@ impl HistoryManager {
         Ok(manager)
     }
 
+    /// Starts a background thread that periodically enforces the retention settings, so old
+    /// entries and audio are removed even when no new transcriptions are being saved.
+    pub fn start_maintenance(manager: &Arc<Self>) {
+        let weak = Arc::downgrade(manager);
+        thread::spawn(move || loop {
+            match weak.upgrade() {
+                Some(manager) => {
+                    if let Err(e) = manager.run_maintenance() {
+                        error!("History maintenance failed: {}", e);
+                    }
+                }
+                None => break,
+            }
+            thread::sleep(MAINTENANCE_INTERVAL);
+        });
+    }
+
     pub fn get_migrations() -> Vec<Migration> {
         vec![
             Migration {
@@ -81,6 +330,81 @@ impl HistoryManager {
                 sql: "ALTER TABLE transcription_history ADD COLUMN post_process_prompt TEXT;",
                 kind: MigrationKind::Up,
             },
+            Migration {
+                version: 4,
+                description: "create_history_snippets_table",
+                sql: "CREATE TABLE IF NOT EXISTS history_snippets (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    history_id INTEGER NOT NULL,
+                    segment_index INTEGER NOT NULL,
+                    start_secs REAL NOT NULL,
+                    end_secs REAL NOT NULL,
+                    text TEXT NOT NULL,
+                    file_name TEXT NOT NULL,
+                    size_bytes INTEGER NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS idx_history_snippets_history_id
+                    ON history_snippets (history_id);",
+                kind: MigrationKind::Up,
+            },
+            Migration {
+                version: 5,
+                description: "create_transcription_versions_table",
+                sql: "CREATE TABLE IF NOT EXISTS transcription_versions (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    history_id INTEGER NOT NULL,
+                    model_id TEXT,
+                    transcription_text TEXT NOT NULL,
+                    created_at INTEGER NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS idx_transcription_versions_history_id
+                    ON transcription_versions (history_id);",
+                kind: MigrationKind::Up,
+            },
+            Migration {
+                version: 6,
+                description: "create_history_tags_table",
+                sql: "CREATE TABLE IF NOT EXISTS history_tags (
+                    history_id INTEGER NOT NULL,
+                    tag TEXT NOT NULL,
+                    PRIMARY KEY (history_id, tag)
+                );
+                CREATE INDEX IF NOT EXISTS idx_history_tags_tag ON history_tags (tag);",
+                kind: MigrationKind::Up,
+            },
+            Migration {
+                version: 7,
+                description: "create_session_metrics_table",
+                sql: "CREATE TABLE IF NOT EXISTS session_metrics (
+                    history_id INTEGER PRIMARY KEY,
+                    timestamp INTEGER NOT NULL,
+                    audio_secs REAL NOT NULL,
+                    char_count INTEGER NOT NULL,
+                    word_count INTEGER NOT NULL,
+                    model_id TEXT,
+                    processing_ms INTEGER NOT NULL,
+                    average_confidence REAL
+                );
+                CREATE INDEX IF NOT EXISTS idx_session_metrics_timestamp
+                    ON session_metrics (timestamp);",
+                kind: MigrationKind::Up,
+            },
+            Migration {
+                version: 8,
+                description: "create_refinement_changes_table",
+                sql: "CREATE TABLE IF NOT EXISTS refinement_changes (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    history_id INTEGER NOT NULL,
+                    start_secs REAL NOT NULL,
+                    end_secs REAL NOT NULL,
+                    live_text TEXT NOT NULL,
+                    refined_text TEXT NOT NULL,
+                    kept_live INTEGER NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS idx_refinement_changes_history_id
+                    ON refinement_changes (history_id);",
+                kind: MigrationKind::Up,
+            },
         ]
     }
 
@@ -95,24 +419,229 @@ impl HistoryManager {
         Ok(Connection::open(&self.db_path)?)
     }
 
-    /// Save a transcription to history (both database and WAV file)
+    /// The cipher for the key in the credential store, loaded on first use. `None` when
+    /// encryption was never turned on.
+    fn cipher(&self) -> Result<Option<Arc<HistoryCipher>>> {
+        let mut cipher = self.cipher.lock().unwrap();
+        if cipher.is_none() {
+            *cipher = HistoryCipher::load()?.map(Arc::new);
+        }
+        Ok(cipher.clone())
+    }
+
+    /// The cipher new history is sealed with, `None` when encryption is off.
+    fn sealing_cipher(&self) -> Result<Option<Arc<HistoryCipher>>> {
+        if !crate::settings::get_settings(&self.app_handle).encrypt_history {
+            return Ok(None);
+        }
+        self.cipher()?
+            .map(Some)
+            .ok_or_else(|| anyhow!("History encryption is on but its key is missing"))
+    }
+
+    /// `text` sealed when encryption is on, also for dictated text kept outside the
+    /// database such as the session journal.
+    pub fn seal(&self, text: &str) -> Result<String> {
+        match self.sealing_cipher()? {
+            Some(cipher) => cipher.seal_text(text),
+            None => Ok(text.to_string()),
+        }
+    }
+
+    fn seal_opt(&self, text: Option<String>) -> Result<Option<String>> {
+        text.map(|text| self.seal(&text)).transpose()
+    }
+
+    /// The plain text of a stored value. A value that can't be decrypted is returned as
+    /// stored, so one bad row doesn't hide the whole history.
+    pub fn open(&self, stored: String) -> String {
+        if !history_crypto::is_sealed_text(&stored) {
+            return stored;
+        }
+        let opened = self
+            .cipher()
+            .and_then(|cipher| cipher.ok_or_else(|| anyhow!("The history key is missing")))
+            .and_then(|cipher| cipher.open_text(&stored));
+        match opened {
+            Ok(text) => text,
+            Err(e) => {
+                error!("Failed to decrypt history text: {}", e);
+                stored
+            }
+        }
+    }
+
+    fn open_opt(&self, stored: Option<String>) -> Option<String> {
+        stored.map(|stored| self.open(stored))
+    }
+
+    /// `data` sealed when encryption is on, for files holding dictated text or audio such
+    /// as the file job checkpoints.
+    pub fn seal_bytes(&self, data: Vec<u8>) -> Result<Vec<u8>> {
+        match self.sealing_cipher()? {
+            Some(cipher) => cipher.seal_bytes(&data),
+            None => Ok(data),
+        }
+    }
+
+    /// The plain content of `stored`, which is returned as it is when it isn't sealed.
+    pub fn open_bytes(&self, stored: Vec<u8>) -> Result<Vec<u8>> {
+        if !history_crypto::is_sealed_bytes(&stored) {
+            return Ok(stored);
+        }
+        self.cipher()?
+            .ok_or_else(|| anyhow!("The history key is missing"))?
+            .open_bytes(&stored)
+    }
+
+    /// Encrypts a freshly written recording in place when encryption is on.
+    fn seal_file(&self, path: &Path) -> Result<()> {
+        if let Some(cipher) = self.sealing_cipher()? {
+            let sealed = cipher.seal_bytes(&fs::read(path)?)?;
+            fs::write(path, sealed)?;
+        }
+        Ok(())
+    }
+
+    pub fn is_incognito(&self) -> bool {
+        self.incognito.load(Ordering::Relaxed)
+    }
+
+    /// Stops dictations, their audio and their journal from being written to disk until
+    /// turned off again. Not a setting, so a restart always starts with history on.
+    pub fn set_incognito(&self, enabled: bool) {
+        if self.incognito.swap(enabled, Ordering::Relaxed) == enabled {
+            return;
+        }
+        info!("Incognito mode {}", if enabled { "on" } else { "off" });
+        if let Err(e) = self.app_handle.emit("incognito-changed", enabled) {
+            error!("Failed to emit incognito-changed event: {}", e);
+        }
+    }
+
+    /// Turns encryption of the history on or off, then seals or opens every stored text and
+    /// recording. The setting changes first so nothing saved meanwhile is missed; an
+    /// interrupted conversion leaves a mix that still reads fine and is finished by turning
+    /// the setting on or off again. The key stays in the credential store, so archives
+    /// written while encryption was on remain readable. Returns how many texts and files
+    /// were converted.
+    pub fn set_encryption(&self, enabled: bool) -> Result<usize> {
+        let cipher = if enabled {
+            let cipher = Arc::new(HistoryCipher::load_or_create()?);
+            *self.cipher.lock().unwrap() = Some(cipher.clone());
+            Some(cipher)
+        } else {
+            self.cipher()?
+        };
+        let mut settings = crate::settings::get_settings(&self.app_handle);
+        settings.encrypt_history = enabled;
+        crate::settings::write_settings(&self.app_handle, settings);
+        // Without a key nothing was ever encrypted
+        let Some(cipher) = cipher else {
+            return Ok(0);
+        };
+
+        let mut changed = 0;
+        let mut conn = self.get_connection()?;
+        let tx = conn.transaction()?;
+        for (table, column) in ENCRYPTED_COLUMNS {
+            let rows = tx
+                .prepare(&format!(
+                    "SELECT rowid, {} FROM {} WHERE {} IS NOT NULL",
+                    column, table, column
+                ))?
+                .query_map([], |row| {
+                    Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            for (rowid, stored) in rows {
+                if history_crypto::is_sealed_text(&stored) == enabled {
+                    continue;
+                }
+                let value = if enabled {
+                    cipher.seal_text(&stored)?
+                } else {
+                    cipher.open_text(&stored)?
+                };
+                tx.execute(
+                    &format!("UPDATE {} SET {} = ?1 WHERE rowid = ?2", table, column),
+                    params![value, rowid],
+                )?;
+                changed += 1;
+            }
+        }
+        tx.commit()?;
+
+        for dir in [
+            self.recordings_dir.clone(),
+            self.recordings_dir.join(SNIPPETS_DIR),
+        ] {
+            let Ok(files) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for path in files.flatten().map(|file| file.path()) {
+                if path.extension().map_or(true, |ext| ext != "wav") {
+                    continue;
+                }
+                let data = fs::read(&path)?;
+                if history_crypto::is_sealed_bytes(&data) == enabled {
+                    continue;
+                }
+                let converted = if enabled {
+                    cipher.seal_bytes(&data)?
+                } else {
+                    cipher.open_bytes(&data)?
+                };
+                // Written next to the recording first so a crash can't leave half a file
+                let temp_path = path.with_extension("wav.tmp");
+                fs::write(&temp_path, converted)?;
+                fs::rename(&temp_path, &path)?;
+                changed += 1;
+            }
+        }
+
+        info!(
+            "History encryption turned {}, {} texts and recordings converted",
+            if enabled { "on" } else { "off" },
+            changed
+        );
+        Ok(changed)
+    }
+
+    /// Save a transcription to history (both database and WAV file), plus a snippet of the
+    /// audio behind each of its `segments`. The entry is titled and tagged by `naming`, a
+    /// missing title falls back to the time of the recording. `metrics` go into the
+    /// dictation statistics. Returns the id of the new entry.
     pub async fn save_transcription(
         &self,
         audio_samples: Vec<f32>,
         transcription_text: String,
         post_processed_text: Option<String>,
         post_process_prompt: Option<String>,
-    ) -> Result<()> {
+        segments: Vec<TranscriptSegment>,
+        naming: SessionNaming,
+        metrics: SessionMetrics,
+    ) -> Result<i64> {
         let timestamp = Utc::now().timestamp();
         let file_name = format!("handy-{}.wav", timestamp);
-        let title = self.format_timestamp_title(timestamp);
+        let title = naming
+            .title
+            .unwrap_or_else(|| self.format_timestamp_title(timestamp));
+
+        // Save WAV file, the entry keeps its file name either way so audio can be attached
+        // later
+        if crate::settings::get_settings(&self.app_handle).save_recording_audio {
+            let file_path = self.recordings_dir.join(&file_name);
+            save_wav_file(&file_path, &audio_samples).await?;
+            self.seal_file(&file_path)?;
+        }
 
-        // Save WAV file
-        let file_path = self.recordings_dir.join(&file_name);
-        save_wav_file(file_path, &audio_samples).await?;
+        let final_text = post_processed_text
+            .clone()
+            .unwrap_or_else(|| transcription_text.clone());
 
         // Save to database
-        self.save_to_database(
+        let history_id = self.save_to_database(
             file_name,
             timestamp,
             title,
@@ -120,15 +649,46 @@ impl HistoryManager {
             post_processed_text,
             post_process_prompt,
         )?;
+        if !naming.tags.is_empty() {
+            self.insert_tags(&self.get_connection()?, history_id, &naming.tags)?;
+        }
+        if let Err(e) = self.save_metrics(history_id, timestamp, &final_text, &metrics) {
+            error!("Failed to save session metrics: {}", e);
+        }
+
+        // Snippets are a convenience, failing to write them doesn't fail the save
+        if let Err(e) = self
+            .save_snippets(history_id, timestamp, &audio_samples, &segments)
+            .await
+        {
+            error!("Failed to save history snippets: {}", e);
+        }
 
         // Clean up old entries
         self.cleanup_old_entries()?;
+        self.enforce_recording_limit()?;
 
         // Emit history updated event
         if let Err(e) = self.app_handle.emit("history-updated", ()) {
             error!("Failed to emit history-updated event: {}", e);
         }
 
+        Ok(history_id)
+    }
+
+    /// Adds a history entry without audio, for a transcription restored from the session
+    /// journal after a crash. `timestamp` is when the dictation started.
+    pub fn save_recovered_transcription(&self, timestamp: i64, text: String) -> Result<()> {
+        let file_name = format!("handy-{}.wav", timestamp);
+        let title = self.format_timestamp_title(timestamp);
+        self.save_to_database(file_name, timestamp, title, text, None, None)?;
+
+        self.cleanup_old_entries()?;
+
+        if let Err(e) = self.app_handle.emit("history-updated", ()) {
+            error!("Failed to emit history-updated event: {}", e);
+        }
+
         Ok(())
     }
 
@@ -140,7 +700,12 @@ impl HistoryManager {
         transcription_text: String,
         post_processed_text: Option<String>,
         post_process_prompt: Option<String>,
-    ) -> Result<()> {
+    ) -> Result<i64> {
+        let title = self.seal(&title)?;
+        let transcription_text = self.seal(&transcription_text)?;
+        let post_processed_text = self.seal_opt(post_processed_text)?;
+        let post_process_prompt = self.seal_opt(post_process_prompt)?;
+
         let conn = self.get_connection()?;
         conn.execute(
             "INSERT INTO transcription_history (file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
@@ -148,16 +713,247 @@ impl HistoryManager {
         )?;
 
         debug!("Saved transcription to database");
+        Ok(conn.last_insert_rowid())
+    }
+
+    fn save_metrics(
+        &self,
+        history_id: i64,
+        timestamp: i64,
+        text: &str,
+        metrics: &SessionMetrics,
+    ) -> Result<()> {
+        let conn = self.get_connection()?;
+        conn.execute(
+            "INSERT OR REPLACE INTO session_metrics (history_id, timestamp, audio_secs, char_count, word_count, model_id, processing_ms, average_confidence) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                history_id,
+                timestamp,
+                metrics.audio_secs,
+                text.chars().count() as i64,
+                text.split_whitespace().count() as i64,
+                metrics.model_id,
+                metrics.processing_ms,
+                metrics.average_confidence
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Dictation statistics of the last `days` days, per day or week and per model.
+    pub async fn get_dictation_stats(
+        &self,
+        period: StatsPeriod,
+        days: u32,
+    ) -> Result<DictationStats> {
+        let to = Utc::now().timestamp();
+        let from = to - days.clamp(1, MAX_STATS_DAYS) as i64 * 24 * 60 * 60;
+
+        let conn = self.get_connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT timestamp, audio_secs, char_count, word_count, model_id, processing_ms, average_confidence
+             FROM session_metrics WHERE timestamp >= ?1 ORDER BY timestamp",
+        )?;
+        let rows = stmt.query_map([from], |row| {
+            Ok(MetricsRow {
+                timestamp: row.get("timestamp")?,
+                audio_secs: row.get("audio_secs")?,
+                char_count: row.get("char_count")?,
+                word_count: row.get("word_count")?,
+                model_id: row.get("model_id")?,
+                processing_ms: row.get("processing_ms")?,
+                average_confidence: row.get("average_confidence")?,
+            })
+        })?;
+
+        let mut metrics = Vec::new();
+        for row in rows {
+            metrics.push(row?);
+        }
+        Ok(aggregate_stats(&metrics, period, from, to))
+    }
+
+    async fn save_snippets(
+        &self,
+        history_id: i64,
+        timestamp: i64,
+        audio_samples: &[f32],
+        segments: &[TranscriptSegment],
+    ) -> Result<()> {
+        let settings = crate::settings::get_settings(&self.app_handle);
+        if !settings.save_recording_audio
+            || settings.snippet_storage_limit_mb == 0
+            || segments.is_empty()
+        {
+            return Ok(());
+        }
+
+        fs::create_dir_all(self.recordings_dir.join(SNIPPETS_DIR))?;
+        let conn = self.get_connection()?;
+
+        let mut saved_count = 0;
+        for (index, segment) in segments.iter().enumerate() {
+            if segment.text.is_empty() || segment.end - segment.start > MAX_SNIPPET_SECS {
+                continue;
+            }
+            let Some(range) = snippet_range(segment, audio_samples.len()) else {
+                continue;
+            };
+
+            let file_name = format!("{}/handy-{}-{}.wav", SNIPPETS_DIR, timestamp, index);
+            let file_path = self.recordings_dir.join(&file_name);
+            save_wav_file(&file_path, &audio_samples[range.clone()]).await?;
+            self.seal_file(&file_path)?;
+
+            // 16-bit mono samples plus the WAV header
+            let size_bytes = (range.len() * 2 + 44) as i64;
+            conn.execute(
+                "INSERT INTO history_snippets (history_id, segment_index, start_secs, end_secs, text, file_name, size_bytes) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![history_id, index as i64, segment.start as f64, segment.end as f64, self.seal(&segment.text)?, file_name, size_bytes],
+            )?;
+            saved_count += 1;
+        }
+
+        debug!(
+            "Saved {} snippets for history entry {}",
+            saved_count, history_id
+        );
+        self.enforce_snippet_limit()?;
+        Ok(())
+    }
+
+    /// Deletes the oldest snippets until the total fits `snippet_storage_limit_mb`. Snippets
+    /// of saved entries are removed last.
+    fn enforce_snippet_limit(&self) -> Result<usize> {
+        let limit_bytes =
+            i64::from(crate::settings::get_settings(&self.app_handle).snippet_storage_limit_mb)
+                * 1024
+                * 1024;
+
+        let conn = self.get_connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT s.id, s.file_name, s.size_bytes FROM history_snippets s
+             LEFT JOIN transcription_history h ON h.id = s.history_id
+             ORDER BY COALESCE(h.saved, 0) DESC, s.id DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>("id")?,
+                row.get::<_, String>("file_name")?,
+                row.get::<_, i64>("size_bytes")?,
+            ))
+        })?;
+
+        let mut total_bytes = 0;
+        let mut to_delete = Vec::new();
+        for row in rows {
+            let (id, file_name, size_bytes) = row?;
+            total_bytes += size_bytes;
+            if total_bytes > limit_bytes {
+                to_delete.push((id, file_name));
+            }
+        }
+
+        for (id, file_name) in &to_delete {
+            self.remove_snippet_file(file_name);
+            conn.execute("DELETE FROM history_snippets WHERE id = ?1", params![id])?;
+        }
+
+        if !to_delete.is_empty() {
+            debug!(
+                "Removed {} snippets over the storage limit",
+                to_delete.len()
+            );
+        }
+        Ok(to_delete.len())
+    }
+
+    /// Removes all snippets of a history entry, files and rows.
+    fn delete_snippets(&self, conn: &Connection, history_id: i64) -> Result<()> {
+        let mut stmt =
+            conn.prepare("SELECT file_name FROM history_snippets WHERE history_id = ?1")?;
+        let files = stmt
+            .query_map(params![history_id], |row| row.get::<_, String>("file_name"))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        for file_name in &files {
+            self.remove_snippet_file(file_name);
+        }
+
+        conn.execute(
+            "DELETE FROM history_snippets WHERE history_id = ?1",
+            params![history_id],
+        )?;
+        Ok(())
+    }
+
+    fn remove_snippet_file(&self, file_name: &str) {
+        let file_path = self.recordings_dir.join(file_name);
+        if file_path.exists() {
+            if let Err(e) = fs::remove_file(&file_path) {
+                error!("Failed to delete snippet {}: {}", file_name, e);
+            }
+        }
+    }
+
+    pub async fn get_snippets(&self, history_id: i64) -> Result<Vec<HistorySnippet>> {
+        let conn = self.get_connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, history_id, segment_index, start_secs, end_secs, text, file_name
+             FROM history_snippets WHERE history_id = ?1 ORDER BY segment_index",
+        )?;
+
+        let rows = stmt.query_map([history_id], |row| {
+            Ok(HistorySnippet {
+                id: row.get("id")?,
+                history_id: row.get("history_id")?,
+                segment_index: row.get("segment_index")?,
+                start_secs: row.get("start_secs")?,
+                end_secs: row.get("end_secs")?,
+                text: self.open(row.get("text")?),
+                file_name: row.get("file_name")?,
+            })
+        })?;
+
+        let mut snippets = Vec::new();
+        for row in rows {
+            snippets.push(row?);
+        }
+
+        Ok(snippets)
+    }
+
+    /// Applies every retention setting once and notifies the frontend if anything was removed.
+    pub fn run_maintenance(&self) -> Result<()> {
+        let removed_entries = self.enforce_retention()?;
+        let removed_audio = self.cleanup_expired_audio()?
+            + self.enforce_recording_limit()?
+            + self.enforce_snippet_limit()?;
+
+        if removed_entries > 0 || removed_audio > 0 {
+            info!(
+                "History maintenance removed {} entries and {} audio files",
+                removed_entries, removed_audio
+            );
+            if let Err(e) = self.app_handle.emit("history-updated", ()) {
+                error!("Failed to emit history-updated event: {}", e);
+            }
+        }
+
         Ok(())
     }
 
     pub fn cleanup_old_entries(&self) -> Result<()> {
+        self.enforce_retention()?;
+        Ok(())
+    }
+
+    fn enforce_retention(&self) -> Result<usize> {
         let retention_period = crate::settings::get_recording_retention_period(&self.app_handle);
 
         match retention_period {
             crate::settings::RecordingRetentionPeriod::Never => {
                 // Don't delete anything
-                return Ok(());
+                return Ok(0);
             }
             crate::settings::RecordingRetentionPeriod::PreserveLimit => {
                 // Use the old count-based logic with history_limit
@@ -177,11 +973,31 @@ impl HistoryManager {
         }
 
         let conn = self.get_connection()?;
+
+        // Archive before deleting so a failed write never loses history
+        if crate::settings::get_settings(&self.app_handle).archive_expired_history {
+            self.archive_entries(&conn, entries)?;
+        }
+
         let mut deleted_count = 0;
 
         for (id, file_name) in entries {
-            // Delete database entry
+            self.delete_snippets(&conn, *id)?;
+            conn.execute(
+                "DELETE FROM transcription_versions WHERE history_id = ?1",
+                params![id],
+            )?;
+            conn.execute(
+                "DELETE FROM refinement_changes WHERE history_id = ?1",
+                params![id],
+            )?;
             conn.execute(
+                "DELETE FROM history_tags WHERE history_id = ?1",
+                params![id],
+            )?;
+
+            // Delete database entry
+            deleted_count += conn.execute(
                 "DELETE FROM transcription_history WHERE id = ?1",
                 params![id],
             )?;
@@ -193,15 +1009,152 @@ impl HistoryManager {
                     error!("Failed to delete WAV file {}: {}", file_name, e);
                 } else {
                     debug!("Deleted old WAV file: {}", file_name);
+                }
+            }
+        }
+
+        Ok(deleted_count)
+    }
+
+    /// Appends the given entries as JSON lines to this month's gzip archive
+    /// (`archive/history-YYYY-MM.jsonl.gz`). Each call adds a new gzip member, which
+    /// standard tools such as `zcat` read back as one continuous file.
+    fn archive_entries(&self, conn: &Connection, entries: &[(i64, String)]) -> Result<()> {
+        let mut stmt = conn.prepare(
+            "SELECT id, file_name, timestamp, saved, title, transcription_text, post_processed_text, post_process_prompt
+             FROM transcription_history WHERE id = ?1",
+        )?;
+
+        let mut lines = String::new();
+        for (id, _) in entries {
+            let entry = stmt
+                .query_row([id], |row| {
+                    let file_name: String = row.get("file_name")?;
+                    let audio_available = self.get_audio_file_path(&file_name).exists();
+                    Ok(HistoryEntry {
+                        id: row.get("id")?,
+                        file_name,
+                        timestamp: row.get("timestamp")?,
+                        saved: row.get("saved")?,
+                        title: row.get("title")?,
+                        transcription_text: row.get("transcription_text")?,
+                        post_processed_text: row.get("post_processed_text")?,
+                        post_process_prompt: row.get("post_process_prompt")?,
+                        audio_available,
+                        tags: Vec::new(),
+                    })
+                })
+                .optional()?;
+            if let Some(mut entry) = entry {
+                entry.tags = self.entry_tags(conn, entry.id)?;
+                lines.push_str(&serde_json::to_string(&entry)?);
+                lines.push('\n');
+            }
+        }
+
+        if lines.is_empty() {
+            return Ok(());
+        }
+
+        fs::create_dir_all(&self.archive_dir)?;
+        let archive_path = self
+            .archive_dir
+            .join(format!("history-{}.jsonl.gz", Local::now().format("%Y-%m")));
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&archive_path)?;
+
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        encoder.write_all(lines.as_bytes())?;
+        encoder.finish()?.sync_all()?;
+
+        debug!(
+            "Archived {} history entries to {:?}",
+            entries.len(),
+            archive_path
+        );
+        Ok(())
+    }
+
+    /// Deletes the audio of unsaved entries older than `audio_retention_days`, keeping their text.
+    fn cleanup_expired_audio(&self) -> Result<usize> {
+        let Some(days) = crate::settings::get_settings(&self.app_handle).audio_retention_days
+        else {
+            return Ok(0);
+        };
+
+        let cutoff_timestamp = Utc::now().timestamp() - i64::from(days) * 24 * 60 * 60;
+
+        let conn = self.get_connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, file_name FROM transcription_history WHERE saved = 0 AND timestamp < ?1",
+        )?;
+        let rows = stmt.query_map(params![cutoff_timestamp], |row| {
+            Ok((row.get::<_, i64>("id")?, row.get::<_, String>("file_name")?))
+        })?;
+
+        let mut deleted_count = 0;
+        for row in rows {
+            let (id, file_name) = row?;
+            self.delete_snippets(&conn, id)?;
+            let file_path = self.recordings_dir.join(&file_name);
+            if !file_path.exists() {
+                continue;
+            }
+            match fs::remove_file(&file_path) {
+                Ok(()) => {
+                    debug!("Deleted expired audio file: {}", file_name);
                     deleted_count += 1;
                 }
+                Err(e) => error!("Failed to delete audio file {}: {}", file_name, e),
             }
         }
 
         Ok(deleted_count)
     }
 
-    fn cleanup_by_count(&self, limit: usize) -> Result<()> {
+    /// Deletes the audio of the oldest unsaved entries until the recordings fit
+    /// `recording_storage_limit_mb`, keeping their text.
+    fn enforce_recording_limit(&self) -> Result<usize> {
+        let Some(limit_mb) =
+            crate::settings::get_settings(&self.app_handle).recording_storage_limit_mb
+        else {
+            return Ok(0);
+        };
+        let limit_bytes = u64::from(limit_mb) * 1024 * 1024;
+
+        let conn = self.get_connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT file_name FROM transcription_history WHERE saved = 0 ORDER BY timestamp DESC",
+        )?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>("file_name"))?;
+
+        let mut total_bytes = 0;
+        let mut deleted_count = 0;
+        for row in rows {
+            let file_name = row?;
+            let file_path = self.recordings_dir.join(&file_name);
+            let Ok(metadata) = fs::metadata(&file_path) else {
+                continue;
+            };
+            total_bytes += metadata.len();
+            if total_bytes <= limit_bytes {
+                continue;
+            }
+            match fs::remove_file(&file_path) {
+                Ok(()) => {
+                    debug!("Deleted audio file over the storage limit: {}", file_name);
+                    deleted_count += 1;
+                }
+                Err(e) => error!("Failed to delete audio file {}: {}", file_name, e),
+            }
+        }
+
+        Ok(deleted_count)
+    }
+
+    fn cleanup_by_count(&self, limit: usize) -> Result<usize> {
         let conn = self.get_connection()?;
 
         // Get all entries that are not saved, ordered by timestamp desc
@@ -218,22 +1171,24 @@ impl HistoryManager {
             entries.push(row?);
         }
 
-        if entries.len() > limit {
-            let entries_to_delete = &entries[limit..];
-            let deleted_count = self.delete_entries_and_files(entries_to_delete)?;
+        if entries.len() <= limit {
+            return Ok(0);
+        }
 
-            if deleted_count > 0 {
-                debug!("Cleaned up {} old history entries by count", deleted_count);
-            }
+        let entries_to_delete = &entries[limit..];
+        let deleted_count = self.delete_entries_and_files(entries_to_delete)?;
+
+        if deleted_count > 0 {
+            debug!("Cleaned up {} old history entries by count", deleted_count);
         }
 
-        Ok(())
+        Ok(deleted_count)
     }
 
     fn cleanup_by_time(
         &self,
         retention_period: crate::settings::RecordingRetentionPeriod,
-    ) -> Result<()> {
+    ) -> Result<usize> {
         let conn = self.get_connection()?;
 
         // Calculate cutoff timestamp (current time minus retention period)
@@ -268,7 +1223,7 @@ impl HistoryManager {
             );
         }
 
-        Ok(())
+        Ok(deleted_count)
     }
 
     pub async fn get_history_entries(&self) -> Result<Vec<HistoryEntry>> {
@@ -278,21 +1233,28 @@ impl HistoryManager {
         )?;
 
         let rows = stmt.query_map([], |row| {
+            let file_name: String = row.get("file_name")?;
+            let audio_available = self.get_audio_file_path(&file_name).exists();
             Ok(HistoryEntry {
                 id: row.get("id")?,
-                file_name: row.get("file_name")?,
+                file_name,
                 timestamp: row.get("timestamp")?,
                 saved: row.get("saved")?,
-                title: row.get("title")?,
-                transcription_text: row.get("transcription_text")?,
-                post_processed_text: row.get("post_processed_text")?,
-                post_process_prompt: row.get("post_process_prompt")?,
+                title: self.open(row.get("title")?),
+                transcription_text: self.open(row.get("transcription_text")?),
+                post_processed_text: self.open_opt(row.get("post_processed_text")?),
+                post_process_prompt: self.open_opt(row.get("post_process_prompt")?),
+                audio_available,
+                tags: Vec::new(),
             })
         })?;
 
+        let mut tags = self.tags_by_entry(&conn)?;
         let mut entries = Vec::new();
         for row in rows {
-            entries.push(row?);
+            let mut entry = row?;
+            entry.tags = tags.remove(&entry.id).unwrap_or_default();
+            entries.push(entry);
         }
 
         Ok(entries)
@@ -329,6 +1291,12 @@ impl HistoryManager {
         self.recordings_dir.join(file_name)
     }
 
+    /// The recording `file_name` as WAV. An encrypted recording is decrypted in memory, so
+    /// it never reaches the disk in the clear.
+    pub fn read_audio(&self, file_name: &str) -> Result<Vec<u8>> {
+        self.open_bytes(fs::read(self.get_audio_file_path(file_name))?)
+    }
+
     pub async fn get_entry_by_id(&self, id: i64) -> Result<Option<HistoryEntry>> {
         let conn = self.get_connection()?;
         let mut stmt = conn.prepare(
@@ -338,20 +1306,375 @@ impl HistoryManager {
 
         let entry = stmt
             .query_row([id], |row| {
+                let file_name: String = row.get("file_name")?;
+                let audio_available = self.get_audio_file_path(&file_name).exists();
                 Ok(HistoryEntry {
                     id: row.get("id")?,
-                    file_name: row.get("file_name")?,
+                    file_name,
                     timestamp: row.get("timestamp")?,
                     saved: row.get("saved")?,
-                    title: row.get("title")?,
-                    transcription_text: row.get("transcription_text")?,
-                    post_processed_text: row.get("post_processed_text")?,
-                    post_process_prompt: row.get("post_process_prompt")?,
+                    title: self.open(row.get("title")?),
+                    transcription_text: self.open(row.get("transcription_text")?),
+                    post_processed_text: self.open_opt(row.get("post_processed_text")?),
+                    post_process_prompt: self.open_opt(row.get("post_process_prompt")?),
+                    audio_available,
+                    tags: Vec::new(),
                 })
             })
             .optional()?;
 
-        Ok(entry)
+        match entry {
+            Some(mut entry) => {
+                entry.tags = self.entry_tags(&conn, id)?;
+                Ok(Some(entry))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// The most recently saved entry, if any.
+    pub async fn get_latest_entry(&self) -> Result<Option<HistoryEntry>> {
+        let id: Option<i64> = self
+            .get_connection()?
+            .query_row(
+                "SELECT id FROM transcription_history ORDER BY timestamp DESC, id DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match id {
+            Some(id) => self.get_entry_by_id(id).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Id and final text of the `limit` most recent entries, newest first. Not async so the
+    /// tray can call it from inside event handlers.
+    pub fn get_recent_transcripts(&self, limit: usize) -> Result<Vec<(i64, String)>> {
+        let conn = self.get_connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, COALESCE(post_processed_text, transcription_text) FROM transcription_history
+             ORDER BY timestamp DESC, id DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(params![limit as i64], |row| {
+            Ok((row.get(0)?, self.open(row.get(1)?)))
+        })?;
+        Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+    }
+
+    /// Renames an entry, an empty title goes back to the time of the recording.
+    pub async fn set_entry_title(&self, id: i64, title: &str) -> Result<()> {
+        let conn = self.get_connection()?;
+        let timestamp: i64 = conn.query_row(
+            "SELECT timestamp FROM transcription_history WHERE id = ?1",
+            params![id],
+            |row| row.get("timestamp"),
+        )?;
+        let title = match title.trim() {
+            "" => self.format_timestamp_title(timestamp),
+            title => title.to_string(),
+        };
+
+        conn.execute(
+            "UPDATE transcription_history SET title = ?1 WHERE id = ?2",
+            params![self.seal(&title)?, id],
+        )?;
+        debug!("Renamed history entry {}", id);
+
+        if let Err(e) = self.app_handle.emit("history-updated", ()) {
+            error!("Failed to emit history-updated event: {}", e);
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the text an entry holds as pasted, e.g. after it was edited before pasting.
+    /// The raw transcription is kept.
+    pub async fn set_final_text(&self, id: i64, text: &str) -> Result<()> {
+        let conn = self.get_connection()?;
+        conn.execute(
+            "UPDATE transcription_history SET post_processed_text = ?1 WHERE id = ?2",
+            params![self.seal(text)?, id],
+        )?;
+        debug!("Updated the final text of history entry {}", id);
+
+        if let Err(e) = self.app_handle.emit("history-updated", ()) {
+            error!("Failed to emit history-updated event: {}", e);
+        }
+
+        Ok(())
+    }
+
+    /// Replaces an entry's tags. Tags are normalized, so the stored ones are returned.
+    pub async fn set_entry_tags(&self, id: i64, tags: &[String]) -> Result<Vec<String>> {
+        let tags = normalize_tags(tags.iter().map(String::as_str));
+        let conn = self.get_connection()?;
+        conn.execute(
+            "DELETE FROM history_tags WHERE history_id = ?1",
+            params![id],
+        )?;
+        self.insert_tags(&conn, id, &tags)?;
+        debug!("Tagged history entry {} with {:?}", id, tags);
+
+        if let Err(e) = self.app_handle.emit("history-updated", ()) {
+            error!("Failed to emit history-updated event: {}", e);
+        }
+
+        Ok(tags)
+    }
+
+    /// Every tag in use with the number of entries carrying it, most used first.
+    pub async fn get_all_tags(&self) -> Result<Vec<HistoryTag>> {
+        let conn = self.get_connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT tag, COUNT(*) AS entries FROM history_tags GROUP BY tag ORDER BY entries DESC, tag",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(HistoryTag {
+                tag: row.get("tag")?,
+                entry_count: row.get("entries")?,
+            })
+        })?;
+
+        let mut tags = Vec::new();
+        for row in rows {
+            tags.push(row?);
+        }
+        Ok(tags)
+    }
+
+    /// Entries whose title or text contains `query`, ignoring case, and that carry all of
+    /// `tags`. Newest first.
+    pub async fn search_entries(
+        &self,
+        query: Option<&str>,
+        tags: &[String],
+    ) -> Result<Vec<HistoryEntry>> {
+        let query = query
+            .map(|q| q.trim().to_lowercase())
+            .filter(|q| !q.is_empty());
+        let tags = normalize_tags(tags.iter().map(String::as_str));
+
+        let entries = self.get_history_entries().await?;
+        Ok(entries
+            .into_iter()
+            .filter(|entry| tags.iter().all(|tag| entry.tags.contains(tag)))
+            .filter(|entry| match &query {
+                Some(query) => [
+                    Some(&entry.title),
+                    Some(&entry.transcription_text),
+                    entry.post_processed_text.as_ref(),
+                ]
+                .into_iter()
+                .flatten()
+                .any(|text| text.to_lowercase().contains(query)),
+                None => true,
+            })
+            .collect())
+    }
+
+    /// Writes every entry to `path` as JSON, grouped by tag. An entry with several tags is
+    /// listed under each, entries without tags are under `untagged`.
+    pub async fn export_by_tag(&self, path: &Path) -> Result<usize> {
+        let entries = self.get_history_entries().await?;
+        let mut groups: BTreeMap<String, Vec<&HistoryEntry>> = BTreeMap::new();
+        for entry in &entries {
+            if entry.tags.is_empty() {
+                groups
+                    .entry("untagged".to_string())
+                    .or_default()
+                    .push(entry);
+            }
+            for tag in &entry.tags {
+                groups.entry(tag.clone()).or_default().push(entry);
+            }
+        }
+
+        fs::write(path, serde_json::to_string_pretty(&groups)?)?;
+        info!(
+            "Exported {} history entries in {} groups to {:?}",
+            entries.len(),
+            groups.len(),
+            path
+        );
+        Ok(entries.len())
+    }
+
+    fn insert_tags(&self, conn: &Connection, history_id: i64, tags: &[String]) -> Result<()> {
+        let mut stmt =
+            conn.prepare("INSERT OR IGNORE INTO history_tags (history_id, tag) VALUES (?1, ?2)")?;
+        for tag in tags {
+            stmt.execute(params![history_id, tag])?;
+        }
+        Ok(())
+    }
+
+    fn entry_tags(&self, conn: &Connection, history_id: i64) -> Result<Vec<String>> {
+        let mut stmt =
+            conn.prepare("SELECT tag FROM history_tags WHERE history_id = ?1 ORDER BY tag")?;
+        let rows = stmt.query_map([history_id], |row| row.get("tag"))?;
+
+        let mut tags = Vec::new();
+        for row in rows {
+            tags.push(row?);
+        }
+        Ok(tags)
+    }
+
+    fn tags_by_entry(&self, conn: &Connection) -> Result<HashMap<i64, Vec<String>>> {
+        let mut stmt = conn.prepare("SELECT history_id, tag FROM history_tags ORDER BY tag")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>("history_id")?,
+                row.get::<_, String>("tag")?,
+            ))
+        })?;
+
+        let mut tags: HashMap<i64, Vec<String>> = HashMap::new();
+        for row in rows {
+            let (id, tag) = row?;
+            tags.entry(id).or_default().push(tag);
+        }
+        Ok(tags)
+    }
+
+    /// Records a new transcription of an entry made with `model_id` and makes it the entry's
+    /// text. The text it replaces stays available through `get_transcription_versions`.
+    pub async fn add_transcription_version(
+        &self,
+        history_id: i64,
+        model_id: &str,
+        transcription_text: String,
+    ) -> Result<TranscriptionVersion> {
+        let entry = self
+            .get_entry_by_id(history_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("History entry {} not found", history_id))?;
+
+        let conn = self.get_connection()?;
+        let existing: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM transcription_versions WHERE history_id = ?1",
+            params![history_id],
+            |row| row.get(0),
+        )?;
+        // The first re-transcription also preserves the original as a version
+        if existing == 0 {
+            conn.execute(
+                "INSERT INTO transcription_versions (history_id, model_id, transcription_text, created_at) VALUES (?1, NULL, ?2, ?3)",
+                params![
+                    history_id,
+                    self.seal(&entry.transcription_text)?,
+                    entry.timestamp
+                ],
+            )?;
+        }
+
+        let created_at = Utc::now().timestamp();
+        let sealed_text = self.seal(&transcription_text)?;
+        conn.execute(
+            "INSERT INTO transcription_versions (history_id, model_id, transcription_text, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![history_id, model_id, sealed_text, created_at],
+        )?;
+        let id = conn.last_insert_rowid();
+
+        conn.execute(
+            "UPDATE transcription_history SET transcription_text = ?1 WHERE id = ?2",
+            params![sealed_text, history_id],
+        )?;
+
+        debug!(
+            "Stored transcription version {} for entry {} using {}",
+            id, history_id, model_id
+        );
+
+        if let Err(e) = self.app_handle.emit("history-updated", ()) {
+            error!("Failed to emit history-updated event: {}", e);
+        }
+
+        Ok(TranscriptionVersion {
+            id,
+            history_id,
+            model_id: Some(model_id.to_string()),
+            transcription_text,
+            created_at,
+        })
+    }
+
+    /// Every transcription of an entry, oldest first. Empty if it was never re-transcribed.
+    pub async fn get_transcription_versions(
+        &self,
+        history_id: i64,
+    ) -> Result<Vec<TranscriptionVersion>> {
+        let conn = self.get_connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, history_id, model_id, transcription_text, created_at
+             FROM transcription_versions WHERE history_id = ?1 ORDER BY id",
+        )?;
+
+        let rows = stmt.query_map([history_id], |row| {
+            Ok(TranscriptionVersion {
+                id: row.get("id")?,
+                history_id: row.get("history_id")?,
+                model_id: row.get("model_id")?,
+                transcription_text: self.open(row.get("transcription_text")?),
+                created_at: row.get("created_at")?,
+            })
+        })?;
+
+        let mut versions = Vec::new();
+        for row in rows {
+            versions.push(row?);
+        }
+
+        Ok(versions)
+    }
+
+    /// Records where the accurate pass of entry `history_id` disagreed with its live
+    /// transcription.
+    pub fn save_refinement_changes(
+        &self,
+        history_id: i64,
+        changes: &[RefinementChange],
+    ) -> Result<()> {
+        let conn = self.get_connection()?;
+        let mut stmt = conn.prepare(
+            "INSERT INTO refinement_changes (history_id, start_secs, end_secs, live_text, refined_text, kept_live) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        for change in changes {
+            stmt.execute(params![
+                history_id,
+                change.start,
+                change.end,
+                self.seal(&change.live_text)?,
+                self.seal(&change.refined_text)?,
+                change.kept_live
+            ])?;
+        }
+        Ok(())
+    }
+
+    /// The regions the accurate pass altered or left alone in entry `history_id`, in order.
+    pub fn get_refinement_changes(&self, history_id: i64) -> Result<Vec<RefinementChange>> {
+        let conn = self.get_connection()?;
+        let mut stmt = conn.prepare(
+            "SELECT start_secs, end_secs, live_text, refined_text, kept_live
+             FROM refinement_changes WHERE history_id = ?1 ORDER BY start_secs",
+        )?;
+        let rows = stmt.query_map([history_id], |row| {
+            Ok(RefinementChange {
+                start: row.get("start_secs")?,
+                end: row.get("end_secs")?,
+                live_text: self.open(row.get("live_text")?),
+                refined_text: self.open(row.get("refined_text")?),
+                kept_live: row.get("kept_live")?,
+            })
+        })?;
+
+        let mut changes = Vec::new();
+        for row in rows {
+            changes.push(row?);
+        }
+        Ok(changes)
     }
 
     pub async fn delete_entry(&self, id: i64) -> Result<()> {
@@ -368,6 +1691,19 @@ impl HistoryManager {
                 }
             }
         }
+        self.delete_snippets(&conn, id)?;
+        conn.execute(
+            "DELETE FROM transcription_versions WHERE history_id = ?1",
+            params![id],
+        )?;
+        conn.execute(
+            "DELETE FROM refinement_changes WHERE history_id = ?1",
+            params![id],
+        )?;
+        conn.execute(
+            "DELETE FROM history_tags WHERE history_id = ?1",
+            params![id],
+        )?;
 
         // Delete from database
         conn.execute(
@@ -395,3 +1731,56 @@ impl HistoryManager {
         }
     }
 }
+
+/// Sample range of `segment` in 16 kHz audio of `len` samples, padded on both sides.
+fn snippet_range(segment: &TranscriptSegment, len: usize) -> Option<Range<usize>> {
+    let to_sample = |secs: f32| ((secs.max(0.0) * WHISPER_SAMPLE_RATE as f32) as usize).min(len);
+    let start = to_sample(segment.start - SNIPPET_PADDING_SECS);
+    let end = to_sample(segment.end + SNIPPET_PADDING_SECS);
+    (end > start).then_some(start..end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(timestamp: i64, model: &str, words: i64, confidence: Option<f64>) -> MetricsRow {
+        MetricsRow {
+            timestamp,
+            audio_secs: 30.0,
+            char_count: words * 5,
+            word_count: words,
+            model_id: Some(model.to_string()),
+            processing_ms: 400,
+            average_confidence: confidence,
+        }
+    }
+
+    #[test]
+    fn test_aggregate_stats() {
+        let day = 24 * 60 * 60;
+        let to = Utc::now().timestamp();
+        let from = to - 6 * day;
+        let rows = vec![
+            row(from, "small", 50, Some(0.8)),
+            row(to, "small", 100, None),
+            row(to, "cloud", 25, Some(0.6)),
+        ];
+
+        let stats = aggregate_stats(&rows, StatsPeriod::Day, from, to);
+        assert_eq!(stats.periods.len(), 7);
+        assert_eq!(stats.periods[0].sessions, 1);
+        assert_eq!(stats.periods[1].sessions, 0);
+        assert_eq!(stats.periods[6].sessions, 2);
+
+        assert_eq!(stats.total.words, 175);
+        assert!((stats.total.words_per_minute - 175.0 / 1.5).abs() < 1e-9);
+        assert!((stats.total.average_confidence.unwrap() - 0.7).abs() < 1e-9);
+        assert_eq!(stats.models[0].label, "small");
+        assert_eq!(stats.models[0].sessions, 2);
+
+        let weeks = aggregate_stats(&rows, StatsPeriod::Week, from, to);
+        assert!(weeks.periods.len() <= 2);
+        assert_eq!(weeks.periods.iter().map(|p| p.sessions).sum::<i64>(), 3);
+    }
+}
@@ -1,4 +1,16 @@
 pub mod audio;
+pub mod captions;
+pub mod chunk_workers;
+pub mod disfluency;
+pub mod file_jobs;
+pub mod hf_hub;
 pub mod history;
 pub mod model;
+pub mod model_cache;
+pub mod model_catalog;
+pub mod model_integrity;
+pub mod model_quantize;
+pub mod model_storage;
+pub mod plugins;
 pub mod transcription;
+pub mod usage;
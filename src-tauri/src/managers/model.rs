@@ -1,4 +1,13 @@
-use crate::settings::{get_settings, write_settings};
+use crate::managers::hf_hub;
+use crate::managers::model_cache::{CacheLock, SharedModelCache};
+use crate::managers::model_catalog;
+use crate::managers::model_integrity::{self, IntegrityReport};
+use crate::managers::model_quantize;
+use crate::managers::model_storage::{
+    self, EvictionProposal, LastUsed, ModelDiskUsage, LAST_USED_FILE,
+};
+use crate::secrets;
+use crate::settings::{get_settings, write_settings, AppSettings};
 use anyhow::Result;
 use flate2::read::GzDecoder;
 use futures_util::StreamExt;
@@ -9,8 +18,9 @@ use std::collections::HashMap;
 use std::fs;
 use std::fs::File;
 use std::io::Write;
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
 use tar::Archive;
 use tauri::{AppHandle, Emitter, Manager};
 
@@ -18,6 +28,18 @@ use tauri::{AppHandle, Emitter, Manager};
 pub enum EngineType {
     Whisper,
     Parakeet,
+    /// A Piper text-to-speech voice, used to read transcriptions back rather than to
+    /// transcribe
+    Piper,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+pub enum Quantization {
+    Fp32,
+    Fp16,
+    Int8,
+    Q5,
+    Q4,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -35,6 +57,20 @@ pub struct ModelInfo {
     pub engine_type: EngineType,
     pub accuracy_score: f32, // 0.0 to 1.0, higher is more accurate
     pub speed_score: f32,    // 0.0 to 1.0, higher is faster
+    pub quantization: Quantization,
+    pub ram_mb: u64, // Approximate memory needed while loaded
+    /// SHA-256 of each file by its path in the model (the file name of a single-file
+    /// model), where the catalog knows it
+    #[serde(default)]
+    pub sha256: HashMap<String, String>,
+    /// For directory models fetched file by file, the files to download relative to `url`
+    pub download_files: Vec<String>,
+}
+
+impl ModelInfo {
+    pub fn is_voice(&self) -> bool {
+        matches!(self.engine_type, EngineType::Piper)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -45,20 +81,39 @@ pub struct DownloadProgress {
     pub percentage: f64,
 }
 
+/// Files making up an fp32 Parakeet ONNX export, fetched individually from Hugging Face.
+/// Parakeet comes as int8, downloaded or quantized locally from this export, and fp32. No
+/// fp16 build is offered: onnxruntime's CPU kernels mostly run fp16 weights as fp32, so it
+/// would save disk space but not the memory low-RAM machines are short of.
+const PARAKEET_FP32_FILES: &[&str] = &[
+    "encoder-model.onnx",
+    "encoder-model.onnx.data",
+    "decoder_joint-model.onnx",
+    "nemo128.onnx",
+    "vocab.txt",
+    "config.json",
+];
+
 pub struct ModelManager {
     app_handle: AppHandle,
-    models_dir: PathBuf,
+    models_dir: RwLock<PathBuf>,
+    shared_cache: Option<SharedModelCache>,
+    last_used: LastUsed,
     available_models: Mutex<HashMap<String, ModelInfo>>,
 }
 
 impl ModelManager {
     pub fn new(app_handle: &AppHandle) -> Result<Self> {
-        // Create models directory in app data
-        let models_dir = app_handle
-            .path()
-            .app_data_dir()
-            .map_err(|e| anyhow::anyhow!("Failed to get app data dir: {}", e))?
-            .join("models");
+        // Create models directory in app data, unless the user picked another folder
+        let models_dir = match get_settings(app_handle).models_dir {
+            // A folder on a drive that isn't connected falls back to app data for this run
+            Some(dir) if fs::create_dir_all(&dir).is_ok() => PathBuf::from(dir),
+            Some(dir) => {
+                warn!("Models folder {} is unavailable, using the default", dir);
+                default_models_dir(app_handle)?
+            }
+            None => default_models_dir(app_handle)?,
+        };
 
         if !models_dir.exists() {
             fs::create_dir_all(&models_dir)?;
@@ -66,7 +121,7 @@ impl ModelManager {
 
         let mut available_models = HashMap::new();
 
-        // TODO this should be read from a JSON file or something..
+        // Built-in catalog, which the signed model manifest adds to and updates
         available_models.insert(
             "small".to_string(),
             ModelInfo {
@@ -83,6 +138,10 @@ impl ModelManager {
                 engine_type: EngineType::Whisper,
                 accuracy_score: 0.60,
                 speed_score: 0.85,
+                quantization: Quantization::Fp16,
+                ram_mb: 800,
+                sha256: HashMap::new(),
+                download_files: Vec::new(),
             },
         );
 
@@ -103,6 +162,10 @@ impl ModelManager {
                 engine_type: EngineType::Whisper,
                 accuracy_score: 0.75,
                 speed_score: 0.60,
+                quantization: Quantization::Q4,
+                ram_mb: 1000,
+                sha256: HashMap::new(),
+                download_files: Vec::new(),
             },
         );
 
@@ -122,6 +185,10 @@ impl ModelManager {
                 engine_type: EngineType::Whisper,
                 accuracy_score: 0.80,
                 speed_score: 0.40,
+                quantization: Quantization::Fp16,
+                ram_mb: 2100,
+                sha256: HashMap::new(),
+                download_files: Vec::new(),
             },
         );
 
@@ -141,6 +208,66 @@ impl ModelManager {
                 engine_type: EngineType::Whisper,
                 accuracy_score: 0.85,
                 speed_score: 0.30,
+                quantization: Quantization::Q5,
+                ram_mb: 1900,
+                sha256: HashMap::new(),
+                download_files: Vec::new(),
+            },
+        );
+
+        // Full precision builds of the quantized Whisper models, downloaded as they are
+        // rather than converted on this machine
+        available_models.insert(
+            "medium-fp16".to_string(),
+            ModelInfo {
+                id: "medium-fp16".to_string(),
+                name: "Whisper Medium (Full Precision)".to_string(),
+                description: "Unquantized Whisper Medium, slightly more accurate but slower"
+                    .to_string(),
+                filename: "ggml-medium.bin".to_string(),
+                url: Some(
+                    "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-medium.bin"
+                        .to_string(),
+                ),
+                size_mb: 1533,
+                is_downloaded: false,
+                is_downloading: false,
+                partial_size: 0,
+                is_directory: false,
+                engine_type: EngineType::Whisper,
+                accuracy_score: 0.77,
+                speed_score: 0.45,
+                quantization: Quantization::Fp16,
+                ram_mb: 2100,
+                sha256: HashMap::new(),
+                download_files: Vec::new(),
+            },
+        );
+
+        available_models.insert(
+            "large-fp16".to_string(),
+            ModelInfo {
+                id: "large-fp16".to_string(),
+                name: "Whisper Large (Full Precision)".to_string(),
+                description: "Unquantized Whisper Large, the most accurate Whisper but slow"
+                    .to_string(),
+                filename: "ggml-large-v3.bin".to_string(),
+                url: Some(
+                    "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-large-v3.bin"
+                        .to_string(),
+                ),
+                size_mb: 3095,
+                is_downloaded: false,
+                is_downloading: false,
+                partial_size: 0,
+                is_directory: false,
+                engine_type: EngineType::Whisper,
+                accuracy_score: 0.87,
+                speed_score: 0.20,
+                quantization: Quantization::Fp16,
+                ram_mb: 3900,
+                sha256: HashMap::new(),
+                download_files: Vec::new(),
             },
         );
 
@@ -161,6 +288,10 @@ impl ModelManager {
                 engine_type: EngineType::Parakeet,
                 accuracy_score: 0.85,
                 speed_score: 0.85,
+                quantization: Quantization::Int8,
+                ram_mb: 1100,
+                sha256: HashMap::new(),
+                download_files: Vec::new(),
             },
         );
 
@@ -180,15 +311,150 @@ impl ModelManager {
                 engine_type: EngineType::Parakeet,
                 accuracy_score: 0.80,
                 speed_score: 0.85,
+                quantization: Quantization::Int8,
+                ram_mb: 1100,
+                sha256: HashMap::new(),
+                download_files: Vec::new(),
+            },
+        );
+
+        available_models.insert(
+            "parakeet-tdt-0.6b-v2-fp32".to_string(),
+            ModelInfo {
+                id: "parakeet-tdt-0.6b-v2-fp32".to_string(),
+                name: "Parakeet V2 (Full Precision)".to_string(),
+                description: "English only, unquantized. Can be converted to int8 locally"
+                    .to_string(),
+                filename: "parakeet-tdt-0.6b-v2-fp32".to_string(), // Directory name
+                url: Some(
+                    "https://huggingface.co/istupakov/parakeet-tdt-0.6b-v2-onnx/resolve/main"
+                        .to_string(),
+                ),
+                size_mb: 2500,
+                is_downloaded: false,
+                is_downloading: false,
+                partial_size: 0,
+                is_directory: true,
+                engine_type: EngineType::Parakeet,
+                accuracy_score: 0.82,
+                speed_score: 0.6,
+                quantization: Quantization::Fp32,
+                ram_mb: 3200,
+                sha256: HashMap::new(),
+                download_files: PARAKEET_FP32_FILES
+                    .iter()
+                    .map(|file| file.to_string())
+                    .collect(),
             },
         );
 
+        available_models.insert(
+            "parakeet-tdt-0.6b-v3-fp32".to_string(),
+            ModelInfo {
+                id: "parakeet-tdt-0.6b-v3-fp32".to_string(),
+                name: "Parakeet V3 (Full Precision)".to_string(),
+                description: "Multilingual, unquantized. Can be converted to int8 locally"
+                    .to_string(),
+                filename: "parakeet-tdt-0.6b-v3-fp32".to_string(), // Directory name
+                url: Some(
+                    "https://huggingface.co/istupakov/parakeet-tdt-0.6b-v3-onnx/resolve/main"
+                        .to_string(),
+                ),
+                size_mb: 2500,
+                is_downloaded: false,
+                is_downloading: false,
+                partial_size: 0,
+                is_directory: true,
+                engine_type: EngineType::Parakeet,
+                accuracy_score: 0.82,
+                speed_score: 0.6,
+                quantization: Quantization::Fp32,
+                ram_mb: 3200,
+                sha256: HashMap::new(),
+                download_files: PARAKEET_FP32_FILES
+                    .iter()
+                    .map(|file| file.to_string())
+                    .collect(),
+            },
+        );
+
+        available_models.insert(
+            "piper-en_US-lessac-medium".to_string(),
+            ModelInfo {
+                id: "piper-en_US-lessac-medium".to_string(),
+                name: "Lessac (English, US)".to_string(),
+                description: "Piper voice for reading transcriptions back.".to_string(),
+                filename: "piper-en_US-lessac-medium".to_string(), // Directory name
+                url: Some(
+                    "https://huggingface.co/rhasspy/piper-voices/resolve/main/en/en_US/lessac/medium"
+                        .to_string(),
+                ),
+                size_mb: 63,
+                is_downloaded: false,
+                is_downloading: false,
+                partial_size: 0,
+                is_directory: true,
+                engine_type: EngineType::Piper,
+                accuracy_score: 0.0,
+                speed_score: 0.8,
+                quantization: Quantization::Fp32,
+                ram_mb: 120,
+                sha256: HashMap::new(),
+                download_files: vec![
+                    "en_US-lessac-medium.onnx".to_string(),
+                    "en_US-lessac-medium.onnx.json".to_string(),
+                ],
+            },
+        );
+
+        // Optionally keep models in a machine-wide cache shared with other apps and users
+        let shared_cache = if get_settings(app_handle).shared_model_cache {
+            SharedModelCache::open(&app_handle.config().identifier)
+        } else {
+            None
+        };
+
         let manager = Self {
             app_handle: app_handle.clone(),
-            models_dir,
+            models_dir: RwLock::new(
+                shared_cache
+                    .as_ref()
+                    .map(|cache| cache.dir().clone())
+                    .unwrap_or_else(|| models_dir.clone()),
+            ),
+            shared_cache,
+            last_used: LastUsed::load(
+                app_handle
+                    .path()
+                    .app_data_dir()
+                    .map_err(|e| anyhow::anyhow!("Failed to get app data dir: {}", e))?
+                    .join(LAST_USED_FILE),
+            ),
             available_models: Mutex::new(available_models),
         };
 
+        // Models published since this version, as of the last manifest fetched
+        if let Some(public_key) = manifest_public_key(app_handle) {
+            let data_dir = app_handle.path().app_data_dir()?;
+            if let Some(models) =
+                model_catalog::load_cached(&data_dir, &public_key, env!("CARGO_PKG_VERSION"))
+            {
+                manager.merge_catalog(models);
+            }
+        }
+
+        // Repos added from the Hugging Face Hub
+        manager.merge_catalog(
+            get_settings(app_handle)
+                .hub_models
+                .iter()
+                .map(hf_hub::model_info)
+                .collect(),
+        );
+
+        // Move models downloaded before the shared cache was enabled into it
+        manager.migrate_to_shared_cache(&models_dir);
+
         // Migrate any bundled models to user directory
         manager.migrate_bundled_models()?;
 
@@ -201,9 +467,23 @@ impl ModelManager {
         Ok(manager)
     }
 
+    /// The transcription models, leaving out text-to-speech voices.
     pub fn get_available_models(&self) -> Vec<ModelInfo> {
         let models = self.available_models.lock().unwrap();
-        models.values().cloned().collect()
+        models
+            .values()
+            .filter(|model| !model.is_voice())
+            .cloned()
+            .collect()
+    }
+
+    pub fn get_voice_models(&self) -> Vec<ModelInfo> {
+        let models = self.available_models.lock().unwrap();
+        models
+            .values()
+            .filter(|model| model.is_voice())
+            .cloned()
+            .collect()
     }
 
     pub fn get_model_info(&self, model_id: &str) -> Option<ModelInfo> {
@@ -211,6 +491,270 @@ impl ModelManager {
         models.get(model_id).cloned()
     }
 
+    /// Fetches the model manifest and adds its models to the catalog, emitting
+    /// `model-catalog-updated`. Returns how many models the manifest lists for this version.
+    pub async fn refresh_catalog(&self) -> Result<usize> {
+        let public_key = manifest_public_key(&self.app_handle)
+            .ok_or_else(|| anyhow::anyhow!("No key to verify the model manifest with"))?;
+        let data_dir = self.app_handle.path().app_data_dir()?;
+        let client = download_client(&get_settings(&self.app_handle))?;
+        let models =
+            model_catalog::fetch(&client, &data_dir, &public_key, env!("CARGO_PKG_VERSION"))
+                .await?;
+        let count = models.len();
+        self.merge_catalog(models);
+        let _ = self.app_handle.emit("model-catalog-updated", count);
+        info!("Model catalog updated, the manifest lists {} models", count);
+        Ok(count)
+    }
+
+    /// Adds or updates catalog entries, keeping the download state of the ones known.
+    pub fn merge_catalog(&self, models: Vec<ModelInfo>) {
+        let models_dir = self.models_dir();
+        let mut available = self.available_models.lock().unwrap();
+        for mut model in models {
+            match available.get(&model.id) {
+                Some(existing) => {
+                    model.is_downloaded = existing.is_downloaded;
+                    model.is_downloading = existing.is_downloading;
+                    model.partial_size = existing.partial_size;
+                }
+                None => model.is_downloaded = models_dir.join(&model.filename).exists(),
+            }
+            available.insert(model.id.clone(), model);
+        }
+    }
+
+    /// Folder the models are currently stored in.
+    pub fn models_dir(&self) -> PathBuf {
+        self.models_dir.read().unwrap().clone()
+    }
+
+    /// Moves the downloaded models, and partial downloads so they can be resumed, into
+    /// `target`, and stores models there from then on. Everything is copied before anything
+    /// is removed, so a failure part way (e.g. a full drive) leaves the models where they
+    /// were. Returns how many models were moved.
+    pub fn move_models(&self, target: &Path) -> Result<usize> {
+        if self.shared_cache.is_some() {
+            return Err(anyhow::anyhow!(
+                "Models are kept in the shared model cache, turn it off to move them"
+            ));
+        }
+        let models: Vec<ModelInfo> = self
+            .available_models
+            .lock()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect();
+        if let Some(model) = models.iter().find(|model| model.is_downloading) {
+            return Err(anyhow::anyhow!(
+                "Wait for {} to finish downloading before moving the models",
+                model.name
+            ));
+        }
+
+        // Held throughout, so nothing resolves a model path while the files are in transit
+        let mut models_dir = self.models_dir.write().unwrap();
+        fs::create_dir_all(target)?;
+        if fs::canonicalize(target)? == fs::canonicalize(&*models_dir)? {
+            return Ok(0);
+        }
+
+        let mut entries = Vec::new();
+        for model in &models {
+            for name in [
+                model.filename.clone(),
+                format!("{}.partial", model.filename),
+            ] {
+                let source = models_dir.join(&name);
+                if source.exists() {
+                    if target.join(&name).exists() {
+                        return Err(anyhow::anyhow!(
+                            "{} already exists in {}",
+                            name,
+                            target.display()
+                        ));
+                    }
+                    entries.push(name);
+                }
+            }
+        }
+
+        // Staged under a temporary name, so the target never holds a half-copied model
+        let mut moved = Vec::new();
+        for name in &entries {
+            let source = models_dir.join(name);
+            let staging = target.join(format!("{}.moving", name));
+            let result = match fs::rename(&source, target.join(name)) {
+                Ok(()) => Ok(None),
+                // Another drive: copy, and remove the source once everything is across
+                Err(_) => copy_all(&source, &staging)
+                    .and_then(|_| fs::rename(&staging, target.join(name)))
+                    .map(|_| Some(source)),
+            };
+            match result {
+                Ok(copied) => moved.push((name, copied)),
+                Err(e) => {
+                    let _ = remove_all(&staging);
+                    // Put back what was renamed, drop what was copied
+                    for (name, copied) in moved {
+                        match copied {
+                            Some(_) => {
+                                let _ = remove_all(&target.join(name));
+                            }
+                            None => {
+                                let _ = fs::rename(target.join(name), models_dir.join(name));
+                            }
+                        }
+                    }
+                    return Err(anyhow::anyhow!("Failed to move {}: {}", name, e));
+                }
+            }
+        }
+
+        let mut settings = get_settings(&self.app_handle);
+        settings.models_dir = (target != default_models_dir(&self.app_handle)?)
+            .then(|| target.to_string_lossy().into_owned());
+        write_settings(&self.app_handle, settings);
+        *models_dir = target.to_path_buf();
+        drop(models_dir);
+
+        for (name, copied) in &moved {
+            if let Some(source) = copied {
+                if let Err(e) = remove_all(source) {
+                    warn!("Failed to remove the old copy of {}: {}", name, e);
+                }
+            }
+        }
+        info!("Moved {} model files to {}", moved.len(), target.display());
+        self.update_download_status()?;
+
+        Ok(moved
+            .iter()
+            .filter(|(name, _)| !name.ends_with(".partial"))
+            .count())
+    }
+
+    /// Disk usage of each model with files on disk, largest first.
+    pub fn disk_usage(&self) -> Vec<ModelDiskUsage> {
+        let models_dir = self.models_dir();
+        let models: Vec<ModelInfo> = self
+            .available_models
+            .lock()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect();
+        let mut usage: Vec<ModelDiskUsage> = models
+            .into_iter()
+            .map(|model| ModelDiskUsage {
+                bytes: model_storage::disk_size(&models_dir.join(&model.filename))
+                    + model_storage::disk_size(
+                        &models_dir.join(format!("{}.partial", model.filename)),
+                    ),
+                last_used: self.last_used.get(&model.id),
+                model_id: model.id,
+                name: model.name,
+            })
+            .filter(|usage| usage.bytes > 0)
+            .collect();
+        usage.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+        usage
+    }
+
+    /// The models to delete to get under the storage limit, if one is set and exceeded.
+    /// The selected model and read-back voice, models being downloaded and `keep` are
+    /// never proposed.
+    pub fn eviction_proposal(&self, keep: &[&str]) -> Option<EvictionProposal> {
+        let settings = get_settings(&self.app_handle);
+        let limit_bytes = (settings.model_storage_limit_gb? * 1024.0 * 1024.0 * 1024.0) as u64;
+        let usage = self.disk_usage();
+        let total_bytes = usage.iter().map(|model| model.bytes).sum();
+        if total_bytes <= limit_bytes {
+            return None;
+        }
+
+        let downloading: Vec<String> = self
+            .available_models
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|model| model.is_downloading)
+            .map(|model| model.id.clone())
+            .collect();
+        let mut keep = keep.to_vec();
+        keep.push(&settings.selected_model);
+        if let Some(battery_model) = &settings.battery_policy.model_id {
+            keep.push(battery_model);
+        }
+        keep.push(&settings.readback_voice);
+        keep.extend(downloading.iter().map(String::as_str));
+
+        let models = model_storage::eviction_candidates(&usage, limit_bytes, &keep);
+        (!models.is_empty()).then_some(EvictionProposal {
+            limit_bytes,
+            total_bytes,
+            models,
+        })
+    }
+
+    /// Emits `model-eviction-proposed` when the models exceed the storage limit, for the
+    /// user to confirm with `evict_models`.
+    pub fn propose_eviction(&self, keep: &[&str]) {
+        if let Some(proposal) = self.eviction_proposal(keep) {
+            info!(
+                "Models use {} bytes, over the {} byte limit; proposing to delete {} of them",
+                proposal.total_bytes,
+                proposal.limit_bytes,
+                proposal.models.len()
+            );
+            let _ = self.app_handle.emit("model-eviction-proposed", &proposal);
+        }
+    }
+
+    fn migrate_to_shared_cache(&self, user_models_dir: &Path) {
+        let Some(cache) = &self.shared_cache else {
+            return;
+        };
+
+        let filenames: Vec<String> = {
+            let models = self.available_models.lock().unwrap();
+            models
+                .values()
+                .map(|model| model.filename.clone())
+                .collect()
+        };
+
+        for filename in filenames {
+            let user_path = user_models_dir.join(&filename);
+            let shared_path = cache.dir().join(&filename);
+            if !user_path.exists() {
+                continue;
+            }
+            // Another consumer brought the same model, use theirs
+            if shared_path.exists() {
+                if let Err(e) = cache.acquire(&filename) {
+                    warn!("Failed to reference shared model {}: {}", filename, e);
+                }
+                continue;
+            }
+
+            match move_across_filesystems(&user_path, &shared_path) {
+                Ok(()) => {
+                    info!("Moved {} into the shared model cache", filename);
+                    if let Err(e) = cache.acquire(&filename) {
+                        warn!("Failed to reference shared model {}: {}", filename, e);
+                    }
+                }
+                Err(e) => warn!(
+                    "Could not move {} into the shared model cache: {}",
+                    filename, e
+                ),
+            }
+        }
+    }
+
     fn migrate_bundled_models(&self) -> Result<()> {
         // Check for bundled models and copy them to user directory
         let bundled_models = ["ggml-small.bin"]; // Add other bundled models here if any
@@ -223,7 +767,7 @@ impl ModelManager {
 
             if let Ok(bundled_path) = bundled_path {
                 if bundled_path.exists() {
-                    let user_path = self.models_dir.join(filename);
+                    let user_path = self.models_dir().join(filename);
 
                     // Only copy if user doesn't already have the model
                     if !user_path.exists() {
@@ -238,16 +782,26 @@ impl ModelManager {
         Ok(())
     }
 
+    /// Whether `filename` is in the shared cache for other consumers only, e.g. after this one
+    /// deleted it while others still use it.
+    fn only_shared_by_others(&self, filename: &str) -> bool {
+        self.shared_cache
+            .as_ref()
+            .is_some_and(|cache| !cache.is_referenced(filename))
+    }
+
     fn update_download_status(&self) -> Result<()> {
         let mut models = self.available_models.lock().unwrap();
 
         for model in models.values_mut() {
             if model.is_directory {
                 // For directory-based models, check if the directory exists
-                let model_path = self.models_dir.join(&model.filename);
-                let partial_path = self.models_dir.join(format!("{}.partial", &model.filename));
+                let model_path = self.models_dir().join(&model.filename);
+                let partial_path = self
+                    .models_dir()
+                    .join(format!("{}.partial", &model.filename));
                 let extracting_path = self
-                    .models_dir
+                    .models_dir()
                     .join(format!("{}.extracting", &model.filename));
 
                 // Clean up any leftover .extracting directories from interrupted extractions
@@ -256,7 +810,9 @@ impl ModelManager {
                     let _ = fs::remove_dir_all(&extracting_path);
                 }
 
-                model.is_downloaded = model_path.exists() && model_path.is_dir();
+                model.is_downloaded = model_path.exists()
+                    && model_path.is_dir()
+                    && !self.only_shared_by_others(&model.filename);
                 model.is_downloading = false;
 
                 // Get partial file size if it exists (for the .tar.gz being downloaded)
@@ -267,10 +823,13 @@ impl ModelManager {
                 }
             } else {
                 // For file-based models (existing logic)
-                let model_path = self.models_dir.join(&model.filename);
-                let partial_path = self.models_dir.join(format!("{}.partial", &model.filename));
+                let model_path = self.models_dir().join(&model.filename);
+                let partial_path = self
+                    .models_dir()
+                    .join(format!("{}.partial", &model.filename));
 
-                model.is_downloaded = model_path.exists();
+                model.is_downloaded =
+                    model_path.exists() && !self.only_shared_by_others(&model.filename);
                 model.is_downloading = false;
 
                 // Get partial file size if it exists
@@ -293,7 +852,10 @@ impl ModelManager {
         if settings.selected_model.is_empty() {
             // Find the first available (downloaded) model
             let models = self.available_models.lock().unwrap();
-            if let Some(available_model) = models.values().find(|model| model.is_downloaded) {
+            if let Some(available_model) = models
+                .values()
+                .find(|model| model.is_downloaded && !model.is_voice())
+            {
                 info!(
                     "Auto-selecting model: {} ({})",
                     available_model.id, available_model.name
@@ -323,21 +885,43 @@ impl ModelManager {
         let url = model_info
             .url
             .ok_or_else(|| anyhow::anyhow!("No download URL for model"))?;
-        let model_path = self.models_dir.join(&model_info.filename);
+        let model_path = self.models_dir().join(&model_info.filename);
         let partial_path = self
-            .models_dir
+            .models_dir()
             .join(format!("{}.partial", &model_info.filename));
 
+        // In the shared cache, wait for any other process downloading the same model
+        let cache_lock = match &self.shared_cache {
+            Some(cache) => Some(cache.lock(&model_info.filename).await?),
+            None => None,
+        };
+
         // Don't download if complete version already exists
         if model_path.exists() {
             // Clean up any partial file that might exist
             if partial_path.exists() {
                 let _ = fs::remove_file(&partial_path);
             }
+            if let Some(cache) = &self.shared_cache {
+                cache.acquire(&model_info.filename)?;
+            }
             self.update_download_status()?;
+            let _ = self.app_handle.emit("model-download-complete", model_id);
             return Ok(());
         }
 
+        if !model_info.download_files.is_empty() {
+            self.set_downloading(model_id, true);
+            if let Err(e) = self
+                .download_model_files(&model_info, &url, cache_lock.as_ref())
+                .await
+            {
+                self.set_downloading(model_id, false);
+                return Err(e);
+            }
+            return self.finish_download(&model_info);
+        }
+
         // Check if we have a partial download to resume
         let mut resume_from = if partial_path.exists() {
             let size = partial_path.metadata()?.len();
@@ -357,7 +941,7 @@ impl ModelManager {
         }
 
         // Create HTTP client with range request for resuming
-        let client = reqwest::Client::new();
+        let client = download_client(&get_settings(&self.app_handle))?;
         let mut request = client.get(&url);
 
         if resume_from > 0 {
@@ -437,6 +1021,7 @@ impl ModelManager {
             .emit("model-download-progress", &initial_progress);
 
         // Download with progress
+        let mut last_lock_refresh = Instant::now();
         while let Some(chunk) = stream.next().await {
             let chunk = chunk.map_err(|e| {
                 // Mark as not downloading on error
@@ -452,6 +1037,13 @@ impl ModelManager {
             file.write_all(&chunk)?;
             downloaded += chunk.len() as u64;
 
+            if let Some(lock) = &cache_lock {
+                if last_lock_refresh.elapsed() > Duration::from_secs(10) {
+                    lock.refresh();
+                    last_lock_refresh = Instant::now();
+                }
+            }
+
             let percentage = if total_size > 0 {
                 (downloaded as f64 / total_size as f64) * 100.0
             } else {
@@ -500,9 +1092,9 @@ impl ModelManager {
 
             // Use a temporary extraction directory to ensure atomic operations
             let temp_extract_dir = self
-                .models_dir
+                .models_dir()
                 .join(format!("{}.extracting", &model_info.filename));
-            let final_model_dir = self.models_dir.join(&model_info.filename);
+            let final_model_dir = self.models_dir().join(&model_info.filename);
 
             // Clean up any previous incomplete extraction
             if temp_extract_dir.exists() {
@@ -566,10 +1158,18 @@ impl ModelManager {
             fs::rename(&partial_path, &model_path)?;
         }
 
+        self.finish_download(&model_info)
+    }
+
+    fn finish_download(&self, model_info: &ModelInfo) -> Result<()> {
+        if let Some(cache) = &self.shared_cache {
+            cache.acquire(&model_info.filename)?;
+        }
+
         // Update download status
         {
             let mut models = self.available_models.lock().unwrap();
-            if let Some(model) = models.get_mut(model_id) {
+            if let Some(model) = models.get_mut(&model_info.id) {
                 model.is_downloading = false;
                 model.is_downloaded = true;
                 model.partial_size = 0;
@@ -577,16 +1177,176 @@ impl ModelManager {
         }
 
         // Emit completion event
-        let _ = self.app_handle.emit("model-download-complete", model_id);
+        let _ = self
+            .app_handle
+            .emit("model-download-complete", &model_info.id);
+        self.propose_eviction(&[&model_info.id]);
 
         info!(
             "Successfully downloaded model {} to {:?}",
-            model_id, model_path
+            model_info.id,
+            self.models_dir().join(&model_info.filename)
         );
 
         Ok(())
     }
 
+    /// Downloads a directory model file by file into `<filename>.partial/`, keeping files
+    /// completed by an earlier attempt, then moves the directory into place.
+    async fn download_model_files(
+        &self,
+        model_info: &ModelInfo,
+        base_url: &str,
+        cache_lock: Option<&CacheLock>,
+    ) -> Result<()> {
+        let partial_dir = self
+            .models_dir()
+            .join(format!("{}.partial", &model_info.filename));
+        let final_dir = self.models_dir().join(&model_info.filename);
+        fs::create_dir_all(&partial_dir)?;
+
+        // Individual file sizes aren't known up front, so progress uses the catalog size
+        let total_size = model_info.size_mb * 1024 * 1024;
+        let mut downloaded = 0u64;
+        let mut last_lock_refresh = Instant::now();
+        let client = download_client(&get_settings(&self.app_handle))?;
+
+        // Gated Hub repos need the access token, which goes nowhere else
+        let hub_token = base_url
+            .starts_with(hf_hub::HUB_URL)
+            .then(|| secrets::get_api_key(hf_hub::TOKEN_ID))
+            .flatten();
+
+        for file_name in &model_info.download_files {
+            let target = partial_dir.join(file_name);
+            if target.exists() {
+                downloaded += target.metadata()?.len();
+                continue;
+            }
+
+            let url = format!("{}/{}", base_url.trim_end_matches('/'), file_name);
+            debug!("Downloading {} for model {}", url, model_info.id);
+            let mut request = client.get(&url);
+            if let Some(token) = &hub_token {
+                request = request.bearer_auth(token);
+            }
+            let response = request.send().await?.error_for_status()?;
+
+            let temp_path = partial_dir.join(format!("{}.tmp", file_name));
+            let mut file = File::create(&temp_path)?;
+            let mut stream = response.bytes_stream();
+            while let Some(chunk) = stream.next().await {
+                let chunk = chunk?;
+                file.write_all(&chunk)?;
+                downloaded += chunk.len() as u64;
+
+                if let Some(lock) = cache_lock {
+                    if last_lock_refresh.elapsed() > Duration::from_secs(10) {
+                        lock.refresh();
+                        last_lock_refresh = Instant::now();
+                    }
+                }
+
+                let progress = DownloadProgress {
+                    model_id: model_info.id.clone(),
+                    downloaded,
+                    total: total_size,
+                    percentage: (downloaded as f64 / total_size as f64 * 100.0).min(100.0),
+                };
+                let _ = self.app_handle.emit("model-download-progress", &progress);
+            }
+            file.flush()?;
+            drop(file);
+
+            fs::rename(&temp_path, &target)?;
+        }
+
+        if final_dir.exists() {
+            fs::remove_dir_all(&final_dir)?;
+        }
+        fs::rename(&partial_dir, &final_dir)?;
+
+        Ok(())
+    }
+
+    /// Creates the int8 variant of a downloaded fp32 Parakeet model on this machine, so it
+    /// doesn't have to be downloaded separately. Returns the id of the int8 model.
+    pub fn quantize_model(&self, model_id: &str) -> Result<String> {
+        let source = self
+            .get_model_info(model_id)
+            .ok_or_else(|| anyhow::anyhow!("Model not found: {}", model_id))?;
+        if source.quantization != Quantization::Fp32
+            || !matches!(source.engine_type, EngineType::Parakeet)
+        {
+            return Err(anyhow::anyhow!(
+                "Only fp32 Parakeet models can be quantized locally"
+            ));
+        }
+        let source_dir = self.get_model_path(model_id)?;
+
+        let target = model_id
+            .strip_suffix("-fp32")
+            .and_then(|target_id| self.get_model_info(target_id))
+            .ok_or_else(|| anyhow::anyhow!("No int8 variant known for {}", model_id))?;
+        let target_dir = self.models_dir().join(&target.filename);
+        let work_dir = self
+            .models_dir()
+            .join(format!("{}.quantizing", &target.filename));
+
+        if work_dir.exists() {
+            fs::remove_dir_all(&work_dir)?;
+        }
+        fs::create_dir_all(&work_dir)?;
+
+        let _ = self.app_handle.emit("model-quantization-started", model_id);
+        info!("Quantizing {} into {}", model_id, target.id);
+
+        let result = (|| -> Result<()> {
+            for file_name in ["nemo128.onnx", "vocab.txt", "config.json"] {
+                let source_file = source_dir.join(file_name);
+                if source_file.exists() {
+                    fs::copy(&source_file, work_dir.join(file_name))?;
+                }
+            }
+            for name in ["encoder-model", "decoder_joint-model"] {
+                let quantized = model_quantize::quantize_dynamic(
+                    &source_dir.join(format!("{}.onnx", name)),
+                    &work_dir.join(format!("{}.int8.onnx", name)),
+                )?;
+                debug!("Quantized {} weights of {}", quantized, name);
+            }
+            Ok(())
+        })();
+
+        if let Err(e) = result {
+            let _ = fs::remove_dir_all(&work_dir);
+            let _ = self.app_handle.emit(
+                "model-quantization-failed",
+                &serde_json::json!({
+                    "model_id": model_id,
+                    "error": e.to_string()
+                }),
+            );
+            return Err(e);
+        }
+
+        if target_dir.exists() {
+            fs::remove_dir_all(&target_dir)?;
+        }
+        fs::rename(&work_dir, &target_dir)?;
+        if let Some(cache) = &self.shared_cache {
+            cache.acquire(&target.filename)?;
+        }
+        self.update_download_status()?;
+
+        let _ = self
+            .app_handle
+            .emit("model-quantization-completed", &target.id);
+        info!("Successfully quantized {} into {}", model_id, target.id);
+
+        Ok(target.id)
+    }
+
     pub fn delete_model(&self, model_id: &str) -> Result<()> {
         debug!("ModelManager: delete_model called for: {}", model_id);
 
@@ -600,13 +1360,21 @@ impl ModelManager {
 
         debug!("ModelManager: Found model info: {:?}", model_info);
 
-        let model_path = self.models_dir.join(&model_info.filename);
+        let model_path = self.models_dir().join(&model_info.filename);
         let partial_path = self
-            .models_dir
+            .models_dir()
             .join(format!("{}.partial", &model_info.filename));
         debug!("ModelManager: Model path: {:?}", model_path);
         debug!("ModelManager: Partial path: {:?}", partial_path);
 
+        // Shared models are only removed from disk once no other consumer references them
+        if let Some(cache) = &self.shared_cache {
+            if !cache.release(&model_info.filename)? {
+                self.update_download_status()?;
+                return Ok(());
+            }
+        }
+
         let mut deleted_something = false;
 
         if model_info.is_directory {
@@ -627,17 +1395,33 @@ impl ModelManager {
             }
         }
 
-        // Delete partial file if it exists (same for both types)
-        if partial_path.exists() {
+        // Delete partial file if it exists (a directory for models downloaded file by file)
+        if partial_path.is_dir() {
+            info!("Deleting partial directory at: {:?}", partial_path);
+            fs::remove_dir_all(&partial_path)?;
+            deleted_something = true;
+        } else if partial_path.exists() {
             info!("Deleting partial file at: {:?}", partial_path);
             fs::remove_file(&partial_path)?;
             info!("Partial file deleted successfully");
             deleted_something = true;
         }
 
+        // Leftovers of an interrupted extraction or move into the shared cache
+        for suffix in ["extracting", "migrating"] {
+            let leftover = self
+                .models_dir()
+                .join(format!("{}.{}", &model_info.filename, suffix));
+            if leftover.exists() {
+                info!("Deleting leftover {:?}", leftover);
+                remove_all(&leftover)?;
+            }
+        }
+
         if !deleted_something {
             return Err(anyhow::anyhow!("No model files found to delete"));
         }
+        self.last_used.forget(model_id);
 
         // Update download status
         self.update_download_status()?;
@@ -646,6 +1430,72 @@ impl ModelManager {
         Ok(())
     }
 
+    /// Checks the files of a downloaded model against the catalog's hashes.
+    pub fn verify_model(&self, model_id: &str) -> Result<IntegrityReport> {
+        let model_info = self
+            .get_model_info(model_id)
+            .ok_or_else(|| anyhow::anyhow!("Model not found: {}", model_id))?;
+        let report = model_integrity::check(&self.models_dir(), &model_info);
+        if !report.is_ok() {
+            warn!(
+                "Model {} has missing files {:?} and damaged files {:?}",
+                model_id, report.missing, report.corrupt
+            );
+        }
+        Ok(report)
+    }
+
+    /// Downloads the damaged files of a model again. Models fetched file by file get just
+    /// those files; a single-file model or one from an archive is downloaded again whole.
+    /// Returns the files that were damaged.
+    pub async fn repair_model(&self, model_id: &str) -> Result<Vec<String>> {
+        let model_info = self
+            .get_model_info(model_id)
+            .ok_or_else(|| anyhow::anyhow!("Model not found: {}", model_id))?;
+        let report = self.verify_model(model_id)?;
+        let damaged = report.damaged();
+        if damaged.is_empty() {
+            return Ok(damaged);
+        }
+
+        let models_dir = self.models_dir();
+        let model_path = models_dir.join(&model_info.filename);
+        if model_info.download_files.is_empty() {
+            info!("Downloading {} again to repair it", model_id);
+            remove_all(&model_path)?;
+        } else {
+            // Back to a partial download missing the damaged files, which resumes with them
+            let partial_dir = models_dir.join(format!("{}.partial", &model_info.filename));
+            if model_path.exists() {
+                fs::rename(&model_path, &partial_dir)?;
+            }
+            for file in &damaged {
+                let path = partial_dir.join(file);
+                if path.exists() {
+                    fs::remove_file(&path)?;
+                }
+            }
+            info!("Downloading {:?} of {} again", damaged, model_id);
+        }
+        {
+            let mut models = self.available_models.lock().unwrap();
+            if let Some(model) = models.get_mut(model_id) {
+                model.is_downloaded = false;
+            }
+        }
+
+        self.download_model(model_id).await?;
+        let report = self.verify_model(model_id)?;
+        if !report.is_ok() {
+            return Err(anyhow::anyhow!(
+                "{} is still damaged after downloading it again: {}",
+                model_id,
+                report.damaged().join(", ")
+            ));
+        }
+        Ok(damaged)
+    }
+
     pub fn get_model_path(&self, model_id: &str) -> Result<PathBuf> {
         let model_info = self
             .get_model_info(model_id)
@@ -663,14 +1513,15 @@ impl ModelManager {
             ));
         }
 
-        let model_path = self.models_dir.join(&model_info.filename);
+        let model_path = self.models_dir().join(&model_info.filename);
         let partial_path = self
-            .models_dir
+            .models_dir()
             .join(format!("{}.partial", &model_info.filename));
 
         if model_info.is_directory {
             // For directory-based models, ensure the directory exists and is complete
             if model_path.exists() && model_path.is_dir() && !partial_path.exists() {
+                self.last_used.touch(model_id);
                 Ok(model_path)
             } else {
                 Err(anyhow::anyhow!(
@@ -681,6 +1532,7 @@ impl ModelManager {
         } else {
             // For file-based models (existing logic)
             if model_path.exists() && !partial_path.exists() {
+                self.last_used.touch(model_id);
                 Ok(model_path)
             } else {
                 Err(anyhow::anyhow!(
@@ -691,6 +1543,13 @@ impl ModelManager {
         }
     }
 
+    fn set_downloading(&self, model_id: &str, downloading: bool) {
+        let mut models = self.available_models.lock().unwrap();
+        if let Some(model) = models.get_mut(model_id) {
+            model.is_downloading = downloading;
+        }
+    }
+
     pub fn cancel_download(&self, model_id: &str) -> Result<()> {
         debug!("ModelManager: cancel_download called for: {}", model_id);
 
@@ -721,3 +1580,106 @@ impl ModelManager {
         Ok(())
     }
 }
+
+/// The updater's public key, which also signs the model manifest.
+fn manifest_public_key(app_handle: &AppHandle) -> Option<String> {
+    app_handle
+        .config()
+        .plugins
+        .0
+        .get("updater")?
+        .get("pubkey")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// The models folder in the app data folder, used unless the user picked another.
+pub fn default_models_dir(app_handle: &AppHandle) -> Result<PathBuf> {
+    Ok(app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| anyhow::anyhow!("Failed to get app data dir: {}", e))?
+        .join("models"))
+}
+
+/// Copies a file or a directory tree.
+fn copy_all(source: &Path, target: &Path) -> std::io::Result<()> {
+    if source.is_dir() {
+        fs::create_dir_all(target)?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            copy_all(&entry.path(), &target.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        fs::copy(source, target).map(|_| ())
+    }
+}
+
+/// Moves `source` to `target`, copying and then removing it when they are on different
+/// filesystems. The copy goes to a temporary name first, so an interrupted one is never
+/// taken for the model.
+fn move_across_filesystems(source: &Path, target: &Path) -> std::io::Result<()> {
+    if fs::rename(source, target).is_ok() {
+        return Ok(());
+    }
+    let mut temp_name = target.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".migrating");
+    let temp = target.with_file_name(temp_name);
+    remove_all(&temp)?;
+    if let Err(e) = copy_all(source, &temp).and_then(|()| fs::rename(&temp, target)) {
+        let _ = remove_all(&temp);
+        return Err(e);
+    }
+    remove_all(source)
+}
+
+fn remove_all(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else if path.exists() {
+        fs::remove_file(path)
+    } else {
+        Ok(())
+    }
+}
+
+/// Builds the HTTP client for model downloads. reqwest picks up the system proxy (the
+/// `HTTPS_PROXY` family of variables, and the OS settings on macOS and Windows) unless an
+/// explicit proxy is configured.
+pub fn download_client(settings: &AppSettings) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder();
+
+    if let Some(proxy) = settings.download_proxy.as_deref().filter(|p| !p.is_empty()) {
+        builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+    }
+
+    if let Some(path) = settings
+        .download_ca_cert_path
+        .as_deref()
+        .filter(|p| !p.is_empty())
+    {
+        for certificate in load_ca_certificates(Path::new(path))? {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    Ok(builder.build()?)
+}
+
+/// Reads the certificates of a PEM bundle, or a single DER certificate.
+pub fn load_ca_certificates(path: &Path) -> Result<Vec<reqwest::Certificate>> {
+    let bytes = fs::read(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read CA certificate {:?}: {}", path, e))?;
+
+    let certificates = if bytes.starts_with(b"-----BEGIN") {
+        reqwest::Certificate::from_pem_bundle(&bytes)?
+    } else {
+        vec![reqwest::Certificate::from_der(&bytes)?]
+    };
+    if certificates.is_empty() {
+        return Err(anyhow::anyhow!("No certificates found in {:?}", path));
+    }
+
+    Ok(certificates)
+}
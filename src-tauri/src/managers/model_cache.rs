@@ -0,0 +1,178 @@
+//! Model cache shared between Handy-based apps and, where the platform has a machine-wide
+//! location users can write to, OS users.
+//!
+//! Models live in a single directory that every consumer can read. Downloads are serialized
+//! with a `<model>.lock` file so two processes never fetch the same model at once, and each
+//! consumer (app + OS user) records a reference in `<model>.refs/` so a model is only removed
+//! from disk once nobody uses it anymore.
+
+use anyhow::Result;
+use log::{debug, info, warn};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// A lock that hasn't been refreshed for this long belongs to a crashed process.
+const STALE_LOCK_AGE: Duration = Duration::from_secs(5 * 60);
+
+pub struct SharedModelCache {
+    dir: PathBuf,
+    consumer: String,
+}
+
+/// Exclusive right to download a model into the cache, released on drop.
+pub struct CacheLock {
+    path: PathBuf,
+    file: File,
+}
+
+impl SharedModelCache {
+    /// Opens the shared cache, creating it if needed. Returns `None` when the directory
+    /// can't be created or written, in which case callers use the per-user models directory.
+    pub fn open(app_identifier: &str) -> Option<Self> {
+        let dir = Self::default_dir()?;
+        if let Err(e) = fs::create_dir_all(&dir) {
+            warn!("Shared model cache {:?} is not available: {}", dir, e);
+            return None;
+        }
+
+        let probe = dir.join(format!(".probe-{}", std::process::id()));
+        if let Err(e) = File::create(&probe) {
+            warn!("Shared model cache {:?} is not writable: {}", dir, e);
+            return None;
+        }
+        let _ = fs::remove_file(&probe);
+
+        let user = std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_else(|_| "unknown".to_string());
+        let consumer = format!("{}@{}", app_identifier, user);
+
+        info!("Using shared model cache at {:?} as {}", dir, consumer);
+        Some(Self { dir, consumer })
+    }
+
+    /// `HANDY_SHARED_MODELS_DIR` if set, otherwise a location every user can write to. Linux
+    /// has none without an administrator, so there the cache is shared between the apps of
+    /// one user, and a machine-wide one takes a group-writable directory set in the variable.
+    fn default_dir() -> Option<PathBuf> {
+        if let Ok(dir) = std::env::var("HANDY_SHARED_MODELS_DIR") {
+            return Some(PathBuf::from(dir));
+        }
+
+        if cfg!(target_os = "macos") {
+            Some(PathBuf::from("/Users/Shared/Handy/models"))
+        } else if cfg!(target_os = "windows") {
+            std::env::var("PROGRAMDATA")
+                .ok()
+                .map(|dir| PathBuf::from(dir).join("Handy").join("models"))
+        } else {
+            std::env::var_os("XDG_CACHE_HOME")
+                .map(PathBuf::from)
+                .filter(|dir| dir.is_absolute())
+                .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+                .map(|dir| dir.join("handy").join("models"))
+        }
+    }
+
+    pub fn dir(&self) -> &PathBuf {
+        &self.dir
+    }
+
+    fn refs_dir(&self, filename: &str) -> PathBuf {
+        self.dir.join(format!("{}.refs", filename))
+    }
+
+    /// Waits until no other process is downloading `filename`, then takes the download lock.
+    pub async fn lock(&self, filename: &str) -> Result<CacheLock> {
+        let path = self.dir.join(format!("{}.lock", filename));
+        let mut waited = false;
+
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    let _ = writeln!(file, "{} {}", self.consumer, std::process::id());
+                    return Ok(CacheLock { path, file });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let age = fs::metadata(&path)
+                        .and_then(|m| m.modified())
+                        .ok()
+                        .and_then(|modified| SystemTime::now().duration_since(modified).ok());
+                    if age.is_some_and(|age| age > STALE_LOCK_AGE) {
+                        warn!("Removing stale model cache lock {:?}", path);
+                        let _ = fs::remove_file(&path);
+                        continue;
+                    }
+                    if !waited {
+                        info!(
+                            "Waiting for another process to finish downloading {}",
+                            filename
+                        );
+                        waited = true;
+                    }
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Records that this consumer uses `filename`. Safe to call repeatedly.
+    pub fn acquire(&self, filename: &str) -> Result<()> {
+        let refs_dir = self.refs_dir(filename);
+        fs::create_dir_all(&refs_dir)?;
+        let ref_path = refs_dir.join(&self.consumer);
+        if !ref_path.exists() {
+            File::create(&ref_path)?;
+            debug!("Added shared cache reference {:?}", ref_path);
+        }
+        Ok(())
+    }
+
+    /// Whether this consumer uses `filename`. Models other consumers downloaded are there on
+    /// disk but don't count as downloaded until this one references them.
+    pub fn is_referenced(&self, filename: &str) -> bool {
+        self.refs_dir(filename).join(&self.consumer).exists()
+    }
+
+    /// Drops this consumer's reference to `filename` and returns true if no other
+    /// consumer still references it, meaning the files may be deleted.
+    pub fn release(&self, filename: &str) -> Result<bool> {
+        let refs_dir = self.refs_dir(filename);
+        let ref_path = refs_dir.join(&self.consumer);
+        if ref_path.exists() {
+            fs::remove_file(&ref_path)?;
+        }
+
+        let remaining = fs::read_dir(&refs_dir)
+            .map(|entries| entries.filter_map(|e| e.ok()).count())
+            .unwrap_or(0);
+        if remaining == 0 {
+            let _ = fs::remove_dir(&refs_dir);
+            return Ok(true);
+        }
+
+        info!(
+            "Keeping {} in the shared model cache, still used by {} other consumer(s)",
+            filename, remaining
+        );
+        Ok(false)
+    }
+}
+
+impl CacheLock {
+    /// Keeps the lock fresh during long downloads so it isn't mistaken for a stale one.
+    pub fn refresh(&self) {
+        let _ = self.file.set_modified(SystemTime::now());
+    }
+}
+
+impl Drop for CacheLock {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.path) {
+            warn!("Failed to remove model cache lock {:?}: {}", self.path, e);
+        }
+    }
+}
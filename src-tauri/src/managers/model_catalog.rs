@@ -0,0 +1,191 @@
+//! The model catalog published as a signed JSON manifest, so new models show up without an
+//! app update. The manifest is signed with the same minisign key as the app updates
+//! (`tauri signer sign manifest.json`), and a copy of the last one verified is kept so the
+//! catalog still works offline. Models needing a newer app than this one are left out.
+//!
+//! ```json
+//! { "models": [{ "id": "parakeet-tdt-0.6b-v4", "name": "Parakeet V4", "description": "…",
+//!   "filename": "parakeet-tdt-0.6b-v4-int8", "url": "https://…/parakeet-v4-int8.tar.gz",
+//!   "size_mb": 478, "is_directory": true, "engine_type": "Parakeet",
+//!   "accuracy_score": 0.85, "speed_score": 0.85, "quantization": "Int8", "ram_mb": 1100,
+//!   "sha256": { "vocab.txt": "…" }, "min_app_version": "0.7.0" }] }
+//! ```
+
+use crate::managers::model::{EngineType, ModelInfo, Quantization};
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use log::warn;
+use minisign_verify::{PublicKey, Signature};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+pub const MANIFEST_URL: &str = "https://blob.handy.computer/models/manifest.json";
+
+const CACHE_FILE: &str = "model_manifest.json";
+const CACHE_SIGNATURE_FILE: &str = "model_manifest.json.sig";
+
+#[derive(Deserialize)]
+struct Manifest {
+    models: Vec<ManifestModel>,
+}
+
+#[derive(Deserialize)]
+struct ManifestModel {
+    id: String,
+    name: String,
+    description: String,
+    filename: String,
+    url: Option<String>,
+    size_mb: u64,
+    #[serde(default)]
+    is_directory: bool,
+    engine_type: EngineType,
+    accuracy_score: f32,
+    speed_score: f32,
+    quantization: Quantization,
+    ram_mb: u64,
+    /// SHA-256 of each file, so a damaged download can be told apart and repaired
+    #[serde(default)]
+    sha256: HashMap<String, String>,
+    #[serde(default)]
+    download_files: Vec<String>,
+    /// Oldest app version that can run the model, e.g. one that needs a newer engine
+    min_app_version: Option<String>,
+}
+
+impl From<ManifestModel> for ModelInfo {
+    fn from(model: ManifestModel) -> Self {
+        ModelInfo {
+            id: model.id,
+            name: model.name,
+            description: model.description,
+            filename: model.filename,
+            url: model.url,
+            size_mb: model.size_mb,
+            is_downloaded: false,
+            is_downloading: false,
+            partial_size: 0,
+            is_directory: model.is_directory,
+            engine_type: model.engine_type,
+            accuracy_score: model.accuracy_score,
+            speed_score: model.speed_score,
+            quantization: model.quantization,
+            ram_mb: model.ram_mb,
+            sha256: model.sha256,
+            download_files: model.download_files,
+        }
+    }
+}
+
+/// The models of a manifest that `app_version` can run.
+pub fn parse(json: &[u8], app_version: &str) -> Result<Vec<ModelInfo>> {
+    let manifest: Manifest = serde_json::from_slice(json)?;
+    let app_version = semver::Version::parse(app_version)?;
+    Ok(manifest
+        .models
+        .into_iter()
+        .filter(|model| match &model.min_app_version {
+            None => true,
+            Some(min) => match semver::Version::parse(min) {
+                Ok(min) => app_version >= min,
+                Err(e) => {
+                    warn!("Skipping model {} with version {}: {}", model.id, min, e);
+                    false
+                }
+            },
+        })
+        .map(ModelInfo::from)
+        .collect())
+}
+
+/// Checks a manifest against its signature. Both the key and the signature are base64
+/// encoded minisign files, as the updater uses them.
+pub fn verify(json: &[u8], signature: &str, public_key: &str) -> Result<()> {
+    let decode = |value: &str| -> Result<String> {
+        let bytes = base64::engine::general_purpose::STANDARD.decode(value.trim())?;
+        Ok(String::from_utf8(bytes)?)
+    };
+    let public_key = PublicKey::decode(&decode(public_key)?)
+        .map_err(|e| anyhow!("Invalid public key: {}", e))?;
+    let signature =
+        Signature::decode(&decode(signature)?).map_err(|e| anyhow!("Invalid signature: {}", e))?;
+    public_key
+        .verify(json, &signature, false)
+        .map_err(|e| anyhow!("The model manifest's signature doesn't match: {}", e))
+}
+
+/// The cached manifest's models, if there is one and it still verifies.
+pub fn load_cached(dir: &Path, public_key: &str, app_version: &str) -> Option<Vec<ModelInfo>> {
+    let json = fs::read(dir.join(CACHE_FILE)).ok()?;
+    let signature = fs::read_to_string(dir.join(CACHE_SIGNATURE_FILE)).ok()?;
+    let models = verify(&json, &signature, public_key).and_then(|_| parse(&json, app_version));
+    match models {
+        Ok(models) => Some(models),
+        Err(e) => {
+            warn!("Ignoring the cached model manifest: {}", e);
+            None
+        }
+    }
+}
+
+/// Downloads and verifies the manifest, caching it in `dir`, and returns its models.
+pub async fn fetch(
+    client: &reqwest::Client,
+    dir: &Path,
+    public_key: &str,
+    app_version: &str,
+) -> Result<Vec<ModelInfo>> {
+    let get = |url: String| async move {
+        let response = client.get(&url).send().await?.error_for_status()?;
+        Ok::<_, anyhow::Error>(response.bytes().await?)
+    };
+    let json = get(MANIFEST_URL.to_string()).await?;
+    let signature = String::from_utf8(get(format!("{}.sig", MANIFEST_URL)).await?.to_vec())?;
+    verify(&json, &signature, public_key)?;
+    let models = parse(&json, app_version)?;
+
+    fs::write(dir.join(CACHE_FILE), &json)?;
+    fs::write(dir.join(CACHE_SIGNATURE_FILE), signature)?;
+    Ok(models)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"{ "models": [
+        { "id": "parakeet-next", "name": "Parakeet Next", "description": "Newer",
+          "filename": "parakeet-next", "url": "https://example.com/parakeet-next.tar.gz",
+          "size_mb": 500, "is_directory": true, "engine_type": "Parakeet",
+          "accuracy_score": 0.9, "speed_score": 0.8, "quantization": "Int8", "ram_mb": 1200,
+          "min_app_version": "0.7.0" },
+        { "id": "whisper-tiny", "name": "Whisper Tiny", "description": "Small",
+          "filename": "ggml-tiny.bin", "url": "https://example.com/ggml-tiny.bin",
+          "size_mb": 75, "engine_type": "Whisper", "accuracy_score": 0.4,
+          "speed_score": 0.95, "quantization": "Fp16", "ram_mb": 200 }
+    ] }"#;
+
+    #[test]
+    fn test_parse_gates_on_app_version() {
+        let ids = |version: &str| -> Vec<String> {
+            parse(MANIFEST.as_bytes(), version)
+                .unwrap()
+                .into_iter()
+                .map(|model| model.id)
+                .collect()
+        };
+        assert_eq!(ids("0.6.5"), ["whisper-tiny"]);
+        assert_eq!(ids("0.7.0"), ["parakeet-next", "whisper-tiny"]);
+
+        let models = parse(MANIFEST.as_bytes(), "1.0.0").unwrap();
+        assert!(models[0].is_directory && !models[0].is_downloaded);
+        assert!(models[1].download_files.is_empty());
+    }
+
+    #[test]
+    fn test_verify_rejects_garbage() {
+        assert!(verify(MANIFEST.as_bytes(), "bm90IGEgc2lnbmF0dXJl", "bm90IGEga2V5").is_err());
+    }
+}
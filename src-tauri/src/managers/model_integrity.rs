@@ -0,0 +1,157 @@
+//! Checks a downloaded model's files when it fails to load, so a truncated or damaged file
+//! can be named and downloaded again instead of surfacing the engine's opaque error. Files
+//! are compared with the SHA-256 the catalog lists; without one, a file can only be found
+//! missing or empty.
+
+use crate::managers::model::ModelInfo;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use specta::Type;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug, Default, Serialize, Type)]
+pub struct IntegrityReport {
+    pub model_id: String,
+    pub missing: Vec<String>,
+    /// Files whose hash or size is wrong
+    pub corrupt: Vec<String>,
+    /// Files present that the catalog has no hash for
+    pub unverified: Vec<String>,
+}
+
+impl IntegrityReport {
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.corrupt.is_empty()
+    }
+
+    /// The files to download again
+    pub fn damaged(&self) -> Vec<String> {
+        self.missing.iter().chain(&self.corrupt).cloned().collect()
+    }
+}
+
+/// The files a model is expected to have, as paths within it; a single-file model is just
+/// its file name.
+pub fn expected_files(model: &ModelInfo) -> Vec<String> {
+    if !model.is_directory {
+        return vec![model.filename.clone()];
+    }
+    let mut files = model.download_files.clone();
+    let mut hashed: Vec<&String> = model
+        .sha256
+        .keys()
+        .filter(|file| !files.contains(file))
+        .collect();
+    hashed.sort();
+    files.extend(hashed.into_iter().cloned());
+    files
+}
+
+/// Where `file` of `model` is on disk.
+pub fn file_path(models_dir: &Path, model: &ModelInfo, file: &str) -> PathBuf {
+    if model.is_directory {
+        models_dir.join(&model.filename).join(file)
+    } else {
+        models_dir.join(file)
+    }
+}
+
+/// Checks each expected file of `model` in `models_dir`.
+pub fn check(models_dir: &Path, model: &ModelInfo) -> IntegrityReport {
+    let mut report = IntegrityReport {
+        model_id: model.id.clone(),
+        ..Default::default()
+    };
+    for file in expected_files(model) {
+        let path = file_path(models_dir, model, &file);
+        let Ok(metadata) = path.metadata() else {
+            report.missing.push(file);
+            continue;
+        };
+        match model.sha256.get(&file) {
+            Some(expected) => match sha256_file(&path) {
+                Ok(hash) if hash.eq_ignore_ascii_case(expected) => {}
+                _ => report.corrupt.push(file),
+            },
+            None if metadata.len() == 0 => report.corrupt.push(file),
+            None => report.unverified.push(file),
+        }
+    }
+    report
+}
+
+/// Hex SHA-256 of a file, read in chunks so a multi-gigabyte model isn't loaded at once.
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::managers::model::{EngineType, Quantization};
+    use std::collections::HashMap;
+    use std::fs;
+
+    const ABC_SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    fn directory_model(sha256: HashMap<String, String>) -> ModelInfo {
+        ModelInfo {
+            id: "parakeet".to_string(),
+            name: "Parakeet".to_string(),
+            description: String::new(),
+            filename: "parakeet".to_string(),
+            url: None,
+            size_mb: 1,
+            is_downloaded: true,
+            is_downloading: false,
+            partial_size: 0,
+            is_directory: true,
+            engine_type: EngineType::Parakeet,
+            accuracy_score: 0.5,
+            speed_score: 0.5,
+            quantization: Quantization::Int8,
+            ram_mb: 1,
+            sha256,
+            download_files: vec![
+                "encoder.onnx".to_string(),
+                "decoder.onnx".to_string(),
+                "vocab.txt".to_string(),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_check_names_missing_and_corrupt_files() {
+        let dir = std::env::temp_dir().join(format!("handy-integrity-{}", std::process::id()));
+        let model_dir = dir.join("parakeet");
+        fs::create_dir_all(&model_dir).unwrap();
+        fs::write(model_dir.join("encoder.onnx"), "abc").unwrap();
+        fs::write(model_dir.join("decoder.onnx"), "abd").unwrap();
+
+        let model = directory_model(HashMap::from([
+            ("encoder.onnx".to_string(), ABC_SHA256.to_uppercase()),
+            ("decoder.onnx".to_string(), ABC_SHA256.to_string()),
+        ]));
+        let report = check(&dir, &model);
+        assert_eq!(report.missing, ["vocab.txt"]);
+        assert_eq!(report.corrupt, ["decoder.onnx"]);
+        assert!(!report.is_ok());
+        assert_eq!(report.damaged(), ["vocab.txt", "decoder.onnx"]);
+
+        fs::write(model_dir.join("decoder.onnx"), "abc").unwrap();
+        fs::write(model_dir.join("vocab.txt"), "a b c").unwrap();
+        let report = check(&dir, &model);
+        assert!(report.is_ok());
+        assert_eq!(report.unverified, ["vocab.txt"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,1094 @@
+//! On-device int8 quantization of ONNX models, so a downloaded fp32 Parakeet export can be
+//! turned into the int8 one instead of downloading that as well. It does to matrix
+//! multiplications what onnxruntime's dynamic quantization does: each `MatMul` by a constant
+//! float weight gets that weight as uint8 with a per-tensor scale and zero point, and
+//! quantizes its input as it runs (`DynamicQuantizeLinear`, `MatMulInteger`, then a rescale
+//! back to float). Every other node keeps its fp32 weights.
+//!
+//! ONNX models are protobuf. Only the messages that change are decoded, every other field
+//! is copied through as it is. Weights are streamed one at a time while writing, so memory
+//! stays near the size of the graph even when gigabytes of weights sit in an external data
+//! file. Those are written into the quantized model, which is small enough to hold them.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Oldest default domain opset with `DynamicQuantizeLinear`
+const MIN_OPSET: u64 = 11;
+/// Bytes of weights read at a time, a whole number of floats
+const CHUNK_BYTES: usize = 1 << 20;
+
+// Wire types
+const VARINT: u64 = 0;
+const FIXED64: u64 = 1;
+const LEN: u64 = 2;
+const FIXED32: u64 = 5;
+
+// Field numbers of the ONNX messages involved
+const MODEL_GRAPH: u32 = 7;
+const MODEL_OPSET_IMPORT: u32 = 8;
+const OPSET_DOMAIN: u32 = 1;
+const OPSET_VERSION: u32 = 2;
+const GRAPH_NODE: u32 = 1;
+const GRAPH_INITIALIZER: u32 = 5;
+const GRAPH_INPUT: u32 = 11;
+const GRAPH_OUTPUT: u32 = 12;
+const VALUE_INFO_NAME: u32 = 1;
+const NODE_INPUT: u32 = 1;
+const NODE_OUTPUT: u32 = 2;
+const NODE_NAME: u32 = 3;
+const NODE_OP_TYPE: u32 = 4;
+const NODE_ATTRIBUTE: u32 = 5;
+const NODE_DOMAIN: u32 = 7;
+const ATTRIBUTE_NAME: u32 = 1;
+const ATTRIBUTE_I: u32 = 3;
+const ATTRIBUTE_G: u32 = 6;
+const ATTRIBUTE_GRAPHS: u32 = 11;
+const ATTRIBUTE_TYPE: u32 = 20;
+const TENSOR_DIMS: u32 = 1;
+const TENSOR_DATA_TYPE: u32 = 2;
+const TENSOR_FLOAT_DATA: u32 = 4;
+const TENSOR_NAME: u32 = 8;
+const TENSOR_RAW_DATA: u32 = 9;
+const TENSOR_EXTERNAL_DATA: u32 = 13;
+const TENSOR_DATA_LOCATION: u32 = 14;
+const ENTRY_KEY: u32 = 1;
+const ENTRY_VALUE: u32 = 2;
+
+// Tensor element types and the attribute type used
+const FLOAT: u64 = 1;
+const UINT8: u64 = 2;
+const ATTRIBUTE_INT: u64 = 2;
+const DATA_LOCATION_EXTERNAL: u64 = 1;
+
+/// Quantizes the matrix multiplications of the model at `input` into a model written to
+/// `output`, which needs no external data file. Returns how many weights were quantized.
+pub fn quantize_dynamic(input: &Path, output: &Path) -> io::Result<usize> {
+    let model = fs::read(input)?;
+    let base_dir = input.parent().unwrap_or(Path::new("."));
+    let model_fields = fields(&model)?;
+    check_opset(&model_fields)?;
+    let graph = model_fields
+        .iter()
+        .find(|field| field.number == MODEL_GRAPH)
+        .and_then(Field::bytes)
+        .ok_or_else(|| invalid("the model has no graph"))?;
+    let plan = plan_graph(graph, base_dir)?;
+
+    let mut out = BufWriter::new(File::create(output)?);
+    let mut graph_written = false;
+    for field in &model_fields {
+        if field.number == MODEL_GRAPH && !graph_written {
+            let mut header = Vec::new();
+            put_tag(&mut header, MODEL_GRAPH, LEN);
+            put_varint(&mut header, plan.len());
+            out.write_all(&header)?;
+            plan.write(&mut out)?;
+            graph_written = true;
+        } else {
+            out.write_all(field.raw)?;
+        }
+    }
+    out.flush()?;
+    Ok(plan.quantized)
+}
+
+fn check_opset(model_fields: &[Field]) -> io::Result<()> {
+    for import in model_fields
+        .iter()
+        .filter(|field| field.number == MODEL_OPSET_IMPORT)
+        .filter_map(Field::bytes)
+    {
+        let import = fields(import)?;
+        let domain = string_field(&import, OPSET_DOMAIN).unwrap_or_default();
+        if domain.is_empty() || domain == "ai.onnx" {
+            let version = varint_field(&import, OPSET_VERSION).unwrap_or_default();
+            if version < MIN_OPSET {
+                return Err(invalid(&format!(
+                    "opset {} is too old to quantize, {} is needed",
+                    version, MIN_OPSET
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A decoded protobuf field
+#[derive(Clone, Copy)]
+struct Field<'a> {
+    number: u32,
+    value: Value<'a>,
+    /// The field as encoded, tag included
+    raw: &'a [u8],
+}
+
+#[derive(Clone, Copy)]
+enum Value<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed,
+}
+
+impl<'a> Field<'a> {
+    fn bytes(&self) -> Option<&'a [u8]> {
+        match self.value {
+            Value::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    fn string(&self) -> Option<&'a str> {
+        self.bytes()
+            .and_then(|bytes| std::str::from_utf8(bytes).ok())
+    }
+}
+
+fn fields(data: &[u8]) -> io::Result<Vec<Field<'_>>> {
+    let mut fields = Vec::new();
+    let mut pos = 0;
+    while pos < data.len() {
+        let start = pos;
+        let tag = read_varint(data, &mut pos)?;
+        let value = match tag & 7 {
+            VARINT => Value::Varint(read_varint(data, &mut pos)?),
+            LEN => {
+                let len = read_varint(data, &mut pos)? as usize;
+                let bytes = take(data, &mut pos, len)?;
+                Value::Bytes(bytes)
+            }
+            FIXED64 => {
+                take(data, &mut pos, 8)?;
+                Value::Fixed
+            }
+            FIXED32 => {
+                take(data, &mut pos, 4)?;
+                Value::Fixed
+            }
+            wire_type => {
+                return Err(invalid(&format!("unsupported wire type {}", wire_type)));
+            }
+        };
+        fields.push(Field {
+            number: (tag >> 3) as u32,
+            value,
+            raw: &data[start..pos],
+        });
+    }
+    Ok(fields)
+}
+
+fn read_varint(data: &[u8], pos: &mut usize) -> io::Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *data.get(*pos).ok_or_else(|| invalid("truncated varint"))?;
+        *pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid("varint too long"))
+}
+
+fn take<'a>(data: &'a [u8], pos: &mut usize, len: usize) -> io::Result<&'a [u8]> {
+    let end = pos
+        .checked_add(len)
+        .filter(|end| *end <= data.len())
+        .ok_or_else(|| invalid("truncated field"))?;
+    let bytes = &data[*pos..end];
+    *pos = end;
+    Ok(bytes)
+}
+
+fn string_field<'a>(fields: &[Field<'a>], number: u32) -> Option<&'a str> {
+    fields
+        .iter()
+        .find(|field| field.number == number)
+        .and_then(Field::string)
+}
+
+fn strings<'a>(fields: &[Field<'a>], number: u32) -> Vec<&'a str> {
+    fields
+        .iter()
+        .filter(|field| field.number == number)
+        .filter_map(Field::string)
+        .collect()
+}
+
+fn varint_field(fields: &[Field], number: u32) -> Option<u64> {
+    fields.iter().find_map(|field| match field.value {
+        Value::Varint(value) if field.number == number => Some(value),
+        _ => None,
+    })
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+struct Node<'a> {
+    name: &'a str,
+    op_type: &'a str,
+    domain: &'a str,
+    inputs: Vec<&'a str>,
+    outputs: Vec<&'a str>,
+}
+
+impl<'a> Node<'a> {
+    fn parse(fields: &[Field<'a>]) -> Self {
+        Self {
+            name: string_field(fields, NODE_NAME).unwrap_or_default(),
+            op_type: string_field(fields, NODE_OP_TYPE).unwrap_or_default(),
+            domain: string_field(fields, NODE_DOMAIN).unwrap_or_default(),
+            inputs: strings(fields, NODE_INPUT),
+            outputs: strings(fields, NODE_OUTPUT),
+        }
+    }
+
+    /// The constant weight of a plain `MatMul`
+    fn matmul_weight(&self) -> Option<&'a str> {
+        let plain = self.op_type == "MatMul" && matches!(self.domain, "" | "ai.onnx");
+        (plain && self.inputs.len() == 2 && self.outputs.len() == 1).then(|| self.inputs[1])
+    }
+}
+
+/// Names read by the nodes of the graphs nested in a node's attributes, such as the
+/// branches of an `If`, which can refer to the outer graph's initializers.
+fn subgraph_inputs<'a>(node: &[Field<'a>], names: &mut HashSet<&'a str>) -> io::Result<()> {
+    for attribute in node
+        .iter()
+        .filter(|field| field.number == NODE_ATTRIBUTE)
+        .filter_map(Field::bytes)
+    {
+        for graph in fields(attribute)?
+            .iter()
+            .filter(|field| matches!(field.number, ATTRIBUTE_G | ATTRIBUTE_GRAPHS))
+            .filter_map(Field::bytes)
+        {
+            for node in fields(graph)?
+                .iter()
+                .filter(|field| field.number == GRAPH_NODE)
+                .filter_map(Field::bytes)
+            {
+                let node = fields(node)?;
+                names.extend(strings(&node, NODE_INPUT));
+                subgraph_inputs(&node, names)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// An initializer, its data left where it is
+struct Tensor<'a> {
+    name: &'a str,
+    data_type: u64,
+    dims: Vec<u64>,
+    data: TensorData<'a>,
+}
+
+enum TensorData<'a> {
+    /// `raw_data`, or packed `float_data`, which is laid out the same
+    Inline(&'a [u8]),
+    External {
+        path: PathBuf,
+        offset: u64,
+        length: Option<u64>,
+    },
+    /// A layout left as it is
+    Other,
+}
+
+impl<'a> Tensor<'a> {
+    fn parse(tensor_fields: &[Field<'a>], base_dir: &Path) -> io::Result<Self> {
+        let mut dims = Vec::new();
+        for field in tensor_fields
+            .iter()
+            .filter(|field| field.number == TENSOR_DIMS)
+        {
+            match field.value {
+                Value::Varint(dim) => dims.push(dim),
+                Value::Bytes(packed) => {
+                    let mut pos = 0;
+                    while pos < packed.len() {
+                        dims.push(read_varint(packed, &mut pos)?);
+                    }
+                }
+                Value::Fixed => return Err(invalid("invalid tensor dimension")),
+            }
+        }
+        let external =
+            varint_field(tensor_fields, TENSOR_DATA_LOCATION) == Some(DATA_LOCATION_EXTERNAL);
+        let data = if external {
+            let mut entries = HashMap::new();
+            for entry in tensor_fields
+                .iter()
+                .filter(|field| field.number == TENSOR_EXTERNAL_DATA)
+                .filter_map(Field::bytes)
+            {
+                let entry = fields(entry)?;
+                if let (Some(key), Some(value)) = (
+                    string_field(&entry, ENTRY_KEY),
+                    string_field(&entry, ENTRY_VALUE),
+                ) {
+                    entries.insert(key, value);
+                }
+            }
+            let number = |key: &str| -> io::Result<Option<u64>> {
+                entries
+                    .get(key)
+                    .map(|value| value.parse().map_err(|_| invalid("invalid external data")))
+                    .transpose()
+            };
+            let location = entries
+                .get("location")
+                .ok_or_else(|| invalid("external data without a location"))?;
+            TensorData::External {
+                path: base_dir.join(location),
+                offset: number("offset")?.unwrap_or(0),
+                length: number("length")?,
+            }
+        } else if let Some(raw) = tensor_fields
+            .iter()
+            .find(|field| field.number == TENSOR_RAW_DATA)
+            .and_then(Field::bytes)
+        {
+            TensorData::Inline(raw)
+        } else {
+            match tensor_fields
+                .iter()
+                .find(|field| field.number == TENSOR_FLOAT_DATA)
+                .map(|field| field.value)
+            {
+                Some(Value::Bytes(packed)) => TensorData::Inline(packed),
+                _ => TensorData::Other,
+            }
+        };
+        Ok(Self {
+            name: string_field(tensor_fields, TENSOR_NAME).unwrap_or_default(),
+            data_type: varint_field(tensor_fields, TENSOR_DATA_TYPE).unwrap_or_default(),
+            dims,
+            data,
+        })
+    }
+
+    fn element_count(&self) -> u64 {
+        self.dims.iter().product()
+    }
+
+    /// Where the tensor's bytes are read from, `None` for a layout left as it is
+    fn source(&self) -> io::Result<Option<Source<'a>>> {
+        Ok(match &self.data {
+            TensorData::Inline(bytes) => Some(Source::Slice(bytes)),
+            TensorData::External {
+                path,
+                offset,
+                length,
+            } => {
+                let len = match (length, element_size(self.data_type)) {
+                    (Some(length), _) => *length,
+                    (None, Some(size)) => self.element_count() * size,
+                    (None, None) => return Err(invalid("external data of unknown length")),
+                };
+                Some(Source::File {
+                    path: path.clone(),
+                    offset: *offset,
+                    len,
+                })
+            }
+            TensorData::Other => None,
+        })
+    }
+}
+
+fn element_size(data_type: u64) -> Option<u64> {
+    match data_type {
+        // uint8, int8, bool
+        2 | 3 | 9 => Some(1),
+        // uint16, int16, float16, bfloat16
+        4 | 5 | 10 | 16 => Some(2),
+        // float, int32, uint32
+        1 | 6 | 12 => Some(4),
+        // int64, double, uint64
+        7 | 11 | 13 => Some(8),
+        _ => None,
+    }
+}
+
+/// The bytes of a tensor as stored
+enum Source<'a> {
+    Slice(&'a [u8]),
+    File {
+        path: PathBuf,
+        offset: u64,
+        len: u64,
+    },
+}
+
+impl Source<'_> {
+    fn len(&self) -> u64 {
+        match self {
+            Source::Slice(bytes) => bytes.len() as u64,
+            Source::File { len, .. } => *len,
+        }
+    }
+
+    fn for_each_chunk(&self, mut f: impl FnMut(&[u8]) -> io::Result<()>) -> io::Result<()> {
+        match self {
+            Source::Slice(bytes) => bytes.chunks(CHUNK_BYTES).try_for_each(f),
+            Source::File { path, offset, len } => {
+                let mut file = File::open(path)?;
+                file.seek(SeekFrom::Start(*offset))?;
+                let mut remaining = *len;
+                let mut buffer = vec![0; CHUNK_BYTES];
+                while remaining > 0 {
+                    let chunk = &mut buffer[..remaining.min(CHUNK_BYTES as u64) as usize];
+                    file.read_exact(chunk)?;
+                    f(chunk)?;
+                    remaining -= chunk.len() as u64;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn for_each_float(&self, mut f: impl FnMut(f32)) -> io::Result<()> {
+        if !self.len().is_multiple_of(4) {
+            return Err(invalid("float data of a length that isn't a multiple of 4"));
+        }
+        self.for_each_chunk(|chunk| {
+            for bytes in chunk.chunks_exact(4) {
+                f(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Asymmetric uint8 quantization of a weight, the range widened to include 0 so that it is
+/// exact
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Quantization {
+    scale: f32,
+    zero_point: u8,
+}
+
+impl Quantization {
+    fn for_range(min: f32, max: f32) -> Self {
+        let (min, max) = (min.min(0.0), max.max(0.0));
+        let scale = if max > min { (max - min) / 255.0 } else { 1.0 };
+        Self {
+            scale,
+            zero_point: (-min / scale).round().clamp(0.0, 255.0) as u8,
+        }
+    }
+
+    fn quantize(&self, value: f32) -> u8 {
+        (value / self.scale + f32::from(self.zero_point))
+            .round()
+            .clamp(0.0, 255.0) as u8
+    }
+}
+
+/// The rewritten graph, as pieces written in order
+struct GraphPlan<'a> {
+    pieces: Vec<Piece<'a>>,
+    quantized: usize,
+}
+
+enum Piece<'a> {
+    Copy(&'a [u8]),
+    Owned(Vec<u8>),
+    /// `header` then the tensor's bytes, quantized on the way when `quantization` is set
+    Tensor {
+        header: Vec<u8>,
+        source: Source<'a>,
+        quantization: Option<Quantization>,
+    },
+}
+
+impl Piece<'_> {
+    fn len(&self) -> u64 {
+        match self {
+            Piece::Copy(bytes) => bytes.len() as u64,
+            Piece::Owned(bytes) => bytes.len() as u64,
+            Piece::Tensor {
+                header,
+                source,
+                quantization,
+            } => header.len() as u64 + data_len(source, quantization.is_some()),
+        }
+    }
+}
+
+fn data_len(source: &Source, quantized: bool) -> u64 {
+    if quantized {
+        source.len() / 4
+    } else {
+        source.len()
+    }
+}
+
+impl GraphPlan<'_> {
+    fn len(&self) -> u64 {
+        self.pieces.iter().map(Piece::len).sum()
+    }
+
+    fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        for piece in &self.pieces {
+            match piece {
+                Piece::Copy(bytes) => out.write_all(bytes)?,
+                Piece::Owned(bytes) => out.write_all(bytes)?,
+                Piece::Tensor {
+                    header,
+                    source,
+                    quantization: None,
+                } => {
+                    out.write_all(header)?;
+                    source.for_each_chunk(|chunk| out.write_all(chunk))?;
+                }
+                Piece::Tensor {
+                    header,
+                    source,
+                    quantization: Some(quantization),
+                } => {
+                    out.write_all(header)?;
+                    let mut quantized = Vec::with_capacity(CHUNK_BYTES / 4);
+                    source.for_each_chunk(|chunk| {
+                        quantized.clear();
+                        quantized.extend(chunk.chunks_exact(4).map(|bytes| {
+                            quantization.quantize(f32::from_le_bytes([
+                                bytes[0], bytes[1], bytes[2], bytes[3],
+                            ]))
+                        }));
+                        out.write_all(&quantized)
+                    })?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn plan_graph<'a>(graph: &'a [u8], base_dir: &Path) -> io::Result<GraphPlan<'a>> {
+    let graph_fields = fields(graph)?;
+
+    let mut nodes = Vec::new();
+    let mut pinned = HashSet::new();
+    for field in &graph_fields {
+        match field.number {
+            GRAPH_NODE => {
+                let node = fields(field.bytes().unwrap_or_default())?;
+                subgraph_inputs(&node, &mut pinned)?;
+                nodes.push(Node::parse(&node));
+            }
+            // Graph inputs can override an initializer, and outputs are read as they are
+            GRAPH_INPUT | GRAPH_OUTPUT => {
+                let value_info = fields(field.bytes().unwrap_or_default())?;
+                pinned.extend(string_field(&value_info, VALUE_INFO_NAME));
+            }
+            _ => {}
+        }
+    }
+    // A weight is quantized only when every node reading it is a `MatMul` multiplying by it
+    let mut reads: HashMap<&str, (usize, usize)> = HashMap::new();
+    for node in &nodes {
+        for &input in &node.inputs {
+            reads.entry(input).or_default().0 += 1;
+        }
+        if let Some(weight) = node.matmul_weight() {
+            reads.entry(weight).or_default().1 += 1;
+        }
+    }
+
+    let mut tensors = HashMap::new();
+    for field in &graph_fields {
+        if field.number == GRAPH_INITIALIZER {
+            let tensor = Tensor::parse(&fields(field.bytes().unwrap_or_default())?, base_dir)?;
+            tensors.insert(tensor.name, tensor);
+        }
+    }
+    let mut quantizations = HashMap::new();
+    for (name, tensor) in &tensors {
+        let only_matmuls = reads
+            .get(name)
+            .is_some_and(|(all, matmuls)| *matmuls > 0 && all == matmuls);
+        if !only_matmuls
+            || pinned.contains(name)
+            || tensor.data_type != FLOAT
+            || tensor.dims.len() != 2
+        {
+            continue;
+        }
+        let Some(source) = tensor.source()? else {
+            continue;
+        };
+        if source.len() != tensor.element_count() * 4 {
+            return Err(invalid(&format!(
+                "the size of {} doesn't match its shape",
+                name
+            )));
+        }
+        let (mut min, mut max) = (f32::INFINITY, f32::NEG_INFINITY);
+        source.for_each_float(|value| {
+            min = min.min(value);
+            max = max.max(value);
+        })?;
+        quantizations.insert(*name, Quantization::for_range(min, max));
+    }
+
+    let mut pieces = Vec::new();
+    for field in &graph_fields {
+        match field.number {
+            GRAPH_NODE => {
+                let node = Node::parse(&fields(field.bytes().unwrap_or_default())?);
+                match node
+                    .matmul_weight()
+                    .filter(|w| quantizations.contains_key(w))
+                {
+                    Some(weight) => pieces.push(Piece::Owned(quantized_matmul(&node, weight))),
+                    None => pieces.push(Piece::Copy(field.raw)),
+                }
+            }
+            GRAPH_INITIALIZER => {
+                let tensor_fields = fields(field.bytes().unwrap_or_default())?;
+                let name = string_field(&tensor_fields, TENSOR_NAME).unwrap_or_default();
+                let tensor = &tensors[name];
+                if let Some(quantization) = quantizations.get(name) {
+                    pieces.extend(quantized_weight(tensor, *quantization)?);
+                } else if matches!(tensor.data, TensorData::External { .. }) {
+                    pieces.push(inlined(&tensor_fields, tensor)?);
+                } else {
+                    pieces.push(Piece::Copy(field.raw));
+                }
+            }
+            _ => pieces.push(Piece::Copy(field.raw)),
+        }
+    }
+    Ok(GraphPlan {
+        pieces,
+        quantized: quantizations.len(),
+    })
+}
+
+/// The nodes replacing `MatMul(A, W) -> Y`: `A` is quantized as the model runs, multiplied
+/// as integers by the quantized `W`, and the product scaled back to float as `Y`.
+fn quantized_matmul(node: &Node, weight: &str) -> Vec<u8> {
+    let input = node.inputs[0];
+    let output = node.outputs[0];
+    let name = if node.name.is_empty() {
+        output
+    } else {
+        node.name
+    };
+    let quantized_input = format!("{}_quantized_input", output);
+    let input_scale = format!("{}_input_scale", output);
+    let input_zero_point = format!("{}_input_zero_point", output);
+    let product = format!("{}_int32", output);
+    let product_float = format!("{}_float", output);
+    let scale = format!("{}_scale", output);
+    let weight_quantized = format!("{}_quantized", weight);
+    let weight_scale = format!("{}_scale", weight);
+    let weight_zero_point = format!("{}_zero_point", weight);
+
+    let mut out = Vec::new();
+    let nodes: [(&str, &str, Vec<&str>, Vec<&str>); 5] = [
+        (
+            "_quantize_input",
+            "DynamicQuantizeLinear",
+            vec![input],
+            vec![&quantized_input, &input_scale, &input_zero_point]
+                .into_iter()
+                .map(String::as_str)
+                .collect(),
+        ),
+        (
+            "_matmul",
+            "MatMulInteger",
+            vec![
+                quantized_input.as_str(),
+                &weight_quantized,
+                &input_zero_point,
+                &weight_zero_point,
+            ],
+            vec![product.as_str()],
+        ),
+        (
+            "_cast",
+            "Cast",
+            vec![product.as_str()],
+            vec![product_float.as_str()],
+        ),
+        (
+            "_scale",
+            "Mul",
+            vec![input_scale.as_str(), &weight_scale],
+            vec![scale.as_str()],
+        ),
+        (
+            "_rescale",
+            "Mul",
+            vec![product_float.as_str(), &scale],
+            vec![output],
+        ),
+    ];
+    for (suffix, op_type, inputs, outputs) in nodes {
+        let mut node = Vec::new();
+        for input in inputs {
+            put_len_field(&mut node, NODE_INPUT, input.as_bytes());
+        }
+        for output in outputs {
+            put_len_field(&mut node, NODE_OUTPUT, output.as_bytes());
+        }
+        put_len_field(
+            &mut node,
+            NODE_NAME,
+            format!("{}{}", name, suffix).as_bytes(),
+        );
+        put_len_field(&mut node, NODE_OP_TYPE, op_type.as_bytes());
+        if op_type == "Cast" {
+            let mut to = Vec::new();
+            put_len_field(&mut to, ATTRIBUTE_NAME, b"to");
+            put_varint_field(&mut to, ATTRIBUTE_I, FLOAT);
+            put_varint_field(&mut to, ATTRIBUTE_TYPE, ATTRIBUTE_INT);
+            put_len_field(&mut node, NODE_ATTRIBUTE, &to);
+        }
+        put_len_field(&mut out, GRAPH_NODE, &node);
+    }
+    out
+}
+
+/// The uint8 weight, its scale and its zero point, as initializers
+fn quantized_weight<'a>(
+    tensor: &Tensor<'a>,
+    quantization: Quantization,
+) -> io::Result<[Piece<'a>; 3]> {
+    let source = tensor
+        .source()?
+        .ok_or_else(|| invalid("a quantized weight without data"))?;
+    let mut weight_fields = Vec::new();
+    for dim in &tensor.dims {
+        put_varint_field(&mut weight_fields, TENSOR_DIMS, *dim);
+    }
+    put_varint_field(&mut weight_fields, TENSOR_DATA_TYPE, UINT8);
+    put_len_field(
+        &mut weight_fields,
+        TENSOR_NAME,
+        format!("{}_quantized", tensor.name).as_bytes(),
+    );
+    let header = tensor_header(weight_fields, data_len(&source, true));
+
+    let scalar = |suffix: &str, data_type: u64, raw: &[u8]| {
+        let mut tensor_fields = Vec::new();
+        put_varint_field(&mut tensor_fields, TENSOR_DATA_TYPE, data_type);
+        put_len_field(
+            &mut tensor_fields,
+            TENSOR_NAME,
+            format!("{}{}", tensor.name, suffix).as_bytes(),
+        );
+        put_len_field(&mut tensor_fields, TENSOR_RAW_DATA, raw);
+        let mut field = Vec::new();
+        put_len_field(&mut field, GRAPH_INITIALIZER, &tensor_fields);
+        Piece::Owned(field)
+    };
+    Ok([
+        Piece::Tensor {
+            header,
+            source,
+            quantization: Some(quantization),
+        },
+        scalar("_scale", FLOAT, &quantization.scale.to_le_bytes()),
+        scalar("_zero_point", UINT8, &[quantization.zero_point]),
+    ])
+}
+
+/// An initializer kept as it is but with its external data written into the model
+fn inlined<'a>(tensor_fields: &[Field], tensor: &Tensor<'a>) -> io::Result<Piece<'a>> {
+    let source = tensor
+        .source()?
+        .ok_or_else(|| invalid("external data without a location"))?;
+    let mut kept = Vec::new();
+    for field in tensor_fields {
+        if !matches!(field.number, TENSOR_EXTERNAL_DATA | TENSOR_DATA_LOCATION) {
+            kept.extend_from_slice(field.raw);
+        }
+    }
+    let header = tensor_header(kept, source.len());
+    Ok(Piece::Tensor {
+        header,
+        source,
+        quantization: None,
+    })
+}
+
+/// The initializer field up to its data: the tensor's other fields and the `raw_data` tag
+/// and length
+fn tensor_header(mut fields: Vec<u8>, data_len: u64) -> Vec<u8> {
+    put_tag(&mut fields, TENSOR_RAW_DATA, LEN);
+    put_varint(&mut fields, data_len);
+    let mut header = Vec::new();
+    put_tag(&mut header, GRAPH_INITIALIZER, LEN);
+    put_varint(&mut header, fields.len() as u64 + data_len);
+    header.extend_from_slice(&fields);
+    header
+}
+
+fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn put_tag(out: &mut Vec<u8>, number: u32, wire_type: u64) {
+    put_varint(out, (u64::from(number) << 3) | wire_type);
+}
+
+fn put_varint_field(out: &mut Vec<u8>, number: u32, value: u64) {
+    put_tag(out, number, VARINT);
+    put_varint(out, value);
+}
+
+fn put_len_field(out: &mut Vec<u8>, number: u32, bytes: &[u8]) {
+    put_tag(out, number, LEN);
+    put_varint(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(build: impl FnOnce(&mut Vec<u8>)) -> Vec<u8> {
+        let mut out = Vec::new();
+        build(&mut out);
+        out
+    }
+
+    fn float_tensor(name: &str, dims: &[u64], values: &[f32]) -> Vec<u8> {
+        message(|out| {
+            for dim in dims {
+                put_varint_field(out, TENSOR_DIMS, *dim);
+            }
+            put_varint_field(out, TENSOR_DATA_TYPE, FLOAT);
+            put_len_field(out, TENSOR_NAME, name.as_bytes());
+            let raw: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+            put_len_field(out, TENSOR_RAW_DATA, &raw);
+        })
+    }
+
+    fn external_tensor(name: &str, dims: &[u64], location: &str) -> Vec<u8> {
+        message(|out| {
+            for dim in dims {
+                put_varint_field(out, TENSOR_DIMS, *dim);
+            }
+            put_varint_field(out, TENSOR_DATA_TYPE, FLOAT);
+            put_len_field(out, TENSOR_NAME, name.as_bytes());
+            for (key, value) in [("location", location), ("offset", "0")] {
+                let entry = message(|entry| {
+                    put_len_field(entry, ENTRY_KEY, key.as_bytes());
+                    put_len_field(entry, ENTRY_VALUE, value.as_bytes());
+                });
+                put_len_field(out, TENSOR_EXTERNAL_DATA, &entry);
+            }
+            put_varint_field(out, TENSOR_DATA_LOCATION, DATA_LOCATION_EXTERNAL);
+        })
+    }
+
+    fn node(op_type: &str, inputs: &[&str], outputs: &[&str]) -> Vec<u8> {
+        message(|out| {
+            for input in inputs {
+                put_len_field(out, NODE_INPUT, input.as_bytes());
+            }
+            for output in outputs {
+                put_len_field(out, NODE_OUTPUT, output.as_bytes());
+            }
+            put_len_field(out, NODE_OP_TYPE, op_type.as_bytes());
+        })
+    }
+
+    fn model(opset: u64, nodes: &[Vec<u8>], initializers: &[Vec<u8>], inputs: &[&str]) -> Vec<u8> {
+        let graph = message(|out| {
+            for node in nodes {
+                put_len_field(out, GRAPH_NODE, node);
+            }
+            for initializer in initializers {
+                put_len_field(out, GRAPH_INITIALIZER, initializer);
+            }
+            for input in inputs {
+                let value_info =
+                    message(|info| put_len_field(info, VALUE_INFO_NAME, input.as_bytes()));
+                put_len_field(out, GRAPH_INPUT, &value_info);
+            }
+        });
+        message(|out| {
+            put_varint_field(out, 1, 8);
+            let import = message(|import| put_varint_field(import, OPSET_VERSION, opset));
+            put_len_field(out, MODEL_OPSET_IMPORT, &import);
+            put_len_field(out, MODEL_GRAPH, &graph);
+        })
+    }
+
+    /// Quantizes `model` in a fresh directory holding `files`, returning the output
+    fn quantize(test: &str, model: &[u8], files: &[(&str, &[u8])]) -> io::Result<(usize, Vec<u8>)> {
+        let dir =
+            std::env::temp_dir().join(format!("handy-quantize-{}-{}", test, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for (name, data) in files {
+            fs::write(dir.join(name), data).unwrap();
+        }
+        fs::write(dir.join("model.onnx"), model).unwrap();
+        let result = quantize_dynamic(&dir.join("model.onnx"), &dir.join("model.int8.onnx"));
+        let output = fs::read(dir.join("model.int8.onnx")).unwrap_or_default();
+        fs::remove_dir_all(&dir).unwrap();
+        result.map(|quantized| (quantized, output))
+    }
+
+    /// The op types of the output's nodes and its initializers by name
+    fn graph_of(model: &[u8]) -> (Vec<Vec<u8>>, HashMap<String, Vec<u8>>) {
+        let model_fields = fields(model).unwrap();
+        let graph = model_fields
+            .iter()
+            .find(|field| field.number == MODEL_GRAPH)
+            .and_then(Field::bytes)
+            .unwrap();
+        let mut nodes = Vec::new();
+        let mut initializers = HashMap::new();
+        for field in fields(graph).unwrap() {
+            let bytes = field.bytes().unwrap().to_vec();
+            match field.number {
+                GRAPH_NODE => nodes.push(bytes),
+                GRAPH_INITIALIZER => {
+                    let name = string_field(&fields(&bytes).unwrap(), TENSOR_NAME)
+                        .unwrap()
+                        .to_string();
+                    initializers.insert(name, bytes);
+                }
+                _ => {}
+            }
+        }
+        (nodes, initializers)
+    }
+
+    fn raw_data(tensor: &[u8]) -> Vec<u8> {
+        match Tensor::parse(&fields(tensor).unwrap(), Path::new("."))
+            .unwrap()
+            .data
+        {
+            TensorData::Inline(raw) => raw.to_vec(),
+            _ => panic!("the tensor's data isn't inline"),
+        }
+    }
+
+    #[test]
+    fn test_quantization_keeps_zero_exact() {
+        let quantization = Quantization::for_range(-1.0, 3.0);
+        assert_eq!(quantization.zero_point, 64);
+        assert_eq!(quantization.quantize(0.0), 64);
+        assert_eq!(quantization.quantize(-1.0), 0);
+        assert_eq!(quantization.quantize(3.0), 255);
+        // A range that excludes 0 is widened to include it
+        assert_eq!(Quantization::for_range(0.5, 2.0).zero_point, 0);
+        assert_eq!(Quantization::for_range(0.0, 0.0).scale, 1.0);
+    }
+
+    #[test]
+    fn test_quantize_dynamic_rewrites_matmuls() {
+        let weights = [0.5, -1.0, 0.25, 2.0, 0.0, -0.75];
+        let bias: Vec<u8> = [0.1f32, 0.2, 0.3]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        let model = model(
+            17,
+            &[
+                node("MatMul", &["x", "w"], &["y"]),
+                node("Add", &["y", "bias"], &["z"]),
+            ],
+            &[
+                float_tensor("w", &[2, 3], &weights),
+                external_tensor("bias", &[3], "weights.data"),
+            ],
+            &["x"],
+        );
+        let (quantized, output) =
+            quantize("rewrite", &model, &[("weights.data", bias.as_slice())]).unwrap();
+        assert_eq!(quantized, 1);
+
+        let (nodes, initializers) = graph_of(&output);
+        let nodes: Vec<Node> = nodes
+            .iter()
+            .map(|node| Node::parse(&fields(node).unwrap()))
+            .collect::<Vec<_>>();
+        let op_types: Vec<&str> = nodes.iter().map(|node| node.op_type).collect();
+        assert_eq!(
+            op_types,
+            [
+                "DynamicQuantizeLinear",
+                "MatMulInteger",
+                "Cast",
+                "Mul",
+                "Mul",
+                "Add"
+            ]
+        );
+        assert_eq!(nodes[0].inputs, ["x"]);
+        assert_eq!(
+            nodes[1].inputs,
+            [
+                "y_quantized_input",
+                "w_quantized",
+                "y_input_zero_point",
+                "w_zero_point"
+            ]
+        );
+        assert_eq!(nodes[4].outputs, ["y"]);
+
+        // The weight is replaced by its uint8 form, within half a step of the original
+        assert!(!initializers.contains_key("w"));
+        let scale = f32::from_le_bytes(raw_data(&initializers["w_scale"]).try_into().unwrap());
+        let zero_point = raw_data(&initializers["w_zero_point"])[0];
+        let quantized_weight = &initializers["w_quantized"];
+        let tensor_fields = fields(quantized_weight).unwrap();
+        let tensor = Tensor::parse(&tensor_fields, Path::new(".")).unwrap();
+        assert_eq!(
+            (tensor.data_type, tensor.dims.as_slice()),
+            (UINT8, [2, 3].as_slice())
+        );
+        for (q, w) in raw_data(quantized_weight).iter().zip(weights) {
+            let dequantized = (f32::from(*q) - f32::from(zero_point)) * scale;
+            assert!((dequantized - w).abs() <= scale / 2.0 + f32::EPSILON);
+        }
+
+        // Weights from the external data file are written into the model
+        assert_eq!(raw_data(&initializers["bias"]), bias);
+    }
+
+    #[test]
+    fn test_quantize_dynamic_keeps_weights_read_elsewhere() {
+        let weights = [1.0, 2.0, 3.0, 4.0];
+        let model = model(
+            17,
+            &[
+                node("MatMul", &["x", "shared"], &["y"]),
+                node("Add", &["y", "shared"], &["z"]),
+                node("MatMul", &["z", "overridable"], &["out"]),
+            ],
+            &[
+                float_tensor("shared", &[2, 2], &weights),
+                float_tensor("overridable", &[2, 2], &weights),
+            ],
+            &["x", "overridable"],
+        );
+        let (quantized, output) = quantize("keep", &model, &[]).unwrap();
+        assert_eq!(quantized, 0);
+        assert_eq!(output, model);
+
+        let old = self::model(10, &[node("MatMul", &["x", "w"], &["y"])], &[], &["x"]);
+        assert!(quantize("old-opset", &old, &[]).is_err());
+    }
+}
@@ -0,0 +1,179 @@
+//! Disk usage of the downloaded models and the optional storage limit. Each model's last
+//! use is recorded when its files are resolved for loading, and when the models take more
+//! than the limit, the least recently used ones are proposed for deletion. Nothing is
+//! deleted until the user confirms the proposal.
+
+use anyhow::Result;
+use log::warn;
+use serde::Serialize;
+use specta::Type;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+pub const LAST_USED_FILE: &str = "model_last_used.json";
+
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct ModelDiskUsage {
+    pub model_id: String,
+    pub name: String,
+    /// Size on disk, partial downloads included
+    pub bytes: u64,
+    /// Unix time the model was last loaded, `None` when it never was since this was tracked
+    pub last_used: Option<i64>,
+}
+
+/// Models that would bring the total under the storage limit, sent with
+/// `model-eviction-proposed` for the user to confirm.
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct EvictionProposal {
+    pub limit_bytes: u64,
+    pub total_bytes: u64,
+    pub models: Vec<ModelDiskUsage>,
+}
+
+/// When each model was last used, kept in a file next to the settings.
+pub struct LastUsed {
+    path: PathBuf,
+    times: Mutex<BTreeMap<String, i64>>,
+}
+
+impl LastUsed {
+    pub fn load(path: PathBuf) -> Self {
+        let times = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                warn!("Ignoring unreadable model usage times: {}", e);
+                BTreeMap::new()
+            }),
+            Err(_) => BTreeMap::new(),
+        };
+        Self {
+            path,
+            times: Mutex::new(times),
+        }
+    }
+
+    pub fn get(&self, model_id: &str) -> Option<i64> {
+        self.times.lock().unwrap().get(model_id).copied()
+    }
+
+    pub fn touch(&self, model_id: &str) {
+        self.update(|times| {
+            times.insert(model_id.to_string(), chrono::Utc::now().timestamp());
+        });
+    }
+
+    pub fn forget(&self, model_id: &str) {
+        self.update(|times| {
+            times.remove(model_id);
+        });
+    }
+
+    fn update(&self, change: impl FnOnce(&mut BTreeMap<String, i64>)) {
+        let mut times = self.times.lock().unwrap();
+        change(&mut times);
+        if let Err(e) = save(&self.path, &times) {
+            warn!("Failed to save model usage times: {}", e);
+        }
+    }
+}
+
+fn save(path: &Path, times: &BTreeMap<String, i64>) -> Result<()> {
+    fs::write(path, serde_json::to_string(times)?)?;
+    Ok(())
+}
+
+/// Size of a file, or of everything in a directory.
+pub fn disk_size(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| disk_size(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// The least recently used models to delete so `usage` fits in `limit_bytes`, never one
+/// of `keep`. Models never used since tracking began go first. When even deleting every
+/// other model isn't enough, all of them are proposed.
+pub fn eviction_candidates(
+    usage: &[ModelDiskUsage],
+    limit_bytes: u64,
+    keep: &[&str],
+) -> Vec<ModelDiskUsage> {
+    let mut total: u64 = usage.iter().map(|model| model.bytes).sum();
+    let mut candidates: Vec<&ModelDiskUsage> = usage
+        .iter()
+        .filter(|model| !keep.contains(&model.model_id.as_str()))
+        .collect();
+    candidates.sort_by_key(|model| model.last_used.unwrap_or(i64::MIN));
+
+    let mut evicted = Vec::new();
+    for model in candidates {
+        if total <= limit_bytes {
+            break;
+        }
+        total -= model.bytes;
+        evicted.push(model.clone());
+    }
+    evicted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(id: &str, gb: u64, last_used: Option<i64>) -> ModelDiskUsage {
+        ModelDiskUsage {
+            model_id: id.to_string(),
+            name: id.to_string(),
+            bytes: gb << 30,
+            last_used,
+        }
+    }
+
+    #[test]
+    fn test_eviction_candidates_least_recently_used_first() {
+        let usage = [
+            model("small", 1, Some(300)),
+            model("medium", 2, Some(100)),
+            model("large", 3, None),
+            model("parakeet", 1, Some(200)),
+        ];
+        let ids = |evicted: Vec<ModelDiskUsage>| -> Vec<String> {
+            evicted.into_iter().map(|model| model.model_id).collect()
+        };
+
+        assert!(eviction_candidates(&usage, 7 << 30, &[]).is_empty());
+        assert_eq!(ids(eviction_candidates(&usage, 5 << 30, &[])), ["large"]);
+        assert_eq!(
+            ids(eviction_candidates(&usage, 2 << 30, &["large"])),
+            ["medium", "parakeet", "small"]
+        );
+        assert_eq!(
+            ids(eviction_candidates(&usage, 3 << 30, &["small"])),
+            ["large", "medium"]
+        );
+    }
+
+    #[test]
+    fn test_disk_size_counts_directories() {
+        let dir = std::env::temp_dir().join(format!("handy-model-size-{}", std::process::id()));
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(dir.join("a.onnx"), [0u8; 100]).unwrap();
+        fs::write(dir.join("nested/b.txt"), [0u8; 28]).unwrap();
+        assert_eq!(disk_size(&dir), 128);
+        assert_eq!(disk_size(&dir.join("a.onnx")), 100);
+        assert_eq!(disk_size(&dir.join("missing")), 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,457 @@
+//! Text plugins: WebAssembly modules dropped into the `plugins` folder of the app data
+//! directory, each run as a step of the text pipeline's plugin stage, in file name order.
+//! A module found in the folder stays disabled until the user enables it.
+//!
+//! A plugin exports its `memory` and two functions:
+//! - `alloc(len: i32) -> i32` returns where the host may write `len` bytes of input
+//! - `transform(ptr: i32, len: i32) -> i64` reads the UTF-8 text at `ptr` and returns where
+//!   its output is, the pointer in the high 32 bits and the length in the low ones
+//!
+//! Modules built for WASI get its imports, but with no files, environment, arguments or
+//! network, and their output streams go nowhere. Each call runs in a fresh instance with a
+//! memory cap and is interrupted at the plugin's timeout. A plugin that fails leaves the
+//! text as it was.
+
+use crate::settings::{get_settings, AppSettings, PluginConfig};
+use anyhow::{anyhow, Result};
+use log::{debug, warn};
+use serde::Serialize;
+use specta::Type;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager};
+use wasmtime::{Config, Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder, Trap};
+use wasmtime_wasi::preview1::{self, WasiP1Ctx};
+use wasmtime_wasi::WasiCtxBuilder;
+
+pub const PLUGINS_DIR: &str = "plugins";
+
+/// Timeout of plugins without one set
+pub const DEFAULT_PLUGIN_TIMEOUT_MS: u64 = 200;
+
+/// Memory a plugin instance may grow to
+const MAX_PLUGIN_MEMORY: usize = 64 * 1024 * 1024;
+
+/// Longest output a plugin may return, far beyond any dictation
+const MAX_PLUGIN_OUTPUT: usize = 1024 * 1024;
+
+/// Interval of the epoch the timeouts are counted in
+const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct PluginInfo {
+    /// File name without the `.wasm` extension
+    pub id: String,
+    pub path: String,
+    pub enabled: bool,
+    pub timeout_ms: u64,
+    /// Why the module can't be used, e.g. a missing export
+    pub error: Option<String>,
+}
+
+struct PluginState {
+    wasi: WasiP1Ctx,
+    limits: StoreLimits,
+}
+
+/// Compiles and runs plugins. While a plugin runs, a thread advances the engine's epoch,
+/// which is what the timeouts are measured against.
+pub struct PluginRuntime {
+    engine: Engine,
+    linker: Linker<PluginState>,
+    ticker: EpochTicker,
+}
+
+#[derive(Default)]
+struct TickerState {
+    /// Plugin calls in progress
+    active: usize,
+    running: bool,
+}
+
+/// Advances an engine's epoch every [`EPOCH_TICK`], only while some call needs it so an
+/// idle app isn't woken up.
+struct EpochTicker {
+    engine: Engine,
+    state: Arc<Mutex<TickerState>>,
+}
+
+impl EpochTicker {
+    fn new(engine: Engine) -> Self {
+        Self {
+            engine,
+            state: Arc::new(Mutex::new(TickerState::default())),
+        }
+    }
+
+    /// Keeps the epoch advancing until the returned guard is dropped.
+    fn start(&self) -> TickGuard {
+        let mut state = self.state.lock().unwrap();
+        state.active += 1;
+        if !state.running {
+            state.running = true;
+            let engine = self.engine.clone();
+            let shared = self.state.clone();
+            std::thread::spawn(move || loop {
+                std::thread::sleep(EPOCH_TICK);
+                let mut state = shared.lock().unwrap();
+                if state.active == 0 {
+                    state.running = false;
+                    break;
+                }
+                drop(state);
+                engine.increment_epoch();
+            });
+        }
+        TickGuard(self.state.clone())
+    }
+}
+
+struct TickGuard(Arc<Mutex<TickerState>>);
+
+impl Drop for TickGuard {
+    fn drop(&mut self) {
+        self.0.lock().unwrap().active -= 1;
+    }
+}
+
+impl PluginRuntime {
+    pub fn new() -> Result<Self> {
+        let mut config = Config::new();
+        config.epoch_interruption(true);
+        let engine = Engine::new(&config)?;
+        let mut linker = Linker::new(&engine);
+        preview1::add_to_linker_sync(&mut linker, |state: &mut PluginState| &mut state.wasi)?;
+        Ok(Self {
+            ticker: EpochTicker::new(engine.clone()),
+            engine,
+            linker,
+        })
+    }
+
+    /// Compiles a module and checks it has the plugin exports.
+    pub fn compile(&self, bytes: &[u8]) -> Result<Module> {
+        let module = Module::new(&self.engine, bytes)?;
+        for export in ["memory", "alloc", "transform"] {
+            if module.get_export(export).is_none() {
+                return Err(anyhow!("The module doesn't export `{}`", export));
+            }
+        }
+        Ok(module)
+    }
+
+    /// Runs `text` through a plugin, in a fresh instance it gets `timeout` to finish in.
+    pub fn run(&self, module: &Module, text: &str, timeout: Duration) -> Result<String> {
+        let state = PluginState {
+            wasi: WasiCtxBuilder::new().build_p1(),
+            limits: StoreLimitsBuilder::new()
+                .memory_size(MAX_PLUGIN_MEMORY)
+                .instances(1)
+                .build(),
+        };
+        let mut store = Store::new(&self.engine, state);
+        store.limiter(|state| &mut state.limits);
+        store.set_epoch_deadline(timeout.as_millis().div_ceil(EPOCH_TICK.as_millis()) as u64);
+
+        let _ticking = self.ticker.start();
+        let result = self.call(&mut store, module, text);
+        result.map_err(|e| match e.downcast_ref::<Trap>() {
+            Some(Trap::Interrupt) => anyhow!("Timed out after {} ms", timeout.as_millis()),
+            _ => e,
+        })
+    }
+
+    fn call(&self, store: &mut Store<PluginState>, module: &Module, text: &str) -> Result<String> {
+        let instance = self.linker.instantiate(&mut *store, module)?;
+        // Reactor modules set themselves up in `_initialize`
+        if let Ok(initialize) = instance.get_typed_func::<(), ()>(&mut *store, "_initialize") {
+            initialize.call(&mut *store, ())?;
+        }
+        let memory = instance
+            .get_memory(&mut *store, "memory")
+            .ok_or_else(|| anyhow!("The module doesn't export its memory"))?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut *store, "alloc")?;
+        let transform = instance.get_typed_func::<(i32, i32), i64>(&mut *store, "transform")?;
+
+        let input = text.as_bytes();
+        let ptr = alloc.call(&mut *store, input.len() as i32)?;
+        memory.write(&mut *store, ptr as u32 as usize, input)?;
+        let packed = transform.call(&mut *store, (ptr, input.len() as i32))?;
+
+        let (out_ptr, out_len) = unpack(packed);
+        // Checked before allocating, the length is whatever the plugin claims
+        if out_len > MAX_PLUGIN_OUTPUT {
+            return Err(anyhow!(
+                "The output is {} bytes, more than the {} allowed",
+                out_len,
+                MAX_PLUGIN_OUTPUT
+            ));
+        }
+        if out_ptr
+            .checked_add(out_len)
+            .is_none_or(|end| end > memory.data_size(&*store))
+        {
+            return Err(anyhow!("The output lies outside the module's memory"));
+        }
+        let mut output = vec![0; out_len];
+        memory.read(&*store, out_ptr, &mut output)?;
+        String::from_utf8(output).map_err(|_| anyhow!("The output isn't valid UTF-8"))
+    }
+}
+
+/// Splits `transform`'s result into the output's pointer and length.
+fn unpack(packed: i64) -> (usize, usize) {
+    let packed = packed as u64;
+    ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize)
+}
+
+struct CompiledPlugin {
+    modified: SystemTime,
+    module: Result<Module, String>,
+}
+
+pub struct PluginManager {
+    app_handle: AppHandle,
+    dir: PathBuf,
+    runtime: PluginRuntime,
+    /// Modules by plugin id, compiled again when their file changes
+    compiled: Mutex<HashMap<String, CompiledPlugin>>,
+}
+
+impl PluginManager {
+    pub fn new(app_handle: &AppHandle) -> Result<Self> {
+        let dir = app_handle.path().app_data_dir()?.join(PLUGINS_DIR);
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            app_handle: app_handle.clone(),
+            dir,
+            runtime: PluginRuntime::new()?,
+            compiled: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The plugins in the folder, in the order they run, each compiled to report errors.
+    pub fn list(&self) -> Vec<PluginInfo> {
+        let settings = get_settings(&self.app_handle);
+        self.plugin_files()
+            .into_iter()
+            .map(|(id, path)| {
+                let config = plugin_config(&settings, &id);
+                let error = self.module(&id, &path).err();
+                PluginInfo {
+                    path: path.to_string_lossy().to_string(),
+                    enabled: config.enabled,
+                    timeout_ms: config.timeout_ms,
+                    error,
+                    id,
+                }
+            })
+            .collect()
+    }
+
+    /// Runs `text` through each enabled plugin in turn.
+    pub fn run_all(&self, text: &str, settings: &AppSettings) -> String {
+        let mut text = text.to_string();
+        for (id, path) in self.plugin_files() {
+            let config = plugin_config(settings, &id);
+            if !config.enabled {
+                continue;
+            }
+            let module = match self.module(&id, &path) {
+                Ok(module) => module,
+                Err(e) => {
+                    debug!("Skipping plugin {}: {}", id, e);
+                    continue;
+                }
+            };
+            match self
+                .runtime
+                .run(&module, &text, Duration::from_millis(config.timeout_ms))
+            {
+                Ok(output) => text = output,
+                Err(e) => warn!("Plugin {} failed, leaving the text unchanged: {}", id, e),
+            }
+        }
+        text
+    }
+
+    /// The `.wasm` files in the plugins folder by id, sorted by file name.
+    fn plugin_files(&self) -> Vec<(String, PathBuf)> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut files: Vec<(String, PathBuf)> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "wasm"))
+            .filter_map(|path| {
+                let id = path.file_stem()?.to_string_lossy().to_string();
+                Some((id, path))
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// The compiled module of a plugin, compiling it when it is new or has changed.
+    fn module(&self, id: &str, path: &Path) -> Result<Module, String> {
+        let modified = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|e| e.to_string())?;
+        let mut compiled = self.compiled.lock().unwrap();
+        if let Some(plugin) = compiled.get(id).filter(|p| p.modified == modified) {
+            return plugin.module.clone();
+        }
+        let module = fs::read(path)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| self.runtime.compile(&bytes).map_err(|e| e.to_string()));
+        compiled.insert(
+            id.to_string(),
+            CompiledPlugin {
+                modified,
+                module: module.clone(),
+            },
+        );
+        module
+    }
+}
+
+/// A plugin's settings. Plugins without any are disabled, nothing runs just because its
+/// file was put in the folder.
+fn plugin_config(settings: &AppSettings, id: &str) -> PluginConfig {
+    settings
+        .plugins
+        .iter()
+        .find(|config| config.id == id)
+        .cloned()
+        .unwrap_or_else(|| PluginConfig {
+            id: id.to_string(),
+            enabled: false,
+            timeout_ms: DEFAULT_PLUGIN_TIMEOUT_MS,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hands its input back unchanged
+    const ECHO: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (global $next (mut i32) (i32.const 1024))
+          (func (export "alloc") (param $len i32) (result i32)
+            (local $ptr i32)
+            (local.set $ptr (global.get $next))
+            (global.set $next (i32.add (global.get $next) (local.get $len)))
+            (local.get $ptr))
+          (func (export "transform") (param $ptr i32) (param $len i32) (result i64)
+            (i64.or
+              (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+              (i64.extend_i32_u (local.get $len)))))
+    "#;
+
+    /// Never returns
+    const SPIN: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (func (export "alloc") (param i32) (result i32) (i32.const 0))
+          (func (export "transform") (param i32 i32) (result i64)
+            (loop $forever (br $forever))
+            (i64.const 0)))
+    "#;
+
+    #[test]
+    fn test_plugin_runs_and_times_out() {
+        let runtime = PluginRuntime::new().unwrap();
+        let echo = runtime.compile(ECHO.as_bytes()).unwrap();
+        assert_eq!(
+            runtime
+                .run(&echo, "Dosage: 5 mg", Duration::from_millis(500))
+                .unwrap(),
+            "Dosage: 5 mg"
+        );
+
+        let spin = runtime.compile(SPIN.as_bytes()).unwrap();
+        let error = runtime
+            .run(&spin, "text", Duration::from_millis(50))
+            .unwrap_err();
+        assert!(error.to_string().contains("Timed out"));
+    }
+
+    /// Claims an output past the end of its memory
+    const OVERRUN: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (func (export "alloc") (param i32) (result i32) (i32.const 0))
+          (func (export "transform") (param i32 i32) (result i64)
+            (i64.const 0x0000_fff0_0000_0100)))
+    "#;
+
+    /// Claims an output of 4 GB
+    const HUGE: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (func (export "alloc") (param i32) (result i32) (i32.const 0))
+          (func (export "transform") (param i32 i32) (result i64)
+            (i64.const 0x0000_0000_ffff_ffff)))
+    "#;
+
+    #[test]
+    fn test_plugin_output_is_bounds_checked() {
+        let runtime = PluginRuntime::new().unwrap();
+        let overrun = runtime.compile(OVERRUN.as_bytes()).unwrap();
+        let error = runtime
+            .run(&overrun, "text", Duration::from_millis(500))
+            .unwrap_err();
+        assert!(error.to_string().contains("outside the module's memory"));
+
+        let huge = runtime.compile(HUGE.as_bytes()).unwrap();
+        let error = runtime
+            .run(&huge, "text", Duration::from_millis(500))
+            .unwrap_err();
+        assert!(error.to_string().contains("more than"));
+    }
+
+    #[test]
+    fn test_compile_requires_plugin_exports() {
+        let runtime = PluginRuntime::new().unwrap();
+        assert!(runtime
+            .compile(br#"(module (memory (export "memory") 1))"#)
+            .is_err());
+    }
+
+    #[test]
+    fn test_epoch_ticks_only_during_calls() {
+        let runtime = PluginRuntime::new().unwrap();
+        let echo = runtime.compile(ECHO.as_bytes()).unwrap();
+        runtime
+            .run(&echo, "text", Duration::from_millis(500))
+            .unwrap();
+
+        // The ticker thread notices the call ended within a tick and stops
+        std::thread::sleep(EPOCH_TICK * 5);
+        let state = runtime.ticker.state.lock().unwrap();
+        assert_eq!(state.active, 0);
+        assert!(!state.running);
+    }
+
+    #[test]
+    fn test_unlisted_plugins_are_disabled() {
+        let settings = crate::settings::get_default_settings();
+        assert!(!plugin_config(&settings, "new-plugin").enabled);
+    }
+
+    #[test]
+    fn test_unpack() {
+        assert_eq!(unpack((1024 << 32) | 12), (1024, 12));
+        assert_eq!(unpack(0x8000_0000_0000_0005u64 as i64), (0x8000_0000, 5));
+    }
+}
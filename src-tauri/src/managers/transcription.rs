@@ -1,10 +1,26 @@
-use crate::audio_toolkit::apply_custom_words;
-use crate::managers::model::{EngineType, ModelManager};
-use crate::settings::{get_settings, ModelUnloadTimeout};
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::audio_toolkit::language_model::{self, NgramModel};
+use crate::audio_toolkit::{apply_custom_words, apply_sounds_like, capitalize_proper_nouns};
+use crate::cloud_transcription;
+use crate::helpers::system_memory;
+use crate::inference_tuning;
+use crate::managers::model::{EngineType, ModelInfo, ModelManager, Quantization};
+use crate::mock_engine::MockEngine;
+use crate::pipeline;
+use crate::power_policy;
+use crate::secrets;
+use crate::settings::{
+    get_settings, AppSettings, LowMemoryGuard, ModelUnloadTimeout, TextStageId,
+    TranscriptionProvider,
+};
+use crate::transcription_error::{self, TranscriptionError};
 use anyhow::Result;
 use log::{debug, error, info, warn};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
 use std::sync::{Arc, Condvar, Mutex};
 use std::thread;
 use std::time::{Duration, SystemTime};
@@ -19,7 +35,20 @@ use transcribe_rs::{
     TranscriptionEngine,
 };
 
-#[derive(Clone, Debug, Serialize)]
+/// A transcription may take this many times the length of its audio before the engine is
+/// considered stuck, when that is longer than the configured timeout
+const TIMEOUT_PER_AUDIO_SECOND: f32 = 4.0;
+
+/// Memory a model should leave to the rest of the system once loaded, so loading it
+/// doesn't push an 8 GB machine into swap
+const MEMORY_HEADROOM_MB: u64 = 512;
+
+/// Silence put between the clips of a local batch, so no word runs from one into the next
+const BATCH_GAP_SAMPLES: usize = WHISPER_SAMPLE_RATE as usize;
+/// Most audio the local engine gets in one pass of a batch
+const MAX_BATCH_PASS_SAMPLES: usize = 120 * WHISPER_SAMPLE_RATE as usize;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ModelStateEvent {
     pub event_type: String,
     pub model_id: Option<String>,
@@ -27,28 +56,229 @@ pub struct ModelStateEvent {
     pub error: Option<String>,
 }
 
+/// A single timed piece of a transcription, in seconds relative to the start of the audio.
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+pub struct TranscriptSegment {
+    pub start: f32,
+    pub end: f32,
+    pub text: String,
+    /// The decoder's confidence in the segment from 0 to 1, `None` when the engine doesn't
+    /// report one
+    pub confidence: Option<f32>,
+    /// Per-word confidence, empty when the engine only scores whole segments
+    pub words: Vec<WordConfidence>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+pub struct WordConfidence {
+    pub text: String,
+    pub confidence: f32,
+}
+
+/// Full transcription result including segment timing when the engine provides it.
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+pub struct TranscriptionOutput {
+    pub text: String,
+    pub segments: Vec<TranscriptSegment>,
+}
+
+impl TranscriptionOutput {
+    /// Mean confidence of the segments that report one.
+    pub fn average_confidence(&self) -> Option<f32> {
+        let scores: Vec<f32> = self.segments.iter().filter_map(|s| s.confidence).collect();
+        if scores.is_empty() {
+            return None;
+        }
+        Some(scores.iter().sum::<f32>() / scores.len() as f32)
+    }
+
+    /// Words scored below `threshold`, in transcript order. Segments without word scores are
+    /// returned whole when the segment itself is below `threshold`.
+    pub fn low_confidence_spans(&self, threshold: f32) -> Vec<String> {
+        let mut spans = Vec::new();
+        for segment in &self.segments {
+            if segment.words.is_empty() {
+                if segment.confidence.is_some_and(|c| c < threshold) && !segment.text.is_empty() {
+                    spans.push(segment.text.clone());
+                }
+            } else {
+                spans.extend(
+                    segment
+                        .words
+                        .iter()
+                        .filter(|w| w.confidence < threshold)
+                        .map(|w| w.text.clone()),
+                );
+            }
+        }
+        spans
+    }
+}
+
+/// Appends `marker` to each of `spans` in `text`, e.g. `Jon[?]`. Spans are matched in order
+/// and as whole words, so only the occurrence of a repeated word the decoder was unsure about
+/// is marked, and "a" isn't found inside other words. Spans that no longer appear in the
+/// text, e.g. after post-processing, are skipped.
+///
+/// Only engines that score their output produce spans: transcribe-rs reports no
+/// confidence for the local Whisper and Parakeet models, so for them nothing is marked.
+pub fn mark_low_confidence(text: &str, spans: &[String], marker: &str) -> String {
+    let mut marked = String::with_capacity(text.len());
+    let mut rest = text;
+    for span in spans {
+        let span = span.trim();
+        if span.is_empty() {
+            continue;
+        }
+        if let Some(index) = find_word(rest, span) {
+            let end = index + span.len();
+            marked.push_str(&rest[..end]);
+            marked.push_str(marker);
+            rest = &rest[end..];
+        }
+    }
+    marked.push_str(rest);
+    marked
+}
+
+/// Byte index of the first occurrence of `word` in `text` that isn't part of a longer word.
+fn find_word(text: &str, word: &str) -> Option<usize> {
+    let is_word_char = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+    text.match_indices(word)
+        .map(|(index, _)| index)
+        .find(|&index| {
+            !is_word_char(text[..index].chars().next_back())
+                && !is_word_char(text[index + word.len()..].chars().next())
+        })
+}
+
+/// Longest vocabulary prompt handed to Whisper-style decoders, which only look at the last
+/// couple hundred tokens of their prompt.
+const MAX_VOCABULARY_PROMPT_CHARS: usize = 600;
+
+/// The custom words, for engines that can favour them while decoding instead of having them
+/// fuzzy-matched into the text afterwards.
+pub struct VocabularyBias {
+    pub words: Vec<String>,
+    /// How strongly boosting engines favour the words
+    pub boost: f32,
+}
+
+impl VocabularyBias {
+    /// `None` when biasing is turned off or there are no custom words. Only engines that
+    /// support biasing ask, so for them it is on unless turned off.
+    pub fn from_settings(settings: &AppSettings) -> Option<Self> {
+        let custom_words = settings.active_custom_words();
+        if settings.vocabulary_biasing == Some(false) || custom_words.is_empty() {
+            return None;
+        }
+        Some(Self {
+            words: custom_words
+                .iter()
+                .map(|custom| custom.word.clone())
+                .collect(),
+            boost: settings.vocabulary_boost,
+        })
+    }
+
+    /// The words as a decoder prompt. Whisper continues in the spelling of its prompt, so
+    /// listing the words makes it prefer them over similar sounding ones.
+    pub fn prompt(&self) -> String {
+        let mut prompt = String::from("Glossary:");
+        for (i, word) in self.words.iter().enumerate() {
+            if prompt.len() + word.len() + 3 > MAX_VOCABULARY_PROMPT_CHARS {
+                break;
+            }
+            prompt.push_str(if i == 0 { " " } else { ", " });
+            prompt.push_str(word);
+        }
+        prompt.push('.');
+        prompt
+    }
+}
+
+/// Sent once per idle period, `seconds_remaining` before the model is unloaded for inactivity.
+#[derive(Clone, Debug, Serialize)]
+pub struct ModelUnloadPendingEvent {
+    pub model_id: Option<String>,
+    pub seconds_remaining: u64,
+}
+
+/// Open recording sessions and transcriptions, and whether an unload was requested
+/// while any of them were running.
+#[derive(Default)]
+struct SessionState {
+    active: usize,
+    unload_deferred: bool,
+}
+
+/// Keeps a session open until dropped.
+pub struct SessionGuard<'a>(&'a TranscriptionManager);
+
+impl Drop for SessionGuard<'_> {
+    fn drop(&mut self) {
+        self.0.end_session();
+    }
+}
+
 enum LoadedEngine {
     Whisper(WhisperEngine),
     Parakeet(ParakeetEngine),
+    Mock(MockEngine),
+}
+
+impl LoadedEngine {
+    /// Whether the engine can be biased toward the custom words while decoding. transcribe-rs
+    /// decodes Parakeet greedily without a hook for hotword boosting, so Parakeet relies on
+    /// correcting the text afterwards.
+    fn supports_vocabulary_bias(&self) -> bool {
+        matches!(self, LoadedEngine::Whisper(_))
+    }
+}
+
+/// A small model kept loaded next to the selected one, answering live chunks at once while
+/// the selected model is loaded on demand for the final pass.
+struct Standby {
+    model_id: String,
+    /// `None` while a transcription runs or after the engine stopped responding
+    engine: Option<LoadedEngine>,
 }
 
 #[derive(Clone)]
 pub struct TranscriptionManager {
     engine: Arc<Mutex<Option<LoadedEngine>>>,
+    standby: Arc<Mutex<Option<Standby>>>,
+    /// The bigger model the finished recording is transcribed again with, and its id. Loaded
+    /// on first use and unloaded with the selected model.
+    rescorer: Arc<Mutex<Option<(String, LoadedEngine)>>>,
     model_manager: Arc<ModelManager>,
     app_handle: AppHandle,
     current_model_id: Arc<Mutex<Option<String>>>,
     last_activity: Arc<AtomicU64>,
+    /// `last_activity` value for which `model-unload-pending` was already emitted
+    unload_warned_for: Arc<AtomicU64>,
     shutdown_signal: Arc<AtomicBool>,
     watcher_handle: Arc<Mutex<Option<thread::JoinHandle<()>>>>,
     is_loading: Arc<Mutex<bool>>,
     loading_condvar: Arc<Condvar>,
+    /// The loads in progress, by generation, and the models they are loading
+    loading_models: Arc<Mutex<Vec<(u64, String)>>>,
+    /// Generation of the last load started
+    load_generation: Arc<AtomicU64>,
+    /// Loads up to this generation were asked to stop
+    cancelled_generation: Arc<AtomicU64>,
+    session_generation: Arc<AtomicU64>,
+    sessions: Arc<Mutex<SessionState>>,
+    /// The loaded user language model and the path it was read from
+    language_model: Arc<Mutex<Option<(String, Arc<NgramModel>)>>>,
 }
 
 impl TranscriptionManager {
     pub fn new(app_handle: &AppHandle, model_manager: Arc<ModelManager>) -> Result<Self> {
         let manager = Self {
             engine: Arc::new(Mutex::new(None)),
+            standby: Arc::new(Mutex::new(None)),
+            rescorer: Arc::new(Mutex::new(None)),
             model_manager,
             app_handle: app_handle.clone(),
             current_model_id: Arc::new(Mutex::new(None)),
@@ -58,10 +288,17 @@ impl TranscriptionManager {
                     .unwrap()
                     .as_millis() as u64,
             )),
+            unload_warned_for: Arc::new(AtomicU64::new(0)),
             shutdown_signal: Arc::new(AtomicBool::new(false)),
             watcher_handle: Arc::new(Mutex::new(None)),
             is_loading: Arc::new(Mutex::new(false)),
             loading_condvar: Arc::new(Condvar::new()),
+            loading_models: Arc::new(Mutex::new(Vec::new())),
+            load_generation: Arc::new(AtomicU64::new(0)),
+            cancelled_generation: Arc::new(AtomicU64::new(0)),
+            session_generation: Arc::new(AtomicU64::new(0)),
+            sessions: Arc::new(Mutex::new(SessionState::default())),
+            language_model: Arc::new(Mutex::new(None)),
         };
 
         // Start the idle watcher
@@ -71,7 +308,8 @@ impl TranscriptionManager {
             let shutdown_signal = manager.shutdown_signal.clone();
             let handle = thread::spawn(move || {
                 while !shutdown_signal.load(Ordering::Relaxed) {
-                    thread::sleep(Duration::from_secs(10)); // Check every 10 seconds
+                    let interval = get_settings(&app_handle_cloned).idle_check_interval_secs;
+                    thread::sleep(Duration::from_secs(interval.max(1)));
 
                     // Check shutdown signal again after sleep
                     if shutdown_signal.load(Ordering::Relaxed) {
@@ -79,11 +317,12 @@ impl TranscriptionManager {
                     }
 
                     let settings = get_settings(&app_handle_cloned);
-                    let timeout_seconds = settings.model_unload_timeout.to_seconds();
+                    let unload_timeout = power_policy::model_unload_timeout(&settings);
+                    let timeout_seconds = unload_timeout.to_seconds();
 
                     if let Some(limit_seconds) = timeout_seconds {
                         // Skip polling-based unloading for immediate timeout since it's handled directly in transcribe()
-                        if settings.model_unload_timeout == ModelUnloadTimeout::Immediately {
+                        if unload_timeout == ModelUnloadTimeout::Immediately {
                             continue;
                         }
 
@@ -93,9 +332,32 @@ impl TranscriptionManager {
                             .unwrap()
                             .as_millis() as u64;
 
-                        if now_ms.saturating_sub(last) > limit_seconds * 1000 {
-                            // idle -> unload
-                            if manager_cloned.is_model_loaded() {
+                        let remaining_ms =
+                            (limit_seconds * 1000).saturating_sub(now_ms.saturating_sub(last));
+
+                        // Warn ahead of the unload so the UI can offer to keep the model loaded
+                        if remaining_ms > 0
+                            && remaining_ms <= settings.unload_warning_secs * 1000
+                            && manager_cloned
+                                .unload_warned_for
+                                .swap(last, Ordering::Relaxed)
+                                != last
+                            && manager_cloned.is_model_loaded()
+                        {
+                            let _ = app_handle_cloned.emit(
+                                "model-unload-pending",
+                                ModelUnloadPendingEvent {
+                                    model_id: manager_cloned.get_current_model(),
+                                    seconds_remaining: remaining_ms.div_ceil(1000),
+                                },
+                            );
+                        }
+
+                        if remaining_ms == 0 {
+                            // idle -> unload, unless a recording is still open
+                            if manager_cloned.is_model_loaded()
+                                && !manager_cloned.has_active_session()
+                            {
                                 let unload_start = std::time::Instant::now();
                                 debug!("Starting to unload model due to inactivity");
 
@@ -124,15 +386,83 @@ impl TranscriptionManager {
             *manager.watcher_handle.lock().unwrap() = Some(handle);
         }
 
+        // The standby model stays loaded for the whole run, load it up front
+        {
+            let manager_cloned = manager.clone();
+            thread::spawn(move || {
+                if let Err(e) = manager_cloned.load_standby() {
+                    warn!("Failed to load the standby model: {}", e);
+                }
+            });
+        }
+
         Ok(manager)
     }
 
+    /// Restarts the idle countdown, e.g. when the user dismisses a pending unload.
+    pub fn keep_model_loaded(&self) {
+        self.last_activity.store(
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64,
+            Ordering::Relaxed,
+        );
+        debug!("Idle unload postponed");
+    }
+
     pub fn is_model_loaded(&self) -> bool {
         let engine = self.engine.lock().unwrap();
         engine.is_some()
     }
 
+    /// Marks a recording session as open. Until the matching `end_session`, `unload_model`
+    /// defers the unload so the engine isn't dropped between recording and transcription.
+    pub fn begin_session(&self) {
+        self.sessions.lock().unwrap().active += 1;
+    }
+
+    /// Takes over a session opened with `begin_session`, which is closed when the guard is
+    /// dropped, whichever way its holder finishes.
+    pub fn session_guard(&self) -> SessionGuard<'_> {
+        SessionGuard(self)
+    }
+
+    /// Closes a session and runs an unload that was deferred while sessions were open.
+    pub fn end_session(&self) {
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.active = sessions.active.saturating_sub(1);
+        if sessions.active == 0 && sessions.unload_deferred {
+            sessions.unload_deferred = false;
+            info!("Running model unload deferred until the session ended");
+            // Keep the session lock so no new session can start mid-unload
+            if let Err(e) = self.force_unload_model() {
+                error!("Failed to run deferred model unload: {}", e);
+            }
+        }
+    }
+
+    pub fn has_active_session(&self) -> bool {
+        self.sessions.lock().unwrap().active > 0
+    }
+
+    /// Unloads the model, or defers the unload until the last open session ends.
     pub fn unload_model(&self) -> Result<()> {
+        let mut sessions = self.sessions.lock().unwrap();
+        if sessions.active > 0 {
+            debug!(
+                "Deferring model unload until {} open session(s) end",
+                sessions.active
+            );
+            sessions.unload_deferred = true;
+            return Ok(());
+        }
+        self.force_unload_model()
+    }
+
+    /// Unloads the model right away even if a session is open. A transcription that is
+    /// already running finishes first since it holds the engine; later ones fail.
+    pub fn force_unload_model(&self) -> Result<()> {
         let unload_start = std::time::Instant::now();
         debug!("Starting to unload model");
 
@@ -142,10 +472,12 @@ impl TranscriptionManager {
                 match loaded_engine {
                     LoadedEngine::Whisper(ref mut whisper) => whisper.unload_model(),
                     LoadedEngine::Parakeet(ref mut parakeet) => parakeet.unload_model(),
+                    LoadedEngine::Mock(_) => {}
                 }
             }
             *engine = None; // Drop the engine to free memory
         }
+        *self.rescorer.lock().unwrap() = None;
         {
             let mut current_model = self.current_model_id.lock().unwrap();
             *current_model = None;
@@ -170,7 +502,126 @@ impl TranscriptionManager {
         Ok(())
     }
 
-    pub fn load_model(&self, model_id: &str) -> Result<()> {
+    /// Compares the model's estimated memory with what the system has available. Per the
+    /// low memory guard, a model that doesn't fit is refused with
+    /// `loading_blocked_low_memory` or loaded after `loading_low_memory`.
+    fn check_memory(&self, model_info: &ModelInfo) -> Result<(), TranscriptionError> {
+        let guard = get_settings(&self.app_handle).low_memory_guard;
+        if guard == LowMemoryGuard::Off {
+            return Ok(());
+        }
+        let Some(memory) = system_memory::system_memory() else {
+            return Ok(());
+        };
+        let available_mb = memory.available_bytes / (1024 * 1024);
+        if model_info.ram_mb + MEMORY_HEADROOM_MB <= available_mb {
+            return Ok(());
+        }
+
+        let error = TranscriptionError::InsufficientMemory {
+            model_id: model_info.id.clone(),
+            required_mb: model_info.ram_mb,
+            available_mb,
+        };
+        warn!("{}", error);
+        let event_type = match guard {
+            LowMemoryGuard::Block => "loading_blocked_low_memory",
+            _ => "loading_low_memory",
+        };
+        let _ = self.app_handle.emit(
+            "model-state-changed",
+            ModelStateEvent {
+                event_type: event_type.to_string(),
+                model_id: Some(model_info.id.clone()),
+                model_name: Some(model_info.name.clone()),
+                error: Some(error.to_string()),
+            },
+        );
+        match guard {
+            LowMemoryGuard::Block => Err(error),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn load_model(&self, model_id: &str) -> Result<(), TranscriptionError> {
+        self.load_model_with(model_id, false)
+    }
+
+    /// Loads the model even when the low memory guard would block it, for when the user
+    /// insists.
+    pub fn load_model_ignoring_memory(&self, model_id: &str) -> Result<(), TranscriptionError> {
+        self.load_model_with(model_id, true)
+    }
+
+    fn load_model_with(
+        &self,
+        model_id: &str,
+        ignore_memory: bool,
+    ) -> Result<(), TranscriptionError> {
+        // Each load gets its own generation, so a load starting later isn't cancelled by a
+        // cancel meant for the ones before it, and concurrent loads keep their own entries
+        let generation = {
+            let mut loading = self.loading_models.lock().unwrap();
+            let generation = self.load_generation.fetch_add(1, Ordering::SeqCst) + 1;
+            loading.push((generation, model_id.to_string()));
+            generation
+        };
+        let result = self.load_model_phases(model_id, ignore_memory, generation);
+        self.loading_models
+            .lock()
+            .unwrap()
+            .retain(|(load, _)| *load != generation);
+        result
+    }
+
+    /// Asks the model loads in progress to stop at their next phase. Returns whether a load
+    /// was in progress.
+    pub fn cancel_model_load(&self) -> bool {
+        let loading = self.loading_models.lock().unwrap();
+        let Some(&(latest, _)) = loading.iter().max_by_key(|(generation, _)| *generation) else {
+            return false;
+        };
+        for (_, model_id) in loading.iter() {
+            info!("Cancelling the load of {}", model_id);
+        }
+        self.cancelled_generation
+            .fetch_max(latest, Ordering::SeqCst);
+        true
+    }
+
+    /// Stops the load of `generation` between phases if it was cancelled: whatever was
+    /// loaded is dropped, leaving no model loaded, and `loading_cancelled` is emitted.
+    fn check_load_cancelled(
+        &self,
+        model_info: &ModelInfo,
+        generation: u64,
+    ) -> Result<(), TranscriptionError> {
+        if generation > self.cancelled_generation.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        if let Err(e) = self.unload_model() {
+            warn!("Failed to unload after cancelling the model load: {}", e);
+        }
+        let _ = self.app_handle.emit(
+            "model-state-changed",
+            ModelStateEvent {
+                event_type: "loading_cancelled".to_string(),
+                model_id: Some(model_info.id.clone()),
+                model_name: Some(model_info.name.clone()),
+                error: None,
+            },
+        );
+        Err(TranscriptionError::LoadCancelled {
+            model_id: model_info.id.clone(),
+        })
+    }
+
+    fn load_model_phases(
+        &self,
+        model_id: &str,
+        ignore_memory: bool,
+        generation: u64,
+    ) -> Result<(), TranscriptionError> {
         let load_start = std::time::Instant::now();
         debug!("Starting to load model: {}", model_id);
 
@@ -185,67 +636,60 @@ impl TranscriptionManager {
             },
         );
 
-        let model_info = self
-            .model_manager
-            .get_model_info(model_id)
-            .ok_or_else(|| anyhow::anyhow!("Model not found: {}", model_id))?;
+        let model_info = self.model_manager.get_model_info(model_id).ok_or_else(|| {
+            TranscriptionError::ModelNotFound {
+                model_id: model_id.to_string(),
+            }
+        })?;
 
-        if !model_info.is_downloaded {
-            let error_msg = "Model not downloaded";
+        // The simulated engine stands in for the model, which needn't be downloaded
+        let simulated = self.simulated();
+        if !model_info.is_downloaded && !simulated {
+            let error = TranscriptionError::ModelNotDownloaded {
+                model_id: model_id.to_string(),
+            };
             let _ = self.app_handle.emit(
                 "model-state-changed",
                 ModelStateEvent {
                     event_type: "loading_failed".to_string(),
                     model_id: Some(model_id.to_string()),
                     model_name: Some(model_info.name.clone()),
-                    error: Some(error_msg.to_string()),
+                    error: Some(error.to_string()),
                 },
             );
-            return Err(anyhow::anyhow!(error_msg));
-        }
-
-        let model_path = self.model_manager.get_model_path(model_id)?;
-
-        // Create appropriate engine based on model type
-        let loaded_engine = match model_info.engine_type {
-            EngineType::Whisper => {
-                let mut engine = WhisperEngine::new();
-                engine.load_model(&model_path).map_err(|e| {
-                    let error_msg = format!("Failed to load whisper model {}: {}", model_id, e);
-                    let _ = self.app_handle.emit(
-                        "model-state-changed",
-                        ModelStateEvent {
-                            event_type: "loading_failed".to_string(),
-                            model_id: Some(model_id.to_string()),
-                            model_name: Some(model_info.name.clone()),
-                            error: Some(error_msg.clone()),
-                        },
-                    );
-                    anyhow::anyhow!(error_msg)
-                })?;
-                LoadedEngine::Whisper(engine)
-            }
-            EngineType::Parakeet => {
-                let mut engine = ParakeetEngine::new();
-                engine
-                    .load_model_with_params(&model_path, ParakeetModelParams::int8())
-                    .map_err(|e| {
-                        let error_msg =
-                            format!("Failed to load parakeet model {}: {}", model_id, e);
-                        let _ = self.app_handle.emit(
-                            "model-state-changed",
-                            ModelStateEvent {
-                                event_type: "loading_failed".to_string(),
-                                model_id: Some(model_id.to_string()),
-                                model_name: Some(model_info.name.clone()),
-                                error: Some(error_msg.clone()),
-                            },
-                        );
-                        anyhow::anyhow!(error_msg)
-                    })?;
-                LoadedEngine::Parakeet(engine)
-            }
-        };
+            return Err(error);
+        }
+
+        if !ignore_memory && !simulated {
+            self.check_memory(&model_info)?;
+        }
+
+        let model_path = self.model_path(&model_info)?;
+        self.check_load_cancelled(&model_info, generation)?;
+
+        let settings = get_settings(&self.app_handle);
+        let loaded_engine = create_engine(&model_info, &model_path, &settings).map_err(|e| {
+            // Name the damaged files, if that's why, so just those can be downloaded again
+            let e = match self.model_manager.verify_model(model_id) {
+                Ok(report) if !report.is_ok() => TranscriptionError::ModelCorrupt {
+                    model_id: model_id.to_string(),
+                    files: report.damaged(),
+                },
+                _ => e,
+            };
+            let _ = self.app_handle.emit(
+                "model-state-changed",
+                ModelStateEvent {
+                    event_type: "loading_failed".to_string(),
+                    model_id: Some(model_id.to_string()),
+                    model_name: Some(model_info.name.clone()),
+                    error: Some(e.to_string()),
+                },
+            );
+            e
+        })?;
+        // The engine that just loaded is dropped if the load was cancelled meanwhile
+        self.check_load_cancelled(&model_info, generation)?;
 
         // Update the current engine and model ID
         {
@@ -256,6 +700,8 @@ impl TranscriptionManager {
             let mut current_model = self.current_model_id.lock().unwrap();
             *current_model = Some(model_id.to_string());
         }
+        // A freshly loaded model shouldn't be dropped by an unload requested for the old one
+        self.sessions.lock().unwrap().unload_deferred = false;
 
         // Emit loading completed event
         let _ = self.app_handle.emit(
@@ -277,8 +723,176 @@ impl TranscriptionManager {
         Ok(())
     }
 
+    /// Loads the standby model the settings name, or drops the loaded one when none is set
+    /// or it is the selected model itself. The standby isn't unloaded when idle.
+    pub fn load_standby(&self) -> Result<(), TranscriptionError> {
+        let settings = get_settings(&self.app_handle);
+        let wanted = settings
+            .standby_model
+            .filter(|id| *id != settings.selected_model);
+        let Some(model_id) = wanted else {
+            if self.standby.lock().unwrap().take().is_some() {
+                info!("Standby model unloaded");
+            }
+            return Ok(());
+        };
+        if self
+            .standby
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|standby| standby.model_id == model_id && standby.engine.is_some())
+        {
+            return Ok(());
+        }
+
+        let load_start = std::time::Instant::now();
+        let (model_info, model_path) = self.downloaded_model(&model_id)?;
+        let settings = get_settings(&self.app_handle);
+        let engine = create_engine(&model_info, &model_path, &settings)?;
+        *self.standby.lock().unwrap() = Some(Standby {
+            model_id: model_id.clone(),
+            engine: Some(engine),
+        });
+        info!(
+            "Loaded standby model {} (took {}ms)",
+            model_id,
+            load_start.elapsed().as_millis()
+        );
+        Ok(())
+    }
+
+    pub fn has_standby(&self) -> bool {
+        self.standby
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|standby| standby.engine.is_some())
+    }
+
+    /// Transcribes a live chunk on this computer, a cloud provider only gets the finished
+    /// recording. With a standby model loaded it answers, so partials don't wait for the
+    /// selected model, which the final pass uses.
+    pub fn transcribe_live(
+        &self,
+        audio: Vec<f32>,
+    ) -> Result<TranscriptionOutput, TranscriptionError> {
+        let settings = get_settings(&self.app_handle);
+        if !self.has_standby() {
+            return self.transcribe_on_device(audio);
+        }
+        self.begin_session();
+        let _session = SessionGuard(self);
+
+        // Like the main engine, the lock is held so live chunks take turns
+        let mut standby_guard = self.standby.lock().unwrap();
+        let Some(engine) = standby_guard
+            .as_mut()
+            .and_then(|standby| standby.engine.take())
+        else {
+            drop(standby_guard);
+            return self.transcribe_on_device(audio);
+        };
+        let bias_supported = engine.supports_vocabulary_bias();
+        let (engine, result) = run_engine_watched(engine, audio, &settings);
+        match engine {
+            Some(engine) => {
+                if let Some(standby) = standby_guard.as_mut() {
+                    standby.engine = Some(engine);
+                }
+            }
+            // Left without an engine, live chunks go to the selected model from now on
+            None => warn!("Standby model stopped responding, using the selected model"),
+        }
+        drop(standby_guard);
+        Ok(apply_corrections(
+            result?,
+            &settings,
+            bias_supported,
+            self.language_model(&settings).as_deref(),
+        ))
+    }
+
+    /// Transcribes a finished recording in one pass. With a rescoring model set for local
+    /// transcription that model does it, falling back to the selected one when it fails.
+    pub fn transcribe_final(
+        &self,
+        audio: Vec<f32>,
+    ) -> Result<TranscriptionOutput, TranscriptionError> {
+        let settings = get_settings(&self.app_handle);
+        let rescore_model = settings.rescore_model.as_deref().filter(|id| {
+            settings.transcription_provider == TranscriptionProvider::Local
+                && self.get_current_model().as_deref() != Some(*id)
+        });
+        let Some(model_id) = rescore_model else {
+            return self.transcribe_detailed(audio);
+        };
+        match self.transcribe_rescoring(model_id, audio.clone(), &settings) {
+            Ok(output) => Ok(output),
+            Err(e) => {
+                warn!(
+                    "Rescoring with {} failed, using the selected model: {}",
+                    model_id, e
+                );
+                self.transcribe_detailed(audio)
+            }
+        }
+    }
+
+    fn transcribe_rescoring(
+        &self,
+        model_id: &str,
+        audio: Vec<f32>,
+        settings: &AppSettings,
+    ) -> Result<TranscriptionOutput, TranscriptionError> {
+        self.begin_session();
+        let _session = SessionGuard(self);
+        let st = std::time::Instant::now();
+
+        let mut rescorer = self.rescorer.lock().unwrap();
+        let engine = match rescorer.take() {
+            Some((loaded_id, engine)) if loaded_id == model_id => engine,
+            _ => {
+                let (model_info, model_path) = self.downloaded_model(model_id)?;
+                let engine = create_engine(&model_info, &model_path, settings)?;
+                info!(
+                    "Loaded rescoring model {} (took {}ms)",
+                    model_id,
+                    st.elapsed().as_millis()
+                );
+                engine
+            }
+        };
+        let bias_supported = engine.supports_vocabulary_bias();
+        let (engine, result) = run_engine_watched(engine, audio, settings);
+        *rescorer = engine.map(|engine| (model_id.to_string(), engine));
+        drop(rescorer);
+
+        let output = apply_corrections(
+            result?,
+            settings,
+            bias_supported,
+            self.language_model(settings).as_deref(),
+        );
+        info!(
+            "Rescored with {} in {}ms: {}",
+            model_id,
+            st.elapsed().as_millis(),
+            output.text
+        );
+        Ok(output)
+    }
+
     /// Kicks off the model loading in a background thread if it's not already loaded
     pub fn initiate_model_load(&self) {
+        // A cloud provider without local fallback never needs the local model in memory
+        let settings = get_settings(&self.app_handle);
+        if settings.transcription_provider != TranscriptionProvider::Local
+            && !settings.cloud_fallback_to_local
+        {
+            return;
+        }
+
         let mut is_loading = self.is_loading.lock().unwrap();
         if *is_loading || self.is_model_loaded() {
             return;
@@ -288,8 +902,13 @@ impl TranscriptionManager {
         let self_clone = self.clone();
         thread::spawn(move || {
             let settings = get_settings(&self_clone.app_handle);
-            if let Err(e) = self_clone.load_model(&settings.selected_model) {
-                error!("Failed to load model: {}", e);
+            let model_id = power_policy::preferred_model(&settings, &self_clone.model_manager);
+            match self_clone.load_model(&model_id) {
+                Ok(()) | Err(TranscriptionError::LoadCancelled { .. }) => {}
+                Err(e) => {
+                    error!("Failed to load model: {}", e);
+                    transcription_error::emit(&self_clone.app_handle, "model_load", &e);
+                }
             }
             let mut is_loading = self_clone.is_loading.lock().unwrap();
             *is_loading = false;
@@ -297,12 +916,108 @@ impl TranscriptionManager {
         });
     }
 
+    pub fn is_model_loading(&self) -> bool {
+        *self.is_loading.lock().unwrap()
+    }
+
+    /// Blocks while a model load is in progress, then returns whether a model is loaded.
+    pub fn wait_for_model(&self) -> bool {
+        let mut is_loading = self.is_loading.lock().unwrap();
+        while *is_loading {
+            is_loading = self.loading_condvar.wait(is_loading).unwrap();
+        }
+        drop(is_loading);
+        self.is_model_loaded()
+    }
+
+    /// Returns the generation of the current recording session. Callers capture this
+    /// before transcribing and compare afterwards to detect an abort in between.
+    pub fn session_generation(&self) -> u64 {
+        self.session_generation.load(Ordering::SeqCst)
+    }
+
+    /// Invalidates every chunk and final transcription belonging to the current session.
+    ///
+    /// Neither Whisper nor Parakeet can be interrupted once inference has started, so
+    /// in-flight calls are allowed to finish and their results are discarded by callers
+    /// that observe a changed session generation. Queued chunks bail out before they
+    /// ever reach the engine.
+    pub fn abort_session(&self) {
+        let generation = self.session_generation.fetch_add(1, Ordering::SeqCst) + 1;
+        debug!("Session aborted, now at generation {}", generation);
+    }
+
     pub fn get_current_model(&self) -> Option<String> {
         let current_model = self.current_model_id.lock().unwrap();
         current_model.clone()
     }
 
-    pub fn transcribe(&self, audio: Vec<f32>) -> Result<String> {
+    /// Reads the language model at `path` and rescores with it from the next transcription
+    /// on, `None` stops rescoring. Returns how many n-grams the model has.
+    pub fn set_language_model(&self, path: Option<&str>) -> Result<usize, String> {
+        let Some(path) = path else {
+            *self.language_model.lock().unwrap() = None;
+            return Ok(0);
+        };
+        let model = NgramModel::from_file(Path::new(path))?;
+        let ngram_count = model.ngram_count();
+        info!("Loaded language model {} ({} n-grams)", path, ngram_count);
+        *self.language_model.lock().unwrap() = Some((path.to_string(), Arc::new(model)));
+        Ok(ngram_count)
+    }
+
+    /// The language model the settings name, read on first use. One that can't be read is
+    /// skipped so dictation keeps working.
+    fn language_model(&self, settings: &AppSettings) -> Option<Arc<NgramModel>> {
+        let path = settings.language_model_path.as_deref()?;
+        let mut loaded = self.language_model.lock().unwrap();
+        if let Some((loaded_path, model)) = loaded.as_ref() {
+            if loaded_path == path {
+                return Some(model.clone());
+            }
+        }
+        match NgramModel::from_file(Path::new(path)) {
+            Ok(model) => {
+                let model = Arc::new(model);
+                *loaded = Some((path.to_string(), model.clone()));
+                Some(model)
+            }
+            Err(e) => {
+                warn!("Skipping the language model: {}", e);
+                None
+            }
+        }
+    }
+
+    pub fn transcribe(&self, audio: Vec<f32>) -> Result<String, TranscriptionError> {
+        Ok(self.transcribe_detailed(audio)?.text)
+    }
+
+    /// Like `transcribe`, but also returns the per-segment timestamps reported by the engine.
+    pub fn transcribe_detailed(
+        &self,
+        audio: Vec<f32>,
+    ) -> Result<TranscriptionOutput, TranscriptionError> {
+        self.run_transcription(audio, true)
+    }
+
+    /// Like `transcribe_detailed`, but always with the local model, whatever the provider.
+    pub fn transcribe_on_device(
+        &self,
+        audio: Vec<f32>,
+    ) -> Result<TranscriptionOutput, TranscriptionError> {
+        self.run_transcription(audio, false)
+    }
+
+    fn run_transcription(
+        &self,
+        audio: Vec<f32>,
+        allow_cloud: bool,
+    ) -> Result<TranscriptionOutput, TranscriptionError> {
+        // Hold off unloads (including the "immediately" one below) until we are done
+        self.begin_session();
+        let _session = SessionGuard(self);
+
         // Update last activity timestamp
         self.last_activity.store(
             SystemTime::now()
@@ -318,85 +1033,45 @@ impl TranscriptionManager {
 
         if audio.len() == 0 {
             debug!("Empty audio vector");
-            return Ok(String::new());
-        }
-
-        // Check if model is loaded, if not try to load it
-        {
-            // If the model is loading, wait for it to complete.
-            let mut is_loading = self.is_loading.lock().unwrap();
-            while *is_loading {
-                is_loading = self.loading_condvar.wait(is_loading).unwrap();
-            }
-
-            let engine_guard = self.engine.lock().unwrap();
-            if engine_guard.is_none() {
-                return Err(anyhow::anyhow!("Model is not loaded for transcription."));
-            }
+            return Ok(TranscriptionOutput {
+                text: String::new(),
+                segments: Vec::new(),
+            });
         }
 
         // Get current settings for configuration
         let settings = get_settings(&self.app_handle);
 
-        // Perform transcription with the appropriate engine
-        let result = {
-            let mut engine_guard = self.engine.lock().unwrap();
-            let engine = engine_guard.as_mut().ok_or_else(|| {
-                anyhow::anyhow!(
-                    "Model failed to load after auto-load attempt. Please check your model settings."
+        let cloud = if allow_cloud {
+            self.transcribe_cloud(&audio, &settings)
+        } else {
+            None
+        };
+        let (result, bias_supported) = match cloud {
+            None => (
+                self.transcribe_local(audio, &settings)?,
+                self.supports_vocabulary_bias(),
+            ),
+            Some(Ok(result)) => (
+                result,
+                cloud_transcription::supports_vocabulary_bias(settings.transcription_provider),
+            ),
+            Some(Err(e)) if settings.cloud_fallback_to_local => {
+                warn!(
+                    "Cloud transcription failed, falling back to the local model: {}",
+                    e
+                );
+                (
+                    self.transcribe_local(audio, &settings)?,
+                    self.supports_vocabulary_bias(),
                 )
-            })?;
-
-            match engine {
-                LoadedEngine::Whisper(whisper_engine) => {
-                    // Normalize language code for Whisper
-                    // Convert zh-Hans and zh-Hant to zh since Whisper uses ISO 639-1 codes
-                    let whisper_language = if settings.selected_language == "auto" {
-                        None
-                    } else {
-                        let normalized = if settings.selected_language == "zh-Hans"
-                            || settings.selected_language == "zh-Hant"
-                        {
-                            "zh".to_string()
-                        } else {
-                            settings.selected_language.clone()
-                        };
-                        Some(normalized)
-                    };
-
-                    let params = WhisperInferenceParams {
-                        language: whisper_language,
-                        translate: settings.translate_to_english,
-                        ..Default::default()
-                    };
-
-                    whisper_engine
-                        .transcribe_samples(audio, Some(params))
-                        .map_err(|e| anyhow::anyhow!("Whisper transcription failed: {}", e))?
-                }
-                LoadedEngine::Parakeet(parakeet_engine) => {
-                    let params = ParakeetInferenceParams {
-                        timestamp_granularity: TimestampGranularity::Segment,
-                        ..Default::default()
-                    };
-
-                    parakeet_engine
-                        .transcribe_samples(audio, Some(params))
-                        .map_err(|e| anyhow::anyhow!("Parakeet transcription failed: {}", e))?
-                }
             }
+            Some(Err(e)) => return Err(e),
         };
 
-        // Apply word correction if custom words are configured
-        let corrected_result = if !settings.custom_words.is_empty() {
-            apply_custom_words(
-                &result.text,
-                &settings.custom_words,
-                settings.word_correction_threshold,
-            )
-        } else {
-            result.text
-        };
+        let language_model = self.language_model(&settings);
+        let output =
+            apply_corrections(result, &settings, bias_supported, language_model.as_deref());
 
         let et = std::time::Instant::now();
         let translation_note = if settings.translate_to_english {
@@ -410,23 +1085,597 @@ impl TranscriptionManager {
             translation_note
         );
 
-        let final_result = corrected_result.trim().to_string();
-
-        if final_result.is_empty() {
+        if output.text.is_empty() {
             info!("Transcription result is empty");
         } else {
-            info!("Transcription result: {}", final_result);
+            info!("Transcription result: {}", output.text);
         }
 
         // Check if we should immediately unload the model after transcription
-        if settings.model_unload_timeout == ModelUnloadTimeout::Immediately {
+        if power_policy::model_unload_timeout(&settings) == ModelUnloadTimeout::Immediately {
             info!("Immediately unloading model after transcription");
             if let Err(e) = self.unload_model() {
                 error!("Failed to immediately unload model: {}", e);
             }
         }
 
-        Ok(final_result)
+        Ok(output)
+    }
+
+    /// Transcribes `audio` with `model_id` locally without replacing the loaded model. The
+    /// loaded engine is used when it already is that model, otherwise a temporary engine is
+    /// loaded for this call and dropped afterwards.
+    pub fn transcribe_with_model(
+        &self,
+        model_id: &str,
+        audio: Vec<f32>,
+    ) -> Result<TranscriptionOutput, TranscriptionError> {
+        self.begin_session();
+        let _session = SessionGuard(self);
+
+        let settings = get_settings(&self.app_handle);
+        if self.get_current_model().as_deref() == Some(model_id) && self.is_model_loaded() {
+            let result = self.transcribe_local(audio, &settings)?;
+            return Ok(apply_corrections(
+                result,
+                &settings,
+                self.supports_vocabulary_bias(),
+                self.language_model(&settings).as_deref(),
+            ));
+        }
+
+        let (model_info, model_path) = self.downloaded_model(model_id)?;
+
+        info!(
+            "Loading {} temporarily for a one-off transcription",
+            model_id
+        );
+        let engine = create_engine(&model_info, &model_path, &settings)?;
+        let bias_supported = engine.supports_vocabulary_bias();
+        let (_, result) = run_engine_watched(engine, audio, &settings);
+        Ok(apply_corrections(
+            result?,
+            &settings,
+            bias_supported,
+            self.language_model(&settings).as_deref(),
+        ))
+    }
+
+    /// Loads `model_id` on its own and transcribes `audio` with it, returning the engine's
+    /// output with the load and transcription times. The loaded model is left alone.
+    pub fn benchmark_model(
+        &self,
+        model_id: &str,
+        audio: Vec<f32>,
+    ) -> Result<(TranscriptionOutput, Duration, Duration), TranscriptionError> {
+        let (model_info, model_path) = self.downloaded_model(model_id)?;
+        let settings = get_settings(&self.app_handle);
+
+        let load_start = std::time::Instant::now();
+        let engine = create_engine(&model_info, &model_path, &settings)?;
+        let load_time = load_start.elapsed();
+
+        let transcribe_start = std::time::Instant::now();
+        let (_, result) = run_engine_watched(engine, audio, &settings);
+        let output = result?;
+        Ok((output, load_time, transcribe_start.elapsed()))
+    }
+
+    /// Whether transcriptions can run concurrently. Cloud providers take parallel requests;
+    /// the local engines run one transcription at a time.
+    pub fn runs_in_parallel(&self) -> bool {
+        get_settings(&self.app_handle).transcription_provider != TranscriptionProvider::Local
+    }
+
+    /// Transcribes several independent clips, returning one result per clip in order. Cloud
+    /// providers get them as parallel requests. The local engines have no batched inference,
+    /// so there the clips are joined, with silence in between, into passes of up to
+    /// `MAX_BATCH_PASS_SAMPLES` and split up again by the segment times.
+    pub fn transcribe_batch(
+        &self,
+        clips: Vec<Vec<f32>>,
+    ) -> Vec<Result<TranscriptionOutput, TranscriptionError>> {
+        // One session for the whole batch so an immediate unload waits for the last clip
+        self.begin_session();
+        let _session = SessionGuard(self);
+
+        if clips.len() < 2 {
+            return clips
+                .into_iter()
+                .map(|clip| self.transcribe_detailed(clip))
+                .collect();
+        }
+        if !self.runs_in_parallel() {
+            return batch_passes(clips)
+                .into_iter()
+                .flat_map(|pass| self.transcribe_pass(pass))
+                .collect();
+        }
+
+        thread::scope(|scope| {
+            let handles: Vec<_> = clips
+                .into_iter()
+                .map(|clip| scope.spawn(move || self.transcribe_detailed(clip)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| {
+                    handle.join().unwrap_or_else(|_| {
+                        Err(TranscriptionError::engine("Transcription thread panicked"))
+                    })
+                })
+                .collect()
+        })
+    }
+
+    /// Transcribes the clips of one local pass in a single run of the engine. When that
+    /// fails, or the engine gives no segment times to split by, the clips are transcribed
+    /// one by one instead, so one bad clip doesn't fail the others.
+    fn transcribe_pass(
+        &self,
+        clips: Vec<Vec<f32>>,
+    ) -> Vec<Result<TranscriptionOutput, TranscriptionError>> {
+        if clips.len() > 1 {
+            let lengths: Vec<usize> = clips.iter().map(Vec::len).collect();
+            let mut joined = Vec::with_capacity(
+                lengths.iter().sum::<usize>() + lengths.len() * BATCH_GAP_SAMPLES,
+            );
+            for clip in &clips {
+                joined.extend_from_slice(clip);
+                joined.resize(joined.len() + BATCH_GAP_SAMPLES, 0.0);
+            }
+            match self.transcribe_detailed(joined) {
+                Ok(output) if output.text.is_empty() || !output.segments.is_empty() => {
+                    return split_batch_output(output, &lengths)
+                        .into_iter()
+                        .map(Ok)
+                        .collect();
+                }
+                Ok(_) => debug!(
+                    "No segment times to split the batch by, transcribing its clips one by one"
+                ),
+                Err(e) => warn!(
+                    "Batch transcription failed, transcribing its clips one by one: {}",
+                    e
+                ),
+            }
+        }
+        clips
+            .into_iter()
+            .map(|clip| self.transcribe_detailed(clip))
+            .collect()
+    }
+
+    fn model_path(&self, model_info: &ModelInfo) -> Result<PathBuf, TranscriptionError> {
+        // The simulated engine reads no model files
+        if self.simulated() {
+            return Ok(PathBuf::new());
+        }
+        self.model_manager
+            .get_model_path(&model_info.id)
+            .map_err(|e| TranscriptionError::ModelLoadFailed {
+                model_id: model_info.id.clone(),
+                message: e.to_string(),
+            })
+    }
+
+    /// Info and path of `model_id`, failing unless it is downloaded.
+    fn downloaded_model(&self, model_id: &str) -> Result<(ModelInfo, PathBuf), TranscriptionError> {
+        let model_info = self.model_manager.get_model_info(model_id).ok_or_else(|| {
+            TranscriptionError::ModelNotFound {
+                model_id: model_id.to_string(),
+            }
+        })?;
+        if !model_info.is_downloaded && !self.simulated() {
+            return Err(TranscriptionError::ModelNotDownloaded {
+                model_id: model_id.to_string(),
+            });
+        }
+        let model_path = self.model_path(&model_info)?;
+        Ok((model_info, model_path))
+    }
+
+    /// Whether the simulated engine is loaded in place of the models.
+    fn simulated(&self) -> bool {
+        get_settings(&self.app_handle).mock_engine.enabled
+    }
+
+    /// Whether the loaded local model can be biased toward the custom words.
+    fn supports_vocabulary_bias(&self) -> bool {
+        self.engine
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(LoadedEngine::supports_vocabulary_bias)
+    }
+
+    /// Transcribes with the loaded local model, returning the engine's text before corrections.
+    fn transcribe_local(
+        &self,
+        audio: Vec<f32>,
+        settings: &AppSettings,
+    ) -> Result<TranscriptionOutput, TranscriptionError> {
+        // Check if model is loaded, if not try to load it
+        {
+            // If the model is loading, wait for it to complete.
+            let mut is_loading = self.is_loading.lock().unwrap();
+            while *is_loading {
+                is_loading = self.loading_condvar.wait(is_loading).unwrap();
+            }
+
+            let engine_guard = self.engine.lock().unwrap();
+            if engine_guard.is_none() {
+                return Err(TranscriptionError::ModelNotLoaded);
+            }
+        }
+
+        // The lock is held throughout so other callers wait their turn, while the engine
+        // itself runs under the watchdog
+        let mut engine_guard = self.engine.lock().unwrap();
+        let engine = engine_guard
+            .take()
+            .ok_or(TranscriptionError::ModelNotLoaded)?;
+        let (engine, result) = run_engine_watched(engine, audio, settings);
+        match engine {
+            Some(engine) => *engine_guard = Some(engine),
+            None => {
+                drop(engine_guard);
+                if let Err(e) = &result {
+                    self.recover_engine(e);
+                }
+            }
+        }
+        result
+    }
+
+    /// Forgets an engine the watchdog gave up on and loads the model again, so the next
+    /// transcription doesn't wait on the stuck one.
+    fn recover_engine(&self, error: &TranscriptionError) {
+        error!("Transcription engine stopped responding: {}", error);
+        *self.current_model_id.lock().unwrap() = None;
+        let _ = self.app_handle.emit(
+            "model-state-changed",
+            ModelStateEvent {
+                event_type: "unloaded".to_string(),
+                model_id: None,
+                model_name: None,
+                error: Some(error.to_string()),
+            },
+        );
+        transcription_error::emit(&self.app_handle, "watchdog", error);
+        self.initiate_model_load();
+    }
+
+    /// Transcribes with the configured cloud provider, or returns `None` when local
+    /// transcription is selected.
+    fn transcribe_cloud(
+        &self,
+        audio: &[f32],
+        settings: &AppSettings,
+    ) -> Option<Result<TranscriptionOutput, TranscriptionError>> {
+        let provider = settings.transcription_provider;
+        if provider == TranscriptionProvider::Local {
+            return None;
+        }
+
+        let Some(api_key) = secrets::get_api_key(provider.id()) else {
+            return Some(Err(TranscriptionError::MissingApiKey {
+                provider: provider.id().to_string(),
+            }));
+        };
+
+        // Run on a dedicated thread: callers may already be inside the async runtime,
+        // where blocking on a future would panic.
+        let audio = audio.to_vec();
+        let settings = settings.clone();
+        let result = thread::spawn(move || {
+            tauri::async_runtime::block_on(cloud_transcription::transcribe(
+                provider, &api_key, &audio, &settings,
+            ))
+        })
+        .join()
+        .unwrap_or_else(|_| Err(anyhow::anyhow!("Cloud transcription thread panicked")));
+
+        Some(result.map_err(|e| TranscriptionError::CloudFailure {
+            provider: provider.id().to_string(),
+            message: e.to_string(),
+        }))
+    }
+}
+
+/// Loads a model, with the inference priority and cores of `settings`. The simulated engine
+/// takes the place of any model while enabled.
+fn create_engine(
+    model_info: &ModelInfo,
+    model_path: &Path,
+    settings: &AppSettings,
+) -> Result<LoadedEngine, TranscriptionError> {
+    if settings.mock_engine.enabled {
+        return MockEngine::load(&settings.mock_engine)
+            .map(LoadedEngine::Mock)
+            .map_err(|message| TranscriptionError::ModelLoadFailed {
+                model_id: model_info.id.clone(),
+                message,
+            });
+    }
+    inference_tuning::run(settings, || load_engine(model_info, model_path))
+}
+
+fn load_engine(
+    model_info: &ModelInfo,
+    model_path: &Path,
+) -> Result<LoadedEngine, TranscriptionError> {
+    let load_failed = |e: &dyn std::fmt::Display| TranscriptionError::ModelLoadFailed {
+        model_id: model_info.id.clone(),
+        message: e.to_string(),
+    };
+    match model_info.engine_type {
+        EngineType::Whisper => {
+            let mut engine = WhisperEngine::new();
+            engine.load_model(model_path).map_err(|e| load_failed(&e))?;
+            Ok(LoadedEngine::Whisper(engine))
+        }
+        EngineType::Parakeet => {
+            let mut engine = ParakeetEngine::new();
+            let params = if model_info.quantization == Quantization::Int8 {
+                ParakeetModelParams::int8()
+            } else {
+                ParakeetModelParams::fp32()
+            };
+            engine
+                .load_model_with_params(model_path, params)
+                .map_err(|e| load_failed(&e))?;
+            Ok(LoadedEngine::Parakeet(engine))
+        }
+        EngineType::Piper => Err(load_failed(&"a text-to-speech voice can't transcribe")),
+    }
+}
+
+fn engine_timeout(settings: &AppSettings, samples: usize) -> Option<Duration> {
+    let secs = settings.transcription_timeout_secs? as f32;
+    let audio_secs = samples as f32 / WHISPER_SAMPLE_RATE as f32;
+    Some(Duration::from_secs_f32(
+        secs.max(audio_secs * TIMEOUT_PER_AUDIO_SECOND),
+    ))
+}
+
+/// Runs `engine` on a worker thread so a wedged inference can't block its caller forever.
+/// The engine is handed back unless the call timed out or the worker died; it then stays
+/// with the worker and is dropped if that ever finishes.
+fn run_engine_watched(
+    mut engine: LoadedEngine,
+    audio: Vec<f32>,
+    settings: &AppSettings,
+) -> (
+    Option<LoadedEngine>,
+    Result<TranscriptionOutput, TranscriptionError>,
+) {
+    let Some(timeout) = engine_timeout(settings, audio.len()) else {
+        let result = run_engine(&mut engine, audio, settings);
+        return (Some(engine), result);
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let worker_settings = settings.clone();
+    thread::spawn(move || {
+        let result = run_engine(&mut engine, audio, &worker_settings);
+        let _ = tx.send((engine, result));
+    });
+
+    match rx.recv_timeout(timeout) {
+        Ok((engine, result)) => (Some(engine), result),
+        Err(RecvTimeoutError::Timeout) => (
+            None,
+            Err(TranscriptionError::Timeout {
+                secs: timeout.as_secs(),
+            }),
+        ),
+        Err(RecvTimeoutError::Disconnected) => (
+            None,
+            Err(TranscriptionError::engine("Transcription thread panicked")),
+        ),
+    }
+}
+
+/// Groups clips in order into passes of up to `MAX_BATCH_PASS_SAMPLES` with their gaps. A
+/// clip longer than that is a pass of its own.
+fn batch_passes(clips: Vec<Vec<f32>>) -> Vec<Vec<Vec<f32>>> {
+    let mut passes: Vec<Vec<Vec<f32>>> = Vec::new();
+    let mut pass_samples = 0;
+    for clip in clips {
+        let samples = clip.len() + BATCH_GAP_SAMPLES;
+        match passes.last_mut() {
+            Some(pass) if pass_samples + samples <= MAX_BATCH_PASS_SAMPLES => {
+                pass_samples += samples;
+                pass.push(clip);
+            }
+            _ => {
+                pass_samples = samples;
+                passes.push(vec![clip]);
+            }
+        }
+    }
+    passes
+}
+
+/// Splits the transcription of clips of `lengths` samples joined by `BATCH_GAP_SAMPLES`
+/// into one output per clip. Each segment goes to the clip its middle falls in, with its
+/// times made relative to that clip.
+fn split_batch_output(output: TranscriptionOutput, lengths: &[usize]) -> Vec<TranscriptionOutput> {
+    let mut starts = Vec::with_capacity(lengths.len());
+    let mut position = 0;
+    for length in lengths {
+        starts.push(position as f32 / WHISPER_SAMPLE_RATE as f32);
+        position += length + BATCH_GAP_SAMPLES;
+    }
+
+    let mut outputs: Vec<TranscriptionOutput> = lengths
+        .iter()
+        .map(|_| TranscriptionOutput {
+            text: String::new(),
+            segments: Vec::new(),
+        })
+        .collect();
+    for segment in output.segments {
+        let middle = (segment.start + segment.end) / 2.0;
+        let index = starts
+            .iter()
+            .rposition(|&start| start <= middle)
+            .unwrap_or(0);
+        let offset = starts[index];
+        let clip = &mut outputs[index];
+        let text = segment.text.trim();
+        if !text.is_empty() {
+            if !clip.text.is_empty() {
+                clip.text.push(' ');
+            }
+            clip.text.push_str(text);
+        }
+        clip.segments.push(TranscriptSegment {
+            start: (segment.start - offset).max(0.0),
+            end: (segment.end - offset).max(0.0),
+            ..segment
+        });
+    }
+    outputs
+}
+
+/// Runs `audio` through `engine`, returning the engine's text before corrections.
+fn run_engine(
+    engine: &mut LoadedEngine,
+    audio: Vec<f32>,
+    settings: &AppSettings,
+) -> Result<TranscriptionOutput, TranscriptionError> {
+    inference_tuning::run(settings, || infer(engine, audio, settings))
+}
+
+fn infer(
+    engine: &mut LoadedEngine,
+    audio: Vec<f32>,
+    settings: &AppSettings,
+) -> Result<TranscriptionOutput, TranscriptionError> {
+    let result = match engine {
+        LoadedEngine::Mock(mock_engine) => return Ok(mock_engine.transcribe(&audio)),
+        LoadedEngine::Whisper(whisper_engine) => {
+            // Normalize language code for Whisper
+            // Convert zh-Hans and zh-Hant to zh since Whisper uses ISO 639-1 codes
+            let whisper_language = if settings.selected_language == "auto" {
+                None
+            } else {
+                let normalized = if settings.selected_language == "zh-Hans"
+                    || settings.selected_language == "zh-Hant"
+                {
+                    "zh".to_string()
+                } else {
+                    settings.selected_language.clone()
+                };
+                Some(normalized)
+            };
+
+            let params = WhisperInferenceParams {
+                language: whisper_language,
+                translate: settings.translate_to_english,
+                initial_prompt: VocabularyBias::from_settings(settings).map(|bias| bias.prompt()),
+                ..Default::default()
+            };
+
+            whisper_engine
+                .transcribe_samples(audio, Some(params))
+                .map_err(|e| TranscriptionError::engine(format!("Whisper: {}", e)))?
+        }
+        LoadedEngine::Parakeet(parakeet_engine) => {
+            let params = ParakeetInferenceParams {
+                timestamp_granularity: TimestampGranularity::Segment,
+                ..Default::default()
+            };
+
+            parakeet_engine
+                .transcribe_samples(audio, Some(params))
+                .map_err(|e| TranscriptionError::engine(format!("Parakeet: {}", e)))?
+        }
+    };
+
+    Ok(TranscriptionOutput {
+        text: result.text,
+        segments: result
+            .segments
+            .unwrap_or_default()
+            .into_iter()
+            .map(|segment| TranscriptSegment {
+                start: segment.start,
+                end: segment.end,
+                text: segment.text,
+                // transcribe-rs doesn't expose token probabilities
+                confidence: None,
+                words: Vec::new(),
+            })
+            .collect(),
+    })
+}
+
+/// Applies custom word correction, language model rescoring and proper noun casing to an
+/// engine result. When the engine was already biased toward the custom words while decoding
+/// only their `sounds_like` spellings are replaced, without fuzzy matching.
+fn apply_corrections(
+    result: TranscriptionOutput,
+    settings: &AppSettings,
+    bias_supported: bool,
+    language_model: Option<&NgramModel>,
+) -> TranscriptionOutput {
+    // Only the words for the current language and profile, another language's vocabulary
+    // would pull words towards it that were never said
+    let custom_words = settings.active_custom_words();
+    // Custom words written with capitals are names too, so their casing is enforced
+    // wherever they appear, including multi-word entries the fuzzy matcher can't handle
+    let proper_nouns: Vec<String> = settings
+        .proper_nouns
+        .iter()
+        .chain(
+            custom_words
+                .iter()
+                .map(|custom| &custom.word)
+                .filter(|word| word.chars().any(|c| c.is_uppercase())),
+        )
+        .cloned()
+        .collect();
+    let hints: Vec<_> = custom_words.iter().map(|w| w.hint()).collect();
+    let biased = bias_supported && VocabularyBias::from_settings(settings).is_some();
+
+    // Apply word correction if custom words are configured
+    let correct = |text: &str| -> String {
+        let corrected = if custom_words.is_empty()
+            || !pipeline::is_enabled(settings, TextStageId::CustomWords)
+        {
+            text.to_string()
+        } else if biased {
+            apply_sounds_like(text, &hints)
+        } else {
+            apply_custom_words(text, &hints, settings.word_correction_threshold)
+        };
+        let corrected = match language_model {
+            Some(model) => language_model::rescore(
+                &corrected,
+                model,
+                settings.language_model_weight,
+                settings.word_correction_threshold,
+            ),
+            None => corrected,
+        };
+        capitalize_proper_nouns(&corrected, &proper_nouns)
+    };
+    let corrected_result = correct(&result.text);
+    let segments: Vec<TranscriptSegment> = result
+        .segments
+        .into_iter()
+        .map(|segment| TranscriptSegment {
+            text: correct(&segment.text).trim().to_string(),
+            ..segment
+        })
+        .collect();
+
+    TranscriptionOutput {
+        text: corrected_result.trim().to_string(),
+        segments,
     }
 }
 
@@ -447,3 +1696,75 @@ impl Drop for TranscriptionManager {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(start: f32, end: f32, text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            start,
+            end,
+            text: text.to_string(),
+            confidence: None,
+            words: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_mark_low_confidence() {
+        let spans = ["a".to_string(), "Jon".to_string()];
+        assert_eq!(
+            mark_low_confidence("Call Jonathan and a friend, Jon.", &spans, "[?]"),
+            "Call Jonathan and a[?] friend, Jon[?]."
+        );
+        // Spans are found in order, the second "is" is the one after the first span
+        let spans = ["it".to_string(), "is".to_string()];
+        assert_eq!(
+            mark_low_confidence("this is it, is it not", &spans, "*"),
+            "this is it*, is* it not"
+        );
+        assert_eq!(
+            mark_low_confidence("nothing here", &["gone".to_string()], "*"),
+            "nothing here"
+        );
+    }
+
+    #[test]
+    fn test_batch_passes() {
+        let rate = WHISPER_SAMPLE_RATE as usize;
+        let clips = vec![
+            vec![0.0; 50 * rate],
+            vec![0.0; 50 * rate],
+            vec![0.0; 30 * rate],
+        ];
+        let passes: Vec<usize> = batch_passes(clips).iter().map(Vec::len).collect();
+        assert_eq!(passes, [2, 1]);
+        assert_eq!(batch_passes(vec![vec![0.0; 200 * rate]]).len(), 1);
+    }
+
+    #[test]
+    fn test_split_batch_output() {
+        let rate = WHISPER_SAMPLE_RATE as usize;
+        // Clips of 2 s and 3 s, the second starting at 3 s after the gap
+        let output = TranscriptionOutput {
+            text: "Hello there. General Kenobi.".to_string(),
+            segments: vec![
+                segment(0.2, 1.8, " Hello there."),
+                segment(3.5, 5.0, " General Kenobi."),
+            ],
+        };
+        let outputs = split_batch_output(output, &[2 * rate, 3 * rate]);
+        assert_eq!(outputs[0].text, "Hello there.");
+        assert_eq!(outputs[1].text, "General Kenobi.");
+        assert_eq!(outputs[1].segments[0].start, 0.5);
+        assert_eq!(outputs[1].segments[0].end, 2.0);
+
+        let silent = TranscriptionOutput {
+            text: String::new(),
+            segments: Vec::new(),
+        };
+        let outputs = split_batch_output(silent, &[rate, rate]);
+        assert!(outputs.iter().all(|output| output.text.is_empty()));
+    }
+}
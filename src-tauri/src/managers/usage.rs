@@ -0,0 +1,311 @@
+//! Coarse usage counters for diagnostics.
+//!
+//! Counts how many sessions ran each day, which features they used and which part of the
+//! pipeline failed, and nothing else: the keys are fixed enums and dates, so no transcript,
+//! prompt, file name or error message can end up in them. The counters never leave this
+//! machine on their own, they are only read into the diagnostics the user chooses to share,
+//! and even then as ranges rather than exact numbers.
+
+use crate::session_report::SessionReport;
+use crate::settings::{get_settings, AppSettings, TranscriptionProvider};
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveDate};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+const COUNTERS_FILE: &str = "usage_counters.json";
+
+/// Days of session counts that are kept
+const RETENTION_DAYS: i64 = 30;
+
+/// Upper bounds of the ranges counts are reported in, anything above the last is "100+"
+const BUCKETS: &[(u32, &str)] = &[(0, "0"), (4, "1-4"), (19, "5-19"), (99, "20-99")];
+
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Type,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageFeature {
+    PostProcessing,
+    PostProcessContext,
+    CloudTranscription,
+    VocabularyBiasing,
+    FillerRemoval,
+    DisfluencyRemoval,
+    RegexRules,
+    Snippets,
+    SessionVoiceCommands,
+    SessionTagRules,
+    LowConfidenceMarker,
+}
+
+/// Where a session went wrong. Only the category is counted, never the message.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Type,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    Transcription,
+    Paste,
+    History,
+    Other,
+}
+
+impl ErrorCategory {
+    fn from_sink(sink: &str) -> Self {
+        match sink {
+            "paste" => Self::Paste,
+            "history" => Self::History,
+            _ => Self::Other,
+        }
+    }
+}
+
+fn enabled_features(settings: &AppSettings) -> Vec<UsageFeature> {
+    [
+        (settings.post_process_enabled, UsageFeature::PostProcessing),
+        (
+            settings.post_process_enabled
+                && (settings.post_process_context_window
+                    || settings.post_process_context_selection),
+            UsageFeature::PostProcessContext,
+        ),
+        (
+            settings.transcription_provider != TranscriptionProvider::Local,
+            UsageFeature::CloudTranscription,
+        ),
+        (
+            settings.vocabulary_biasing != Some(false) && !settings.custom_words.is_empty(),
+            UsageFeature::VocabularyBiasing,
+        ),
+        (settings.filler_removal, UsageFeature::FillerRemoval),
+        (settings.disfluency_removal, UsageFeature::DisfluencyRemoval),
+        (
+            settings.regex_rules.iter().any(|rule| rule.enabled),
+            UsageFeature::RegexRules,
+        ),
+        (!settings.snippets.is_empty(), UsageFeature::Snippets),
+        (
+            settings.session_voice_commands,
+            UsageFeature::SessionVoiceCommands,
+        ),
+        (
+            !settings.session_tag_rules.is_empty(),
+            UsageFeature::SessionTagRules,
+        ),
+        (
+            settings.low_confidence_marker.is_some(),
+            UsageFeature::LowConfidenceMarker,
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(enabled, feature)| enabled.then_some(feature))
+    .collect()
+}
+
+/// The range `count` falls in, e.g. "5-19".
+fn bucket(count: u32) -> String {
+    BUCKETS
+        .iter()
+        .find(|(max, _)| count <= *max)
+        .map(|(_, label)| label.to_string())
+        .unwrap_or_else(|| "100+".to_string())
+}
+
+fn day(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Counters {
+    /// Sessions per local date, YYYY-MM-DD
+    sessions: BTreeMap<String, u32>,
+    /// Sessions that ran with each feature enabled
+    features: BTreeMap<UsageFeature, u32>,
+    errors: BTreeMap<ErrorCategory, u32>,
+}
+
+impl Counters {
+    fn record(
+        &mut self,
+        date: NaiveDate,
+        features: &[UsageFeature],
+        errors: impl IntoIterator<Item = ErrorCategory>,
+    ) {
+        *self.sessions.entry(day(date)).or_insert(0) += 1;
+        for feature in features {
+            *self.features.entry(*feature).or_insert(0) += 1;
+        }
+        for error in errors {
+            *self.errors.entry(error).or_insert(0) += 1;
+        }
+
+        // ISO dates sort in calendar order
+        let oldest = day(date - Duration::days(RETENTION_DAYS - 1));
+        self.sessions.retain(|day, _| *day >= oldest);
+    }
+
+    fn snapshot(&self) -> UsageSnapshot {
+        UsageSnapshot {
+            sessions_per_day: self
+                .sessions
+                .iter()
+                .map(|(date, count)| DailySessions {
+                    date: date.clone(),
+                    sessions: bucket(*count),
+                })
+                .collect(),
+            features: self
+                .features
+                .iter()
+                .map(|(feature, count)| FeatureUsage {
+                    feature: *feature,
+                    sessions: bucket(*count),
+                })
+                .collect(),
+            errors: self
+                .errors
+                .iter()
+                .map(|(category, count)| ErrorCount {
+                    category: *category,
+                    count: bucket(*count),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct DailySessions {
+    /// YYYY-MM-DD, local time
+    pub date: String,
+    pub sessions: String,
+}
+
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct FeatureUsage {
+    pub feature: UsageFeature,
+    pub sessions: String,
+}
+
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct ErrorCount {
+    pub category: ErrorCategory,
+    pub count: String,
+}
+
+/// The counters as they go into diagnostics, every number replaced by its range.
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct UsageSnapshot {
+    pub sessions_per_day: Vec<DailySessions>,
+    pub features: Vec<FeatureUsage>,
+    pub errors: Vec<ErrorCount>,
+}
+
+pub struct UsageCounters {
+    path: PathBuf,
+    counters: Mutex<Counters>,
+    app_handle: AppHandle,
+}
+
+impl UsageCounters {
+    pub fn new(app_handle: &AppHandle) -> Result<Self> {
+        let path = app_handle.path().app_data_dir()?.join(COUNTERS_FILE);
+        let counters = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                warn!("Ignoring unreadable usage counters: {}", e);
+                Counters::default()
+            }),
+            Err(_) => Counters::default(),
+        };
+
+        Ok(Self {
+            path,
+            counters: Mutex::new(counters),
+            app_handle: app_handle.clone(),
+        })
+    }
+
+    /// Counts a finished session, unless the user turned the counters off.
+    pub fn record_session(&self, report: &SessionReport) {
+        let settings = get_settings(&self.app_handle);
+        if !settings.usage_counters {
+            return;
+        }
+
+        let errors = report
+            .error
+            .iter()
+            .map(|_| ErrorCategory::Transcription)
+            .chain(
+                report
+                    .sinks
+                    .iter()
+                    .filter(|sink| !sink.success)
+                    .map(|sink| ErrorCategory::from_sink(&sink.sink)),
+            );
+
+        let mut counters = self.counters.lock().unwrap();
+        counters.record(
+            Local::now().date_naive(),
+            &enabled_features(&settings),
+            errors,
+        );
+        if let Err(e) = self.save(&counters) {
+            warn!("Failed to save usage counters: {}", e);
+        }
+    }
+
+    pub fn snapshot(&self) -> UsageSnapshot {
+        self.counters.lock().unwrap().snapshot()
+    }
+
+    pub fn reset(&self) -> Result<()> {
+        let mut counters = self.counters.lock().unwrap();
+        *counters = Counters::default();
+        self.save(&counters)?;
+        info!("Reset usage counters");
+        Ok(())
+    }
+
+    fn save(&self, counters: &Counters) -> Result<()> {
+        fs::write(&self.path, serde_json::to_string(counters)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counters_are_bucketed_and_pruned() {
+        let mut counters = Counters::default();
+        let today = NaiveDate::from_ymd_opt(2024, 5, 31).unwrap();
+        let old = today - Duration::days(RETENTION_DAYS);
+
+        counters.record(old, &[], []);
+        for _ in 0..6 {
+            counters.record(
+                today,
+                &[UsageFeature::Snippets],
+                [ErrorCategory::from_sink("paste")],
+            );
+        }
+
+        let snapshot = counters.snapshot();
+        assert_eq!(snapshot.sessions_per_day.len(), 1);
+        assert_eq!(snapshot.sessions_per_day[0].date, "2024-05-31");
+        assert_eq!(snapshot.sessions_per_day[0].sessions, "5-19");
+        assert_eq!(snapshot.features[0].feature, UsageFeature::Snippets);
+        assert_eq!(snapshot.errors[0].category, ErrorCategory::Paste);
+        assert_eq!(bucket(0), "0");
+        assert_eq!(bucket(250), "100+");
+    }
+}
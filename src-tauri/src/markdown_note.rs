@@ -0,0 +1,119 @@
+//! The Markdown note output target: dictations appended to a note in a local folder, such
+//! as an Obsidian vault, instead of being pasted. Each goes under a heading with the time it
+//! was dictated, in the day's note (`2024-05-17.md`) or in a note the user picked.
+
+use crate::settings::AppSettings;
+use chrono::{DateTime, Local};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Appends `text` to the note the settings select, returning the note's path.
+pub fn append(settings: &AppSettings, text: &str) -> Result<PathBuf, String> {
+    let folder = settings
+        .markdown_folder
+        .as_deref()
+        .filter(|folder| !folder.is_empty())
+        .ok_or("No folder is set for Markdown notes")?;
+    let now = Local::now();
+    let path = note_path(Path::new(folder), settings.markdown_file.as_deref(), &now)?;
+    append_entry(&path, text, &now)?;
+    Ok(path)
+}
+
+/// The note in `folder` named `file`, or the day's note, with `.md` added when the name
+/// has no extension. Names may lead into subfolders but not out of `folder`.
+fn note_path(folder: &Path, file: Option<&str>, now: &DateTime<Local>) -> Result<PathBuf, String> {
+    if !folder.is_dir() {
+        return Err(format!(
+            "The notes folder {} doesn't exist",
+            folder.display()
+        ));
+    }
+    let name = match file.map(str::trim).filter(|file| !file.is_empty()) {
+        Some(file) => file.to_string(),
+        None => now.format("%Y-%m-%d").to_string(),
+    };
+    let relative = Path::new(&name);
+    if relative.is_absolute()
+        || relative
+            .components()
+            .any(|part| matches!(part, std::path::Component::ParentDir))
+    {
+        return Err(format!("The note {} is outside the notes folder", name));
+    }
+    let mut path = folder.join(relative);
+    if path.extension().is_none() {
+        path.set_extension("md");
+    }
+    Ok(path)
+}
+
+/// Appends `text` under a heading with the time. A note that doesn't exist yet is created,
+/// titled with the date.
+fn append_entry(path: &Path, text: &str, now: &DateTime<Local>) -> Result<(), String> {
+    let existing = fs::read_to_string(path).ok();
+    let mut entry = String::new();
+    match existing.as_deref() {
+        None => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            entry.push_str(&format!("# {}\n\n", now.format("%Y-%m-%d")));
+        }
+        Some("") => {}
+        // Keep a blank line between the previous content and the new heading
+        Some(content) if content.ends_with("\n\n") => {}
+        Some(content) if content.ends_with('\n') => entry.push('\n'),
+        Some(_) => entry.push_str("\n\n"),
+    }
+    entry.push_str(&format!("## {}\n\n{}\n", now.format("%H:%M"), text.trim()));
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    file.write_all(entry.as_bytes())
+        .map_err(|e| format!("Failed to write to {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn time(hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2024, 5, 17, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_note_path() {
+        let dir = std::env::temp_dir();
+        assert_eq!(
+            note_path(&dir, None, &time(9, 0)).unwrap(),
+            dir.join("2024-05-17.md")
+        );
+        assert_eq!(
+            note_path(&dir, Some("Journal/Inbox"), &time(9, 0)).unwrap(),
+            dir.join("Journal/Inbox.md")
+        );
+        assert!(note_path(&dir, Some("../escape.md"), &time(9, 0)).is_err());
+        assert!(note_path(&dir.join("handy-missing-vault"), None, &time(9, 0)).is_err());
+    }
+
+    #[test]
+    fn test_append_entry_creates_then_appends() {
+        let dir = std::env::temp_dir().join(format!("handy-notes-test-{}", std::process::id()));
+        let path = dir.join("daily/2024-05-17.md");
+        append_entry(&path, "Went for a run.", &time(7, 5)).unwrap();
+        append_entry(&path, " Called Sam. ", &time(12, 30)).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# 2024-05-17\n\n## 07:05\n\nWent for a run.\n\n## 12:30\n\nCalled Sam.\n"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
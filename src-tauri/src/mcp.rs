@@ -0,0 +1,246 @@
+//! Model Context Protocol endpoint, so local AI agents and IDE assistants can use Handy as
+//! a tool. It is served by the local API server at `POST /mcp` with the streamable HTTP
+//! transport, one JSON-RPC message per request and a plain JSON response, and offers tools to
+//! transcribe a file, read the latest transcripts and start or stop a dictation.
+
+use crate::automation;
+use serde_json::{json, Value};
+use tauri::AppHandle;
+
+pub const PROTOCOL_VERSION: &str = "2025-03-26";
+
+/// Transcripts `get_last_transcripts` returns at most
+const MAX_TRANSCRIPTS: u64 = 50;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// The tools, as `tools/list` describes them
+fn tools() -> Value {
+    json!([
+        {
+            "name": "transcribe_file",
+            "description": "Transcribe a WAV file on this computer with the local model.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Absolute path of the WAV file" }
+                },
+                "required": ["path"]
+            }
+        },
+        {
+            "name": "get_last_transcripts",
+            "description": "The latest dictations from the history, newest first.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "limit": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "maximum": MAX_TRANSCRIPTS,
+                        "description": "How many to return, 5 by default"
+                    }
+                }
+            }
+        },
+        {
+            "name": "start_dictation",
+            "description": "Start recording a dictation, as if the shortcut was pressed. The text is delivered to the focused application when it is stopped.",
+            "inputSchema": { "type": "object", "properties": {} }
+        },
+        {
+            "name": "stop_dictation",
+            "description": "Stop the dictation in progress and transcribe it.",
+            "inputSchema": { "type": "object", "properties": {} }
+        }
+    ])
+}
+
+/// Handles a request body, returning the response body, or `None` for notifications,
+/// which get no response.
+pub fn handle(app: &AppHandle, body: &[u8]) -> Option<Value> {
+    match serde_json::from_slice::<Value>(body) {
+        Ok(message) => handle_message(&message, |name, arguments| call_tool(app, name, arguments)),
+        Err(e) => Some(error(Value::Null, PARSE_ERROR, e.to_string())),
+    }
+}
+
+/// Answers one JSON-RPC message, running tools with `call`.
+fn handle_message(
+    message: &Value,
+    call: impl Fn(&str, &Value) -> Result<String, String>,
+) -> Option<Value> {
+    let Some(method) = message.get("method").and_then(Value::as_str) else {
+        // Responses to requests of ours, which are never sent, are ignored as well
+        return message
+            .get("id")
+            .is_none()
+            .then(|| error(Value::Null, INVALID_REQUEST, "Not a JSON-RPC request"));
+    };
+    let id = message.get("id")?.clone();
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = match method {
+        "initialize" => json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "handy", "version": env!("CARGO_PKG_VERSION") },
+        }),
+        "ping" => json!({}),
+        "tools/list" => json!({ "tools": tools() }),
+        "tools/call" => {
+            let Some(name) = params.get("name").and_then(Value::as_str) else {
+                return Some(error(id, INVALID_PARAMS, "Missing the tool name"));
+            };
+            let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+            // Failures of the tool itself are results the agent gets to see
+            let (text, is_error) = match call(name, &arguments) {
+                Ok(text) => (text, false),
+                Err(e) => (e, true),
+            };
+            json!({
+                "content": [{ "type": "text", "text": text }],
+                "isError": is_error,
+            })
+        }
+        _ => {
+            return Some(error(
+                id,
+                METHOD_NOT_FOUND,
+                format!("Unknown method {}", method),
+            ))
+        }
+    };
+    Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+}
+
+fn error(id: Value, code: i64, message: impl Into<String>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message.into() },
+    })
+}
+
+fn call_tool(app: &AppHandle, name: &str, arguments: &Value) -> Result<String, String> {
+    match name {
+        "transcribe_file" => {
+            let path = arguments
+                .get("path")
+                .and_then(Value::as_str)
+                .ok_or("Missing the path argument")?;
+            automation::transcribe_file(app, path)
+        }
+        "get_last_transcripts" => {
+            let limit = arguments
+                .get("limit")
+                .and_then(Value::as_u64)
+                .unwrap_or(5)
+                .clamp(1, MAX_TRANSCRIPTS) as usize;
+            let entries = tauri::async_runtime::block_on(automation::last_transcripts(app, limit))?;
+            let transcripts: Vec<Value> = entries
+                .into_iter()
+                .map(|entry| {
+                    json!({
+                        "id": entry.id,
+                        "timestamp": entry.timestamp,
+                        "title": entry.title,
+                        "text": entry.post_processed_text.unwrap_or(entry.transcription_text),
+                    })
+                })
+                .collect();
+            serde_json::to_string_pretty(&transcripts).map_err(|e| e.to_string())
+        }
+        "start_dictation" => {
+            automation::start_dictation(app, "mcp")?;
+            Ok("Recording started".to_string())
+        }
+        "stop_dictation" => {
+            automation::stop_dictation(app, "mcp")?;
+            Ok(
+                "Recording stopped, the transcription will be delivered to the focused application"
+                    .to_string(),
+            )
+        }
+        _ => Err(format!("Unknown tool {}", name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_tools(_: &str, _: &Value) -> Result<String, String> {
+        Err("no tools".to_string())
+    }
+
+    #[test]
+    fn test_initialize_and_list_tools() {
+        let response = handle_message(
+            &json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+            no_tools,
+        )
+        .unwrap();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["protocolVersion"], PROTOCOL_VERSION);
+
+        let response = handle_message(
+            &json!({ "jsonrpc": "2.0", "id": "a", "method": "tools/list" }),
+            no_tools,
+        )
+        .unwrap();
+        let names: Vec<&str> = response["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect();
+        assert!(names.contains(&"transcribe_file"));
+        assert!(names.contains(&"get_last_transcripts"));
+    }
+
+    #[test]
+    fn test_tool_call_results_and_errors() {
+        let call = |name: &str, arguments: &Value| match name {
+            "echo" => Ok(arguments["text"].as_str().unwrap_or_default().to_string()),
+            _ => Err(format!("Unknown tool {}", name)),
+        };
+        let response = handle_message(
+            &json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "tools/call",
+                "params": { "name": "echo", "arguments": { "text": "hi" } }
+            }),
+            call,
+        )
+        .unwrap();
+        assert_eq!(response["result"]["content"][0]["text"], "hi");
+        assert_eq!(response["result"]["isError"], false);
+
+        let response = handle_message(
+            &json!({ "jsonrpc": "2.0", "id": 3, "method": "tools/call", "params": { "name": "nope" } }),
+            call,
+        )
+        .unwrap();
+        assert_eq!(response["result"]["isError"], true);
+    }
+
+    #[test]
+    fn test_notifications_and_unknown_methods() {
+        assert!(handle_message(
+            &json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+            no_tools
+        )
+        .is_none());
+        let response = handle_message(
+            &json!({ "jsonrpc": "2.0", "id": 4, "method": "resources/list" }),
+            no_tools,
+        )
+        .unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+    }
+}
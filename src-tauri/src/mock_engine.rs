@@ -0,0 +1,200 @@
+//! A simulated transcription engine for frontend work and integration tests, which then
+//! don't need a model download. It answers each transcription with the next utterance of a
+//! script after a delay, so a run gives the same results every time.
+//!
+//! A script is plain text. An utterance ends at an `<eou>` marker, which can say how long
+//! transcribing it takes, as in `<eou 800ms>` or `<eou 2s>`; otherwise the configured delay
+//! per second of audio applies. An empty utterance stands for audio without speech. Lines
+//! starting with `#` are comments, and the script starts over after its last utterance.
+
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::managers::transcription::{TranscriptSegment, TranscriptionOutput};
+use crate::settings::MockEngineSettings;
+use log::debug;
+use std::fs;
+use std::thread;
+use std::time::Duration;
+
+/// Used when the settings name no script
+const BUILTIN_SCRIPT: &str = "\
+# Handy's simulated engine
+The quick brown fox jumps over the lazy dog. <eou>
+This is a simulated transcription, no model was loaded. <eou>
+<eou>
+Testing one, two, three. <eou 1500ms>
+";
+
+#[derive(Clone, Debug, PartialEq)]
+struct Utterance {
+    text: String,
+    /// Time to transcribe it, overriding the delay per second of audio
+    delay: Option<Duration>,
+}
+
+pub struct MockEngine {
+    utterances: Vec<Utterance>,
+    next: usize,
+    delay_per_audio_second: Duration,
+}
+
+impl MockEngine {
+    /// Reads the script, taking the configured load delay like a model would.
+    pub fn load(settings: &MockEngineSettings) -> Result<Self, String> {
+        let script = match &settings.script_path {
+            Some(path) => fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read the script {}: {}", path, e))?,
+            None => BUILTIN_SCRIPT.to_string(),
+        };
+        let utterances = parse_script(&script)?;
+        thread::sleep(Duration::from_millis(settings.load_delay_ms));
+        debug!(
+            "Simulated engine loaded with {} utterances",
+            utterances.len()
+        );
+        Ok(Self {
+            utterances,
+            next: 0,
+            delay_per_audio_second: Duration::from_millis(settings.delay_per_audio_second_ms),
+        })
+    }
+
+    /// The next utterance of the script, as one segment spanning the audio.
+    pub fn transcribe(&mut self, audio: &[f32]) -> TranscriptionOutput {
+        let utterance = &self.utterances[self.next % self.utterances.len()];
+        self.next += 1;
+        let audio_secs = audio.len() as f32 / WHISPER_SAMPLE_RATE as f32;
+        thread::sleep(
+            utterance
+                .delay
+                .unwrap_or_else(|| self.delay_per_audio_second.mul_f32(audio_secs)),
+        );
+        let segments = if utterance.text.is_empty() {
+            Vec::new()
+        } else {
+            vec![TranscriptSegment {
+                start: 0.0,
+                end: audio_secs,
+                text: utterance.text.clone(),
+                confidence: None,
+                words: Vec::new(),
+            }]
+        };
+        TranscriptionOutput {
+            text: utterance.text.clone(),
+            segments,
+        }
+    }
+}
+
+/// Checks that the script at `path` can be used.
+pub fn validate_script(path: &str) -> Result<(), String> {
+    let script =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read the script: {}", e))?;
+    parse_script(&script).map(|_| ())
+}
+
+fn parse_script(script: &str) -> Result<Vec<Utterance>, String> {
+    let mut utterances = Vec::new();
+    let mut text = String::new();
+    for (index, line) in script.lines().enumerate() {
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+        let mut rest = line;
+        while let Some(start) = rest.find("<eou") {
+            let end = rest[start..]
+                .find('>')
+                .map(|end| start + end)
+                .ok_or_else(|| format!("Line {}: unclosed <eou> marker", index + 1))?;
+            text.push(' ');
+            text.push_str(&rest[..start]);
+            let delay = parse_delay(&rest[start + "<eou".len()..end]).ok_or_else(|| {
+                format!("Line {}: invalid marker {}", index + 1, &rest[start..=end])
+            })?;
+            utterances.push(Utterance {
+                text: collapse_whitespace(&text),
+                delay,
+            });
+            text.clear();
+            rest = &rest[end + 1..];
+        }
+        text.push(' ');
+        text.push_str(rest);
+    }
+    // Text after the last marker ends the script
+    let text = collapse_whitespace(&text);
+    if !text.is_empty() {
+        utterances.push(Utterance { text, delay: None });
+    }
+    if utterances.is_empty() {
+        return Err("The script has no utterances".to_string());
+    }
+    Ok(utterances)
+}
+
+/// What follows `<eou` in a marker: nothing, or a delay such as `800ms` or `2s`.
+fn parse_delay(argument: &str) -> Option<Option<Duration>> {
+    let argument = argument.trim();
+    if argument.is_empty() {
+        return Some(None);
+    }
+    if let Some(ms) = argument.strip_suffix("ms") {
+        return ms
+            .trim()
+            .parse()
+            .ok()
+            .map(|ms| Some(Duration::from_millis(ms)));
+    }
+    let secs: f32 = argument.strip_suffix('s')?.trim().parse().ok()?;
+    Duration::try_from_secs_f32(secs).ok().map(Some)
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utterance(text: &str, delay_ms: Option<u64>) -> Utterance {
+        Utterance {
+            text: text.to_string(),
+            delay: delay_ms.map(Duration::from_millis),
+        }
+    }
+
+    #[test]
+    fn test_parse_script() {
+        let script =
+            "# A comment <eou>\nHello there. <eou 250ms> General\nKenobi. <eou 2s>\n<eou>\nThe end";
+        assert_eq!(
+            parse_script(script).unwrap(),
+            [
+                utterance("Hello there.", Some(250)),
+                utterance("General Kenobi.", Some(2000)),
+                utterance("", None),
+                utterance("The end", None),
+            ]
+        );
+        assert!(parse_script(BUILTIN_SCRIPT).is_ok());
+        assert!(parse_script("# Only a comment\n").is_err());
+        assert!(parse_script("Hello <eou soon>").is_err());
+        assert!(parse_script("Hello <eou").is_err());
+    }
+
+    #[test]
+    fn test_transcribe_cycles_through_the_script() {
+        let mut engine = MockEngine {
+            utterances: vec![utterance("One.", Some(0)), utterance("", Some(0))],
+            next: 0,
+            delay_per_audio_second: Duration::ZERO,
+        };
+        let audio = vec![0.0; WHISPER_SAMPLE_RATE as usize * 2];
+        let first = engine.transcribe(&audio);
+        assert_eq!(first.text, "One.");
+        assert_eq!(first.segments[0].end, 2.0);
+        assert!(engine.transcribe(&audio).segments.is_empty());
+        assert_eq!(engine.transcribe(&audio).text, "One.");
+    }
+}
@@ -0,0 +1,75 @@
+//! Loads the model of the focused application's profile while no dictation is running, so
+//! it is already loaded when the user starts dictating there. The active profile stays as
+//! it is, and a model unloaded while idle stays unloaded.
+
+use crate::helpers::focused_app;
+use crate::managers::audio::AudioRecordingManager;
+use crate::managers::model::ModelManager;
+use crate::managers::transcription::TranscriptionManager;
+use crate::profiles;
+use crate::settings::get_settings;
+use log::debug;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// How often the focused application is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Starts the background thread that follows the focused application. It only looks at
+/// the focus while prefetching is on and some profile names applications.
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    thread::spawn(move || {
+        let mut last_focused: Option<String> = None;
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            let settings = get_settings(&app);
+            if !settings.prefetch_profile_models
+                || settings.profiles.iter().all(|p| p.app_patterns.is_empty())
+            {
+                last_focused = None;
+                continue;
+            }
+            if !is_idle(&app) {
+                continue;
+            }
+
+            let focused = focused_app::focused_app();
+            if focused == last_focused {
+                continue;
+            }
+            last_focused = focused.clone();
+            let Some(model_id) = focused
+                .as_deref()
+                .and_then(|focused| profiles::profile_for_app(&settings.profiles, focused))
+                .and_then(|profile| profile.model_id.as_deref())
+            else {
+                continue;
+            };
+
+            let model_manager = app.state::<Arc<ModelManager>>();
+            if !model_manager
+                .get_model_info(model_id)
+                .is_some_and(|model| model.is_downloaded)
+            {
+                continue;
+            }
+            debug!(
+                "Prefetching {} for {}",
+                model_id,
+                focused.as_deref().unwrap_or_default()
+            );
+            // Only replaces a loaded model, so the idle unload isn't undone
+            profiles::reload_model(&app, model_id);
+        }
+    });
+}
+
+fn is_idle(app: &AppHandle) -> bool {
+    let rm = app.state::<Arc<AudioRecordingManager>>();
+    let tm = app.state::<Arc<TranscriptionManager>>();
+    !rm.is_recording() && !tm.has_active_session() && !tm.is_model_loading()
+}
@@ -1,7 +1,13 @@
+use crate::managers::audio::AudioRecordingManager;
+use crate::managers::transcription::TranscriptionManager;
 use crate::settings;
 use crate::settings::OverlayPosition;
 use enigo::{Enigo, Mouse};
-use tauri::{AppHandle, Emitter, Manager, PhysicalPosition, PhysicalSize};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, EventTarget, Manager, PhysicalPosition, PhysicalSize};
 
 #[cfg(not(target_os = "macos"))]
 use log::debug;
@@ -25,9 +31,30 @@ tauri_panel! {
     })
 }
 
+/// How long the name of a newly active profile stays on screen
+const PROFILE_INDICATOR_DURATION: Duration = Duration::from_millis(1500);
+
+/// Counts profile indicators shown, so only the latest one hides the overlay
+static PROFILE_INDICATOR: AtomicU64 = AtomicU64::new(0);
+
 const OVERLAY_WIDTH: f64 = 600.0;  // Increased to accommodate text
 const OVERLAY_HEIGHT: f64 = 200.0; // Increased to allow for multiple lines
 
+/// Label of the caption window. Captions are sent to it alone, so they never show up in
+/// the recording overlay and dictation text never reaches the captions.
+pub const CAPTION_WINDOW: &str = "caption_overlay";
+
+const CAPTION_WIDTH: f64 = 900.0;
+const CAPTION_HEIGHT: f64 = 140.0;
+/// Gap between the captions and the bottom of the screen
+const CAPTION_BOTTOM_OFFSET: f64 = 80.0;
+
+/// Label of the window a held transcription is reviewed and edited in
+pub const PREVIEW_WINDOW: &str = "dictation_preview";
+
+const PREVIEW_WIDTH: f64 = 520.0;
+const PREVIEW_HEIGHT: f64 = 240.0;
+
 #[cfg(target_os = "macos")]
 const OVERLAY_TOP_OFFSET: f64 = 46.0;
 #[cfg(any(target_os = "windows", target_os = "linux"))]
@@ -134,6 +161,33 @@ fn calculate_overlay_position(app_handle: &AppHandle) -> Option<(f64, f64)> {
     None
 }
 
+/// Centers the captions near the bottom of the monitor with the cursor, in logical pixels.
+fn calculate_caption_position(app_handle: &AppHandle) -> Option<(f64, f64)> {
+    let monitor = get_monitor_with_cursor(app_handle)?;
+    let work_area = monitor.work_area();
+    let scale = monitor.scale_factor();
+    let work_area_width = work_area.size.width as f64 / scale;
+    let work_area_height = work_area.size.height as f64 / scale;
+    let work_area_x = work_area.position.x as f64 / scale;
+    let work_area_y = work_area.position.y as f64 / scale;
+
+    let x = work_area_x + (work_area_width - CAPTION_WIDTH) / 2.0;
+    let y = work_area_y + work_area_height - CAPTION_HEIGHT - CAPTION_BOTTOM_OFFSET;
+    Some((x, y))
+}
+
+/// Centers the preview on the monitor with the cursor, in logical pixels.
+fn calculate_preview_position(app_handle: &AppHandle) -> Option<(f64, f64)> {
+    let monitor = get_monitor_with_cursor(app_handle)?;
+    let work_area = monitor.work_area();
+    let scale = monitor.scale_factor();
+    let x = work_area.position.x as f64 / scale
+        + (work_area.size.width as f64 / scale - PREVIEW_WIDTH) / 2.0;
+    let y = work_area.position.y as f64 / scale
+        + (work_area.size.height as f64 / scale - PREVIEW_HEIGHT) / 2.0;
+    Some((x, y))
+}
+
 /// Creates the recording overlay window and keeps it hidden by default
 #[cfg(not(target_os = "macos"))]
 pub fn create_recording_overlay(app_handle: &AppHandle) {
@@ -255,6 +309,13 @@ pub fn show_transcribing_overlay(app_handle: &AppHandle) {
     }
 }
 
+/// Shows or clears the paused state of the recording overlay. The live text stays.
+pub fn set_overlay_paused(app_handle: &AppHandle, paused: bool) {
+    if let Some(overlay_window) = app_handle.get_webview_window("recording_overlay") {
+        let _ = overlay_window.emit("recording-paused", paused);
+    }
+}
+
 /// Updates the overlay window position based on current settings
 pub fn update_overlay_position(app_handle: &AppHandle) {
     if let Some(overlay_window) = app_handle.get_webview_window("recording_overlay") {
@@ -311,6 +372,53 @@ pub fn show_post_processing_overlay(app_handle: &AppHandle) {
     }
 }
 
+/// Whether a dictation has the overlay up, from recording until its transcription is done
+fn dictation_in_progress(app_handle: &AppHandle) -> bool {
+    app_handle
+        .state::<Arc<AudioRecordingManager>>()
+        .is_recording()
+        || app_handle
+            .state::<Arc<TranscriptionManager>>()
+            .has_active_session()
+}
+
+/// Briefly shows the name of the profile just switched to. During a dictation the overlay is
+/// already up, so only the name is sent and the overlay stays.
+pub fn show_profile_indicator(app_handle: &AppHandle, name: &str) {
+    let settings = settings::get_settings(app_handle);
+    if settings.overlay_position == OverlayPosition::None {
+        return;
+    }
+    let Some(overlay_window) = app_handle.get_webview_window("recording_overlay") else {
+        return;
+    };
+
+    let _ = overlay_window.emit("profile-indicator", name);
+    if dictation_in_progress(app_handle) {
+        return;
+    }
+
+    update_overlay_position(app_handle);
+    let _ = overlay_window.show();
+
+    // On Windows, aggressively re-assert "topmost" in the native Z-order after showing
+    #[cfg(target_os = "windows")]
+    force_overlay_topmost(&overlay_window);
+
+    let _ = overlay_window.emit("show-overlay", "profile");
+
+    let shown = PROFILE_INDICATOR.fetch_add(1, Ordering::SeqCst) + 1;
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(PROFILE_INDICATOR_DURATION);
+        // A later indicator, or a dictation started meanwhile, keeps the overlay up
+        if PROFILE_INDICATOR.load(Ordering::SeqCst) == shown && !dictation_in_progress(&app_handle)
+        {
+            hide_recording_overlay(&app_handle);
+        }
+    });
+}
+
 /// Emits transcription text to the overlay for real-time display (appends to existing text)
 pub fn emit_transcription_update(app_handle: &AppHandle, text: &str) {
     if let Some(overlay_window) = app_handle.get_webview_window("recording_overlay") {
@@ -324,3 +432,93 @@ pub fn set_final_transcription(app_handle: &AppHandle, text: &str) {
         let _ = overlay_window.emit("transcription-final", text);
     }
 }
+
+/// Shows the frameless caption window, creating it the first time. It stays on top of other
+/// windows without taking the focus, and can be resized to fit more lines.
+pub fn show_caption_window(app_handle: &AppHandle) -> Result<(), String> {
+    if let Some(caption_window) = app_handle.get_webview_window(CAPTION_WINDOW) {
+        return caption_window.show().map_err(|e| e.to_string());
+    }
+    let (x, y) = calculate_caption_position(app_handle).unwrap_or((0.0, 0.0));
+    tauri::WebviewWindowBuilder::new(
+        app_handle,
+        CAPTION_WINDOW,
+        tauri::WebviewUrl::App("src/captions/index.html".into()),
+    )
+    .title("Captions")
+    .position(x, y)
+    .inner_size(CAPTION_WIDTH, CAPTION_HEIGHT)
+    .shadow(false)
+    .maximizable(false)
+    .minimizable(false)
+    .decorations(false)
+    .always_on_top(true)
+    .visible_on_all_workspaces(true)
+    .skip_taskbar(true)
+    .transparent(true)
+    .focused(false)
+    .build()
+    .map(|_| ())
+    .map_err(|e| format!("Failed to create the caption window: {}", e))
+}
+
+pub fn hide_caption_window(app_handle: &AppHandle) {
+    if let Some(caption_window) = app_handle.get_webview_window(CAPTION_WINDOW) {
+        let _ = caption_window.hide();
+    }
+}
+
+/// Sends an event to the window labelled `label` only. Listeners scoped to other windows
+/// don't receive it, unlike events emitted through a window handle, which go to every
+/// window.
+pub fn emit_to_window<S: Serialize + Clone>(
+    app_handle: &AppHandle,
+    label: &str,
+    event: &str,
+    payload: S,
+) {
+    if app_handle.get_webview_window(label).is_some() {
+        let _ = app_handle.emit_to(EventTarget::webview_window(label), event, payload);
+    }
+}
+
+/// Shows the preview of a held transcription, creating its window the first time. The
+/// window doesn't take the focus until clicked, so a confirm shortcut or spoken command
+/// still pastes into the app the dictation was meant for.
+pub fn show_preview_window(app_handle: &AppHandle, text: &str) -> Result<(), String> {
+    if let Some(preview_window) = app_handle.get_webview_window(PREVIEW_WINDOW) {
+        emit_to_window(app_handle, PREVIEW_WINDOW, "preview-text", text);
+        return preview_window.show().map_err(|e| e.to_string());
+    }
+    // A new window asks for the held text once it has loaded
+    let (x, y) = calculate_preview_position(app_handle).unwrap_or((0.0, 0.0));
+    tauri::WebviewWindowBuilder::new(
+        app_handle,
+        PREVIEW_WINDOW,
+        tauri::WebviewUrl::App("src/preview/index.html".into()),
+    )
+    .title("Dictation Preview")
+    .position(x, y)
+    .inner_size(PREVIEW_WIDTH, PREVIEW_HEIGHT)
+    .resizable(false)
+    .maximizable(false)
+    .minimizable(false)
+    .decorations(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .transparent(true)
+    .focused(false)
+    .build()
+    .map(|_| ())
+    .map_err(|e| format!("Failed to create the preview window: {}", e))
+}
+
+/// Hides the preview, returning whether it had the focus.
+pub fn hide_preview_window(app_handle: &AppHandle) -> bool {
+    let Some(preview_window) = app_handle.get_webview_window(PREVIEW_WINDOW) else {
+        return false;
+    };
+    let focused = preview_window.is_focused().unwrap_or(false);
+    let _ = preview_window.hide();
+    focused
+}
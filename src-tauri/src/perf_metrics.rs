@@ -0,0 +1,169 @@
+//! The `perf-metrics` event stream, for when dictation "feels slow": one event per live
+//! chunk saying where its time went, and a summary once the session is finalized. Users
+//! can attach the numbers to a report and the UI can graph them. Off unless enabled, the
+//! chunks of the running session are only kept for its summary.
+
+use crate::settings::get_settings;
+use log::warn;
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Where the time of one live chunk went, from the recorder handing it over until its text
+/// was shown.
+#[derive(Clone, Debug, Serialize)]
+pub struct ChunkMetrics {
+    pub session: u64,
+    pub utterance: usize,
+    pub is_final: bool,
+    pub audio_ms: u64,
+    /// From capture until a worker took the chunk, waiting for a model load included
+    pub queue_ms: u64,
+    pub inference_ms: u64,
+    /// Merging the text into the live transcript
+    pub post_process_ms: u64,
+    /// Journaling, showing and announcing the text
+    pub output_ms: u64,
+}
+
+/// Timings of the final pass, measured from the end of the recording.
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct FinalTimings {
+    pub inference_ms: u64,
+    /// Text stages and LLM post-processing
+    pub post_process_ms: u64,
+    /// Pasting, or appending to the note
+    pub output_ms: u64,
+    /// From the end of the recording until the text was delivered
+    pub total_ms: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct StageSummary {
+    pub mean_ms: u64,
+    pub p95_ms: u64,
+    pub max_ms: u64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SessionSummary {
+    pub session: u64,
+    pub chunk_count: usize,
+    pub queue: StageSummary,
+    pub inference: StageSummary,
+    pub post_process: StageSummary,
+    pub output: StageSummary,
+    #[serde(rename = "final")]
+    pub final_pass: FinalTimings,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PerfMetricsEvent {
+    Chunk(ChunkMetrics),
+    Session(SessionSummary),
+}
+
+#[derive(Default)]
+pub struct PerfMetrics {
+    chunks: Mutex<Vec<ChunkMetrics>>,
+}
+
+impl PerfMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Sends the metrics of a live chunk and keeps them for the session summary.
+pub fn record_chunk(app: &AppHandle, metrics: ChunkMetrics) {
+    if !get_settings(app).perf_metrics_enabled {
+        return;
+    }
+    emit(app, &PerfMetricsEvent::Chunk(metrics.clone()));
+    if let Some(state) = app.try_state::<PerfMetrics>() {
+        state.chunks.lock().unwrap().push(metrics);
+    }
+}
+
+/// Sends the summary of `session`, dropping the chunks kept for it and any left over from
+/// earlier sessions.
+pub fn finish_session(app: &AppHandle, session: u64, final_pass: FinalTimings) {
+    let chunks: Vec<ChunkMetrics> = match app.try_state::<PerfMetrics>() {
+        Some(state) => std::mem::take(&mut *state.chunks.lock().unwrap()),
+        None => Vec::new(),
+    };
+    if !get_settings(app).perf_metrics_enabled {
+        return;
+    }
+    let chunks: Vec<ChunkMetrics> = chunks
+        .into_iter()
+        .filter(|chunk| chunk.session == session)
+        .collect();
+    emit(
+        app,
+        &PerfMetricsEvent::Session(summarize(session, &chunks, final_pass)),
+    );
+}
+
+fn emit(app: &AppHandle, event: &PerfMetricsEvent) {
+    if let Err(e) = app.emit("perf-metrics", event) {
+        warn!("Failed to emit perf-metrics event: {}", e);
+    }
+}
+
+fn summarize(session: u64, chunks: &[ChunkMetrics], final_pass: FinalTimings) -> SessionSummary {
+    let stage = |ms: fn(&ChunkMetrics) -> u64| stage_summary(chunks.iter().map(ms).collect());
+    SessionSummary {
+        session,
+        chunk_count: chunks.len(),
+        queue: stage(|chunk| chunk.queue_ms),
+        inference: stage(|chunk| chunk.inference_ms),
+        post_process: stage(|chunk| chunk.post_process_ms),
+        output: stage(|chunk| chunk.output_ms),
+        final_pass,
+    }
+}
+
+/// Mean, nearest-rank 95th percentile and maximum, all zero without values.
+fn stage_summary(mut values: Vec<u64>) -> StageSummary {
+    if values.is_empty() {
+        return StageSummary::default();
+    }
+    values.sort_unstable();
+    let rank = (values.len() * 95).div_ceil(100);
+    StageSummary {
+        mean_ms: values.iter().sum::<u64>() / values.len() as u64,
+        p95_ms: values[rank - 1],
+        max_ms: values[values.len() - 1],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stage_summary() {
+        assert_eq!(stage_summary(Vec::new()), StageSummary::default());
+        assert_eq!(
+            stage_summary(vec![40]),
+            StageSummary {
+                mean_ms: 40,
+                p95_ms: 40,
+                max_ms: 40,
+            }
+        );
+        // One slow chunk in twenty is beyond the 95th percentile
+        let mut values = vec![100; 19];
+        values.push(2000);
+        assert_eq!(
+            stage_summary(values),
+            StageSummary {
+                mean_ms: 195,
+                p95_ms: 100,
+                max_ms: 2000,
+            }
+        );
+    }
+}
@@ -0,0 +1,536 @@
+//! The text pipeline a dictation runs through between transcription and output. Its stages
+//! run in the order the settings give, each one switched on or off on its own. The LLM stage
+//! is awaited by the caller, every other stage is a `TextStage`.
+
+use crate::audio_toolkit::apply_custom_words;
+use crate::managers::disfluency::DisfluencyManager;
+use crate::managers::plugins::PluginManager;
+use crate::settings::{AppSettings, PipelineStage, TextStageId};
+use crate::snippets;
+use crate::text_rules;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+
+/// Order and state of the stages when nothing is configured. Stages that rewrite what was
+/// said without being asked to start switched off.
+pub const DEFAULT_PIPELINE: &[(TextStageId, bool)] = &[
+    (TextStageId::Fillers, true),
+    (TextStageId::CustomWords, true),
+    (TextStageId::Itn, false),
+    (TextStageId::Punctuation, false),
+    (TextStageId::Llm, true),
+    (TextStageId::Regex, true),
+    (TextStageId::Plugins, true),
+    (TextStageId::Profanity, false),
+    (TextStageId::Snippets, true),
+];
+
+/// Spoken punctuation and what it is written as
+const SPOKEN_MARKS: &[(&[&str], &str)] = &[
+    (&["new", "paragraph"], "\n\n"),
+    (&["new", "line"], "\n"),
+    (&["question", "mark"], "?"),
+    (&["exclamation", "mark"], "!"),
+    (&["exclamation", "point"], "!"),
+    (&["full", "stop"], "."),
+    (&["period"], "."),
+    (&["comma"], ","),
+    (&["semicolon"], ";"),
+    (&["colon"], ":"),
+];
+
+/// Words the profanity stage masks
+const PROFANITY: &[&str] = &[
+    "fuck",
+    "fucking",
+    "fucked",
+    "motherfucker",
+    "shit",
+    "bullshit",
+    "bitch",
+    "asshole",
+    "bastard",
+    "cunt",
+    "dick",
+    "prick",
+];
+
+const UNITS: &[&str] = &[
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const TENS: &[&str] = &[
+    "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+/// A stage that rewrites the text on its own, without waiting on anything.
+pub trait TextStage {
+    fn id(&self) -> TextStageId;
+
+    fn apply(&self, text: &str, settings: &AppSettings) -> String;
+}
+
+/// A step of the pipeline as the caller runs it.
+pub enum Step {
+    Text(Box<dyn TextStage + Send>),
+    /// LLM post-processing, or the Chinese variant conversion in its place
+    Llm,
+}
+
+/// The enabled stages in order. Stages missing from the settings, e.g. ones added since
+/// they were saved, take their default state and go after the configured ones.
+pub fn enabled_stages(settings: &AppSettings) -> Vec<TextStageId> {
+    let configured = settings.text_pipeline.iter().map(|s| (s.stage, s.enabled));
+    let missing = DEFAULT_PIPELINE
+        .iter()
+        .copied()
+        .filter(|(id, _)| !settings.text_pipeline.iter().any(|s| s.stage == *id));
+    let mut stages: Vec<TextStageId> = Vec::new();
+    for (id, enabled) in configured.chain(missing) {
+        if enabled && !stages.contains(&id) {
+            stages.push(id);
+        }
+    }
+    stages
+}
+
+pub fn is_enabled(settings: &AppSettings, id: TextStageId) -> bool {
+    enabled_stages(settings).contains(&id)
+}
+
+pub fn default_pipeline() -> Vec<PipelineStage> {
+    DEFAULT_PIPELINE
+        .iter()
+        .map(|&(stage, enabled)| PipelineStage { stage, enabled })
+        .collect()
+}
+
+/// The steps to run for `settings`.
+pub fn steps(app: &AppHandle, settings: &AppSettings) -> Vec<Step> {
+    enabled_stages(settings)
+        .into_iter()
+        .map(|id| match id {
+            TextStageId::Llm => Step::Llm,
+            TextStageId::Fillers => Step::Text(Box::new(FillersStage(app.clone()))),
+            TextStageId::Snippets => Step::Text(Box::new(SnippetsStage(app.clone()))),
+            TextStageId::Plugins => Step::Text(Box::new(PluginsStage(app.clone()))),
+            TextStageId::CustomWords => Step::Text(Box::new(CustomWordsStage)),
+            TextStageId::Itn => Step::Text(Box::new(ItnStage)),
+            TextStageId::Punctuation => Step::Text(Box::new(PunctuationStage)),
+            TextStageId::Regex => Step::Text(Box::new(RegexStage)),
+            TextStageId::Profanity => Step::Text(Box::new(ProfanityStage)),
+        })
+        .collect()
+}
+
+/// Rule-based and learned filler removal, each when switched on in the settings.
+pub struct FillersStage(pub AppHandle);
+
+impl TextStage for FillersStage {
+    fn id(&self) -> TextStageId {
+        TextStageId::Fillers
+    }
+
+    fn apply(&self, text: &str, _settings: &AppSettings) -> String {
+        let disfluency = Arc::clone(&self.0.state::<Arc<DisfluencyManager>>());
+        disfluency.clean(text)
+    }
+}
+
+/// Correction towards the custom words of the current language and profile. It also runs
+/// on the engine's output, this stage catches what later stages wrote differently.
+pub struct CustomWordsStage;
+
+impl TextStage for CustomWordsStage {
+    fn id(&self) -> TextStageId {
+        TextStageId::CustomWords
+    }
+
+    fn apply(&self, text: &str, settings: &AppSettings) -> String {
+        let hints: Vec<_> = settings
+            .active_custom_words()
+            .into_iter()
+            .map(|word| word.hint())
+            .collect();
+        if hints.is_empty() {
+            return text.to_string();
+        }
+        apply_custom_words(text, &hints, settings.word_correction_threshold)
+    }
+}
+
+/// Inverse text normalization: English number words written as digits.
+pub struct ItnStage;
+
+impl TextStage for ItnStage {
+    fn id(&self) -> TextStageId {
+        TextStageId::Itn
+    }
+
+    fn apply(&self, text: &str, settings: &AppSettings) -> String {
+        if !is_english(settings) {
+            return text.to_string();
+        }
+        numbers_to_digits(text)
+    }
+}
+
+/// Spoken punctuation, "comma" or "new line", written as the marks themselves.
+pub struct PunctuationStage;
+
+impl TextStage for PunctuationStage {
+    fn id(&self) -> TextStageId {
+        TextStageId::Punctuation
+    }
+
+    fn apply(&self, text: &str, settings: &AppSettings) -> String {
+        if !is_english(settings) {
+            return text.to_string();
+        }
+        spoken_punctuation(text)
+    }
+}
+
+/// The user's find/replace rules for the current language and profile.
+pub struct RegexStage;
+
+impl TextStage for RegexStage {
+    fn id(&self) -> TextStageId {
+        TextStageId::Regex
+    }
+
+    fn apply(&self, text: &str, settings: &AppSettings) -> String {
+        text_rules::apply(text, &settings.active_regex_rules())
+    }
+}
+
+/// The user's WebAssembly plugins, in file name order.
+pub struct PluginsStage(pub AppHandle);
+
+impl TextStage for PluginsStage {
+    fn id(&self) -> TextStageId {
+        TextStageId::Plugins
+    }
+
+    fn apply(&self, text: &str, settings: &AppSettings) -> String {
+        let plugins = Arc::clone(&self.0.state::<Arc<PluginManager>>());
+        plugins.run_all(text, settings)
+    }
+}
+
+pub struct ProfanityStage;
+
+impl TextStage for ProfanityStage {
+    fn id(&self) -> TextStageId {
+        TextStageId::Profanity
+    }
+
+    fn apply(&self, text: &str, _settings: &AppSettings) -> String {
+        mask_profanity(text)
+    }
+}
+
+/// Spoken snippet triggers replaced with their templates.
+pub struct SnippetsStage(pub AppHandle);
+
+impl TextStage for SnippetsStage {
+    fn id(&self) -> TextStageId {
+        TextStageId::Snippets
+    }
+
+    fn apply(&self, text: &str, settings: &AppSettings) -> String {
+        snippets::expand(&self.0, settings, text)
+    }
+}
+
+/// The number and spoken punctuation stages know English words only.
+fn is_english(settings: &AppSettings) -> bool {
+    settings.selected_language == "auto" || settings.selected_language.starts_with("en")
+}
+
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// What a number word adds to the number being read.
+#[derive(Clone, Copy, PartialEq)]
+enum NumberWord {
+    /// Zero to nineteen
+    Unit(u64),
+    Tens(u64),
+    Hundred,
+    /// Thousand, million or billion
+    Scale(u64),
+}
+
+fn number_word(word: &str) -> Option<NumberWord> {
+    if let Some(value) = UNITS.iter().position(|unit| *unit == word) {
+        return Some(NumberWord::Unit(value as u64));
+    }
+    if let Some(index) = TENS.iter().position(|tens| *tens == word) {
+        return Some(NumberWord::Tens(20 + 10 * index as u64));
+    }
+    match word {
+        "hundred" => Some(NumberWord::Hundred),
+        "thousand" => Some(NumberWord::Scale(1_000)),
+        "million" => Some(NumberWord::Scale(1_000_000)),
+        "billion" => Some(NumberWord::Scale(1_000_000_000)),
+        _ => None,
+    }
+}
+
+/// Whether `next` continues a number ending in `last`, so "twenty three" is one number but
+/// "two three" are two.
+fn continues(last: NumberWord, next: NumberWord) -> bool {
+    match (last, next) {
+        (NumberWord::Unit(_), NumberWord::Unit(_) | NumberWord::Tens(_)) => false,
+        (NumberWord::Tens(_), NumberWord::Unit(unit)) => unit < 10 && unit > 0,
+        (NumberWord::Tens(_), NumberWord::Tens(_)) => false,
+        (NumberWord::Hundred | NumberWord::Scale(_), _) => true,
+        (_, NumberWord::Hundred | NumberWord::Scale(_)) => true,
+    }
+}
+
+/// Reads a run of number words as one number.
+fn number_value(words: &[NumberWord]) -> u64 {
+    let mut total = 0;
+    let mut current = 0;
+    for word in words {
+        match *word {
+            NumberWord::Unit(value) | NumberWord::Tens(value) => current += value,
+            NumberWord::Hundred => current = current.max(1) * 100,
+            NumberWord::Scale(scale) => {
+                total += current.max(1) * scale;
+                current = 0;
+            }
+        }
+    }
+    total + current
+}
+
+/// Writes runs of English number words as digits, "twenty three" as "23" and "one hundred
+/// and five" as "105". A single word below ten stays a word, "one of them" reads better
+/// than "1 of them".
+pub fn numbers_to_digits(text: &str) -> String {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let mut out: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let mut run: Vec<NumberWord> = Vec::new();
+        let mut end = i;
+        while end < tokens.len() {
+            let word = normalize(tokens[end]);
+            // "hundred and five", the "and" belongs to the number only between its parts
+            if word == "and"
+                && matches!(run.last(), Some(NumberWord::Hundred | NumberWord::Scale(_)))
+            {
+                let next = tokens.get(end + 1).map(|t| normalize(t));
+                if next
+                    .as_deref()
+                    .and_then(number_word)
+                    .is_some_and(|next| matches!(next, NumberWord::Unit(_) | NumberWord::Tens(_)))
+                    && !has_inner_punctuation(tokens[end - 1])
+                {
+                    end += 1;
+                    continue;
+                }
+                break;
+            }
+            let Some(number) = number_word(&word) else {
+                break;
+            };
+            if run.last().is_some_and(|last| !continues(*last, number)) {
+                break;
+            }
+            run.push(number);
+            end += 1;
+            // A comma or period after a word ends the number there
+            if has_inner_punctuation(tokens[end - 1]) {
+                break;
+            }
+        }
+
+        let single_small = run.len() == 1 && matches!(run[0], NumberWord::Unit(v) if v < 10);
+        if run.is_empty() || single_small {
+            out.push(tokens[i].to_string());
+            i += 1;
+            continue;
+        }
+        let (_, _, suffix) = split_punctuation(tokens[end - 1]);
+        let (prefix, _, _) = split_punctuation(tokens[i]);
+        out.push(format!("{}{}{}", prefix, number_value(&run), suffix));
+        i = end;
+    }
+    out.join(" ")
+}
+
+/// Splits a token into its leading punctuation, the word and its trailing punctuation.
+fn split_punctuation(token: &str) -> (&str, &str, &str) {
+    let start = token.len()
+        - token
+            .trim_start_matches(|c: char| !c.is_alphanumeric())
+            .len();
+    let end = token
+        .trim_end_matches(|c: char| !c.is_alphanumeric())
+        .len()
+        .max(start);
+    (&token[..start], &token[start..end], &token[end..])
+}
+
+fn has_inner_punctuation(token: &str) -> bool {
+    token.ends_with([',', '.', ';', ':', '?', '!'])
+}
+
+/// Writes spoken punctuation as marks: "see you comma Alex period" becomes "see you, Alex."
+/// Punctuation the engine put around the spoken mark is dropped, and the word after a
+/// sentence end or a new line is capitalized.
+pub fn spoken_punctuation(text: &str) -> String {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let normalized: Vec<String> = tokens.iter().map(|token| normalize(token)).collect();
+    let mut out = String::with_capacity(text.len());
+    let mut capitalize = false;
+    let mut i = 0;
+    while i < tokens.len() {
+        let mark = SPOKEN_MARKS.iter().find(|(words, _)| {
+            normalized.len() >= i + words.len()
+                && words
+                    .iter()
+                    .zip(&normalized[i..])
+                    .all(|(word, token)| word == token)
+        });
+        if let Some((words, mark)) = mark {
+            let kept = out
+                .trim_end_matches([',', '.', ';', ':', '?', '!', ' '])
+                .len();
+            out.truncate(kept);
+            out.push_str(mark);
+            capitalize = mark.contains(['.', '?', '!', '\n']);
+            i += words.len();
+            continue;
+        }
+
+        if !out.is_empty() && !out.ends_with('\n') {
+            out.push(' ');
+        }
+        let token = if out.is_empty() || !capitalize {
+            tokens[i].to_string()
+        } else {
+            let mut chars = tokens[i].chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        };
+        out.push_str(&token);
+        capitalize = false;
+        i += 1;
+    }
+    out
+}
+
+/// Masks the letters of profane words after the first, keeping punctuation.
+pub fn mask_profanity(text: &str) -> String {
+    text.split(' ')
+        .map(|token| {
+            let (prefix, word, suffix) = split_punctuation(token);
+            if !PROFANITY.contains(&word.to_lowercase().as_str()) {
+                return token.to_string();
+            }
+            let mut chars = word.chars();
+            let first = chars.next().map(String::from).unwrap_or_default();
+            format!("{}{}{}{}", prefix, first, "*".repeat(chars.count()), suffix)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::get_default_settings;
+
+    #[test]
+    fn test_numbers_to_digits() {
+        assert_eq!(numbers_to_digits("twenty three apples"), "23 apples");
+        assert_eq!(
+            numbers_to_digits("one hundred and five people"),
+            "105 people"
+        );
+        assert_eq!(numbers_to_digits("two thousand twenty four."), "2024.");
+        assert_eq!(numbers_to_digits("one of them"), "one of them");
+        assert_eq!(numbers_to_digits("two three"), "two three");
+        assert_eq!(numbers_to_digits("bread and butter"), "bread and butter");
+        assert_eq!(numbers_to_digits("a hundred and"), "a 100 and");
+    }
+
+    #[test]
+    fn test_spoken_punctuation() {
+        assert_eq!(
+            spoken_punctuation("see you comma Alex period thanks"),
+            "see you, Alex. Thanks"
+        );
+        // Engines often punctuate the spoken mark itself
+        assert_eq!(
+            spoken_punctuation("Are you there, question mark."),
+            "Are you there?"
+        );
+        assert_eq!(
+            spoken_punctuation("Dear Sam, new line. thanks"),
+            "Dear Sam\nThanks"
+        );
+    }
+
+    #[test]
+    fn test_mask_profanity() {
+        assert_eq!(mask_profanity("Oh shit, really"), "Oh s***, really");
+        assert_eq!(mask_profanity("shiitake"), "shiitake");
+    }
+
+    #[test]
+    fn test_enabled_stages_follow_settings_order() {
+        let mut settings = get_default_settings();
+        settings.text_pipeline = vec![
+            PipelineStage {
+                stage: TextStageId::Regex,
+                enabled: true,
+            },
+            PipelineStage {
+                stage: TextStageId::Fillers,
+                enabled: false,
+            },
+            PipelineStage {
+                stage: TextStageId::Profanity,
+                enabled: true,
+            },
+        ];
+        let stages = enabled_stages(&settings);
+        assert_eq!(stages[..2], [TextStageId::Regex, TextStageId::Profanity]);
+        // Stages missing from the settings come after with their defaults
+        assert!(!stages.contains(&TextStageId::Fillers));
+        assert!(stages.contains(&TextStageId::Snippets));
+        assert!(!stages.contains(&TextStageId::Itn));
+        assert!(ProfanityStage.apply("shit", &settings) == "s***");
+    }
+}
@@ -0,0 +1,270 @@
+//! The battery policy: while the machine runs on battery the model is unloaded sooner, a
+//! smaller model can stand in for the selected one, the audio level meter reports less
+//! often and inference yields to the foreground apps. The power source is polled, and a
+//! `power-policy-changed` event is sent whenever the policy starts or stops applying.
+
+use crate::managers::audio::AudioRecordingManager;
+use crate::managers::model::ModelManager;
+use crate::profiles;
+use crate::settings::{get_settings, AppSettings, InferencePriority, ModelUnloadTimeout};
+use log::info;
+use serde::Serialize;
+use specta::Type;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// How often the power source is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(20);
+
+static ON_BATTERY: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct PowerPolicyEvent {
+    pub on_battery: bool,
+    /// On battery with the battery policy enabled
+    pub policy_active: bool,
+}
+
+/// Starts the background thread that follows the power source.
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    thread::spawn(move || loop {
+        // Without a battery, or where it can't be told, the machine counts as plugged in
+        let on_battery = on_battery_power().unwrap_or(false);
+        if ON_BATTERY.swap(on_battery, Ordering::Relaxed) != on_battery {
+            info!(
+                "Running on {}",
+                if on_battery { "battery" } else { "AC power" }
+            );
+            apply(&app);
+        }
+        thread::sleep(POLL_INTERVAL);
+    });
+}
+
+pub fn state(settings: &AppSettings) -> PowerPolicyEvent {
+    PowerPolicyEvent {
+        on_battery: ON_BATTERY.load(Ordering::Relaxed),
+        policy_active: is_active(settings),
+    }
+}
+
+/// Brings the recorder and the loaded model in line with the policy, after the power
+/// source or the policy changed. The unload timeout and inference priority are read as
+/// they're needed.
+pub fn apply(app: &AppHandle) {
+    let settings = get_settings(app);
+    app.state::<Arc<AudioRecordingManager>>()
+        .update_audio_level_rate(audio_level_rate_hz(&settings));
+    let model_manager = app.state::<Arc<ModelManager>>();
+    profiles::reload_model(app, &preferred_model(&settings, &model_manager));
+    let _ = app.emit("power-policy-changed", state(&settings));
+}
+
+fn is_active(settings: &AppSettings) -> bool {
+    settings.battery_policy.enabled && ON_BATTERY.load(Ordering::Relaxed)
+}
+
+pub fn model_unload_timeout(settings: &AppSettings) -> ModelUnloadTimeout {
+    if !is_active(settings) {
+        return settings.model_unload_timeout;
+    }
+    // The shorter of the two, Never being the longest
+    let seconds = |timeout: ModelUnloadTimeout| timeout.to_seconds().unwrap_or(u64::MAX);
+    let battery = settings.battery_policy.model_unload_timeout;
+    if seconds(battery) < seconds(settings.model_unload_timeout) {
+        battery
+    } else {
+        settings.model_unload_timeout
+    }
+}
+
+/// The model to load: the battery model while the policy applies and it is downloaded,
+/// otherwise the selected one.
+pub fn preferred_model(settings: &AppSettings, model_manager: &ModelManager) -> String {
+    let battery_model = settings
+        .battery_policy
+        .model_id
+        .as_ref()
+        .filter(|_| is_active(settings))
+        .filter(|id| {
+            model_manager
+                .get_model_info(id)
+                .is_some_and(|model| model.is_downloaded)
+        });
+    battery_model.unwrap_or(&settings.selected_model).clone()
+}
+
+pub fn audio_level_rate_hz(settings: &AppSettings) -> u32 {
+    if is_active(settings) {
+        settings
+            .audio_level_rate_hz
+            .min(settings.battery_policy.audio_level_rate_hz)
+    } else {
+        settings.audio_level_rate_hz
+    }
+}
+
+pub fn inference_priority(settings: &AppSettings) -> InferencePriority {
+    if is_active(settings) {
+        settings
+            .inference_priority
+            .max(settings.battery_policy.inference_priority)
+    } else {
+        settings.inference_priority
+    }
+}
+
+/// Whether the machine runs on battery, `None` where that can't be told.
+#[cfg(target_os = "linux")]
+fn on_battery_power() -> Option<bool> {
+    let supplies: Vec<PowerSupply> = std::fs::read_dir("/sys/class/power_supply")
+        .ok()?
+        .flatten()
+        .map(|entry| {
+            let read = |name: &str| {
+                std::fs::read_to_string(entry.path().join(name))
+                    .map(|value| value.trim().to_string())
+                    .unwrap_or_default()
+            };
+            PowerSupply {
+                kind: read("type"),
+                scope: read("scope"),
+                online: read("online"),
+                status: read("status"),
+            }
+        })
+        .collect();
+    on_battery_from_supplies(&supplies)
+}
+
+#[cfg(target_os = "macos")]
+fn on_battery_power() -> Option<bool> {
+    let output = std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .ok()?;
+    on_battery_from_pmset(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(target_os = "windows")]
+fn on_battery_power() -> Option<bool> {
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status = SYSTEM_POWER_STATUS::default();
+    unsafe { GetSystemPowerStatus(&mut status).ok()? };
+    match status.ACLineStatus {
+        0 => Some(true),
+        1 => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn on_battery_power() -> Option<bool> {
+    None
+}
+
+/// An entry of `/sys/class/power_supply`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+struct PowerSupply {
+    /// `Mains`, `Battery`, `USB`, …
+    kind: String,
+    /// `Device` for the battery of a peripheral, such as a wireless mouse
+    scope: String,
+    /// `1` for a connected charger
+    online: String,
+    /// `Charging`, `Discharging`, `Full`, … for a battery
+    status: String,
+}
+
+/// On battery when a system battery discharges and no charger is connected.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn on_battery_from_supplies(supplies: &[PowerSupply]) -> Option<bool> {
+    let batteries: Vec<&PowerSupply> = supplies
+        .iter()
+        .filter(|supply| supply.kind == "Battery" && supply.scope != "Device")
+        .collect();
+    if batteries.is_empty() {
+        return None;
+    }
+    let charger_online = supplies
+        .iter()
+        .any(|supply| supply.kind != "Battery" && supply.online == "1");
+    Some(!charger_online && batteries.iter().any(|b| b.status == "Discharging"))
+}
+
+/// Reads the first line of `pmset -g batt`, e.g. `Now drawing from 'Battery Power'`.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn on_battery_from_pmset(output: &str) -> Option<bool> {
+    let source = output.lines().next()?.split('\'').nth(1)?;
+    match source {
+        "Battery Power" => Some(true),
+        "AC Power" | "UPS Power" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn supply(kind: &str, online: &str, status: &str) -> PowerSupply {
+        PowerSupply {
+            kind: kind.to_string(),
+            scope: String::new(),
+            online: online.to_string(),
+            status: status.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_on_battery_from_supplies() {
+        assert_eq!(
+            on_battery_from_supplies(&[
+                supply("Mains", "0", ""),
+                supply("Battery", "", "Discharging"),
+            ]),
+            Some(true)
+        );
+        assert_eq!(
+            on_battery_from_supplies(&[
+                supply("Mains", "1", ""),
+                supply("Battery", "", "Charging"),
+            ]),
+            Some(false)
+        );
+        // A full battery on a USB-C charger
+        assert_eq!(
+            on_battery_from_supplies(&[supply("USB", "1", ""), supply("Battery", "", "Full")]),
+            Some(false)
+        );
+        // A desktop with a wireless mouse
+        let mouse = PowerSupply {
+            scope: "Device".to_string(),
+            ..supply("Battery", "", "Discharging")
+        };
+        assert_eq!(
+            on_battery_from_supplies(&[supply("Mains", "1", ""), mouse]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_on_battery_from_pmset() {
+        assert_eq!(
+            on_battery_from_pmset(
+                "Now drawing from 'Battery Power'\n -InternalBattery-0 (id=1234)\t85%; discharging;"
+            ),
+            Some(true)
+        );
+        assert_eq!(
+            on_battery_from_pmset("Now drawing from 'AC Power'\n"),
+            Some(false)
+        );
+        assert_eq!(on_battery_from_pmset(""), None);
+    }
+}
@@ -0,0 +1,362 @@
+//! Named dictation profiles, e.g. "email", "code" and "chat". Switching to one copies its
+//! settings over the base settings, so the rest of the app keeps reading plain settings.
+//! The base is saved when the first profile is applied and restored on every switch, so
+//! nothing of one profile carries over to the next, and switching to none brings it back.
+//! A profile can also be switched to by saying "switch to the code profile".
+
+use crate::managers::audio::AudioRecordingManager;
+use crate::managers::model::ModelManager;
+use crate::managers::transcription::TranscriptionManager;
+use crate::overlay;
+use crate::settings::{get_settings, write_settings, AppSettings, DictationProfile};
+use crate::tray;
+use log::{error, info};
+use std::sync::Arc;
+use std::thread;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Copies the fields `profile` sets into `settings`.
+pub fn apply(settings: &mut AppSettings, profile: &DictationProfile) {
+    if let Some(model_id) = &profile.model_id {
+        settings.selected_model = model_id.clone();
+    }
+    if let Some(language) = &profile.selected_language {
+        settings.selected_language = language.clone();
+    }
+    if let Some(enabled) = profile.post_process_enabled {
+        settings.post_process_enabled = enabled;
+    }
+    if let Some(provider_id) = &profile.post_process_provider_id {
+        settings.post_process_provider_id = provider_id.clone();
+    }
+    if let Some(prompt_id) = &profile.post_process_prompt_id {
+        settings.post_process_selected_prompt_id = Some(prompt_id.clone());
+    }
+    if let Some(formatting) = profile.text_formatting {
+        settings.text_formatting = formatting;
+    }
+    if let Some(paste_method) = profile.paste_method {
+        settings.paste_method = paste_method;
+    }
+    if let Some(target) = profile.output_target {
+        settings.output_target = target;
+    }
+    if let Some(sensitivity) = profile.endpoint_sensitivity {
+        settings.endpoint_sensitivity = sensitivity;
+    }
+    if let Some(provider) = profile.transcription_provider {
+        settings.transcription_provider = provider;
+    }
+}
+
+/// Applies `profile` over the base settings, saving them first when no profile was applied.
+pub fn overlay(settings: &mut AppSettings, profile: &DictationProfile) {
+    restore_base(settings);
+    settings.profile_base = Some(base_of(settings));
+    apply(settings, profile);
+}
+
+/// Puts back the settings saved before the first profile was applied, leaving none applied.
+pub fn restore_base(settings: &mut AppSettings) {
+    if let Some(base) = settings.profile_base.take() {
+        apply(settings, &base);
+        // `apply` only sets a prompt, the base may have had none
+        settings.post_process_selected_prompt_id = base.post_process_prompt_id;
+    }
+}
+
+/// The current values of every setting a profile can override.
+fn base_of(settings: &AppSettings) -> DictationProfile {
+    DictationProfile {
+        id: String::new(),
+        name: String::new(),
+        model_id: Some(settings.selected_model.clone()),
+        selected_language: Some(settings.selected_language.clone()),
+        post_process_enabled: Some(settings.post_process_enabled),
+        post_process_provider_id: Some(settings.post_process_provider_id.clone()),
+        post_process_prompt_id: settings.post_process_selected_prompt_id.clone(),
+        text_formatting: Some(settings.text_formatting),
+        paste_method: Some(settings.paste_method),
+        output_target: Some(settings.output_target),
+        endpoint_sensitivity: Some(settings.endpoint_sensitivity),
+        transcription_provider: Some(settings.transcription_provider),
+        app_patterns: Vec::new(),
+    }
+}
+
+/// The profile after `active` in order, wrapping around. The first one when none is active
+/// or the active one was removed.
+pub fn next_profile<'a>(
+    profiles: &'a [DictationProfile],
+    active: Option<&str>,
+) -> Option<&'a DictationProfile> {
+    let next = active
+        .and_then(|id| profiles.iter().position(|p| p.id == id))
+        .map_or(0, |index| (index + 1) % profiles.len());
+    profiles.get(next)
+}
+
+/// The first profile meant for application `app`.
+pub fn profile_for_app<'a>(
+    profiles: &'a [DictationProfile],
+    app: &str,
+) -> Option<&'a DictationProfile> {
+    let app = app.to_lowercase();
+    profiles.iter().find(|profile| {
+        profile.app_patterns.iter().any(|pattern| {
+            let pattern = pattern.trim().to_lowercase();
+            !pattern.is_empty() && app.contains(&pattern)
+        })
+    })
+}
+
+/// Splits a leading "Switch to [the] <name> profile" sentence off `text`, matching `<name>`
+/// against the profile names regardless of case and punctuation. Returns the profile and the
+/// text that follows the command.
+pub fn extract_switch_command<'a>(
+    text: &str,
+    profiles: &'a [DictationProfile],
+) -> Option<(&'a DictationProfile, String)> {
+    let text = text.trim_start();
+    let end = text
+        .find(['.', '!', '?', '\n'])
+        .map(|i| i + 1)
+        .unwrap_or(text.len());
+    let sentence = words(&text[..end]);
+    let sentence: Vec<&str> = sentence.iter().map(String::as_str).collect();
+
+    let name = sentence.strip_prefix(&["switch", "to"])?;
+    let name = name.strip_prefix(&["the"]).unwrap_or(name);
+    let name = name.strip_suffix(&["profile"])?;
+    let profile = profiles
+        .iter()
+        .find(|p| !name.is_empty() && words(&p.name) == name)?;
+
+    Some((profile, text[end..].trim_start().to_string()))
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Makes profile `id` the active one, emits `profile-changed` and briefly shows its name.
+/// When the profile names another model than the loaded one, that model is loaded in the
+/// background; dictation started meanwhile waits for it like after startup.
+pub fn switch_to(app: &AppHandle, id: &str) -> Result<DictationProfile, String> {
+    let mut settings = get_settings(app);
+    let profile = settings
+        .profiles
+        .iter()
+        .find(|p| p.id == id)
+        .cloned()
+        .ok_or_else(|| format!("Profile '{}' not found", id))?;
+
+    if let Some(model_id) = &profile.model_id {
+        let model_manager = app.state::<Arc<ModelManager>>();
+        if !model_manager
+            .get_model_info(model_id)
+            .is_some_and(|model| model.is_downloaded)
+        {
+            return Err(format!(
+                "Profile '{}' uses model '{}', which isn't downloaded",
+                profile.name, model_id
+            ));
+        }
+    }
+
+    overlay(&mut settings, &profile);
+    settings.active_profile_id = Some(profile.id.clone());
+    write_settings(app, settings);
+    info!("Switched to profile '{}'", profile.name);
+
+    if let Some(model_id) = &profile.model_id {
+        reload_model(app, model_id);
+    }
+    if profile.endpoint_sensitivity.is_some() {
+        app.state::<Arc<AudioRecordingManager>>()
+            .update_endpointing();
+    }
+    let _ = app.emit("profile-changed", &profile);
+    overlay::show_profile_indicator(app, &profile.name);
+    Ok(profile)
+}
+
+/// Leaves the active profile for the base settings, emitting `profile-cleared`.
+pub fn clear(app: &AppHandle) {
+    let mut settings = get_settings(app);
+    if settings.active_profile_id.take().is_none() && settings.profile_base.is_none() {
+        return;
+    }
+    restore_base(&mut settings);
+    let model_id = settings.selected_model.clone();
+    write_settings(app, settings);
+    info!("Switched back to the base settings");
+
+    reload_model(app, &model_id);
+    app.state::<Arc<AudioRecordingManager>>()
+        .update_endpointing();
+    let _ = app.emit("profile-cleared", ());
+}
+
+/// Loads `model_id` when another model is loaded. Nothing is loaded when no model is, the
+/// next dictation loads the selected one anyway.
+pub fn reload_model(app: &AppHandle, model_id: &str) {
+    let tm = app.state::<Arc<TranscriptionManager>>();
+    if !tm.is_model_loaded() || tm.get_current_model().as_deref() == Some(model_id) {
+        return;
+    }
+    let app = app.clone();
+    let model_id = model_id.to_string();
+    thread::spawn(move || {
+        let tm = app.state::<Arc<TranscriptionManager>>();
+        if let Err(e) = tm.load_model(&model_id) {
+            error!("Failed to load model {} for the profile: {}", model_id, e);
+        }
+        tray::refresh_tray_menu(&app);
+    });
+}
+
+/// Switches to the next profile, `None` when there are none.
+pub fn cycle(app: &AppHandle) -> Result<Option<DictationProfile>, String> {
+    let settings = get_settings(app);
+    let Some(next) = next_profile(&settings.profiles, settings.active_profile_id.as_deref()) else {
+        return Ok(None);
+    };
+    switch_to(app, &next.id).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::{get_default_settings, TextFormatting, TranscriptionProvider};
+
+    fn profile(id: &str) -> DictationProfile {
+        DictationProfile {
+            id: id.to_string(),
+            name: id.to_string(),
+            model_id: None,
+            selected_language: None,
+            post_process_enabled: None,
+            post_process_provider_id: None,
+            post_process_prompt_id: None,
+            text_formatting: None,
+            paste_method: None,
+            output_target: None,
+            endpoint_sensitivity: None,
+            transcription_provider: None,
+            app_patterns: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_profile_for_app() {
+        let profiles = [
+            profile("email"),
+            DictationProfile {
+                app_patterns: vec!["code".to_string(), " ".to_string()],
+                ..profile("code")
+            },
+            DictationProfile {
+                app_patterns: vec!["Visual Studio".to_string()],
+                ..profile("other")
+            },
+        ];
+        let find = |app| profile_for_app(&profiles, app).map(|p| p.id.as_str());
+
+        assert_eq!(find("Code - Insiders"), Some("code"));
+        assert_eq!(find("Microsoft Visual Studio"), Some("other"));
+        assert_eq!(find("Mail"), None);
+    }
+
+    #[test]
+    fn test_extract_switch_command() {
+        let profiles = [
+            DictationProfile {
+                name: "Code Review".to_string(),
+                ..profile("review")
+            },
+            profile("email"),
+        ];
+        let switch =
+            |text| extract_switch_command(text, &profiles).map(|(p, rest)| (p.id.as_str(), rest));
+
+        assert_eq!(
+            switch("Switch to the code review profile. Looks good to me."),
+            Some(("review", "Looks good to me.".to_string()))
+        );
+        assert_eq!(
+            switch("switch to email profile"),
+            Some(("email", String::new()))
+        );
+        assert_eq!(switch("Switch to the chat profile."), None);
+        assert_eq!(switch("Switch to email."), None);
+        assert_eq!(switch("Please switch to the email profile."), None);
+    }
+
+    #[test]
+    fn test_next_profile_wraps_around() {
+        let profiles = [profile("email"), profile("code"), profile("chat")];
+        let next = |active| next_profile(&profiles, active).map(|p| p.id.as_str());
+
+        assert_eq!(next(None), Some("email"));
+        assert_eq!(next(Some("email")), Some("code"));
+        assert_eq!(next(Some("chat")), Some("email"));
+        assert_eq!(next(Some("removed")), Some("email"));
+        assert!(next_profile(&[], Some("email")).is_none());
+    }
+
+    #[test]
+    fn test_overlay_restores_the_base_between_profiles() {
+        let mut settings = get_default_settings();
+        settings.selected_language = "de".to_string();
+        settings.post_process_selected_prompt_id = None;
+
+        let email = DictationProfile {
+            selected_language: Some("en".to_string()),
+            post_process_prompt_id: Some("formal".to_string()),
+            ..profile("email")
+        };
+        let code = DictationProfile {
+            text_formatting: Some(TextFormatting::Markdown),
+            ..profile("code")
+        };
+        overlay(&mut settings, &email);
+        assert_eq!(settings.selected_language, "en");
+
+        // Nothing of the email profile is left once the code profile is applied
+        overlay(&mut settings, &code);
+        assert_eq!(settings.selected_language, "de");
+        assert_eq!(settings.post_process_selected_prompt_id, None);
+        assert_eq!(settings.text_formatting, TextFormatting::Markdown);
+
+        restore_base(&mut settings);
+        assert_eq!(settings.text_formatting, TextFormatting::default());
+        assert!(settings.profile_base.is_none());
+    }
+
+    #[test]
+    fn test_apply_keeps_unset_fields() {
+        let mut settings = get_default_settings();
+        settings.selected_language = "de".to_string();
+        settings.post_process_enabled = true;
+
+        let code = DictationProfile {
+            text_formatting: Some(TextFormatting::Markdown),
+            post_process_enabled: Some(false),
+            transcription_provider: Some(TranscriptionProvider::Deepgram),
+            ..profile("code")
+        };
+        apply(&mut settings, &code);
+
+        assert_eq!(settings.selected_language, "de");
+        assert!(!settings.post_process_enabled);
+        assert_eq!(settings.text_formatting, TextFormatting::Markdown);
+        assert_eq!(
+            settings.transcription_provider,
+            TranscriptionProvider::Deepgram
+        );
+    }
+}
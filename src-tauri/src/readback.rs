@@ -0,0 +1,187 @@
+//! Holding a dictation back before it is pasted. With readback on, the final transcription
+//! is read aloud, and with confirm mode it is shown in an editable preview. Either way it
+//! waits for the user to confirm it, by shortcut, button or saying "send it", which pastes
+//! it, or to discard it. The latest transcription can also be read back on request.
+
+use crate::managers::history::HistoryManager;
+use crate::overlay;
+use crate::settings::get_settings;
+use crate::tts;
+use crate::utils;
+use log::{error, info};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Spoken commands that paste the held transcription
+const CONFIRM_COMMANDS: &[&str] = &["send it", "send that", "paste it"];
+
+/// Time for the focus to go back to the dictation's app once the preview is hidden
+const FOCUS_RETURN_DELAY: Duration = Duration::from_millis(150);
+
+/// A transcription waiting for the user's decision.
+pub struct PendingReadback {
+    text: String,
+    /// Whether the text was changed in the preview
+    edited: bool,
+    history_id: Option<i64>,
+}
+
+pub type ManagedReadback = Mutex<Option<PendingReadback>>;
+
+/// Holds `text` instead of pasting it and emits `readback-pending` with it, then shows it in
+/// the preview and reads it aloud as the settings ask. A transcription still held is
+/// replaced.
+pub fn hold(app: &AppHandle, text: String) {
+    *app.state::<ManagedReadback>().lock().unwrap() = Some(PendingReadback {
+        text: text.clone(),
+        edited: false,
+        history_id: None,
+    });
+    if let Err(e) = app.emit("readback-pending", &text) {
+        error!("Failed to emit readback-pending event: {}", e);
+    }
+    let settings = get_settings(app);
+    if settings.confirm_before_paste {
+        if let Err(e) = overlay::show_preview_window(app, &text) {
+            error!("Failed to show the preview: {}", e);
+        }
+    }
+    if !settings.readback_before_paste {
+        return;
+    }
+    let app = app.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = tts::speak(&app, &text) {
+            error!("Failed to read the transcription back: {}", e);
+        }
+    });
+}
+
+/// Records the history entry of the held transcription, so pasting it can be undone.
+pub fn link_history(app: &AppHandle, history_id: i64) {
+    if let Some(pending) = app.state::<ManagedReadback>().lock().unwrap().as_mut() {
+        pending.history_id = Some(history_id);
+    }
+}
+
+/// Replaces the held text with the user's edit from the preview.
+pub fn edit(app: &AppHandle, text: String) -> Result<(), String> {
+    let state = app.state::<ManagedReadback>();
+    let mut pending = state.lock().unwrap();
+    let pending = pending
+        .as_mut()
+        .ok_or("No transcription is waiting to be pasted")?;
+    if pending.text != text {
+        pending.text = text;
+        pending.edited = true;
+    }
+    Ok(())
+}
+
+pub fn pending_text(app: &AppHandle) -> Option<String> {
+    app.state::<ManagedReadback>()
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|pending| pending.text.clone())
+}
+
+/// Reads the held transcription aloud again, or the latest one in the history when none is
+/// held.
+pub async fn read_back(app: &AppHandle) -> Result<(), String> {
+    let text = match pending_text(app) {
+        Some(text) => text,
+        None => {
+            let hm = Arc::clone(&app.state::<Arc<HistoryManager>>());
+            let entry = hm
+                .get_latest_entry()
+                .await
+                .map_err(|e| e.to_string())?
+                .ok_or("No transcription to read back")?;
+            entry
+                .post_processed_text
+                .unwrap_or(entry.transcription_text)
+        }
+    };
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || tts::speak(&app, &text))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Pastes the held transcription into the focused app. Edits made in the preview are saved
+/// to its history entry.
+pub async fn confirm(app: &AppHandle) -> Result<(), String> {
+    let (pending, preview_focused) = take(app);
+    let pending = pending.ok_or("No transcription is waiting to be pasted")?;
+    if preview_focused {
+        tokio::time::sleep(FOCUS_RETURN_DELAY).await;
+    }
+    if pending.edited {
+        if let Some(history_id) = pending.history_id {
+            let hm = Arc::clone(&app.state::<Arc<HistoryManager>>());
+            if let Err(e) = hm.set_final_text(history_id, &pending.text).await {
+                error!("Failed to save the edited transcription: {}", e);
+            }
+        }
+    }
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let ah = app.clone();
+    app.run_on_main_thread(move || {
+        let _ = tx.send(utils::paste(pending.text, ah));
+    })
+    .map_err(|e| e.to_string())?;
+    rx.await
+        .unwrap_or_else(|_| Err("Paste did not run".to_string()))?;
+    if let Some(history_id) = pending.history_id {
+        utils::link_last_output(app, history_id);
+    }
+    info!("Pasted the held transcription");
+    Ok(())
+}
+
+/// Drops the held transcription without pasting it. Returns whether one was held.
+pub fn discard(app: &AppHandle) -> bool {
+    let discarded = take(app).0.is_some();
+    if discarded {
+        info!("Discarded the held transcription");
+    }
+    discarded
+}
+
+/// Whether `text` is only a spoken command to paste the held transcription, e.g. "Send it."
+pub fn is_confirm_command(text: &str) -> bool {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    !words.is_empty() && CONFIRM_COMMANDS.contains(&words.join(" ").as_str())
+}
+
+/// Takes the held transcription, hides the preview and emits `readback-resolved`. Also
+/// returns whether the preview had the focus.
+fn take(app: &AppHandle) -> (Option<PendingReadback>, bool) {
+    let pending = app.state::<ManagedReadback>().lock().unwrap().take();
+    let preview_focused = overlay::hide_preview_window(app);
+    if pending.is_some() {
+        if let Err(e) = app.emit("readback-resolved", ()) {
+            error!("Failed to emit readback-resolved event: {}", e);
+        }
+    }
+    (pending, preview_focused)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_confirm_command() {
+        assert!(is_confirm_command("Send it."));
+        assert!(is_confirm_command("paste it"));
+        assert!(!is_confirm_command("Send it to Alex"));
+        assert!(!is_confirm_command(""));
+    }
+}
@@ -0,0 +1,203 @@
+//! Merges the live transcription of a dictation with the accurate pass run once it ends.
+//! The accurate pass usually wins, but it is compared utterance by utterance: where the two
+//! agree nothing changes, and where they disagree the more confident one is kept. Every
+//! disagreement is recorded so the history can show what the refinement altered.
+
+use crate::managers::transcription::TranscriptionOutput;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// How much more confident the live pass has to be to override the accurate pass.
+const LIVE_CONFIDENCE_MARGIN: f32 = 0.1;
+/// Live text the accurate pass left out entirely is kept when scored at least this high.
+const DROPPED_SPEECH_CONFIDENCE: f32 = 0.6;
+
+/// A finished utterance of the live pass, times in seconds from the start of the recording.
+#[derive(Clone, Debug)]
+pub struct LiveSegment {
+    pub start: f32,
+    pub end: f32,
+    pub text: String,
+    pub confidence: Option<f32>,
+}
+
+/// A stretch of the recording where the two passes disagreed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Type)]
+pub struct RefinementChange {
+    pub start: f32,
+    pub end: f32,
+    pub live_text: String,
+    pub refined_text: String,
+    /// The live text was kept because the accurate pass was less sure of its version
+    pub kept_live: bool,
+}
+
+pub struct MergedTranscript {
+    pub text: String,
+    pub changes: Vec<RefinementChange>,
+}
+
+/// Words only, ignoring case and punctuation, so formatting differences don't count as
+/// disagreements.
+fn normalized(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+fn keep_live(live: &LiveSegment, refined_text: &str, refined_confidence: Option<f32>) -> bool {
+    match (live.confidence, refined_confidence) {
+        (Some(live), Some(refined)) => live > refined + LIVE_CONFIDENCE_MARGIN,
+        // Without scores from the accurate pass it is trusted, unless it dropped speech the
+        // live pass was sure of
+        (Some(live), None) => refined_text.is_empty() && live >= DROPPED_SPEECH_CONFIDENCE,
+        _ => false,
+    }
+}
+
+/// Merges `live` into the accurate pass `refined`. Each segment of the accurate pass is
+/// matched to the live utterance containing its midpoint. `None` when there is nothing to
+/// align, the accurate pass is then used as it is.
+pub fn merge(live: &[LiveSegment], refined: &TranscriptionOutput) -> Option<MergedTranscript> {
+    let live: Vec<&LiveSegment> = live.iter().filter(|l| l.end > l.start).collect();
+    if live.is_empty() || refined.segments.is_empty() {
+        return None;
+    }
+
+    // (start time, text) of each piece of the merged transcript
+    let mut pieces: Vec<(f32, String)> = Vec::new();
+    let mut matched: Vec<Vec<usize>> = vec![Vec::new(); live.len()];
+    for (index, segment) in refined.segments.iter().enumerate() {
+        let mid = (segment.start + segment.end) / 2.0;
+        match live.iter().position(|l| mid >= l.start && mid < l.end) {
+            Some(utterance) => matched[utterance].push(index),
+            None => pieces.push((segment.start, segment.text.clone())),
+        }
+    }
+
+    let mut changes = Vec::new();
+    for (utterance, indices) in live.iter().zip(matched) {
+        let segments: Vec<_> = indices.iter().map(|&i| &refined.segments[i]).collect();
+        let refined_text = segments
+            .iter()
+            .map(|s| s.text.trim())
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        if normalized(&refined_text) == normalized(&utterance.text) {
+            pieces.push((utterance.start, refined_text));
+            continue;
+        }
+
+        let scores: Vec<f32> = segments.iter().filter_map(|s| s.confidence).collect();
+        let refined_confidence =
+            (!scores.is_empty()).then(|| scores.iter().sum::<f32>() / scores.len() as f32);
+        let kept_live = keep_live(utterance, &refined_text, refined_confidence);
+        pieces.push((
+            utterance.start,
+            if kept_live {
+                utterance.text.trim().to_string()
+            } else {
+                refined_text.clone()
+            },
+        ));
+        changes.push(RefinementChange {
+            start: utterance.start,
+            end: utterance.end,
+            live_text: utterance.text.trim().to_string(),
+            refined_text,
+            kept_live,
+        });
+    }
+
+    pieces.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let text = pieces
+        .iter()
+        .map(|(_, text)| text.trim())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    Some(MergedTranscript { text, changes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::managers::transcription::TranscriptSegment;
+
+    fn live(start: f32, end: f32, text: &str, confidence: Option<f32>) -> LiveSegment {
+        LiveSegment {
+            start,
+            end,
+            text: text.to_string(),
+            confidence,
+        }
+    }
+
+    fn refined(segments: &[(f32, f32, &str, Option<f32>)]) -> TranscriptionOutput {
+        TranscriptionOutput {
+            text: segments.iter().map(|s| s.2).collect::<Vec<_>>().join(" "),
+            segments: segments
+                .iter()
+                .map(|&(start, end, text, confidence)| TranscriptSegment {
+                    start,
+                    end,
+                    text: text.to_string(),
+                    confidence,
+                    words: Vec::new(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_merge_keeps_agreement_and_prefers_confident_pass() {
+        let live_pass = [
+            live(0.0, 2.0, "hello there", Some(0.9)),
+            live(2.0, 4.0, "meat at noon", Some(0.4)),
+            live(4.0, 6.0, "send it to Anna", Some(0.95)),
+        ];
+        let accurate = refined(&[
+            (0.0, 2.0, "Hello there.", Some(0.9)),
+            (2.1, 3.9, "Meet at noon.", Some(0.8)),
+            (4.0, 6.0, "Send it to Ana.", Some(0.5)),
+            (6.0, 7.0, "Thanks.", Some(0.9)),
+        ]);
+
+        let merged = merge(&live_pass, &accurate).unwrap();
+        assert_eq!(
+            merged.text,
+            "Hello there. Meet at noon. send it to Anna Thanks."
+        );
+        assert_eq!(merged.changes.len(), 2);
+        assert!(!merged.changes[0].kept_live);
+        assert_eq!(merged.changes[0].live_text, "meat at noon");
+        assert!(merged.changes[1].kept_live);
+        assert_eq!(merged.changes[1].refined_text, "Send it to Ana.");
+    }
+
+    #[test]
+    fn test_merge_without_scores_trusts_accurate_pass() {
+        let live_pass = [
+            live(0.0, 2.0, "write this down", None),
+            live(2.0, 4.0, "important part", Some(0.9)),
+        ];
+        let accurate = refined(&[(0.0, 2.0, "Right, this down.", None)]);
+
+        let merged = merge(&live_pass, &accurate).unwrap();
+        // the dropped utterance was scored high enough to survive
+        assert_eq!(merged.text, "Right, this down. important part");
+        assert!(!merged.changes[0].kept_live);
+        assert!(merged.changes[1].kept_live);
+
+        assert!(merge(&[], &accurate).is_none());
+        assert!(merge(&live_pass, &refined(&[])).is_none());
+    }
+}
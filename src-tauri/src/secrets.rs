@@ -0,0 +1,93 @@
+//! API keys for cloud services, stored in the OS credential store (Keychain, Credential
+//! Manager or Secret Service) instead of the plain-text settings file.
+
+use anyhow::Result;
+use keyring::Entry;
+use log::warn;
+
+const SERVICE: &str = "com.pais.handy";
+
+fn entry(provider_id: &str) -> Result<Entry> {
+    Ok(Entry::new(SERVICE, &format!("{}-api-key", provider_id))?)
+}
+
+pub fn get_api_key(provider_id: &str) -> Option<String> {
+    let password = entry(provider_id).and_then(|entry| Ok(entry.get_password()?));
+    match password {
+        Ok(key) if !key.is_empty() => Some(key),
+        Ok(_) => None,
+        Err(e) => {
+            if !matches!(
+                e.downcast_ref::<keyring::Error>(),
+                Some(keyring::Error::NoEntry)
+            ) {
+                warn!("Failed to read API key for {}: {}", provider_id, e);
+            }
+            None
+        }
+    }
+}
+
+/// Stores the key for `provider_id`, or removes it when `key` is empty.
+pub fn set_api_key(provider_id: &str, key: &str) -> Result<()> {
+    let entry = entry(provider_id)?;
+    if key.is_empty() {
+        match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    } else {
+        Ok(entry.set_password(key)?)
+    }
+}
+
+fn history_key_entry() -> Result<Entry> {
+    Ok(Entry::new(SERVICE, "history-encryption-key")?)
+}
+
+/// The key the history is encrypted with, `None` before encryption was first turned on.
+pub fn get_history_key() -> Result<Option<String>> {
+    match history_key_entry()?.get_password() {
+        Ok(key) => Ok(Some(key)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn set_history_key(key: &str) -> Result<()> {
+    Ok(history_key_entry()?.set_password(key)?)
+}
+
+fn webhook_secret_entry() -> Result<Entry> {
+    Ok(Entry::new(SERVICE, "webhook-secret")?)
+}
+
+/// The secret webhook requests are signed with, `None` when they go unsigned.
+pub fn get_webhook_secret() -> Option<String> {
+    match webhook_secret_entry().and_then(|entry| Ok(entry.get_password()?)) {
+        Ok(secret) if !secret.is_empty() => Some(secret),
+        Ok(_) => None,
+        Err(e) => {
+            if !matches!(
+                e.downcast_ref::<keyring::Error>(),
+                Some(keyring::Error::NoEntry)
+            ) {
+                warn!("Failed to read the webhook secret: {}", e);
+            }
+            None
+        }
+    }
+}
+
+/// Stores the webhook secret, or removes it when `secret` is empty.
+pub fn set_webhook_secret(secret: &str) -> Result<()> {
+    let entry = webhook_secret_entry()?;
+    if secret.is_empty() {
+        match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    } else {
+        Ok(entry.set_password(secret)?)
+    }
+}
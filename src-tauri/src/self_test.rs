@@ -0,0 +1,158 @@
+//! One-click health check of the whole dictation path: a bundled recording of a known phrase
+//! is played through the speakers while the selected microphone records, and the
+//! transcription of that recording is scored against the phrase. A failure points at the
+//! step that most likely broke, from a muted output to a microphone that heard nothing.
+
+use crate::audio_feedback;
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::benchmark::word_error_rate;
+use crate::managers::audio::AudioRecordingManager;
+use crate::managers::transcription::TranscriptionManager;
+use log::info;
+use serde::Serialize;
+use specta::Type;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+
+/// The bundled recording and what is said in it
+const PHRASE_PATH: &str = "resources/self_test_phrase.wav";
+const PHRASE_TEXT: &str = "The quick brown fox jumps over the lazy dog.";
+
+/// Recording binding the test runs under, so a hotkey can't stop it
+const SELF_TEST_BINDING: &str = "self_test";
+
+/// Recorded before the phrase starts and after it ends, for the microphone to open and the
+/// room to go quiet
+const LEAD_IN: Duration = Duration::from_millis(300);
+const TAIL: Duration = Duration::from_millis(700);
+
+/// Loudest sample below which the recording is taken as silence
+const SILENCE_PEAK: f32 = 0.01;
+/// Most words the transcription may get wrong for the test to pass
+const PASS_WORD_ERROR_RATE: f64 = 0.4;
+
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct SelfTestReport {
+    pub expected: String,
+    pub heard: String,
+    /// Share of the phrase's words missed, swapped or added
+    pub word_error_rate: f64,
+    /// Loudest sample of the recording, from 0 to 1
+    pub peak_level: f32,
+    pub recorded_secs: f32,
+    pub passed: bool,
+    /// What most likely went wrong, when the test failed
+    pub problem: Option<String>,
+}
+
+/// Plays the phrase, records and transcribes it. Errors are for a test that couldn't run at
+/// all, a test that ran and failed is reported with its problem.
+pub fn run(app: &AppHandle) -> Result<SelfTestReport, String> {
+    let rm = app.state::<Arc<AudioRecordingManager>>();
+    let tm = app.state::<Arc<TranscriptionManager>>();
+    if !tm.is_model_loaded() {
+        return Err("Load a model before running the self-test".to_string());
+    }
+    let phrase = app
+        .path()
+        .resolve(PHRASE_PATH, BaseDirectory::Resource)
+        .ok()
+        .filter(|path| path.exists())
+        .ok_or("The self-test phrase isn't bundled with this build")?;
+
+    if !rm.try_start_recording(SELF_TEST_BINDING) {
+        return Err("The microphone is busy or couldn't be opened".to_string());
+    }
+    thread::sleep(LEAD_IN);
+    let played = audio_feedback::play_file_blocking(app, &phrase);
+    thread::sleep(TAIL);
+    let samples = rm.stop_recording(SELF_TEST_BINDING).unwrap_or_default();
+    played.map_err(|e| format!("Failed to play the phrase: {}", e))?;
+
+    let peak_level = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    let recorded_secs = samples.len() as f32 / WHISPER_SAMPLE_RATE as f32;
+    let heard = if peak_level < SILENCE_PEAK {
+        String::new()
+    } else {
+        tm.transcribe(samples).map_err(|e| e.to_string())?
+    };
+    let word_error_rate = word_error_rate(PHRASE_TEXT, &heard).unwrap_or(1.0);
+    let problem = diagnose(peak_level, &heard, word_error_rate);
+    info!(
+        "Self-test heard '{}' (peak {:.3}, WER {:.2})",
+        heard, peak_level, word_error_rate
+    );
+
+    Ok(SelfTestReport {
+        expected: PHRASE_TEXT.to_string(),
+        heard,
+        word_error_rate,
+        peak_level,
+        recorded_secs,
+        passed: problem.is_none(),
+        problem,
+    })
+}
+
+fn diagnose(peak_level: f32, heard: &str, word_error_rate: f64) -> Option<String> {
+    if peak_level < SILENCE_PEAK {
+        Some(
+            "The microphone picked up nothing. Check the input device and that the speakers \
+             are on and audible."
+                .to_string(),
+        )
+    } else if heard.trim().is_empty() {
+        Some("Sound was recorded but no speech was recognized. Try a louder volume.".to_string())
+    } else if word_error_rate > PASS_WORD_ERROR_RATE {
+        Some(
+            "The phrase was recognized poorly. Check the microphone level, background noise \
+             and the selected language."
+                .to_string(),
+        )
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnose_names_the_failing_step() {
+        assert!(diagnose(0.001, "", 1.0)
+            .unwrap()
+            .contains("picked up nothing"));
+        assert!(diagnose(0.3, " ", 1.0)
+            .unwrap()
+            .contains("no speech was recognized"));
+        assert!(diagnose(0.3, "the quack", 0.8)
+            .unwrap()
+            .contains("recognized poorly"));
+        assert_eq!(diagnose(0.3, PHRASE_TEXT, 0.0), None);
+    }
+
+    #[test]
+    fn test_phrase_is_bundled() {
+        let manifest_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+        let reader = hound::WavReader::open(manifest_dir.join(PHRASE_PATH)).unwrap();
+        let spec = reader.spec();
+        let secs = reader.duration() as f32 / spec.sample_rate as f32;
+        assert_eq!(spec.channels, 1);
+        assert!((1.0..10.0).contains(&secs));
+
+        // Resources resolve against the bundle's resource list, which must cover the phrase
+        let config: serde_json::Value =
+            serde_json::from_str(include_str!("../tauri.conf.json")).unwrap();
+        let resources = config["bundle"]["resources"].as_array().unwrap();
+        assert!(resources.iter().filter_map(|r| r.as_str()).any(|pattern| {
+            pattern == PHRASE_PATH
+                || pattern
+                    .strip_suffix("**/*")
+                    .is_some_and(|dir| PHRASE_PATH.starts_with(dir))
+        }));
+    }
+}
@@ -0,0 +1,228 @@
+//! User commands run when a dictation session starts and ends, e.g. pausing music, setting a
+//! "dictating" chat status or switching an on-air light. Hooks run in the background so a
+//! slow one never delays the recording, and are killed once they exceed their timeout.
+
+use crate::settings::{get_settings, SessionHook, SessionHookEvent};
+use log::{debug, warn};
+use serde::Serialize;
+use specta::Type;
+use std::io::Read;
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+pub const MAX_HOOK_TIMEOUT_SECS: u32 = 300;
+
+// Output kept from each stream, enough for an error message
+const MAX_OUTPUT_BYTES: usize = 4096;
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct HookOutput {
+    /// `None` when the command was killed or ended by a signal
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl HookOutput {
+    pub fn success(&self) -> bool {
+        !self.timed_out && self.exit_code == Some(0)
+    }
+}
+
+/// Payload of `session-hook-failed`.
+#[derive(Clone, Debug, Serialize)]
+pub struct HookFailure {
+    pub hook_id: String,
+    pub name: String,
+    pub message: String,
+}
+
+/// Remembers the session the start hooks ran for, so the end hooks run exactly once for it
+/// whether the recording is stopped or cancelled.
+#[derive(Default)]
+pub struct SessionHooks {
+    active_binding: Mutex<Option<String>>,
+}
+
+impl SessionHooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Runs the enabled start hooks for a recording started by `binding_id`.
+pub fn session_started(app: &AppHandle, binding_id: &str) {
+    if let Some(hooks) = app.try_state::<SessionHooks>() {
+        *hooks.active_binding.lock().unwrap() = Some(binding_id.to_string());
+    }
+    run_hooks(app, SessionHookEvent::Start, binding_id);
+}
+
+/// Runs the enabled end hooks, if the start hooks ran for the current session.
+pub fn session_ended(app: &AppHandle) {
+    let binding_id = app
+        .try_state::<SessionHooks>()
+        .and_then(|hooks| hooks.active_binding.lock().unwrap().take());
+    if let Some(binding_id) = binding_id {
+        run_hooks(app, SessionHookEvent::End, &binding_id);
+    }
+}
+
+fn run_hooks(app: &AppHandle, event: SessionHookEvent, binding_id: &str) {
+    let hooks: Vec<SessionHook> = get_settings(app)
+        .session_hooks
+        .into_iter()
+        .filter(|hook| hook.enabled && hook.event == event)
+        .collect();
+
+    for hook in hooks {
+        let app = app.clone();
+        let binding_id = binding_id.to_string();
+        thread::spawn(move || {
+            let message = match run_hook(&hook, &binding_id) {
+                Ok(output) if output.success() => {
+                    debug!("Session hook '{}' finished", hook.name);
+                    return;
+                }
+                Ok(output) if output.timed_out => {
+                    format!("Killed after {}s", hook.timeout_secs)
+                }
+                Ok(output) => format!(
+                    "Exited with {}: {}",
+                    output
+                        .exit_code
+                        .map_or("a signal".to_string(), |code| format!("code {}", code)),
+                    output.stderr.trim()
+                ),
+                Err(e) => e,
+            };
+            warn!("Session hook '{}' failed: {}", hook.name, message);
+            let _ = app.emit(
+                "session-hook-failed",
+                HookFailure {
+                    hook_id: hook.id,
+                    name: hook.name,
+                    message,
+                },
+            );
+        });
+    }
+}
+
+fn shell_command(command: &str) -> Command {
+    #[cfg(target_os = "windows")]
+    {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+/// Runs `hook` and waits for it, killing it once its timeout passes. The event and the
+/// binding are passed in `HANDY_SESSION_EVENT` and `HANDY_BINDING_ID`.
+pub fn run_hook(hook: &SessionHook, binding_id: &str) -> Result<HookOutput, String> {
+    let event = match hook.event {
+        SessionHookEvent::Start => "start",
+        SessionHookEvent::End => "end",
+    };
+    let mut child = shell_command(&hook.command)
+        .env("HANDY_SESSION_EVENT", event)
+        .env("HANDY_BINDING_ID", binding_id)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start: {}", e))?;
+
+    // Read the pipes while waiting so a chatty command can't block on a full pipe
+    let stdout = child.stdout.take().map(read_pipe);
+    let stderr = child.stderr.take().map(read_pipe);
+    let (exit_code, timed_out) = wait_with_timeout(&mut child, hook.timeout_secs)?;
+
+    // Processes the command left behind may still hold the pipes open, the output of a
+    // killed command isn't waited for
+    let collect = |reader: Option<thread::JoinHandle<String>>| {
+        reader.filter(|_| !timed_out).and_then(|r| r.join().ok())
+    };
+    Ok(HookOutput {
+        exit_code,
+        timed_out,
+        stdout: collect(stdout).unwrap_or_default(),
+        stderr: collect(stderr).unwrap_or_default(),
+    })
+}
+
+fn read_pipe(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf);
+        buf.truncate(MAX_OUTPUT_BYTES);
+        String::from_utf8_lossy(&buf).into_owned()
+    })
+}
+
+fn wait_with_timeout(child: &mut Child, timeout_secs: u32) -> Result<(Option<i32>, bool), String> {
+    let deadline = Instant::now() + Duration::from_secs(timeout_secs as u64);
+    loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            return Ok((status.code(), false));
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok((None, true));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn hook(command: &str, timeout_secs: u32) -> SessionHook {
+        SessionHook {
+            id: "test".to_string(),
+            name: "test".to_string(),
+            event: SessionHookEvent::Start,
+            command: command.to_string(),
+            enabled: true,
+            timeout_secs,
+        }
+    }
+
+    #[test]
+    fn test_run_hook_passes_session_details() {
+        let output = run_hook(
+            &hook("echo $HANDY_SESSION_EVENT $HANDY_BINDING_ID", 5),
+            "transcribe",
+        )
+        .unwrap();
+        assert!(output.success());
+        assert_eq!(output.stdout.trim(), "start transcribe");
+
+        let output = run_hook(&hook("echo oops >&2; exit 3", 5), "transcribe").unwrap();
+        assert_eq!(output.exit_code, Some(3));
+        assert_eq!(output.stderr.trim(), "oops");
+    }
+
+    #[test]
+    fn test_run_hook_kills_after_timeout() {
+        let start = Instant::now();
+        let output = run_hook(&hook("sleep 10", 1), "transcribe").unwrap();
+        assert!(output.timed_out);
+        assert!(!output.success());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,222 @@
+//! Append-only journal of the partial transcriptions of the running dictation. Each chunk
+//! result is written and synced as it arrives, so a dictation interrupted by a crash or power
+//! loss can be restored on the next launch instead of being lost with the in-memory state.
+//! The texts are sealed like the rest of the history when it is encrypted.
+
+use crate::managers::history::HistoryManager;
+use anyhow::Result;
+use chrono::Utc;
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+
+const JOURNAL_PREFIX: &str = "session-";
+const JOURNAL_EXTENSION: &str = "jsonl";
+
+/// One line of a journal file.
+#[derive(Serialize, Deserialize)]
+struct JournalRecord {
+    utterance: usize,
+    text: String,
+}
+
+struct OpenJournal {
+    id: String,
+    file: File,
+}
+
+/// A journal left behind by a dictation that never finished.
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct RecoveredSession {
+    pub id: String,
+    /// Unix timestamp of when the dictation started
+    pub started_at: i64,
+    pub text: String,
+}
+
+pub struct SessionJournal {
+    app_handle: AppHandle,
+    dir: PathBuf,
+    current: Mutex<Option<OpenJournal>>,
+}
+
+impl SessionJournal {
+    pub fn new(app_handle: &AppHandle) -> Result<Self> {
+        let dir = app_handle.path().app_data_dir()?.join("journal");
+        fs::create_dir_all(&dir)?;
+
+        Ok(Self {
+            app_handle: app_handle.clone(),
+            dir,
+            current: Mutex::new(None),
+        })
+    }
+
+    /// Starts the journal of a new dictation and returns its id. A journal that is still
+    /// open is closed without being removed, since its session never finished.
+    pub fn begin(&self) -> Option<String> {
+        let started_at = Utc::now().timestamp_millis();
+        let id = format!("{}{}", JOURNAL_PREFIX, started_at);
+        let path = self.path_for(&id);
+
+        let file = match OpenOptions::new().create(true).append(true).open(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                error!("Failed to create session journal {:?}: {}", path, e);
+                return None;
+            }
+        };
+
+        debug!("Opened session journal {}", id);
+        *self.current.lock().unwrap() = Some(OpenJournal {
+            id: id.clone(),
+            file,
+        });
+        Some(id)
+    }
+
+    /// Id of the journal of the running dictation, if any.
+    pub fn current_id(&self) -> Option<String> {
+        self.current.lock().unwrap().as_ref().map(|j| j.id.clone())
+    }
+
+    /// Records the latest transcription of utterance `utterance`, replacing earlier ones.
+    /// Results that arrive after their session's journal was closed are dropped.
+    pub fn append(&self, id: &str, utterance: usize, text: &str) {
+        let mut current = self.current.lock().unwrap();
+        let Some(journal) = current.as_mut().filter(|j| j.id == id) else {
+            return;
+        };
+
+        let result = self
+            .history()
+            .seal(text)
+            .and_then(|text| Ok(serde_json::to_string(&JournalRecord { utterance, text })?))
+            .and_then(|line| {
+                writeln!(journal.file, "{}", line)?;
+                journal.file.sync_data()?;
+                Ok(())
+            });
+        if let Err(e) = result {
+            warn!("Failed to write to session journal {}: {}", id, e);
+        }
+    }
+
+    /// Closes and removes journal `id` once its dictation was delivered. A newer dictation
+    /// may already have started, in which case its journal stays open.
+    pub fn finish(&self, id: &str) {
+        let mut current = self.current.lock().unwrap();
+        if current.as_ref().is_some_and(|j| j.id == id) {
+            *current = None;
+        }
+        drop(current);
+        remove_journal(&self.path_for(id));
+    }
+
+    /// Closes and removes the journal of the running dictation when it's cancelled.
+    pub fn cancel(&self) {
+        if let Some(journal) = self.current.lock().unwrap().take() {
+            remove_journal(&self.path_for(&journal.id));
+        }
+    }
+
+    /// Journals of earlier dictations that didn't finish, most recent first.
+    pub fn recoverable_sessions(&self) -> Result<Vec<RecoveredSession>> {
+        let current = self.current_id();
+        let mut sessions = Vec::new();
+
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(JOURNAL_EXTENSION) {
+                continue;
+            }
+            let Some(id) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            if current.as_deref() == Some(id) {
+                continue;
+            }
+            let Some(started_at) = id
+                .strip_prefix(JOURNAL_PREFIX)
+                .and_then(|ms| ms.parse::<i64>().ok())
+            else {
+                continue;
+            };
+
+            let text = read_journal(&path, &self.history())?;
+            if text.is_empty() {
+                // Nothing was transcribed before the interruption
+                remove_journal(&path);
+                continue;
+            }
+
+            sessions.push(RecoveredSession {
+                id: id.to_string(),
+                started_at: started_at / 1000,
+                text,
+            });
+        }
+
+        sessions.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        Ok(sessions)
+    }
+
+    /// Removes the journal of a recovered session, after it was restored or dismissed.
+    pub fn discard(&self, id: &str) -> Result<()> {
+        if !id.starts_with(JOURNAL_PREFIX) || id.contains(['/', '\\', '.']) {
+            return Err(anyhow::anyhow!("Invalid session id: {}", id));
+        }
+        if self.current_id().as_deref() == Some(id) {
+            return Err(anyhow::anyhow!("Session {} is still recording", id));
+        }
+
+        let path = self.path_for(id);
+        if path.exists() {
+            fs::remove_file(&path)?;
+            info!("Removed session journal {}", id);
+        }
+        Ok(())
+    }
+
+    fn history(&self) -> Arc<HistoryManager> {
+        Arc::clone(&self.app_handle.state::<Arc<HistoryManager>>())
+    }
+
+    fn path_for(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", id, JOURNAL_EXTENSION))
+    }
+}
+
+/// Joins the last transcription of each utterance of a journal in utterance order. A line cut
+/// short by the crash is skipped.
+fn read_journal(path: &Path, history: &HistoryManager) -> Result<String> {
+    let mut utterances = BTreeMap::new();
+    for record in BufReader::new(File::open(path)?)
+        .lines()
+        .map_while(|line| line.ok())
+        .filter_map(|line| serde_json::from_str::<JournalRecord>(&line).ok())
+    {
+        utterances.insert(record.utterance, history.open(record.text));
+    }
+
+    Ok(utterances
+        .values()
+        .map(|text| text.trim())
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join(" "))
+}
+
+fn remove_journal(path: &Path) {
+    match fs::remove_file(path) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => warn!("Failed to remove session journal {:?}: {}", path, e),
+    }
+}
@@ -0,0 +1,143 @@
+//! Titles and tags for history sessions, either spoken at the start of a dictation
+//! ("Title: weekly sync. Tags: planning, team.") or added by rules for the focused app.
+
+use crate::helpers::focused_app::WorkspaceContext;
+use crate::settings::{AppSettings, SessionTagRule};
+
+/// Title and tags to store with a session, nothing is changed when empty.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SessionNaming {
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl SessionNaming {
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.tags.is_empty()
+    }
+
+    /// Adds `tags` that aren't there yet, keeping the order.
+    pub fn add_tags<I: IntoIterator<Item = String>>(&mut self, tags: I) {
+        for tag in tags {
+            if !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
+        }
+    }
+}
+
+/// Lowercases a tag and joins its words with dashes, so "Project Alpha" and "project-alpha"
+/// are the same tag. `None` when nothing is left.
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let words: Vec<String> = tag
+        .split(|c: char| c.is_whitespace() || c == '-' || c == '_')
+        .map(|word| {
+            word.chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect();
+    (!words.is_empty()).then(|| words.join("-"))
+}
+
+/// Normalizes `tags`, dropping empty and repeated ones.
+pub fn normalize_tags<'a, I: IntoIterator<Item = &'a str>>(tags: I) -> Vec<String> {
+    let mut naming = SessionNaming::default();
+    naming.add_tags(tags.into_iter().filter_map(normalize_tag));
+    naming.tags
+}
+
+/// The keyword a command sentence starts with and the rest of the sentence.
+fn command<'a>(sentence: &'a str, keywords: &[&str]) -> Option<&'a str> {
+    keywords.iter().find_map(|keyword| {
+        let prefix = sentence.get(..keyword.len())?;
+        let rest = &sentence[keyword.len()..];
+        // "Title" must be its own word, "Titled ..." is dictation
+        if !prefix.eq_ignore_ascii_case(keyword) || !rest.starts_with([':', ',', ' ']) {
+            return None;
+        }
+        let value = rest.trim_start_matches([':', ',', ' ']).trim();
+        (!value.is_empty()).then_some(value)
+    })
+}
+
+/// Splits the "Title: ..." and "Tags: ..." sentences off the start of `text`. What follows
+/// them is returned as the text to deliver.
+pub fn extract_voice_commands(text: &str) -> (String, SessionNaming) {
+    let mut naming = SessionNaming::default();
+    let mut rest = text.trim_start();
+
+    loop {
+        let end = rest
+            .find(['.', '!', '?', '\n'])
+            .map(|i| i + 1)
+            .unwrap_or(rest.len());
+        let sentence = rest[..end].trim_end_matches(['.', '!', '?', '\n']).trim();
+
+        if let Some(title) = command(sentence, &["title"]) {
+            naming.title = Some(title.to_string());
+        } else if let Some(tags) = command(sentence, &["tags", "tag"]) {
+            naming.add_tags(
+                tags.split(',')
+                    .flat_map(|part| part.split(" and "))
+                    .filter_map(normalize_tag),
+            );
+        } else {
+            break;
+        }
+        rest = rest[end..].trim_start();
+    }
+
+    (rest.to_string(), naming)
+}
+
+fn rule_matches(rule: &SessionTagRule, context: &WorkspaceContext) -> bool {
+    let pattern = rule.app_pattern.trim().to_lowercase();
+    !pattern.is_empty()
+        && [&context.app_name, &context.window_title]
+            .into_iter()
+            .flatten()
+            .any(|name| name.to_lowercase().contains(&pattern))
+}
+
+/// Tags of the rules matching the focused application. The window is only looked at when
+/// there are rules.
+pub fn rule_tags(settings: &AppSettings) -> Vec<String> {
+    if settings.session_tag_rules.is_empty() {
+        return Vec::new();
+    }
+
+    let context = WorkspaceContext::capture(true, false);
+    normalize_tags(
+        settings
+            .session_tag_rules
+            .iter()
+            .filter(|rule| rule_matches(rule, &context))
+            .flat_map(|rule| rule.tags.iter().map(String::as_str)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_voice_commands() {
+        let (text, naming) = extract_voice_commands(
+            "Title: weekly sync. Tags: Project Alpha, planning and team. Let's start.",
+        );
+        assert_eq!(text, "Let's start.");
+        assert_eq!(naming.title.as_deref(), Some("weekly sync"));
+        assert_eq!(naming.tags, vec!["project-alpha", "planning", "team"]);
+
+        let (text, naming) = extract_voice_commands("Titled works are fine. Title: later.");
+        assert_eq!(text, "Titled works are fine. Title: later.");
+        assert!(naming.is_empty());
+
+        let (text, naming) = extract_voice_commands("Tag, urgent");
+        assert!(text.is_empty());
+        assert_eq!(naming.tags, vec!["urgent"]);
+    }
+}
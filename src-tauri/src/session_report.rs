@@ -0,0 +1,66 @@
+//! The `session-report` event: one summary per finished dictation, so integrations and the
+//! UI don't have to piece the outcome together from the individual progress events.
+
+use crate::managers::usage::UsageCounters;
+use log::{debug, warn};
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Outcome of delivering the transcription to one destination, e.g. paste or history.
+#[derive(Clone, Debug, Serialize)]
+pub struct SinkResult {
+    pub sink: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+impl SinkResult {
+    pub fn new(sink: &str, result: Result<(), String>) -> Self {
+        Self {
+            sink: sink.to_string(),
+            success: result.is_ok(),
+            error: result.err(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct SessionReport {
+    pub binding_id: String,
+    /// Text delivered to the sinks, after post-processing
+    pub text: String,
+    /// Text as transcribed, before post-processing
+    pub raw_text: String,
+    pub audio_duration_secs: f32,
+    /// Time from the end of the recording until the session was finalized
+    pub processing_ms: u64,
+    /// Partial transcriptions produced while recording
+    pub chunk_count: usize,
+    /// `None` when the engine doesn't report confidence, which includes every local model
+    pub average_confidence: Option<f32>,
+    /// Words, or whole segments, scored below the low-confidence threshold
+    pub low_confidence_words: Vec<String>,
+    pub provider: String,
+    pub model: Option<String>,
+    pub post_processed: bool,
+    pub post_process_prompt: Option<String>,
+    pub sinks: Vec<SinkResult>,
+    /// Set when transcription failed, in which case nothing was delivered
+    pub error: Option<String>,
+}
+
+pub fn emit(app: &AppHandle, report: &SessionReport) {
+    debug!(
+        "Session report: {} chars, {:.1}s of audio, {}ms processing",
+        report.text.len(),
+        report.audio_duration_secs,
+        report.processing_ms
+    );
+    if let Err(e) = app.emit("session-report", report) {
+        warn!("Failed to emit session-report event: {}", e);
+    }
+    if let Some(usage) = app.try_state::<Arc<UsageCounters>>() {
+        usage.record_session(report);
+    }
+}
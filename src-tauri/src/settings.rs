@@ -1,3 +1,6 @@
+use crate::audio_toolkit::WordHint;
+use crate::managers::model::Quantization;
+use crate::settings_bundle::{self, SETTINGS_VERSION, SETTINGS_VERSION_KEY};
 use log::{debug, warn};
 use serde::de::{self, Visitor};
 use serde::{Deserialize, Deserializer, Serialize};
@@ -73,13 +76,29 @@ impl From<LogLevel> for tauri_plugin_log::LogLevel {
     }
 }
 
+impl From<log::Level> for LogLevel {
+    fn from(level: log::Level) -> Self {
+        match level {
+            log::Level::Trace => LogLevel::Trace,
+            log::Level::Debug => LogLevel::Debug,
+            log::Level::Info => LogLevel::Info,
+            log::Level::Warn => LogLevel::Warn,
+            log::Level::Error => LogLevel::Error,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Type)]
 pub struct ShortcutBinding {
     pub id: String,
     pub name: String,
     pub description: String,
     pub default_binding: String,
+    /// Empty when no trigger is assigned
     pub current_binding: String,
+    /// Separate trigger that stops recording. When set, `current_binding` only starts it.
+    #[serde(default)]
+    pub stop_binding: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Type)]
@@ -89,6 +108,136 @@ pub struct LLMPrompt {
     pub prompt: String,
 }
 
+/// Find/replace rule run over the final transcript. `replacement` may refer to capture
+/// groups as `$1` or `${name}`.
+#[derive(Serialize, Deserialize, Debug, Clone, Type)]
+pub struct RegexRule {
+    pub id: String,
+    pub name: String,
+    pub pattern: String,
+    pub replacement: String,
+    #[serde(default = "default_rule_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub case_insensitive: bool,
+    /// Languages the rule is for, all when empty
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// Ids of the profiles the rule is for, all when empty
+    #[serde(default)]
+    pub profiles: Vec<String>,
+}
+
+/// Text inserted in place of a spoken trigger phrase, e.g. "insert signature".
+#[derive(Serialize, Deserialize, Debug, Clone, Type)]
+pub struct Snippet {
+    pub id: String,
+    /// Phrase that is replaced, matched ignoring case and punctuation
+    pub trigger: String,
+    /// Replacement text, may span lines and use `${date}`, `${time}` and `${clipboard}`
+    pub template: String,
+}
+
+/// Settings of a text plugin, by the file name of its module without `.wasm`.
+#[derive(Serialize, Deserialize, Debug, Clone, Type)]
+pub struct PluginConfig {
+    pub id: String,
+    pub enabled: bool,
+    pub timeout_ms: u64,
+}
+
+/// A stage of the text pipeline run between transcription and output.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum TextStageId {
+    /// Rule-based and learned filler removal
+    Fillers,
+    CustomWords,
+    /// Number words written as digits
+    Itn,
+    /// Spoken punctuation written as marks
+    Punctuation,
+    Llm,
+    Regex,
+    /// The WebAssembly plugins in the plugins folder
+    Plugins,
+    Profanity,
+    Snippets,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Type)]
+pub struct PipelineStage {
+    pub stage: TextStageId,
+    pub enabled: bool,
+}
+
+/// Tags added to sessions dictated while a matching application is focused.
+#[derive(Serialize, Deserialize, Debug, Clone, Type)]
+pub struct SessionTagRule {
+    /// Matched ignoring case against the focused application's name and window title
+    pub app_pattern: String,
+    pub tags: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionHookEvent {
+    /// Recording started
+    Start,
+    /// Recording stopped or was cancelled, before the transcription is delivered
+    End,
+}
+
+/// Shell command run when a dictation session starts or ends, e.g. to pause music, set a
+/// chat status or switch on an on-air light.
+#[derive(Serialize, Deserialize, Debug, Clone, Type)]
+pub struct SessionHook {
+    pub id: String,
+    pub name: String,
+    pub event: SessionHookEvent,
+    /// Run with `sh -c`, or `cmd /C` on Windows
+    pub command: String,
+    #[serde(default = "default_rule_enabled")]
+    pub enabled: bool,
+    /// The command is killed when it runs longer
+    #[serde(default = "default_hook_timeout_secs")]
+    pub timeout_secs: u32,
+}
+
+/// A named set of dictation settings switched to as a whole, e.g. "email" or "code". Fields
+/// left `None` keep their current value when switching.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+pub struct DictationProfile {
+    pub id: String,
+    pub name: String,
+    /// Loaded when switching, if another model is loaded
+    #[serde(default)]
+    pub model_id: Option<String>,
+    #[serde(default)]
+    pub selected_language: Option<String>,
+    #[serde(default)]
+    pub post_process_enabled: Option<bool>,
+    #[serde(default)]
+    pub post_process_provider_id: Option<String>,
+    #[serde(default)]
+    pub post_process_prompt_id: Option<String>,
+    #[serde(default)]
+    pub text_formatting: Option<TextFormatting>,
+    #[serde(default)]
+    pub paste_method: Option<PasteMethod>,
+    #[serde(default)]
+    pub output_target: Option<OutputTarget>,
+    #[serde(default)]
+    pub endpoint_sensitivity: Option<EndpointSensitivity>,
+    /// Local model or cloud provider for the profile's dictation
+    #[serde(default)]
+    pub transcription_provider: Option<TranscriptionProvider>,
+    /// Applications the profile is for, matched ignoring case against the focused
+    /// application's name
+    #[serde(default)]
+    pub app_patterns: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Type)]
 pub struct PostProcessProvider {
     pub id: String,
@@ -108,6 +257,36 @@ pub enum OverlayPosition {
     Bottom,
 }
 
+/// What recognized text is read out through the screen reader.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreenReaderAnnouncements {
+    Off,
+    /// The transcription once the recording ends
+    Final,
+    /// Words as they are recognized while recording, then the rest of the transcription
+    Live,
+}
+
+/// Audio the caption overlay transcribes.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptionSource {
+    Microphone,
+    /// What the computer plays, captured through a loopback or monitor input
+    SystemAudio,
+}
+
+/// How soon a pause ends an utterance of the live transcription.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum EndpointSensitivity {
+    Aggressive,
+    Balanced,
+    /// Waits out longer pauses, for speakers who stop to think mid-sentence
+    Patient,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
 #[serde(rename_all = "snake_case")]
 pub enum ModelUnloadTimeout {
@@ -128,6 +307,220 @@ pub enum PasteMethod {
     Direct,
     None,
     ShiftInsert,
+    HumanizedTyping,
+    /// Inserts at the caret through the accessibility API, pasting where that can't reach
+    Accessibility,
+}
+
+/// A Parakeet export added to the catalog from the Hugging Face Hub.
+#[derive(Serialize, Deserialize, Debug, Clone, Type)]
+pub struct HubModelConfig {
+    /// e.g. `istupakov/parakeet-tdt-0.6b-v3-onnx`
+    pub repo_id: String,
+    pub quantization: Quantization,
+    /// Files downloaded from the repo
+    pub files: Vec<String>,
+    pub size_mb: u64,
+    /// SHA-256 of the files in Git LFS
+    #[serde(default)]
+    pub sha256: HashMap<String, String>,
+}
+
+/// Applications in which the shortcuts and triggers may start a dictation, matched ignoring
+/// case against the focused application's name.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerAppScope {
+    Everywhere,
+    /// Only in the `trigger_apps`
+    OnlyIn,
+    /// Anywhere but in the `trigger_apps`, e.g. games or remote desktops
+    ExceptIn,
+}
+
+/// What happens when a model needs more memory than is available.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum LowMemoryGuard {
+    /// Refuse to load it, unless the user insists
+    Block,
+    /// Load it after warning
+    Warn,
+    Off,
+}
+
+/// OS priority of model loading and inference, lowest last.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum InferencePriority {
+    Normal,
+    /// Yields to the foreground apps, e.g. on battery
+    BelowNormal,
+    /// Only runs when nothing else wants the CPU
+    Idle,
+}
+
+/// Which cores of a hybrid CPU inference runs on.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum InferenceCores {
+    Any,
+    Performance,
+    /// Slower, but spares the battery and the performance cores
+    Efficiency,
+}
+
+/// Where the final text of a dictation goes.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputTarget {
+    /// Into the focused application, with the paste method
+    Paste,
+    /// Appended to a Markdown note in `markdown_folder`
+    MarkdownNote,
+}
+
+/// A word transcriptions are corrected towards, with hints for how the engine mishears it.
+#[derive(Serialize, Debug, Clone, PartialEq, Type)]
+pub struct CustomWord {
+    pub word: String,
+    /// Alternate spellings or "sounds like" strings, e.g. "get hub" for "GitHub"
+    pub sounds_like: Vec<String>,
+    /// Overrides `word_correction_threshold` for this word
+    pub threshold: Option<f64>,
+    /// Languages the word is corrected towards in, all when empty
+    pub languages: Vec<String>,
+    /// Ids of the profiles the word is used in, all when empty
+    pub profiles: Vec<String>,
+}
+
+impl CustomWord {
+    pub fn hint(&self) -> WordHint<'_> {
+        WordHint {
+            word: &self.word,
+            sounds_like: &self.sounds_like,
+            threshold: self.threshold,
+        }
+    }
+}
+
+/// Custom words used to be stored as plain strings.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredCustomWord {
+    Plain(String),
+    Detailed {
+        word: String,
+        #[serde(default)]
+        sounds_like: Vec<String>,
+        #[serde(default)]
+        threshold: Option<f64>,
+        #[serde(default)]
+        languages: Vec<String>,
+        #[serde(default)]
+        profiles: Vec<String>,
+    },
+}
+
+impl<'de> Deserialize<'de> for CustomWord {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(match StoredCustomWord::deserialize(deserializer)? {
+            StoredCustomWord::Plain(word) => Self {
+                word,
+                sounds_like: Vec::new(),
+                threshold: None,
+                languages: Vec::new(),
+                profiles: Vec::new(),
+            },
+            StoredCustomWord::Detailed {
+                word,
+                sounds_like,
+                threshold,
+                languages,
+                profiles,
+            } => Self {
+                word,
+                sounds_like,
+                threshold,
+                languages,
+                profiles,
+            },
+        })
+    }
+}
+
+/// Keystroke timing used by `PasteMethod::HumanizedTyping`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+pub struct HumanizedTypingSettings {
+    /// Average delay between keystrokes
+    pub mean_delay_ms: u32,
+    /// Standard deviation of the delay between keystrokes
+    pub delay_jitter_ms: u32,
+    /// Average number of characters typed before a longer pause, 0 disables pauses
+    pub burst_length: u32,
+    pub burst_pause_ms: u32,
+}
+
+/// What changes while the machine runs on battery. Each knob only ever saves power: a
+/// shorter unload timeout or lower rate configured outside the policy is kept.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+pub struct BatteryPolicy {
+    pub enabled: bool,
+    pub model_unload_timeout: ModelUnloadTimeout,
+    /// A smaller model used instead of the selected one, if downloaded
+    pub model_id: Option<String>,
+    /// Readings per second of the `audio-level` events
+    pub audio_level_rate_hz: u32,
+    pub inference_priority: InferencePriority,
+}
+
+impl Default for BatteryPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            model_unload_timeout: ModelUnloadTimeout::Min5,
+            model_id: None,
+            audio_level_rate_hz: 10,
+            inference_priority: InferencePriority::BelowNormal,
+        }
+    }
+}
+
+impl Default for HumanizedTypingSettings {
+    fn default() -> Self {
+        Self {
+            mean_delay_ms: 45,
+            delay_jitter_ms: 20,
+            burst_length: 20,
+            burst_pause_ms: 400,
+        }
+    }
+}
+
+/// The simulated transcription engine, a debug setting for frontend work and tests. While
+/// enabled it stands in for every local model, downloaded or not.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Type)]
+pub struct MockEngineSettings {
+    pub enabled: bool,
+    /// Script the transcriptions are read from, a built-in one when unset
+    pub script_path: Option<String>,
+    pub load_delay_ms: u64,
+    /// Transcription time per second of audio, for utterances that don't set their own
+    pub delay_per_audio_second_ms: u64,
+}
+
+impl Default for MockEngineSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            script_path: None,
+            load_delay_ms: 300,
+            delay_per_audio_second_ms: 100,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
@@ -137,6 +530,25 @@ pub enum ClipboardHandling {
     CopyToClipboard,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum TextFormatting {
+    Plain,
+    Markdown,
+    RichText,
+}
+
+/// Where recordings are transcribed. Everything except `Local` uploads audio to a third party.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptionProvider {
+    Local,
+    #[serde(rename = "openai")]
+    OpenAi,
+    Deepgram,
+    Azure,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Type)]
 #[serde(rename_all = "snake_case")]
 pub enum RecordingRetentionPeriod {
@@ -153,6 +565,36 @@ impl Default for ModelUnloadTimeout {
     }
 }
 
+impl Default for TriggerAppScope {
+    fn default() -> Self {
+        TriggerAppScope::Everywhere
+    }
+}
+
+impl Default for LowMemoryGuard {
+    fn default() -> Self {
+        LowMemoryGuard::Block
+    }
+}
+
+impl Default for InferencePriority {
+    fn default() -> Self {
+        InferencePriority::Normal
+    }
+}
+
+impl Default for InferenceCores {
+    fn default() -> Self {
+        InferenceCores::Any
+    }
+}
+
+impl Default for OutputTarget {
+    fn default() -> Self {
+        OutputTarget::Paste
+    }
+}
+
 impl Default for PasteMethod {
     fn default() -> Self {
         // Default to CtrlV for macOS and Windows, Direct for Linux
@@ -169,6 +611,40 @@ impl Default for ClipboardHandling {
     }
 }
 
+impl Default for TranscriptionProvider {
+    fn default() -> Self {
+        TranscriptionProvider::Local
+    }
+}
+
+impl TranscriptionProvider {
+    pub fn id(&self) -> &'static str {
+        match self {
+            TranscriptionProvider::Local => "local",
+            TranscriptionProvider::OpenAi => "openai",
+            TranscriptionProvider::Deepgram => "deepgram",
+            TranscriptionProvider::Azure => "azure",
+        }
+    }
+}
+
+impl Default for TextFormatting {
+    fn default() -> Self {
+        TextFormatting::Plain
+    }
+}
+
+impl EndpointSensitivity {
+    /// Silence after the VAD hangover that ends an utterance.
+    pub fn pause_ms(self) -> u64 {
+        match self {
+            EndpointSensitivity::Aggressive => 150,
+            EndpointSensitivity::Balanced => 300,
+            EndpointSensitivity::Patient => 800,
+        }
+    }
+}
+
 impl ModelUnloadTimeout {
     pub fn to_minutes(self) -> Option<u64> {
         match self {
@@ -251,24 +727,196 @@ pub struct AppSettings {
     pub selected_language: String,
     #[serde(default = "default_overlay_position")]
     pub overlay_position: OverlayPosition,
+    #[serde(default = "default_screen_reader_announcements")]
+    pub screen_reader_announcements: ScreenReaderAnnouncements,
     #[serde(default = "default_debug_mode")]
     pub debug_mode: bool,
     #[serde(default = "default_log_level")]
     pub log_level: LogLevel,
     #[serde(default)]
-    pub custom_words: Vec<String>,
+    pub custom_words: Vec<CustomWord>,
+    #[serde(default)]
+    pub proper_nouns: Vec<String>,
+    #[serde(default)]
+    pub snippets: Vec<Snippet>,
+    /// Applied in order to the final transcript
+    #[serde(default)]
+    pub regex_rules: Vec<RegexRule>,
+    /// Stages the final text runs through, in order
+    #[serde(default = "default_text_pipeline")]
+    pub text_pipeline: Vec<PipelineStage>,
+    /// Plugins not listed are disabled
+    #[serde(default)]
+    pub plugins: Vec<PluginConfig>,
     #[serde(default)]
     pub model_unload_timeout: ModelUnloadTimeout,
+    #[serde(default = "default_idle_check_interval_secs")]
+    pub idle_check_interval_secs: u64,
+    #[serde(default = "default_unload_warning_secs")]
+    pub unload_warning_secs: u64,
     #[serde(default = "default_word_correction_threshold")]
     pub word_correction_threshold: f64,
+    /// Steers engines that support it toward the custom words while decoding, instead of
+    /// fuzzy-correcting the text afterwards. Unset, it is on for the engines that support
+    /// it, Whisper and the OpenAI and Deepgram APIs, and off for the others such as Parakeet.
+    #[serde(default)]
+    pub vocabulary_biasing: Option<bool>,
+    /// How strongly biasing engines favour the custom words
+    #[serde(default = "default_vocabulary_boost")]
+    pub vocabulary_boost: f32,
+    /// ARPA n-gram model whose likelier words replace similarly spelled ones in the text
+    #[serde(default)]
+    pub language_model_path: Option<String>,
+    /// How much the language model's judgement counts against changing a word's spelling
+    #[serde(default = "default_language_model_weight")]
+    pub language_model_weight: f64,
     #[serde(default = "default_history_limit")]
     pub history_limit: usize,
     #[serde(default = "default_recording_retention_period")]
     pub recording_retention_period: RecordingRetentionPeriod,
     #[serde(default)]
+    pub audio_retention_days: Option<u32>,
+    /// Keep each session's audio as a 16 kHz WAV next to its history entry, off until the
+    /// user opts in
+    #[serde(default)]
+    pub save_recording_audio: bool,
+    /// Disk space for session audio; the oldest unsaved recordings are removed first
+    #[serde(default)]
+    pub recording_storage_limit_mb: Option<u32>,
+    #[serde(default)]
+    pub archive_expired_history: bool,
+    /// Dictated text in the history database and the recordings are encrypted at rest, with
+    /// a key kept in the OS credential store
+    #[serde(default)]
+    pub encrypt_history: bool,
+    /// Leading "Title: ..." and "Tags: ..." sentences name and tag the session instead of
+    /// being pasted
+    #[serde(default)]
+    pub session_voice_commands: bool,
+    #[serde(default)]
+    pub session_tag_rules: Vec<SessionTagRule>,
+    #[serde(default)]
+    pub session_hooks: Vec<SessionHook>,
+    /// POSTs each finished dictation to `webhook_url`
+    #[serde(default)]
+    pub webhook_enabled: bool,
+    #[serde(default)]
+    pub webhook_url: String,
+    #[serde(default)]
+    pub profiles: Vec<DictationProfile>,
+    /// The profile last switched to, `None` before any
+    #[serde(default)]
+    pub active_profile_id: Option<String>,
+    /// The settings profiles override, as they were before a profile was applied. Switching
+    /// profiles or back to none restores them first, so changes made to these settings while
+    /// a profile is active last until then.
+    #[serde(default)]
+    pub profile_base: Option<DictationProfile>,
+    /// Loads the model of the focused application's profile while idle, in place of the
+    /// loaded model, so dictating there doesn't wait for it
+    #[serde(default)]
+    pub prefetch_profile_models: bool,
+    /// Keep coarse, content-free usage counters for diagnostics
+    #[serde(default = "default_usage_counters")]
+    pub usage_counters: bool,
+    /// Disk space for per-utterance history snippets, oldest are removed first; 0, the
+    /// default, disables them
+    #[serde(default)]
+    pub snippet_storage_limit_mb: u32,
+    #[serde(default)]
     pub paste_method: PasteMethod,
     #[serde(default)]
+    pub trigger_app_scope: TriggerAppScope,
+    #[serde(default)]
+    pub trigger_apps: Vec<String>,
+    #[serde(default)]
+    pub output_target: OutputTarget,
+    /// Folder of the Markdown notes, e.g. an Obsidian vault
+    #[serde(default)]
+    pub markdown_folder: Option<String>,
+    /// Note in `markdown_folder` dictations are appended to, the day's note when `None`
+    #[serde(default)]
+    pub markdown_file: Option<String>,
+    #[serde(default)]
+    pub humanized_typing: HumanizedTypingSettings,
+    #[serde(default)]
     pub clipboard_handling: ClipboardHandling,
+    /// Longer transcriptions are copied to the clipboard in parts of at most this many
+    /// characters, for target apps that truncate large pastes.
+    #[serde(default)]
+    pub clipboard_chunk_chars: Option<usize>,
+    /// Words the decoder scores below this confidence (0 to 1) are flagged for review. Only
+    /// the OpenAI and Deepgram providers score their output, local models flag nothing.
+    #[serde(default = "default_low_confidence_threshold")]
+    pub low_confidence_threshold: f32,
+    /// Appended to low-confidence words in the pasted text, e.g. `[?]`. `None` pastes the
+    /// text unmarked, as does transcribing with a local model.
+    #[serde(default)]
+    pub low_confidence_marker: Option<String>,
+    /// Removes hesitations, and in English "like", "you know" and doubled words, by rule
+    #[serde(default)]
+    pub filler_removal: bool,
+    /// Removes the speaker's filler words from transcriptions.
+    #[serde(default)]
+    pub disfluency_removal: bool,
+    /// Lets the disfluency model learn from post-processed transcriptions. Opt-in, since it
+    /// stores statistics about the user's speech.
+    #[serde(default)]
+    pub disfluency_learning: bool,
+    /// Whose disfluency model is used and trained.
+    #[serde(default = "default_disfluency_speaker")]
+    pub disfluency_speaker: String,
+    #[serde(default)]
+    pub text_formatting: TextFormatting,
+    #[serde(default)]
+    pub shared_model_cache: bool,
+    /// Folder the models are stored in, e.g. on a secondary drive. `None` keeps them in the
+    /// app data folder.
+    #[serde(default)]
+    pub models_dir: Option<String>,
+    /// Disk space the models may take, in GB. Past it, the least recently used models are
+    /// proposed for deletion.
+    #[serde(default)]
+    pub model_storage_limit_gb: Option<f32>,
+    #[serde(default)]
+    pub hub_models: Vec<HubModelConfig>,
+    #[serde(default)]
+    pub low_memory_guard: LowMemoryGuard,
+    /// Most CPUs inference may use; all of them when unset
+    #[serde(default)]
+    pub inference_threads: Option<u32>,
+    #[serde(default)]
+    pub inference_priority: InferencePriority,
+    #[serde(default)]
+    pub inference_cores: InferenceCores,
+    #[serde(default)]
+    pub battery_policy: BatteryPolicy,
+    /// Send `perf-metrics` events with the latency of each stage
+    #[serde(default)]
+    pub perf_metrics_enabled: bool,
+    /// Transcribe with the simulated engine instead of a model
+    #[serde(default)]
+    pub mock_engine: MockEngineSettings,
+    /// Proxy URL for model downloads. When unset the system proxy configuration is used.
+    #[serde(default)]
+    pub download_proxy: Option<String>,
+    /// PEM file with extra root certificates trusted for model downloads, for networks that
+    /// intercept TLS.
+    #[serde(default)]
+    pub download_ca_cert_path: Option<String>,
+    #[serde(default)]
+    pub transcription_provider: TranscriptionProvider,
+    #[serde(default = "default_cloud_fallback_to_local")]
+    pub cloud_fallback_to_local: bool,
+    #[serde(default = "default_azure_speech_region")]
+    pub azure_speech_region: String,
+    #[serde(default)]
+    pub api_server_enabled: bool,
+    #[serde(default = "default_api_server_port")]
+    pub api_server_port: u16,
+    /// Serves the MCP endpoint at `/mcp` on the API server's port
+    #[serde(default)]
+    pub mcp_server_enabled: bool,
     #[serde(default = "default_post_process_enabled")]
     pub post_process_enabled: bool,
     #[serde(default = "default_post_process_provider_id")]
@@ -283,10 +931,80 @@ pub struct AppSettings {
     pub post_process_prompts: Vec<LLMPrompt>,
     #[serde(default)]
     pub post_process_selected_prompt_id: Option<String>,
+    /// Sends the focused app and window title along with post-processing prompts
+    #[serde(default)]
+    pub post_process_context_window: bool,
+    /// Sends the text selected in the focused app along with post-processing prompts
+    #[serde(default)]
+    pub post_process_context_selection: bool,
     #[serde(default)]
     pub mute_while_recording: bool,
+    #[serde(default = "default_audio_level_rate_hz")]
+    pub audio_level_rate_hz: u32,
     #[serde(default)]
     pub append_trailing_space: bool,
+    /// Adapts pasted text to the focused field, e.g. joins lines for single-line inputs
+    #[serde(default)]
+    pub fit_output_to_field: bool,
+    /// Refuses to record or paste while a password field has the focus
+    #[serde(default = "default_block_secure_fields")]
+    pub block_secure_fields: bool,
+    /// Multiplier applied to microphone input before VAD and transcription
+    #[serde(default = "default_input_gain")]
+    pub input_gain: f32,
+    /// Automatic gain control, on top of `input_gain`
+    #[serde(default)]
+    pub agc_enabled: bool,
+    /// Pre-emphasis on microphone input, lifting consonants over low-frequency energy
+    #[serde(default)]
+    pub pre_emphasis: bool,
+    /// Cutoff of the high-pass filter on microphone input in Hz, `None` turns it off
+    #[serde(default)]
+    pub high_pass_cutoff_hz: Option<u32>,
+    /// Seconds of speech after which the live transcription closes a segment even if the
+    /// speaker never pauses, `None` waits for a pause
+    #[serde(default = "default_max_utterance_secs")]
+    pub max_utterance_secs: Option<u32>,
+    #[serde(default = "default_endpoint_sensitivity")]
+    pub endpoint_sensitivity: EndpointSensitivity,
+    /// Ends an utterance at the sensitivity's pause only once its live text reads as a
+    /// finished sentence, otherwise after `unfinished_pause_ms` of silence
+    #[serde(default)]
+    pub semantic_endpointing: bool,
+    #[serde(default = "default_unfinished_pause_ms")]
+    pub unfinished_pause_ms: u32,
+    /// Seconds a local transcription may run before the engine is considered stuck and
+    /// reloaded, raised for long recordings. `None` waits indefinitely.
+    #[serde(default = "default_transcription_timeout_secs")]
+    pub transcription_timeout_secs: Option<u32>,
+    /// Live chunks transcribed at once when the provider takes parallel requests
+    #[serde(default = "default_transcription_workers")]
+    pub transcription_workers: u32,
+    #[serde(default = "default_caption_source")]
+    pub caption_source: CaptionSource,
+    /// Input device the captions record from, `None` picks one for the source
+    #[serde(default)]
+    pub caption_device: Option<String>,
+    /// Reads the final transcription aloud and holds it until confirmed instead of pasting
+    #[serde(default)]
+    pub readback_before_paste: bool,
+    /// Shows the final transcription in an editable preview and holds it until confirmed
+    /// instead of pasting
+    #[serde(default)]
+    pub confirm_before_paste: bool,
+    /// Id of the Piper voice transcriptions are read back with
+    #[serde(default = "default_readback_voice")]
+    pub readback_voice: String,
+    /// The `piper` program, `None` looks it up on the PATH
+    #[serde(default)]
+    pub piper_path: Option<String>,
+    /// Small model kept loaded for live partials while the selected one loads on demand
+    #[serde(default)]
+    pub standby_model: Option<String>,
+    /// Bigger model the finished recording is transcribed again with, `None` uses the
+    /// selected one
+    #[serde(default)]
+    pub rescore_model: Option<String>,
 }
 
 fn default_model() -> String {
@@ -317,6 +1035,10 @@ fn default_selected_language() -> String {
     "auto".to_string()
 }
 
+fn default_screen_reader_announcements() -> ScreenReaderAnnouncements {
+    ScreenReaderAnnouncements::Off
+}
+
 fn default_overlay_position() -> OverlayPosition {
     #[cfg(target_os = "linux")]
     return OverlayPosition::None;
@@ -332,10 +1054,46 @@ fn default_log_level() -> LogLevel {
     LogLevel::Debug
 }
 
+fn default_text_pipeline() -> Vec<PipelineStage> {
+    crate::pipeline::default_pipeline()
+}
+
+fn default_idle_check_interval_secs() -> u64 {
+    10
+}
+
+fn default_unload_warning_secs() -> u64 {
+    30
+}
+
 fn default_word_correction_threshold() -> f64 {
     0.18
 }
 
+fn default_rule_enabled() -> bool {
+    true
+}
+
+fn default_hook_timeout_secs() -> u32 {
+    10
+}
+
+fn default_vocabulary_boost() -> f32 {
+    2.0
+}
+
+fn default_language_model_weight() -> f64 {
+    1.0
+}
+
+fn default_usage_counters() -> bool {
+    true
+}
+
+fn default_block_secure_fields() -> bool {
+    true
+}
+
 fn default_history_limit() -> usize {
     5
 }
@@ -344,6 +1102,54 @@ fn default_recording_retention_period() -> RecordingRetentionPeriod {
     RecordingRetentionPeriod::PreserveLimit
 }
 
+fn default_cloud_fallback_to_local() -> bool {
+    true
+}
+
+fn default_azure_speech_region() -> String {
+    "eastus".to_string()
+}
+
+fn default_api_server_port() -> u16 {
+    8790
+}
+
+fn default_audio_level_rate_hz() -> u32 {
+    20
+}
+
+fn default_transcription_timeout_secs() -> Option<u32> {
+    Some(60)
+}
+
+fn default_transcription_workers() -> u32 {
+    2
+}
+
+fn default_caption_source() -> CaptionSource {
+    CaptionSource::Microphone
+}
+
+fn default_readback_voice() -> String {
+    "piper-en_US-lessac-medium".to_string()
+}
+
+fn default_max_utterance_secs() -> Option<u32> {
+    Some(20)
+}
+
+fn default_endpoint_sensitivity() -> EndpointSensitivity {
+    EndpointSensitivity::Balanced
+}
+
+fn default_unfinished_pause_ms() -> u32 {
+    1500
+}
+
+fn default_input_gain() -> f32 {
+    1.0
+}
+
 fn default_audio_feedback_volume() -> f32 {
     1.0
 }
@@ -424,6 +1230,14 @@ fn default_post_process_prompts() -> Vec<LLMPrompt> {
     }]
 }
 
+fn default_low_confidence_threshold() -> f32 {
+    0.5
+}
+
+fn default_disfluency_speaker() -> String {
+    "default".to_string()
+}
+
 pub const SETTINGS_STORE_PATH: &str = "settings_store.json";
 
 pub fn get_default_settings() -> AppSettings {
@@ -436,6 +1250,11 @@ pub fn get_default_settings() -> AppSettings {
     #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
     let default_shortcut = "alt+space";
 
+    #[cfg(target_os = "macos")]
+    let default_copy_next_part_shortcut = "option+shift+v";
+    #[cfg(not(target_os = "macos"))]
+    let default_copy_next_part_shortcut = "alt+shift+v";
+
     let mut bindings = HashMap::new();
     bindings.insert(
         "transcribe".to_string(),
@@ -445,6 +1264,7 @@ pub fn get_default_settings() -> AppSettings {
             description: "Converts your speech into text.".to_string(),
             default_binding: default_shortcut.to_string(),
             current_binding: default_shortcut.to_string(),
+            stop_binding: None,
         },
     );
     bindings.insert(
@@ -455,6 +1275,144 @@ pub fn get_default_settings() -> AppSettings {
             description: "Cancels the current recording.".to_string(),
             default_binding: "escape".to_string(),
             current_binding: "escape".to_string(),
+            stop_binding: None,
+        },
+    );
+    bindings.insert(
+        "copy_next_part".to_string(),
+        ShortcutBinding {
+            id: "copy_next_part".to_string(),
+            name: "Copy Next Part".to_string(),
+            description: "Copies the next part of a long transcription to the clipboard."
+                .to_string(),
+            default_binding: default_copy_next_part_shortcut.to_string(),
+            current_binding: default_copy_next_part_shortcut.to_string(),
+            stop_binding: None,
+        },
+    );
+    // Unassigned until the user picks a shortcut
+    bindings.insert(
+        "name_session".to_string(),
+        ShortcutBinding {
+            id: "name_session".to_string(),
+            name: "Name Session".to_string(),
+            description: "Opens the title and tags of the last dictation for editing.".to_string(),
+            default_binding: String::new(),
+            current_binding: String::new(),
+            stop_binding: None,
+        },
+    );
+    bindings.insert(
+        "push_to_talk".to_string(),
+        ShortcutBinding {
+            id: "push_to_talk".to_string(),
+            name: "Push to Talk".to_string(),
+            description: "Records while held, whatever the push-to-talk setting.".to_string(),
+            default_binding: String::new(),
+            current_binding: String::new(),
+            stop_binding: None,
+        },
+    );
+    bindings.insert(
+        "toggle_recording".to_string(),
+        ShortcutBinding {
+            id: "toggle_recording".to_string(),
+            name: "Toggle Recording".to_string(),
+            description: "Starts recording on one press and stops it on the next.".to_string(),
+            default_binding: String::new(),
+            current_binding: String::new(),
+            stop_binding: None,
+        },
+    );
+    bindings.insert(
+        "cancel_session".to_string(),
+        ShortcutBinding {
+            id: "cancel_session".to_string(),
+            name: "Cancel and Discard".to_string(),
+            description: "Stops the current dictation without pasting anything.".to_string(),
+            default_binding: String::new(),
+            current_binding: String::new(),
+            stop_binding: None,
+        },
+    );
+    bindings.insert(
+        "cycle_profile".to_string(),
+        ShortcutBinding {
+            id: "cycle_profile".to_string(),
+            name: "Next Profile".to_string(),
+            description: "Switches to the next dictation profile.".to_string(),
+            default_binding: String::new(),
+            current_binding: String::new(),
+            stop_binding: None,
+        },
+    );
+    bindings.insert(
+        "repeat_last_paste".to_string(),
+        ShortcutBinding {
+            id: "repeat_last_paste".to_string(),
+            name: "Paste Last Transcription".to_string(),
+            description: "Pastes the most recent transcription again.".to_string(),
+            default_binding: String::new(),
+            current_binding: String::new(),
+            stop_binding: None,
+        },
+    );
+    bindings.insert(
+        "undo_dictation".to_string(),
+        ShortcutBinding {
+            id: "undo_dictation".to_string(),
+            name: "Undo Last Dictation".to_string(),
+            description:
+                "Removes the last pasted transcription, pressing again removes the one before."
+                    .to_string(),
+            default_binding: String::new(),
+            current_binding: String::new(),
+            stop_binding: None,
+        },
+    );
+    bindings.insert(
+        "open_history".to_string(),
+        ShortcutBinding {
+            id: "open_history".to_string(),
+            name: "Open History".to_string(),
+            description: "Opens the history of your dictations.".to_string(),
+            default_binding: String::new(),
+            current_binding: String::new(),
+            stop_binding: None,
+        },
+    );
+    bindings.insert(
+        "read_back".to_string(),
+        ShortcutBinding {
+            id: "read_back".to_string(),
+            name: "Read That Back".to_string(),
+            description: "Reads the held or the last transcription aloud.".to_string(),
+            default_binding: String::new(),
+            current_binding: String::new(),
+            stop_binding: None,
+        },
+    );
+    bindings.insert(
+        "confirm_readback".to_string(),
+        ShortcutBinding {
+            id: "confirm_readback".to_string(),
+            name: "Confirm Transcription".to_string(),
+            description: "Pastes the transcription held for readback or review.".to_string(),
+            default_binding: String::new(),
+            current_binding: String::new(),
+            stop_binding: None,
+        },
+    );
+    bindings.insert(
+        "discard_readback".to_string(),
+        ShortcutBinding {
+            id: "discard_readback".to_string(),
+            name: "Discard Transcription".to_string(),
+            description: "Drops the transcription held for readback or review without pasting it."
+                .to_string(),
+            default_binding: String::new(),
+            current_binding: String::new(),
+            stop_binding: None,
         },
     );
 
@@ -475,15 +1433,76 @@ pub fn get_default_settings() -> AppSettings {
         translate_to_english: false,
         selected_language: "auto".to_string(),
         overlay_position: default_overlay_position(),
+        screen_reader_announcements: default_screen_reader_announcements(),
         debug_mode: false,
         log_level: default_log_level(),
         custom_words: Vec::new(),
+        proper_nouns: Vec::new(),
+        snippets: Vec::new(),
+        regex_rules: Vec::new(),
+        text_pipeline: default_text_pipeline(),
+        plugins: Vec::new(),
         model_unload_timeout: ModelUnloadTimeout::Never,
+        idle_check_interval_secs: default_idle_check_interval_secs(),
+        unload_warning_secs: default_unload_warning_secs(),
         word_correction_threshold: default_word_correction_threshold(),
+        vocabulary_biasing: None,
+        vocabulary_boost: default_vocabulary_boost(),
+        language_model_path: None,
+        language_model_weight: default_language_model_weight(),
         history_limit: default_history_limit(),
         recording_retention_period: default_recording_retention_period(),
+        audio_retention_days: None,
+        save_recording_audio: false,
+        recording_storage_limit_mb: None,
+        archive_expired_history: false,
+        encrypt_history: false,
+        session_voice_commands: false,
+        session_tag_rules: Vec::new(),
+        session_hooks: Vec::new(),
+        webhook_enabled: false,
+        webhook_url: String::new(),
+        profiles: Vec::new(),
+        active_profile_id: None,
+        profile_base: None,
+        prefetch_profile_models: false,
+        usage_counters: default_usage_counters(),
+        snippet_storage_limit_mb: 0,
         paste_method: PasteMethod::default(),
+        trigger_app_scope: TriggerAppScope::default(),
+        trigger_apps: Vec::new(),
+        output_target: OutputTarget::default(),
+        markdown_folder: None,
+        markdown_file: None,
+        humanized_typing: HumanizedTypingSettings::default(),
         clipboard_handling: ClipboardHandling::default(),
+        clipboard_chunk_chars: None,
+        low_confidence_threshold: default_low_confidence_threshold(),
+        low_confidence_marker: None,
+        filler_removal: false,
+        disfluency_removal: false,
+        disfluency_learning: false,
+        disfluency_speaker: default_disfluency_speaker(),
+        text_formatting: TextFormatting::default(),
+        shared_model_cache: false,
+        models_dir: None,
+        model_storage_limit_gb: None,
+        hub_models: Vec::new(),
+        low_memory_guard: LowMemoryGuard::default(),
+        inference_threads: None,
+        inference_priority: InferencePriority::default(),
+        inference_cores: InferenceCores::default(),
+        battery_policy: BatteryPolicy::default(),
+        perf_metrics_enabled: false,
+        mock_engine: MockEngineSettings::default(),
+        download_proxy: None,
+        download_ca_cert_path: None,
+        transcription_provider: TranscriptionProvider::default(),
+        cloud_fallback_to_local: default_cloud_fallback_to_local(),
+        azure_speech_region: default_azure_speech_region(),
+        api_server_enabled: false,
+        api_server_port: default_api_server_port(),
+        mcp_server_enabled: false,
         post_process_enabled: default_post_process_enabled(),
         post_process_provider_id: default_post_process_provider_id(),
         post_process_providers: default_post_process_providers(),
@@ -491,12 +1510,76 @@ pub fn get_default_settings() -> AppSettings {
         post_process_models: default_post_process_models(),
         post_process_prompts: default_post_process_prompts(),
         post_process_selected_prompt_id: None,
+        post_process_context_window: false,
+        post_process_context_selection: false,
         mute_while_recording: false,
+        audio_level_rate_hz: default_audio_level_rate_hz(),
         append_trailing_space: false,
+        fit_output_to_field: false,
+        block_secure_fields: default_block_secure_fields(),
+        input_gain: default_input_gain(),
+        agc_enabled: false,
+        pre_emphasis: false,
+        high_pass_cutoff_hz: None,
+        max_utterance_secs: default_max_utterance_secs(),
+        endpoint_sensitivity: default_endpoint_sensitivity(),
+        semantic_endpointing: false,
+        unfinished_pause_ms: default_unfinished_pause_ms(),
+        transcription_timeout_secs: default_transcription_timeout_secs(),
+        transcription_workers: default_transcription_workers(),
+        caption_source: default_caption_source(),
+        caption_device: None,
+        readback_before_paste: false,
+        confirm_before_paste: false,
+        readback_voice: default_readback_voice(),
+        piper_path: None,
+        standby_model: None,
+        rescore_model: None,
     }
 }
 
 impl AppSettings {
+    /// The custom words for the selected language and the active profile.
+    pub fn active_custom_words(&self) -> Vec<&CustomWord> {
+        self.custom_words
+            .iter()
+            .filter(|word| self.in_scope(&word.languages, &word.profiles))
+            .collect()
+    }
+
+    /// The regex rules for the selected language and the active profile.
+    pub fn active_regex_rules(&self) -> Vec<RegexRule> {
+        self.regex_rules
+            .iter()
+            .filter(|rule| self.in_scope(&rule.languages, &rule.profiles))
+            .cloned()
+            .collect()
+    }
+
+    /// Whether an entry limited to `languages` and `profiles` applies now. Languages match on
+    /// their primary subtag, so "zh" covers "zh-Hans". With the language left to detection
+    /// it isn't known, and entries for any language apply.
+    fn in_scope(&self, languages: &[String], profiles: &[String]) -> bool {
+        let primary = |language: &str| {
+            language
+                .split(['-', '_'])
+                .next()
+                .unwrap_or_default()
+                .to_lowercase()
+        };
+        let language_matches = languages.is_empty()
+            || self.selected_language == "auto"
+            || languages
+                .iter()
+                .any(|language| primary(language) == primary(&self.selected_language));
+        let profile_matches = profiles.is_empty()
+            || self
+                .active_profile_id
+                .as_ref()
+                .is_some_and(|active| profiles.contains(active));
+        language_matches && profile_matches
+    }
+
     pub fn active_post_process_provider(&self) -> Option<&PostProcessProvider> {
         self.post_process_providers
             .iter()
@@ -525,13 +1608,24 @@ pub fn load_or_create_app_settings(app: &AppHandle) -> AppSettings {
         .store(SETTINGS_STORE_PATH)
         .expect("Failed to initialize store");
 
-    let settings = if let Some(settings_value) = store.get("settings") {
+    let version = store
+        .get(SETTINGS_VERSION_KEY)
+        .and_then(|version| version.as_u64())
+        .map_or(0, |version| version as u32);
+
+    let settings = if let Some(mut settings_value) = store.get("settings") {
+        // Bring settings written by an older version up to date before parsing them
+        let migrated = version < SETTINGS_VERSION;
+        if let Err(e) = settings_bundle::migrate(&mut settings_value, version) {
+            warn!("Failed to migrate settings from version {}: {}", version, e);
+        }
+
         // Parse the entire settings object
         match serde_json::from_value::<AppSettings>(settings_value) {
             Ok(mut settings) => {
-                debug!("Found existing settings: {:?}", settings);
+                debug!("Found existing settings");
                 let default_settings = get_default_settings();
-                let mut updated = false;
+                let mut updated = migrated;
 
                 // Merge default bindings into existing settings
                 for (key, value) in default_settings.bindings {
@@ -543,7 +1637,7 @@ pub fn load_or_create_app_settings(app: &AppHandle) -> AppSettings {
                 }
 
                 if updated {
-                    debug!("Settings updated with new bindings");
+                    debug!("Settings updated with new bindings or migrated");
                     store.set("settings", serde_json::to_value(&settings).unwrap());
                 }
 
@@ -562,6 +1656,9 @@ pub fn load_or_create_app_settings(app: &AppHandle) -> AppSettings {
         store.set("settings", serde_json::to_value(&default_settings).unwrap());
         default_settings
     };
+    if version < SETTINGS_VERSION {
+        store.set(SETTINGS_VERSION_KEY, SETTINGS_VERSION);
+    }
 
     settings
 }
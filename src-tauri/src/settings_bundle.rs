@@ -0,0 +1,361 @@
+//! Versioned settings. The stored settings carry the version that wrote them and are brought
+//! up to date step by step on load, so older formats don't have to be understood forever.
+//! The same steps upgrade bundles made by `export`, the JSON used to move settings between
+//! machines.
+
+use crate::settings::AppSettings;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use specta::Type;
+use std::collections::HashMap;
+
+/// Version of the settings format this build writes
+pub const SETTINGS_VERSION: u32 = 2;
+
+/// Store key holding the version of the stored settings
+pub const SETTINGS_VERSION_KEY: &str = "settings_version";
+
+/// Settings tied to this machine or its secrets, never exported. Shortcuts stay behind too,
+/// they differ between platforms.
+const MACHINE_SETTINGS: &[&str] = &[
+    "bindings",
+    "post_process_api_keys",
+    "selected_microphone",
+    "clamshell_microphone",
+    "selected_output_device",
+    "caption_device",
+    "download_ca_cert_path",
+    "language_model_path",
+    "piper_path",
+    "markdown_folder",
+    "models_dir",
+    "inference_threads",
+    "mock_engine",
+    "autostart_enabled",
+];
+
+/// Settings that run commands, send text or audio elsewhere, or open the app to other
+/// programs. An import only takes them over when the user confirmed each one, see
+/// [`sensitive_changes`]. Profiles are among them as they can switch to a cloud provider.
+const SENSITIVE_SETTINGS: &[&str] = &[
+    "session_hooks",
+    "webhook_url",
+    "webhook_enabled",
+    "plugins",
+    "encrypt_history",
+    "transcription_provider",
+    "profiles",
+    "api_server_enabled",
+    "mcp_server_enabled",
+    "post_process_context_window",
+    "post_process_context_selection",
+    "download_proxy",
+];
+
+/// Prefix of the confirmation key for a post-processing provider's base URL, followed by
+/// the provider id
+const PROVIDER_BASE_URL_PREFIX: &str = "post_process_providers.base_url.";
+
+/// A sensitive setting a bundle would change, which the user has to confirm by `key`.
+#[derive(Clone, Debug, PartialEq, Serialize, Type)]
+pub struct SensitiveChange {
+    pub key: String,
+    /// The bundle's value, as JSON
+    pub value: String,
+}
+
+/// Steps from each version to the next, the first upgrading version 0
+const MIGRATIONS: [fn(&mut Map<String, Value>); SETTINGS_VERSION as usize] =
+    [log_level_names, custom_word_objects];
+
+/// Upgrades settings written by version `from` to `SETTINGS_VERSION`.
+pub fn migrate(settings: &mut Value, from: u32) -> Result<(), String> {
+    if from > SETTINGS_VERSION {
+        return Err(format!(
+            "Settings are from a newer version ({} > {})",
+            from, SETTINGS_VERSION
+        ));
+    }
+    let settings = settings
+        .as_object_mut()
+        .ok_or("Settings must be a JSON object")?;
+    for step in &MIGRATIONS[from as usize..] {
+        step(settings);
+    }
+    Ok(())
+}
+
+/// 0 → 1: log levels were stored as numbers from 1 (trace) to 5 (error)
+fn log_level_names(settings: &mut Map<String, Value>) {
+    let name = match settings.get("log_level").and_then(Value::as_u64) {
+        Some(1) => "trace",
+        Some(2) => "debug",
+        Some(3) => "info",
+        Some(4) => "warn",
+        Some(5) => "error",
+        _ => return,
+    };
+    settings.insert("log_level".to_string(), Value::from(name));
+}
+
+/// 1 → 2: custom words were plain strings, without spellings or thresholds
+fn custom_word_objects(settings: &mut Map<String, Value>) {
+    let Some(Value::Array(words)) = settings.get_mut("custom_words") else {
+        return;
+    };
+    for word in words.iter_mut() {
+        if let Value::String(text) = word {
+            *word = serde_json::json!({
+                "word": text,
+                "sounds_like": [],
+                "threshold": null,
+            });
+        }
+    }
+}
+
+/// Settings exported for another machine
+#[derive(Serialize, Deserialize)]
+pub struct SettingsBundle {
+    pub version: u32,
+    pub settings: Value,
+}
+
+/// Bundles `settings` without the machine's own ones. Custom words and regex rules are
+/// left out unless asked for.
+pub fn export(
+    settings: &AppSettings,
+    include_custom_words: bool,
+    include_regex_rules: bool,
+) -> Result<SettingsBundle, String> {
+    let mut value = serde_json::to_value(settings).map_err(|e| e.to_string())?;
+    let object = value
+        .as_object_mut()
+        .ok_or("Settings must be a JSON object")?;
+    for key in MACHINE_SETTINGS {
+        object.remove(*key);
+    }
+    if !include_custom_words {
+        object.remove("custom_words");
+    }
+    if !include_regex_rules {
+        object.remove("regex_rules");
+    }
+    Ok(SettingsBundle {
+        version: SETTINGS_VERSION,
+        settings: value,
+    })
+}
+
+/// The sensitive settings `bundle` would change on this machine, upgraded from the version
+/// that made it first.
+pub fn sensitive_changes(
+    current: &AppSettings,
+    bundle: &SettingsBundle,
+) -> Result<Vec<SensitiveChange>, String> {
+    let mut imported = bundle.settings.clone();
+    migrate(&mut imported, bundle.version)?;
+    let current = serde_json::to_value(current).map_err(|e| e.to_string())?;
+    let mut changes = Vec::new();
+    for key in SENSITIVE_SETTINGS {
+        if let Some(value) = imported
+            .get(*key)
+            .filter(|value| current.get(*key) != Some(*value))
+        {
+            changes.push(SensitiveChange {
+                key: key.to_string(),
+                value: value.to_string(),
+            });
+        }
+    }
+    for (id, base_url) in provider_base_urls(&imported) {
+        if provider_base_urls(&current).get(&id) != Some(&base_url) {
+            changes.push(SensitiveChange {
+                key: format!("{}{}", PROVIDER_BASE_URL_PREFIX, id),
+                value: Value::from(base_url).to_string(),
+            });
+        }
+    }
+    Ok(changes)
+}
+
+/// Applies `bundle` over `current`, upgrading it from the version that made it first.
+/// Settings the bundle leaves out, and this machine's own ones, keep their current values.
+/// So do the sensitive ones whose key isn't in `confirmed`; a provider whose base URL isn't
+/// confirmed keeps the current one, or isn't added when this machine doesn't have it.
+pub fn import(
+    current: &AppSettings,
+    bundle: SettingsBundle,
+    confirmed: &[String],
+) -> Result<AppSettings, String> {
+    let SettingsBundle {
+        version,
+        settings: mut imported,
+    } = bundle;
+    migrate(&mut imported, version)?;
+    let is_confirmed = |key: &str| confirmed.iter().any(|confirmed| confirmed == key);
+
+    let mut merged = serde_json::to_value(current).map_err(|e| e.to_string())?;
+    let current_base_urls = provider_base_urls(&merged);
+    let merged_object = merged
+        .as_object_mut()
+        .ok_or("Settings must be a JSON object")?;
+    for (key, value) in imported.as_object().into_iter().flatten() {
+        if MACHINE_SETTINGS.contains(&key.as_str())
+            || (SENSITIVE_SETTINGS.contains(&key.as_str()) && !is_confirmed(key))
+        {
+            continue;
+        }
+        let mut value = value.clone();
+        if key == "post_process_providers" {
+            if let Value::Array(providers) = &mut value {
+                providers.retain_mut(|provider| {
+                    let Some(id) = provider.get("id").and_then(Value::as_str) else {
+                        return false;
+                    };
+                    if is_confirmed(&format!("{}{}", PROVIDER_BASE_URL_PREFIX, id)) {
+                        return true;
+                    }
+                    match current_base_urls.get(id) {
+                        Some(base_url) => {
+                            provider["base_url"] = Value::from(base_url.as_str());
+                            true
+                        }
+                        None => false,
+                    }
+                });
+            }
+        }
+        merged_object.insert(key.clone(), value);
+    }
+    serde_json::from_value(merged).map_err(|e| format!("Invalid settings bundle: {}", e))
+}
+
+/// Base URL of each post-processing provider in `settings`, by provider id.
+fn provider_base_urls(settings: &Value) -> HashMap<String, String> {
+    settings
+        .get("post_process_providers")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|provider| {
+            Some((
+                provider.get("id")?.as_str()?.to_string(),
+                provider.get("base_url")?.as_str()?.to_string(),
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::{get_default_settings, CustomWord, LogLevel};
+    use serde_json::json;
+
+    #[test]
+    fn test_migrate_from_each_version() {
+        let mut settings = json!({ "log_level": 2, "custom_words": ["GitHub"] });
+        migrate(&mut settings, 0).unwrap();
+        assert_eq!(settings["log_level"], "debug");
+        assert_eq!(settings["custom_words"][0]["word"], "GitHub");
+
+        // Version 1 already wrote names, so numbers are left alone
+        let mut settings = json!({ "log_level": 2, "custom_words": ["GitHub"] });
+        migrate(&mut settings, 1).unwrap();
+        assert_eq!(settings["log_level"], 2);
+        assert_eq!(settings["custom_words"][0]["sounds_like"], json!([]));
+
+        assert!(migrate(&mut json!({}), SETTINGS_VERSION + 1).is_err());
+    }
+
+    #[test]
+    fn test_export_and_import_round_trip() {
+        let mut source = get_default_settings();
+        source.log_level = LogLevel::Warn;
+        source.custom_words = vec![CustomWord {
+            word: "Kubernetes".to_string(),
+            sounds_like: Vec::new(),
+            threshold: None,
+            languages: Vec::new(),
+            profiles: Vec::new(),
+        }];
+        source
+            .post_process_api_keys
+            .insert("openai".to_string(), "sk-secret".to_string());
+
+        let bundle = export(&source, false, false).unwrap();
+        assert!(bundle.settings.get("post_process_api_keys").is_none());
+        assert!(bundle.settings.get("custom_words").is_none());
+
+        let mut target = get_default_settings();
+        target.custom_words = vec![CustomWord {
+            word: "Handy".to_string(),
+            sounds_like: Vec::new(),
+            threshold: None,
+            languages: Vec::new(),
+            profiles: Vec::new(),
+        }];
+        let imported = import(&target, bundle, &[]).unwrap();
+        assert_eq!(imported.log_level, LogLevel::Warn);
+        assert_eq!(imported.custom_words[0].word, "Handy");
+        assert_eq!(imported.post_process_api_keys, target.post_process_api_keys);
+
+        let bundle = export(&source, true, false).unwrap();
+        let imported = import(&target, bundle, &[]).unwrap();
+        assert_eq!(imported.custom_words[0].word, "Kubernetes");
+    }
+
+    #[test]
+    fn test_import_needs_sensitive_settings_confirmed() {
+        let target = get_default_settings();
+        let mut source = get_default_settings();
+        source.webhook_enabled = true;
+        source.webhook_url = "https://example.com/hook".to_string();
+        source.post_process_providers[0].base_url = "https://example.com/v1".to_string();
+        source.api_server_enabled = true;
+        source.post_process_context_selection = true;
+        source.download_proxy = Some("http://proxy.example.com:8080".to_string());
+        let provider_id = source.post_process_providers[0].id.clone();
+        let base_url_key = format!("{}{}", PROVIDER_BASE_URL_PREFIX, provider_id);
+
+        let bundle = export(&source, false, false).unwrap();
+        let keys: Vec<String> = sensitive_changes(&target, &bundle)
+            .unwrap()
+            .into_iter()
+            .map(|change| change.key)
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                "webhook_url",
+                "webhook_enabled",
+                "api_server_enabled",
+                "post_process_context_selection",
+                "download_proxy",
+                base_url_key.as_str()
+            ]
+        );
+
+        let imported = import(&target, bundle, &[]).unwrap();
+        assert!(!imported.webhook_enabled);
+        assert!(!imported.api_server_enabled);
+        assert!(!imported.post_process_context_selection);
+        assert_eq!(imported.download_proxy, None);
+        assert_eq!(imported.webhook_url, target.webhook_url);
+        assert_eq!(
+            imported.post_process_providers[0].base_url,
+            target.post_process_providers[0].base_url
+        );
+
+        let bundle = export(&source, false, false).unwrap();
+        let confirmed = ["webhook_url".to_string(), base_url_key];
+        let imported = import(&target, bundle, &confirmed).unwrap();
+        assert!(!imported.webhook_enabled);
+        assert_eq!(imported.webhook_url, "https://example.com/hook");
+        assert_eq!(
+            imported.post_process_providers[0].base_url,
+            "https://example.com/v1"
+        );
+    }
+}
@@ -0,0 +1,298 @@
+//! Manifests describing a whole dictation setup: the models it uses, down to where they were
+//! downloaded from, and the shareable settings including profiles, custom words and regex
+//! rules. Applying one on another machine downloads what is missing and takes over the
+//! settings, so a team can start from the same setup.
+
+use crate::managers::model::{ModelInfo, Quantization};
+use crate::managers::model_integrity;
+use crate::settings::AppSettings;
+use crate::settings_bundle::{self, SettingsBundle};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Version of the manifest format this build writes
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// A model as the manifest pins it. The download URL, quantization and file hashes tell
+/// versions of the same model apart.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ManifestModel {
+    pub id: String,
+    pub name: String,
+    pub url: Option<String>,
+    pub quantization: Quantization,
+    pub size_mb: u64,
+    /// SHA-256 of each file by its path in the model, from the catalog or the downloaded
+    /// files
+    #[serde(default)]
+    pub sha256: HashMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SetupManifest {
+    pub version: u32,
+    /// Handy version that wrote the manifest
+    pub app_version: String,
+    pub models: Vec<ManifestModel>,
+    pub settings: SettingsBundle,
+}
+
+/// What applying a manifest did.
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct ManifestApplied {
+    pub downloaded: Vec<String>,
+    pub settings: AppSettings,
+}
+
+/// The steps to apply a manifest, worked out before anything changes.
+#[derive(Debug, Default, PartialEq)]
+pub struct ManifestPlan {
+    pub to_download: Vec<String>,
+}
+
+/// Describes the setup made of `settings` and the downloaded `models` in `models_dir`.
+/// Models the settings or a profile select are listed even when they aren't downloaded
+/// here. Downloaded files the catalog has no hash for are hashed.
+pub fn generate(
+    settings: &AppSettings,
+    models: &[ModelInfo],
+    models_dir: &Path,
+) -> Result<SetupManifest, String> {
+    let used = |id: &str| {
+        settings.selected_model == id
+            || settings
+                .profiles
+                .iter()
+                .any(|profile| profile.model_id.as_deref() == Some(id))
+    };
+    Ok(SetupManifest {
+        version: MANIFEST_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        models: models
+            .iter()
+            .filter(|model| model.is_downloaded || used(&model.id))
+            .map(|model| ManifestModel {
+                id: model.id.clone(),
+                name: model.name.clone(),
+                url: model.url.clone(),
+                quantization: model.quantization,
+                size_mb: model.size_mb,
+                sha256: file_hashes(model, models_dir),
+            })
+            .collect(),
+        settings: settings_bundle::export(settings, true, true)?,
+    })
+}
+
+/// The hash of each file of `model`, hashing the downloaded ones the catalog has none for.
+fn file_hashes(model: &ModelInfo, models_dir: &Path) -> HashMap<String, String> {
+    let mut hashes = model.sha256.clone();
+    if model.is_downloaded {
+        for file in model_integrity::expected_files(model) {
+            if hashes.contains_key(&file) {
+                continue;
+            }
+            let path = model_integrity::file_path(models_dir, model, &file);
+            if let Ok(hash) = model_integrity::sha256_file(&path) {
+                hashes.insert(file, hash);
+            }
+        }
+    }
+    hashes
+}
+
+/// Whether `model` in `models_dir` is the version `wanted` pins. Files are compared by the
+/// catalog's hash, or for a downloaded model the file's own.
+fn matches_pin(wanted: &ManifestModel, model: &ModelInfo, models_dir: &Path) -> bool {
+    if model.url != wanted.url || model.quantization != wanted.quantization {
+        return false;
+    }
+    wanted.sha256.iter().all(|(file, pinned)| {
+        let actual = match model.sha256.get(file) {
+            Some(hash) => Some(hash.clone()),
+            None if model.is_downloaded => {
+                model_integrity::sha256_file(&model_integrity::file_path(models_dir, model, file))
+                    .ok()
+            }
+            None => return true,
+        };
+        actual.is_some_and(|hash| hash.eq_ignore_ascii_case(pinned))
+    })
+}
+
+/// Checks that the models downloaded for `manifest` are the versions it pins.
+pub fn verify_downloads(
+    manifest: &SetupManifest,
+    downloaded: &[ModelInfo],
+    models_dir: &Path,
+) -> Result<(), String> {
+    let mismatched: Vec<&str> = manifest
+        .models
+        .iter()
+        .filter(|wanted| {
+            downloaded
+                .iter()
+                .find(|model| model.id == wanted.id)
+                .is_some_and(|model| !matches_pin(wanted, model, models_dir))
+        })
+        .map(|wanted| wanted.id.as_str())
+        .collect();
+    if mismatched.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Downloaded models differ from the manifest's: {}",
+            mismatched.join(", ")
+        ))
+    }
+}
+
+/// Checks `manifest` against the `available` models in `models_dir` and lists the ones to
+/// download. Fails, before anything is changed, when the manifest is newer than this build,
+/// names a model this build doesn't know, or pins another version of one.
+pub fn plan(
+    manifest: &SetupManifest,
+    available: &[ModelInfo],
+    models_dir: &Path,
+) -> Result<ManifestPlan, String> {
+    if manifest.version > MANIFEST_VERSION {
+        return Err(format!(
+            "The manifest is from a newer version of Handy ({})",
+            manifest.app_version
+        ));
+    }
+    let mut plan = ManifestPlan::default();
+    let mut unknown = Vec::new();
+    let mut mismatched = Vec::new();
+    for wanted in &manifest.models {
+        let Some(model) = available.iter().find(|model| model.id == wanted.id) else {
+            unknown.push(wanted.id.clone());
+            continue;
+        };
+        if !matches_pin(wanted, model, models_dir) {
+            mismatched.push(wanted.id.clone());
+        }
+        if !model.is_downloaded {
+            plan.to_download.push(wanted.id.clone());
+        }
+    }
+    if !unknown.is_empty() {
+        return Err(format!("Unknown models: {}", unknown.join(", ")));
+    }
+    if !mismatched.is_empty() {
+        return Err(format!(
+            "Models differ from the manifest's version: {}",
+            mismatched.join(", ")
+        ));
+    }
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::managers::model::EngineType;
+    use crate::settings::{get_default_settings, DictationProfile};
+    use std::collections::HashMap;
+
+    fn model(id: &str, is_downloaded: bool) -> ModelInfo {
+        ModelInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            filename: format!("{}.bin", id),
+            url: Some(format!("https://example.com/{}.bin", id)),
+            size_mb: 100,
+            is_downloaded,
+            is_downloading: false,
+            partial_size: 0,
+            is_directory: false,
+            engine_type: EngineType::Whisper,
+            accuracy_score: 0.5,
+            speed_score: 0.5,
+            quantization: Quantization::Fp16,
+            ram_mb: 200,
+            sha256: HashMap::new(),
+            download_files: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_generate_lists_used_and_downloaded_models() {
+        let mut settings = get_default_settings();
+        settings.selected_model = "small".to_string();
+        settings.profiles = vec![DictationProfile {
+            id: "code".to_string(),
+            name: "Code".to_string(),
+            model_id: Some("large".to_string()),
+            selected_language: None,
+            post_process_enabled: None,
+            post_process_provider_id: None,
+            post_process_prompt_id: None,
+            text_formatting: None,
+            paste_method: None,
+            output_target: None,
+            endpoint_sensitivity: None,
+            transcription_provider: None,
+            app_patterns: Vec::new(),
+        }];
+        let models = [
+            model("small", true),
+            model("large", false),
+            model("turbo", true),
+            model("unused", false),
+        ];
+
+        let manifest = generate(&settings, &models, Path::new("/nonexistent")).unwrap();
+        let ids: Vec<&str> = manifest.models.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["small", "large", "turbo"]);
+        assert_eq!(manifest.settings.settings["profiles"][0]["id"], "code");
+    }
+
+    #[test]
+    fn test_plan_downloads_missing_models() {
+        let settings = get_default_settings();
+        let dir = Path::new("/nonexistent");
+        let mut small = model("small", true);
+        small
+            .sha256
+            .insert("small.bin".to_string(), "aaaa".to_string());
+        let manifest = generate(&settings, &[small.clone(), model("turbo", true)], dir).unwrap();
+        assert_eq!(manifest.models[0].sha256["small.bin"], "aaaa");
+
+        small.is_downloaded = false;
+        let steps = plan(&manifest, &[small.clone(), model("turbo", true)], dir).unwrap();
+        assert_eq!(steps.to_download, ["small"]);
+
+        assert!(plan(&manifest, &[small.clone()], dir)
+            .unwrap_err()
+            .contains("turbo"));
+    }
+
+    #[test]
+    fn test_plan_fails_on_another_version() {
+        let settings = get_default_settings();
+        let dir = Path::new("/nonexistent");
+        let mut small = model("small", true);
+        small
+            .sha256
+            .insert("small.bin".to_string(), "aaaa".to_string());
+        let manifest = generate(&settings, &[small.clone()], dir).unwrap();
+
+        let mut quantized = small.clone();
+        quantized.quantization = Quantization::Int8;
+        assert!(plan(&manifest, &[quantized], dir)
+            .unwrap_err()
+            .contains("small"));
+
+        let mut rebuilt = small.clone();
+        rebuilt
+            .sha256
+            .insert("small.bin".to_string(), "bbbb".to_string());
+        assert!(plan(&manifest, &[rebuilt.clone()], dir).is_err());
+        assert!(verify_downloads(&manifest, &[rebuilt], dir).is_err());
+        assert!(verify_downloads(&manifest, &[small], dir).is_ok());
+    }
+}
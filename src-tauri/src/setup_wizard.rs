@@ -0,0 +1,323 @@
+//! The probing behind the first-run setup: which microphones there are, a five-second test
+//! take run through the model, and which model tier the machine can comfortably run. The
+//! frontend only walks the user through the steps and shows what comes back.
+
+use crate::audio_toolkit::audio::{list_input_devices, recommend_gain, AudioBuffer};
+use crate::audio_toolkit::constants::WHISPER_SAMPLE_RATE;
+use crate::helpers::system_memory;
+use crate::managers::audio::AudioRecordingManager;
+use crate::managers::model::ModelManager;
+use crate::managers::transcription::TranscriptionManager;
+use crate::settings::AppSettings;
+use log::{info, warn};
+use serde::Serialize;
+use specta::Type;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+/// The model offered to new users, and tested with when the selected one isn't downloaded
+pub const DEFAULT_MODEL: &str = "parakeet-tdt-0.6b-v3";
+
+const TEST_DURATION: Duration = Duration::from_secs(5);
+
+/// Loudest sample below which the take is taken as silence
+const SILENCE_PEAK: f32 = 0.01;
+/// Loudest sample at which the take is taken as clipped
+const CLIPPING_PEAK: f32 = 0.99;
+/// Speech RMS below which the user is asked to speak up or raise the gain
+const QUIET_SPEECH_RMS: f32 = 0.02;
+/// Inference taking longer than this share of the audio's duration is too slow for live use
+const SLOW_REALTIME_FACTOR: f32 = 0.5;
+
+/// Machines report a little less memory than is fitted, so an 8 GB machine shows about 7.7 GB
+const LIGHT_BELOW_MB: u64 = 7 * 1024;
+const HIGH_FROM_MB: u64 = 15 * 1024;
+const LIGHT_BELOW_CORES: usize = 4;
+const HIGH_FROM_CORES: usize = 8;
+
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct Microphone {
+    pub name: String,
+    /// The OS default input
+    pub is_default: bool,
+    /// The one in the settings, or the default when none is set
+    pub is_selected: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct TestTakeReport {
+    pub model_id: String,
+    pub heard: String,
+    pub recorded_secs: f32,
+    /// Loudest sample, from 0 to 1
+    pub peak_level: f32,
+    /// RMS of the louder (speech) part of the take
+    pub speech_rms: f32,
+    /// RMS of the quieter (background) part of the take
+    pub noise_floor: f32,
+    /// Manual input gain that would bring the speech to a comfortable level, `None` for a
+    /// silent take
+    pub recommended_gain: Option<f32>,
+    /// Time to load the model, 0 when it already was
+    pub load_ms: u64,
+    pub inference_ms: u64,
+    /// Inference time over the take's duration, below 1 is faster than real time
+    pub realtime_factor: f32,
+    /// What to fix before dictating, when something is off
+    pub problem: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelTier {
+    /// Fast and small, for machines short on memory or cores
+    Light,
+    Standard,
+    /// The most accurate model, for machines with plenty of both
+    High,
+}
+
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct ModelRecommendation {
+    pub tier: ModelTier,
+    pub model_id: String,
+    pub total_memory_mb: Option<u64>,
+    pub cpu_cores: u32,
+    pub reason: String,
+}
+
+/// The input devices, the selected one marked.
+pub fn detect_microphones(settings: &AppSettings) -> Result<Vec<Microphone>, String> {
+    let devices =
+        list_input_devices().map_err(|e| format!("Failed to list audio devices: {}", e))?;
+    let selected = settings
+        .selected_microphone
+        .as_deref()
+        .filter(|name| devices.iter().any(|device| device.name == *name));
+    Ok(devices
+        .into_iter()
+        .map(|device| Microphone {
+            is_selected: match selected {
+                Some(name) => device.name == name,
+                None => device.is_default,
+            },
+            is_default: device.is_default,
+            name: device.name,
+        })
+        .collect())
+}
+
+/// Records five seconds from the selected microphone and transcribes them with the selected
+/// model, or the default one when that isn't downloaded, loading it if needed and putting
+/// back the model loaded before afterwards. The user should say a sentence while it runs.
+pub fn run_test_take(app: &AppHandle, settings: &AppSettings) -> Result<TestTakeReport, String> {
+    let rm = app.state::<Arc<AudioRecordingManager>>();
+    let tm = app.state::<Arc<TranscriptionManager>>();
+    let model_manager = app.state::<Arc<ModelManager>>();
+    if rm.is_recording() {
+        return Err("Finish the recording in progress first".to_string());
+    }
+    let model_id = [settings.selected_model.as_str(), DEFAULT_MODEL]
+        .into_iter()
+        .find(|id| {
+            model_manager
+                .get_model_info(id)
+                .is_some_and(|model| model.is_downloaded)
+        })
+        .ok_or("Download a model before testing")?
+        .to_string();
+
+    let (samples, sample_rate) = rm.capture_input(TEST_DURATION).map_err(|e| e.to_string())?;
+    let recorded_secs = samples.len() as f32 / sample_rate as f32;
+    let levels = recommend_gain(&samples, sample_rate);
+    let peak_level = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+
+    let previous_model = tm.get_current_model();
+    let swapped = previous_model.as_deref() != Some(model_id.as_str());
+    let mut load_ms = 0;
+    if swapped {
+        let load_started = Instant::now();
+        tm.load_model(&model_id).map_err(|e| e.to_string())?;
+        load_ms = load_started.elapsed().as_millis() as u64;
+    }
+
+    // The take never goes to a cloud provider, it is timed on this machine
+    let transcribed = if peak_level < SILENCE_PEAK {
+        Ok((String::new(), 0))
+    } else {
+        let audio = AudioBuffer {
+            samples,
+            sample_rate,
+            channels: 1,
+        }
+        .resample(WHISPER_SAMPLE_RATE);
+        let inference_started = Instant::now();
+        tm.transcribe_on_device(audio.samples)
+            .map(|output| (output.text, inference_started.elapsed().as_millis() as u64))
+            .map_err(|e| e.to_string())
+    };
+    if swapped {
+        restore_model(&tm, previous_model.as_deref());
+    }
+    let (heard, inference_ms) = transcribed?;
+    let realtime_factor = if recorded_secs > 0.0 {
+        inference_ms as f32 / 1000.0 / recorded_secs
+    } else {
+        0.0
+    };
+    let speech_rms = levels.as_ref().map_or(0.0, |levels| levels.speech_rms);
+    let problem = diagnose(peak_level, speech_rms, &heard, realtime_factor);
+    info!(
+        "Setup test with {}: peak {:.3}, speech RMS {:.4}, inference {}ms (RTF {:.2})",
+        model_id, peak_level, speech_rms, inference_ms, realtime_factor
+    );
+
+    Ok(TestTakeReport {
+        model_id,
+        heard,
+        recorded_secs,
+        peak_level,
+        speech_rms,
+        noise_floor: levels.as_ref().map_or(0.0, |levels| levels.noise_floor),
+        recommended_gain: levels.map(|levels| levels.gain),
+        load_ms,
+        inference_ms,
+        realtime_factor,
+        problem,
+    })
+}
+
+/// Puts back the model that was loaded before the test take, or unloads the one it loaded.
+fn restore_model(tm: &TranscriptionManager, model_id: Option<&str>) {
+    let restored = match model_id {
+        Some(model_id) => tm.load_model(model_id).map_err(|e| e.to_string()),
+        None => tm.unload_model().map_err(|e| e.to_string()),
+    };
+    if let Err(e) = restored {
+        warn!("Failed to restore the model after the setup test: {}", e);
+    }
+}
+
+/// The model tier for this machine's memory and cores, a tier lower when the test take
+/// showed inference running slow.
+pub fn recommend_model(realtime_factor: Option<f32>) -> ModelRecommendation {
+    const MB: u64 = 1024 * 1024;
+    let total_memory_mb = system_memory::system_memory().map(|memory| memory.total_bytes / MB);
+    let cpu_cores = std::thread::available_parallelism().map_or(1, |cores| cores.get());
+    let slow = realtime_factor.is_some_and(|factor| factor > SLOW_REALTIME_FACTOR);
+    let tier = tier_for(total_memory_mb, cpu_cores, slow);
+    ModelRecommendation {
+        tier,
+        model_id: tier_model(tier).to_string(),
+        reason: reason(tier, total_memory_mb, cpu_cores, slow),
+        total_memory_mb,
+        cpu_cores: cpu_cores as u32,
+    }
+}
+
+fn tier_for(total_memory_mb: Option<u64>, cpu_cores: usize, slow: bool) -> ModelTier {
+    // Unknown memory counts as enough for the standard tier, never the high one
+    let tier = match total_memory_mb {
+        Some(memory) if memory < LIGHT_BELOW_MB => ModelTier::Light,
+        _ if cpu_cores < LIGHT_BELOW_CORES => ModelTier::Light,
+        Some(memory) if memory >= HIGH_FROM_MB && cpu_cores >= HIGH_FROM_CORES => ModelTier::High,
+        _ => ModelTier::Standard,
+    };
+    match (tier, slow) {
+        (ModelTier::High, true) => ModelTier::Standard,
+        (_, true) => ModelTier::Light,
+        (tier, false) => tier,
+    }
+}
+
+fn tier_model(tier: ModelTier) -> &'static str {
+    match tier {
+        ModelTier::Light => "small",
+        ModelTier::Standard => DEFAULT_MODEL,
+        ModelTier::High => "parakeet-tdt-0.6b-v3-fp32",
+    }
+}
+
+fn reason(tier: ModelTier, total_memory_mb: Option<u64>, cpu_cores: usize, slow: bool) -> String {
+    let machine = match total_memory_mb {
+        Some(memory) => format!(
+            "{:.0} GB of memory and {} CPU cores",
+            memory as f64 / 1024.0,
+            cpu_cores
+        ),
+        None => format!("{} CPU cores", cpu_cores),
+    };
+    let fit = match tier {
+        ModelTier::Light => "a small, fast model keeps dictation responsive",
+        ModelTier::Standard => "the standard model balances speed and accuracy",
+        ModelTier::High => "the most accurate model runs comfortably",
+    };
+    if slow {
+        format!("The test transcription was slow on {}, so {}", machine, fit)
+    } else {
+        format!("With {}, {}", machine, fit)
+    }
+}
+
+fn diagnose(peak_level: f32, speech_rms: f32, heard: &str, realtime_factor: f32) -> Option<String> {
+    if peak_level < SILENCE_PEAK {
+        Some("The microphone picked up nothing. Check it is connected and not muted.".to_string())
+    } else if peak_level >= CLIPPING_PEAK {
+        Some(
+            "The recording is clipping. Lower the input gain or move away from the microphone."
+                .to_string(),
+        )
+    } else if speech_rms < QUIET_SPEECH_RMS {
+        Some(
+            "The recording is quiet. Raise the input gain or move closer to the microphone."
+                .to_string(),
+        )
+    } else if heard.trim().is_empty() {
+        Some("No speech was recognized. Say a sentence while the test records.".to_string())
+    } else if realtime_factor > SLOW_REALTIME_FACTOR {
+        Some(
+            "Transcription was slow on this machine. A lighter model will feel more responsive."
+                .to_string(),
+        )
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tier_for() {
+        // 8 GB and 16 GB machines, as they report their memory
+        assert_eq!(tier_for(Some(7851), 8, false), ModelTier::Standard);
+        assert_eq!(tier_for(Some(15731), 12, false), ModelTier::High);
+        assert_eq!(tier_for(Some(15731), 4, false), ModelTier::Standard);
+        assert_eq!(tier_for(Some(3900), 8, false), ModelTier::Light);
+        assert_eq!(tier_for(Some(32000), 2, false), ModelTier::Light);
+        assert_eq!(tier_for(None, 16, false), ModelTier::Standard);
+        // A slow test take steps down a tier
+        assert_eq!(tier_for(Some(15731), 12, true), ModelTier::Standard);
+        assert_eq!(tier_for(Some(7851), 8, true), ModelTier::Light);
+    }
+
+    #[test]
+    fn test_diagnose() {
+        assert!(diagnose(0.001, 0.0, "", 0.0)
+            .unwrap()
+            .contains("picked up nothing"));
+        assert!(diagnose(1.0, 0.3, "hello", 0.1)
+            .unwrap()
+            .contains("clipping"));
+        assert!(diagnose(0.05, 0.005, "hello", 0.1)
+            .unwrap()
+            .contains("quiet"));
+        assert!(diagnose(0.5, 0.1, " ", 0.1).unwrap().contains("No speech"));
+        assert!(diagnose(0.5, 0.1, "hello", 0.9)
+            .unwrap()
+            .contains("lighter model"));
+        assert_eq!(diagnose(0.5, 0.1, "hello", 0.1), None);
+    }
+}
@@ -1,27 +1,41 @@
-use log::{error, warn};
+use log::{debug, error, warn};
 use serde::Serialize;
 use specta::Type;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_autostart::ManagerExt;
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
 
 use crate::actions::ACTION_MAP;
+use crate::api_server::ApiServer;
+use crate::helpers::focused_app;
+use crate::input_triggers::{self, TriggerListener};
 use crate::managers::audio::AudioRecordingManager;
 use crate::settings::ShortcutBinding;
 use crate::settings::{
-    self, get_settings, ClipboardHandling, LLMPrompt, OverlayPosition, PasteMethod, SoundTheme,
+    self, get_settings, ClipboardHandling, CustomWord, HumanizedTypingSettings, LLMPrompt,
+    OutputTarget, OverlayPosition, PasteMethod, PipelineStage, RegexRule, Snippet, SoundTheme,
+    TextFormatting, TriggerAppScope,
 };
+use crate::text_rules::{self, RulePreview};
 use crate::ManagedToggleState;
 
+/// Bindings registered only while they can be used, e.g. cancel during a recording
+const DYNAMIC_BINDINGS: [&str; 2] = ["cancel", "copy_next_part"];
+
+fn is_dynamic(id: &str) -> bool {
+    DYNAMIC_BINDINGS.contains(&id)
+}
+
 pub fn init_shortcuts(app: &AppHandle) {
     let default_bindings = settings::get_default_settings().bindings;
     let user_settings = settings::load_or_create_app_settings(app);
 
     // Register all default shortcuts, applying user customizations
     for (id, default_binding) in default_bindings {
-        if id == "cancel" {
-            continue; // Skip cancel shortcut, it will be registered dynamically
+        if is_dynamic(&id) {
+            continue; // Skip dynamic shortcuts, they are registered when needed
         }
         let binding = user_settings
             .bindings
@@ -64,46 +78,50 @@ pub fn change_binding(
             });
         }
     };
-    // If this is the cancel binding, just update the settings and return
-    // It's managed dynamically, so we don't register/unregister here
-    if id == "cancel" {
-        if let Some(mut b) = settings.bindings.get(&id).cloned() {
-            b.current_binding = binding;
-            settings.bindings.insert(id.clone(), b.clone());
-            settings::write_settings(&app, settings);
+    // An empty binding unassigns the trigger
+    let binding = binding.trim().to_string();
+    if !binding.is_empty() {
+        if let Err(e) = validate_shortcut_string(&binding) {
+            warn!("change_binding validation error: {}", e);
+            return Err(e);
+        }
+        if let Some(other) = find_conflict(&settings.bindings, &id, &binding) {
+            let error_msg = format!("'{}' is already used by {}", binding, other.name);
+            warn!("change_binding conflict: {}", error_msg);
             return Ok(BindingResponse {
-                success: true,
-                binding: Some(b.clone()),
-                error: None,
+                success: false,
+                binding: None,
+                error: Some(error_msg),
             });
         }
+        if let Some(stop) = binding_to_modify.stop_binding.as_deref() {
+            if same_trigger(stop, &binding) {
+                return Err("The start trigger must differ from the stop trigger".to_string());
+            }
+        }
     }
 
-    // Unregister the existing binding
-    if let Err(e) = unregister_shortcut(&app, binding_to_modify.clone()) {
-        let error_msg = format!("Failed to unregister shortcut: {}", e);
-        error!("change_binding error: {}", error_msg);
-    }
-
-    // Validate the new shortcut before we touch the current registration
-    if let Err(e) = validate_shortcut_string(&binding) {
-        warn!("change_binding validation error: {}", e);
-        return Err(e);
-    }
-
-    // Create an updated binding
-    let mut updated_binding = binding_to_modify;
+    let mut updated_binding = binding_to_modify.clone();
     updated_binding.current_binding = binding;
 
-    // Register the new binding
-    if let Err(e) = register_shortcut(&app, updated_binding.clone()) {
-        let error_msg = format!("Failed to register shortcut: {}", e);
-        error!("change_binding error: {}", error_msg);
-        return Ok(BindingResponse {
-            success: false,
-            binding: None,
-            error: Some(error_msg),
-        });
+    // Dynamic bindings are registered when needed, so only the settings change here
+    if !is_dynamic(&id) {
+        if let Err(e) = unregister_shortcut(&app, binding_to_modify.clone()) {
+            let error_msg = format!("Failed to unregister shortcut: {}", e);
+            error!("change_binding error: {}", error_msg);
+        }
+
+        if let Err(e) = register_shortcut(&app, updated_binding.clone()) {
+            let error_msg = format!("Failed to register shortcut: {}", e);
+            error!("change_binding error: {}", error_msg);
+            // Put the previous trigger back so the binding keeps working
+            let _ = register_shortcut(&app, binding_to_modify);
+            return Ok(BindingResponse {
+                success: false,
+                binding: None,
+                error: Some(error_msg),
+            });
+        }
     }
 
     // Update the binding in the settings
@@ -128,6 +146,67 @@ pub fn reset_binding(app: AppHandle, id: String) -> Result<BindingResponse, Stri
     return change_binding(app, id, binding.default_binding);
 }
 
+/// Sets or clears a separate stop trigger for a binding. With one set, the binding's main
+/// trigger only starts recording and this one only stops it, regardless of push-to-talk.
+#[tauri::command]
+#[specta::specta]
+pub fn change_stop_binding(
+    app: AppHandle,
+    id: String,
+    stop_binding: Option<String>,
+) -> Result<BindingResponse, String> {
+    if id == "cancel" {
+        return Err("The cancel binding can't have a stop trigger".to_string());
+    }
+
+    let mut settings = settings::get_settings(&app);
+    let Some(binding_to_modify) = settings.bindings.get(&id).cloned() else {
+        return Err(format!("Binding with id '{}' not found", id));
+    };
+
+    let stop_binding = stop_binding.filter(|b| !b.trim().is_empty());
+    if let Some(stop) = &stop_binding {
+        validate_shortcut_string(stop)?;
+        if same_trigger(stop, &binding_to_modify.current_binding) {
+            return Err("The stop trigger must differ from the start trigger".to_string());
+        }
+        if let Some(other) = find_conflict(&settings.bindings, &id, stop) {
+            return Err(format!("'{}' is already used by {}", stop, other.name));
+        }
+    }
+
+    if let Err(e) = unregister_shortcut(&app, binding_to_modify.clone()) {
+        error!(
+            "change_stop_binding error: Failed to unregister shortcut: {}",
+            e
+        );
+    }
+
+    let mut updated_binding = binding_to_modify.clone();
+    updated_binding.stop_binding = stop_binding;
+
+    if let Err(e) = register_shortcut(&app, updated_binding.clone()) {
+        let error_msg = format!("Failed to register shortcut: {}", e);
+        error!("change_stop_binding error: {}", error_msg);
+        // Put the previous triggers back so the binding keeps working
+        let _ = register_shortcut(&app, binding_to_modify);
+        return Ok(BindingResponse {
+            success: false,
+            binding: None,
+            error: Some(error_msg),
+        });
+    }
+
+    settings.bindings.insert(id, updated_binding.clone());
+    settings::write_settings(&app, settings);
+
+    Ok(BindingResponse {
+        success: true,
+        binding: Some(updated_binding),
+        error: None,
+    })
+}
+
 #[tauri::command]
 #[specta::specta]
 pub fn change_ptt_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
@@ -138,6 +217,7 @@ pub fn change_ptt_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
     settings.push_to_talk = enabled;
 
     settings::write_settings(&app, settings);
+    crate::tray::refresh_tray_menu(&app);
 
     Ok(())
 }
@@ -148,6 +228,7 @@ pub fn change_audio_feedback_setting(app: AppHandle, enabled: bool) -> Result<()
     let mut settings = settings::get_settings(&app);
     settings.audio_feedback = enabled;
     settings::write_settings(&app, settings);
+    crate::tray::refresh_tray_menu(&app);
     Ok(())
 }
 
@@ -303,9 +384,39 @@ pub fn change_update_checks_setting(app: AppHandle, enabled: bool) -> Result<(),
 
 #[tauri::command]
 #[specta::specta]
-pub fn update_custom_words(app: AppHandle, words: Vec<String>) -> Result<(), String> {
+pub fn update_custom_words(app: AppHandle, words: Vec<CustomWord>) -> Result<(), String> {
+    if let Some(word) = words
+        .iter()
+        .find(|w| w.threshold.is_some_and(|t| !(0.0..=1.0).contains(&t)))
+    {
+        return Err(format!(
+            "Correction threshold for '{}' must be between 0 and 1",
+            word.word
+        ));
+    }
+
     let mut settings = settings::get_settings(&app);
-    settings.custom_words = words;
+    settings.custom_words = words
+        .into_iter()
+        .map(|mut w| {
+            w.word = w.word.trim().to_string();
+            w.sounds_like.retain(|s| !s.trim().is_empty());
+            w.languages.retain(|l| !l.trim().is_empty());
+            w.profiles.retain(|p| !p.trim().is_empty());
+            w
+        })
+        .filter(|w| !w.word.is_empty())
+        .collect();
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Names that are always written with the given capitalization, e.g. "New York".
+#[tauri::command]
+#[specta::specta]
+pub fn update_proper_nouns(app: AppHandle, names: Vec<String>) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.proper_nouns = names;
     settings::write_settings(&app, settings);
     Ok(())
 }
@@ -331,6 +442,8 @@ pub fn change_paste_method_setting(app: AppHandle, method: String) -> Result<(),
         "direct" => PasteMethod::Direct,
         "none" => PasteMethod::None,
         "shift_insert" => PasteMethod::ShiftInsert,
+        "humanized_typing" => PasteMethod::HumanizedTyping,
+        "accessibility" => PasteMethod::Accessibility,
         other => {
             warn!("Invalid paste method '{}', defaulting to ctrl_v", other);
             PasteMethod::CtrlV
@@ -341,6 +454,48 @@ pub fn change_paste_method_setting(app: AppHandle, method: String) -> Result<(),
     Ok(())
 }
 
+/// Sets where dictations go. Appending to Markdown notes needs an existing folder; `file`
+/// names the note to append to, the day's note being used when it is `None`.
+#[tauri::command]
+#[specta::specta]
+pub fn set_output_target(
+    app: AppHandle,
+    target: OutputTarget,
+    markdown_folder: Option<String>,
+    markdown_file: Option<String>,
+) -> Result<(), String> {
+    let markdown_folder = markdown_folder
+        .map(|folder| folder.trim().to_string())
+        .filter(|folder| !folder.is_empty());
+    if target == OutputTarget::MarkdownNote
+        && !markdown_folder
+            .as_deref()
+            .is_some_and(|folder| std::path::Path::new(folder).is_dir())
+    {
+        return Err("Choose an existing folder for the Markdown notes".to_string());
+    }
+    let mut settings = settings::get_settings(&app);
+    settings.output_target = target;
+    settings.markdown_folder = markdown_folder;
+    settings.markdown_file = markdown_file
+        .map(|file| file.trim().to_string())
+        .filter(|file| !file.is_empty());
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_humanized_typing_settings(
+    app: AppHandle,
+    typing: HumanizedTypingSettings,
+) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.humanized_typing = typing;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub fn change_clipboard_handling_setting(app: AppHandle, handling: String) -> Result<(), String> {
@@ -361,12 +516,134 @@ pub fn change_clipboard_handling_setting(app: AppHandle, handling: String) -> Re
     Ok(())
 }
 
+/// Copies transcriptions longer than `max_chars` characters to the clipboard one part at a
+/// time, `None` copies them whole.
+#[tauri::command]
+#[specta::specta]
+pub fn change_clipboard_chunk_size_setting(
+    app: AppHandle,
+    max_chars: Option<usize>,
+) -> Result<(), String> {
+    if max_chars == Some(0) {
+        return Err("Clipboard part size must be at least one character".to_string());
+    }
+    let mut settings = settings::get_settings(&app);
+    settings.clipboard_chunk_chars = max_chars;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_text_formatting_setting(app: AppHandle, formatting: String) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    let parsed = match formatting.as_str() {
+        "plain" => TextFormatting::Plain,
+        "markdown" => TextFormatting::Markdown,
+        "rich_text" => TextFormatting::RichText,
+        other => {
+            warn!("Invalid text formatting '{}', defaulting to plain", other);
+            TextFormatting::Plain
+        }
+    };
+    settings.text_formatting = parsed;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_api_server_enabled_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.api_server_enabled = enabled;
+    settings::write_settings(&app, settings);
+
+    app.state::<ApiServer>()
+        .apply_settings(&app)
+        .map_err(|e| format!("Failed to start API server: {}", e))
+}
+
+/// Limits the applications the shortcuts and triggers can start a dictation in.
+#[tauri::command]
+#[specta::specta]
+pub fn set_trigger_app_scope(
+    app: AppHandle,
+    scope: TriggerAppScope,
+    apps: Vec<String>,
+) -> Result<(), String> {
+    let apps: Vec<String> = apps
+        .iter()
+        .map(|app| app.trim().to_string())
+        .filter(|app| !app.is_empty())
+        .collect();
+    if scope == TriggerAppScope::OnlyIn && apps.is_empty() {
+        return Err("Name at least one application to allow dictation in".to_string());
+    }
+    let mut settings = settings::get_settings(&app);
+    settings.trigger_app_scope = scope;
+    settings.trigger_apps = apps;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Enables the MCP endpoint, which runs on the API server's port.
+#[tauri::command]
+#[specta::specta]
+pub fn change_mcp_server_enabled_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.mcp_server_enabled = enabled;
+    settings::write_settings(&app, settings);
+
+    app.state::<ApiServer>()
+        .apply_settings(&app)
+        .map_err(|e| format!("Failed to start API server: {}", e))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_api_server_port_setting(app: AppHandle, port: u16) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.api_server_port = port;
+    settings::write_settings(&app, settings);
+
+    app.state::<ApiServer>()
+        .apply_settings(&app)
+        .map_err(|e| format!("Failed to start API server on port {}: {}", port, e))
+}
+
 #[tauri::command]
 #[specta::specta]
 pub fn change_post_process_enabled_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
     let mut settings = settings::get_settings(&app);
     settings.post_process_enabled = enabled;
     settings::write_settings(&app, settings);
+    crate::tray::refresh_tray_menu(&app);
+    Ok(())
+}
+
+/// Lets post-processing see the focused app and window title. Off by default for privacy.
+#[tauri::command]
+#[specta::specta]
+pub fn change_post_process_context_window_setting(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.post_process_context_window = enabled;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Lets post-processing see the text selected in the focused app. Off by default for privacy.
+#[tauri::command]
+#[specta::specta]
+pub fn change_post_process_context_selection_setting(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.post_process_context_selection = enabled;
+    settings::write_settings(&app, settings);
     Ok(())
 }
 
@@ -528,6 +805,110 @@ pub fn delete_post_process_prompt(app: AppHandle, id: String) -> Result<(), Stri
     Ok(())
 }
 
+#[tauri::command]
+#[specta::specta]
+pub fn add_snippet(app: AppHandle, trigger: String, template: String) -> Result<Snippet, String> {
+    if trigger.trim().is_empty() {
+        return Err("Snippet trigger cannot be empty".to_string());
+    }
+    let mut settings = settings::get_settings(&app);
+
+    let snippet = Snippet {
+        id: format!("snippet_{}", chrono::Utc::now().timestamp_millis()),
+        trigger: trigger.trim().to_string(),
+        template,
+    };
+
+    settings.snippets.push(snippet.clone());
+    settings::write_settings(&app, settings);
+
+    Ok(snippet)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn update_snippet(
+    app: AppHandle,
+    id: String,
+    trigger: String,
+    template: String,
+) -> Result<(), String> {
+    if trigger.trim().is_empty() {
+        return Err("Snippet trigger cannot be empty".to_string());
+    }
+    let mut settings = settings::get_settings(&app);
+
+    let snippet = settings
+        .snippets
+        .iter_mut()
+        .find(|s| s.id == id)
+        .ok_or_else(|| format!("Snippet with id '{}' not found", id))?;
+    snippet.trigger = trigger.trim().to_string();
+    snippet.template = template;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn delete_snippet(app: AppHandle, id: String) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+
+    let original_len = settings.snippets.len();
+    settings.snippets.retain(|s| s.id != id);
+    if settings.snippets.len() == original_len {
+        return Err(format!("Snippet with id '{}' not found", id));
+    }
+
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Replaces the regex rules, which run in the given order. Every pattern must compile.
+#[tauri::command]
+#[specta::specta]
+pub fn set_regex_rules(app: AppHandle, rules: Vec<RegexRule>) -> Result<(), String> {
+    for rule in &rules {
+        text_rules::compile(rule)?;
+    }
+    let mut settings = settings::get_settings(&app);
+    settings.regex_rules = rules;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Replaces the text pipeline's stages. Each stage may appear once, stages left out run
+/// in their default place and state.
+#[tauri::command]
+#[specta::specta]
+pub fn set_text_pipeline(app: AppHandle, stages: Vec<PipelineStage>) -> Result<(), String> {
+    for (i, stage) in stages.iter().enumerate() {
+        if stages[..i].iter().any(|s| s.stage == stage.stage) {
+            return Err(format!(
+                "The stage {:?} appears more than once",
+                stage.stage
+            ));
+        }
+    }
+    let mut settings = settings::get_settings(&app);
+    settings.text_pipeline = stages;
+    settings::write_settings(&app, settings);
+    Ok(())
+}
+
+/// Dry run of the regex rules on `sample`, showing the text after each rule. Previews the
+/// saved rules unless `rules` is given, so edits can be checked before saving them.
+#[tauri::command]
+#[specta::specta]
+pub fn preview_regex_rules(
+    app: AppHandle,
+    sample: String,
+    rules: Option<Vec<RegexRule>>,
+) -> Result<RulePreview, String> {
+    let rules = rules.unwrap_or_else(|| settings::get_settings(&app).regex_rules);
+    text_rules::preview(&sample, &rules)
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn fetch_post_process_models(
@@ -703,10 +1084,82 @@ pub fn change_append_trailing_space_setting(app: AppHandle, enabled: bool) -> Re
     Ok(())
 }
 
+#[tauri::command]
+#[specta::specta]
+pub fn change_fit_output_to_field_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.fit_output_to_field = enabled;
+    settings::write_settings(&app, settings);
+
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_block_secure_fields_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.block_secure_fields = enabled;
+    settings::write_settings(&app, settings);
+
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_readback_before_paste_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.readback_before_paste = enabled;
+    settings::write_settings(&app, settings);
+
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn change_confirm_before_paste_setting(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = settings::get_settings(&app);
+    settings.confirm_before_paste = enabled;
+    settings::write_settings(&app, settings);
+
+    Ok(())
+}
+
+/// Whether two triggers fire on the same keys or button, however they are spelled.
+fn same_trigger(a: &str, b: &str) -> bool {
+    let (a, b) = (a.trim(), b.trim());
+    if input_triggers::is_device_trigger(a) || input_triggers::is_device_trigger(b) {
+        return a.eq_ignore_ascii_case(b);
+    }
+    match (a.parse::<Shortcut>(), b.parse::<Shortcut>()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a.eq_ignore_ascii_case(b),
+    }
+}
+
+/// The binding other than `id` that already starts or stops on `trigger`. Dynamic bindings
+/// count too, their triggers are only unregistered for now.
+fn find_conflict<'a>(
+    bindings: &'a HashMap<String, ShortcutBinding>,
+    id: &str,
+    trigger: &str,
+) -> Option<&'a ShortcutBinding> {
+    bindings.values().find(|other| {
+        other.id != id
+            && std::iter::once(other.current_binding.as_str())
+                .chain(other.stop_binding.as_deref())
+                .any(|t| !t.trim().is_empty() && same_trigger(t, trigger))
+    })
+}
+
 /// Determine whether a shortcut string contains at least one non-modifier key.
 /// We allow single non-modifier keys (e.g. "f5" or "space") but disallow
 /// modifier-only combos (e.g. "ctrl" or "ctrl+shift").
+/// Mouse and gamepad triggers are checked by `input_triggers` instead.
 fn validate_shortcut_string(raw: &str) -> Result<(), String> {
+    if input_triggers::is_device_trigger(raw) {
+        return input_triggers::validate_trigger(raw).map(|_| ());
+    }
+
     let modifiers = [
         "ctrl", "control", "shift", "alt", "option", "meta", "command", "cmd", "super", "win",
         "windows",
@@ -748,11 +1201,46 @@ pub fn resume_binding(app: AppHandle, id: String) -> Result<(), String> {
     Ok(())
 }
 
+/// Unregisters the shortcuts that start an action, or registers them again, so dictation
+/// can be paused from the tray. The dynamic ones come and go with their session as usual.
+pub fn set_shortcuts_paused(app: &AppHandle, paused: bool) {
+    for (id, binding) in settings::get_bindings(app) {
+        if is_dynamic(&id) {
+            continue;
+        }
+        let result = if paused {
+            unregister_shortcut(app, binding)
+        } else {
+            register_shortcut(app, binding)
+        };
+        if let Err(e) = result {
+            error!("Failed to update paused shortcut {}: {}", id, e);
+        }
+    }
+}
+
 pub fn register_cancel_shortcut(app: &AppHandle) {
-    // Cancel shortcut is disabled on Linux due to instability with dynamic shortcut registration
+    register_dynamic_shortcut(app, "cancel");
+}
+
+pub fn unregister_cancel_shortcut(app: &AppHandle) {
+    unregister_dynamic_shortcut(app, "cancel");
+}
+
+/// Registered only while a chunked clipboard copy has parts left.
+pub fn register_copy_next_part_shortcut(app: &AppHandle) {
+    register_dynamic_shortcut(app, "copy_next_part");
+}
+
+pub fn unregister_copy_next_part_shortcut(app: &AppHandle) {
+    unregister_dynamic_shortcut(app, "copy_next_part");
+}
+
+fn register_dynamic_shortcut(app: &AppHandle, binding_id: &'static str) {
+    // Dynamic shortcuts are disabled on Linux due to instability with dynamic shortcut registration
     #[cfg(target_os = "linux")]
     {
-        let _ = app;
+        let _ = (app, binding_id);
         return;
     }
 
@@ -760,20 +1248,20 @@ pub fn register_cancel_shortcut(app: &AppHandle) {
     {
         let app_clone = app.clone();
         tauri::async_runtime::spawn(async move {
-            if let Some(cancel_binding) = get_settings(&app_clone).bindings.get("cancel").cloned() {
-                if let Err(e) = register_shortcut(&app_clone, cancel_binding) {
-                    eprintln!("Failed to register cancel shortcut: {}", e);
+            if let Some(binding) = get_settings(&app_clone).bindings.get(binding_id).cloned() {
+                if let Err(e) = register_shortcut(&app_clone, binding) {
+                    eprintln!("Failed to register {} shortcut: {}", binding_id, e);
                 }
             }
         });
     }
 }
 
-pub fn unregister_cancel_shortcut(app: &AppHandle) {
-    // Cancel shortcut is disabled on Linux due to instability with dynamic shortcut registration
+fn unregister_dynamic_shortcut(app: &AppHandle, binding_id: &'static str) {
+    // Dynamic shortcuts are disabled on Linux due to instability with dynamic shortcut registration
     #[cfg(target_os = "linux")]
     {
-        let _ = app;
+        let _ = (app, binding_id);
         return;
     }
 
@@ -781,32 +1269,89 @@ pub fn unregister_cancel_shortcut(app: &AppHandle) {
     {
         let app_clone = app.clone();
         tauri::async_runtime::spawn(async move {
-            if let Some(cancel_binding) = get_settings(&app_clone).bindings.get("cancel").cloned() {
+            if let Some(binding) = get_settings(&app_clone).bindings.get(binding_id).cloned() {
                 // We ignore errors here as it might already be unregistered
-                let _ = unregister_shortcut(&app_clone, cancel_binding);
+                let _ = unregister_shortcut(&app_clone, binding);
             }
         });
     }
 }
 
+/// How a trigger drives its binding's action.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TriggerRole {
+    /// The binding's only trigger: push-to-talk or toggle, depending on settings
+    Binding,
+    /// Starts the action; a separate stop trigger ends it
+    Start,
+    /// Ends an action started by the binding's start trigger
+    Stop,
+}
+
 pub fn register_shortcut(app: &AppHandle, binding: ShortcutBinding) -> Result<(), String> {
+    if binding.current_binding.is_empty() {
+        return Ok(());
+    }
+    let Some(stop_binding) = binding.stop_binding.as_deref() else {
+        return register_trigger(
+            app,
+            &binding.id,
+            &binding.current_binding,
+            TriggerRole::Binding,
+        );
+    };
+
+    register_trigger(
+        app,
+        &binding.id,
+        &binding.current_binding,
+        TriggerRole::Start,
+    )?;
+    if let Err(e) = register_trigger(app, &binding.id, stop_binding, TriggerRole::Stop) {
+        let _ = unregister_trigger(app, &binding.current_binding);
+        return Err(e);
+    }
+    Ok(())
+}
+
+pub fn unregister_shortcut(app: &AppHandle, binding: ShortcutBinding) -> Result<(), String> {
+    if binding.current_binding.is_empty() {
+        return Ok(());
+    }
+    let result = unregister_trigger(app, &binding.current_binding);
+    if let Some(stop_binding) = binding.stop_binding.as_deref() {
+        unregister_trigger(app, stop_binding)?;
+    }
+    result
+}
+
+fn register_trigger(
+    app: &AppHandle,
+    binding_id: &str,
+    trigger: &str,
+    role: TriggerRole,
+) -> Result<(), String> {
+    if input_triggers::is_device_trigger(trigger) {
+        return app
+            .state::<TriggerListener>()
+            .register(app, trigger, binding_id, role)
+            .inspect_err(|e| warn!("_register_shortcut trigger error: {}", e));
+    }
+
     // Validate human-level rules first
-    if let Err(e) = validate_shortcut_string(&binding.current_binding) {
+    if let Err(e) = validate_shortcut_string(trigger) {
         warn!(
             "_register_shortcut validation error for binding '{}': {}",
-            binding.current_binding, e
+            trigger, e
         );
         return Err(e);
     }
 
     // Parse shortcut and return error if it fails
-    let shortcut = match binding.current_binding.parse::<Shortcut>() {
+    let shortcut = match trigger.parse::<Shortcut>() {
         Ok(s) => s,
         Err(e) => {
-            let error_msg = format!(
-                "Failed to parse shortcut '{}': {}",
-                binding.current_binding, e
-            );
+            let error_msg = format!("Failed to parse shortcut '{}': {}", trigger, e);
             error!("_register_shortcut parse error: {}", error_msg);
             return Err(error_msg);
         }
@@ -814,66 +1359,28 @@ pub fn register_shortcut(app: &AppHandle, binding: ShortcutBinding) -> Result<()
 
     // Prevent duplicate registrations that would silently shadow one another
     if app.global_shortcut().is_registered(shortcut) {
-        let error_msg = format!("Shortcut '{}' is already in use", binding.current_binding);
+        let error_msg = format!("Shortcut '{}' is already in use", trigger);
         warn!("_register_shortcut duplicate error: {}", error_msg);
         return Err(error_msg);
     }
 
-    // Clone binding.id for use in the closure
-    let binding_id_for_closure = binding.id.clone();
+    // Clone binding id for use in the closure
+    let binding_id_for_closure = binding_id.to_string();
 
     app.global_shortcut()
         .on_shortcut(shortcut, move |ah, scut, event| {
             if scut == &shortcut {
-                let shortcut_string = scut.into_string();
-                let settings = get_settings(ah);
-
-                if let Some(action) = ACTION_MAP.get(&binding_id_for_closure) {
-                    if binding_id_for_closure == "cancel" {
-                        let audio_manager = ah.state::<Arc<AudioRecordingManager>>();
-                        if audio_manager.is_recording() && event.state == ShortcutState::Pressed {
-                            action.start(ah, &binding_id_for_closure, &shortcut_string);
-                        }
-                        return;
-                    } else if settings.push_to_talk {
-                        if event.state == ShortcutState::Pressed {
-                            action.start(ah, &binding_id_for_closure, &shortcut_string);
-                        } else if event.state == ShortcutState::Released {
-                            action.stop(ah, &binding_id_for_closure, &shortcut_string);
-                        }
-                    } else {
-                        if event.state == ShortcutState::Pressed {
-                            let toggle_state_manager = ah.state::<ManagedToggleState>();
-
-                            let mut states = toggle_state_manager.lock().expect("Failed to lock toggle state manager");
-
-                            let is_currently_active = states.active_toggles
-                                .entry(binding_id_for_closure.clone())
-                                .or_insert(false);
-
-                            if *is_currently_active {
-                                action.stop(
-                                    ah,
-                                    &binding_id_for_closure,
-                                    &shortcut_string,
-                                );
-                                *is_currently_active = false; // Update state to inactive
-                            } else {
-                                action.start(ah, &binding_id_for_closure, &shortcut_string);
-                                *is_currently_active = true; // Update state to active
-                            }
-                        }
-                    }
-                } else {
-                    warn!(
-                        "No action defined in ACTION_MAP for shortcut ID '{}'. Shortcut: '{}', State: {:?}",
-                        binding_id_for_closure, shortcut_string, event.state
-                    );
-                }
+                handle_trigger(
+                    ah,
+                    &binding_id_for_closure,
+                    role,
+                    &scut.into_string(),
+                    event.state,
+                );
             }
         })
         .map_err(|e| {
-            let error_msg = format!("Couldn't register shortcut '{}': {}", binding.current_binding, e);
+            let error_msg = format!("Couldn't register shortcut '{}': {}", trigger, e);
             error!("_register_shortcut registration error: {}", error_msg);
             error_msg
         })?;
@@ -881,13 +1388,18 @@ pub fn register_shortcut(app: &AppHandle, binding: ShortcutBinding) -> Result<()
     Ok(())
 }
 
-pub fn unregister_shortcut(app: &AppHandle, binding: ShortcutBinding) -> Result<(), String> {
-    let shortcut = match binding.current_binding.parse::<Shortcut>() {
+fn unregister_trigger(app: &AppHandle, trigger: &str) -> Result<(), String> {
+    if input_triggers::is_device_trigger(trigger) {
+        app.state::<TriggerListener>().unregister(trigger);
+        return Ok(());
+    }
+
+    let shortcut = match trigger.parse::<Shortcut>() {
         Ok(s) => s,
         Err(e) => {
             let error_msg = format!(
                 "Failed to parse shortcut '{}' for unregistration: {}",
-                binding.current_binding, e
+                trigger, e
             );
             error!("_unregister_shortcut parse error: {}", error_msg);
             return Err(error_msg);
@@ -895,13 +1407,232 @@ pub fn unregister_shortcut(app: &AppHandle, binding: ShortcutBinding) -> Result<
     };
 
     app.global_shortcut().unregister(shortcut).map_err(|e| {
-        let error_msg = format!(
-            "Failed to unregister shortcut '{}': {}",
-            binding.current_binding, e
-        );
+        let error_msg = format!("Failed to unregister shortcut '{}': {}", trigger, e);
         error!("_unregister_shortcut error: {}", error_msg);
         error_msg
     })?;
 
     Ok(())
 }
+
+/// Starts the action of `binding_id` when it isn't active and stops it when it is, whatever
+/// the push-to-talk setting. For triggers without a release, like a signal or a menu item.
+/// Returns whether the action is active afterwards.
+pub fn toggle_binding(ah: &AppHandle, binding_id: &str, source: &str) -> bool {
+    let Some(action) = ACTION_MAP.get(binding_id) else {
+        warn!(
+            "No action defined in ACTION_MAP for binding ID '{}'",
+            binding_id
+        );
+        return false;
+    };
+    let toggle_state_manager = ah.state::<ManagedToggleState>();
+    // Signals and D-Bus calls must not bring the app down over a poisoned lock
+    let Ok(mut states) = toggle_state_manager.lock() else {
+        warn!("Failed to lock toggle state manager for '{}'", binding_id);
+        return false;
+    };
+    let is_currently_active = states
+        .active_toggles
+        .entry(binding_id.to_string())
+        .or_insert(false);
+
+    if *is_currently_active {
+        action.stop(ah, binding_id, source);
+        *is_currently_active = false;
+    } else {
+        action.start(ah, binding_id, source);
+        // A refused or failed start, e.g. in a password field, leaves the binding inactive
+        *is_currently_active = ah.state::<Arc<AudioRecordingManager>>().is_recording();
+    }
+    *is_currently_active
+}
+
+/// Runs the action of `binding_id` for a press or release of one of its triggers, whether a
+/// keyboard shortcut or a mouse/gamepad button.
+pub fn handle_trigger(
+    ah: &AppHandle,
+    binding_id: &str,
+    role: TriggerRole,
+    shortcut_string: &str,
+    state: ShortcutState,
+) {
+    let Some(action) = ACTION_MAP.get(binding_id) else {
+        warn!(
+            "No action defined in ACTION_MAP for shortcut ID '{}'. Shortcut: '{}', State: {:?}",
+            binding_id, shortcut_string, state
+        );
+        return;
+    };
+
+    if binding_id == "cancel" {
+        let audio_manager = ah.state::<Arc<AudioRecordingManager>>();
+        if audio_manager.is_recording() && state == ShortcutState::Pressed {
+            action.start(ah, binding_id, shortcut_string);
+        }
+        return;
+    }
+
+    // The dedicated bindings keep their mode whatever the push-to-talk setting
+    let push_to_talk = match binding_id {
+        "push_to_talk" => true,
+        "toggle_recording" => false,
+        _ => get_settings(ah).push_to_talk,
+    };
+    if role == TriggerRole::Binding && push_to_talk {
+        if state == ShortcutState::Pressed {
+            if !allowed_in_focused_app(ah) {
+                return;
+            }
+            action.start(ah, binding_id, shortcut_string);
+        } else if state == ShortcutState::Released {
+            action.stop(ah, binding_id, shortcut_string);
+        }
+        return;
+    }
+
+    if state != ShortcutState::Pressed {
+        return;
+    }
+
+    let toggle_state_manager = ah.state::<ManagedToggleState>();
+    let mut states = toggle_state_manager
+        .lock()
+        .expect("Failed to lock toggle state manager");
+
+    let is_currently_active = states
+        .active_toggles
+        .entry(binding_id.to_string())
+        .or_insert(false);
+
+    // A start trigger never stops and a stop trigger never starts, so pressing either twice
+    // is harmless
+    let should_stop = match role {
+        TriggerRole::Binding => *is_currently_active,
+        TriggerRole::Start => false,
+        TriggerRole::Stop => true,
+    };
+
+    if should_stop && *is_currently_active {
+        action.stop(ah, binding_id, shortcut_string);
+        *is_currently_active = false; // Update state to inactive
+    } else if !should_stop && !*is_currently_active {
+        if !allowed_in_focused_app(ah) {
+            return;
+        }
+        action.start(ah, binding_id, shortcut_string);
+        *is_currently_active = true; // Update state to active
+    }
+}
+
+/// Whether the trigger app scope lets a trigger start in the focused application. When the
+/// focused application can't be told, triggers work as if no scope was set.
+fn allowed_in_focused_app(ah: &AppHandle) -> bool {
+    let settings = get_settings(ah);
+    if settings.trigger_app_scope == TriggerAppScope::Everywhere {
+        return true;
+    }
+    let focused = focused_app::focused_app();
+    let allowed = allowed_in_app(
+        settings.trigger_app_scope,
+        &settings.trigger_apps,
+        focused.as_deref(),
+    );
+    if !allowed {
+        debug!(
+            "Ignoring the trigger in {}",
+            focused.as_deref().unwrap_or_default()
+        );
+    }
+    allowed
+}
+
+fn allowed_in_app(scope: TriggerAppScope, apps: &[String], focused: Option<&str>) -> bool {
+    let Some(focused) = focused else {
+        return true;
+    };
+    let focused = focused.to_lowercase();
+    let listed = apps.iter().any(|app| {
+        let app = app.trim().to_lowercase();
+        !app.is_empty() && focused.contains(&app)
+    });
+    match scope {
+        TriggerAppScope::Everywhere => true,
+        TriggerAppScope::OnlyIn => listed,
+        TriggerAppScope::ExceptIn => !listed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allowed_in_app() {
+        let apps = vec![
+            "Remote Desktop".to_string(),
+            "steam".to_string(),
+            " ".to_string(),
+        ];
+        let except = |focused| allowed_in_app(TriggerAppScope::ExceptIn, &apps, focused);
+        assert!(!except(Some("Microsoft Remote Desktop")));
+        assert!(!except(Some("Steam")));
+        assert!(except(Some("Mail")));
+        assert!(except(None));
+
+        let only = |focused| allowed_in_app(TriggerAppScope::OnlyIn, &apps, focused);
+        assert!(only(Some("steam_app_570")));
+        assert!(!only(Some("Mail")));
+        assert!(only(None));
+    }
+
+    fn binding(id: &str, current: &str, stop: Option<&str>) -> (String, ShortcutBinding) {
+        (
+            id.to_string(),
+            ShortcutBinding {
+                id: id.to_string(),
+                name: id.to_string(),
+                description: String::new(),
+                default_binding: String::new(),
+                current_binding: current.to_string(),
+                stop_binding: stop.map(str::to_string),
+            },
+        )
+    }
+
+    #[test]
+    fn test_same_trigger_ignores_spelling() {
+        assert!(same_trigger("ctrl+space", "Control+Space"));
+        assert!(same_trigger("shift+alt+h", "alt+shift+h"));
+        assert!(!same_trigger("ctrl+space", "ctrl+shift+space"));
+        assert!(same_trigger("mouse:middle", "Mouse:Middle"));
+        assert!(!same_trigger("mouse:4", "ctrl+space"));
+    }
+
+    #[test]
+    fn test_find_conflict_checks_other_bindings() {
+        let bindings: HashMap<_, _> = [
+            binding("transcribe", "ctrl+space", Some("ctrl+shift+space")),
+            binding("cancel", "escape", None),
+            binding("open_history", "", None),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            find_conflict(&bindings, "push_to_talk", "control+space").map(|b| b.id.as_str()),
+            Some("transcribe")
+        );
+        assert_eq!(
+            find_conflict(&bindings, "push_to_talk", "ctrl+shift+space").map(|b| b.id.as_str()),
+            Some("transcribe")
+        );
+        assert_eq!(
+            find_conflict(&bindings, "push_to_talk", "Escape").map(|b| b.id.as_str()),
+            Some("cancel")
+        );
+        // a binding doesn't conflict with itself, and unassigned bindings never do
+        assert!(find_conflict(&bindings, "transcribe", "ctrl+space").is_none());
+        assert!(find_conflict(&bindings, "push_to_talk", "alt+h").is_none());
+    }
+}
@@ -0,0 +1,182 @@
+//! Snippet expansion: spoken trigger phrases like "insert signature" are replaced with the
+//! user's templates. Runs last in the post-processing chain, so triggers are matched in the
+//! text that would otherwise be pasted.
+
+use crate::settings::{AppSettings, Snippet};
+use chrono::Local;
+use log::debug;
+use tauri::AppHandle;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+/// Expands the snippets of `settings` in `text`, filling in template variables when used.
+pub fn expand(app: &AppHandle, settings: &AppSettings, text: &str) -> String {
+    if settings.snippets.is_empty() {
+        return text.to_string();
+    }
+
+    let expanded = expand_snippets(text, &settings.snippets, &|name: &str| match name {
+        "date" => Some(Local::now().format("%Y-%m-%d").to_string()),
+        "time" => Some(Local::now().format("%H:%M").to_string()),
+        "clipboard" => app.clipboard().read_text().ok(),
+        _ => None,
+    });
+    if expanded != text {
+        debug!("Expanded snippets: '{}' -> '{}'", text, expanded);
+    }
+    expanded
+}
+
+/// A word of the text, lowercased without punctuation, and where it sits in the text.
+struct Word {
+    normalized: String,
+    start: usize,
+    /// End of the word without trailing punctuation
+    end: usize,
+}
+
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric() || *c == '\'')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn words(text: &str) -> Vec<Word> {
+    let mut words = Vec::new();
+    let mut offset = 0;
+    for token in text.split_whitespace() {
+        let start = offset + text[offset..].find(token).unwrap_or(0);
+        offset = start + token.len();
+
+        let trimmed = token.trim_end_matches(|c: char| !c.is_alphanumeric());
+        let normalized = normalize(token);
+        if normalized.is_empty() {
+            continue;
+        }
+        words.push(Word {
+            normalized,
+            start,
+            end: start + trimmed.len(),
+        });
+    }
+    words
+}
+
+/// Replaces each trigger phrase in `text` with its snippet's template. Triggers match whole
+/// words, ignoring case and punctuation, and longer triggers win over shorter ones they
+/// contain. A dictation that is nothing but a trigger becomes just the template.
+///
+/// `${name}` variables in templates are filled in by `variable`, unknown ones are kept as is.
+pub fn expand_snippets(
+    text: &str,
+    snippets: &[Snippet],
+    variable: &dyn Fn(&str) -> Option<String>,
+) -> String {
+    let mut triggers: Vec<(Vec<String>, &Snippet)> = snippets
+        .iter()
+        .map(|snippet| {
+            let trigger = snippet
+                .trigger
+                .split_whitespace()
+                .map(normalize)
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<_>>();
+            (trigger, snippet)
+        })
+        .filter(|(trigger, _)| !trigger.is_empty())
+        .collect();
+    triggers.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+
+    let words = words(text);
+    let mut expanded = String::with_capacity(text.len());
+    let mut copied_to = 0;
+    let mut i = 0;
+    while i < words.len() {
+        let matched = triggers.iter().find(|(trigger, _)| {
+            words.len() - i >= trigger.len()
+                && trigger
+                    .iter()
+                    .zip(&words[i..])
+                    .all(|(expected, word)| *expected == word.normalized)
+        });
+        let Some((trigger, snippet)) = matched else {
+            i += 1;
+            continue;
+        };
+
+        let first = &words[i];
+        let last = &words[i + trigger.len() - 1];
+        let whole_text = i == 0 && i + trigger.len() == words.len();
+        let (start, end) = if whole_text {
+            (0, text.len())
+        } else {
+            (first.start, last.end)
+        };
+
+        expanded.push_str(&text[copied_to..start]);
+        expanded.push_str(&fill_template(&snippet.template, variable));
+        copied_to = end;
+        i += trigger.len();
+    }
+    expanded.push_str(&text[copied_to..]);
+    expanded
+}
+
+fn fill_template(template: &str, variable: &dyn Fn(&str) -> Option<String>) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find("${") {
+        let Some(close) = rest[open..].find('}') else {
+            break;
+        };
+        let name = &rest[open + 2..open + close];
+        filled.push_str(&rest[..open]);
+        match variable(name) {
+            Some(value) => filled.push_str(&value),
+            None => filled.push_str(&rest[open..open + close + 1]),
+        }
+        rest = &rest[open + close + 1..];
+    }
+    filled.push_str(rest);
+    filled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippet(trigger: &str, template: &str) -> Snippet {
+        Snippet {
+            id: trigger.to_string(),
+            trigger: trigger.to_string(),
+            template: template.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_expand_snippets() {
+        let snippets = vec![
+            snippet("insert signature", "Best,\nJane\n${date}"),
+            snippet("my address", "1 Main St"),
+            snippet("my work address", "2 Office Rd"),
+        ];
+        let variable = |name: &str| (name == "date").then(|| "2024-05-01".to_string());
+
+        assert_eq!(
+            expand_snippets("Insert signature.", &snippets, &variable),
+            "Best,\nJane\n2024-05-01"
+        );
+        assert_eq!(
+            expand_snippets("Send it to my work address, please", &snippets, &variable),
+            "Send it to 2 Office Rd, please"
+        );
+        assert_eq!(
+            expand_snippets("My address is ${unknown}", &snippets, &variable),
+            "1 Main St is ${unknown}"
+        );
+        assert_eq!(
+            expand_snippets("no triggers here", &snippets, &variable),
+            "no triggers here"
+        );
+    }
+}
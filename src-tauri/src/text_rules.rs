@@ -0,0 +1,112 @@
+//! User-defined regex find/replace rules, applied in order to the final transcript, e.g. to
+//! strip "um" or turn "gonna" into "going to".
+
+use crate::settings::RegexRule;
+use log::warn;
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use specta::Type;
+
+/// Compiled patterns are capped so a careless rule can't use unbounded memory
+const MAX_REGEX_SIZE: usize = 1 << 20;
+
+/// The text after one rule ran.
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct RuleStep {
+    pub rule_id: String,
+    pub matches: usize,
+    pub text: String,
+}
+
+/// Before and after of a dry run, with the text after each enabled rule.
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct RulePreview {
+    pub before: String,
+    pub after: String,
+    pub steps: Vec<RuleStep>,
+}
+
+pub fn compile(rule: &RegexRule) -> Result<Regex, String> {
+    RegexBuilder::new(&rule.pattern)
+        .case_insensitive(rule.case_insensitive)
+        .size_limit(MAX_REGEX_SIZE)
+        .build()
+        .map_err(|e| format!("Invalid pattern in rule '{}': {}", rule.name, e))
+}
+
+/// Runs the enabled rules over `text` in order and records each step. Rules that don't
+/// compile fail the preview.
+pub fn preview(text: &str, rules: &[RegexRule]) -> Result<RulePreview, String> {
+    let mut current = text.to_string();
+    let mut steps = Vec::new();
+    for rule in rules.iter().filter(|rule| rule.enabled) {
+        let regex = compile(rule)?;
+        let matches = regex.find_iter(&current).count();
+        current = regex
+            .replace_all(&current, rule.replacement.as_str())
+            .into_owned();
+        steps.push(RuleStep {
+            rule_id: rule.id.clone(),
+            matches,
+            text: current.clone(),
+        });
+    }
+
+    Ok(RulePreview {
+        before: text.to_string(),
+        after: current,
+        steps,
+    })
+}
+
+/// Runs the enabled rules over `text` in order. Rules that don't compile are skipped.
+pub fn apply(text: &str, rules: &[RegexRule]) -> String {
+    let mut current = text.to_string();
+    for rule in rules.iter().filter(|rule| rule.enabled) {
+        match compile(rule) {
+            Ok(regex) => {
+                current = regex
+                    .replace_all(&current, rule.replacement.as_str())
+                    .into_owned()
+            }
+            Err(e) => warn!("Skipping regex rule: {}", e),
+        }
+    }
+    current
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(id: &str, pattern: &str, replacement: &str) -> RegexRule {
+        RegexRule {
+            id: id.to_string(),
+            name: id.to_string(),
+            pattern: pattern.to_string(),
+            replacement: replacement.to_string(),
+            enabled: true,
+            case_insensitive: true,
+            languages: Vec::new(),
+            profiles: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_rules_run_in_order() {
+        let mut rules = vec![
+            rule("fillers", r"\b(um|uh),?\s*", ""),
+            rule("gonna", r"\bgonna\b", "going to"),
+            rule("disabled", r"going", "went"),
+        ];
+        rules[2].enabled = false;
+
+        let result = preview("Um, I'm gonna uh check", &rules).unwrap();
+        assert_eq!(result.after, "I'm going to check");
+        assert_eq!(result.steps.len(), 2);
+        assert_eq!(result.steps[0].matches, 2);
+        assert_eq!(apply("Um, I'm gonna uh check", &rules), result.after);
+
+        assert!(preview("text", &[rule("bad", "(", "")]).is_err());
+    }
+}
@@ -0,0 +1,201 @@
+//! Errors of the transcription pipeline and the `transcription-error` event. Each error has
+//! a stable code so the UI can show an actionable message ("download the model", "add an
+//! API key") instead of the raw error text.
+
+use log::{debug, warn};
+use serde::Serialize;
+use specta::Type;
+use std::fmt;
+use tauri::{AppHandle, Emitter};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Type)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum TranscriptionError {
+    ModelNotFound {
+        model_id: String,
+    },
+    ModelNotDownloaded {
+        model_id: String,
+    },
+    /// No model is loaded and loading one failed or wasn't attempted
+    ModelNotLoaded,
+    ModelLoadFailed {
+        model_id: String,
+        message: String,
+    },
+    /// The model failed to load and some of its files are missing or damaged, which
+    /// `repair_model` downloads again
+    ModelCorrupt {
+        model_id: String,
+        files: Vec<String>,
+    },
+    /// The user cancelled the model load
+    LoadCancelled {
+        model_id: String,
+    },
+    /// Loading the model would leave the system short of memory, so it wasn't loaded
+    InsufficientMemory {
+        model_id: String,
+        required_mb: u64,
+        available_mb: u64,
+    },
+    /// The local engine failed on the audio
+    EngineFailure {
+        message: String,
+    },
+    /// The audio couldn't be decoded or isn't usable
+    AudioFormatError {
+        message: String,
+    },
+    MissingApiKey {
+        provider: String,
+    },
+    CloudFailure {
+        provider: String,
+        message: String,
+    },
+    /// The local engine didn't finish in time and is being reloaded, trying again works
+    Timeout {
+        secs: u64,
+    },
+}
+
+impl TranscriptionError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::ModelNotFound { .. } => "model_not_found",
+            Self::ModelNotDownloaded { .. } => "model_not_downloaded",
+            Self::ModelNotLoaded => "model_not_loaded",
+            Self::ModelLoadFailed { .. } => "model_load_failed",
+            Self::ModelCorrupt { .. } => "model_corrupt",
+            Self::InsufficientMemory { .. } => "insufficient_memory",
+            Self::LoadCancelled { .. } => "load_cancelled",
+            Self::EngineFailure { .. } => "engine_failure",
+            Self::AudioFormatError { .. } => "audio_format_error",
+            Self::MissingApiKey { .. } => "missing_api_key",
+            Self::CloudFailure { .. } => "cloud_failure",
+            Self::Timeout { .. } => "timeout",
+        }
+    }
+
+    pub(crate) fn engine(e: impl fmt::Display) -> Self {
+        Self::EngineFailure {
+            message: e.to_string(),
+        }
+    }
+
+    pub(crate) fn audio_format(e: impl fmt::Display) -> Self {
+        Self::AudioFormatError {
+            message: e.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for TranscriptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ModelNotFound { model_id } => write!(f, "Model not found: {}", model_id),
+            Self::ModelNotDownloaded { model_id } => {
+                write!(f, "Model not downloaded: {}", model_id)
+            }
+            Self::ModelNotLoaded => write!(f, "Model is not loaded for transcription."),
+            Self::ModelLoadFailed { model_id, message } => {
+                write!(f, "Failed to load model {}: {}", model_id, message)
+            }
+            Self::ModelCorrupt { model_id, files } => write!(
+                f,
+                "Model {} has missing or damaged files: {}",
+                model_id,
+                files.join(", ")
+            ),
+            Self::LoadCancelled { model_id } => {
+                write!(f, "Loading model {} was cancelled", model_id)
+            }
+            Self::InsufficientMemory {
+                model_id,
+                required_mb,
+                available_mb,
+            } => write!(
+                f,
+                "Model {} needs about {} MB of memory, only {} MB is available",
+                model_id, required_mb, available_mb
+            ),
+            Self::EngineFailure { message } => write!(f, "Transcription failed: {}", message),
+            Self::AudioFormatError { message } => write!(f, "Unsupported audio: {}", message),
+            Self::MissingApiKey { provider } => {
+                write!(f, "No API key configured for {}", provider)
+            }
+            Self::CloudFailure { provider, message } => {
+                write!(f, "{} transcription failed: {}", provider, message)
+            }
+            Self::Timeout { secs } => write!(
+                f,
+                "Transcription took longer than {}s, the model is being reloaded",
+                secs
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TranscriptionError {}
+
+/// Payload of `transcription-error`.
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct TranscriptionErrorEvent {
+    /// What failed: "dictation", "model_load" or "watchdog"
+    pub source: String,
+    pub error: TranscriptionError,
+    /// The error as text, for logs and as a fallback in the UI
+    pub message: String,
+}
+
+pub fn emit(app: &AppHandle, source: &str, error: &TranscriptionError) {
+    debug!(
+        "Transcription error ({}) in {}: {}",
+        error.code(),
+        source,
+        error
+    );
+    let event = TranscriptionErrorEvent {
+        source: source.to_string(),
+        error: error.clone(),
+        message: error.to_string(),
+    };
+    if let Err(e) = app.emit("transcription-error", event) {
+        warn!("Failed to emit transcription-error event: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialized_code_matches() {
+        let errors = [
+            TranscriptionError::ModelNotDownloaded {
+                model_id: "small".to_string(),
+            },
+            TranscriptionError::ModelNotLoaded,
+            TranscriptionError::ModelCorrupt {
+                model_id: "parakeet-tdt-0.6b-v3".to_string(),
+                files: vec!["vocab.txt".to_string()],
+            },
+            TranscriptionError::engine("out of memory"),
+            TranscriptionError::Timeout { secs: 60 },
+        ];
+        for error in errors {
+            let json = serde_json::to_value(&error).unwrap();
+            assert_eq!(json["code"], error.code());
+        }
+
+        let json = serde_json::to_value(TranscriptionError::MissingApiKey {
+            provider: "openai".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "code": "missing_api_key", "provider": "openai" })
+        );
+    }
+}
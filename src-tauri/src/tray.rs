@@ -1,8 +1,68 @@
-use crate::settings;
+use crate::managers::history::HistoryManager;
+use crate::managers::model::ModelManager;
+use crate::managers::transcription::{ModelStateEvent, TranscriptionManager};
+use crate::settings::{self, AppSettings};
+use crate::shortcut;
+use chrono::{DateTime, Local};
+use log::error;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 use tauri::image::Image;
-use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
 use tauri::tray::TrayIcon;
-use tauri::{AppHandle, Manager, Theme};
+use tauri::{AppHandle, Emitter, Listener, Manager, Theme};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+/// Transcripts listed under "Recent"
+const RECENT_LIMIT: usize = 5;
+
+/// Longest label of a recent transcript, in characters
+const RECENT_LABEL_CHARS: usize = 40;
+
+/// Settings that can be switched on and off from the tray, by their settings key
+const QUICK_SETTINGS: &[(&str, &str)] = &[
+    ("push_to_talk", "Push to Talk"),
+    ("audio_feedback", "Audio Feedback"),
+    ("post_process_enabled", "Post-Processing"),
+    ("filler_removal", "Remove Filler Words"),
+];
+
+/// How long "Pause for 1 Hour" pauses the shortcuts
+const TIMED_PAUSE: Duration = Duration::from_secs(60 * 60);
+
+/// How often a timed pause checks whether it is over. The wall clock is compared, so time
+/// spent asleep counts.
+const PAUSE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Events after which the menu no longer matches the backend
+const REFRESH_EVENTS: &[&str] = &[
+    "history-updated",
+    "model-download-complete",
+    "incognito-changed",
+];
+
+/// The model's state as the last `model-state-changed` event told it
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ModelLoadState {
+    Unloaded,
+    Loading,
+    Loaded,
+    Failed,
+}
+
+impl ModelLoadState {
+    fn from_event(event_type: &str) -> Option<Self> {
+        match event_type {
+            "loading_started" => Some(Self::Loading),
+            "loading_completed" => Some(Self::Loaded),
+            "loading_failed" => Some(Self::Failed),
+            "unloaded" => Some(Self::Unloaded),
+            _ => None,
+        }
+    }
+}
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum TrayIconState {
@@ -75,66 +135,427 @@ pub fn change_tray_icon(app: &AppHandle, icon: TrayIconState) {
 }
 
 pub fn update_tray_menu(app: &AppHandle, state: &TrayIconState) {
-    let settings = settings::get_settings(app);
-
-    // Platform-specific accelerators
-    #[cfg(target_os = "macos")]
-    let (settings_accelerator, quit_accelerator) = (Some("Cmd+,"), Some("Cmd+Q"));
-    #[cfg(not(target_os = "macos"))]
-    let (settings_accelerator, quit_accelerator) = (Some("Ctrl+,"), Some("Ctrl+Q"));
-
-    // Create common menu items
-    let version_label = format!("Handy v{}", env!("CARGO_PKG_VERSION"));
-    let version_i = MenuItem::with_id(app, "version", &version_label, false, None::<&str>)
-        .expect("failed to create version item");
-    let settings_i = MenuItem::with_id(app, "settings", "Settings...", true, settings_accelerator)
-        .expect("failed to create settings item");
-    let check_updates_i = MenuItem::with_id(
-        app,
-        "check_updates",
-        "Check for Updates...",
-        settings.update_checks_enabled,
-        None::<&str>,
-    )
-    .expect("failed to create check updates item");
-    let quit_i = MenuItem::with_id(app, "quit", "Quit", true, quit_accelerator)
-        .expect("failed to create quit item");
-    let separator = || PredefinedMenuItem::separator(app).expect("failed to create separator");
-
-    let menu = match state {
-        TrayIconState::Recording | TrayIconState::Transcribing => {
-            let cancel_i = MenuItem::with_id(app, "cancel", "Cancel", true, None::<&str>)
-                .expect("failed to create cancel item");
-            Menu::with_items(
+    if let Some(tray_manager) = app.try_state::<Arc<TrayManager>>() {
+        tray_manager.set_state(state.clone());
+    }
+}
+
+/// Rebuilds the tray menu after a setting it shows was changed elsewhere.
+pub fn refresh_tray_menu(app: &AppHandle) {
+    if let Some(tray_manager) = app.try_state::<Arc<TrayManager>>() {
+        tray_manager.refresh();
+    }
+}
+
+fn quick_setting<'a>(settings: &'a mut AppSettings, key: &str) -> Option<&'a mut bool> {
+    match key {
+        "push_to_talk" => Some(&mut settings.push_to_talk),
+        "audio_feedback" => Some(&mut settings.audio_feedback),
+        "post_process_enabled" => Some(&mut settings.post_process_enabled),
+        "filler_removal" => Some(&mut settings.filler_removal),
+        _ => None,
+    }
+}
+
+/// First line of a transcript, shortened to fit a menu item.
+fn recent_label(text: &str) -> String {
+    let line = text.lines().map(str::trim).find(|line| !line.is_empty());
+    let line = line.unwrap_or("(empty)");
+    if line.chars().count() <= RECENT_LABEL_CHARS {
+        return line.to_string();
+    }
+    let short: String = line.chars().take(RECENT_LABEL_CHARS - 1).collect();
+    format!("{}…", short.trim_end())
+}
+
+/// Owns the tray menu. It is rebuilt from backend state whenever that state changes, so the
+/// model and its state, recording, recent transcripts, pause state and quick settings can be
+/// reached without opening the main window.
+pub struct TrayManager {
+    app_handle: AppHandle,
+    state: Mutex<TrayIconState>,
+    model_state: Mutex<ModelLoadState>,
+    paused: AtomicBool,
+    /// When a timed pause ends, `None` for a pause until resumed
+    resume_at: Mutex<Option<DateTime<Local>>>,
+    /// Transcripts currently listed under "Recent", by history id
+    recent: Mutex<Vec<(i64, String)>>,
+}
+
+impl TrayManager {
+    pub fn new(app_handle: &AppHandle) -> Self {
+        Self {
+            app_handle: app_handle.clone(),
+            state: Mutex::new(TrayIconState::Idle),
+            model_state: Mutex::new(ModelLoadState::Unloaded),
+            paused: AtomicBool::new(false),
+            resume_at: Mutex::new(None),
+            recent: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Rebuilds the menu whenever history or the loaded model changes.
+    pub fn listen(manager: &Arc<Self>) {
+        for event in REFRESH_EVENTS {
+            let manager_clone = manager.clone();
+            manager
+                .app_handle
+                .listen(*event, move |_| manager_clone.refresh());
+        }
+        let manager_clone = manager.clone();
+        manager
+            .app_handle
+            .listen("model-state-changed", move |event| {
+                let state = serde_json::from_str::<ModelStateEvent>(event.payload())
+                    .ok()
+                    .and_then(|event| ModelLoadState::from_event(&event.event_type));
+                if let Some(state) = state {
+                    *manager_clone.model_state.lock().unwrap() = state;
+                }
+                manager_clone.refresh();
+            });
+    }
+
+    pub fn set_state(&self, state: TrayIconState) {
+        *self.state.lock().unwrap() = state;
+        self.refresh();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Stops the shortcuts from starting dictation until resumed.
+    pub fn set_paused(&self, paused: bool) {
+        if !paused {
+            *self.resume_at.lock().unwrap() = None;
+        }
+        if self.paused.swap(paused, Ordering::Relaxed) == paused {
+            return;
+        }
+        crate::shortcut::set_shortcuts_paused(&self.app_handle, paused);
+        let _ = self.app_handle.emit("shortcuts-paused", paused);
+        self.refresh();
+    }
+
+    /// Pauses the shortcuts for `duration`, or until resumed when `None`. A pause made
+    /// meanwhile replaces this one.
+    pub fn pause(&self, duration: Option<Duration>) {
+        let until = duration
+            .and_then(|duration| chrono::Duration::from_std(duration).ok())
+            .map(|duration| Local::now() + duration);
+        *self.resume_at.lock().unwrap() = until;
+        if self.is_paused() {
+            self.refresh();
+        } else {
+            self.set_paused(true);
+        }
+
+        let Some(until) = until else {
+            return;
+        };
+        let app = self.app_handle.clone();
+        thread::spawn(move || loop {
+            thread::sleep(PAUSE_CHECK_INTERVAL);
+            let tray = app.state::<Arc<TrayManager>>();
+            let mut resume_at = tray.resume_at.lock().unwrap();
+            if *resume_at != Some(until) {
+                break;
+            }
+            if Local::now() >= until {
+                *resume_at = None;
+                drop(resume_at);
+                tray.set_paused(false);
+                break;
+            }
+        });
+    }
+
+    pub fn refresh(&self) {
+        match self.build_menu() {
+            Ok(menu) => {
+                let tray = self.app_handle.state::<TrayIcon>();
+                let _ = tray.set_menu(Some(menu));
+                let _ = tray.set_icon_as_template(true);
+            }
+            Err(e) => error!("Failed to build tray menu: {}", e),
+        }
+    }
+
+    pub fn handle_menu_event(&self, id: &str) {
+        let app = &self.app_handle;
+        match id {
+            "settings" => {
+                crate::show_main_window(app);
+            }
+            "check_updates" => {
+                let settings = settings::get_settings(app);
+                if settings.update_checks_enabled {
+                    crate::show_main_window(app);
+                    let _ = app.emit("check-for-updates", ());
+                }
+            }
+            "cancel" => {
+                use crate::utils::cancel_current_operation;
+
+                // Use centralized cancellation that handles all operations
+                cancel_current_operation(app);
+            }
+            "pause" => {
+                if self.is_paused() {
+                    self.set_paused(false);
+                } else {
+                    self.pause(None);
+                }
+            }
+            "pause_hour" => self.pause(Some(TIMED_PAUSE)),
+            "toggle_recording" => {
+                shortcut::toggle_binding(app, "transcribe", "tray");
+            }
+            "incognito" => {
+                let hm = app.state::<Arc<HistoryManager>>();
+                hm.set_incognito(!hm.is_incognito());
+            }
+            "quit" => {
+                app.exit(0);
+            }
+            _ => {
+                if let Some(model_id) = id.strip_prefix("model:") {
+                    self.select_model(model_id);
+                } else if let Some(entry_id) = id.strip_prefix("recent:") {
+                    self.copy_recent(entry_id);
+                } else if let Some(key) = id.strip_prefix("setting:") {
+                    self.toggle_setting(key);
+                }
+            }
+        }
+    }
+
+    /// Loads the model in the background and makes it the selected one once it's ready.
+    fn select_model(&self, model_id: &str) {
+        let app = self.app_handle.clone();
+        let model_id = model_id.to_string();
+        thread::spawn(move || {
+            let tm = app.state::<Arc<TranscriptionManager>>();
+            if let Err(e) = tm.load_model(&model_id) {
+                error!("Failed to load model {} from the tray: {}", model_id, e);
+            } else {
+                let mut settings = settings::get_settings(&app);
+                settings.selected_model = model_id;
+                settings::write_settings(&app, settings);
+            }
+            refresh_tray_menu(&app);
+        });
+    }
+
+    fn copy_recent(&self, entry_id: &str) {
+        let text = entry_id.parse::<i64>().ok().and_then(|entry_id| {
+            let recent = self.recent.lock().unwrap();
+            recent
+                .iter()
+                .find(|(id, _)| *id == entry_id)
+                .map(|(_, text)| text.clone())
+        });
+        if let Some(text) = text {
+            if let Err(e) = self.app_handle.clipboard().write_text(text) {
+                error!("Failed to copy transcript from the tray: {}", e);
+            }
+        }
+    }
+
+    fn toggle_setting(&self, key: &str) {
+        let mut settings = settings::get_settings(&self.app_handle);
+        let Some(value) = quick_setting(&mut settings, key) else {
+            return;
+        };
+        *value = !*value;
+        settings::write_settings(&self.app_handle, settings);
+        let _ = self.app_handle.emit("settings-changed", key);
+        self.refresh();
+    }
+
+    fn build_menu(&self) -> tauri::Result<Menu<tauri::Wry>> {
+        let app = &self.app_handle;
+        let settings = settings::get_settings(app);
+        let state = self.state.lock().unwrap().clone();
+
+        // Platform-specific accelerators
+        #[cfg(target_os = "macos")]
+        let (settings_accelerator, quit_accelerator) = (Some("Cmd+,"), Some("Cmd+Q"));
+        #[cfg(not(target_os = "macos"))]
+        let (settings_accelerator, quit_accelerator) = (Some("Ctrl+,"), Some("Ctrl+Q"));
+
+        let menu = Menu::new(app)?;
+        let version_label = format!("Handy v{}", env!("CARGO_PKG_VERSION"));
+        menu.append(&MenuItem::with_id(
+            app,
+            "version",
+            &version_label,
+            false,
+            None::<&str>,
+        )?)?;
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+
+        if state != TrayIconState::Idle {
+            menu.append(&MenuItem::with_id(
                 app,
-                &[
-                    &version_i,
-                    &separator(),
-                    &cancel_i,
-                    &separator(),
-                    &settings_i,
-                    &check_updates_i,
-                    &separator(),
-                    &quit_i,
-                ],
-            )
-            .expect("failed to create menu")
+                "cancel",
+                "Cancel",
+                true,
+                None::<&str>,
+            )?)?;
+            menu.append(&PredefinedMenuItem::separator(app)?)?;
         }
-        TrayIconState::Idle => Menu::with_items(
+
+        let recording_label = match state {
+            TrayIconState::Recording => "Stop Recording",
+            _ => "Start Recording",
+        };
+        menu.append(&MenuItem::with_id(
             app,
-            &[
-                &version_i,
-                &separator(),
-                &settings_i,
-                &check_updates_i,
-                &separator(),
-                &quit_i,
-            ],
-        )
-        .expect("failed to create menu"),
-    };
+            "toggle_recording",
+            recording_label,
+            state != TrayIconState::Transcribing,
+            None::<&str>,
+        )?)?;
+        menu.append(&self.model_menu(&settings)?)?;
+        menu.append(&self.recent_menu()?)?;
+        let pause_label = match *self.resume_at.lock().unwrap() {
+            Some(until) => format!("Paused Until {}", until.format("%H:%M")),
+            None => "Pause Shortcuts".to_string(),
+        };
+        menu.append(&CheckMenuItem::with_id(
+            app,
+            "pause",
+            pause_label,
+            true,
+            self.is_paused(),
+            None::<&str>,
+        )?)?;
+        menu.append(&MenuItem::with_id(
+            app,
+            "pause_hour",
+            "Pause for 1 Hour",
+            !self.is_paused(),
+            None::<&str>,
+        )?)?;
+        menu.append(&CheckMenuItem::with_id(
+            app,
+            "incognito",
+            "Incognito",
+            true,
+            app.state::<Arc<HistoryManager>>().is_incognito(),
+            None::<&str>,
+        )?)?;
+        menu.append(&self.quick_settings_menu(&settings)?)?;
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
 
-    let tray = app.state::<TrayIcon>();
-    let _ = tray.set_menu(Some(menu));
-    let _ = tray.set_icon_as_template(true);
+        menu.append(&MenuItem::with_id(
+            app,
+            "settings",
+            "Settings...",
+            true,
+            settings_accelerator,
+        )?)?;
+        menu.append(&MenuItem::with_id(
+            app,
+            "check_updates",
+            "Check for Updates...",
+            settings.update_checks_enabled,
+            None::<&str>,
+        )?)?;
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+        menu.append(&MenuItem::with_id(
+            app,
+            "quit",
+            "Quit",
+            true,
+            quit_accelerator,
+        )?)?;
+
+        Ok(menu)
+    }
+
+    /// The selected model as the title, the downloaded models to switch to inside.
+    fn model_menu(&self, settings: &AppSettings) -> tauri::Result<Submenu<tauri::Wry>> {
+        let app = &self.app_handle;
+        let models: Vec<_> = app
+            .state::<Arc<ModelManager>>()
+            .get_available_models()
+            .into_iter()
+            .filter(|model| model.is_downloaded)
+            .collect();
+
+        let selected = models
+            .iter()
+            .find(|model| model.id == settings.selected_model)
+            .map(|model| model.name.clone())
+            .unwrap_or_else(|| "None".to_string());
+        // The model is loaded on first use and unloaded when idle, the title says which
+        let loaded = app.state::<Arc<TranscriptionManager>>().get_current_model();
+        let title = match *self.model_state.lock().unwrap() {
+            ModelLoadState::Loading => format!("Model: {} (loading…)", selected),
+            ModelLoadState::Failed => format!("Model: {} (failed to load)", selected),
+            _ if loaded.as_deref() == Some(settings.selected_model.as_str()) => {
+                format!("Model: {}", selected)
+            }
+            _ => format!("Model: {} (not loaded)", selected),
+        };
+
+        let submenu = Submenu::with_id(app, "model", title, !models.is_empty())?;
+        for model in &models {
+            submenu.append(&CheckMenuItem::with_id(
+                app,
+                format!("model:{}", model.id),
+                &model.name,
+                true,
+                model.id == settings.selected_model,
+                None::<&str>,
+            )?)?;
+        }
+        Ok(submenu)
+    }
+
+    /// The latest transcripts, clicking one copies it to the clipboard.
+    fn recent_menu(&self) -> tauri::Result<Submenu<tauri::Wry>> {
+        let app = &self.app_handle;
+        let recent = app
+            .state::<Arc<HistoryManager>>()
+            .get_recent_transcripts(RECENT_LIMIT)
+            .unwrap_or_else(|e| {
+                error!("Failed to read recent transcripts for the tray: {}", e);
+                Vec::new()
+            });
+
+        let submenu = Submenu::with_id(app, "recent", "Copy Recent", !recent.is_empty())?;
+        for (id, text) in &recent {
+            submenu.append(&MenuItem::with_id(
+                app,
+                format!("recent:{}", id),
+                recent_label(text),
+                true,
+                None::<&str>,
+            )?)?;
+        }
+        *self.recent.lock().unwrap() = recent;
+        Ok(submenu)
+    }
+
+    fn quick_settings_menu(&self, settings: &AppSettings) -> tauri::Result<Submenu<tauri::Wry>> {
+        let app = &self.app_handle;
+        let mut settings = settings.clone();
+        let submenu = Submenu::with_id(app, "quick_settings", "Quick Settings", true)?;
+        for (key, label) in QUICK_SETTINGS {
+            let checked = quick_setting(&mut settings, key).is_some_and(|value| *value);
+            submenu.append(&CheckMenuItem::with_id(
+                app,
+                format!("setting:{}", key),
+                *label,
+                true,
+                checked,
+                None::<&str>,
+            )?)?;
+        }
+        Ok(submenu)
+    }
 }
@@ -0,0 +1,119 @@
+//! Speech synthesis with Piper voices. Voices are downloaded through the model manager like
+//! transcription models, and spoken by the `piper` program, which reads the text on stdin
+//! and writes a WAV file that is then played on the selected output device.
+
+use crate::audio_feedback;
+use crate::managers::model::ModelManager;
+use crate::settings::get_settings;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+
+/// Held while speaking, so a second readback waits for the first instead of talking over it
+static SPEAKING: Mutex<()> = Mutex::new(());
+
+/// Speaks `text` with the readback voice, returning once it has been played.
+pub fn speak(app: &AppHandle, text: &str) -> Result<(), String> {
+    let text = speakable(text);
+    if text.is_empty() {
+        return Ok(());
+    }
+    let settings = get_settings(app);
+    let model_manager = app.state::<Arc<ModelManager>>();
+    let voice = model_manager
+        .get_model_info(&settings.readback_voice)
+        .filter(|model| model.is_voice())
+        .ok_or_else(|| format!("Unknown voice: {}", settings.readback_voice))?;
+    if !voice.is_downloaded {
+        return Err(format!("The voice {} isn't downloaded", voice.name));
+    }
+    let voice_dir = model_manager
+        .get_model_path(&voice.id)
+        .map_err(|e| e.to_string())?;
+    let piper = settings.piper_path.as_deref().unwrap_or("piper");
+
+    let _speaking = SPEAKING.lock().unwrap_or_else(|e| e.into_inner());
+    let output = std::env::temp_dir().join(format!("handy-readback-{}.wav", std::process::id()));
+    synthesize(piper, &voice_model(&voice_dir)?, &text, &output)?;
+    let played = audio_feedback::play_file_blocking(app, &output);
+    let _ = fs::remove_file(&output);
+    played
+}
+
+/// Writes `text` spoken with the voice `model` to `output` as a WAV file.
+fn synthesize(piper: &str, model: &Path, text: &str, output: &Path) -> Result<(), String> {
+    let mut child = Command::new(piper)
+        .arg("--model")
+        .arg(model)
+        .arg("--output_file")
+        .arg(output)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", piper, e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .map_err(|e| format!("Failed to write to {}: {}", piper, e))?;
+    }
+    let result = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to run {}: {}", piper, e))?;
+    if !result.status.success() {
+        return Err(format!(
+            "{} failed: {}",
+            piper,
+            String::from_utf8_lossy(&result.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// The voice's `.onnx` file in its directory. Its `.onnx.json` config sits next to it,
+/// where piper looks for it.
+fn voice_model(voice_dir: &Path) -> Result<PathBuf, String> {
+    fs::read_dir(voice_dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| path.extension().is_some_and(|ext| ext == "onnx"))
+        .ok_or_else(|| format!("No voice model in {}", voice_dir.display()))
+}
+
+/// piper takes every input line for an utterance of its own, so the text is put on one line
+/// to come out as a single recording.
+fn speakable(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_speakable_joins_lines() {
+        assert_eq!(
+            speakable("First line.\nSecond  line.\n"),
+            "First line. Second line."
+        );
+        assert_eq!(speakable(" \n "), "");
+    }
+
+    #[test]
+    fn test_voice_model_finds_onnx_file() {
+        let dir = std::env::temp_dir().join(format!("handy-voice-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("en_US-lessac-medium.onnx.json"), "{}").unwrap();
+        assert!(voice_model(&dir).is_err());
+        fs::write(dir.join("en_US-lessac-medium.onnx"), "").unwrap();
+        assert_eq!(
+            voice_model(&dir).unwrap(),
+            dir.join("en_US-lessac-medium.onnx")
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,9 +1,15 @@
+use crate::helpers::focused_app;
 use crate::managers::audio::AudioRecordingManager;
+use crate::managers::history::HistoryManager;
+use crate::managers::transcription::TranscriptionManager;
+use crate::session_hooks;
+use crate::session_journal::SessionJournal;
+use crate::settings::get_settings;
 use crate::shortcut;
 use crate::ManagedToggleState;
 use log::{info, warn};
 use std::sync::Arc;
-use tauri::{AppHandle, Manager};
+use tauri::{AppHandle, Emitter, Manager};
 
 // Re-export all utility modules for easy access
 // pub use crate::audio_feedback::*;
@@ -28,9 +34,15 @@ pub fn cancel_current_operation(app: &AppHandle) {
         warn!("Failed to lock toggle state manager during cancellation");
     }
 
-    // Cancel any ongoing recording
+    // Cancel any ongoing recording and close its session
     let audio_manager = app.state::<Arc<AudioRecordingManager>>();
+    let was_recording = audio_manager.is_recording();
     audio_manager.cancel_recording();
+    if was_recording {
+        app.state::<Arc<TranscriptionManager>>().end_session();
+        app.state::<SessionJournal>().cancel();
+    }
+    session_hooks::session_ended(app);
 
     // Update tray icon and hide overlay
     change_tray_icon(app, crate::tray::TrayIconState::Idle);
@@ -39,6 +51,129 @@ pub fn cancel_current_operation(app: &AppHandle) {
     info!("Operation cancellation completed - returned to idle state");
 }
 
+/// Cancels the active dictation session and discards it, e.g. when it was activated by
+/// accident. Unlike a plain cancel, this also invalidates any chunk or final transcription
+/// that is still queued or running so nothing from the session is shown, saved, or pasted.
+/// The engines keep no state between calls, so a call already running is left to finish
+/// and its result dropped.
+pub fn cancel_current_session(app: &AppHandle) {
+    info!("Cancelling current session...");
+
+    // Invalidate the session first so results racing with the cancellation are dropped
+    let transcription_manager = app.state::<Arc<TranscriptionManager>>();
+    transcription_manager.abort_session();
+    app.state::<SessionJournal>().cancel();
+
+    // Stop capture (and therefore chunk emission) and reset UI state
+    cancel_current_operation(app);
+    app.state::<Arc<AudioRecordingManager>>()
+        .discard_live_transcript();
+
+    if let Err(e) = app.emit("session-cancelled", ()) {
+        warn!("Failed to emit session-cancelled event: {}", e);
+    }
+    // The former name of the event, for listeners that predate the rename
+    if let Err(e) = app.emit("session-aborted", ()) {
+        warn!("Failed to emit session-aborted event: {}", e);
+    }
+}
+
+/// Pauses or resumes the active dictation session, e.g. to take a call midway. A paused
+/// session keeps its live transcript and its open transcription session, so the model
+/// stays loaded, and the dictation continues into the same transcript once resumed.
+pub fn set_session_paused(app: &AppHandle, paused: bool) -> Result<(), String> {
+    let audio_manager = app.state::<Arc<AudioRecordingManager>>();
+    if paused {
+        if !audio_manager.pause_recording() {
+            return Err("No recording to pause".to_string());
+        }
+    } else {
+        if !audio_manager.resume_recording() {
+            return Err("No paused recording to resume".to_string());
+        }
+        // The idle countdown starts over once the dictation goes on
+        app.state::<Arc<TranscriptionManager>>().keep_model_loaded();
+    }
+    set_overlay_paused(app, paused);
+    info!("Session {}", if paused { "paused" } else { "resumed" });
+    if let Err(e) = app.emit("session-paused", paused) {
+        warn!("Failed to emit session-paused event: {}", e);
+    }
+    Ok(())
+}
+
+/// Outputs a history entry again with the current paste settings, the latest one when `id`
+/// is `None`. For when the target app wasn't focused as the paste first fired.
+pub async fn repaste_history_entry(app: &AppHandle, id: Option<i64>) -> Result<(), String> {
+    let hm = Arc::clone(&app.state::<Arc<HistoryManager>>());
+    let entry = match id {
+        Some(id) => hm.get_entry_by_id(id).await,
+        None => hm.get_latest_entry().await,
+    }
+    .map_err(|e| e.to_string())?
+    .ok_or("No transcription to paste")?;
+    let text = entry
+        .post_processed_text
+        .unwrap_or(entry.transcription_text);
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let ah = app.clone();
+    app.run_on_main_thread(move || {
+        let _ = tx.send(paste(text, ah));
+    })
+    .map_err(|e| e.to_string())?;
+    rx.await
+        .unwrap_or_else(|_| Err("Paste did not run".to_string()))
+}
+
+/// Whether a password field has the focus, which dictation is kept out of. Emits
+/// `blocked-secure-field` with what was refused, "recording" or "paste".
+pub fn blocked_by_secure_field(app: &AppHandle, refused: &str) -> bool {
+    if !get_settings(app).block_secure_fields || !focused_app::is_secure_field() {
+        return false;
+    }
+    info!("Refused {} in a password field", refused);
+    if let Err(e) = app.emit("blocked-secure-field", refused) {
+        warn!("Failed to emit blocked-secure-field event: {}", e);
+    }
+    true
+}
+
+/// Tag given to history entries whose paste was undone
+const UNDONE_TAG: &str = "undone";
+
+/// Removes the most recent pasted dictation still on the undo stack from the focused app and
+/// tags its history entry, then emits `dictation-undone`.
+pub async fn undo_last_dictation(app: &AppHandle) -> Result<(), String> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let ah = app.clone();
+    app.run_on_main_thread(move || {
+        let _ = tx.send(undo_last_output(&ah));
+    })
+    .map_err(|e| e.to_string())?;
+    let history_id = rx
+        .await
+        .unwrap_or_else(|_| Err("Undo did not run".to_string()))?;
+
+    if let Some(id) = history_id {
+        let hm = Arc::clone(&app.state::<Arc<HistoryManager>>());
+        if let Ok(Some(entry)) = hm.get_entry_by_id(id).await {
+            let mut tags = entry.tags;
+            if !tags.iter().any(|tag| tag == UNDONE_TAG) {
+                tags.push(UNDONE_TAG.to_string());
+                if let Err(e) = hm.set_entry_tags(id, &tags).await {
+                    warn!("Failed to tag the undone history entry: {}", e);
+                }
+            }
+        }
+    }
+
+    if let Err(e) = app.emit("dictation-undone", history_id) {
+        warn!("Failed to emit dictation-undone event: {}", e);
+    }
+    Ok(())
+}
+
 /// Check if using the Wayland display server protocol
 #[cfg(target_os = "linux")]
 pub fn is_wayland() -> bool {
@@ -0,0 +1,176 @@
+//! The webhook sink: each finished dictation POSTed as JSON to a URL the user sets, e.g. an
+//! n8n or Zapier trigger. Delivery runs in the background and is retried on transient
+//! failures. With a secret set, the request carries an HMAC-SHA256 signature of
+//! `{timestamp}.{body}` so the receiver can check it came from here.
+
+use crate::helpers::focused_app;
+use crate::secrets;
+use crate::session_report::SessionReport;
+use crate::settings::{get_settings, AppSettings};
+use anyhow::{anyhow, Result};
+use hmac::{Hmac, Mac};
+use log::{debug, warn};
+use reqwest::StatusCode;
+use serde::Serialize;
+use sha2::Sha256;
+use specta::Type;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+pub const SIGNATURE_HEADER: &str = "X-Handy-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Handy-Timestamp";
+
+const MAX_ATTEMPTS: u32 = 4;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Body of the webhook request
+#[derive(Clone, Debug, Serialize, Type)]
+pub struct WebhookPayload {
+    /// Text as delivered, after post-processing
+    pub text: String,
+    pub raw_text: String,
+    pub duration_secs: f32,
+    /// Name of the active dictation profile
+    pub profile: Option<String>,
+    /// Application that had the focus when the text was delivered
+    pub app: Option<String>,
+    pub binding_id: String,
+    /// RFC 3339
+    pub finished_at: String,
+}
+
+impl WebhookPayload {
+    pub fn from_report(report: &SessionReport, settings: &AppSettings) -> Self {
+        let profile = settings.active_profile_id.as_ref().and_then(|id| {
+            settings
+                .profiles
+                .iter()
+                .find(|profile| &profile.id == id)
+                .map(|profile| profile.name.clone())
+        });
+        Self {
+            text: report.text.clone(),
+            raw_text: report.raw_text.clone(),
+            duration_secs: report.audio_duration_secs,
+            profile,
+            app: focused_app::focused_app(),
+            binding_id: report.binding_id.clone(),
+            finished_at: chrono::Local::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Sends the dictation in `report` to the webhook in the background, if one is enabled.
+/// A delivery that fails after its retries emits `webhook-failed` with the error.
+pub fn deliver(app: &AppHandle, report: &SessionReport) {
+    let settings = get_settings(app);
+    if !settings.webhook_enabled || settings.webhook_url.is_empty() {
+        return;
+    }
+    let payload = WebhookPayload::from_report(report, &settings);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let secret = secrets::get_webhook_secret();
+        match send(&settings.webhook_url, secret.as_deref(), &payload).await {
+            Ok(()) => debug!("Delivered the dictation to the webhook"),
+            Err(e) => {
+                warn!("Failed to deliver the dictation to the webhook: {}", e);
+                let _ = app.emit("webhook-failed", e.to_string());
+            }
+        }
+    });
+}
+
+/// POSTs `payload` to `url`, signed when a secret is given. Timeouts, connection errors,
+/// rate limits and server errors are retried with exponential backoff, other failures are
+/// returned at once.
+pub async fn send(url: &str, secret: Option<&str>, payload: &WebhookPayload) -> Result<()> {
+    let body = serde_json::to_vec(payload)?;
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()?;
+
+    let mut attempt = 1;
+    loop {
+        // Signed again on every attempt, so receivers can reject stale timestamps
+        let timestamp = chrono::Utc::now().timestamp().to_string();
+        let mut request = client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(TIMESTAMP_HEADER, &timestamp)
+            .body(body.clone());
+        if let Some(secret) = secret {
+            request = request.header(SIGNATURE_HEADER, sign(secret, &timestamp, &body));
+        }
+
+        let (error, retryable) = match request.send().await {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => {
+                let status = response.status();
+                (
+                    anyhow!("HTTP {}", status),
+                    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
+                )
+            }
+            Err(e) => {
+                let retryable = e.is_timeout() || e.is_connect();
+                (e.into(), retryable)
+            }
+        };
+
+        if !retryable || attempt >= MAX_ATTEMPTS {
+            return Err(error);
+        }
+
+        let delay = Duration::from_secs(2u64.pow(attempt - 1));
+        warn!(
+            "Webhook attempt {} failed ({}), retrying in {}s",
+            attempt,
+            error,
+            delay.as_secs()
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// The signature header's value, `sha256=` and the hex HMAC of `{timestamp}.{body}`.
+pub fn sign(secret: &str, timestamp: &str, body: &[u8]) -> String {
+    let mut message = Vec::with_capacity(timestamp.len() + 1 + body.len());
+    message.extend_from_slice(timestamp.as_bytes());
+    message.push(b'.');
+    message.extend_from_slice(body);
+    format!("sha256={}", hmac_hex(secret.as_bytes(), &message))
+}
+
+fn hmac_hex(key: &[u8], message: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(message);
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hmac_matches_rfc_4231() {
+        assert_eq!(
+            hmac_hex(b"Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_sign_covers_timestamp_and_body() {
+        let signature = sign("secret", "1700000000", br#"{"text":"hi"}"#);
+        assert!(signature.starts_with("sha256="));
+        assert_eq!(signature.len(), "sha256=".len() + 64);
+        assert_ne!(signature, sign("secret", "1700000001", br#"{"text":"hi"}"#));
+        assert_ne!(signature, sign("other", "1700000000", br#"{"text":"hi"}"#));
+    }
+}
@@ -21,6 +21,18 @@ async resetBinding(id: string) : Promise<Result<BindingResponse, string>> {
     else return { status: "error", error: e  as any };
 }
 },
+/**
+ * Sets or clears a separate stop trigger for a binding. With one set, the binding's main
+ * trigger only starts recording and this one only stops it, regardless of push-to-talk.
+ */
+async changeStopBinding(id: string, stopBinding: string | null) : Promise<Result<BindingResponse, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("change_stop_binding", { id, stopBinding }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
 async changePttSetting(enabled: boolean) : Promise<Result<null, string>> {
     try {
     return { status: "ok", data: await TAURI_INVOKE("change_ptt_setting", { enabled }) };
@@ -117,489 +129,1988 @@ async changePasteMethodSetting(method: string) : Promise<Result<null, string>> {
     else return { status: "error", error: e  as any };
 }
 },
-async changeClipboardHandlingSetting(handling: string) : Promise<Result<null, string>> {
-    try {
-    return { status: "ok", data: await TAURI_INVOKE("change_clipboard_handling_setting", { handling }) };
-} catch (e) {
-    if(e instanceof Error) throw e;
-    else return { status: "error", error: e  as any };
-}
-},
-async changePostProcessEnabledSetting(enabled: boolean) : Promise<Result<null, string>> {
+/**
+ * Sets where dictations go. Appending to Markdown notes needs an existing folder; `file`
+ * names the note to append to, the day's note being used when it is `None`.
+ */
+async setOutputTarget(target: OutputTarget, markdownFolder: string | null, markdownFile: string | null) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_post_process_enabled_setting", { enabled }) };
+    return { status: "ok", data: await TAURI_INVOKE("set_output_target", { target, markdownFolder, markdownFile }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changePostProcessBaseUrlSetting(providerId: string, baseUrl: string) : Promise<Result<null, string>> {
+/**
+ * Limits the applications the shortcuts and triggers can start a dictation in.
+ */
+async setTriggerAppScope(scope: TriggerAppScope, apps: string[]) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_post_process_base_url_setting", { providerId, baseUrl }) };
+    return { status: "ok", data: await TAURI_INVOKE("set_trigger_app_scope", { scope, apps }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changePostProcessApiKeySetting(providerId: string, apiKey: string) : Promise<Result<null, string>> {
+async changeClipboardHandlingSetting(handling: string) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_post_process_api_key_setting", { providerId, apiKey }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_clipboard_handling_setting", { handling }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changePostProcessModelSetting(providerId: string, model: string) : Promise<Result<null, string>> {
+/**
+ * Copies transcriptions longer than `max_chars` characters to the clipboard one part at a
+ * time, `None` copies them whole.
+ */
+async changeClipboardChunkSizeSetting(maxChars: string | null) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_post_process_model_setting", { providerId, model }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_clipboard_chunk_size_setting", { maxChars }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async setPostProcessProvider(providerId: string) : Promise<Result<null, string>> {
+async changeHumanizedTypingSettings(typing: HumanizedTypingSettings) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("set_post_process_provider", { providerId }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_humanized_typing_settings", { typing }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async fetchPostProcessModels(providerId: string) : Promise<Result<string[], string>> {
+async changeTextFormattingSetting(formatting: string) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("fetch_post_process_models", { providerId }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_text_formatting_setting", { formatting }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async addPostProcessPrompt(name: string, prompt: string) : Promise<Result<LLMPrompt, string>> {
+async changeApiServerEnabledSetting(enabled: boolean) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("add_post_process_prompt", { name, prompt }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_api_server_enabled_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async updatePostProcessPrompt(id: string, name: string, prompt: string) : Promise<Result<null, string>> {
+async changeApiServerPortSetting(port: number) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("update_post_process_prompt", { id, name, prompt }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_api_server_port_setting", { port }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async deletePostProcessPrompt(id: string) : Promise<Result<null, string>> {
+/**
+ * Enables the MCP endpoint, which runs on the API server's port.
+ */
+async changeMcpServerEnabledSetting(enabled: boolean) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("delete_post_process_prompt", { id }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_mcp_server_enabled_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async setPostProcessSelectedPrompt(id: string) : Promise<Result<null, string>> {
+async changePostProcessEnabledSetting(enabled: boolean) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("set_post_process_selected_prompt", { id }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_post_process_enabled_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async updateCustomWords(words: string[]) : Promise<Result<null, string>> {
+/**
+ * Lets post-processing see the focused app and window title. Off by default for privacy.
+ */
+async changePostProcessContextWindowSetting(enabled: boolean) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("update_custom_words", { words }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_post_process_context_window_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
 /**
- * Temporarily unregister a binding while the user is editing it in the UI.
- * This avoids firing the action while keys are being recorded.
+ * Lets post-processing see the text selected in the focused app. Off by default for privacy.
  */
-async suspendBinding(id: string) : Promise<Result<null, string>> {
+async changePostProcessContextSelectionSetting(enabled: boolean) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("suspend_binding", { id }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_post_process_context_selection_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-/**
- * Re-register the binding after the user has finished editing.
- */
-async resumeBinding(id: string) : Promise<Result<null, string>> {
+async changePostProcessBaseUrlSetting(providerId: string, baseUrl: string) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("resume_binding", { id }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_post_process_base_url_setting", { providerId, baseUrl }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changeMuteWhileRecordingSetting(enabled: boolean) : Promise<Result<null, string>> {
+async changePostProcessApiKeySetting(providerId: string, apiKey: string) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_mute_while_recording_setting", { enabled }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_post_process_api_key_setting", { providerId, apiKey }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changeAppendTrailingSpaceSetting(enabled: boolean) : Promise<Result<null, string>> {
+async changePostProcessModelSetting(providerId: string, model: string) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_append_trailing_space_setting", { enabled }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_post_process_model_setting", { providerId, model }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async changeUpdateChecksSetting(enabled: boolean) : Promise<Result<null, string>> {
+async setPostProcessProvider(providerId: string) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("change_update_checks_setting", { enabled }) };
+    return { status: "ok", data: await TAURI_INVOKE("set_post_process_provider", { providerId }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async triggerUpdateCheck() : Promise<Result<null, string>> {
+async fetchPostProcessModels(providerId: string) : Promise<Result<string[], string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("trigger_update_check") };
+    return { status: "ok", data: await TAURI_INVOKE("fetch_post_process_models", { providerId }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async cancelOperation() : Promise<void> {
-    await TAURI_INVOKE("cancel_operation");
-},
-async getAppDirPath() : Promise<Result<string, string>> {
+async addPostProcessPrompt(name: string, prompt: string) : Promise<Result<LLMPrompt, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_app_dir_path") };
+    return { status: "ok", data: await TAURI_INVOKE("add_post_process_prompt", { name, prompt }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getAppSettings() : Promise<Result<AppSettings, string>> {
+async updatePostProcessPrompt(id: string, name: string, prompt: string) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_app_settings") };
+    return { status: "ok", data: await TAURI_INVOKE("update_post_process_prompt", { id, name, prompt }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getDefaultSettings() : Promise<Result<AppSettings, string>> {
+async deletePostProcessPrompt(id: string) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_default_settings") };
+    return { status: "ok", data: await TAURI_INVOKE("delete_post_process_prompt", { id }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getLogDirPath() : Promise<Result<string, string>> {
+async addSnippet(trigger: string, template: string) : Promise<Result<Snippet, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_log_dir_path") };
+    return { status: "ok", data: await TAURI_INVOKE("add_snippet", { trigger, template }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async setLogLevel(level: LogLevel) : Promise<Result<null, string>> {
+async updateSnippet(id: string, trigger: string, template: string) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("set_log_level", { level }) };
+    return { status: "ok", data: await TAURI_INVOKE("update_snippet", { id, trigger, template }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async openRecordingsFolder() : Promise<Result<null, string>> {
+async deleteSnippet(id: string) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("open_recordings_folder") };
+    return { status: "ok", data: await TAURI_INVOKE("delete_snippet", { id }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async openLogDir() : Promise<Result<null, string>> {
+/**
+ * Replaces the regex rules, which run in the given order. Every pattern must compile.
+ */
+async setRegexRules(rules: RegexRule[]) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("open_log_dir") };
+    return { status: "ok", data: await TAURI_INVOKE("set_regex_rules", { rules }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async openAppDataDir() : Promise<Result<null, string>> {
+/**
+ * Replaces the text pipeline's stages. Each stage may appear once, stages left out run
+ * in their default place and state.
+ */
+async setTextPipeline(stages: PipelineStage[]) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("open_app_data_dir") };
+    return { status: "ok", data: await TAURI_INVOKE("set_text_pipeline", { stages }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getAvailableModels() : Promise<Result<ModelInfo[], string>> {
+/**
+ * Dry run of the regex rules on `sample`, showing the text after each rule. Previews the
+ * saved rules unless `rules` is given, so edits can be checked before saving them.
+ */
+async previewRegexRules(sample: string, rules: RegexRule[] | null) : Promise<Result<RulePreview, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_available_models") };
+    return { status: "ok", data: await TAURI_INVOKE("preview_regex_rules", { sample, rules }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getModelInfo(modelId: string) : Promise<Result<ModelInfo | null, string>> {
+async setPostProcessSelectedPrompt(id: string) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_model_info", { modelId }) };
+    return { status: "ok", data: await TAURI_INVOKE("set_post_process_selected_prompt", { id }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async downloadModel(modelId: string) : Promise<Result<null, string>> {
+async updateCustomWords(words: CustomWord[]) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("download_model", { modelId }) };
+    return { status: "ok", data: await TAURI_INVOKE("update_custom_words", { words }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async deleteModel(modelId: string) : Promise<Result<null, string>> {
+/**
+ * Names that are always written with the given capitalization, e.g. "New York".
+ */
+async updateProperNouns(names: string[]) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("delete_model", { modelId }) };
+    return { status: "ok", data: await TAURI_INVOKE("update_proper_nouns", { names }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async cancelDownload(modelId: string) : Promise<Result<null, string>> {
+/**
+ * Temporarily unregister a binding while the user is editing it in the UI.
+ * This avoids firing the action while keys are being recorded.
+ */
+async suspendBinding(id: string) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("cancel_download", { modelId }) };
+    return { status: "ok", data: await TAURI_INVOKE("suspend_binding", { id }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async setActiveModel(modelId: string) : Promise<Result<null, string>> {
+/**
+ * Re-register the binding after the user has finished editing.
+ */
+async resumeBinding(id: string) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("set_active_model", { modelId }) };
+    return { status: "ok", data: await TAURI_INVOKE("resume_binding", { id }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getCurrentModel() : Promise<Result<string, string>> {
+async changeMuteWhileRecordingSetting(enabled: boolean) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_current_model") };
+    return { status: "ok", data: await TAURI_INVOKE("change_mute_while_recording_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getTranscriptionModelStatus() : Promise<Result<string | null, string>> {
+async changeAppendTrailingSpaceSetting(enabled: boolean) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_transcription_model_status") };
+    return { status: "ok", data: await TAURI_INVOKE("change_append_trailing_space_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async isModelLoading() : Promise<Result<boolean, string>> {
+async changeFitOutputToFieldSetting(enabled: boolean) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("is_model_loading") };
+    return { status: "ok", data: await TAURI_INVOKE("change_fit_output_to_field_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async hasAnyModelsAvailable() : Promise<Result<boolean, string>> {
+async changeBlockSecureFieldsSetting(enabled: boolean) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("has_any_models_available") };
+    return { status: "ok", data: await TAURI_INVOKE("change_block_secure_fields_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async hasAnyModelsOrDownloads() : Promise<Result<boolean, string>> {
+async changeReadbackBeforePasteSetting(enabled: boolean) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("has_any_models_or_downloads") };
+    return { status: "ok", data: await TAURI_INVOKE("change_readback_before_paste_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getRecommendedFirstModel() : Promise<Result<string, string>> {
+async changeConfirmBeforePasteSetting(enabled: boolean) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_recommended_first_model") };
+    return { status: "ok", data: await TAURI_INVOKE("change_confirm_before_paste_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async updateMicrophoneMode(alwaysOn: boolean) : Promise<Result<null, string>> {
+async changeUpdateChecksSetting(enabled: boolean) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("update_microphone_mode", { alwaysOn }) };
+    return { status: "ok", data: await TAURI_INVOKE("change_update_checks_setting", { enabled }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getMicrophoneMode() : Promise<Result<boolean, string>> {
+async triggerUpdateCheck() : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_microphone_mode") };
+    return { status: "ok", data: await TAURI_INVOKE("trigger_update_check") };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getAvailableMicrophones() : Promise<Result<AudioDevice[], string>> {
+async cancelOperation() : Promise<void> {
+    await TAURI_INVOKE("cancel_operation");
+},
+/**
+ * Copies the next part of a long transcription that is being copied in parts. Returns `None`
+ * when no parts are left.
+ */
+async copyNextClipboardPart() : Promise<Result<ClipboardChunkProgress | null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_available_microphones") };
+    return { status: "ok", data: await TAURI_INVOKE("copy_next_clipboard_part") };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async setSelectedMicrophone(deviceName: string) : Promise<Result<null, string>> {
+/**
+ * The usage counters as they would be shared in diagnostics: content-free and bucketed.
+ */
+async getUsageCounters() : Promise<UsageSnapshot> {
+    return await TAURI_INVOKE("get_usage_counters");
+},
+async resetUsageCounters() : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("set_selected_microphone", { deviceName }) };
+    return { status: "ok", data: await TAURI_INVOKE("reset_usage_counters") };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getSelectedMicrophone() : Promise<Result<string, string>> {
+/**
+ * Writes a zip for bug reports to `path`: recent logs, redacted settings, the models with
+ * their hashes and the audio devices, with a loopback latency test when asked.
+ */
+async generateDiagnostics(path: string, loopbackTest: boolean) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_selected_microphone") };
+    return { status: "ok", data: await TAURI_INVOKE("generate_diagnostics", { path, loopbackTest }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getAvailableOutputDevices() : Promise<Result<AudioDevice[], string>> {
+async setUsageCountersEnabled(enabled: boolean) : Promise<void> {
+    await TAURI_INVOKE("set_usage_counters_enabled", { enabled });
+},
+/**
+ * Opt-in read-back of recognized text through the screen reader.
+ */
+async setScreenReaderAnnouncements(mode: ScreenReaderAnnouncements) : Promise<void> {
+    await TAURI_INVOKE("set_screen_reader_announcements", { mode });
+},
+/**
+ * Replaces the session hooks. Every hook needs a command and a timeout of at most
+ * `MAX_HOOK_TIMEOUT_SECS`.
+ */
+async setSessionHooks(hooks: SessionHook[]) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_available_output_devices") };
+    return { status: "ok", data: await TAURI_INVOKE("set_session_hooks", { hooks }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async setSelectedOutputDevice(deviceName: string) : Promise<Result<null, string>> {
+/**
+ * Sets the webhook each finished dictation is sent to. `secret` replaces the signing
+ * secret in the OS keychain when given, an empty one removes it.
+ */
+async setWebhook(enabled: boolean, url: string, secret: string | null) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("set_selected_output_device", { deviceName }) };
+    return { status: "ok", data: await TAURI_INVOKE("set_webhook", { enabled, url, secret }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getSelectedOutputDevice() : Promise<Result<string, string>> {
+/**
+ * Sends a sample dictation to the webhook, retrying like a real delivery.
+ */
+async testWebhook() : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_selected_output_device") };
+    return { status: "ok", data: await TAURI_INVOKE("test_webhook") };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async playTestSound(soundType: string) : Promise<void> {
-    await TAURI_INVOKE("play_test_sound", { soundType });
-},
-async checkCustomSounds() : Promise<CustomSounds> {
-    return await TAURI_INVOKE("check_custom_sounds");
-},
-async setClamshellMicrophone(deviceName: string) : Promise<Result<null, string>> {
+/**
+ * Replaces the dictation profiles. Each needs a name and an id of its own; when the active
+ * profile is removed the base settings are back until the next switch.
+ */
+async setProfiles(profiles: DictationProfile[]) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("set_clamshell_microphone", { deviceName }) };
+    return { status: "ok", data: await TAURI_INVOKE("set_profiles", { profiles }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getClamshellMicrophone() : Promise<Result<string, string>> {
+/**
+ * Switches to profile `id`, loading its model when another one is loaded.
+ */
+async switchProfile(id: string) : Promise<Result<DictationProfile, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_clamshell_microphone") };
+    return { status: "ok", data: await TAURI_INVOKE("switch_profile", { id }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async setModelUnloadTimeout(timeout: ModelUnloadTimeout) : Promise<void> {
-    await TAURI_INVOKE("set_model_unload_timeout", { timeout });
+/**
+ * Leaves the active profile, restoring the settings from before the first profile switch.
+ */
+async clearProfile() : Promise<void> {
+    await TAURI_INVOKE("clear_profile");
 },
-async getModelLoadStatus() : Promise<Result<ModelLoadStatus, string>> {
+/**
+ * Writes the settings to `path` as a JSON bundle for another machine. API keys, devices,
+ * shortcuts and local paths stay behind; custom words and regex rules are included on
+ * request.
+ */
+async exportSettings(path: string, includeCustomWords: boolean, includeRegexRules: boolean) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_model_load_status") };
+    return { status: "ok", data: await TAURI_INVOKE("export_settings", { path, includeCustomWords, includeRegexRules }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async unloadModelManually() : Promise<Result<null, string>> {
+/**
+ * The hooks, webhook, plugins, providers, servers, context sharing and other sensitive
+ * settings the bundle at `path` would change, for the user to confirm before importing it.
+ */
+async previewSettingsImport(path: string) : Promise<Result<SensitiveChange[], string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("unload_model_manually") };
+    return { status: "ok", data: await TAURI_INVOKE("preview_settings_import", { path }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getHistoryEntries() : Promise<Result<HistoryEntry[], string>> {
+/**
+ * Applies a bundle written by `export_settings`, upgrading it from the version that wrote
+ * it. Of the settings `preview_settings_import` lists, only the `confirmed` ones are taken
+ * over. Returns the resulting settings.
+ */
+async importSettings(path: string, confirmed: string[]) : Promise<Result<AppSettings, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_history_entries") };
+    return { status: "ok", data: await TAURI_INVOKE("import_settings", { path, confirmed }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async toggleHistoryEntrySaved(id: string) : Promise<Result<null, string>> {
+/**
+ * Writes a manifest of this setup to `path`: the models in use with where they come from,
+ * and the settings including profiles, custom words and regex rules. Machine-specific
+ * settings stay behind as with `export_settings`.
+ */
+async generateSetupManifest(path: string) : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("toggle_history_entry_saved", { id }) };
+    return { status: "ok", data: await TAURI_INVOKE("generate_setup_manifest", { path }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async getAudioFilePath(fileName: string) : Promise<Result<string, string>> {
+/**
+ * The sensitive settings the manifest at `path` would change, for the user to confirm
+ * before applying it, as with `preview_settings_import`.
+ */
+async previewManifest(path: string) : Promise<Result<SensitiveChange[], string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("get_audio_file_path", { fileName }) };
+    return { status: "ok", data: await TAURI_INVOKE("preview_manifest", { path }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async deleteHistoryEntry(id: string) : Promise<Result<null, string>> {
+/**
+ * Sets this machine up from a manifest written by `generate_setup_manifest`: downloads the
+ * models it lists that are missing, then applies its settings, the sensitive ones only
+ * when `confirmed`, and loads the selected model if another one is loaded. Fails without
+ * changing the settings when a model here, or one it downloads, isn't the version the
+ * manifest pins.
+ */
+async applyManifest(path: string, confirmed: string[]) : Promise<Result<ManifestApplied, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("delete_history_entry", { id }) };
+    return { status: "ok", data: await TAURI_INVOKE("apply_manifest", { path, confirmed }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async updateHistoryLimit(limit: string) : Promise<Result<null, string>> {
+/**
+ * Runs `hook` once, outside a session, and returns its output so it can be checked before
+ * it is saved.
+ */
+async testSessionHook(hook: SessionHook) : Promise<Result<HookOutput, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("update_history_limit", { limit }) };
+    return { status: "ok", data: await TAURI_INVOKE("test_session_hook", { hook }) };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
-async updateRecordingRetentionPeriod(period: string) : Promise<Result<null, string>> {
+async cancelSession() : Promise<void> {
+    await TAURI_INVOKE("cancel_session");
+},
+/**
+ * The former name of `cancel_session`, kept for existing callers.
+ */
+async abortSession() : Promise<void> {
+    await TAURI_INVOKE("abort_session");
+},
+/**
+ * Pauses the recording without finishing it, keeping its transcript so far.
+ */
+async pauseSession() : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("update_recording_retention_period", { period }) };
+    return { status: "ok", data: await TAURI_INVOKE("pause_session") };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
 },
 /**
- * Checks if the Mac is a laptop by detecting battery presence
- * 
- * This uses pmset to check for battery information.
- * Returns true if a battery is detected (laptop), false otherwise (desktop)
+ * Continues a paused recording into the same transcript.
  */
-async isLaptop() : Promise<Result<boolean, string>> {
+async resumeSession() : Promise<Result<null, string>> {
     try {
-    return { status: "ok", data: await TAURI_INVOKE("is_laptop") };
+    return { status: "ok", data: await TAURI_INVOKE("resume_session") };
 } catch (e) {
     if(e instanceof Error) throw e;
     else return { status: "error", error: e  as any };
 }
-}
-}
-
+},
+/**
+ * Starts dictation, or stops it when recording, like the transcribe shortcut in toggle
+ * mode. Returns whether it is recording afterwards.
+ */
+async toggleRecording() : Promise<boolean> {
+    return await TAURI_INVOKE("toggle_recording");
+},
+/**
+ * Starts a dictation for automation, failing when one is already in progress.
+ */
+async startDictation() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("start_dictation") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async stopDictation() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("stop_dictation") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Transcribes the WAV file at `path` and returns its text.
+ */
+async transcribeFileAtPath(path: string) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("transcribe_file_at_path", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Text of the latest dictation, `None` when the history is empty.
+ */
+async getLastTranscript() : Promise<Result<string | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_last_transcript") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Stops the shortcuts from starting dictation for `minutes`, or until resumed when `None`.
+ */
+async pauseShortcuts(minutes: number | null) : Promise<void> {
+    await TAURI_INVOKE("pause_shortcuts", { minutes });
+},
+async resumeShortcuts() : Promise<void> {
+    await TAURI_INVOKE("resume_shortcuts");
+},
+async getAppDirPath() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_app_dir_path") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAppSettings() : Promise<Result<AppSettings, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_app_settings") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getDefaultSettings() : Promise<Result<AppSettings, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_default_settings") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getLogDirPath() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_log_dir_path") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setLogLevel(level: LogLevel) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_log_level", { level }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Recent log lines for the in-app log viewer, oldest first. Poll with `after` set to the
+ * last id received to follow the log.
+ */
+async getLogs(query: LogQuery) : Promise<LogEntry[]> {
+    return await TAURI_INVOKE("get_logs", { query });
+},
+/**
+ * Empties the log viewer. The log file is left alone.
+ */
+async clearLogs() : Promise<void> {
+    await TAURI_INVOKE("clear_logs");
+},
+async openRecordingsFolder() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("open_recordings_folder") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async openLogDir() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("open_log_dir") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async openAppDataDir() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("open_app_data_dir") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAvailableModels() : Promise<Result<ModelInfo[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_available_models") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The text-to-speech voices transcriptions can be read back with.
+ */
+async getVoiceModels() : Promise<Result<ModelInfo[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_voice_models") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setReadbackVoice(voiceId: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_readback_voice", { voiceId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Sets the `piper` program voices are spoken with, `None` looks it up on the PATH.
+ */
+async setPiperPath(path: string | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_piper_path", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Sets the small model kept loaded for live partials while the selected one loads on
+ * demand, `None` keeps no standby model.
+ */
+async setStandbyModel(modelId: string | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_standby_model", { modelId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Sets the model finished recordings are transcribed again with, `None` leaves the final
+ * pass to the selected model.
+ */
+async setRescoreModel(modelId: string | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_rescore_model", { modelId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getModelInfo(modelId: string) : Promise<Result<ModelInfo | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_model_info", { modelId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Transcribes a WAV clip with each downloaded model, or with `model_ids` only, and reports
+ * speed, memory and, given the clip's `reference_text`, word error rate. Without an
+ * `audio_path` the most recent dictation whose audio was kept is used.
+ */
+async benchmarkModels(audioPath: string | null, referenceText: string | null, modelIds: string[] | null) : Promise<Result<ModelBenchmark[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("benchmark_models", { audioPath, referenceText, modelIds }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async downloadModel(modelId: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("download_model", { modelId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async deleteModel(modelId: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_model", { modelId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async cancelDownload(modelId: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("cancel_download", { modelId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Stores models in a machine-wide cache shared with other Handy-based apps and OS users.
+ * Takes effect the next time Handy starts.
+ */
+async setSharedModelCache(enabled: boolean) : Promise<void> {
+    await TAURI_INVOKE("set_shared_model_cache", { enabled });
+},
+/**
+ * Fetches the model manifest for models published since this version.
+ */
+async refreshModelCatalog() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("refresh_model_catalog") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Makes `model_id` the active model although the low memory guard blocked it, once the
+ * user confirmed they want it anyway.
+ */
+async setActiveModelIgnoringMemory(modelId: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_active_model_ignoring_memory", { modelId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Stops the model load in progress between its phases, leaving no model loaded. Returns
+ * whether a load was in progress.
+ */
+async cancelModelLoad() : Promise<boolean> {
+    return await TAURI_INVOKE("cancel_model_load");
+},
+async getMemoryStatus() : Promise<MemoryStatus> {
+    return await TAURI_INVOKE("get_memory_status");
+},
+async setLowMemoryGuard(guard: LowMemoryGuard) : Promise<void> {
+    await TAURI_INVOKE("set_low_memory_guard", { guard });
+},
+/**
+ * Takes effect from the next model load or transcription.
+ */
+async setInferenceTuning(threads: number | null, priority: InferencePriority, cores: InferenceCores) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_inference_tuning", { threads, priority, cores }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Checks a downloaded model's files, naming the missing and damaged ones.
+ */
+async verifyModel(modelId: string) : Promise<Result<IntegrityReport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("verify_model", { modelId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Downloads a model's missing and damaged files again. Returns the files repaired.
+ */
+async repairModel(modelId: string) : Promise<Result<string[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("repair_model", { modelId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Searches the Hugging Face Hub for Parakeet ONNX exports matching `query`.
+ */
+async searchHubModels(query: string) : Promise<Result<HubModel[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("search_hub_models", { query }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Adds a Hub repo to the model catalog, so it can be downloaded and selected like the
+ * built-in models. Returns its catalog entry.
+ */
+async addHubModel(repoId: string) : Promise<Result<ModelInfo, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("add_hub_model", { repoId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Stores the Hugging Face access token used for gated repos, or removes it when empty.
+ */
+async setHubToken(token: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_hub_token", { token }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Folder the models are currently stored in.
+ */
+async getModelsDir() : Promise<string> {
+    return await TAURI_INVOKE("get_models_dir");
+},
+/**
+ * Moves the downloaded models to `path`, or back to the app data folder when `None`, and
+ * stores models there from then on. Returns how many models were moved.
+ */
+async moveModels(path: string | null) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("move_models", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Disk usage of each downloaded or partially downloaded model, largest first.
+ */
+async getModelDiskUsage() : Promise<Result<ModelDiskUsage[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_model_disk_usage") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Limits the disk space the models may take, `None` for no limit. Returns the models
+ * the new limit proposes to delete, which are only deleted through `evict_models`.
+ */
+async setModelStorageLimit(limitGb: number | null) : Promise<Result<EvictionProposal | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_model_storage_limit", { limitGb }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Deletes the models of a confirmed eviction proposal. The selected model and the one
+ * loaded are refused, in case they changed since it was made.
+ */
+async evictModels(modelIds: string[]) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("evict_models", { modelIds }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Sends model downloads through `proxy` (e.g. `http://proxy.corp:8080`), `None` goes back to
+ * the system proxy configuration.
+ */
+async setDownloadProxy(proxy: string | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_download_proxy", { proxy }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Trusts the root certificates in `path` for model downloads, in addition to the system ones.
+ */
+async setDownloadCaCert(path: string | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_download_ca_cert", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Converts a downloaded fp32 Parakeet model to int8 on this machine and returns the id
+ * of the resulting model. Quantization takes a few minutes, so it runs off the async runtime.
+ */
+async quantizeModel(modelId: string) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("quantize_model", { modelId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setActiveModel(modelId: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_active_model", { modelId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Downloads `model_id` if needed and makes it the active model as soon as it is on disk,
+ * so picking a model that isn't downloaded yet takes a single step. Both steps are
+ * reported as one `model-setup-progress`. Models are loaded from their complete files, so
+ * loading starts when the last one has arrived.
+ */
+async downloadAndActivateModel(modelId: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("download_and_activate_model", { modelId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getCurrentModel() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_current_model") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getTranscriptionModelStatus() : Promise<Result<string | null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_transcription_model_status") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async isModelLoading() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("is_model_loading") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async hasAnyModelsAvailable() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("has_any_models_available") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async hasAnyModelsOrDownloads() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("has_any_models_or_downloads") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getRecommendedFirstModel() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_recommended_first_model") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The model tier for this machine's memory and cores. Pass the real-time factor of the
+ * setup test, when it ran, to step down a tier if inference was slow.
+ */
+async recommendModelTier(realtimeFactor: number | null) : Promise<ModelRecommendation> {
+    return await TAURI_INVOKE("recommend_model_tier", { realtimeFactor });
+},
+async updateMicrophoneMode(alwaysOn: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_microphone_mode", { alwaysOn }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getMicrophoneMode() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_microphone_mode") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAvailableMicrophones() : Promise<Result<AudioDevice[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_available_microphones") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setSelectedMicrophone(deviceName: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_selected_microphone", { deviceName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getSelectedMicrophone() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_selected_microphone") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAvailableOutputDevices() : Promise<Result<AudioDevice[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_available_output_devices") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setSelectedOutputDevice(deviceName: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_selected_output_device", { deviceName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getSelectedOutputDevice() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_selected_output_device") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async playTestSound(soundType: string) : Promise<void> {
+    await TAURI_INVOKE("play_test_sound", { soundType });
+},
+async checkCustomSounds() : Promise<CustomSounds> {
+    return await TAURI_INVOKE("check_custom_sounds");
+},
+async setClamshellMicrophone(deviceName: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_clamshell_microphone", { deviceName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * How many `audio-level` events are sent per second while recording, 0 disables them.
+ */
+async setAudioLevelRate(readingsPerSecond: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_audio_level_rate", { readingsPerSecond }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Multiplier applied to the microphone signal, clamped to the supported range.
+ */
+async setInputGain(gain: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_input_gain", { gain }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Seconds of speech after which the live transcription closes an utterance even if the
+ * speaker never pauses, `None` waits for a pause.
+ */
+async setMaxUtteranceDuration(seconds: number | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_max_utterance_duration", { seconds }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * How soon a pause ends an utterance, and whether only once its live text reads as a
+ * finished sentence. Unfinished ones then end after `unfinished_pause_ms` of silence.
+ */
+async setEndpointing(sensitivity: EndpointSensitivity, semantic: boolean, unfinishedPauseMs: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_endpointing", { sensitivity, semantic, unfinishedPauseMs }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAudioFileInfo(path: string) : Promise<Result<AudioFileInfo, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_audio_file_info", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Converts the WAV file at `input` and writes the result to `output` as 16-bit WAV.
+ */
+async convertAudioFile(input: string, output: string, conversion: AudioConversion) : Promise<Result<AudioFileInfo, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("convert_audio_file", { input, output, conversion }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Transcribes the WAV file at `path` through the file job queue. Progress is checkpointed,
+ * so running it again after an interruption continues where it stopped.
+ */
+async transcribeAudioFile(path: string) : Promise<Result<TranscriptionOutput, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("transcribe_audio_file", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getInterruptedFileJobs() : Promise<Result<InterruptedFileJob[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_interrupted_file_jobs") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Continues an interrupted file job from its checkpoint. Jobs uploaded through the API can't
+ * be resumed from here; submitting the same audio again resumes them.
+ */
+async resumeFileJob(id: string) : Promise<Result<TranscriptionOutput, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("resume_file_job", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async discardFileJob(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("discard_file_job", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setAgcEnabled(enabled: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_agc_enabled", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setPreEmphasis(enabled: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_pre_emphasis", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Cutoff of the high-pass filter that removes rumble and plosives, `None` turns it off.
+ */
+async setHighPassCutoff(cutoffHz: number | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_high_pass_cutoff", { cutoffHz }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Records three seconds from the microphone and recommends an input gain. The user should
+ * speak normally while it runs; the recommendation is not applied automatically.
+ */
+async calibrateInputGain() : Promise<Result<GainCalibration, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("calibrate_input_gain") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Plays a known phrase through the speakers, records it with the selected microphone and
+ * scores its transcription, checking the devices, the pipeline and the model in one go.
+ */
+async runAudioSelfTest() : Promise<Result<SelfTestReport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("run_audio_self_test") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The input devices for the setup wizard, the one that will be used marked.
+ */
+async detectMicrophones() : Promise<Result<Microphone[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("detect_microphones") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Records five seconds from the microphone and transcribes them, reporting the levels, the
+ * latency and what to fix. The user should say a sentence while it runs.
+ */
+async runSetupTest() : Promise<Result<TestTakeReport, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("run_setup_test") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getClamshellMicrophone() : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_clamshell_microphone") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getCaptionStatus() : Promise<CaptionStatus> {
+    return await TAURI_INVOKE("get_caption_status");
+},
+/**
+ * Opens the caption window and starts captioning the configured source.
+ */
+async startCaptions() : Promise<Result<CaptionStatus, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("start_captions") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async stopCaptions() : Promise<void> {
+    await TAURI_INVOKE("stop_captions");
+},
+/**
+ * Sets what the captions transcribe, `device` naming the input to record from or `None`
+ * to pick one for the source. Running captions switch over right away.
+ */
+async setCaptionSource(source: CaptionSource, device: string | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_caption_source", { source, device }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The plugins in the plugins folder, in the order they run.
+ */
+async getPlugins() : Promise<PluginInfo[]> {
+    return await TAURI_INVOKE("get_plugins");
+},
+async setPluginConfig(id: string, enabled: boolean, timeoutMs: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_plugin_config", { id, enabled, timeoutMs }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async openPluginsFolder() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("open_plugins_folder") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setModelUnloadTimeout(timeout: ModelUnloadTimeout) : Promise<void> {
+    await TAURI_INVOKE("set_model_unload_timeout", { timeout });
+},
+/**
+ * Takes effect right away when running on battery.
+ */
+async setBatteryPolicy(policy: BatteryPolicy) : Promise<void> {
+    await TAURI_INVOKE("set_battery_policy", { policy });
+},
+async getPowerState() : Promise<PowerPolicyEvent> {
+    return await TAURI_INVOKE("get_power_state");
+},
+/**
+ * Turns the `perf-metrics` events on or off.
+ */
+async setPerfMetricsEnabled(enabled: boolean) : Promise<void> {
+    await TAURI_INVOKE("set_perf_metrics_enabled", { enabled });
+},
+/**
+ * Switches between the simulated engine and the models. The loaded model is unloaded so the
+ * next transcription loads the other kind.
+ */
+async setMockEngine(mockEngine: MockEngineSettings) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_mock_engine", { mockEngine }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setIdleCheckInterval(seconds: string) : Promise<void> {
+    await TAURI_INVOKE("set_idle_check_interval", { seconds });
+},
+/**
+ * How long before an idle unload the `model-unload-pending` event is sent.
+ */
+async setUnloadWarningSeconds(seconds: string) : Promise<void> {
+    await TAURI_INVOKE("set_unload_warning_seconds", { seconds });
+},
+/**
+ * Cancels a pending idle unload by restarting the inactivity timer.
+ */
+async keepModelLoaded() : Promise<void> {
+    await TAURI_INVOKE("keep_model_loaded");
+},
+async getModelLoadStatus() : Promise<Result<ModelLoadStatus, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_model_load_status") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async unloadModelManually() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("unload_model_manually") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Unloads the model even while a recording or transcription is in progress.
+ */
+async forceUnloadModel() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("force_unload_model") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getTranscriptionProviders() : Promise<CloudProviderInfo[]> {
+    return await TAURI_INVOKE("get_transcription_providers");
+},
+async setTranscriptionProvider(provider: TranscriptionProvider) : Promise<void> {
+    await TAURI_INVOKE("set_transcription_provider", { provider });
+},
+async setLowConfidenceThreshold(threshold: number) : Promise<void> {
+    await TAURI_INVOKE("set_low_confidence_threshold", { threshold });
+},
+/**
+ * Marks low-confidence words in pasted text with `marker`, `None` turns marking off. Only
+ * providers whose `reports_confidence` is set produce any.
+ */
+async setLowConfidenceMarker(marker: string | null) : Promise<void> {
+    await TAURI_INVOKE("set_low_confidence_marker", { marker });
+},
+/**
+ * Lets engines that support it favour the custom words while decoding. Engines that don't
+ * keep correcting the text afterwards. `None` goes back to biasing wherever it is supported.
+ */
+async setVocabularyBiasing(enabled: boolean | null) : Promise<void> {
+    await TAURI_INVOKE("set_vocabulary_biasing", { enabled });
+},
+/**
+ * Whether the selected model or provider can be biased toward the custom words: Whisper
+ * models and the OpenAI and Deepgram providers can, for the others the setting has no effect
+ * and custom words are only corrected afterwards.
+ */
+async supportsVocabularyBias() : Promise<boolean> {
+    return await TAURI_INVOKE("supports_vocabulary_bias");
+},
+async setVocabularyBoost(boost: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_vocabulary_boost", { boost }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Rescores transcriptions with the ARPA language model at `path`, `None` stops rescoring.
+ * The file is read right away so a broken one is reported here. Returns its n-gram count.
+ */
+async setLanguageModel(path: string | null) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_language_model", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setLanguageModelWeight(weight: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_language_model_weight", { weight }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Seconds a local transcription may run before the engine is reloaded, `None` waits
+ * indefinitely. Long recordings get proportionally more time.
+ */
+async setTranscriptionTimeout(seconds: number | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_transcription_timeout", { seconds }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Number of live chunks sent to a cloud provider at once. Local engines run one chunk at a
+ * time regardless.
+ */
+async setTranscriptionWorkers(workers: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_transcription_workers", { workers }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setFillerRemoval(enabled: boolean) : Promise<void> {
+    await TAURI_INVOKE("set_filler_removal", { enabled });
+},
+async setDisfluencyRemoval(enabled: boolean) : Promise<void> {
+    await TAURI_INVOKE("set_disfluency_removal", { enabled });
+},
+async setDisfluencyLearning(enabled: boolean) : Promise<void> {
+    await TAURI_INVOKE("set_disfluency_learning", { enabled });
+},
+/**
+ * Switches to `speaker`'s disfluency model, which starts out untrained for a new speaker.
+ */
+async setDisfluencySpeaker(speaker: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_disfluency_speaker", { speaker }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The fillers `speaker`'s model currently removes, the current speaker when `None`.
+ */
+async getDisfluencyModel(speaker: string | null) : Promise<DisfluencyModelSummary> {
+    return await TAURI_INVOKE("get_disfluency_model", { speaker });
+},
+async resetDisfluencyModel(speaker: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("reset_disfluency_model", { speaker }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async setCloudFallbackToLocal(enabled: boolean) : Promise<void> {
+    await TAURI_INVOKE("set_cloud_fallback_to_local", { enabled });
+},
+async setAzureSpeechRegion(region: string) : Promise<void> {
+    await TAURI_INVOKE("set_azure_speech_region", { region });
+},
+/**
+ * Stores the API key for a cloud provider in the OS keychain. An empty key removes it.
+ */
+async setCloudApiKey(provider: TranscriptionProvider, apiKey: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_cloud_api_key", { provider, apiKey }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The transcription held for readback, waiting to be confirmed or discarded.
+ */
+async getPendingReadback() : Promise<string | null> {
+    return await TAURI_INVOKE("get_pending_readback");
+},
+/**
+ * Reads the held transcription aloud again, or the latest one when none is held.
+ */
+async readBack() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("read_back") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Replaces the held transcription with the text edited in the preview.
+ */
+async editPendingReadback(text: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("edit_pending_readback", { text }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async confirmReadback() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("confirm_readback") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async discardReadback() : Promise<boolean> {
+    return await TAURI_INVOKE("discard_readback");
+},
+async getHistoryEntries() : Promise<Result<HistoryEntry[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_history_entries") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async toggleHistoryEntrySaved(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("toggle_history_entry_saved", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getAudioFilePath(fileName: string) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_audio_file_path", { fileName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * The recording `file_name` as WAV, decrypted when the history is encrypted. For playback,
+ * where the stored file may not be readable as it is.
+ */
+async getAudioFile(fileName: string) : Promise<Result<number[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_audio_file", { fileName }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async deleteHistoryEntry(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("delete_history_entry", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Per-utterance audio clips of a history entry, in transcript order.
+ */
+async getHistorySnippets(id: string) : Promise<Result<HistorySnippet[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_history_snippets", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateSnippetStorageLimit(limitMb: number) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_snippet_storage_limit", { limitMb }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateSaveRecordingAudio(enabled: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_save_recording_audio", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Caps the disk space used by session audio, `None` removes the cap.
+ */
+async updateRecordingStorageLimit(limitMb: number | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_recording_storage_limit", { limitMb }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Transcribes an entry's saved audio again with `model_id` and stores the result as a new
+ * version of the entry. The model doesn't need to be the selected one.
+ */
+async retranscribe(historyId: string, modelId: string) : Promise<Result<TranscriptionVersion, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("retranscribe", { historyId, modelId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getTranscriptionVersions(historyId: string) : Promise<Result<TranscriptionVersion[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_transcription_versions", { historyId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Where the accurate pass of entry `history_id` disagreed with the live transcription.
+ */
+async getRefinementChanges(historyId: string) : Promise<Result<RefinementChange[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_refinement_changes", { historyId }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Pastes history entry `id`, or the latest one, again using the current output settings.
+ * The main window is hidden first so the text lands in the app that was focused before it.
+ */
+async pasteHistoryEntry(id: string | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("paste_history_entry", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Removes the last pasted dictation from the app it went into and tags its history entry.
+ * The cursor has to still be right after the pasted text.
+ */
+async undoLastDictation() : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("undo_last_dictation") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Dictations interrupted by a crash or power loss, with the text transcribed before the
+ * interruption, most recent first.
+ */
+async getRecoverableSessions() : Promise<Result<RecoveredSession[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_recoverable_sessions") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Saves an interrupted dictation to history and removes its journal.
+ */
+async restoreSession(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("restore_session", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async discardRecoverableSession(id: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("discard_recoverable_session", { id }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateHistoryLimit(limit: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_history_limit", { limit }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateRecordingRetentionPeriod(period: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_recording_retention_period", { period }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateAudioRetentionDays(days: number | null) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_audio_retention_days", { days }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateArchiveExpiredHistory(enabled: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_archive_expired_history", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Turns encryption of the history at rest on or off and converts the existing history.
+ * Returns how many texts and recordings were converted.
+ */
+async setHistoryEncryption(enabled: boolean) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_history_encryption", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getIncognito() : Promise<boolean> {
+    return await TAURI_INVOKE("get_incognito");
+},
+/**
+ * Keeps dictations out of history, and off the disk, until turned off or the app restarts.
+ */
+async setIncognito(enabled: boolean) : Promise<void> {
+    await TAURI_INVOKE("set_incognito", { enabled });
+},
+/**
+ * Entries matching a text `query` that carry all of `tags`, newest first.
+ */
+async searchHistory(query: string | null, tags: string[]) : Promise<Result<HistoryEntry[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("search_history", { query, tags }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Renames an entry, an empty title restores the default one.
+ */
+async renameHistoryEntry(id: string, title: string) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("rename_history_entry", { id, title }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Replaces an entry's tags and returns them as stored, lowercased and dash-joined.
+ */
+async setHistoryEntryTags(id: string, tags: string[]) : Promise<Result<string[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("set_history_entry_tags", { id, tags }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async getHistoryTags() : Promise<Result<HistoryTag[], string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_history_tags") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Writes the history to `path` as JSON grouped by tag, returns the number of entries.
+ */
+async exportHistoryByTag(path: string) : Promise<Result<string, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("export_history_by_tag", { path }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Words per minute, audio time, latency and confidence of the last `days` days, per day or
+ * week and per model.
+ */
+async getDictationStats(period: StatsPeriod, days: number) : Promise<Result<DictationStats, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("get_dictation_stats", { period, days }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateSessionVoiceCommands(enabled: boolean) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_session_voice_commands", { enabled }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+async updateSessionTagRules(rules: SessionTagRule[]) : Promise<Result<null, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("update_session_tag_rules", { rules }) };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+},
+/**
+ * Checks if the Mac is a laptop by detecting battery presence
+ * 
+ * This uses pmset to check for battery information.
+ * Returns true if a battery is detected (laptop), false otherwise (desktop)
+ */
+async isLaptop() : Promise<Result<boolean, string>> {
+    try {
+    return { status: "ok", data: await TAURI_INVOKE("is_laptop") };
+} catch (e) {
+    if(e instanceof Error) throw e;
+    else return { status: "error", error: e  as any };
+}
+}
+}
+
 /** user-defined events **/
 
 
@@ -610,24 +2121,910 @@ async isLaptop() : Promise<Result<boolean, string>> {
 
 /** user-defined types **/
 
-export type AppSettings = { bindings: Partial<{ [key in string]: ShortcutBinding }>; push_to_talk: boolean; audio_feedback: boolean; audio_feedback_volume?: number; sound_theme?: SoundTheme; start_hidden?: boolean; autostart_enabled?: boolean; update_checks_enabled?: boolean; selected_model?: string; always_on_microphone?: boolean; selected_microphone?: string | null; clamshell_microphone?: string | null; selected_output_device?: string | null; translate_to_english?: boolean; selected_language?: string; overlay_position?: OverlayPosition; debug_mode?: boolean; log_level?: LogLevel; custom_words?: string[]; model_unload_timeout?: ModelUnloadTimeout; word_correction_threshold?: number; history_limit?: string; recording_retention_period?: RecordingRetentionPeriod; paste_method?: PasteMethod; clipboard_handling?: ClipboardHandling; post_process_enabled?: boolean; post_process_provider_id?: string; post_process_providers?: PostProcessProvider[]; post_process_api_keys?: Partial<{ [key in string]: string }>; post_process_models?: Partial<{ [key in string]: string }>; post_process_prompts?: LLMPrompt[]; post_process_selected_prompt_id?: string | null; mute_while_recording?: boolean; append_trailing_space?: boolean }
+export type AppSettings = { bindings: Partial<{ [key in string]: ShortcutBinding }>; push_to_talk: boolean; audio_feedback: boolean; audio_feedback_volume?: number; sound_theme?: SoundTheme; start_hidden?: boolean; autostart_enabled?: boolean; update_checks_enabled?: boolean; selected_model?: string; always_on_microphone?: boolean; selected_microphone?: string | null; clamshell_microphone?: string | null; selected_output_device?: string | null; translate_to_english?: boolean; selected_language?: string; overlay_position?: OverlayPosition; screen_reader_announcements?: ScreenReaderAnnouncements; debug_mode?: boolean; log_level?: LogLevel; custom_words?: CustomWord[]; proper_nouns?: string[]; snippets?: Snippet[]; 
+/**
+ * Applied in order to the final transcript
+ */
+regex_rules?: RegexRule[]; 
+/**
+ * Stages the final text runs through, in order
+ */
+text_pipeline?: PipelineStage[]; 
+/**
+ * Plugins not listed are disabled
+ */
+plugins?: PluginConfig[]; model_unload_timeout?: ModelUnloadTimeout; idle_check_interval_secs?: string; unload_warning_secs?: string; word_correction_threshold?: number; 
+/**
+ * Steers engines that support it toward the custom words while decoding, instead of
+ * fuzzy-correcting the text afterwards. Unset, it is on for the engines that support
+ * it, Whisper and the OpenAI and Deepgram APIs, and off for the others such as Parakeet.
+ */
+vocabulary_biasing?: boolean | null; 
+/**
+ * How strongly biasing engines favour the custom words
+ */
+vocabulary_boost?: number; 
+/**
+ * ARPA n-gram model whose likelier words replace similarly spelled ones in the text
+ */
+language_model_path?: string | null; 
+/**
+ * How much the language model's judgement counts against changing a word's spelling
+ */
+language_model_weight?: number; history_limit?: string; recording_retention_period?: RecordingRetentionPeriod; audio_retention_days?: number | null; 
+/**
+ * Keep each session's audio as a 16 kHz WAV next to its history entry, off until the
+ * user opts in
+ */
+save_recording_audio?: boolean; 
+/**
+ * Disk space for session audio; the oldest unsaved recordings are removed first
+ */
+recording_storage_limit_mb?: number | null; archive_expired_history?: boolean; 
+/**
+ * Dictated text in the history database and the recordings are encrypted at rest, with
+ * a key kept in the OS credential store
+ */
+encrypt_history?: boolean; 
+/**
+ * Leading "Title: ..." and "Tags: ..." sentences name and tag the session instead of
+ * being pasted
+ */
+session_voice_commands?: boolean; session_tag_rules?: SessionTagRule[]; session_hooks?: SessionHook[]; 
+/**
+ * POSTs each finished dictation to `webhook_url`
+ */
+webhook_enabled?: boolean; webhook_url?: string; profiles?: DictationProfile[]; 
+/**
+ * The profile last switched to, `None` before any
+ */
+active_profile_id?: string | null; 
+/**
+ * The settings profiles override, as they were before a profile was applied. Switching
+ * profiles or back to none restores them first, so changes made to these settings while
+ * a profile is active last until then.
+ */
+profile_base?: DictationProfile | null; 
+/**
+ * Loads the model of the focused application's profile while idle, in place of the
+ * loaded model, so dictating there doesn't wait for it
+ */
+prefetch_profile_models?: boolean; 
+/**
+ * Keep coarse, content-free usage counters for diagnostics
+ */
+usage_counters?: boolean; 
+/**
+ * Disk space for per-utterance history snippets, oldest are removed first; 0, the
+ * default, disables them
+ */
+snippet_storage_limit_mb?: number; paste_method?: PasteMethod; trigger_app_scope?: TriggerAppScope; trigger_apps?: string[]; output_target?: OutputTarget; 
+/**
+ * Folder of the Markdown notes, e.g. an Obsidian vault
+ */
+markdown_folder?: string | null; 
+/**
+ * Note in `markdown_folder` dictations are appended to, the day's note when `None`
+ */
+markdown_file?: string | null; humanized_typing?: HumanizedTypingSettings; clipboard_handling?: ClipboardHandling; 
+/**
+ * Longer transcriptions are copied to the clipboard in parts of at most this many
+ * characters, for target apps that truncate large pastes.
+ */
+clipboard_chunk_chars?: string | null; 
+/**
+ * Words the decoder scores below this confidence (0 to 1) are flagged for review. Only
+ * the OpenAI and Deepgram providers score their output, local models flag nothing.
+ */
+low_confidence_threshold?: number; 
+/**
+ * Appended to low-confidence words in the pasted text, e.g. `[?]`. `None` pastes the
+ * text unmarked, as does transcribing with a local model.
+ */
+low_confidence_marker?: string | null; 
+/**
+ * Removes hesitations, and in English "like", "you know" and doubled words, by rule
+ */
+filler_removal?: boolean; 
+/**
+ * Removes the speaker's filler words from transcriptions.
+ */
+disfluency_removal?: boolean; 
+/**
+ * Lets the disfluency model learn from post-processed transcriptions. Opt-in, since it
+ * stores statistics about the user's speech.
+ */
+disfluency_learning?: boolean; 
+/**
+ * Whose disfluency model is used and trained.
+ */
+disfluency_speaker?: string; text_formatting?: TextFormatting; shared_model_cache?: boolean; 
+/**
+ * Folder the models are stored in, e.g. on a secondary drive. `None` keeps them in the
+ * app data folder.
+ */
+models_dir?: string | null; 
+/**
+ * Disk space the models may take, in GB. Past it, the least recently used models are
+ * proposed for deletion.
+ */
+model_storage_limit_gb?: number | null; hub_models?: HubModelConfig[]; low_memory_guard?: LowMemoryGuard; 
+/**
+ * Most CPUs inference may use; all of them when unset
+ */
+inference_threads?: number | null; inference_priority?: InferencePriority; inference_cores?: InferenceCores; battery_policy?: BatteryPolicy; 
+/**
+ * Send `perf-metrics` events with the latency of each stage
+ */
+perf_metrics_enabled?: boolean; 
+/**
+ * Transcribe with the simulated engine instead of a model
+ */
+mock_engine?: MockEngineSettings; 
+/**
+ * Proxy URL for model downloads. When unset the system proxy configuration is used.
+ */
+download_proxy?: string | null; 
+/**
+ * PEM file with extra root certificates trusted for model downloads, for networks that
+ * intercept TLS.
+ */
+download_ca_cert_path?: string | null; transcription_provider?: TranscriptionProvider; cloud_fallback_to_local?: boolean; azure_speech_region?: string; api_server_enabled?: boolean; api_server_port?: number; 
+/**
+ * Serves the MCP endpoint at `/mcp` on the API server's port
+ */
+mcp_server_enabled?: boolean; post_process_enabled?: boolean; post_process_provider_id?: string; post_process_providers?: PostProcessProvider[]; post_process_api_keys?: Partial<{ [key in string]: string }>; post_process_models?: Partial<{ [key in string]: string }>; post_process_prompts?: LLMPrompt[]; post_process_selected_prompt_id?: string | null; 
+/**
+ * Sends the focused app and window title along with post-processing prompts
+ */
+post_process_context_window?: boolean; 
+/**
+ * Sends the text selected in the focused app along with post-processing prompts
+ */
+post_process_context_selection?: boolean; mute_while_recording?: boolean; audio_level_rate_hz?: number; append_trailing_space?: boolean; 
+/**
+ * Adapts pasted text to the focused field, e.g. joins lines for single-line inputs
+ */
+fit_output_to_field?: boolean; 
+/**
+ * Refuses to record or paste while a password field has the focus
+ */
+block_secure_fields?: boolean; 
+/**
+ * Multiplier applied to microphone input before VAD and transcription
+ */
+input_gain?: number; 
+/**
+ * Automatic gain control, on top of `input_gain`
+ */
+agc_enabled?: boolean; 
+/**
+ * Pre-emphasis on microphone input, lifting consonants over low-frequency energy
+ */
+pre_emphasis?: boolean; 
+/**
+ * Cutoff of the high-pass filter on microphone input in Hz, `None` turns it off
+ */
+high_pass_cutoff_hz?: number | null; 
+/**
+ * Seconds of speech after which the live transcription closes a segment even if the
+ * speaker never pauses, `None` waits for a pause
+ */
+max_utterance_secs?: number | null; endpoint_sensitivity?: EndpointSensitivity; 
+/**
+ * Ends an utterance at the sensitivity's pause only once its live text reads as a
+ * finished sentence, otherwise after `unfinished_pause_ms` of silence
+ */
+semantic_endpointing?: boolean; unfinished_pause_ms?: number; 
+/**
+ * Seconds a local transcription may run before the engine is considered stuck and
+ * reloaded, raised for long recordings. `None` waits indefinitely.
+ */
+transcription_timeout_secs?: number | null; 
+/**
+ * Live chunks transcribed at once when the provider takes parallel requests
+ */
+transcription_workers?: number; caption_source?: CaptionSource; 
+/**
+ * Input device the captions record from, `None` picks one for the source
+ */
+caption_device?: string | null; 
+/**
+ * Reads the final transcription aloud and holds it until confirmed instead of pasting
+ */
+readback_before_paste?: boolean; 
+/**
+ * Shows the final transcription in an editable preview and holds it until confirmed
+ * instead of pasting
+ */
+confirm_before_paste?: boolean; 
+/**
+ * Id of the Piper voice transcriptions are read back with
+ */
+readback_voice?: string; 
+/**
+ * The `piper` program, `None` looks it up on the PATH
+ */
+piper_path?: string | null; 
+/**
+ * Small model kept loaded for live partials while the selected one loads on demand
+ */
+standby_model?: string | null; 
+/**
+ * Bigger model the finished recording is transcribed again with, `None` uses the
+ * selected one
+ */
+rescore_model?: string | null }
+/**
+ * Steps applied by `convert_audio_file`, in the order listed. The defaults turn any WAV
+ * file into 16 kHz mono for transcription.
+ */
+export type AudioConversion = { 
+/**
+ * Output sample rate, 16 kHz when not set
+ */
+sample_rate: number | null; 
+/**
+ * Mixes all channels into one, on unless turned off
+ */
+mono: boolean | null; trim_start_secs: number | null; trim_end_secs: number | null; 
+/**
+ * Scales the audio so its loudest sample reaches this level, e.g. -1.0
+ */
+normalize_peak_dbfs: number | null }
 export type AudioDevice = { index: string; name: string; is_default: boolean }
+export type AudioFileInfo = { sample_rate: number; channels: number; duration_secs: number; 
+/**
+ * Whether the file is already 16 kHz mono, the format the models expect
+ */
+transcription_ready: boolean }
+/**
+ * What changes while the machine runs on battery. Each knob only ever saves power: a
+ * shorter unload timeout or lower rate configured outside the policy is kept.
+ */
+export type BatteryPolicy = { enabled: boolean; model_unload_timeout: ModelUnloadTimeout; 
+/**
+ * A smaller model used instead of the selected one, if downloaded
+ */
+model_id: string | null; 
+/**
+ * Readings per second of the `audio-level` events
+ */
+audio_level_rate_hz: number; inference_priority: InferencePriority }
 export type BindingResponse = { success: boolean; binding: ShortcutBinding | null; error: string | null }
+/**
+ * Audio the caption overlay transcribes.
+ */
+export type CaptionSource = "microphone" | 
+/**
+ * What the computer plays, captured through a loopback or monitor input
+ */
+"system_audio"
+export type CaptionStatus = { running: boolean; source: CaptionSource; 
+/**
+ * Input the captions record from, `None` while stopped or on the default input
+ */
+device: string | null }
+/**
+ * Payload of the `clipboard-chunk-copied` event.
+ */
+export type ClipboardChunkProgress = { 
+/**
+ * 1-based number of the part now on the clipboard
+ */
+part: string; total: string }
 export type ClipboardHandling = "dont_modify" | "copy_to_clipboard"
+export type CloudProviderInfo = { id: string; name: string; privacy_notice: string; has_api_key: boolean; 
+/**
+ * Whether transcripts come with confidence scores, so low-confidence words can be
+ * flagged. The local models don't report any.
+ */
+reports_confidence: boolean }
 export type CustomSounds = { start: boolean; stop: boolean }
-export type EngineType = "Whisper" | "Parakeet"
-export type HistoryEntry = { id: string; file_name: string; timestamp: string; saved: boolean; title: string; transcription_text: string; post_processed_text: string | null; post_process_prompt: string | null }
+/**
+ * A word transcriptions are corrected towards, with hints for how the engine mishears it.
+ */
+export type CustomWord = { word: string; 
+/**
+ * Alternate spellings or "sounds like" strings, e.g. "get hub" for "GitHub"
+ */
+sounds_like: string[]; 
+/**
+ * Overrides `word_correction_threshold` for this word
+ */
+threshold: number | null; 
+/**
+ * Languages the word is corrected towards in, all when empty
+ */
+languages: string[]; 
+/**
+ * Ids of the profiles the word is used in, all when empty
+ */
+profiles: string[] }
+export type DailySessions = { 
+/**
+ * YYYY-MM-DD, local time
+ */
+date: string; sessions: string }
+/**
+ * A named set of dictation settings switched to as a whole, e.g. "email" or "code". Fields
+ * left `None` keep their current value when switching.
+ */
+export type DictationProfile = { id: string; name: string; 
+/**
+ * Loaded when switching, if another model is loaded
+ */
+model_id?: string | null; selected_language?: string | null; post_process_enabled?: boolean | null; post_process_provider_id?: string | null; post_process_prompt_id?: string | null; text_formatting?: TextFormatting | null; paste_method?: PasteMethod | null; output_target?: OutputTarget | null; endpoint_sensitivity?: EndpointSensitivity | null; 
+/**
+ * Local model or cloud provider for the profile's dictation
+ */
+transcription_provider?: TranscriptionProvider | null; 
+/**
+ * Applications the profile is for, matched ignoring case against the focused
+ * application's name
+ */
+app_patterns?: string[] }
+export type DictationStats = { 
+/**
+ * Every period of the range, oldest first, including ones without sessions
+ */
+periods: StatsSummary[]; 
+/**
+ * Most used first
+ */
+models: StatsSummary[]; total: StatsSummary }
+export type DisfluencyModelSummary = { speaker: string; 
+/**
+ * Transcriptions the model learned from
+ */
+samples: number; fillers: FillerPattern[] }
+/**
+ * How soon a pause ends an utterance of the live transcription.
+ */
+export type EndpointSensitivity = "aggressive" | "balanced" | 
+/**
+ * Waits out longer pauses, for speakers who stop to think mid-sentence
+ */
+"patient"
+export type EngineType = "Whisper" | "Parakeet" | 
+/**
+ * A Piper text-to-speech voice, used to read transcriptions back rather than to
+ * transcribe
+ */
+"Piper"
+/**
+ * Where a session went wrong. Only the category is counted, never the message.
+ */
+export type ErrorCategory = "transcription" | "paste" | "history" | "other"
+export type ErrorCount = { category: ErrorCategory; count: string }
+/**
+ * Models that would bring the total under the storage limit, sent with
+ * `model-eviction-proposed` for the user to confirm.
+ */
+export type EvictionProposal = { limit_bytes: string; total_bytes: string; models: ModelDiskUsage[] }
+export type FeatureUsage = { feature: UsageFeature; sessions: string }
+/**
+ * A pattern the model currently removes.
+ */
+export type FillerPattern = { pattern: string; 
+/**
+ * Share of occurrences the cleanup deleted
+ */
+probability: number; removed: number; seen: number }
+export type GainCalibration = { recommended_gain: number; speech_rms: number; noise_floor: number; peak: number }
+export type HistoryEntry = { id: string; file_name: string; timestamp: string; saved: boolean; title: string; transcription_text: string; post_processed_text: string | null; post_process_prompt: string | null; 
+/**
+ * Whether the session audio is still on disk
+ */
+audio_available?: boolean; tags?: string[] }
+/**
+ * The audio behind one segment of a history entry's transcription.
+ */
+export type HistorySnippet = { id: string; history_id: string; segment_index: string; start_secs: number; end_secs: number; text: string; 
+/**
+ * Relative to the recordings directory, resolve it with `get_audio_file_path`
+ */
+file_name: string }
+/**
+ * A tag in use and how many entries carry it.
+ */
+export type HistoryTag = { tag: string; entry_count: string }
+export type HookOutput = { 
+/**
+ * `None` when the command was killed or ended by a signal
+ */
+exit_code: number | null; timed_out: boolean; stdout: string; stderr: string }
+/**
+ * A compatible repo, as the browser lists it
+ */
+export type HubModel = { repo_id: string; downloads: string; likes: string; license: string | null; languages: string[]; 
+/**
+ * Needs an access token with the repo's terms accepted
+ */
+gated: boolean; 
+/**
+ * Size of the files that would be downloaded, when the Hub reported file sizes
+ */
+size_mb: string | null; quantization: Quantization; files: string[]; 
+/**
+ * SHA-256 of the files in Git LFS
+ */
+sha256: Partial<{ [key in string]: string }> }
+/**
+ * A Parakeet export added to the catalog from the Hugging Face Hub.
+ */
+export type HubModelConfig = { 
+/**
+ * e.g. `istupakov/parakeet-tdt-0.6b-v3-onnx`
+ */
+repo_id: string; quantization: Quantization; 
+/**
+ * Files downloaded from the repo
+ */
+files: string[]; size_mb: string; 
+/**
+ * SHA-256 of the files in Git LFS
+ */
+sha256?: Partial<{ [key in string]: string }> }
+/**
+ * Keystroke timing used by `PasteMethod::HumanizedTyping`.
+ */
+export type HumanizedTypingSettings = { 
+/**
+ * Average delay between keystrokes
+ */
+mean_delay_ms: number; 
+/**
+ * Standard deviation of the delay between keystrokes
+ */
+delay_jitter_ms: number; 
+/**
+ * Average number of characters typed before a longer pause, 0 disables pauses
+ */
+burst_length: number; burst_pause_ms: number }
+/**
+ * Which cores of a hybrid CPU inference runs on.
+ */
+export type InferenceCores = "any" | "performance" | 
+/**
+ * Slower, but spares the battery and the performance cores
+ */
+"efficiency"
+/**
+ * OS priority of model loading and inference, lowest last.
+ */
+export type InferencePriority = "normal" | 
+/**
+ * Yields to the foreground apps, e.g. on battery
+ */
+"below_normal" | 
+/**
+ * Only runs when nothing else wants the CPU
+ */
+"idle"
+export type IntegrityReport = { model_id: string; missing: string[]; 
+/**
+ * Files whose hash or size is wrong
+ */
+corrupt: string[]; 
+/**
+ * Files present that the catalog has no hash for
+ */
+unverified: string[] }
+/**
+ * A checkpoint left behind by a file job that didn't finish.
+ */
+export type InterruptedFileJob = { id: string; source: string | null; duration_secs: number; completed_segments: string; total_segments: string; updated_at: string }
 export type LLMPrompt = { id: string; name: string; prompt: string }
+export type LogEntry = { id: string; level: LogLevel; 
+/**
+ * Module path of the code that logged, such as `handy::managers::audio`
+ */
+module: string; 
+/**
+ * The line as written to the log file, timestamp included
+ */
+line: string }
 export type LogLevel = "trace" | "debug" | "info" | "warn" | "error"
-export type ModelInfo = { id: string; name: string; description: string; filename: string; url: string | null; size_mb: string; is_downloaded: boolean; is_downloading: boolean; partial_size: string; is_directory: boolean; engine_type: EngineType; accuracy_score: number; speed_score: number }
+/**
+ * Which entries to return; every field left unset matches all of them.
+ */
+export type LogQuery = { 
+/**
+ * Only entries logged after the one with this id
+ */
+after: string | null; 
+/**
+ * Only entries at least this severe
+ */
+level: LogLevel | null; 
+/**
+ * Only entries from this module or the ones below it
+ */
+module: string | null; 
+/**
+ * Only the last this many of the matching entries
+ */
+limit: number | null }
+/**
+ * What happens when a model needs more memory than is available.
+ */
+export type LowMemoryGuard = 
+/**
+ * Refuse to load it, unless the user insists
+ */
+"block" | 
+/**
+ * Load it after warning
+ */
+"warn" | "off"
+/**
+ * What applying a manifest did.
+ */
+export type ManifestApplied = { downloaded: string[]; settings: AppSettings }
+/**
+ * Memory of the machine and of Handy, in MB, to show next to the models' requirements.
+ */
+export type MemoryStatus = { total_mb: string | null; available_mb: string | null; 
+/**
+ * Memory Handy itself holds
+ */
+app_resident_mb: string | null; 
+/**
+ * Estimate for the model loaded, if any
+ */
+loaded_model_mb: string | null }
+export type Microphone = { name: string; 
+/**
+ * The OS default input
+ */
+is_default: boolean; 
+/**
+ * The one in the settings, or the default when none is set
+ */
+is_selected: boolean }
+/**
+ * The simulated transcription engine, a debug setting for frontend work and tests. While
+ * enabled it stands in for every local model, downloaded or not.
+ */
+export type MockEngineSettings = { enabled: boolean; 
+/**
+ * Script the transcriptions are read from, a built-in one when unset
+ */
+script_path: string | null; load_delay_ms: string; 
+/**
+ * Transcription time per second of audio, for utterances that don't set their own
+ */
+delay_per_audio_second_ms: string }
+export type ModelBenchmark = { model_id: string; model_name: string; load_ms: string; transcribe_ms: string; 
+/**
+ * Transcription time over clip duration, below 1 is faster than real time
+ */
+real_time_factor: number; 
+/**
+ * How far the app's resident memory rose above what it used before while the model
+ * loaded and ran, where the platform reports it
+ */
+peak_ram_mb: number | null; text: string; 
+/**
+ * Share of reference words missed, swapped or added, when a reference was given
+ */
+word_error_rate: number | null; 
+/**
+ * Set when the model failed to load or transcribe, the measurements are then empty
+ */
+error: string | null }
+export type ModelDiskUsage = { model_id: string; name: string; 
+/**
+ * Size on disk, partial downloads included
+ */
+bytes: string; 
+/**
+ * Unix time the model was last loaded, `None` when it never was since this was tracked
+ */
+last_used: string | null }
+export type ModelInfo = { id: string; name: string; description: string; filename: string; url: string | null; size_mb: string; is_downloaded: boolean; is_downloading: boolean; partial_size: string; is_directory: boolean; engine_type: EngineType; accuracy_score: number; speed_score: number; quantization: Quantization; ram_mb: string; 
+/**
+ * SHA-256 of each file by its path in the model (the file name of a single-file
+ * model), where the catalog knows it
+ */
+sha256?: Partial<{ [key in string]: string }>; 
+/**
+ * For directory models fetched file by file, the files to download relative to `url`
+ */
+download_files: string[] }
 export type ModelLoadStatus = { is_loaded: boolean; current_model: string | null }
+export type ModelRecommendation = { tier: ModelTier; model_id: string; total_memory_mb: string | null; cpu_cores: number; reason: string }
+export type ModelTier = 
+/**
+ * Fast and small, for machines short on memory or cores
+ */
+"light" | "standard" | 
+/**
+ * The most accurate model, for machines with plenty of both
+ */
+"high"
 export type ModelUnloadTimeout = "never" | "immediately" | "min_2" | "min_5" | "min_10" | "min_15" | "hour_1" | "sec_5"
+/**
+ * Where the final text of a dictation goes.
+ */
+export type OutputTarget = 
+/**
+ * Into the focused application, with the paste method
+ */
+"paste" | 
+/**
+ * Appended to a Markdown note in `markdown_folder`
+ */
+"markdown_note"
 export type OverlayPosition = "none" | "top" | "bottom"
-export type PasteMethod = "ctrl_v" | "direct" | "none" | "shift_insert"
+export type PasteMethod = "ctrl_v" | "direct" | "none" | "shift_insert" | "humanized_typing" | 
+/**
+ * Inserts at the caret through the accessibility API, pasting where that can't reach
+ */
+"accessibility"
+export type PipelineStage = { stage: TextStageId; enabled: boolean }
+/**
+ * Settings of a text plugin, by the file name of its module without `.wasm`.
+ */
+export type PluginConfig = { id: string; enabled: boolean; timeout_ms: string }
+export type PluginInfo = { 
+/**
+ * File name without the `.wasm` extension
+ */
+id: string; path: string; enabled: boolean; timeout_ms: string; 
+/**
+ * Why the module can't be used, e.g. a missing export
+ */
+error: string | null }
 export type PostProcessProvider = { id: string; label: string; base_url: string; allow_base_url_edit?: boolean; models_endpoint?: string | null }
+export type PowerPolicyEvent = { on_battery: boolean; 
+/**
+ * On battery with the battery policy enabled
+ */
+policy_active: boolean }
+export type Quantization = "Fp32" | "Fp16" | "Int8" | "Q5" | "Q4"
 export type RecordingRetentionPeriod = "never" | "preserve_limit" | "days_3" | "weeks_2" | "months_3"
-export type ShortcutBinding = { id: string; name: string; description: string; default_binding: string; current_binding: string }
+/**
+ * A journal left behind by a dictation that never finished.
+ */
+export type RecoveredSession = { id: string; 
+/**
+ * Unix timestamp of when the dictation started
+ */
+started_at: string; text: string }
+/**
+ * A stretch of the recording where the two passes disagreed.
+ */
+export type RefinementChange = { start: number; end: number; live_text: string; refined_text: string; 
+/**
+ * The live text was kept because the accurate pass was less sure of its version
+ */
+kept_live: boolean }
+/**
+ * Find/replace rule run over the final transcript. `replacement` may refer to capture
+ * groups as `$1` or `${name}`.
+ */
+export type RegexRule = { id: string; name: string; pattern: string; replacement: string; enabled?: boolean; case_insensitive?: boolean; 
+/**
+ * Languages the rule is for, all when empty
+ */
+languages?: string[]; 
+/**
+ * Ids of the profiles the rule is for, all when empty
+ */
+profiles?: string[] }
+/**
+ * Before and after of a dry run, with the text after each enabled rule.
+ */
+export type RulePreview = { before: string; after: string; steps: RuleStep[] }
+/**
+ * The text after one rule ran.
+ */
+export type RuleStep = { rule_id: string; matches: string; text: string }
+/**
+ * What recognized text is read out through the screen reader.
+ */
+export type ScreenReaderAnnouncements = "off" | 
+/**
+ * The transcription once the recording ends
+ */
+"final" | 
+/**
+ * Words as they are recognized while recording, then the rest of the transcription
+ */
+"live"
+export type SelfTestReport = { expected: string; heard: string; 
+/**
+ * Share of the phrase's words missed, swapped or added
+ */
+word_error_rate: number; 
+/**
+ * Loudest sample of the recording, from 0 to 1
+ */
+peak_level: number; recorded_secs: number; passed: boolean; 
+/**
+ * What most likely went wrong, when the test failed
+ */
+problem: string | null }
+/**
+ * A sensitive setting a bundle would change, which the user has to confirm by `key`.
+ */
+export type SensitiveChange = { key: string; 
+/**
+ * The bundle's value, as JSON
+ */
+value: string }
+/**
+ * Shell command run when a dictation session starts or ends, e.g. to pause music, set a
+ * chat status or switch on an on-air light.
+ */
+export type SessionHook = { id: string; name: string; event: SessionHookEvent; 
+/**
+ * Run with `sh -c`, or `cmd /C` on Windows
+ */
+command: string; enabled?: boolean; 
+/**
+ * The command is killed when it runs longer
+ */
+timeout_secs?: number }
+export type SessionHookEvent = 
+/**
+ * Recording started
+ */
+"start" | 
+/**
+ * Recording stopped or was cancelled, before the transcription is delivered
+ */
+"end"
+/**
+ * Tags added to sessions dictated while a matching application is focused.
+ */
+export type SessionTagRule = { 
+/**
+ * Matched ignoring case against the focused application's name and window title
+ */
+app_pattern: string; tags: string[] }
+export type ShortcutBinding = { id: string; name: string; description: string; default_binding: string; 
+/**
+ * Empty when no trigger is assigned
+ */
+current_binding: string; 
+/**
+ * Separate trigger that stops recording. When set, `current_binding` only starts it.
+ */
+stop_binding?: string | null }
+/**
+ * Text inserted in place of a spoken trigger phrase, e.g. "insert signature".
+ */
+export type Snippet = { id: string; 
+/**
+ * Phrase that is replaced, matched ignoring case and punctuation
+ */
+trigger: string; 
+/**
+ * Replacement text, may span lines and use `${date}`, `${time}` and `${clipboard}`
+ */
+template: string }
 export type SoundTheme = "marimba" | "pop" | "custom"
+export type StatsPeriod = "day" | 
+/**
+ * Weeks starting on Monday
+ */
+"week"
+/**
+ * Dictation statistics of a day, a week, a model or everything.
+ */
+export type StatsSummary = { 
+/**
+ * First day of the period as YYYY-MM-DD, or the model id
+ */
+label: string; sessions: string; audio_secs: number; words: string; characters: string; 
+/**
+ * Words produced per minute of audio
+ */
+words_per_minute: number; average_latency_ms: number; 
+/**
+ * Mean decoder confidence of the sessions that reported one, a proxy for accuracy. Only
+ * cloud providers report confidence, so `None` when every session was transcribed locally
+ */
+average_confidence: number | null }
+export type TestTakeReport = { model_id: string; heard: string; recorded_secs: number; 
+/**
+ * Loudest sample, from 0 to 1
+ */
+peak_level: number; 
+/**
+ * RMS of the louder (speech) part of the take
+ */
+speech_rms: number; 
+/**
+ * RMS of the quieter (background) part of the take
+ */
+noise_floor: number; 
+/**
+ * Manual input gain that would bring the speech to a comfortable level, `None` for a
+ * silent take
+ */
+recommended_gain: number | null; 
+/**
+ * Time to load the model, 0 when it already was
+ */
+load_ms: string; inference_ms: string; 
+/**
+ * Inference time over the take's duration, below 1 is faster than real time
+ */
+realtime_factor: number; 
+/**
+ * What to fix before dictating, when something is off
+ */
+problem: string | null }
+export type TextFormatting = "plain" | "markdown" | "rich_text"
+/**
+ * A stage of the text pipeline run between transcription and output.
+ */
+export type TextStageId = 
+/**
+ * Rule-based and learned filler removal
+ */
+"fillers" | "custom_words" | 
+/**
+ * Number words written as digits
+ */
+"itn" | 
+/**
+ * Spoken punctuation written as marks
+ */
+"punctuation" | "llm" | "regex" | 
+/**
+ * The WebAssembly plugins in the plugins folder
+ */
+"plugins" | "profanity" | "snippets"
+/**
+ * A single timed piece of a transcription, in seconds relative to the start of the audio.
+ */
+export type TranscriptSegment = { start: number; end: number; text: string; 
+/**
+ * The decoder's confidence in the segment from 0 to 1, `None` when the engine doesn't
+ * report one
+ */
+confidence: number | null; 
+/**
+ * Per-word confidence, empty when the engine only scores whole segments
+ */
+words: WordConfidence[] }
+/**
+ * Full transcription result including segment timing when the engine provides it.
+ */
+export type TranscriptionOutput = { text: string; segments: TranscriptSegment[] }
+/**
+ * Where recordings are transcribed. Everything except `Local` uploads audio to a third party.
+ */
+export type TranscriptionProvider = "local" | "openai" | "deepgram" | "azure"
+/**
+ * One transcription of a history entry's audio. The first version is the original
+ * transcription, which has no recorded `model_id`.
+ */
+export type TranscriptionVersion = { id: string; history_id: string; model_id: string | null; transcription_text: string; created_at: string }
+/**
+ * Applications in which the shortcuts and triggers may start a dictation, matched ignoring
+ * case against the focused application's name.
+ */
+export type TriggerAppScope = "everywhere" | 
+/**
+ * Only in the `trigger_apps`
+ */
+"only_in" | 
+/**
+ * Anywhere but in the `trigger_apps`, e.g. games or remote desktops
+ */
+"except_in"
+export type UsageFeature = "post_processing" | "post_process_context" | "cloud_transcription" | "vocabulary_biasing" | "filler_removal" | "disfluency_removal" | "regex_rules" | "snippets" | "session_voice_commands" | "session_tag_rules" | "low_confidence_marker"
+/**
+ * The counters as they go into diagnostics, every number replaced by its range.
+ */
+export type UsageSnapshot = { sessions_per_day: DailySessions[]; features: FeatureUsage[]; errors: ErrorCount[] }
+export type WordConfidence = { text: string; confidence: number }
 
 /** tauri-specta globals **/
 
@@ -0,0 +1,77 @@
+import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
+import React, { useEffect, useRef, useState } from "react";
+import { CancelIcon } from "../components/icons";
+import "./CaptionOverlay.css";
+import { commands } from "@/bindings";
+
+// Finished lines kept above the one being spoken
+const MAX_LINES = 2;
+
+const CaptionOverlay: React.FC = () => {
+  const [lines, setLines] = useState<string[]>([]);
+  const [partial, setPartial] = useState("");
+  const bottomRef = useRef<HTMLDivElement>(null);
+
+  useEffect(() => {
+    // Captions are sent to this window only, so listen on the window rather than globally
+    const captionWindow = getCurrentWebviewWindow();
+
+    const setupEventListeners = async () => {
+      const unlistenPartial = await captionWindow.listen<string>(
+        "caption-partial",
+        (event) => {
+          setPartial(event.payload);
+        }
+      );
+
+      const unlistenFinal = await captionWindow.listen<string>(
+        "caption-final",
+        (event) => {
+          setLines((previous) => [...previous, event.payload].slice(-MAX_LINES));
+          setPartial("");
+        }
+      );
+
+      return () => {
+        unlistenPartial();
+        unlistenFinal();
+      };
+    };
+
+    const cleanup = setupEventListeners();
+    return () => {
+      cleanup.then((unlisten) => unlisten());
+    };
+  }, []);
+
+  useEffect(() => {
+    bottomRef.current?.scrollIntoView({ block: "end" });
+  }, [lines, partial]);
+
+  return (
+    <div className="caption-overlay">
+      <div className="caption-lines">
+        {lines.length === 0 && !partial && (
+          <div className="caption-waiting">Listening...</div>
+        )}
+        {lines.map((line, i) => (
+          <div key={i} className="caption-line">
+            {line}
+          </div>
+        ))}
+        {partial && <div className="caption-line partial">{partial}</div>}
+        <div ref={bottomRef} />
+      </div>
+      <div
+        className="caption-close"
+        onClick={() => {
+          commands.stopCaptions();
+        }}
+      >
+        <CancelIcon />
+      </div>
+    </div>
+  );
+};
+
+export default CaptionOverlay;
@@ -1,4 +1,5 @@
 import React, { useState } from "react";
+import type { CustomWord } from "@/bindings";
 import { useSettings } from "../../hooks/useSettings";
 import { Input } from "../ui/Input";
 import { Button } from "../ui/Button";
@@ -22,9 +23,16 @@ export const CustomWords: React.FC<CustomWordsProps> = React.memo(
         sanitizedWord &&
         !sanitizedWord.includes(" ") &&
         sanitizedWord.length <= 50 &&
-        !customWords.includes(sanitizedWord)
+        !customWords.some((word) => word.word === sanitizedWord)
       ) {
-        updateSetting("custom_words", [...customWords, sanitizedWord]);
+        const customWord: CustomWord = {
+          word: sanitizedWord,
+          sounds_like: [],
+          threshold: null,
+          languages: [],
+          profiles: [],
+        };
+        updateSetting("custom_words", [...customWords, customWord]);
         setNewWord("");
       }
     };
@@ -32,7 +40,7 @@ export const CustomWords: React.FC<CustomWordsProps> = React.memo(
     const handleRemoveWord = (wordToRemove: string) => {
       updateSetting(
         "custom_words",
-        customWords.filter((word) => word !== wordToRemove),
+        customWords.filter((word) => word.word !== wordToRemove),
       );
     };
 
@@ -81,7 +89,7 @@ export const CustomWords: React.FC<CustomWordsProps> = React.memo(
           <div
             className={`px-4 p-2 ${grouped ? "" : "rounded-lg border border-mid-gray/20"} flex flex-wrap gap-1`}
           >
-            {customWords.map((word) => (
+            {customWords.map(({ word }) => (
               <Button
                 key={word}
                 onClick={() => handleRemoveWord(word)}
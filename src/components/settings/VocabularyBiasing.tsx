@@ -0,0 +1,54 @@
+import React, { useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import { ToggleSwitch } from "../ui/ToggleSwitch";
+import { useSettings } from "../../hooks/useSettings";
+import { useModels } from "../../hooks/useModels";
+import { commands } from "@/bindings";
+
+interface VocabularyBiasingProps {
+  descriptionMode?: "inline" | "tooltip";
+  grouped?: boolean;
+}
+
+export const VocabularyBiasing: React.FC<VocabularyBiasingProps> = React.memo(
+  ({ descriptionMode = "tooltip", grouped = false }) => {
+    const { getSetting, updateSetting, isUpdating } = useSettings();
+    const { currentModel, loadCurrentModel } = useModels();
+    const [isSupported, setIsSupported] = useState(false);
+
+    // Unset means on wherever it is supported
+    const vocabularyBiasing = getSetting("vocabulary_biasing") ?? true;
+
+    useEffect(() => {
+      commands.supportsVocabularyBias().then(setIsSupported);
+    }, [currentModel]);
+
+    // Listen for model state changes to update UI reactively
+    useEffect(() => {
+      const modelStateUnlisten = listen("model-state-changed", () => {
+        loadCurrentModel();
+      });
+
+      return () => {
+        modelStateUnlisten.then((fn) => fn());
+      };
+    }, [loadCurrentModel]);
+
+    const description = isSupported
+      ? "Steer transcription toward your custom words while decoding, instead of only correcting them afterwards."
+      : "Only Whisper models, and the OpenAI and Deepgram providers, can be steered toward custom words. The current model corrects them after transcription instead.";
+
+    return (
+      <ToggleSwitch
+        checked={isSupported && vocabularyBiasing}
+        onChange={(enabled) => updateSetting("vocabulary_biasing", enabled)}
+        isUpdating={isUpdating("vocabulary_biasing")}
+        disabled={!isSupported}
+        label="Vocabulary Biasing (Whisper only)"
+        description={description}
+        descriptionMode={descriptionMode}
+        grouped={grouped}
+      />
+    );
+  },
+);
@@ -2,7 +2,6 @@ import React, { useState, useEffect, useCallback } from "react";
 import { AudioPlayer } from "../../ui/AudioPlayer";
 import { Button } from "../../ui/Button";
 import { Copy, Star, Check, Trash2, FolderOpen } from "lucide-react";
-import { convertFileSrc } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { commands, type HistoryEntry } from "@/bindings";
 
@@ -86,13 +85,17 @@ export const HistorySettings: React.FC = () => {
 
   const getAudioUrl = async (fileName: string) => {
     try {
-      const result = await commands.getAudioFilePath(fileName);
+      // Read through the backend, which decrypts encrypted recordings in memory
+      const result = await commands.getAudioFile(fileName);
       if (result.status === "ok") {
-        return convertFileSrc(`${result.data}`, "asset");
+        const wav = new Blob([new Uint8Array(result.data)], {
+          type: "audio/wav",
+        });
+        return URL.createObjectURL(wav);
       }
       return null;
     } catch (error) {
-      console.error("Failed to get audio file path:", error);
+      console.error("Failed to get audio file:", error);
       return null;
     }
   };
@@ -207,11 +210,23 @@ const HistoryEntryComponent: React.FC<HistoryEntryProps> = ({
   const [showCopied, setShowCopied] = useState(false);
 
   useEffect(() => {
+    let url: string | null = null;
+    let cancelled = false;
     const loadAudio = async () => {
-      const url = await getAudioUrl(entry.file_name);
+      url = await getAudioUrl(entry.file_name);
+      if (cancelled && url) {
+        URL.revokeObjectURL(url);
+        return;
+      }
       setAudioUrl(url);
     };
     loadAudio();
+    return () => {
+      cancelled = true;
+      if (url) {
+        URL.revokeObjectURL(url);
+      }
+    };
   }, [entry.file_name, getAudioUrl]);
 
   const handleCopyText = () => {
@@ -8,13 +8,16 @@ import {
 import "./RecordingOverlay.css";
 import { commands } from "@/bindings";
 
-type OverlayState = "recording" | "transcribing" | "post-processing";
+type OverlayState = "recording" | "transcribing" | "post-processing" | "profile";
 
 const RecordingOverlay: React.FC = () => {
   const [isVisible, setIsVisible] = useState(false);
   const [state, setState] = useState<OverlayState>("recording");
   const [levels, setLevels] = useState<number[]>(Array(16).fill(0));
   const [transcriptionText, setTranscriptionText] = useState("");
+  const [profileName, setProfileName] = useState("");
+  const [paused, setPaused] = useState(false);
+  const profileTimeoutRef = useRef<number | undefined>(undefined);
   const smoothedLevelsRef = useRef<number[]>(Array(16).fill(0));
 
   useEffect(() => {
@@ -24,6 +27,7 @@ const RecordingOverlay: React.FC = () => {
         const overlayState = event.payload as OverlayState;
         setState(overlayState);
         setIsVisible(true);
+        setPaused(false);
         // Clear transcription text when showing overlay
         if (overlayState === "recording") {
           setTranscriptionText("");
@@ -67,6 +71,27 @@ const RecordingOverlay: React.FC = () => {
         }
       );
 
+      // Listen for the recording being paused and resumed
+      const unlistenPaused = await listen<boolean>(
+        "recording-paused",
+        (event) => {
+          setPaused(event.payload);
+        }
+      );
+
+      // Listen for profile switches, shown briefly by name
+      const unlistenProfile = await listen<string>(
+        "profile-indicator",
+        (event) => {
+          setProfileName(event.payload);
+          window.clearTimeout(profileTimeoutRef.current);
+          profileTimeoutRef.current = window.setTimeout(
+            () => setProfileName(""),
+            1500
+          );
+        }
+      );
+
       // Cleanup function
       return () => {
         unlistenShow();
@@ -74,6 +99,8 @@ const RecordingOverlay: React.FC = () => {
         unlistenLevel();
         unlistenTranscription();
         unlistenFinalTranscription();
+        unlistenProfile();
+        unlistenPaused();
       };
     };
 
@@ -97,7 +124,16 @@ const RecordingOverlay: React.FC = () => {
       <div className="overlay-left">{getIcon()}</div>
 
       <div className="overlay-middle">
-        {state === "recording" && !transcriptionText && (
+        {profileName && !transcriptionText && (
+          <div className="transcribing-text">{profileName}</div>
+        )}
+        {state === "recording" && paused && (
+          <div className="transcribing-text">Paused</div>
+        )}
+        {state === "recording" &&
+          !paused &&
+          !transcriptionText &&
+          !profileName && (
           <div className="bars-container">
             {levels.map((v, i) => (
               <div
@@ -118,7 +154,7 @@ const RecordingOverlay: React.FC = () => {
         {state === "post-processing" && !transcriptionText && (
           <div className="transcribing-text">Post-processing...</div>
         )}
-        {transcriptionText && (
+        {transcriptionText && !paused && (
           <div className="transcription-text">{transcriptionText}</div>
         )}
       </div>
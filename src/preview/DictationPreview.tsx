@@ -0,0 +1,73 @@
+import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
+import React, { useEffect, useState } from "react";
+import "./DictationPreview.css";
+import { commands } from "@/bindings";
+
+const DictationPreview: React.FC = () => {
+  const [text, setText] = useState("");
+
+  useEffect(() => {
+    // The window is created with the first held transcription, before it could listen
+    commands.getPendingReadback().then((pending) => {
+      if (pending !== null) {
+        setText(pending);
+      }
+    });
+
+    // Later transcriptions are sent to this window only
+    const unlisten = getCurrentWebviewWindow().listen<string>(
+      "preview-text",
+      (event) => {
+        setText(event.payload);
+      }
+    );
+
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, []);
+
+  const onChange = (value: string) => {
+    setText(value);
+    // Kept in the backend as well, so the confirm shortcut pastes the edit
+    commands.editPendingReadback(value);
+  };
+
+  return (
+    <div className="dictation-preview">
+      <textarea
+        className="preview-text"
+        value={text}
+        onChange={(event) => onChange(event.target.value)}
+        onKeyDown={(event) => {
+          if (event.key === "Enter" && (event.metaKey || event.ctrlKey)) {
+            event.preventDefault();
+            commands.confirmReadback();
+          } else if (event.key === "Escape") {
+            commands.discardReadback();
+          }
+        }}
+      />
+      <div className="preview-actions">
+        <button
+          className="preview-discard"
+          onClick={() => {
+            commands.discardReadback();
+          }}
+        >
+          Discard
+        </button>
+        <button
+          className="preview-send"
+          onClick={() => {
+            commands.confirmReadback();
+          }}
+        >
+          Send
+        </button>
+      </div>
+    </div>
+  );
+};
+
+export default DictationPreview;
@@ -16,12 +16,14 @@ export default defineConfig(async () => ({
     },
   },
 
-  // Multiple entry points for main app and overlay
+  // Multiple entry points for main app and its extra windows
   build: {
     rollupOptions: {
       input: {
         main: resolve(__dirname, "index.html"),
         overlay: resolve(__dirname, "src/overlay/index.html"),
+        captions: resolve(__dirname, "src/captions/index.html"),
+        preview: resolve(__dirname, "src/preview/index.html"),
       },
     },
   },